@@ -0,0 +1,57 @@
+//! Compares `batch::stretch_clips` with creating an instance per clip.
+//!
+//! Run with `cargo bench --bench batch`, and with `--features rayon` to include the
+//! parallel version. A thousand clips of 0.05 to 0.5 s are stretched both ways and the
+//! time per clip and the speedup are printed. The outputs must be identical.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use soundtouch::{batch, Config, SoundTouch};
+
+const CLIPS: usize = 1000;
+
+fn clips(sample_rate: usize) -> Vec<Vec<f32>> {
+    (0..CLIPS)
+        .map(|clip| {
+            let frames = sample_rate / 20 + clip * 7919 % (sample_rate * 9 / 20);
+            (0..frames * 2)
+                .map(|i| ((i / 2) as f32 * 0.05 * (1 + clip % 5) as f32).sin() * 0.5)
+                .collect()
+        })
+        .collect()
+}
+
+fn main() {
+    let mut config = Config::new();
+    config.set_tempo(1.2).set_pitch(0.9);
+    let clips = clips(config.sample_rate() as usize);
+    let clips: Vec<&[f32]> = clips.iter().map(Vec::as_slice).collect();
+
+    let start = Instant::now();
+    let naive: Vec<Vec<f32>> = clips
+        .iter()
+        .map(|clip| SoundTouch::with_config(&config).generate_audio(clip))
+        .collect();
+    let naive_time = start.elapsed();
+    black_box(&naive);
+
+    let start = Instant::now();
+    let batched = batch::stretch_clips(&config, &clips);
+    let batch_time = start.elapsed();
+    black_box(&batched);
+
+    let per_clip = |time: std::time::Duration| time.as_secs_f64() * 1e6 / CLIPS as f64;
+    println!("instance per clip: {:8.1} µs/clip", per_clip(naive_time));
+    println!(
+        "stretch_clips:     {:8.1} µs/clip, {:.2}x{}",
+        per_clip(batch_time),
+        naive_time.as_secs_f64() / batch_time.as_secs_f64(),
+        if cfg!(feature = "rayon") {
+            " (rayon)"
+        } else {
+            ""
+        }
+    );
+    assert!(naive == batched, "stretch_clips changed the output");
+}
@@ -0,0 +1,123 @@
+//! Checks the heuristic behind `Config::complexity_hint` against measured processing
+//! time.
+//!
+//! Run with `cargo bench --bench complexity`. Every configuration is processed for a
+//! few seconds of audio and its cost is printed next to the estimate. The estimate is
+//! only useful if the classes are ordered by cost, so the bench fails if any `Low`
+//! configuration is slower than any `High` one, or if the average cost of the classes
+//! isn't increasing.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use soundtouch::{ComplexityClass, Config, Setting, SoundTouch};
+
+const BLOCK_FRAMES: usize = 4096;
+const SECONDS: usize = 20;
+
+/// Returns the processing time per second of audio, in microseconds.
+fn measure(config: &Config) -> f64 {
+    let channels = config.channels() as usize;
+    let mut soundtouch = SoundTouch::with_config(config);
+    let input: Vec<f32> = (0..BLOCK_FRAMES * channels)
+        .map(|i| ((i / channels) as f32 * 0.031).sin() * 0.5)
+        .collect();
+    let mut output = vec![0.0; BLOCK_FRAMES * channels];
+    let blocks = SECONDS * config.sample_rate() as usize / BLOCK_FRAMES;
+
+    let start = Instant::now();
+    for _ in 0..blocks {
+        soundtouch.put_samples(&input, BLOCK_FRAMES);
+        while soundtouch.receive_samples(&mut output, BLOCK_FRAMES) > 0 {
+            black_box(&output);
+        }
+    }
+    start.elapsed().as_secs_f64() * 1e6 / SECONDS as f64
+}
+
+fn main() {
+    let configs = [
+        ("mono", Config::new().set_channels(1).clone()),
+        (
+            "stereo quickseek",
+            Config::new().set_setting(Setting::UseQuickseek, 1).clone(),
+        ),
+        (
+            "stereo tempo 1.5 quickseek",
+            Config::new()
+                .set_tempo(1.5)
+                .set_setting(Setting::UseQuickseek, 1)
+                .clone(),
+        ),
+        ("stereo", Config::new()),
+        ("stereo tempo 1.25", Config::new().set_tempo(1.25).clone()),
+        ("stereo rate 0.8", Config::new().set_rate(0.8).clone()),
+        (
+            "stereo pitch +3 semitones",
+            Config::new().set_pitch(2f64.powf(3.0 / 12.0)).clone(),
+        ),
+        (
+            "stereo 96 kHz",
+            Config::new().set_sample_rate(96000).clone(),
+        ),
+        (
+            "6 channels 48 kHz",
+            Config::new().set_channels(6).set_sample_rate(48000).clone(),
+        ),
+        (
+            "stereo 192 kHz tempo 0.75",
+            Config::new()
+                .set_sample_rate(192_000)
+                .set_tempo(0.75)
+                .clone(),
+        ),
+    ];
+
+    let mut costs: Vec<(ComplexityClass, f64)> = Vec::new();
+    for (name, config) in &configs {
+        let class = config.complexity_hint();
+        let cost = measure(config);
+        println!(
+            "{name:<28} {class:<6?} {:>8.1} Mops/s estimated {:>9.0} us/s measured",
+            config.estimated_ops_per_second() / 1e6,
+            cost,
+        );
+        costs.push((class, cost));
+    }
+
+    let classes = [
+        ComplexityClass::Low,
+        ComplexityClass::Medium,
+        ComplexityClass::High,
+    ];
+    let mean = |class| {
+        let costs: Vec<f64> = costs
+            .iter()
+            .filter(|&&(c, _)| c == class)
+            .map(|&(_, cost)| cost)
+            .collect();
+        assert!(!costs.is_empty(), "no {class:?} configuration");
+        costs.iter().sum::<f64>() / costs.len() as f64
+    };
+    let means = classes.map(mean);
+    println!("mean us/s per class: {means:.0?}");
+    assert!(
+        means.windows(2).all(|pair| pair[0] < pair[1]),
+        "classes aren't ordered by cost"
+    );
+
+    let slowest_low = costs
+        .iter()
+        .filter(|&&(class, _)| class == ComplexityClass::Low)
+        .map(|&(_, cost)| cost)
+        .fold(0.0, f64::max);
+    let fastest_high = costs
+        .iter()
+        .filter(|&&(class, _)| class == ComplexityClass::High)
+        .map(|&(_, cost)| cost)
+        .fold(f64::INFINITY, f64::min);
+    assert!(
+        slowest_low < fastest_high,
+        "a Low configuration is slower than a High one"
+    );
+}
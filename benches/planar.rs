@@ -0,0 +1,68 @@
+//! Cost of interleaving planar decoder output with `put_audio_buffer`.
+//!
+//! Run with `cargo bench --bench planar`. Every run processes the same minute of stereo
+//! input at 48 kHz in packets of 1152 frames, once from interleaved samples with
+//! `put_samples` and once from planes with `put_audio_buffer`. The difference is the
+//! cost of interleaving, shown as a share of the whole processing time.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use soundtouch::SoundTouch;
+
+const SAMPLE_RATE: usize = 48000;
+const PACKET_FRAMES: usize = 1152;
+const RUNS: usize = 5;
+
+fn bench(mut put: impl FnMut(&mut SoundTouch, usize, usize)) -> f64 {
+    let frames = 60 * SAMPLE_RATE;
+    let mut best = f64::INFINITY;
+    let mut output = vec![0.0; 8192];
+    for _ in 0..RUNS {
+        let mut soundtouch = SoundTouch::new();
+        soundtouch
+            .set_channels(2)
+            .set_sample_rate(SAMPLE_RATE as u32)
+            .set_tempo(1.25);
+        let start = Instant::now();
+        for offset in (0..frames).step_by(PACKET_FRAMES) {
+            put(&mut soundtouch, offset, PACKET_FRAMES.min(frames - offset));
+            while soundtouch.receive_samples(&mut output, 4096) > 0 {
+                black_box(&output);
+            }
+        }
+        soundtouch.flush();
+        while soundtouch.receive_samples(&mut output, 4096) > 0 {
+            black_box(&output);
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    best
+}
+
+fn main() {
+    let left: Vec<f32> = (0..60 * SAMPLE_RATE)
+        .map(|i| (i as f32 * 0.031).sin() * 0.5)
+        .collect();
+    let right: Vec<f32> = left.iter().map(|sample| -sample).collect();
+    let interleaved: Vec<f32> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(&l, &r)| [l, r])
+        .collect();
+
+    let baseline = bench(|soundtouch, offset, frames| {
+        soundtouch.put_samples(&interleaved[offset * 2..(offset + frames) * 2], frames);
+    });
+    let planar = bench(|soundtouch, offset, frames| {
+        let planes = [&left[offset..], &right[offset..]];
+        soundtouch.put_audio_buffer(&planes, frames).unwrap();
+    });
+    println!("{:<12} {:>8.2} ms", "interleaved", baseline * 1000.0);
+    println!(
+        "{:<12} {:>8.2} ms  interleaving {:.2}% of the total",
+        "planar",
+        planar * 1000.0,
+        (planar - baseline).max(0.0) / planar * 100.0
+    );
+}
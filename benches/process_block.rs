@@ -0,0 +1,92 @@
+//! Steady-state cost of `SoundTouch::process_block`.
+//!
+//! Run with `cargo bench --bench process_block`. Besides the throughput, this checks
+//! that processing doesn't allocate once the buffers are set up, and shows what
+//! metering the output costs next to the same configuration without it. Only allocations
+//! made through Rust's global allocator are counted; SoundTouch's own C++ buffers
+//! grow to their working size during the warm-up and are reused afterwards.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use soundtouch::{Config, Scratch, SoundTouch};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const BLOCK_FRAMES: usize = 512;
+const WARMUP_BLOCKS: usize = 200;
+const BLOCKS: usize = 5000;
+
+fn bench(name: &str, config: &Config, metering: bool) {
+    let channels = config.channels() as usize;
+    let mut soundtouch = SoundTouch::with_config(config);
+    soundtouch.set_metering(metering);
+    let mut scratch = Scratch::for_config(config);
+    let input: Vec<f32> = (0..BLOCK_FRAMES * channels)
+        .map(|i| ((i / channels) as f32 * 0.031).sin() * 0.5)
+        .collect();
+    let mut output = vec![0.0; 4 * BLOCK_FRAMES * channels];
+
+    let mut run = |blocks: usize| {
+        for _ in 0..blocks {
+            let result = soundtouch.process_block(&input, &mut output, &mut scratch);
+            black_box(&output[..result.produced_frames * channels]);
+        }
+    };
+
+    run(WARMUP_BLOCKS);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    run(BLOCKS);
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    let seconds = (BLOCKS * BLOCK_FRAMES) as f64 / config.sample_rate() as f64;
+    println!(
+        "{name:<24} {:>8.2} ms  {:>6.0}x realtime  {allocations} allocations",
+        elapsed.as_secs_f64() * 1000.0,
+        seconds / elapsed.as_secs_f64(),
+    );
+    assert_eq!(
+        allocations, 0,
+        "{name}: process_block allocated in steady state"
+    );
+}
+
+fn main() {
+    bench("tempo 1.25", Config::new().set_tempo(1.25), false);
+    bench("tempo 1.25 metered", Config::new().set_tempo(1.25), true);
+    bench(
+        "pitch +3 semitones",
+        Config::new().set_pitch(2f64.powf(3.0 / 12.0)),
+        false,
+    );
+    bench(
+        "rate 0.8 mono",
+        Config::new().set_channels(1).set_rate(0.8),
+        false,
+    );
+}
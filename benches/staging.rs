@@ -0,0 +1,57 @@
+//! Cost of putting small decoder packets with and without input staging.
+//!
+//! Run with `cargo bench --bench staging`. Every run puts the same minute of stereo input
+//! in packets of 128 frames and receives the output after each; the staged runs put it
+//! into SoundTouch in batches of the given size.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use soundtouch::SoundTouch;
+
+const SAMPLE_RATE: usize = 44100;
+const PACKET_FRAMES: usize = 128;
+const RUNS: usize = 5;
+
+fn bench(input: &[f32], staging: Option<usize>) -> f64 {
+    let mut best = f64::INFINITY;
+    let mut output = vec![0.0; 8192];
+    for _ in 0..RUNS {
+        let mut soundtouch = SoundTouch::new();
+        soundtouch
+            .set_channels(2)
+            .set_sample_rate(SAMPLE_RATE as u32)
+            .set_tempo(1.25)
+            .set_input_staging_frames(staging);
+        let start = Instant::now();
+        for packet in input.chunks(PACKET_FRAMES * 2) {
+            soundtouch.put_samples(packet, packet.len() / 2);
+            while soundtouch.receive_samples(&mut output, 4096) > 0 {
+                black_box(&output);
+            }
+        }
+        soundtouch.flush();
+        while soundtouch.receive_samples(&mut output, 4096) > 0 {
+            black_box(&output);
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    best
+}
+
+fn main() {
+    let input: Vec<f32> = (0..60 * SAMPLE_RATE * 2)
+        .map(|i| ((i / 2) as f32 * 0.031).sin() * 0.5)
+        .collect();
+    let unstaged = bench(&input, None);
+    println!("{:<16} {:>8.2} ms", "unstaged", unstaged * 1000.0);
+    for frames in [1024, 4096] {
+        let staged = bench(&input, Some(frames));
+        println!(
+            "{:<16} {:>8.2} ms  {:.2}x",
+            format!("staged {frames}"),
+            staged * 1000.0,
+            unstaged / staged
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Changes the tempo of raw interleaved `f32le` PCM from standard input and writes the
+//! result to standard output, e.g.:
+//!
+//! ```sh
+//! ffmpeg -i in.mp3 -f f32le -ac 2 -ar 44100 - \
+//!     | cargo run --example stretch_pipe -- 1.25 2 44100 \
+//!     | ffmpeg -f f32le -ac 2 -ar 44100 -i - out.wav
+//! ```
+
+use std::io::{self, BufWriter};
+use std::process::ExitCode;
+
+use soundtouch::io::{pipe, SampleFormat};
+use soundtouch::Config;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let parsed = match args.as_slice() {
+        [tempo, channels, sample_rate] => tempo
+            .parse()
+            .ok()
+            .zip(channels.parse().ok())
+            .zip(sample_rate.parse().ok()),
+        _ => None,
+    };
+    let Some(((tempo, channels), sample_rate)) = parsed else {
+        eprintln!("usage: stretch_pipe <tempo> <channels> <sample rate>");
+        return ExitCode::FAILURE;
+    };
+
+    let mut config = Config::new();
+    config
+        .set_channels(channels)
+        .set_sample_rate(sample_rate)
+        .set_tempo(tempo);
+    let stdout = BufWriter::new(io::stdout().lock());
+    match pipe(io::stdin().lock(), stdout, &config, SampleFormat::F32Le) {
+        Ok(stats) => {
+            eprintln!(
+                "{} frames in, {} frames out{}",
+                stats.frames_in,
+                stats.frames_out,
+                if stats.output_closed {
+                    ", output closed early"
+                } else {
+                    ""
+                }
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("stretch_pipe: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
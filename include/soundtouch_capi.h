@@ -0,0 +1,200 @@
+#ifndef SOUNDTOUCH_CAPI_H
+#define SOUNDTOUCH_CAPI_H
+
+/* Generated by cbindgen from src/capi.rs, don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * The result of a C API call: [`StStatus::Ok`], the [`SoundTouchError`] the call
+ * failed with, or a failure of the call itself.
+ */
+typedef enum StStatus {
+  /**
+   * The call succeeded.
+   */
+  ST_STATUS_OK = 0,
+  /**
+   * [`SoundTouchError::InvalidChannels`].
+   */
+  ST_STATUS_INVALID_CHANNELS = 1,
+  /**
+   * [`SoundTouchError::InvalidSampleRate`].
+   */
+  ST_STATUS_INVALID_SAMPLE_RATE = 2,
+  /**
+   * [`SoundTouchError::InvalidSetting`].
+   */
+  ST_STATUS_INVALID_SETTING = 3,
+  /**
+   * [`SoundTouchError::NotConfigured`].
+   */
+  ST_STATUS_NOT_CONFIGURED = 4,
+  /**
+   * [`SoundTouchError::ChannelMismatch`].
+   */
+  ST_STATUS_CHANNEL_MISMATCH = 5,
+  /**
+   * [`SoundTouchError::InputTooShort`].
+   */
+  ST_STATUS_INPUT_TOO_SHORT = 6,
+  /**
+   * [`SoundTouchError::OutputTooSmall`].
+   */
+  ST_STATUS_OUTPUT_TOO_SMALL = 7,
+  /**
+   * [`SoundTouchError::ParametersOutOfRange`].
+   */
+  ST_STATUS_PARAMETERS_OUT_OF_RANGE = 8,
+  /**
+   * [`SoundTouchError::AlreadyFlushed`].
+   */
+  ST_STATUS_ALREADY_FLUSHED = 9,
+  /**
+   * [`SoundTouchError::NonFiniteInput`].
+   */
+  ST_STATUS_NON_FINITE_INPUT = 10,
+  /**
+   * [`SoundTouchError::PipelineNotEmpty`].
+   */
+  ST_STATUS_PIPELINE_NOT_EMPTY = 11,
+  /**
+   * [`SoundTouchError::PartialFrame`].
+   */
+  ST_STATUS_PARTIAL_FRAME = 12,
+  /**
+   * [`SoundTouchError::Backpressure`].
+   */
+  ST_STATUS_BACKPRESSURE = 13,
+  /**
+   * [`SoundTouchError::TooManySamples`].
+   */
+  ST_STATUS_TOO_MANY_SAMPLES = 14,
+  /**
+   * [`SoundTouchError::OverlapTooLong`].
+   */
+  ST_STATUS_OVERLAP_TOO_LONG = 15,
+  /**
+   * [`SoundTouchError::Cancelled`].
+   */
+  ST_STATUS_CANCELLED = 16,
+  /**
+   * [`SoundTouchError::StreamEnded`].
+   */
+  ST_STATUS_STREAM_ENDED = 17,
+  /**
+   * [`SoundTouchError::MisalignedOutput`].
+   */
+  ST_STATUS_MISALIGNED_OUTPUT = 18,
+  /**
+   * A required pointer was null.
+   */
+  ST_STATUS_NULL_POINTER = -1,
+  /**
+   * The call panicked. The handle must not be used again, except to free it.
+   */
+  ST_STATUS_PANIC = -2,
+} StStatus;
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * An opaque handle to a [`SoundTouch`] instance, created with [`st_new`] and freed
+ * with [`st_free`]. A handle can be moved between threads, but not used by two at once.
+ */
+typedef struct StSoundTouch StSoundTouch;
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Creates an instance with `channels` channels at `sample_rate` Hz. Returns null if
+ * either is invalid, with the reason in [`st_last_error_message`]. Free the handle with
+ * [`st_free`].
+ */
+StSoundTouch *st_new(uint32_t channels, uint32_t sample_rate);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Frees an instance. Does nothing if `handle` is null.
+ *
+ * # Safety
+ *
+ * `handle` must be null or a handle from [`st_new`] that hasn't been freed, and must
+ * not be used afterwards.
+ */
+void st_free(StSoundTouch *handle);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Sets the tempo, see [`SoundTouch::try_set_tempo`].
+ *
+ * # Safety
+ *
+ * `handle` must be null or a live handle from [`st_new`] that no other thread uses
+ * during the call.
+ */
+StStatus st_set_tempo(StSoundTouch *handle, double tempo);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Puts `frames` frames of interleaved samples, see [`SoundTouch::try_put_samples`].
+ * `samples` may be null if `frames` is `0`.
+ *
+ * # Safety
+ *
+ * `handle` must be null or a live handle from [`st_new`] that no other thread uses
+ * during the call, and `samples` must be null or point to `frames` times the number of
+ * channels readable floats.
+ */
+StStatus st_put_samples(StSoundTouch *handle, const float *samples, size_t frames);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Receives up to `max_frames` frames of interleaved samples into `samples`, see
+ * [`SoundTouch::try_receive_samples`], and stores how many there were in `*received`.
+ * `samples` may be null if `max_frames` is `0`.
+ *
+ * # Safety
+ *
+ * `handle` must be null or a live handle from [`st_new`] that no other thread uses
+ * during the call, `samples` must be null or point to `max_frames` times the number of
+ * channels writable floats, and `received` must be null or point to a writable
+ * `size_t`.
+ */
+StStatus st_receive_samples(StSoundTouch *handle,
+                            float *samples,
+                            size_t max_frames,
+                            size_t *received);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Flushes the stream, see [`SoundTouch::flush`]. Receive the rest of the output with
+ * [`st_receive_samples`] afterwards.
+ *
+ * # Safety
+ *
+ * `handle` must be null or a live handle from [`st_new`] that no other thread uses
+ * during the call.
+ */
+StStatus st_flush(StSoundTouch *handle);
+
+/**
+ * **NOT FROM SOUNDTOUCH**
+ *
+ * Returns the message of the last failed call on this thread, or an empty string if
+ * none failed yet. The string stays valid until the next failing call on this thread.
+ */
+const char *st_last_error_message(void);
+
+#endif /* SOUNDTOUCH_CAPI_H */
@@ -0,0 +1,120 @@
+//! Aligning two renditions of the same audio before comparing them.
+//!
+//! Outputs of the same input processed with different settings, e.g. with and without
+//! [quickseek], are often offset from each other by a few frames of latency. Comparing
+//! them sample by sample then measures the offset rather than the difference.
+//! [`best_alignment`] finds the offset and the remaining difference, and [`aligned`]
+//! returns the overlapping parts to compare further.
+//!
+//! ```rust
+//! use soundtouch::align::best_alignment;
+//!
+//! let a: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.1).sin()).collect();
+//! // `b` is `a` delayed by 3 frames.
+//! let b: Vec<f32> = [0.0; 3].iter().chain(&a).copied().collect();
+//! let alignment = best_alignment(&a, &b, 1, 16);
+//! assert_eq!(alignment.offset, 3);
+//! assert_eq!(alignment.rms_error, 0.0);
+//! ```
+//!
+//! [quickseek]: crate::Setting::UseQuickseek
+
+use crate::math::sqrt;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The offset that aligns two signals best, as found by [`best_alignment`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Alignment {
+    /// Offset in frames of the second signal relative to the first. Positive means the
+    /// second one lags, i.e. frame `i` of the first matches frame `i + offset` of the
+    /// second.
+    pub offset: isize,
+    /// RMS of the difference between the aligned signals over the frames they overlap,
+    /// or `0.0` if they don't.
+    pub rms_error: f32,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Finds the offset of up to `max_offset` frames in either direction at which `b`
+/// matches `a` best, for interleaved signals of `channels` channels.
+///
+/// The offset maximizes the normalized cross-correlation of the mono downmixes over
+/// the frames the signals overlap, which doesn't depend on their levels. The search
+/// goes outwards from `0`, so of equally good offsets, e.g. for silence, the smallest
+/// wins, preferring the positive one. It takes time proportional to `max_offset` times
+/// the length, and allocates nothing.
+///
+/// # Panics
+///
+/// Panics if `channels` is `0`.
+pub fn best_alignment(a: &[f32], b: &[f32], channels: usize, max_offset: usize) -> Alignment {
+    assert!(channels > 0, "signals need at least one channel");
+    // Beyond the longer signal, nothing overlaps.
+    let max_offset = max_offset.min(a.len().max(b.len()) / channels) as isize;
+    let offsets = (0..=max_offset)
+        .flat_map(|offset| [offset, -offset])
+        .skip(1);
+    let mut best = (0, f64::MIN);
+    for offset in offsets {
+        let correlation = correlation(a, b, channels, offset);
+        if correlation > best.1 {
+            best = (offset, correlation);
+        }
+    }
+
+    let (a, b) = aligned(a, b, channels, best.0);
+    let error: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(&a, &b)| {
+            let difference = f64::from(a) - f64::from(b);
+            difference * difference
+        })
+        .sum();
+    Alignment {
+        offset: best.0,
+        rms_error: sqrt(error / a.len().max(1) as f64) as f32,
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns the overlapping parts of `a` and `b` with `b` shifted back by `offset`
+/// frames of `channels` channels, as found by [`best_alignment`]. Both have the same
+/// length, which is `0` if they don't overlap.
+pub fn aligned<'a>(
+    a: &'a [f32],
+    b: &'a [f32],
+    channels: usize,
+    offset: isize,
+) -> (&'a [f32], &'a [f32]) {
+    let shift = offset.unsigned_abs().saturating_mul(channels);
+    let (a, b) = if offset >= 0 {
+        (a, b.get(shift..).unwrap_or(&[]))
+    } else {
+        (a.get(shift..).unwrap_or(&[]), b)
+    };
+    let len = a.len().min(b.len()) / channels * channels;
+    (&a[..len], &b[..len])
+}
+
+/// Returns the normalized cross-correlation of the mono downmixes of `a` and `b`
+/// aligned at `offset`, or `0.0` if either is silent where they overlap.
+fn correlation(a: &[f32], b: &[f32], channels: usize, offset: isize) -> f64 {
+    let (a, b) = aligned(a, b, channels, offset);
+    let downmix = |frame: &[f32]| frame.iter().map(|&sample| f64::from(sample)).sum::<f64>();
+    let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+    for (x, y) in a.chunks_exact(channels).zip(b.chunks_exact(channels)) {
+        let (x, y) = (downmix(x), downmix(y));
+        xy += x * y;
+        xx += x * x;
+        yy += y * y;
+    }
+    if xx > 0.0 && yy > 0.0 {
+        xy / sqrt(xx * yy)
+    } else {
+        0.0
+    }
+}
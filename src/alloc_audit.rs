@@ -0,0 +1,126 @@
+//! Checking that code doesn't allocate, behind the `alloc-audit` feature.
+//!
+//! Install [`CountingAllocator`] as the global allocator of a test binary, then wrap the
+//! code under test in [`assert_no_alloc`]. Allocations are counted per thread, so tests
+//! running in parallel don't see each other's.
+//!
+//! ```rust,no_run
+//! use soundtouch::alloc_audit::{assert_no_alloc, CountingAllocator};
+//! use soundtouch::SoundTouch;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator;
+//!
+//! fn main() {
+//!     let mut soundtouch = SoundTouch::new();
+//!     soundtouch.set_channels(2).set_sample_rate(44100);
+//!     let input = [0.0; 2048];
+//!     let mut output = [0.0; 2048];
+//!     assert_no_alloc(|| {
+//!         soundtouch.put_samples(&input, 1024);
+//!         soundtouch.receive_samples(&mut output, 1024);
+//!     });
+//! }
+//! ```
+//!
+//! Only allocations made through Rust's global allocator are counted. SoundTouch's own
+//! C++ buffers are allocated with `new` and grow to their working size while the first
+//! few batches are processed, so put some input through before auditing.
+//!
+//! The crate's tests use this to check that the processing path (`put_samples`,
+//! `receive_samples`, `process_block`, `process_chunk` into a slice and their `try_`
+//! and `_f64` variants) never allocates. The `alloc`-gated conveniences such as
+//! `generate_audio` allocate by design.
+
+use core::cell::Cell;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::boxed::Box;
+use std::thread_local;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A global allocator that forwards to [`System`] and counts the allocations and
+/// reallocations of every thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+fn count() {
+    // `try_with` as the thread may be tearing down its thread-locals.
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+// SAFETY: Every call is forwarded unchanged to `System`, which upholds the contract.
+// Counting doesn't allocate, as the thread-local is a `const`-initialized `Cell`.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: The caller upholds the contract of `alloc`.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: The caller upholds the contract of `alloc_zeroed`.
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: The caller upholds the contract of `dealloc`, and `ptr` came from
+        // `System` through this allocator.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        // SAFETY: As for `dealloc`, and the caller upholds the contract of `realloc`.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Returns the number of allocations the current thread has made so far, or `0` if
+/// [`CountingAllocator`] isn't the global allocator.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Returns `true` if [`CountingAllocator`] is the global allocator.
+pub fn is_installed() -> bool {
+    let before = allocations();
+    drop(core::hint::black_box(Box::new(0u8)));
+    allocations() > before
+}
+
+/// Returns the result of `f` and the number of allocations it made on the current
+/// thread.
+///
+/// # Panics
+///
+/// Panics if [`CountingAllocator`] isn't the global allocator, as nothing would be
+/// counted.
+pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    assert!(
+        is_installed(),
+        "CountingAllocator must be the #[global_allocator] to count allocations"
+    );
+    let before = allocations();
+    let result = f();
+    (result, allocations() - before)
+}
+
+/// Runs `f` and returns its result.
+///
+/// # Panics
+///
+/// Panics if `f` allocates on the current thread, or if [`CountingAllocator`] isn't the
+/// global allocator.
+#[track_caller]
+pub fn assert_no_alloc<R>(f: impl FnOnce() -> R) -> R {
+    let (result, allocations) = count_allocations(f);
+    assert_eq!(
+        allocations, 0,
+        "{allocations} allocations where none were expected"
+    );
+    result
+}
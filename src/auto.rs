@@ -0,0 +1,238 @@
+//! Choosing the settings for a file from an analysis pass before processing it.
+//!
+//! SoundTouch's automatic sequence length depends on the tempo only. Offline, e.g.
+//! when transcoding an archive, the audio can be analyzed first: [`analyze`] measures
+//! the [`MaterialProfile`] of the whole input, and
+//! [`MaterialProfile::recommended_tuning`] turns it into [`TuningParams`], with longer
+//! sequences for sustained material, which keeps its tone smooth, and shorter ones for
+//! percussive material, which keeps its hits from being doubled or smeared. A
+//! [`RecommendationPolicy`] sets how. [`auto_stretch`] runs both passes.
+//!
+//! ```rust
+//! use soundtouch::{auto, signal};
+//!
+//! let clicks = signal::click_track(300.0, 44100, 5 * 44100).into_vec();
+//! let profile = auto::analyze(&clicks, 1, 44100);
+//! assert!(profile.transient_density > 4.0);
+//! // Fully percussive, so the shortest sequence of the default policy or shorter.
+//! let tuning = profile.recommended_tuning(1.0);
+//! assert!(tuning.sequence_ms <= Some(40));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::math::sqrt;
+use crate::oneshot::configured;
+use crate::onsets::{detect_onsets, OnsetConfig};
+use crate::{BPMDetect, Downmix, TuningParams};
+
+/// Length of the hops the silence is measured over, in milliseconds.
+const SILENCE_HOP_MS: usize = 10;
+
+/// Mean square of the mono downmix of a hop at or below which it is silent, -60 dBFS.
+const SILENCE_MEAN_SQUARE: f64 = 1.0e-6;
+
+/// Frames put into the BPM detector at a time.
+const BPM_CHUNK_FRAMES: usize = 4096;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`analyze`] found out about some audio.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MaterialProfile {
+    /// Onsets per second of the audio that isn't silent, as found by [`detect_onsets`]
+    /// with the default [`OnsetConfig`]; `0.0` if all of it is silent.
+    pub transient_density: f64,
+    /// Fraction of the audio, in `0.0..=1.0`, whose level stays at or below -60 dBFS
+    /// over 10 ms.
+    pub silence_ratio: f64,
+    /// The tempo found by [`BPMDetect`] in beats per minute, or `None` if it found no
+    /// beat.
+    pub bpm: Option<f32>,
+    /// Length of the audio in seconds.
+    pub duration_secs: f64,
+}
+
+impl MaterialProfile {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the settings to process the material at `tempo` with, as recommended by
+    /// the default [`RecommendationPolicy`].
+    pub fn recommended_tuning(&self, tempo: f64) -> TuningParams {
+        RecommendationPolicy::default().recommend(self, tempo)
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How [`RecommendationPolicy::recommend`] maps a [`MaterialProfile`] to
+/// [`TuningParams`].
+///
+/// The material is the more percussive the closer its transient density is to
+/// [`percussive_density`], and fully so from there on. The sequence and seek window
+/// lengths are interpolated linearly between the sustained and the percussive ones by
+/// that, then scaled by `1 / √tempo`, with the tempo clamped to `0.5..=2.0`, since
+/// faster tempos need shorter sequences for the same smoothness, as SoundTouch's
+/// automatic choice also assumes. With a [known beat](MaterialProfile::bpm), the
+/// sequence is capped at [`max_beat_fraction`] of a beat divided by how percussive the
+/// material is, so that fully percussive material gets sequences that don't span two
+/// hits, and sustained material isn't capped. Finally the lengths are rounded to whole
+/// milliseconds and the sequence is kept longer than the overlap. The other settings are
+/// left at their defaults.
+///
+/// [`percussive_density`]: RecommendationPolicy::percussive_density
+/// [`max_beat_fraction`]: RecommendationPolicy::max_beat_fraction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecommendationPolicy {
+    /// Sequence length for sustained material in milliseconds. Defaults to `100`.
+    pub sustained_sequence_ms: u32,
+    /// Sequence length for percussive material in milliseconds. Defaults to `40`.
+    pub percussive_sequence_ms: u32,
+    /// Seek window length for sustained material in milliseconds. Defaults to `25`.
+    pub sustained_seek_window_ms: u32,
+    /// Seek window length for percussive material in milliseconds. Defaults to `12`.
+    pub percussive_seek_window_ms: u32,
+    /// Transient density, in onsets per second, from which on the material counts as
+    /// fully percussive. Defaults to `4.0`.
+    pub percussive_density: f64,
+    /// Longest sequence of fully percussive material as a fraction of a beat. Defaults
+    /// to `0.125`, an eighth of a beat, e.g. 62 ms at 120 BPM.
+    pub max_beat_fraction: f64,
+    /// Overlap length in milliseconds. Defaults to that of
+    /// [`TuningParams::default`].
+    pub overlap_ms: u32,
+}
+
+impl Default for RecommendationPolicy {
+    fn default() -> Self {
+        Self {
+            sustained_sequence_ms: 100,
+            percussive_sequence_ms: 40,
+            sustained_seek_window_ms: 25,
+            percussive_seek_window_ms: 12,
+            percussive_density: 4.0,
+            max_beat_fraction: 0.125,
+            overlap_ms: TuningParams::default().overlap_ms,
+        }
+    }
+}
+
+impl RecommendationPolicy {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the settings to process `profile` at `tempo` with. They pass
+    /// [`TuningParams::validate`] as long as the overlap is shorter than
+    /// [`TuningParams::MAX_MS`].
+    pub fn recommend(&self, profile: &MaterialProfile, tempo: f64) -> TuningParams {
+        let percussiveness = if self.percussive_density > 0.0 {
+            (profile.transient_density / self.percussive_density).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let scale = 1.0 / sqrt(tempo.clamp(0.5, 2.0));
+        let length = |sustained: u32, percussive: u32| {
+            let (sustained, percussive) = (sustained as f64, percussive as f64);
+            (sustained + (percussive - sustained) * percussiveness) * scale
+        };
+
+        let mut sequence_ms = length(self.sustained_sequence_ms, self.percussive_sequence_ms);
+        if let Some(bpm) = profile.bpm.filter(|&bpm| bpm > 0.0 && percussiveness > 0.0) {
+            let beat_ms = 60_000.0 / bpm as f64;
+            sequence_ms = sequence_ms.min(beat_ms * self.max_beat_fraction / percussiveness);
+        }
+        let seek_window_ms = length(
+            self.sustained_seek_window_ms,
+            self.percussive_seek_window_ms,
+        );
+        let round = |ms: f64| ((ms + 0.5) as u32).clamp(1, TuningParams::MAX_MS);
+        TuningParams {
+            sequence_ms: Some(round(sequence_ms).max(self.overlap_ms + 1)),
+            seek_window_ms: Some(round(seek_window_ms)),
+            overlap_ms: self.overlap_ms,
+            ..TuningParams::default()
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Measures the [`MaterialProfile`] of interleaved `samples` with `channels` channels,
+/// the first of the two passes of [`auto_stretch`]. It runs [`detect_onsets`] and
+/// [`BPMDetect`] over the whole input, which takes a fraction of the time processing
+/// it does. A trailing partial frame in `samples` is ignored.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`BPMDetect::new`].
+pub fn analyze(samples: &[f32], channels: u32, sample_rate: u32) -> MaterialProfile {
+    let mut detector = BPMDetect::new(channels, sample_rate);
+    let width = channels as usize;
+    let frames = samples.len() / width;
+    let samples = &samples[..frames * width];
+    detector.input_all(samples, BPM_CHUNK_FRAMES);
+    let bpm = Some(detector.get_bpm()).filter(|&bpm| bpm > 0.0);
+
+    let hop_frames = (sample_rate as usize * SILENCE_HOP_MS / 1000).max(1);
+    let mut hops = 0;
+    let mut silent = 0;
+    for hop in samples.chunks(hop_frames * width) {
+        let energy: f64 = hop
+            .chunks_exact(width)
+            .map(|frame| {
+                let sample = f64::from(Downmix::Average.mix(frame));
+                sample * sample
+            })
+            .sum();
+        hops += 1;
+        if energy / (hop.len() / width) as f64 <= SILENCE_MEAN_SQUARE {
+            silent += 1;
+        }
+    }
+    let silence_ratio = if hops == 0 {
+        0.0
+    } else {
+        silent as f64 / hops as f64
+    };
+
+    let duration_secs = frames as f64 / sample_rate as f64;
+    let sounding_secs = duration_secs * (1.0 - silence_ratio);
+    let onsets = detect_onsets(samples, channels, sample_rate, OnsetConfig::default());
+    let transient_density = if sounding_secs > 0.0 {
+        onsets.len() as f64 / sounding_secs
+    } else {
+        0.0
+    };
+
+    MaterialProfile {
+        transient_density,
+        silence_ratio,
+        bpm,
+        duration_secs,
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Changes the tempo of interleaved `samples` with `channels` channels by `tempo` in
+/// two passes: [`analyze`] first, then processing with the
+/// [recommended settings](MaterialProfile::recommended_tuning), as
+/// [`generate_audio`] does. The output has `frames / tempo` frames, to within one.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`BPMDetect::new`] or
+/// [`SoundTouch::set_channels`], or if `tempo` is outside what
+/// [`SoundTouch::try_set_tempo`] accepts.
+///
+/// [`generate_audio`]: crate::SoundTouch::generate_audio
+/// [`SoundTouch::set_channels`]: crate::SoundTouch::set_channels
+/// [`SoundTouch::try_set_tempo`]: crate::SoundTouch::try_set_tempo
+pub fn auto_stretch(samples: &[f32], channels: u32, sample_rate: u32, tempo: f64) -> Vec<f32> {
+    let tuning = analyze(samples, channels, sample_rate).recommended_tuning(tempo);
+    let mut soundtouch = configured(channels, sample_rate);
+    soundtouch.try_set_tempo(tempo).or_panic();
+    soundtouch.set_tuning(&tuning).or_panic();
+    let frames = samples.len() / channels as usize;
+    soundtouch.generate_audio(&samples[..frames * channels as usize])
+}
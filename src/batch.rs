@@ -0,0 +1,76 @@
+//! Processing many short clips with one configuration, such as the sound effects of a
+//! game at load time.
+//!
+//! Creating an instance per clip costs more than stretching a sub-second clip does.
+//! [`stretch_clips`] reuses one instance instead, [reset] and reconfigured between
+//! clips, or one per worker thread with the `rayon` feature.
+//!
+//! ```rust
+//! use soundtouch::{batch, Config};
+//!
+//! let footstep = vec![0.0; 4400 * 2];
+//! let door = vec![0.0; 22000 * 2];
+//! let stretched = batch::stretch_clips(Config::new().set_tempo(0.8), &[&footstep, &door]);
+//! assert_eq!(stretched[0].len(), 5500 * 2);
+//! assert_eq!(stretched[1].len(), 27500 * 2);
+//! ```
+//!
+//! [reset]: SoundTouch::reset
+
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::{Config, SoundTouch};
+
+/// Processes every clip of interleaved samples with `config`, as
+/// [`SoundTouch::generate_audio`] on a new instance would, and returns the outputs in
+/// the order of `clips`.
+///
+/// Every output has exactly `round(frames * ratio)` frames, where the ratio is the
+/// [input/output ratio] of `config`: the last frame is completed with silence or cut,
+/// whichever it takes. A trailing partial frame in a clip is ignored.
+///
+/// With the `rayon` feature, the clips are spread over the threads of rayon's global
+/// pool, with an instance per worker.
+///
+/// # Panics
+///
+/// Panics if `config` is [invalid].
+///
+/// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+/// [invalid]: Config::validate
+pub fn stretch_clips(config: &Config, clips: &[&[f32]]) -> Vec<Vec<f32>> {
+    config.validate().or_panic();
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        clips
+            .par_iter()
+            .map_init(
+                || SoundTouch::with_config(config),
+                |soundtouch, clip| stretch_clip(soundtouch, config, clip),
+            )
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut soundtouch = SoundTouch::with_config(config);
+        clips
+            .iter()
+            .map(|clip| stretch_clip(&mut soundtouch, config, clip))
+            .collect()
+    }
+}
+
+fn stretch_clip(soundtouch: &mut SoundTouch, config: &Config, clip: &[f32]) -> Vec<f32> {
+    soundtouch.reset();
+    soundtouch.apply_config(config);
+    let channels = config.channels() as usize;
+    let frames = clip.len() / channels;
+    let target = (frames as f64 * soundtouch.get_input_output_sample_ratio() + 0.5) as usize;
+    let mut output = soundtouch.generate_audio(clip);
+    output.resize(target * channels, 0.0);
+    output
+}
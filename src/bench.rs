@@ -0,0 +1,107 @@
+//! Measuring how fast SoundTouch runs on the current machine, behind the `std` feature,
+//! for calibrating the [complexity hints] of configurations.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use soundtouch::{bench, Config, Setting};
+//!
+//! let report = bench::quick_selftest(Duration::from_millis(50));
+//! let mut config = Config::new();
+//! config.set_tempo(1.5);
+//! // Leave some headroom for the rest of the app.
+//! if config.estimated_ops_per_second() > 0.25 * report.ops_per_second_capacity() {
+//!     config.set_setting(Setting::UseQuickseek, 1);
+//! }
+//! ```
+//!
+//! [complexity hints]: crate::Config::complexity_hint
+
+use core::time::Duration;
+use std::time::Instant;
+use std::vec;
+use std::vec::Vec;
+
+use crate::{Config, SoundTouch};
+
+/// Frames put per round of the self-test.
+const BLOCK_FRAMES: usize = 4096;
+
+/// The result of [`quick_selftest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThroughputReport {
+    /// The configuration that was measured.
+    pub config: Config,
+    /// Input frames processed.
+    pub frames: u64,
+    /// Time spent processing them.
+    pub elapsed: Duration,
+    /// Input frames processed per second.
+    pub frames_per_second: f64,
+}
+
+impl ThroughputReport {
+    /// Returns how many times faster than real time the configuration was processed.
+    pub fn realtime_factor(&self) -> f64 {
+        self.frames_per_second / self.config.sample_rate() as f64
+    }
+
+    /// Returns the [estimated operations per second] this machine sustains, i.e. the
+    /// estimate for the measured configuration scaled by the [`realtime_factor`].
+    ///
+    /// A configuration with a higher estimate than this can't be processed in real time.
+    ///
+    /// [estimated operations per second]: Config::estimated_ops_per_second
+    /// [`realtime_factor`]: ThroughputReport::realtime_factor
+    pub fn ops_per_second_capacity(&self) -> f64 {
+        self.config.estimated_ops_per_second() * self.realtime_factor()
+    }
+}
+
+/// Returns the configuration measured by [`quick_selftest`]: stereo at 44100 Hz with a
+/// tempo of `1.25` and the default settings, which is [`Medium`].
+///
+/// [`Medium`]: crate::ComplexityClass::Medium
+pub fn representative_config() -> Config {
+    let mut config = Config::new();
+    config.set_tempo(1.25);
+    config
+}
+
+/// Processes a [representative configuration] for about `duration` and reports the
+/// throughput. At least one block of 4096 frames is processed, so a zero `duration`
+/// still gives a (noisy) result.
+///
+/// The measurement runs on the calling thread and includes creating the instance, so
+/// durations of a few tens of milliseconds or more give stable numbers.
+///
+/// [representative configuration]: representative_config
+pub fn quick_selftest(duration: Duration) -> ThroughputReport {
+    let config = representative_config();
+    let channels = config.channels() as usize;
+    // A triangle wave, as the cost doesn't depend on the content.
+    let input: Vec<f32> = (0..BLOCK_FRAMES * channels)
+        .map(|i| (i / channels % 100) as f32 / 50.0 - 1.0)
+        .collect();
+    let mut output = vec![0.0; BLOCK_FRAMES * channels];
+
+    let start = Instant::now();
+    let mut soundtouch = SoundTouch::with_config(&config);
+    let mut frames = 0;
+    loop {
+        soundtouch.put_samples(&input, BLOCK_FRAMES);
+        while soundtouch.receive_samples(&mut output, BLOCK_FRAMES) > 0 {}
+        frames += BLOCK_FRAMES as u64;
+        if start.elapsed() >= duration {
+            break;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    ThroughputReport {
+        frames_per_second: frames as f64 / elapsed.as_secs_f64(),
+        config,
+        frames,
+        elapsed,
+    }
+}
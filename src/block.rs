@@ -0,0 +1,162 @@
+use crate::error::OrPanic;
+use crate::{Config, Pending, SoundTouch, SoundTouchError};
+
+/// Reusable state for [`SoundTouch::process_block`].
+///
+/// Holds the samples of an incomplete trailing frame until the rest of the frame
+/// arrives with the next block, so blocks don't need to be a whole number of frames.
+/// It is sized once when created and never allocates afterwards.
+#[derive(Debug, Clone)]
+pub struct Scratch {
+    partial: [f32; SoundTouch::MAX_CHANNELS as usize],
+    partial_len: usize,
+    channels: usize,
+}
+
+impl Scratch {
+    /// Creates scratch space for processing with `config`.
+    pub fn for_config(config: &Config) -> Self {
+        Self::for_channels(config.channels())
+    }
+
+    /// Creates scratch space for processing `channels` channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is `0` or above [`SoundTouch::MAX_CHANNELS`].
+    pub fn for_channels(channels: u32) -> Self {
+        Self::try_for_channels(channels).or_panic()
+    }
+
+    /// Creates scratch space for processing `channels` channels, returning an error if
+    /// `channels` is `0` or above [`SoundTouch::MAX_CHANNELS`].
+    pub fn try_for_channels(channels: u32) -> Result<Self, SoundTouchError> {
+        if !(1..=SoundTouch::MAX_CHANNELS).contains(&channels) {
+            return Err(SoundTouchError::InvalidChannels(channels));
+        }
+        Ok(Self {
+            partial: [0.0; SoundTouch::MAX_CHANNELS as usize],
+            partial_len: 0,
+            channels: channels as usize,
+        })
+    }
+
+    /// Returns the number of samples of an incomplete frame held back from the
+    /// previous block.
+    pub fn pending_samples(&self) -> usize {
+        self.partial_len
+    }
+
+    /// Drops any held back samples.
+    pub fn clear(&mut self) {
+        self.partial_len = 0;
+    }
+}
+
+/// The result of [`SoundTouch::process_block`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlockResult {
+    /// Frames handed to SoundTouch, including one completed from samples held in
+    /// the [`Scratch`].
+    pub consumed_frames: usize,
+    /// Frames written to the start of the output slice.
+    pub produced_frames: usize,
+    /// What is left to receive: [`Pending::MoreAvailable`] if ready output didn't fit
+    /// into the output slice, [`Pending::BufferTooSmall`] if not even one [output block]
+    /// fits into it, and [`Pending::PartialBlockHeld`] if frames are held back for an
+    /// incomplete block.
+    ///
+    /// [output block]: SoundTouch::set_output_block_frames
+    pub pending: Pending,
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Puts all of `input` into the pipeline and receives as many ready frames as fit
+    /// into `output`, without allocating.
+    ///
+    /// `input` and `output` are interleaved and don't have to hold a whole number of
+    /// frames: an incomplete trailing input frame is kept in `scratch` and completed by
+    /// the next call, and unused space at the end of `output` is left untouched. Call
+    /// with an empty `input` to receive pending output, and after [`flush`] to receive
+    /// the end of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set, or if
+    /// `scratch` was created for a different number of channels. Use
+    /// [`try_process_block`] to handle these cases. Like [`put_samples`], input after a
+    /// [`flush`] discards flushed output that hasn't been received, where
+    /// [`try_process_block`] returns [`SoundTouchError::AlreadyFlushed`].
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`try_process_block`]: SoundTouch::try_process_block
+    pub fn process_block(
+        &mut self,
+        input: &[f32],
+        output: &mut [f32],
+        scratch: &mut Scratch,
+    ) -> BlockResult {
+        self.discard_flushed(input.len());
+        self.try_process_block(input, output, scratch).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`process_block`], but returns an error instead of panicking.
+    ///
+    /// [`process_block`]: SoundTouch::process_block
+    pub fn try_process_block(
+        &mut self,
+        mut input: &[f32],
+        output: &mut [f32],
+        scratch: &mut Scratch,
+    ) -> Result<BlockResult, SoundTouchError> {
+        self.check_configured()?;
+        let channels = self.num_channels() as usize;
+        if scratch.channels != channels {
+            return Err(SoundTouchError::ChannelMismatch {
+                expected: channels as u32,
+                actual: scratch.channels as u32,
+            });
+        }
+
+        let mut consumed_frames = 0;
+        if scratch.partial_len > 0 {
+            let take = (channels - scratch.partial_len).min(input.len());
+            scratch.partial[scratch.partial_len..scratch.partial_len + take]
+                .copy_from_slice(&input[..take]);
+            scratch.partial_len += take;
+            input = &input[take..];
+            if scratch.partial_len == channels {
+                self.try_put_samples(&scratch.partial[..channels], 1)?;
+                scratch.partial_len = 0;
+                consumed_frames += 1;
+            }
+        }
+
+        let frames = input.len() / channels;
+        if frames > 0 {
+            self.try_put_samples(input, frames)?;
+            consumed_frames += frames;
+        }
+        let rest = &input[frames * channels..];
+        scratch.partial[..rest.len()].copy_from_slice(rest);
+        scratch.partial_len += rest.len();
+
+        let capacity = output.len() / channels;
+        let produced_frames = if capacity > 0 {
+            self.receive_samples(&mut output[..capacity * channels], capacity)
+        } else {
+            0
+        };
+
+        Ok(BlockResult {
+            consumed_frames,
+            produced_frames,
+            pending: self.pending_within(capacity),
+        })
+    }
+}
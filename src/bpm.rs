@@ -0,0 +1,243 @@
+//! Working with the beats found by [`BPMDetect`].
+//!
+//! [`BeatGrid`] describes a steady beat by its tempo and the position of its
+//! first beat, and converts it into the timing signals lighting and sync
+//! setups need: 24 PPQN MIDI clock ticks and an audible click track to check
+//! the grid against the music by ear.
+//!
+//! ```rust
+//! use soundtouch::bpm::BeatGrid;
+//!
+//! let grid = BeatGrid::new(120.0, 0.25);
+//! let ticks: Vec<u64> = grid.to_midi_clock(48000).take(25).collect();
+//! assert_eq!(ticks[0], 12000);
+//! assert_eq!(ticks[24], 36000);
+//! ```
+//!
+//! [`BPMDetect`]: crate::BPMDetect
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+use crate::onsets::Onset;
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+use crate::BPMDetect;
+
+/// MIDI clock ticks per quarter note.
+const MIDI_CLOCK_PPQN: u32 = 24;
+
+/// Length of a click rendered by [`BeatGrid::to_click_track`], in seconds.
+#[cfg(feature = "alloc")]
+const CLICK_SECONDS: f64 = 0.02;
+
+/// Frequency of the square wave of a click, in Hz.
+#[cfg(feature = "alloc")]
+const CLICK_FREQUENCY: f64 = 1000.0;
+
+/// A steady beat: a tempo and the position of the first beat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatGrid {
+    /// Tempo in beats per minute.
+    pub bpm: f64,
+    /// Position of the first beat in seconds from the start of the audio, in
+    /// `0.0..`[`period`](BeatGrid::period). This is the phase of the grid.
+    pub offset: f64,
+}
+
+impl BeatGrid {
+    /// Creates a grid with `bpm` beats per minute, with beats at `offset` seconds
+    /// plus whole periods. The offset is wrapped into the first period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bpm` isn't positive and finite.
+    pub fn new(bpm: f64, offset: f64) -> Self {
+        assert!(
+            bpm > 0.0 && bpm.is_finite(),
+            "invalid tempo of {bpm} beats per minute"
+        );
+        let mut grid = Self { bpm, offset: 0.0 };
+        grid.offset = wrap(offset, grid.period());
+        grid
+    }
+
+    /// Returns the time between two beats in seconds.
+    pub fn period(&self) -> f64 {
+        60.0 / self.bpm
+    }
+
+    /// Returns the position of beat `index` in seconds.
+    pub fn beat_position(&self, index: u64) -> f64 {
+        self.offset + index as f64 * self.period()
+    }
+
+    /// Returns the frame positions at `sample_rate` of the MIDI clock ticks of the
+    /// grid, 24 per beat, starting with the tick on the first beat.
+    ///
+    /// The iterator is endless; take as many ticks as needed. Every position is
+    /// computed from the grid directly, so rounding errors don't accumulate.
+    pub fn to_midi_clock(&self, sample_rate: u32) -> impl Iterator<Item = u64> {
+        let offset = self.offset * sample_rate as f64;
+        let tick = self.period() * sample_rate as f64 / MIDI_CLOCK_PPQN as f64;
+        (0..).map(move |index: u64| (offset + index as f64 * tick + 0.5) as u64)
+    }
+
+    /// Renders a mono click track of `length_frames` frames at `sample_rate` with a
+    /// short 1 kHz click starting at every beat of the grid.
+    ///
+    /// Mix it with the analyzed audio to check the grid by ear, or feed it back to a
+    /// [`BPMDetect`].
+    #[cfg(feature = "alloc")]
+    pub fn to_click_track(&self, sample_rate: u32, length_frames: usize) -> Vec<f32> {
+        let sample_rate = sample_rate as f64;
+        let click_len = (CLICK_SECONDS * sample_rate) as usize;
+        let half_wave = (sample_rate / CLICK_FREQUENCY / 2.0).max(1.0);
+        let mut track = vec![0.0; length_frames];
+        for index in 0.. {
+            let start = (self.beat_position(index) * sample_rate + 0.5) as usize;
+            if start >= length_frames {
+                break;
+            }
+            let end = (start + click_len).min(length_frames);
+            for (i, sample) in track[start..end].iter_mut().enumerate() {
+                let decay = 1.0 - i as f32 / click_len as f32;
+                let sign = if ((i as f64 / half_wave) as usize).is_multiple_of(2) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                *sample = 0.8 * sign * decay;
+            }
+        }
+        track
+    }
+
+    /// Returns the index and position in seconds of the beat nearest to `time_secs`.
+    /// Times before the first beat snap to it.
+    pub fn nearest_beat(&self, time_secs: f64) -> (u64, f64) {
+        let beats = (time_secs - self.offset) / self.period() + 0.5;
+        let index = if beats > 0.0 { beats as u64 } else { 0 };
+        (index, self.beat_position(index))
+    }
+
+    /// Returns the positions of the beats from `start` up to, but not including, `end`,
+    /// in order.
+    ///
+    /// Positions are rounded to the nearest nanosecond, halves up, like
+    /// [`Frames::to_duration`], and compared with `start` and `end` after rounding.
+    ///
+    /// ```rust
+    /// use soundtouch::bpm::BeatGrid;
+    /// use std::time::Duration;
+    ///
+    /// let grid = BeatGrid::new(120.0, 0.25);
+    /// let beats: Vec<Duration> = grid
+    ///     .beats_between(Duration::from_millis(250), Duration::from_secs(2))
+    ///     .collect();
+    /// assert_eq!(beats.len(), 4);
+    /// assert_eq!(beats[1], Duration::from_millis(750));
+    /// ```
+    ///
+    /// [`Frames::to_duration`]: crate::units::Frames::to_duration
+    pub fn beats_between(&self, start: Duration, end: Duration) -> impl Iterator<Item = Duration> {
+        let grid = *self;
+        let beats = (start.as_secs_f64() - self.offset) / self.period();
+        // Truncating rounds towards zero, so this is at or before the first beat.
+        let first = if beats > 1.0 { beats as u64 - 1 } else { 0 };
+        (first..)
+            .map(move |index| {
+                let nanos = grid.beat_position(index) * 1e9 + 0.5;
+                Duration::from_nanos(nanos as u64)
+            })
+            .skip_while(move |&beat| beat < start)
+            .take_while(move |&beat| beat < end)
+    }
+
+    /// Moves each onset to the [nearest beat] of the grid, e.g. to slice a loop on the
+    /// beat where the sounds start. When several onsets snap to the same beat, only the
+    /// strongest is kept, so the result has at most one onset per beat, in order.
+    ///
+    /// [nearest beat]: BeatGrid::nearest_beat
+    #[cfg(all(feature = "alloc", feature = "bpm"))]
+    pub fn snap_onsets(&self, onsets: &[Onset]) -> Vec<Onset> {
+        let mut snapped: Vec<(u64, Onset)> = Vec::with_capacity(onsets.len());
+        for onset in onsets {
+            let (index, time_secs) = self.nearest_beat(onset.time_secs);
+            let onset = Onset {
+                time_secs,
+                strength: onset.strength,
+            };
+            match snapped.iter_mut().find(|(beat, _)| *beat == index) {
+                Some((_, kept)) if kept.strength < onset.strength => *kept = onset,
+                Some(_) => {}
+                None => snapped.push((index, onset)),
+            }
+        }
+        snapped.sort_by_key(|&(index, _)| index);
+        snapped.into_iter().map(|(_, onset)| onset).collect()
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+impl BPMDetect {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the beat grid of the analyzed audio, or `None` if no tempo or no beats
+    /// were detected.
+    ///
+    /// The tempo comes from [`get_bpm`]. The phase is fitted to the beats at or above
+    /// the [`suggested_threshold`], weighted by their strength, so weak detections
+    /// between the beats don't pull the grid off.
+    ///
+    /// [`get_bpm`]: BPMDetect::get_bpm
+    /// [`suggested_threshold`]: BPMDetect::suggested_threshold
+    pub fn beat_grid(&mut self) -> Option<BeatGrid> {
+        let bpm = self.get_bpm() as f64;
+        if bpm <= 0.0 {
+            return None;
+        }
+        let period = 60.0 / bpm;
+        let threshold = self.suggested_threshold();
+        let beats: Vec<(f64, f64)> = self
+            .beats()
+            .into_iter()
+            .filter(|beat| beat.strength >= threshold)
+            .map(|beat| (wrap(beat.position as f64, period), beat.strength as f64))
+            .collect();
+
+        // The weighted mean of phases only makes sense around the right center, as they
+        // wrap around, so start from the beat phase closest to all the others.
+        let distance = |a: f64, b: f64| wrap(a - b + period / 2.0, period) - period / 2.0;
+        let cost = |center: f64| -> f64 {
+            beats
+                .iter()
+                .map(|&(phase, weight)| weight * distance(phase, center) * distance(phase, center))
+                .sum()
+        };
+        let center = beats
+            .iter()
+            .map(|&(phase, _)| phase)
+            .min_by(|&a, &b| cost(a).total_cmp(&cost(b)))?;
+
+        let weight: f64 = beats.iter().map(|&(_, weight)| weight).sum();
+        let shift: f64 = beats
+            .iter()
+            .map(|&(phase, weight)| weight * distance(phase, center))
+            .sum();
+        Some(BeatGrid::new(bpm, center + shift / weight))
+    }
+}
+
+/// Wraps `x` into `0.0..period`. `f64::rem_euclid` needs std.
+fn wrap(x: f64, period: f64) -> f64 {
+    let wrapped = x - period * ((x / period) as i64 as f64);
+    if wrapped < 0.0 {
+        wrapped + period
+    } else if wrapped >= period {
+        wrapped - period
+    } else {
+        wrapped
+    }
+}
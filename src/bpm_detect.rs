@@ -1,19 +1,43 @@
+use core::ffi::c_void;
+use core::fmt;
+use core::ops::Range;
 use core::ptr::null_mut;
+use core::slice;
+use core::time::Duration;
 
 use ffi::{BPMDetect as BPMDetectSys, BPMDetect_BPMDetect_destructor};
 use libc::c_int;
 use soundtouch_ffi as ffi;
 
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::cancel::CancellationToken;
+#[cfg(feature = "alloc")]
+use crate::resample::resampler;
+
+use crate::error::OrPanic;
+use crate::ffi_call::ffi_call;
+use crate::instance::Instance;
+use crate::prefilter::Prefilter;
+use crate::{ffi_chunks, PrefilterConfig, SoundTouch, SoundTouchError};
+
+/// Samples converted at a time for [`BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE`] input.
+#[cfg(feature = "alloc")]
+const UPSAMPLE_BUFFER_SAMPLES: usize = 4096;
+
 /// Beats-per-minute (BPM) detection routine.
 ///
 /// The beat detection algorithm works as follows:
 /// - Use function [`input_samples`] to input a chunks of samples to the class for
 ///   analysis. It's a good idea to enter a large sound file or stream in smallish
 ///   chunks of around few kilosamples in order not to extinguish too much RAM memory.
-/// - Input sound data is decimated to approx 500 Hz to reduce calculation burden,
-///   which is basically ok as low (bass) frequencies mostly determine the beat rate.
-///   Simple averaging is used for anti-alias filtering because the resulting signal
-///   quality isn't of that high importance.
+/// - Input sound data is mixed down to mono and decimated to approx 1000 Hz (see
+///   [`effective_analysis_rate`]) to reduce calculation burden, which is basically ok
+///   as low (bass) frequencies mostly determine the beat rate. Simple averaging is
+///   used for anti-alias filtering because the resulting signal quality isn't of that
+///   high importance.
 /// - Decimated sound data is enveloped, i.e. the amplitude shape is detected by
 ///   taking absolute value that's smoothed by sliding average. Signal levels that
 ///   are below a couple of times the general RMS amplitude level are cut away to
@@ -26,20 +50,425 @@ use soundtouch_ffi as ffi;
 ///
 ///  [`get_bpm`]: BPMDetect::get_bpm
 ///  [`input_samples`]: BPMDetect::input_samples
-pub struct BPMDetect(BPMDetectSys);
+///  [`effective_analysis_rate`]: BPMDetect::effective_analysis_rate
+pub struct BPMDetect {
+    inner: BPMDetectSys,
+    /// Number of channels of the input, which differs from `inner.channels` when the
+    /// input is downmixed before analysis.
+    channels: u32,
+    /// Sample rate of the input, which differs from `inner.sampleRate` when the input is
+    /// upsampled before analysis.
+    sample_rate: u32,
+    downmix: Downmix,
+    /// Low-pass filter applied to the mono downmix, at the sample rate of `inner`.
+    prefilter: Option<Prefilter>,
+    /// Input after which beat positions are no longer collected.
+    max_analysis_duration: Option<Duration>,
+    /// Frames analyzed so far, at the sample rate of `inner`.
+    analyzed_frames: u64,
+    /// Samples of an incomplete frame at the end of the last input, of which the first
+    /// `partial_len` are set. The next input completes the frame.
+    partial: [f32; SoundTouch::MAX_CHANNELS as usize],
+    partial_len: usize,
+    #[cfg(feature = "alloc")]
+    envelope: Option<Envelope>,
+    /// Converts input below [`BPMDetect::MIN_SAMPLE_RATE`] to that rate before analysis.
+    #[cfg(feature = "alloc")]
+    upsampler: Option<SoundTouch>,
+    instance: Instance,
+}
 
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How [`BPMDetect`] mixes multichannel input down to the mono signal it analyzes.
+///
+/// Left and right are the first two channels. For mono input both are that channel, so
+/// [`Side`](Downmix::Side) is silent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Downmix {
+    /// The average of all channels, which is what SoundTouch does itself.
+    #[default]
+    Average,
+    /// Only the left channel.
+    LeftOnly,
+    /// Only the right channel.
+    RightOnly,
+    /// The average of left and right, `(L + R) / 2`.
+    Mid,
+    /// Half the difference of left and right, `(L - R) / 2`, i.e. what differs between
+    /// the two channels.
+    Side,
+}
+
+impl Downmix {
+    pub(crate) fn mix(self, frame: &[f32]) -> f32 {
+        let left = frame[0];
+        let right = frame.get(1).copied().unwrap_or(left);
+        match self {
+            Downmix::Average => frame.iter().sum::<f32>() / frame.len() as f32,
+            Downmix::LeftOnly => left,
+            Downmix::RightOnly => right,
+            Downmix::Mid => (left + right) / 2.0,
+            Downmix::Side => (left - right) / 2.0,
+        }
+    }
+}
+
+/// The envelope collected by [`BPMDetect::set_collect_envelope`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default)]
+struct Envelope {
+    values: Vec<f32>,
+    sum: f32,
+    frames: usize,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Builder for a [`BPMDetect`] that validates the format before creating it.
+///
+/// Any number of channels up to [`SoundTouch::MAX_CHANNELS`] works, as the input is
+/// mixed down to mono before analysis. The defaults are 2 channels at 44100 Hz.
+///
+/// ```rust
+/// use soundtouch::BPMDetect;
+///
+/// let bpm = BPMDetect::builder().channels(2).sample_rate(44100).build().unwrap();
+/// assert_eq!(bpm.decimation_factor(), 44);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BPMDetectBuilder {
+    channels: u32,
+    sample_rate: u32,
+    downmix: Downmix,
+}
+
+impl Default for BPMDetectBuilder {
+    fn default() -> Self {
+        Self {
+            channels: 2,
+            sample_rate: 44100,
+            downmix: Downmix::Average,
+        }
+    }
+}
+
+impl BPMDetectBuilder {
+    /// Sets the number of channels of the input.
+    pub fn channels(&mut self, channels: u32) -> &mut Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Sets the sample rate of the input.
+    pub fn sample_rate(&mut self, sample_rate: u32) -> &mut Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Sets how the input is mixed down to mono. See [`BPMDetect::set_downmix`].
+    pub fn downmix(&mut self, downmix: Downmix) -> &mut Self {
+        self.downmix = downmix;
+        self
+    }
+
+    /// Creates the [`BPMDetect`], returning an error if the number of channels or the
+    /// sample rate is invalid. See [`BPMDetect::new`] for the valid ranges.
+    pub fn build(&self) -> Result<BPMDetect, SoundTouchError> {
+        let mut bpm = BPMDetect::try_new(self.channels, self.sample_rate)?;
+        bpm.set_downmix(self.downmix);
+        Ok(bpm)
+    }
+}
+
+// SAFETY: The C++ object exclusively owns its buffers (the autocorrelation
+// arrays, the decimation FIFO and the beat list), uses no thread-local storage
+// and no global mutable state (its only statics are constants), so it can be
+// moved to another thread. It is not `Sync`, as nothing in it is synchronized.
 unsafe impl Send for BPMDetect {}
 
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A beat detected by [`BPMDetect`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Beat {
+    /// Position of the beat in seconds from the start of the input.
+    pub position: f32,
+    /// Strength of the beat. Normalized to `0.0..=1.0` by [`BPMDetect::beats`], raw
+    /// autocorrelation magnitude from [`BPMDetect::beats_raw`].
+    pub strength: f32,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The tempo found by [`BPMDetect::result`], with how sure the analysis is of it.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BpmResult {
+    /// The tempo [`BPMDetect::get_bpm`] found, or `None` if it found none or the
+    /// confidence is below [`MIN_CONFIDENCE`].
+    ///
+    /// [`MIN_CONFIDENCE`]: BpmResult::MIN_CONFIDENCE
+    pub bpm: Option<f32>,
+    /// How much the autocorrelation peak of the tempo stands out from the strongest
+    /// peak that isn't a harmonic of it, in `0.0..=1.0`. `1.0` means no other tempo
+    /// repeats at all, `0.0` that another one repeats as strongly.
+    pub confidence: f32,
+    /// Other tempos the input repeats at, including harmonics such as half and double
+    /// the tempo, with their strengths relative to the tempo's peak. Sorted from the
+    /// strongest, at most [`MAX_COMPETING`].
+    ///
+    /// [`MAX_COMPETING`]: BpmResult::MAX_COMPETING
+    pub competing: Vec<(f32, f32)>,
+}
+
+#[cfg(feature = "alloc")]
+impl BpmResult {
+    /// Lowest confidence for which [`bpm`](BpmResult::bpm) is reported.
+    pub const MIN_CONFIDENCE: f32 = 0.3;
+
+    /// Most tempos listed in [`competing`](BpmResult::competing).
+    pub const MAX_COMPETING: usize = 3;
+
+    /// Relative difference up to which two lags count as the same peak, or one as a
+    /// harmonic of the other.
+    const LAG_TOLERANCE: f64 = 0.06;
+
+    /// Ratios of lags that are harmonics of each other, the larger lag over the smaller.
+    const HARMONICS: [f64; 5] = [2.0, 3.0, 4.0, 1.5, 4.0 / 3.0];
+}
+
+/// Returns `true` if `a` and `b` are equal within [`BpmResult::LAG_TOLERANCE`].
+#[cfg(feature = "alloc")]
+fn near(a: f64, b: f64) -> bool {
+    a > b * (1.0 - BpmResult::LAG_TOLERANCE) && a < b * (1.0 + BpmResult::LAG_TOLERANCE)
+}
+
+#[cfg(feature = "alloc")]
+impl Envelope {
+    fn input(&mut self, samples: &[f32], channels: usize, decimate_by: usize) {
+        for frame in samples.chunks_exact(channels) {
+            self.sum += frame.iter().map(|sample| sample.abs()).sum::<f32>();
+            self.frames += 1;
+            if self.frames == decimate_by {
+                self.values.push(self.sum / (decimate_by * channels) as f32);
+                self.sum = 0.0;
+                self.frames = 0;
+            }
+        }
+    }
+}
+
 impl Default for BPMDetect {
     fn default() -> Self {
-        Self(unsafe { BPMDetectSys::new(2, 44100) })
+        // SAFETY: Stereo at 44.1 kHz is a valid format, so the constructor doesn't throw.
+        Self::from_sys(unsafe { BPMDetectSys::new(2, 44100) }, 44100)
     }
 }
 
 impl BPMDetect {
+    /// Lowest sample rate SoundTouch analyzes directly. It decimates the input by
+    /// `sample_rate / 1000` and needs at least a factor of 8. With the `alloc` feature,
+    /// input down to [`MIN_UPSAMPLED_SAMPLE_RATE`] is upsampled to this rate first.
+    ///
+    /// [`MIN_UPSAMPLED_SAMPLE_RATE`]: BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE
+    pub const MIN_SAMPLE_RATE: u32 = 8000;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Lowest sample rate accepted with the `alloc` feature. Input below
+    /// [`MIN_SAMPLE_RATE`] is converted to that rate with SoundTouch's rate transposer,
+    /// as in [`resample`], before it is analyzed. The beats then come out a few
+    /// milliseconds late, by the latency of the conversion, and the last few
+    /// milliseconds of input are only analyzed once more input follows.
+    ///
+    /// [`MIN_SAMPLE_RATE`]: BPMDetect::MIN_SAMPLE_RATE
+    /// [`resample`]: crate::resample
+    #[cfg(feature = "alloc")]
+    pub const MIN_UPSAMPLED_SAMPLE_RATE: u32 = 2000;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// The sample rate SoundTouch decimates the input to, in Hz.
+    pub const TARGET_ANALYSIS_RATE: u32 = 1000;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Smallest decimation factor accepted. SoundTouch itself needs this much, as it
+    /// decimates blocks of 2048 frames into at most 256 values.
+    pub const MIN_DECIMATION_FACTOR: u32 = 8;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Largest accepted relative difference between the [effective analysis rate] and
+    /// [`TARGET_ANALYSIS_RATE`], caused by the decimation factor being rounded down.
+    ///
+    /// [effective analysis rate]: BPMDetect::effective_analysis_rate
+    /// [`TARGET_ANALYSIS_RATE`]: BPMDetect::TARGET_ANALYSIS_RATE
+    pub const MAX_ANALYSIS_RATE_DRIFT: f64 = 0.2;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Chunk size, in frames, that [`input_all`] is meant to be used with. Small enough
+    /// to keep the analyzer's buffers small and large enough that the per-call overhead
+    /// doesn't matter.
+    ///
+    /// [`input_all`]: BPMDetect::input_all
+    pub const RECOMMENDED_CHUNK_FRAMES: usize = 4096;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Most decimated samples [`update_x_corr`] processes in one call, the length of
+    /// SoundTouch's `XCORR_UPDATE_SEQUENCE`.
+    ///
+    /// [`update_x_corr`]: BPMDetect::update_x_corr
+    pub const MAX_XCORR_SAMPLES: usize = 200;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Most decimated samples [`update_beat_pos`] processes in one call, half of
+    /// [`MAX_XCORR_SAMPLES`].
+    ///
+    /// [`update_beat_pos`]: BPMDetect::update_beat_pos
+    /// [`MAX_XCORR_SAMPLES`]: BPMDetect::MAX_XCORR_SAMPLES
+    pub const MAX_BEAT_POS_SAMPLES: usize = Self::MAX_XCORR_SAMPLES / 2;
+
     /// Creates a new BPMDetect instance with the given channels and sample rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_channels` is outside `1..=`[`SoundTouch::MAX_CHANNELS`] or
+    /// `sample_rate` is outside [`MIN_SAMPLE_RATE`]`..=`[`SoundTouch::MAX_SAMPLE_RATE`]
+    /// ([`MIN_UPSAMPLED_SAMPLE_RATE`]`..=`[`SoundTouch::MAX_SAMPLE_RATE`] with the
+    /// `alloc` feature), for which SoundTouch would throw a C++ exception and abort the
+    /// process. Use [`try_new`] to handle these cases.
+    ///
+    /// [`SoundTouch::MAX_CHANNELS`]: crate::SoundTouch::MAX_CHANNELS
+    /// [`SoundTouch::MAX_SAMPLE_RATE`]: crate::SoundTouch::MAX_SAMPLE_RATE
+    /// [`MIN_SAMPLE_RATE`]: BPMDetect::MIN_SAMPLE_RATE
+    /// [`MIN_UPSAMPLED_SAMPLE_RATE`]: BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE
+    /// [`try_new`]: BPMDetect::try_new
     pub fn new(num_channels: u32, sample_rate: u32) -> Self {
-        Self(unsafe { BPMDetectSys::new(num_channels as c_int, sample_rate as c_int) })
+        Self::try_new(num_channels, sample_rate).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Creates a new BPMDetect instance, returning an error if the number of channels
+    /// or the sample rate is invalid. See [`new`].
+    ///
+    /// [`new`]: BPMDetect::new
+    pub fn try_new(num_channels: u32, sample_rate: u32) -> Result<Self, SoundTouchError> {
+        if !(1..=SoundTouch::MAX_CHANNELS).contains(&num_channels) {
+            return Err(SoundTouchError::InvalidChannels(num_channels));
+        }
+        let analysis_rate = Self::analysis_input_rate(sample_rate)
+            .ok_or(SoundTouchError::InvalidSampleRate(sample_rate))?;
+        let factor = Self::decimation_factor_for(analysis_rate);
+        let drift = analysis_rate as f64 / factor as f64 / Self::TARGET_ANALYSIS_RATE as f64;
+        if factor < Self::MIN_DECIMATION_FACTOR
+            || (drift - 1.0).abs() > Self::MAX_ANALYSIS_RATE_DRIFT
+        {
+            return Err(SoundTouchError::InvalidSampleRate(sample_rate));
+        }
+        // SAFETY: The format was validated above, so the constructor doesn't throw.
+        let inner = unsafe { BPMDetectSys::new(num_channels as c_int, analysis_rate as c_int) };
+        let bpm = Self::from_sys(inner, sample_rate);
+        #[cfg(feature = "alloc")]
+        let bpm = {
+            let mut bpm = bpm;
+            if analysis_rate != sample_rate {
+                bpm.upsampler = Some(resampler(num_channels, sample_rate, analysis_rate));
+            }
+            bpm
+        };
+        Ok(bpm)
+    }
+
+    /// Returns the rate input at `sample_rate` is analyzed at, or `None` if it can't be.
+    fn analysis_input_rate(sample_rate: u32) -> Option<u32> {
+        if (Self::MIN_SAMPLE_RATE..=SoundTouch::MAX_SAMPLE_RATE).contains(&sample_rate) {
+            return Some(sample_rate);
+        }
+        #[cfg(feature = "alloc")]
+        if (Self::MIN_UPSAMPLED_SAMPLE_RATE..Self::MIN_SAMPLE_RATE).contains(&sample_rate) {
+            return Some(Self::MIN_SAMPLE_RATE);
+        }
+        None
+    }
+
+    fn from_sys(inner: BPMDetectSys, sample_rate: u32) -> Self {
+        Self {
+            channels: inner.channels as u32,
+            sample_rate,
+            downmix: Downmix::Average,
+            prefilter: None,
+            max_analysis_duration: None,
+            analyzed_frames: 0,
+            partial: [0.0; SoundTouch::MAX_CHANNELS as usize],
+            partial_len: 0,
+            instance: Instance::new("BPMDetect", inner.channels as u32, sample_rate),
+            inner,
+            #[cfg(feature = "alloc")]
+            envelope: None,
+            #[cfg(feature = "alloc")]
+            upsampler: None,
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the ID of the instance. IDs are shared with [`SoundTouch::id`].
+    pub fn id(&self) -> u64 {
+        self.instance.id()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns a [`BPMDetectBuilder`] with the default format.
+    pub fn builder() -> BPMDetectBuilder {
+        BPMDetectBuilder::default()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the factor the input is decimated by before analysis, i.e.
+    /// `sample_rate / 1000` rounded down, where the sample rate is
+    /// [`MIN_SAMPLE_RATE`] for [upsampled] input.
+    ///
+    /// [`MIN_SAMPLE_RATE`]: BPMDetect::MIN_SAMPLE_RATE
+    /// [upsampled]: BPMDetect::is_upsampling
+    pub fn decimation_factor(&self) -> u32 {
+        self.inner.decimateBy as u32
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the factor input at `sample_rate` is decimated by, as SoundTouch computes
+    /// it, without checking that the rate is valid.
+    pub const fn decimation_factor_for(sample_rate: u32) -> u32 {
+        sample_rate / Self::TARGET_ANALYSIS_RATE
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns `true` if the input is below [`MIN_SAMPLE_RATE`] and upsampled to it
+    /// before analysis, see [`MIN_UPSAMPLED_SAMPLE_RATE`].
+    ///
+    /// [`MIN_SAMPLE_RATE`]: BPMDetect::MIN_SAMPLE_RATE
+    /// [`MIN_UPSAMPLED_SAMPLE_RATE`]: BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE
+    #[cfg(feature = "alloc")]
+    pub fn is_upsampling(&self) -> bool {
+        self.upsampler.is_some()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the sample rate, in Hz, of the decimated signal the beat detection runs
+    /// on. It is close to 1000 Hz, which limits the time resolution of beat positions
+    /// to about a millisecond.
+    pub fn effective_analysis_rate(&self) -> f64 {
+        self.inner.sampleRate as f64 / self.inner.decimateBy as f64
     }
 
     /// Inputs a block of samples for analyzing: Envelopes the samples and then
@@ -49,21 +478,518 @@ impl BPMDetect {
     ///
     /// Notice that data in `samples` array can be disrupted in processing.
     ///
+    /// **NOT FROM SOUNDTOUCH**: An empty `samples` does nothing. `samples` doesn't have
+    /// to hold whole frames: the samples of an incomplete frame at the end are kept and
+    /// completed by the next call, so every frame is analyzed exactly once however the
+    /// input is split.
+    ///
+    /// The results don't depend on the split either. SoundTouch carries its decimation,
+    /// envelope and autocorrelation state across calls, and updates the autocorrelation
+    /// whenever enough decimated samples have accumulated, so the same frames lead to
+    /// the same updates in the same order. The only input not analyzed yet at any point,
+    /// an incomplete frame and the decimated samples short of the next update, is the
+    /// same for the same total input.
+    ///
     /// [`get_bpm`]: BPMDetect::get_bpm
-    pub fn input_samples(&mut self, samples: &[f32]) {
+    pub fn input_samples(&mut self, mut samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let channels = self.channels as usize;
+        if self.partial_len > 0 {
+            let taken = (channels - self.partial_len).min(samples.len());
+            let end = self.partial_len + taken;
+            self.partial[self.partial_len..end].copy_from_slice(&samples[..taken]);
+            self.partial_len = end;
+            samples = &samples[taken..];
+            if end < channels {
+                return;
+            }
+            let frame = self.partial;
+            self.partial_len = 0;
+            self.input_frames(&frame[..channels]);
+        }
+        let (frames, rest) = samples.split_at(samples.len() / channels * channels);
+        self.partial[..rest.len()].copy_from_slice(rest);
+        self.partial_len = rest.len();
+        self.input_frames(frames);
+    }
+
+    /// Inputs whole frames of the input format.
+    fn input_frames(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let channels = self.channels as usize;
+        self.instance.record_put(samples.len() / channels);
+        #[cfg(feature = "alloc")]
+        if let Some(mut upsampler) = self.upsampler.take() {
+            upsampler.put_samples(samples, samples.len() / channels);
+            let mut buffer = [0.0; UPSAMPLE_BUFFER_SAMPLES];
+            let buffer = &mut buffer[..UPSAMPLE_BUFFER_SAMPLES / channels * channels];
+            loop {
+                let frames = upsampler.receive_samples(buffer, buffer.len() / channels);
+                if frames == 0 {
+                    break;
+                }
+                self.analyze(&buffer[..frames * channels]);
+            }
+            self.upsampler = Some(upsampler);
+            return;
+        }
+        self.analyze(samples);
+    }
+
+    /// Analyzes input at the rate of the analyzer.
+    fn analyze(&mut self, samples: &[f32]) {
+        let channels = self.channels as usize;
+        #[cfg(feature = "alloc")]
+        if let Some(envelope) = &mut self.envelope {
+            envelope.input(samples, channels, self.inner.decimateBy as usize);
+        }
+        if !self.mixes_down() {
+            self.input_interleaved(samples);
+            return;
+        }
+
+        const BLOCK_FRAMES: usize = 1024;
+        let mut mono = [0.0; BLOCK_FRAMES];
+        for block in samples.chunks(BLOCK_FRAMES * channels) {
+            let mut frames = 0;
+            for (sample, frame) in mono.iter_mut().zip(block.chunks_exact(channels)) {
+                *sample = self.downmix.mix(frame);
+                frames += 1;
+            }
+            if let Some(prefilter) = &mut self.prefilter {
+                prefilter.apply(&mut mono[..frames]);
+            }
+            self.input_interleaved(&mono[..frames]);
+        }
+    }
+
+    /// Inputs samples with the number of channels the analyzer was created with.
+    fn input_interleaved(&mut self, samples: &[f32]) {
+        let channels = self.inner.channels as usize;
+        let frames = samples.len() / channels;
+        let with_beats = match self.max_analysis_duration {
+            Some(max) => {
+                let max_frames = (max.as_secs_f64() * self.inner.sampleRate as f64) as u64;
+                max_frames
+                    .saturating_sub(self.analyzed_frames)
+                    .min(frames as u64) as usize
+            }
+            None => frames,
+        };
+        self.analyzed_frames += frames as u64;
+
+        let mut offset = 0;
+        for frames in ffi_chunks(with_beats, channels as u32) {
+            let inner = &mut self.inner;
+            // SAFETY: `inputSamples` reads `frames` frames of the channels the analyzer was
+            // created with.
+            ffi_call!([input = &samples[offset * channels..], frames * channels] => {
+                inner.inputSamples(input, frames as c_int)
+            });
+            offset += frames;
+        }
+        if with_beats < frames {
+            self.input_tempo_only(&samples[with_beats * channels..frames * channels]);
+        }
+    }
+
+    /// Inputs samples like SoundTouch's `inputSamples`, but only updates the
+    /// autocorrelation and not the beat positions, whose list grows with the input.
+    fn input_tempo_only(&mut self, samples: &[f32]) {
+        const INPUT_BLOCK_FRAMES: usize = 2048;
+        const DECIMATED_BLOCK_SAMPLES: usize =
+            INPUT_BLOCK_FRAMES / BPMDetect::MIN_DECIMATION_FACTOR as usize;
+        /// Decimated samples dropped after each autocorrelation update, as SoundTouch
+        /// overlaps the updates by a factor of 4.
+        const XCORR_STEP: usize = BPMDetect::MAX_XCORR_SAMPLES / 4;
+
+        let channels = self.inner.channels as usize;
+        let buffer = self.inner.buffer as *mut c_void;
+        let mut decimated = [0.0; DECIMATED_BLOCK_SAMPLES];
+        for block in samples.chunks(INPUT_BLOCK_FRAMES * channels) {
+            // With a decimation factor of at least 8, a block fits into `decimated`.
+            let written = self
+                .try_decimate(&mut decimated, block, (block.len() / channels) as i32)
+                .or_panic();
+            // SAFETY: `buffer` is the analyzer's own mono FIFO, allocated in its
+            // constructor, and `putSamples` copies the `written` samples of `decimated`.
+            unsafe {
+                ffi::FIFOSampleBuffer_putSamples(buffer, decimated.as_ptr(), written as ffi::uint)
+            };
+        }
+
+        let window = self.inner.windowLen as usize;
+        let required = (window + Self::MAX_XCORR_SAMPLES).max(2 * Self::MAX_XCORR_SAMPLES);
+        // SAFETY: `buffer` is the analyzer's own FIFO.
+        while unsafe { ffi::FIFOSampleBuffer_numSamples(buffer) } as usize >= required {
+            self.try_update_x_corr(Self::MAX_XCORR_SAMPLES as i32)
+                .or_panic();
+            // SAFETY: `buffer` is the analyzer's own FIFO, which holds more than
+            // `XCORR_STEP` samples.
+            unsafe { ffi::FIFOSampleBuffer_receiveSamples1(buffer, XCORR_STEP as ffi::uint) };
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets how the input is mixed down to mono before the analysis. The default,
+    /// [`Downmix::Average`], leaves it to SoundTouch; with any other downmix the input is
+    /// mixed down here and the analyzer only ever sees mono data.
+    ///
+    /// The number of channels passed to [`input_samples`] stays the same. Changing the
+    /// downmix restarts the analysis, so set it before the first input.
+    ///
+    /// [`input_samples`]: BPMDetect::input_samples
+    pub fn set_downmix(&mut self, downmix: Downmix) -> &mut Self {
+        if downmix != self.downmix {
+            self.downmix = downmix;
+            self.restart();
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets a low-pass filter to apply to the input before the analysis, or removes it
+    /// with `None`, the default. See [`PrefilterConfig`] for when it helps.
+    ///
+    /// The input is then mixed down here with the current [downmix], even for
+    /// [`Downmix::Average`], and the filter runs on the mono signal, after [upsampling]
+    /// and before SoundTouch decimates it. Changing the filter restarts the analysis,
+    /// which also resets the filter, so set it before the first input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cutoff_hz` isn't positive and below half the sample rate, or `order` is
+    /// outside `1..=`[`PrefilterConfig::MAX_ORDER`].
+    ///
+    /// [downmix]: BPMDetect::set_downmix
+    /// [upsampling]: BPMDetect::is_upsampling
+    pub fn set_prefilter(&mut self, prefilter: Option<PrefilterConfig>) -> &mut Self {
+        if let Some(config) = prefilter {
+            assert!(
+                config.is_valid_for(self.sample_rate),
+                "invalid prefilter of order {} at {} Hz for {} Hz input",
+                config.order,
+                config.cutoff_hz,
+                self.sample_rate
+            );
+        }
+        if prefilter != self.prefilter() {
+            self.prefilter =
+                prefilter.map(|config| Prefilter::new(config, self.inner.sampleRate as u32));
+            self.restart();
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the low-pass filter applied before the analysis. See [`set_prefilter`].
+    ///
+    /// [`set_prefilter`]: BPMDetect::set_prefilter
+    pub fn prefilter(&self) -> Option<PrefilterConfig> {
+        self.prefilter.as_ref().map(Prefilter::config)
+    }
+
+    /// Returns `true` if the input is mixed down here rather than by SoundTouch.
+    fn mixes_down(&self) -> bool {
+        self.downmix != Downmix::Average || self.prefilter.is_some()
+    }
+
+    /// Replaces the analyzer with a new one for the current downmix and forgets the input
+    /// analyzed so far, keeping the settings.
+    fn restart(&mut self) {
+        let channels = if self.mixes_down() { 1 } else { self.channels };
+        // SAFETY: The format is that of the current analyzer or mono at its sample rate,
+        // both valid. The old analyzer is destroyed once and then overwritten without
+        // being dropped.
         unsafe {
-            self.0
-                .inputSamples(samples.as_ptr(), samples.len() as c_int / self.0.channels)
+            let inner = BPMDetectSys::new(channels as c_int, self.inner.sampleRate);
+            BPMDetect_BPMDetect_destructor(&mut self.inner);
+            core::ptr::write(&mut self.inner, inner);
+        }
+        self.analyzed_frames = 0;
+        self.partial_len = 0;
+        if let Some(prefilter) = &mut self.prefilter {
+            prefilter.reset();
+        }
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(envelope) = &mut self.envelope {
+                *envelope = Envelope::default();
+            }
+            if let Some(upsampler) = &mut self.upsampler {
+                upsampler.clear();
+            }
         }
     }
 
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns how the input is mixed down to mono. See [`set_downmix`].
+    ///
+    /// [`set_downmix`]: BPMDetect::set_downmix
+    pub fn downmix(&self) -> Downmix {
+        self.downmix
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Inputs all of `samples` for analysis in chunks of `chunk_frames` frames, as
+    /// recommended for large files. The result is the same as a single call to
+    /// [`input_samples`]. [`RECOMMENDED_CHUNK_FRAMES`] is a good default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_frames` is `0`.
+    ///
+    /// [`input_samples`]: BPMDetect::input_samples
+    /// [`RECOMMENDED_CHUNK_FRAMES`]: BPMDetect::RECOMMENDED_CHUNK_FRAMES
+    pub fn input_all(&mut self, samples: &[f32], chunk_frames: usize) {
+        self.input_all_with_progress(samples, chunk_frames, |_, _| {});
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`input_all`], but calls `progress` after every chunk with the number of
+    /// frames input so far and the total number of frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_frames` is `0`.
+    ///
+    /// [`input_all`]: BPMDetect::input_all
+    pub fn input_all_with_progress<F>(
+        &mut self,
+        samples: &[f32],
+        chunk_frames: usize,
+        mut progress: F,
+    ) where
+        F: FnMut(usize, usize),
+    {
+        assert!(chunk_frames > 0, "chunk_frames must be greater than 0");
+        let channels = self.channels as usize;
+        let total = samples.len() / channels;
+        let mut done = 0;
+        for chunk in samples.chunks(chunk_frames.saturating_mul(channels)) {
+            self.input_samples(chunk);
+            done += chunk.len() / channels;
+            progress(done, total);
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`input_all`], but checks `token` before every chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::Cancelled`] once `token` is cancelled. The analysis is
+    /// then restarted: the input so far, its beats and its envelope are forgotten, the
+    /// settings are kept, and the instance is ready for new input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_frames` is `0`.
+    ///
+    /// [`input_all`]: BPMDetect::input_all
+    #[cfg(feature = "alloc")]
+    pub fn input_all_cancellable(
+        &mut self,
+        samples: &[f32],
+        chunk_frames: usize,
+        token: &CancellationToken,
+    ) -> Result<(), SoundTouchError> {
+        assert!(chunk_frames > 0, "chunk_frames must be greater than 0");
+        let channels = self.channels as usize;
+        for chunk in samples.chunks(chunk_frames.saturating_mul(channels)) {
+            if token.is_cancelled() {
+                self.restart();
+                return Err(SoundTouchError::Cancelled);
+            }
+            self.input_samples(chunk);
+        }
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Limits how much of the input beat positions are collected for, or removes the
+    /// limit with `None`, the default.
+    ///
+    /// SoundTouch keeps every beat it detects, so the memory for them grows with the
+    /// length of the input, by a few kilobytes per minute. Past `max` of input, the
+    /// tempo is still analyzed, with the fixed-size autocorrelation, but no more beats
+    /// are added to those returned by [`get_beats`]. This bounds the memory when
+    /// analyzing the tempo of hours of audio.
+    ///
+    /// [`get_beats`]: BPMDetect::get_beats
+    pub fn set_max_analysis_duration(&mut self, max: Option<Duration>) -> &mut Self {
+        self.max_analysis_duration = max;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the limit set with [`set_max_analysis_duration`].
+    ///
+    /// [`set_max_analysis_duration`]: BPMDetect::set_max_analysis_duration
+    pub fn max_analysis_duration(&self) -> Option<Duration> {
+        self.max_analysis_duration
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the duration of the input analyzed so far, which doesn't include input
+    /// still held back by the upsampling of [low sample rates].
+    ///
+    /// [low sample rates]: BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE
+    pub fn analysis_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.analyzed_frames as f64 / self.inner.sampleRate as f64)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables collecting a low-rate amplitude envelope of the input, e.g.
+    /// for a waveform display, while it is analyzed. Disabling it drops the values that
+    /// haven't been taken yet.
+    ///
+    /// Every [`decimation_factor`] frames of input add one value: the mean absolute
+    /// sample value of those frames over all channels, taken after [upsampling]. The values are therefore spaced
+    /// at [`envelope_rate`] and, if enabled before the first input, line up with the
+    /// signal the beat detection runs on. An incomplete block at the end of the input is
+    /// carried over to the next call.
+    ///
+    /// Off by default, as the values accumulate until [`take_envelope`] is called.
+    ///
+    /// [`decimation_factor`]: BPMDetect::decimation_factor
+    /// [upsampling]: BPMDetect::is_upsampling
+    /// [`envelope_rate`]: BPMDetect::envelope_rate
+    /// [`take_envelope`]: BPMDetect::take_envelope
+    #[cfg(feature = "alloc")]
+    pub fn set_collect_envelope(&mut self, collect: bool) -> &mut Self {
+        match (collect, &self.envelope) {
+            (true, None) => self.envelope = Some(Envelope::default()),
+            (false, _) => self.envelope = None,
+            (true, Some(_)) => {}
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the envelope values collected since the last call, or an empty vector if
+    /// collecting isn't enabled. See [`set_collect_envelope`].
+    ///
+    /// [`set_collect_envelope`]: BPMDetect::set_collect_envelope
+    #[cfg(feature = "alloc")]
+    pub fn take_envelope(&mut self) -> Vec<f32> {
+        self.envelope
+            .as_mut()
+            .map(|envelope| core::mem::take(&mut envelope.values))
+            .unwrap_or_default()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the rate of the envelope values in Hz, i.e. the value at index `i` covers
+    /// the input from `i / envelope_rate()` seconds on. This is the same as
+    /// [`effective_analysis_rate`].
+    ///
+    /// [`effective_analysis_rate`]: BPMDetect::effective_analysis_rate
+    #[cfg(feature = "alloc")]
+    pub fn envelope_rate(&self) -> f64 {
+        self.effective_analysis_rate()
+    }
+
     /// Analyzes the results and returns the BPM rate. Use this function to read result
     /// after whole song data has been input to the class by consecutive calls of
     /// [`input_samples`] function.
     ///
     /// [`input_samples`]: BPMDetect::input_samples
     pub fn get_bpm(&mut self) -> f32 {
-        unsafe { ffi::BPMDetect_getBpm(&mut self.0) }
+        // SAFETY: Only reads the autocorrelation of a live analyzer.
+        unsafe { ffi::BPMDetect_getBpm(&mut self.inner) }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Analyzes the results like [`get_bpm`] and also rates how clearly the input has
+    /// that tempo, e.g. to tell a steady beat from a guess on a drone, and which other
+    /// tempos it could be.
+    ///
+    /// The confidence compares the [autocorrelation] peak of the tempo to the strongest
+    /// peak at a lag that isn't a harmonic of it. Harmonics, such as half and double the
+    /// tempo, repeat wherever the tempo does, so they don't make it less certain; they
+    /// are listed in [`competing`](BpmResult::competing) with the other alternatives.
+    ///
+    /// [`get_bpm`]: BPMDetect::get_bpm
+    /// [autocorrelation]: BPMDetect::xcorr
+    #[cfg(feature = "alloc")]
+    pub fn result(&mut self) -> BpmResult {
+        let bpm = self.get_bpm();
+        if bpm <= 0.0 {
+            return BpmResult::default();
+        }
+        let coefficient = 60.0 * self.effective_analysis_rate();
+        let lags = self.xcorr_lags();
+        let xcorr = self.xcorr();
+
+        let tempo_lag = coefficient / bpm as f64;
+        let strength = xcorr[lags.clone()]
+            .iter()
+            .enumerate()
+            .filter(|&(offset, _)| near((lags.start + offset) as f64, tempo_lag))
+            .fold(0.0f32, |max, (_, &value)| max.max(value));
+        if strength <= 0.0 {
+            return BpmResult::default();
+        }
+        let mut peaks: Vec<(f64, f32)> = (lags.start + 1..lags.end.saturating_sub(1))
+            .filter(|&lag| xcorr[lag] > xcorr[lag - 1] && xcorr[lag] >= xcorr[lag + 1])
+            .filter(|&lag| xcorr[lag] > 0.0 && !near(lag as f64, tempo_lag))
+            .map(|lag| (lag as f64, xcorr[lag]))
+            .collect();
+
+        let is_harmonic = |lag: f64| {
+            let ratio = if lag > tempo_lag {
+                lag / tempo_lag
+            } else {
+                tempo_lag / lag
+            };
+            BpmResult::HARMONICS
+                .iter()
+                .any(|&harmonic| near(ratio, harmonic))
+        };
+        let rival = peaks
+            .iter()
+            .filter(|&&(lag, _)| !is_harmonic(lag))
+            .fold(0.0f32, |max, &(_, value)| max.max(value));
+        let confidence = (1.0 - rival / strength).clamp(0.0, 1.0);
+
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let mut competing: Vec<(f64, f32)> = Vec::new();
+        for (lag, value) in peaks {
+            if competing.len() == BpmResult::MAX_COMPETING {
+                break;
+            }
+            if !competing.iter().any(|&(kept, _)| near(lag, kept)) {
+                competing.push((lag, value));
+            }
+        }
+
+        BpmResult {
+            bpm: (confidence >= BpmResult::MIN_CONFIDENCE).then_some(bpm),
+            confidence,
+            competing: competing
+                .into_iter()
+                .map(|(lag, value)| ((coefficient / lag) as f32, value / strength))
+                .collect(),
+        }
     }
 
     /// Get beat position arrays. Note: The array includes also really low beat detection values
@@ -73,61 +999,311 @@ impl BPMDetect {
     /// - `max_num` indicates max.size of `pos` and `values` array.  
     ///
     /// You can query a suitable array sized by calling the [`query_size`] function.
-    /// Returns the number of beats in the arrays.
+    /// Returns the total number of detected beats, of which at most `max_num` are
+    /// written. `max_num` is limited to the length of the shorter slice.
     ///
     /// [`query_size`]: BPMDetect::query_size
     pub fn get_beats(&mut self, pos: &mut [f32], values: &mut [f32], max_num: i32) -> i32 {
-        unsafe {
-            self.0
-                .getBeats(pos.as_mut_ptr(), values.as_mut_ptr(), max_num)
-        }
+        let len = pos.len().min(values.len()).min(i32::MAX as usize);
+        let max_num = max_num.clamp(0, len as i32);
+        let inner = &mut self.inner;
+        // SAFETY: `getBeats` writes at most `max_num` beats to each array.
+        ffi_call!([pos = pos, max_num as usize], [values = values, max_num as usize] => {
+            inner.getBeats(pos, values, max_num)
+        })
     }
 
     /// Queries a suitable array sized for [`get_beats`].
     ///
     /// [`get_beats`]: BPMDetect::get_beats
     pub fn query_size(&mut self, max_num: i32) -> i32 {
-        unsafe { self.0.getBeats(null_mut(), null_mut(), max_num) }
+        // SAFETY: `getBeats` only returns the number of beats when given null pointers.
+        unsafe { self.inner.getBeats(null_mut(), null_mut(), max_num) }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the detected beats with their strengths normalized to the strongest beat,
+    /// so the strongest beat has a strength of `1.0` and all others are in `0.0..=1.0`.
+    ///
+    /// The raw strengths from [`beats_raw`] are autocorrelation magnitudes that grow
+    /// with the level of the input, so a fixed threshold on them only works for one
+    /// track. Relative to the strongest beat they don't depend on the level, and
+    /// [`suggested_threshold`] gives a threshold on this scale.
+    ///
+    /// [`beats_raw`]: BPMDetect::beats_raw
+    /// [`suggested_threshold`]: BPMDetect::suggested_threshold
+    #[cfg(feature = "alloc")]
+    pub fn beats(&mut self) -> Vec<Beat> {
+        let mut beats = self.beats_raw();
+        let max = beats.iter().map(|beat| beat.strength).fold(0.0, f32::max);
+        if max > 0.0 {
+            for beat in &mut beats {
+                beat.strength /= max;
+            }
+        }
+        beats
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the detected beats with the strengths as SoundTouch reports them. See
+    /// [`get_beats`].
+    ///
+    /// [`get_beats`]: BPMDetect::get_beats
+    #[cfg(feature = "alloc")]
+    pub fn beats_raw(&mut self) -> Vec<Beat> {
+        let len = self.query_size(0).max(0) as usize;
+        let (mut positions, mut strengths) = (vec![0.0; len], vec![0.0; len]);
+        self.get_beats(&mut positions, &mut strengths, len as i32);
+        positions
+            .into_iter()
+            .zip(strengths)
+            .map(|(position, strength)| Beat { position, strength })
+            .collect()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns a strength threshold on the normalized scale of [`beats`] that separates
+    /// the clear beats from the weak detections SoundTouch also reports in the absence of
+    /// strong beats.
+    ///
+    /// The threshold splits the distribution of strengths into two classes with the
+    /// largest possible variance between them (Otsu's method, over 64 bins). It is `0.0`,
+    /// keeping all beats, if there are fewer than two beats or they are all equally
+    /// strong.
+    ///
+    /// [`beats`]: BPMDetect::beats
+    #[cfg(feature = "alloc")]
+    pub fn suggested_threshold(&mut self) -> f32 {
+        const BINS: usize = 64;
+        let beats = self.beats();
+        let mut histogram = [0usize; BINS];
+        for beat in &beats {
+            histogram[((beat.strength * BINS as f32) as usize).min(BINS - 1)] += 1;
+        }
+
+        let total = beats.len() as f64;
+        let total_sum: f64 = histogram
+            .iter()
+            .enumerate()
+            .map(|(bin, &count)| bin as f64 * count as f64)
+            .sum();
+        let (mut below, mut below_sum) = (0.0, 0.0);
+        let mut best = (0, 0.0);
+        for (bin, &count) in histogram.iter().enumerate().take(BINS - 1) {
+            below += count as f64;
+            below_sum += bin as f64 * count as f64;
+            let above = total - below;
+            if below == 0.0 || above == 0.0 {
+                continue;
+            }
+            let difference = below_sum / below - (total_sum - below_sum) / above;
+            let variance = below * above * difference * difference;
+            if variance > best.1 {
+                best = (bin + 1, variance);
+            }
+        }
+        best.0 as f32 / BINS as f32
     }
 
     /// Detects individual beat positions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `process_samples` is above [`MAX_BEAT_POS_SAMPLES`] or more than the
+    /// analyzer has buffered beyond its autocorrelation window, where SoundTouch would
+    /// read out of bounds. Use [`try_update_beat_pos`] to handle these cases.
+    ///
+    /// [`MAX_BEAT_POS_SAMPLES`]: BPMDetect::MAX_BEAT_POS_SAMPLES
+    /// [`try_update_beat_pos`]: BPMDetect::try_update_beat_pos
     pub fn update_beat_pos(&mut self, process_samples: i32) {
-        unsafe { self.0.updateBeatPos(process_samples) }
+        self.try_update_beat_pos(process_samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`update_beat_pos`], but returns an error instead of panicking.
+    ///
+    /// [`update_beat_pos`]: BPMDetect::update_beat_pos
+    pub fn try_update_beat_pos(&mut self, process_samples: i32) -> Result<(), SoundTouchError> {
+        self.check_window(process_samples, Self::MAX_BEAT_POS_SAMPLES)?;
+        // SAFETY: The check keeps the reads within the analyzer's buffer and its
+        // fixed-size arrays.
+        unsafe { self.inner.updateBeatPos(process_samples) };
+        Ok(())
     }
 
     /// Removes constant bias from xcorr data.
     pub fn remove_bias(&mut self) {
-        unsafe { self.0.removeBias() }
+        // SAFETY: Only updates the autocorrelation of a live analyzer.
+        unsafe { self.inner.removeBias() }
     }
 
     /// Calculates amplitude envelope for the buffer of samples.
     /// Result is output to `samples`.
     #[cfg(not(all(target_env="gnu", target_os="windows")))]
     pub fn calc_envelope(&mut self, samples: &mut [f32]) {
-        unsafe {
-            self.0
-                .calcEnvelope(samples.as_mut_ptr(), samples.len() as c_int)
+        let mut offset = 0;
+        for frames in ffi_chunks(samples.len(), 1) {
+            let inner = &mut self.inner;
+            // SAFETY: `calcEnvelope` reads and writes `frames` mono samples in place.
+            ffi_call!([samples = &mut samples[offset..], frames] => {
+                inner.calcEnvelope(samples, frames as c_int)
+            });
+            offset += frames;
         }
     }
 
     /// Decimates samples to approx. 500 Hz.
     ///
     /// Returns the number of output samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` holds fewer than `numsamples` frames of the channels the analyzer
+    /// works with, or `dest` has room for fewer than the decimated samples. Use
+    /// [`try_decimate`] to handle these cases.
+    ///
+    /// [`try_decimate`]: BPMDetect::try_decimate
     pub fn decimate(&mut self, dest: &mut [f32], src: &[f32], numsamples: i32) -> i32 {
-        unsafe { self.0.decimate(dest.as_mut_ptr(), src.as_ptr(), numsamples) }
+        self.try_decimate(dest, src, numsamples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`decimate`], but returns an error instead of panicking.
+    ///
+    /// [`decimate`]: BPMDetect::decimate
+    pub fn try_decimate(
+        &mut self,
+        dest: &mut [f32],
+        src: &[f32],
+        numsamples: i32,
+    ) -> Result<i32, SoundTouchError> {
+        let frames = numsamples.max(0) as usize;
+        let channels = self.inner.channels as usize;
+        if frames * channels > src.len() {
+            return Err(SoundTouchError::InputTooShort {
+                frames,
+                channels,
+                len: src.len(),
+            });
+        }
+        // Samples are accumulated across calls, so the first output may complete a
+        // decimation window started by an earlier call.
+        let outputs = (self.inner.decimateCount as usize + frames) / self.inner.decimateBy as usize;
+        if outputs > dest.len() {
+            return Err(SoundTouchError::OutputTooSmall {
+                frames: outputs,
+                channels: 1,
+                len: dest.len(),
+            });
+        }
+        let inner = &mut self.inner;
+        // SAFETY: `decimate` reads `frames` frames from `src` and writes `outputs` samples to
+        // `dest`.
+        let written = ffi_call!([dest = dest, outputs], [src = src, frames * channels] => {
+            inner.decimate(dest, src, numsamples)
+        });
+        Ok(written)
     }
 
     /// Updates auto-correlation function for given number of decimated samples that
     /// are read from the internal `buffer' pipe (samples aren't removed from the pipe
     /// though).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `process_samples` is above [`MAX_XCORR_SAMPLES`] or more than the
+    /// analyzer has buffered beyond its autocorrelation window, where SoundTouch would
+    /// read out of bounds. Use [`try_update_x_corr`] to handle these cases.
+    ///
+    /// [`MAX_XCORR_SAMPLES`]: BPMDetect::MAX_XCORR_SAMPLES
+    /// [`try_update_x_corr`]: BPMDetect::try_update_x_corr
     pub fn update_x_corr(&mut self, process_samples: i32) {
-        unsafe { self.0.updateXCorr(process_samples) }
+        self.try_update_x_corr(process_samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`update_x_corr`], but returns an error instead of panicking.
+    ///
+    /// [`update_x_corr`]: BPMDetect::update_x_corr
+    pub fn try_update_x_corr(&mut self, process_samples: i32) -> Result<(), SoundTouchError> {
+        self.check_window(process_samples, Self::MAX_XCORR_SAMPLES)?;
+        // SAFETY: The check keeps the reads within the analyzer's buffer and its
+        // fixed-size arrays.
+        unsafe { self.inner.updateXCorr(process_samples) };
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the autocorrelation of the envelope of the input analyzed so far, indexed
+    /// by the lag in envelope values at the [effective analysis rate], so a peak at lag
+    /// `lag` stands for `60.0 * effective_analysis_rate() / lag` BPM.
+    ///
+    /// Only the lags in [`xcorr_lags`] are computed; the others are `0.0`. [`get_bpm`]
+    /// and [`remove_bias`] subtract the linear trend from the values in place.
+    ///
+    /// [effective analysis rate]: BPMDetect::effective_analysis_rate
+    /// [`xcorr_lags`]: BPMDetect::xcorr_lags
+    /// [`get_bpm`]: BPMDetect::get_bpm
+    /// [`remove_bias`]: BPMDetect::remove_bias
+    pub fn xcorr(&self) -> &[f32] {
+        // SAFETY: `xcorr` is the analyzer's own array of `windowLen` values, allocated in
+        // its constructor and only changed through `&mut self`.
+        unsafe { slice::from_raw_parts(self.inner.xcorr, self.inner.windowLen as usize) }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the lags of [`xcorr`] SoundTouch computes, covering the tempos it
+    /// detects.
+    ///
+    /// [`xcorr`]: BPMDetect::xcorr
+    pub fn xcorr_lags(&self) -> Range<usize> {
+        self.inner.windowStart as usize..self.inner.windowLen as usize
+    }
+
+    /// Checks that `process_samples` decimated samples, followed by a whole
+    /// autocorrelation window, are buffered, and that they fit into arrays of `max`.
+    fn check_window(&self, process_samples: i32, max: usize) -> Result<(), SoundTouchError> {
+        let requested = process_samples.max(0) as usize;
+        // SAFETY: `buffer` is the analyzer's own FIFO, allocated in its constructor.
+        let buffered =
+            unsafe { ffi::FIFOSampleBuffer_numSamples(self.inner.buffer as *mut c_void) } as usize;
+        let available = buffered
+            .saturating_sub(self.inner.windowLen as usize)
+            .min(max);
+        if requested > available {
+            return Err(SoundTouchError::TooManySamples {
+                requested,
+                max: available,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BPMDetect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BPMDetect")
+            .field("id", &self.id())
+            .field("channels", &self.channels)
+            .field("sample_rate", &self.sample_rate)
+            .field("downmix", &self.downmix)
+            .field("prefilter", &self.prefilter())
+            .finish()
     }
 }
 
 #[cfg(not(windows))]
 impl Drop for BPMDetect {
     fn drop(&mut self) {
-        unsafe { BPMDetect_BPMDetect_destructor(&mut self.0) }
+        // SAFETY: The analyzer is destroyed once, when it is dropped.
+        unsafe { BPMDetect_BPMDetect_destructor(&mut self.inner) }
     }
 }
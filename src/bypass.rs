@@ -0,0 +1,228 @@
+use alloc::vec::Vec;
+
+use crate::sound_touch::STACK_BUFFER_SAMPLES;
+use crate::{FlushMode, SoundTouch};
+
+/// Length of the crossfades into and out of the bypass, in seconds.
+const CROSSFADE_SECONDS: f64 = 0.005;
+
+/// The state of the bypass set with [`SoundTouch::set_bypass_when_neutral`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bypass {
+    enabled: bool,
+    /// Whether the last input was passed around SoundTouch.
+    active: bool,
+    /// The last input frames, up to the length of a crossfade: faded into when entering
+    /// the bypass, and put into SoundTouch ahead of the input when leaving it, so that
+    /// its output is running by the time it is faded in.
+    history: Vec<f32>,
+    /// Output frames of the history put when leaving that are still to be discarded.
+    skip: usize,
+    /// The input put when leaving the bypass, faded out against the first output of
+    /// SoundTouch.
+    fade: Vec<f32>,
+    /// Frames of `fade` already crossfaded.
+    faded: usize,
+}
+
+impl Bypass {
+    /// Forgets the stream, for a new one.
+    pub(crate) fn restart(&mut self) {
+        self.active = false;
+        self.history.clear();
+        self.skip = 0;
+        self.fade.clear();
+        self.faded = 0;
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables passing the input straight to the output while the tempo,
+    /// pitch and rate are all neutral, i.e. the effective tempo and rate are exactly
+    /// `1.0`. SoundTouch would otherwise still stretch the audio by a factor of one,
+    /// which costs CPU time and delays the output by the [initial latency]. Disabled
+    /// by default, and [`reset`] disables it.
+    ///
+    /// While bypassed, put input is ready to be received right away and unchanged, bit
+    /// for bit, and [`buffer_requirements`] reports no latency. Switching to a neutral
+    /// configuration flushes SoundTouch's pending output and crossfades its end into the
+    /// input over 5 ms; switching away starts SoundTouch again and crossfades from the
+    /// input into its output over 5 ms, so toggling between `1.0` and other speeds
+    /// doesn't click. Every input frame is counted as put, and the output length stays
+    /// the input length times the [input/output ratio] to within a few frames.
+    ///
+    /// [initial latency]: crate::Setting::InitialLatency
+    /// [`reset`]: SoundTouch::reset
+    /// [`buffer_requirements`]: SoundTouch::buffer_requirements
+    /// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+    pub fn set_bypass_when_neutral(&mut self, bypass: bool) -> &mut Self {
+        self.bypass.enabled = bypass;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns whether the [bypass] is enabled.
+    ///
+    /// [bypass]: SoundTouch::set_bypass_when_neutral
+    pub fn bypass_when_neutral(&self) -> bool {
+        self.bypass.enabled
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns whether the last input was passed around SoundTouch by the [bypass].
+    ///
+    /// [bypass]: SoundTouch::set_bypass_when_neutral
+    pub fn is_bypassed(&self) -> bool {
+        self.bypass.active
+    }
+
+    /// Returns whether input put now would be bypassed.
+    pub(crate) fn bypass_wanted(&self) -> bool {
+        let params = self.effective_parameters();
+        self.bypass.enabled && params.tempo == 1.0 && params.rate == 1.0
+    }
+
+    /// Enters or leaves the bypass as configured, and passes `samples` to the output if
+    /// bypassed. Returns whether it did, otherwise they still have to be put.
+    pub(crate) fn bypass_put(&mut self, samples: &[f32], frames: usize) -> bool {
+        if !self.bypass.enabled && !self.bypass.active {
+            return false;
+        }
+        let channels = self.num_channels() as usize;
+        let samples = &samples[..frames * channels];
+        let crossfade = self.bypass_crossfade_frames();
+        let bypassed = match (self.bypass_wanted(), self.bypass.active) {
+            (true, false) => {
+                self.enter_bypass(crossfade);
+                true
+            }
+            (false, true) => {
+                self.leave_bypass(samples, crossfade);
+                false
+            }
+            (wanted, _) => wanted,
+        };
+        if bypassed {
+            self.converted.extend(samples);
+        }
+
+        let keep = crossfade * channels;
+        let history = &mut self.bypass.history;
+        if samples.len() >= keep {
+            history.clear();
+            history.extend_from_slice(&samples[samples.len() - keep..]);
+        } else {
+            history.extend_from_slice(samples);
+            history.drain(..history.len().saturating_sub(keep));
+        }
+        bypassed
+    }
+
+    /// Moves the output of SoundTouch after leaving the bypass to the output: discards
+    /// that of the history, then crossfades the input into it. Call it whenever
+    /// SoundTouch may have produced output.
+    pub(crate) fn settle_bypass(&mut self) {
+        let channels = self.num_channels() as usize;
+        let fade_frames = self.bypass.fade.len().checked_div(channels).unwrap_or(0);
+        if self.bypass.skip == 0 && self.bypass.faded >= fade_frames {
+            return;
+        }
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        while self.bypass.skip > 0 {
+            let max_frames = self.bypass.skip.min(STACK_BUFFER_SAMPLES / channels);
+            let frames = self.receive_from_pipeline(&mut buffer, max_frames);
+            if frames == 0 {
+                return;
+            }
+            self.bypass.skip -= frames;
+        }
+        while self.bypass.faded < fade_frames {
+            let max_frames = (fade_frames - self.bypass.faded).min(STACK_BUFFER_SAMPLES / channels);
+            let frames = self.receive_from_pipeline(&mut buffer, max_frames);
+            if frames == 0 {
+                return;
+            }
+            let faded = self.bypass.faded;
+            let output = &mut buffer[..frames * channels];
+            let input = &self.bypass.fade[faded * channels..];
+            for (index, (output, input)) in output
+                .chunks_exact_mut(channels)
+                .zip(input.chunks_exact(channels))
+                .enumerate()
+            {
+                let weight = ((faded + index) as f32 + 0.5) / fade_frames as f32;
+                for (sample, &input) in output.iter_mut().zip(input) {
+                    *sample = weight * *sample + (1.0 - weight) * input;
+                }
+            }
+            self.converted.extend(&*output);
+            self.bypass.faded += frames;
+        }
+    }
+
+    fn bypass_crossfade_frames(&self) -> usize {
+        self.frames_for_duration(CROSSFADE_SECONDS)
+            .unwrap_or(0)
+            .max(1)
+    }
+
+    /// Flushes SoundTouch into the output and crossfades the end of it into the history,
+    /// the input it rendered last, which the bypassed input continues.
+    fn enter_bypass(&mut self, crossfade: usize) {
+        self.bypass.active = true;
+        let channels = self.num_channels() as usize;
+        let start = self.converted.len();
+        self.flush_with_mode(FlushMode::Raw);
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        loop {
+            let frames = self.receive_from_pipeline(&mut buffer, STACK_BUFFER_SAMPLES / channels);
+            if frames == 0 {
+                break;
+            }
+            self.converted.extend(&buffer[..frames * channels]);
+        }
+        let padding = self.restart_pipeline();
+        self.converted
+            .resize(self.converted.len() + padding * channels, 0.0);
+        self.bypass.skip = 0;
+        self.bypass.fade.clear();
+        self.bypass.faded = 0;
+
+        let history = &self.bypass.history;
+        let frames = ((self.converted.len() - start) / channels)
+            .min(history.len() / channels)
+            .min(crossfade);
+        let output_start = self.converted.len() - frames * channels;
+        let history_start = history.len() - frames * channels;
+        for index in 0..frames * channels {
+            let weight = ((index / channels) as f32 + 0.5) / frames as f32;
+            let sample = &mut self.converted[output_start + index];
+            *sample = (1.0 - weight) * *sample + weight * history[history_start + index];
+        }
+    }
+
+    /// Puts the history into SoundTouch, whose output of it is discarded, and keeps the
+    /// start of `samples`, the first input that isn't bypassed, to fade out against the
+    /// output that follows.
+    fn leave_bypass(&mut self, samples: &[f32], crossfade: usize) {
+        self.bypass.active = false;
+        let channels = self.num_channels() as usize;
+        let history = core::mem::take(&mut self.bypass.history);
+        let preroll = history.len() / channels;
+        self.put_to_pipeline(&history, preroll);
+        self.bypass.history = history;
+        let ratio = self.get_input_output_sample_ratio();
+        self.bypass.skip = (preroll as f64 * ratio + 0.5) as usize;
+
+        let frames = (samples.len() / channels).min(crossfade);
+        self.bypass.fade.clear();
+        self.bypass
+            .fade
+            .extend_from_slice(&samples[..frames * channels]);
+        self.bypass.faded = 0;
+    }
+}
@@ -0,0 +1,58 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::SoundTouchError;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A flag that aborts the long-running calls it is passed to, shared by all of its
+/// clones. Cancel it from any thread, e.g. from a GUI's cancel button while a worker
+/// runs one stage of processing after another with the same token.
+///
+/// The calls taking a token check it at least once per block of input and then return
+/// [`SoundTouchError::Cancelled`]; each documents the state it leaves its instance in.
+/// A cancelled token stays cancelled, so use a new one for the next job.
+///
+/// ```rust
+/// use soundtouch::{CancellationToken, SoundTouch, SoundTouchError};
+///
+/// let token = CancellationToken::new();
+/// token.clone().cancel();
+///
+/// let mut soundtouch = SoundTouch::new();
+/// soundtouch.set_channels(2).set_sample_rate(44100);
+/// let result = soundtouch.generate_audio_cancellable(&[0.0; 44100 * 2], &token);
+/// assert_eq!(result, Err(SoundTouchError::Cancelled));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Frames processed between two checks of a token.
+    pub const CHECK_FRAMES: usize = 4096;
+
+    /// Creates a token that isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the calls this token and its clones were passed to.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns [`SoundTouchError::Cancelled`] if the token has been cancelled.
+    pub(crate) fn check(&self) -> Result<(), SoundTouchError> {
+        if self.is_cancelled() {
+            return Err(SoundTouchError::Cancelled);
+        }
+        Ok(())
+    }
+}
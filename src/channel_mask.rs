@@ -0,0 +1,197 @@
+use core::ops::{BitAnd, BitOr};
+
+use crate::SoundTouch;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The speakers the channels of a stream are meant for, as in the `dwChannelMask` of a
+/// `WAVE_FORMAT_EXTENSIBLE` WAV header: one bit per speaker position, with the
+/// interleaved channels in the order of their bits from the lowest.
+///
+/// ```rust
+/// use soundtouch::ChannelMask;
+///
+/// let layout = ChannelMask::SURROUND_5_1;
+/// assert_eq!(layout.channels(), 6);
+/// assert_eq!(layout.index_of(ChannelMask::LOW_FREQUENCY), Some(3));
+/// // Channel 3 of the interleaved stream, for `SoundTouch::set_correlation_channel_mask`.
+/// assert_eq!(layout.channel_bits(ChannelMask::LOW_FREQUENCY), 0b1000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ChannelMask(pub u32);
+
+impl ChannelMask {
+    /// Front left.
+    pub const FRONT_LEFT: Self = Self(0x1);
+    /// Front right.
+    pub const FRONT_RIGHT: Self = Self(0x2);
+    /// Front center.
+    pub const FRONT_CENTER: Self = Self(0x4);
+    /// Low frequency effects (LFE).
+    pub const LOW_FREQUENCY: Self = Self(0x8);
+    /// Back left.
+    pub const BACK_LEFT: Self = Self(0x10);
+    /// Back right.
+    pub const BACK_RIGHT: Self = Self(0x20);
+    /// Front left of center.
+    pub const FRONT_LEFT_OF_CENTER: Self = Self(0x40);
+    /// Front right of center.
+    pub const FRONT_RIGHT_OF_CENTER: Self = Self(0x80);
+    /// Back center.
+    pub const BACK_CENTER: Self = Self(0x100);
+    /// Side left.
+    pub const SIDE_LEFT: Self = Self(0x200);
+    /// Side right.
+    pub const SIDE_RIGHT: Self = Self(0x400);
+
+    /// Front left and right.
+    pub const STEREO: Self = Self(0x3);
+    /// Front left, right and center, low frequency, back left and right.
+    pub const SURROUND_5_1: Self = Self(0x3F);
+    /// [`ChannelMask::SURROUND_5_1`] plus side left and right.
+    pub const SURROUND_7_1: Self = Self(0x63F);
+
+    /// Returns the mask that assigns `channels` channels to the lowest speaker positions,
+    /// which `hound` writes to the headers it creates. Channels beyond the 18 defined
+    /// positions aren't assigned to any.
+    pub const fn default_for(channels: u32) -> Self {
+        let assigned = if channels < 18 { channels } else { 18 };
+        Self((1 << assigned) - 1)
+    }
+
+    /// Returns the number of speakers in the mask.
+    pub const fn channels(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Returns whether all speakers of `speakers` are in the mask.
+    pub const fn contains(self, speakers: Self) -> bool {
+        self.0 & speakers.0 == speakers.0
+    }
+
+    /// Returns the index of the interleaved channel of the single speaker `speaker`, or
+    /// `None` if it isn't in the mask or isn't a single speaker.
+    pub const fn index_of(self, speaker: Self) -> Option<usize> {
+        if speaker.0.count_ones() != 1 || !self.contains(speaker) {
+            return None;
+        }
+        Some((self.0 & (speaker.0 - 1)).count_ones() as usize)
+    }
+
+    /// Returns the indexes of the interleaved channels of the speakers of `speakers` that
+    /// are in the mask, as bits from the lowest, for
+    /// [`SoundTouch::set_correlation_channel_mask`].
+    pub const fn channel_bits(self, speakers: Self) -> u32 {
+        let mut bits = 0;
+        let mut index = 0;
+        let mut rest = self.0;
+        while rest != 0 {
+            let speaker = rest & rest.wrapping_neg();
+            if speakers.0 & speaker != 0 {
+                bits |= 1 << index;
+            }
+            rest &= !speaker;
+            index += 1;
+        }
+        bits
+    }
+}
+
+impl BitOr for ChannelMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for ChannelMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl SoundTouch {
+    /// Factor the channels set with [`SoundTouch::set_correlation_channel_mask`] are
+    /// scaled by inside the pipeline. A power of two, so scaling back is exact.
+    const CORRELATION_WEIGHT: f32 = 1.0 / 256.0;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Down-weights the channels whose bits are set in `mask`, bit `i` for the interleaved
+    /// channel `i`, in the time-stretcher's search for the best overlap position, e.g. to
+    /// keep a loud LFE channel from deciding the splices of a surround stream. The channels
+    /// are still processed like the others, with the same splices. `0`, the default,
+    /// weights all channels alike. Bits beyond the number of channels are ignored. Use
+    /// [`ChannelMask::channel_bits`] to find the bits of speakers.
+    ///
+    /// SoundTouch sums the correlation over all channels, so the channels are scaled by
+    /// 1/256 on their way into the pipeline, which leaves them 1/65536 of their share in
+    /// the search, and scaled back on the way out. Both are powers of two, so apart from
+    /// the splices the output is the same as without the mask.
+    ///
+    /// Samples already in the pipeline keep the weighting they were put with, so a new mask
+    /// takes effect at the start of the next stream: right away if nothing has been put
+    /// since the instance was created or [cleared], otherwise after the next [`clear`].
+    ///
+    /// [cleared]: SoundTouch::clear
+    /// [`clear`]: SoundTouch::clear
+    pub fn set_correlation_channel_mask(&mut self, mask: u32) -> &mut Self {
+        self.correlation_mask = mask;
+        if self.stream_frames_in == 0 {
+            self.weighted_channels = mask;
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the mask set with [`set_correlation_channel_mask`].
+    ///
+    /// [`set_correlation_channel_mask`]: SoundTouch::set_correlation_channel_mask
+    pub fn correlation_channel_mask(&self) -> u32 {
+        self.correlation_mask
+    }
+
+    /// Returns the factor samples of `channel` are scaled by inside the pipeline.
+    pub(crate) fn pipeline_weight(&self, channel: usize) -> f32 {
+        if channel < 32 && self.weighted_channels & (1 << channel) != 0 {
+            Self::CORRELATION_WEIGHT
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns whether any channel of the instance is scaled inside the pipeline.
+    pub(crate) fn has_pipeline_weights(&self) -> bool {
+        (0..self.num_channels() as usize).any(|channel| self.pipeline_weight(channel) != 1.0)
+    }
+
+    /// Scales the interleaved `samples` put into the pipeline by the weights of their
+    /// channels.
+    pub(crate) fn weigh(&self, samples: &mut [f32]) {
+        self.scale_channels(samples, |weight| weight);
+    }
+
+    /// Undoes [`SoundTouch::weigh`] on the interleaved `samples` received from the
+    /// pipeline.
+    pub(crate) fn unweigh(&self, samples: &mut [f32]) {
+        self.scale_channels(samples, |weight| 1.0 / weight);
+    }
+
+    fn scale_channels(&self, samples: &mut [f32], factor: impl Fn(f32) -> f32) {
+        let channels = self.num_channels() as usize;
+        for channel in 0..channels {
+            let weight = self.pipeline_weight(channel);
+            if weight == 1.0 {
+                continue;
+            }
+            let factor = factor(weight);
+            for sample in samples.iter_mut().skip(channel).step_by(channels) {
+                *sample *= factor;
+            }
+        }
+    }
+}
@@ -0,0 +1,446 @@
+use core::hash::{Hash, Hasher};
+
+use crate::fingerprint::{fnv1a, FNV_OFFSET_BASIS};
+use crate::{EffectiveParams, Setting, SoundTouch, SoundTouchError};
+
+#[cfg(feature = "alloc")]
+use crate::ConfigParseError;
+
+/// A complete set of processing parameters that can be applied to a [`SoundTouch`]
+/// instance in one go with [`SoundTouch::apply_config`].
+///
+/// The setters mirror the ones on [`SoundTouch`]. The default configuration is
+/// stereo at 44100 Hz with tempo, pitch and rate at `1.0` and every [`Setting`]
+/// at its [default value].
+///
+/// **NOT FROM SOUNDTOUCH**: Configurations can be compared and hashed, e.g. to key a
+/// cache of processed audio, see [`Config::cache_key`]. The tempo, pitch and rate are
+/// always finite, as the setters never store NaN and infinities, and are stored with
+/// negative zero and subnormal values as `0.0`. Two configurations are equal exactly when the bit
+/// patterns of these values and all other fields are, which makes [`Eq`] an equivalence
+/// and [`Hash`] agree with it.
+///
+/// [default value]: Setting::default_value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    channels: u32,
+    sample_rate: u32,
+    tempo: f64,
+    pitch: f64,
+    rate: f64,
+    settings: [i32; Setting::WRITABLE.len()],
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A rough class of how much CPU time a [`Config`] needs, as returned by
+/// [`Config::complexity_hint`].
+///
+/// The class is derived from [`Config::estimated_ops_per_second`], which counts the
+/// multiply-adds of the two expensive stages per second of input:
+/// - The time-stretcher correlates the overlap with every position in the seek window
+///   (about one in eight with [`Setting::UseQuickseek`]) once per sequence, and a
+///   sequence advances the input by `tempo * (sequence - overlap)` frames. Automatic
+///   sequence and seek window lengths are computed from the effective tempo as SoundTouch
+///   does, so they shrink as the tempo deviates upwards from `1.0`.
+/// - When the effective rate isn't `1.0`, the rate transposer interpolates every output
+///   frame and, with [`Setting::UseAaFilter`], runs the anti-alias filter of
+///   [`Setting::AaFilterLength`] taps.
+///
+/// Both scale with the number of channels, and the total with the square of the
+/// sample rate, as the window lengths are in milliseconds. The estimate ignores the
+/// copying and mixing, which are cheap in comparison.
+///
+/// Stereo at 44100 Hz with the default settings is [`Medium`](ComplexityClass::Medium),
+/// mono or quickseek bring it down to [`Low`](ComplexityClass::Low) and 96 kHz takes
+/// it to [`High`](ComplexityClass::High). The `complexity` benchmark measures
+/// configurations of each class, and [`bench::quick_selftest`] measures what the
+/// current machine sustains.
+///
+/// [`bench::quick_selftest`]: crate::bench::quick_selftest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComplexityClass {
+    /// Below [`MEDIUM_OPS_PER_SECOND`](ComplexityClass::MEDIUM_OPS_PER_SECOND).
+    Low,
+    /// Below [`HIGH_OPS_PER_SECOND`](ComplexityClass::HIGH_OPS_PER_SECOND).
+    Medium,
+    /// Everything else.
+    High,
+}
+
+impl ComplexityClass {
+    /// Estimated multiply-adds per second of input from which a configuration is
+    /// [`Medium`](ComplexityClass::Medium).
+    pub const MEDIUM_OPS_PER_SECOND: f64 = 5.0e6;
+
+    /// Estimated multiply-adds per second of input from which a configuration is
+    /// [`High`](ComplexityClass::High).
+    pub const HIGH_OPS_PER_SECOND: f64 = 20.0e6;
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The floats are never NaN and have a single representation of zero, see the setters.
+impl Eq for Config {}
+
+impl Hash for Config {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.channels.hash(state);
+        self.sample_rate.hash(state);
+        self.tempo.to_bits().hash(state);
+        self.pitch.to_bits().hash(state);
+        self.rate.to_bits().hash(state);
+        self.settings.hash(state);
+    }
+}
+
+/// Returns `value` with negative zero and subnormal values as `0.0`, or `None` if it
+/// isn't finite.
+fn canonical(value: f64) -> Option<f64> {
+    if !value.is_finite() {
+        None
+    } else if value == 0.0 || value.is_subnormal() {
+        Some(0.0)
+    } else {
+        Some(value)
+    }
+}
+
+impl Config {
+    /// Creates the default configuration.
+    pub const fn new() -> Self {
+        let mut settings = [0; Setting::WRITABLE.len()];
+        let mut i = 0;
+        while i < settings.len() {
+            settings[i] = Setting::WRITABLE[i].default_value();
+            i += 1;
+        }
+        Self {
+            channels: 2,
+            sample_rate: 44100,
+            tempo: 1.0,
+            pitch: 1.0,
+            rate: 1.0,
+            settings,
+        }
+    }
+
+    /// Set the number of channels.
+    pub fn set_channels(&mut self, num_channels: u32) -> &mut Self {
+        self.channels = num_channels;
+        self
+    }
+
+    /// Set the sample rate.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) -> &mut Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Set the tempo. See [`SoundTouch::set_tempo`].
+    ///
+    /// **Keeps the previous tempo** if `tempo` is NaN or infinite, with a debug
+    /// assertion only, so in release builds a factor computed as NaN silently leaves a
+    /// configuration, and its [cache key](Config::cache_key), standing for another one.
+    /// Use [`try_set_tempo`](Config::try_set_tempo) for computed values.
+    pub fn set_tempo(&mut self, tempo: f64) -> &mut Self {
+        debug_assert!(tempo.is_finite(), "non-finite tempo: {tempo}");
+        if let Some(tempo) = canonical(tempo) {
+            self.tempo = tempo;
+        }
+        self
+    }
+
+    /// Set the pitch. See [`SoundTouch::set_pitch`].
+    ///
+    /// **Keeps the previous pitch** if `pitch` is NaN or infinite, with a debug
+    /// assertion only, so in release builds a factor computed as NaN silently leaves a
+    /// configuration, and its [cache key](Config::cache_key), standing for another one.
+    /// Use [`try_set_pitch`](Config::try_set_pitch) for computed values.
+    pub fn set_pitch(&mut self, pitch: f64) -> &mut Self {
+        debug_assert!(pitch.is_finite(), "non-finite pitch: {pitch}");
+        if let Some(pitch) = canonical(pitch) {
+            self.pitch = pitch;
+        }
+        self
+    }
+
+    /// Set the rate. See [`SoundTouch::set_rate`].
+    ///
+    /// **Keeps the previous rate** if `rate` is NaN or infinite, with a debug
+    /// assertion only, so in release builds a factor computed as NaN silently leaves a
+    /// configuration, and its [cache key](Config::cache_key), standing for another one.
+    /// Use [`try_set_rate`](Config::try_set_rate) for computed values.
+    pub fn set_rate(&mut self, rate: f64) -> &mut Self {
+        debug_assert!(rate.is_finite(), "non-finite rate: {rate}");
+        if let Some(rate) = canonical(rate) {
+            self.rate = rate;
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the tempo, returning an error if the resulting effective tempo or rate would
+    /// be outside [`SoundTouch::MIN_EFFECTIVE_FACTOR`]`..=`[`SoundTouch::MAX_EFFECTIVE_FACTOR`],
+    /// as with [`SoundTouch::try_set_tempo`]. NaN and infinities never are in range.
+    /// Nothing is changed in that case.
+    pub fn try_set_tempo(&mut self, tempo: f64) -> Result<&mut Self, SoundTouchError> {
+        EffectiveParams::new(tempo, self.rate, self.pitch).check()?;
+        Ok(self.set_tempo(tempo))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the pitch, returning an error as [`Config::try_set_tempo`] does.
+    pub fn try_set_pitch(&mut self, pitch: f64) -> Result<&mut Self, SoundTouchError> {
+        EffectiveParams::new(self.tempo, self.rate, pitch).check()?;
+        Ok(self.set_pitch(pitch))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the rate, returning an error as [`Config::try_set_tempo`] does.
+    pub fn try_set_rate(&mut self, rate: f64) -> Result<&mut Self, SoundTouchError> {
+        EffectiveParams::new(self.tempo, rate, self.pitch).check()?;
+        Ok(self.set_rate(rate))
+    }
+
+    /// Changes a setting. Read-only settings are ignored, as with
+    /// [`SoundTouch::set_setting`].
+    pub fn set_setting(&mut self, setting: Setting, value: i32) -> &mut Self {
+        if let Some(i) = Setting::writable_index(setting) {
+            self.settings[i] = value;
+        }
+        self
+    }
+
+    /// Returns the number of channels.
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Returns the sample rate.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the tempo.
+    pub fn tempo(&self) -> f64 {
+        self.tempo
+    }
+
+    /// Returns the pitch.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Returns the rate.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Returns an error if the number of channels or the sample rate is out of the
+    /// range accepted by [`SoundTouch::try_set_channels`] and
+    /// [`SoundTouch::try_set_sample_rate`].
+    pub fn validate(&self) -> Result<(), SoundTouchError> {
+        SoundTouch::validate_channels(self.channels)?;
+        SoundTouch::validate_sample_rate(self.sample_rate)
+    }
+
+    /// Returns the value of a setting, or `0` for read-only settings.
+    pub fn setting(&self, setting: Setting) -> i32 {
+        Setting::writable_index(setting).map_or(0, |i| self.settings[i])
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns a 64-bit key of the configuration for caching processed audio, the same
+    /// for equal configurations however they were built.
+    ///
+    /// Unlike [`Hash`], whose values depend on the hasher, the key is stable: it is the
+    /// 64-bit FNV-1a hash of the little-endian bytes of the number of channels, the
+    /// sample rate, the bit patterns of the tempo, pitch and rate and the writable
+    /// settings in the order of the [`Setting`] variants, so it is the same on every
+    /// target and in every run. It only changes in a release that changes what a configuration
+    /// holds, which the changelog says. Different configurations can collide, if rarely,
+    /// so store the configuration with the cached audio when a collision matters.
+    ///
+    /// The key is only that of the intended configuration if every factor was stored.
+    /// Set computed tempos, pitches and rates with [`try_set_tempo`], [`try_set_pitch`]
+    /// and [`try_set_rate`], which return an error for NaN and infinities, as the
+    /// infallible setters keep the previous value instead.
+    ///
+    /// ```rust
+    /// use soundtouch::Config;
+    ///
+    /// let a = Config::new().try_set_tempo(1.25)?.try_set_pitch(0.9)?.clone();
+    /// let b = Config::new().try_set_pitch(0.9)?.try_set_tempo(1.25)?.clone();
+    /// assert_eq!(a, b);
+    /// assert_eq!(a.cache_key(), b.cache_key());
+    /// assert_ne!(a.cache_key(), Config::new().cache_key());
+    ///
+    /// // A factor computed as NaN is an error rather than a key of another configuration.
+    /// assert!(Config::new().try_set_tempo(f64::NAN).is_err());
+    /// # Ok::<(), soundtouch::SoundTouchError>(())
+    /// ```
+    ///
+    /// [`try_set_tempo`]: Config::try_set_tempo
+    /// [`try_set_pitch`]: Config::try_set_pitch
+    /// [`try_set_rate`]: Config::try_set_rate
+    pub fn cache_key(&self) -> u64 {
+        let mut key = FNV_OFFSET_BASIS;
+        for value in [self.channels, self.sample_rate] {
+            key = fnv1a(key, &value.to_le_bytes());
+        }
+        for value in [self.tempo, self.pitch, self.rate] {
+            key = fnv1a(key, &value.to_bits().to_le_bytes());
+        }
+        for value in self.settings {
+            key = fnv1a(key, &value.to_le_bytes());
+        }
+        key
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Estimates how many multiply-adds SoundTouch spends per second of input with this
+    /// configuration. See [`ComplexityClass`] for the model.
+    pub fn estimated_ops_per_second(&self) -> f64 {
+        let params = EffectiveParams::new(self.tempo, self.rate, self.pitch);
+        let channels = self.channels as f64;
+        let frames = |ms: f64| self.sample_rate as f64 * ms / 1000.0;
+        // The automatic lengths of TDStretch::calcSeqParameters.
+        let auto = (params.tempo.clamp(0.5, 2.0) - 0.5) / 1.5;
+        let ms = |setting, at_slowest: f64, at_fastest: f64| match self.setting(setting) {
+            0 => at_slowest + (at_fastest - at_slowest) * auto,
+            ms => ms as f64,
+        };
+
+        let overlap = frames(self.setting(Setting::OverlapMs) as f64).max(16.0);
+        let sequence = frames(ms(Setting::SequenceMs, 90.0, 40.0)).max(2.0 * overlap);
+        let seek_window = frames(ms(Setting::SeekwindowMs, 20.0, 15.0));
+        let positions = if self.setting(Setting::UseQuickseek) != 0 {
+            seek_window / 8.0
+        } else {
+            seek_window
+        };
+        // A higher rate is transposed first and leaves fewer frames to stretch.
+        let stretched = 1.0 / params.rate.max(1.0);
+        let stretch =
+            positions * overlap * channels * stretched / (params.tempo * (sequence - overlap));
+
+        let transpose = if params.rate == 1.0 {
+            0.0
+        } else {
+            let filter = match self.setting(Setting::UseAaFilter) {
+                0 => 0.0,
+                _ => self.setting(Setting::AaFilterLength) as f64,
+            };
+            // A lower rate is filtered after transposing, at the higher output length.
+            (filter * (1.0 / params.rate).max(1.0) + 4.0 / params.rate) * channels
+        };
+
+        (stretch + transpose) * self.sample_rate as f64
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns a rough class of how much CPU time this configuration needs, for picking
+    /// settings by device without running a benchmark first. See [`ComplexityClass`]
+    /// for how it is derived.
+    pub fn complexity_hint(&self) -> ComplexityClass {
+        let ops = self.estimated_ops_per_second();
+        if ops < ComplexityClass::MEDIUM_OPS_PER_SECOND {
+            ComplexityClass::Low
+        } else if ops < ComplexityClass::HIGH_OPS_PER_SECOND {
+            ComplexityClass::Medium
+        } else {
+            ComplexityClass::High
+        }
+    }
+
+    /// Parses a configuration from key-value pairs, such as command line arguments,
+    /// starting from the default configuration.
+    ///
+    /// The keys are `channels`, `sample-rate`, `tempo`, `pitch`, `rate` and the
+    /// [names] of the writable settings, all ignoring case. Later pairs override earlier
+    /// ones. The values aren't [validated], but NaN and infinite factors are invalid.
+    ///
+    /// ```rust
+    /// use soundtouch::{Config, Setting};
+    ///
+    /// let config = Config::from_kv_pairs(&[("tempo", "1.4"), ("use-quickseek", "1")])?;
+    /// assert_eq!(config.tempo(), 1.4);
+    /// assert_eq!(config.setting(Setting::UseQuickseek), 1);
+    /// # Ok::<(), soundtouch::ConfigParseError>(())
+    /// ```
+    ///
+    /// [names]: Setting::name
+    /// [validated]: Config::validate
+    #[cfg(feature = "alloc")]
+    pub fn from_kv_pairs(pairs: &[(&str, &str)]) -> Result<Config, ConfigParseError> {
+        let mut config = Config::new();
+        for &(key, value) in pairs {
+            let invalid = || ConfigParseError::InvalidValue {
+                key: key.into(),
+                value: value.into(),
+            };
+            let value = value.trim();
+            let factor = || {
+                let factor: f64 = value.parse().map_err(|_| invalid())?;
+                factor.is_finite().then_some(factor).ok_or_else(invalid)
+            };
+            let key_is = |name: &str| key.eq_ignore_ascii_case(name);
+            if key_is("channels") {
+                config.set_channels(value.parse().map_err(|_| invalid())?);
+            } else if key_is("sample-rate") {
+                config.set_sample_rate(value.parse().map_err(|_| invalid())?);
+            } else if key_is("tempo") {
+                config.set_tempo(factor()?);
+            } else if key_is("pitch") {
+                config.set_pitch(factor()?);
+            } else if key_is("rate") {
+                config.set_rate(factor()?);
+            } else {
+                let setting = Setting::from_name(key)
+                    .ok_or_else(|| ConfigParseError::UnknownKey(key.into()))?;
+                if setting.is_read_only() {
+                    return Err(ConfigParseError::ReadOnlySetting(setting));
+                }
+                config.set_setting(setting, value.parse().map_err(|_| invalid())?);
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Creates a new SoundTouch instance with the given configuration.
+    pub fn with_config(config: &Config) -> Self {
+        let mut soundtouch = Self::new();
+        soundtouch.apply_config(config);
+        soundtouch
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Applies every parameter and setting in `config`.
+    pub fn apply_config(&mut self, config: &Config) -> &mut Self {
+        self.set_channels(config.channels)
+            .set_sample_rate(config.sample_rate)
+            .set_tempo(config.tempo)
+            .set_pitch(config.pitch)
+            .set_rate(config.rate);
+        for (setting, value) in Setting::WRITABLE.into_iter().zip(config.settings) {
+            self.set_setting(setting, value);
+        }
+        self
+    }
+}
@@ -0,0 +1,162 @@
+use core::hint::spin_loop;
+use core::ops::{BitAnd, BitOr, Not};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use ffi::uint;
+use soundtouch_ffi as ffi;
+
+extern "C" {
+    #[cfg_attr(
+        all(not(target_env = "msvc"), not(target_vendor = "apple")),
+        link_name = "\u{1}_Z17disableExtensionsj"
+    )]
+    #[cfg_attr(target_vendor = "apple", link_name = "\u{1}__Z17disableExtensionsj")]
+    #[cfg_attr(target_env = "msvc", link_name = "\u{1}?disableExtensions@@YAXI@Z")]
+    fn disableExtensions(mask: uint);
+
+    #[cfg_attr(
+        all(not(target_env = "msvc"), not(target_vendor = "apple")),
+        link_name = "\u{1}_Z19detectCPUextensionsv"
+    )]
+    #[cfg_attr(target_vendor = "apple", link_name = "\u{1}__Z19detectCPUextensionsv")]
+    #[cfg_attr(target_env = "msvc", link_name = "\u{1}?detectCPUextensions@@YAIXZ")]
+    fn detectCPUextensions() -> uint;
+}
+
+/// Mirror of the mask last passed to `disableExtensions`, which SoundTouch
+/// doesn't allow reading back.
+static DISABLED: AtomicU32 = AtomicU32::new(0);
+
+/// Guards the unsynchronized global mask in `cpu_detect_x86.cpp`, which is written
+/// by `disableExtensions` and read by `detectCPUextensions` whenever SoundTouch
+/// constructs its tempo changer and anti-alias filter.
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        LOCK.store(false, Ordering::Release);
+    }
+}
+
+fn lock() -> Guard {
+    while LOCK
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        spin_loop();
+    }
+    Guard
+}
+
+fn set_disabled(mask: Extensions) {
+    DISABLED.store(mask.0, Ordering::Relaxed);
+    // SAFETY: Writes the global mask, which callers only do while holding the lock.
+    unsafe { disableExtensions(mask.0) }
+}
+
+/// A set of CPU instruction set extensions that SoundTouch can use.
+///
+/// Mirrors the `SUPPORT_...` defines in `cpu_detect.h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Extensions(u32);
+
+impl Extensions {
+    /// No extensions.
+    pub const NONE: Self = Self(0);
+    /// MMX (only used with integer samples, so never by this crate).
+    pub const MMX: Self = Self(0x0001);
+    /// AMD 3DNow!
+    pub const THREE_DNOW: Self = Self(0x0002);
+    /// PowerPC AltiVec.
+    pub const ALTIVEC: Self = Self(0x0004);
+    /// SSE, used by the tempo changer and the anti-alias filter.
+    pub const SSE: Self = Self(0x0008);
+    /// SSE2.
+    pub const SSE2: Self = Self(0x0010);
+    /// Every extension SoundTouch knows about.
+    pub const ALL: Self = Self(0x001f);
+
+    /// Returns the raw `SUPPORT_...` bitmask.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if all extensions in `other` are also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no extensions are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Extensions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Extensions {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for Extensions {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+/// Returns the extensions that SoundTouch will use for newly created instances,
+/// i.e. the ones supported by the CPU (and the build) minus the disabled ones.
+pub fn detect_extensions() -> Extensions {
+    let _guard = lock();
+    // SAFETY: Reads the global mask, which the lock keeps from changing meanwhile.
+    Extensions(unsafe { detectCPUextensions() }) & Extensions::ALL
+}
+
+/// Returns the extensions currently disabled with [`disable_extensions`].
+pub fn disabled_extensions() -> Extensions {
+    Extensions(DISABLED.load(Ordering::Relaxed))
+}
+
+/// Disables the given set of extensions, re-enabling all others.
+///
+/// SoundTouch picks its processing routines when an instance is constructed,
+/// so this only affects [`SoundTouch`] instances created afterwards. The setting
+/// is global to the process.
+///
+/// [`SoundTouch`]: crate::SoundTouch
+pub fn disable_extensions(mask: Extensions) {
+    let _guard = lock();
+    set_disabled(mask);
+}
+
+/// Runs `f`, which constructs SoundTouch objects, while no other thread can
+/// change the extension mask.
+pub(crate) fn with_extensions_locked<T>(f: impl FnOnce() -> T) -> T {
+    let _guard = lock();
+    f()
+}
+
+/// Like [`with_extensions_locked`], but with the given extensions disabled for
+/// the duration of `f`.
+pub(crate) fn with_extensions_disabled<T>(mask: Extensions, f: impl FnOnce() -> T) -> T {
+    let _guard = lock();
+    let previous = disabled_extensions();
+    set_disabled(mask);
+    let ret = f();
+    set_disabled(previous);
+    ret
+}
@@ -0,0 +1,217 @@
+use crate::error::OrPanic;
+use crate::sound_touch::STACK_BUFFER_SAMPLES;
+use crate::{OutputSink, Sanitize, SoundTouch, SoundTouchError};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`put_samples`], but for `f64` samples, which are converted in chunks on the
+    /// stack without allocating.
+    ///
+    /// SoundTouch is built with `f32` samples, so this doesn't process in double
+    /// precision: every sample is rounded to the nearest `f32`, which keeps a 24-bit
+    /// mantissa, and the output of the `_f64` methods is the `f32` output widened
+    /// exactly. The result is bit for bit what the `f32` methods give for the rounded
+    /// input, so this only saves the conversion buffers. Samples beyond the range of
+    /// `f32` round to infinity and are handled by the [input sanitization].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set, or if
+    /// `samples` holds fewer than `num_samples` frames.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [input sanitization]: SoundTouch::set_input_sanitization
+    pub fn put_samples_f64(&mut self, samples: &[f64], num_samples: usize) {
+        self.discard_flushed(num_samples);
+        self.try_put_samples_f64(samples, num_samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`put_samples_f64`], but returns an error instead of panicking.
+    ///
+    /// [`put_samples_f64`]: SoundTouch::put_samples_f64
+    pub fn try_put_samples_f64(
+        &mut self,
+        samples: &[f64],
+        num_samples: usize,
+    ) -> Result<(), SoundTouchError> {
+        let samples = self.prepare_f64(samples, Some(num_samples))?;
+        for chunk in samples.chunks(self.f64_chunk_len()) {
+            self.put_narrowed(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`receive_samples`], but widens the output to `f64`. See
+    /// [`put_samples_f64`] for the precision.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` has room for fewer than `max_samples` frames, or doesn't hold
+    /// a whole number of frames.
+    ///
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [`put_samples_f64`]: SoundTouch::put_samples_f64
+    pub fn receive_samples_f64(&mut self, samples: &mut [f64], max_samples: usize) -> usize {
+        self.try_receive_samples_f64(samples, max_samples)
+            .or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`receive_samples_f64`], but returns an error instead of panicking.
+    ///
+    /// [`receive_samples_f64`]: SoundTouch::receive_samples_f64
+    pub fn try_receive_samples_f64(
+        &mut self,
+        samples: &mut [f64],
+        max_samples: usize,
+    ) -> Result<usize, SoundTouchError> {
+        let channels = self.num_channels() as usize;
+        if max_samples.saturating_mul(channels) > samples.len() {
+            return Err(SoundTouchError::OutputTooSmall {
+                frames: max_samples,
+                channels,
+                len: samples.len(),
+            });
+        }
+        if channels == 0 {
+            return Ok(0);
+        }
+        if max_samples > 0 && samples.len() % channels != 0 {
+            return Err(SoundTouchError::MisalignedOutput {
+                len: samples.len(),
+                channels,
+            });
+        }
+        let mut sink = WidenSlice {
+            slice: &mut samples[..max_samples * channels],
+            len: 0,
+        };
+        Ok(self.receive_ready(&mut sink))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio`], but for `f64` samples. The input is converted and put in
+    /// chunks on the stack, so the only allocation is the returned vector. See
+    /// [`put_samples_f64`] for the precision.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set, or if the
+    /// pipeline isn't empty.
+    ///
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    /// [`put_samples_f64`]: SoundTouch::put_samples_f64
+    #[cfg(feature = "alloc")]
+    pub fn generate_audio_f64(&mut self, samples: &[f64]) -> Vec<f64> {
+        self.try_generate_audio_f64(samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio_f64`], but returns an error instead of panicking.
+    ///
+    /// [`generate_audio_f64`]: SoundTouch::generate_audio_f64
+    #[cfg(feature = "alloc")]
+    pub fn try_generate_audio_f64(&mut self, samples: &[f64]) -> Result<Vec<f64>, SoundTouchError> {
+        self.check_pipeline_empty()?;
+        let samples = self.prepare_f64(samples, None)?;
+        let mut out = Vec::new();
+        for chunk in samples.chunks(self.f64_chunk_len()) {
+            self.put_narrowed(chunk)?;
+            self.receive_ready(&mut WidenVec(&mut out));
+        }
+        self.finish(&mut WidenVec(&mut out));
+        Ok(out)
+    }
+
+    /// Does the checks of [`try_put_samples`] up front, so that nothing is put if any
+    /// chunk would fail, and returns the `num_samples` frames to put, or all whole frames
+    /// if it is `None`.
+    ///
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    fn prepare_f64<'a>(
+        &mut self,
+        samples: &'a [f64],
+        num_samples: Option<usize>,
+    ) -> Result<&'a [f64], SoundTouchError> {
+        self.begin_put(num_samples.unwrap_or(samples.len()))?;
+        let channels = self.num_channels() as usize;
+        let num_samples = num_samples.unwrap_or(samples.len() / channels);
+        if num_samples.saturating_mul(channels) > samples.len() {
+            return Err(SoundTouchError::InputTooShort {
+                frames: num_samples,
+                channels,
+                len: samples.len(),
+            });
+        }
+        let samples = &samples[..num_samples * channels];
+        if self.input_sanitization() == Sanitize::Error {
+            if let Some(index) = samples.iter().position(|&s| !(s as f32).is_finite()) {
+                return Err(SoundTouchError::NonFiniteInput { index });
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Returns the length of the chunks that `f64` input is converted in, a whole number
+    /// of frames.
+    fn f64_chunk_len(&self) -> usize {
+        let channels = self.num_channels() as usize;
+        STACK_BUFFER_SAMPLES / channels * channels
+    }
+
+    /// Rounds `chunk` to `f32` and puts it.
+    fn put_narrowed(&mut self, chunk: &[f64]) -> Result<(), SoundTouchError> {
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        for (narrowed, &sample) in buffer.iter_mut().zip(chunk) {
+            *narrowed = sample as f32;
+        }
+        let channels = self.num_channels() as usize;
+        self.try_put_samples(&buffer, chunk.len() / channels)
+    }
+}
+
+/// An [`OutputSink`] widening the output into a slice of `f64`.
+struct WidenSlice<'a> {
+    slice: &'a mut [f64],
+    len: usize,
+}
+
+impl OutputSink for WidenSlice<'_> {
+    fn remaining(&self) -> Option<usize> {
+        Some(self.slice.len() - self.len)
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        for (out, &sample) in self.slice[self.len..].iter_mut().zip(samples) {
+            *out = f64::from(sample);
+        }
+        self.len += samples.len();
+    }
+}
+
+/// An [`OutputSink`] widening the output into a vector of `f64`.
+#[cfg(feature = "alloc")]
+struct WidenVec<'a>(&'a mut Vec<f64>);
+
+#[cfg(feature = "alloc")]
+impl OutputSink for WidenVec<'_> {
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        self.0
+            .extend(samples.iter().map(|&sample| f64::from(sample)));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{SoundTouch, StreamState};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How far the output of a [`SoundTouch`] instance is from the length its input asks
+/// for, as returned by [`SoundTouch::drift`]. The counts cover everything since the
+/// instance was created or [reset], across [flushes] and [clears].
+///
+/// While a stream runs, the output lags behind by the latency of the pipeline, which
+/// [`pending_output_frames`] estimates. Each stream that ends adds its difference to the
+/// drift for good: that of a [flushed] stream whose output was received is the padding
+/// or trimming of the flush, that of a [cleared] stream the output of the input it
+/// discarded. [`residual_frames`] is what remains, the drift accumulated within the
+/// streams, which should stay within a few sequences of the time-stretcher however long
+/// the instance runs.
+///
+/// [reset]: SoundTouch::reset
+/// [flushes]: SoundTouch::flush
+/// [flushed]: SoundTouch::flush
+/// [clears]: SoundTouch::clear
+/// [cleared]: SoundTouch::clear
+/// [`pending_output_frames`]: DriftReport::pending_output_frames
+/// [`residual_frames`]: DriftReport::residual_frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DriftReport {
+    /// The output the input put asks for: the sum of the frames put with each
+    /// [input/output ratio] times that ratio, rounded.
+    ///
+    /// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+    pub expected_output_frames: u64,
+    /// The frames received, skipped or discarded with [`clear_output_only`].
+    ///
+    /// [`clear_output_only`]: SoundTouch::clear_output_only
+    pub actual_output_frames: u64,
+    /// `actual_output_frames - expected_output_frames`, negative while the output lags.
+    pub drift_frames: i64,
+    /// The output the pipeline still holds or owes for the input put: the ready frames
+    /// plus the input SoundTouch hasn't processed yet times the ratio it was put with.
+    pub pending_output_frames: u64,
+    /// The part of `drift_frames` left by streams that ended with all of their flushed
+    /// output received.
+    pub flush_drift_frames: i64,
+    /// The part of `drift_frames` left by streams that were cleared, including flushed
+    /// streams whose output wasn't all received.
+    pub clear_drift_frames: i64,
+}
+
+impl DriftReport {
+    /// Returns the drift that neither the pending output nor the ends of streams
+    /// explain, i.e. what the output has gained on the input within the streams.
+    pub fn residual_frames(&self) -> i64 {
+        self.drift_frames + self.pending_output_frames as i64
+            - self.flush_drift_frames
+            - self.clear_drift_frames
+    }
+}
+
+/// The counts behind [`SoundTouch::drift`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DriftTracker {
+    /// Expected output of the streams that ended.
+    ended_expected: f64,
+    /// Output of the streams that ended.
+    ended_output: u64,
+    /// Expected output of the segments of the current stream before the current one.
+    stream_expected: f64,
+    /// Input frames of the current segment, all put with the same ratio.
+    segment_frames: f64,
+    /// The input/output ratio and the effective rate of the current segment.
+    ratio: f64,
+    rate: f64,
+    flush_drift: f64,
+    clear_drift: f64,
+}
+
+impl DriftTracker {
+    /// Returns the expected output of the current stream.
+    fn stream_expected(&self) -> f64 {
+        self.stream_expected + self.segment_frames * self.ratio
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns how far the output has drifted from the input length times the
+    /// [input/output ratio], e.g. to check that an instance running for days keeps up
+    /// with the wall clock. See [`DriftReport`].
+    ///
+    /// The input is counted in segments of constant ratio: when the tempo, rate or pitch
+    /// changed since the last put, a new segment starts, taking along the input
+    /// SoundTouch hasn't processed yet, as that is processed with the new ratio.
+    ///
+    /// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+    pub fn drift(&self) -> DriftReport {
+        let tracker = &self.drift;
+        let expected = (tracker.ended_expected + tracker.stream_expected() + 0.5) as u64;
+        let actual = tracker.ended_output + self.stream_frames_out;
+        let pending = self.unprocessed_input() * tracker.ratio + self.ready_frames() as f64;
+        DriftReport {
+            expected_output_frames: expected,
+            actual_output_frames: actual,
+            drift_frames: actual as i64 - expected as i64,
+            pending_output_frames: (pending + 0.5) as u64,
+            flush_drift_frames: round(tracker.flush_drift),
+            clear_drift_frames: round(tracker.clear_drift),
+        }
+    }
+
+    /// Counts `frames` frames about to be put into the pipeline.
+    pub(crate) fn record_drift_put(&mut self, frames: usize) {
+        let params = self.effective_parameters();
+        let ratio = params.input_output_ratio();
+        if ratio != self.drift.ratio {
+            let moved = self.unprocessed_input().min(self.drift.segment_frames);
+            let tracker = &mut self.drift;
+            tracker.stream_expected += (tracker.segment_frames - moved) * tracker.ratio;
+            tracker.segment_frames = moved;
+            tracker.ratio = ratio;
+            tracker.rate = params.rate;
+        }
+        self.drift.segment_frames += frames as f64;
+    }
+
+    /// Adds the counts of the current stream to those of the ended streams, before the
+    /// stream is cleared.
+    pub(crate) fn end_drift_stream(&mut self) {
+        let flushed = self.state == StreamState::Flushed && self.ready_frames() == 0;
+        let output = self.stream_frames_out;
+        let tracker = &mut self.drift;
+        let expected = tracker.stream_expected();
+        let drift = output as f64 - expected;
+        if flushed {
+            tracker.flush_drift += drift;
+        } else {
+            tracker.clear_drift += drift;
+        }
+        tracker.ended_expected += expected;
+        tracker.ended_output += output;
+        tracker.stream_expected = 0.0;
+        tracker.segment_frames = 0.0;
+    }
+
+    /// Returns the input frames put into SoundTouch that it hasn't processed yet. With a
+    /// rate of at most `1.0` the rate transposer runs first, so SoundTouch counts them
+    /// after transposing.
+    fn unprocessed_input(&self) -> f64 {
+        let unprocessed = (self.num_unprocessed_samples() - self.staged_frames()) as f64;
+        if self.drift.rate <= 1.0 {
+            unprocessed * self.drift.rate
+        } else {
+            unprocessed
+        }
+    }
+}
+
+fn round(frames: f64) -> i64 {
+    if frames < 0.0 {
+        (frames - 0.5) as i64
+    } else {
+        (frames + 0.5) as i64
+    }
+}
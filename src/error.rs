@@ -0,0 +1,454 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::{Preset, Setting};
+
+/// Errors returned by the fallible methods of [`SoundTouch`].
+///
+/// [`SoundTouch`]: crate::SoundTouch
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SoundTouchError {
+    /// The number of channels is zero or above [`SoundTouch::MAX_CHANNELS`].
+    ///
+    /// [`SoundTouch::MAX_CHANNELS`]: crate::SoundTouch::MAX_CHANNELS
+    InvalidChannels(u32),
+    /// The sample rate is zero or above [`SoundTouch::MAX_SAMPLE_RATE`], or below
+    /// [`BPMDetect::MIN_SAMPLE_RATE`] for BPM detection.
+    ///
+    /// [`SoundTouch::MAX_SAMPLE_RATE`]: crate::SoundTouch::MAX_SAMPLE_RATE
+    /// [`BPMDetect::MIN_SAMPLE_RATE`]: crate::BPMDetect::MIN_SAMPLE_RATE
+    InvalidSampleRate(u32),
+    /// The value isn't valid for the setting, e.g. an [`AaFilterLength`] that isn't a
+    /// positive multiple of 8.
+    ///
+    /// [`AaFilterLength`]: crate::Setting::AaFilterLength
+    InvalidSetting {
+        /// The setting that was changed.
+        setting: Setting,
+        /// The rejected value.
+        value: i32,
+    },
+    /// The number of channels or the sample rate hasn't been set.
+    NotConfigured,
+    /// Something set up for one number of channels was used with another.
+    ChannelMismatch {
+        /// The number of channels of the instance.
+        expected: u32,
+        /// The number of channels that was used.
+        actual: u32,
+    },
+    /// The input slice holds fewer samples than the requested number of frames.
+    InputTooShort {
+        /// The requested number of frames.
+        frames: usize,
+        /// The number of channels.
+        channels: usize,
+        /// The length of the slice in samples.
+        len: usize,
+    },
+    /// The output slice has room for fewer samples than the requested number of frames.
+    OutputTooSmall {
+        /// The requested number of frames.
+        frames: usize,
+        /// The number of channels.
+        channels: usize,
+        /// The length of the slice in samples.
+        len: usize,
+    },
+    /// The effective tempo or rate is outside
+    /// [`SoundTouch::MIN_EFFECTIVE_FACTOR`]`..=`[`SoundTouch::MAX_EFFECTIVE_FACTOR`].
+    ///
+    /// [`SoundTouch::MIN_EFFECTIVE_FACTOR`]: crate::SoundTouch::MIN_EFFECTIVE_FACTOR
+    /// [`SoundTouch::MAX_EFFECTIVE_FACTOR`]: crate::SoundTouch::MAX_EFFECTIVE_FACTOR
+    ParametersOutOfRange {
+        /// The effective tempo that would have resulted.
+        tempo: f64,
+        /// The effective rate that would have resulted.
+        rate: f64,
+    },
+    /// Input was put with a `try_` method after a [`flush`] while flushed output was
+    /// still waiting to be received. Receive it or [`clear`] the instance first. The
+    /// infallible methods such as [`put_samples`] discard that output instead.
+    ///
+    /// [`flush`]: crate::SoundTouch::flush
+    /// [`clear`]: crate::SoundTouch::clear
+    /// [`put_samples`]: crate::SoundTouch::put_samples
+    AlreadyFlushed {
+        /// The number of frames still ready.
+        ready_frames: usize,
+    },
+    /// The input contains a NaN or infinite sample, and [`Sanitize::Error`] is set.
+    ///
+    /// [`Sanitize::Error`]: crate::Sanitize::Error
+    NonFiniteInput {
+        /// The index of the first such sample in the input slice.
+        index: usize,
+    },
+    /// [`generate_audio`] was called while the pipeline held samples of an earlier
+    /// stream, which would have been mixed into the output. [`clear`] the instance or use
+    /// [`generate_audio_appending`].
+    ///
+    /// [`generate_audio`]: crate::SoundTouch::generate_audio
+    /// [`clear`]: crate::SoundTouch::clear
+    /// [`generate_audio_appending`]: crate::SoundTouch::generate_audio_appending
+    PipelineNotEmpty {
+        /// The number of input frames waiting to be processed.
+        pending_frames: usize,
+        /// The number of processed frames ready to be received.
+        ready_frames: usize,
+    },
+    /// The input ended in the middle of a frame.
+    PartialFrame {
+        /// The number of samples of the incomplete frame.
+        trailing_samples: usize,
+        /// The number of channels.
+        channels: usize,
+    },
+    /// Input was put while more output was waiting to be received than the cap set with
+    /// [`set_max_pending_output_frames`]. Receive some of it first.
+    ///
+    /// [`set_max_pending_output_frames`]: crate::SoundTouch::set_max_pending_output_frames
+    Backpressure {
+        /// The number of frames waiting to be received.
+        ready_frames: usize,
+        /// The cap.
+        max_frames: usize,
+    },
+    /// A low-level step of [`BPMDetect`] was asked to process more samples than it has
+    /// buffered or can handle in one call.
+    ///
+    /// [`BPMDetect`]: crate::BPMDetect
+    TooManySamples {
+        /// The requested number of samples.
+        requested: usize,
+        /// The largest number that can be processed.
+        max: usize,
+    },
+    /// The overlap of [`TuningParams`] isn't shorter than the sequence.
+    ///
+    /// [`TuningParams`]: crate::TuningParams
+    OverlapTooLong {
+        /// The overlap in milliseconds.
+        overlap_ms: u32,
+        /// The sequence length in milliseconds, or `None` if it is automatic.
+        sequence_ms: Option<u32>,
+    },
+    /// The [`CancellationToken`] passed to a long-running call was cancelled.
+    ///
+    /// [`CancellationToken`]: crate::CancellationToken
+    Cancelled,
+    /// The input of [`fill_exact`] ended before the output was filled.
+    ///
+    /// [`fill_exact`]: crate::SoundTouch::fill_exact
+    StreamEnded {
+        /// The number of frames written.
+        written_frames: usize,
+    },
+    /// The output slice doesn't hold a whole number of frames. Received frames would
+    /// leave its end unwritten, so a caller treating it as all output would shift the
+    /// channels. Use [`receive_frames`] to receive into the whole frames of any slice.
+    ///
+    /// [`receive_frames`]: crate::SoundTouch::receive_frames
+    MisalignedOutput {
+        /// The length of the slice in samples.
+        len: usize,
+        /// The number of channels.
+        channels: usize,
+    },
+}
+
+impl fmt::Display for SoundTouchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChannels(channels) => write!(
+                f,
+                "invalid number of channels {channels}, expected 1..={}",
+                crate::SoundTouch::MAX_CHANNELS
+            ),
+            Self::InvalidSampleRate(sample_rate) => write!(f, "invalid sample rate {sample_rate}"),
+            Self::InvalidSetting { setting, value } => {
+                write!(f, "invalid value {value} for setting {setting:?}")
+            }
+            Self::NotConfigured => f.write_str(
+                "the number of channels and the sample rate must be set before processing",
+            ),
+            Self::ChannelMismatch { expected, actual } => {
+                write!(f, "expected {expected} channels, but got {actual}")
+            }
+            Self::InputTooShort {
+                frames,
+                channels,
+                len,
+            } => write!(
+                f,
+                "{frames} frames of {channels} channels requested, but only {len} samples given"
+            ),
+            Self::OutputTooSmall {
+                frames,
+                channels,
+                len,
+            } => write!(
+                f,
+                "{frames} frames of {channels} channels requested, but only room for {len} samples"
+            ),
+            Self::ParametersOutOfRange { tempo, rate } => write!(
+                f,
+                "effective tempo {tempo} and rate {rate} must both be within {}..={}",
+                crate::SoundTouch::MIN_EFFECTIVE_FACTOR,
+                crate::SoundTouch::MAX_EFFECTIVE_FACTOR
+            ),
+            Self::AlreadyFlushed { ready_frames } => write!(
+                f,
+                "input put after a flush while {ready_frames} flushed frames weren't received yet"
+            ),
+            Self::NonFiniteInput { index } => {
+                write!(f, "input sample {index} is NaN or infinite")
+            }
+            Self::PipelineNotEmpty {
+                pending_frames,
+                ready_frames,
+            } => write!(
+                f,
+                "the pipeline already holds {pending_frames} unprocessed and {ready_frames} \
+                 ready frames"
+            ),
+            Self::PartialFrame {
+                trailing_samples,
+                channels,
+            } => write!(
+                f,
+                "input ended with {trailing_samples} samples of a frame of {channels} channels"
+            ),
+            Self::Backpressure {
+                ready_frames,
+                max_frames,
+            } => write!(
+                f,
+                "{ready_frames} frames are waiting to be received, more than the cap of \
+                 {max_frames}"
+            ),
+            Self::TooManySamples { requested, max } => write!(
+                f,
+                "{requested} samples requested, but at most {max} can be processed"
+            ),
+            Self::OverlapTooLong {
+                overlap_ms,
+                sequence_ms: Some(sequence_ms),
+            } => write!(
+                f,
+                "overlap of {overlap_ms} ms must be shorter than the sequence of {sequence_ms} ms"
+            ),
+            Self::OverlapTooLong {
+                overlap_ms,
+                sequence_ms: None,
+            } => write!(
+                f,
+                "overlap of {overlap_ms} ms must be shorter than the automatic sequence of at \
+                 least {} ms",
+                crate::TuningParams::MIN_AUTO_SEQUENCE_MS
+            ),
+            Self::Cancelled => f.write_str("the operation was cancelled"),
+            Self::StreamEnded { written_frames } => {
+                write!(f, "the stream ended after {written_frames} frames")
+            }
+            Self::MisalignedOutput { len, channels } => write!(
+                f,
+                "output of {len} samples isn't a whole number of frames of {channels} channels"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SoundTouchError {}
+
+/// The error returned when parsing a [`Setting`] from a string that isn't the
+/// [name] of one.
+///
+/// [name]: Setting::name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseSettingError;
+
+impl fmt::Display for ParseSettingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown setting, expected one of ")?;
+        for (i, setting) in Setting::ALL.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(setting.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseSettingError {}
+
+/// The error returned when parsing a [`Preset`] from a string that isn't the [name] of
+/// one.
+///
+/// [name]: Preset::name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsePresetError;
+
+impl fmt::Display for ParsePresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown preset, expected one of ")?;
+        for (i, preset) in Preset::ALL.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(preset.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParsePresetError {}
+
+/// Errors returned by [`Config::from_kv_pairs`].
+///
+/// [`Config::from_kv_pairs`]: crate::Config::from_kv_pairs
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigParseError {
+    /// The key is neither a parameter nor the [name] of a setting.
+    ///
+    /// [name]: Setting::name
+    UnknownKey(String),
+    /// The value couldn't be parsed as the type of the key.
+    InvalidValue {
+        /// The key.
+        key: String,
+        /// The rejected value.
+        value: String,
+    },
+    /// The setting can't be changed.
+    ReadOnlySetting(Setting),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ConfigParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey(key) => write!(
+                f,
+                "unknown key `{key}`, expected channels, sample-rate, tempo, pitch, rate or a setting name"
+            ),
+            Self::InvalidValue { key, value } => write!(f, "invalid value `{value}` for `{key}`"),
+            Self::ReadOnlySetting(setting) => write!(f, "setting `{setting}` is read-only"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigParseError {}
+
+/// The error returned by [`io::pipe`](crate::io::pipe).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PipeError {
+    /// The configuration can't be processed, e.g. the number of channels isn't set.
+    Config(SoundTouchError),
+    /// Reading the input or writing the output failed, other than by the output being
+    /// closed.
+    Io(std::io::Error),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for PipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(err) => write!(f, "invalid configuration: {err}"),
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PipeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for PipeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// The error returned by [`wav::process_file_streaming`](crate::wav::process_file_streaming).
+#[cfg(feature = "wav")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WavError {
+    /// The configuration can't be processed with the format of the input.
+    Config(SoundTouchError),
+    /// Reading the input or writing the output failed.
+    Wav(hound::Error),
+    /// The [`CancellationToken`] was cancelled. The output is a valid WAV file of the
+    /// frames written until then.
+    ///
+    /// [`CancellationToken`]: crate::CancellationToken
+    Cancelled,
+}
+
+#[cfg(feature = "wav")]
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(err) => write!(f, "invalid configuration: {err}"),
+            Self::Wav(err) => write!(f, "WAV error: {err}"),
+            Self::Cancelled => f.write_str("processing was cancelled"),
+        }
+    }
+}
+
+#[cfg(feature = "wav")]
+impl std::error::Error for WavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(err) => Some(err),
+            Self::Wav(err) => Some(err),
+            Self::Cancelled => None,
+        }
+    }
+}
+
+#[cfg(feature = "wav")]
+impl From<hound::Error> for WavError {
+    fn from(err: hound::Error) -> Self {
+        Self::Wav(err)
+    }
+}
+
+/// Turns an error into a panic for the methods that mirror the C++ API and can't
+/// return a `Result`.
+///
+/// The crate denies `unwrap` and `expect`; every deliberate panic goes through this
+/// helper next to a fallible `try_` variant, so they are easy to find.
+pub(crate) trait OrPanic<T> {
+    fn or_panic(self) -> T;
+}
+
+impl<T> OrPanic<T> for Result<T, SoundTouchError> {
+    #[track_caller]
+    fn or_panic(self) -> T {
+        match self {
+            Ok(value) => value,
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::OrPanic;
+use crate::sound_touch::STACK_BUFFER_SAMPLES;
+use crate::{SoundTouch, SoundTouchError};
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Puts interleaved samples from an iterator, copying them into a stack buffer and
+    /// putting them in batches of up to 4096 samples, so a lazy source such as a decoder
+    /// doesn't have to be collected first. Returns the number of frames put.
+    ///
+    /// The iterator must end on a frame boundary. If it doesn't, the whole frames are
+    /// put and [`SoundTouchError::PartialFrame`] is returned for the rest. Other errors
+    /// are those of [`try_put_samples`], with the whole frames of the batches before
+    /// the failing one put already and [`NonFiniteInput`] counting from the start of
+    /// the iterator.
+    ///
+    /// The [`Extend<f32>`] implementation calls this and panics on errors.
+    ///
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    /// [`NonFiniteInput`]: SoundTouchError::NonFiniteInput
+    pub fn try_put_iter(
+        &mut self,
+        samples: impl IntoIterator<Item = f32>,
+    ) -> Result<usize, SoundTouchError> {
+        self.put_iter(samples.into_iter(), None)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`try_put_iter`], but for frames of `N` samples, e.g. `[left, right]` pairs.
+    /// Returns [`SoundTouchError::ChannelMismatch`] if `N` isn't the number of channels.
+    ///
+    /// The [`Extend<[f32; N]>`] implementation calls this and panics on errors.
+    ///
+    /// [`try_put_iter`]: SoundTouch::try_put_iter
+    pub fn try_put_frames_iter<const N: usize>(
+        &mut self,
+        frames: impl IntoIterator<Item = [f32; N]>,
+    ) -> Result<usize, SoundTouchError> {
+        self.put_iter(frames.into_iter().flatten(), Some(N))
+    }
+
+    /// Puts `samples` in batches, checking that the number of channels is `frame_len`
+    /// if it is given.
+    fn put_iter(
+        &mut self,
+        mut samples: impl Iterator<Item = f32>,
+        frame_len: Option<usize>,
+    ) -> Result<usize, SoundTouchError> {
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        // Samples in `buffer`, a partial frame carried over from the previous batch first.
+        let mut len = 0;
+        let mut put = 0;
+        loop {
+            for (slot, sample) in buffer[len..].iter_mut().zip(&mut samples) {
+                *slot = sample;
+                len += 1;
+            }
+            let exhausted = len < STACK_BUFFER_SAMPLES;
+
+            // Resolves a pending channel change before the batch is split into frames.
+            self.begin_put(len)?;
+            let channels = self.num_channels() as usize;
+            match frame_len {
+                Some(frame_len) if len > 0 && frame_len != channels => {
+                    return Err(SoundTouchError::ChannelMismatch {
+                        expected: channels as u32,
+                        actual: frame_len as u32,
+                    });
+                }
+                _ => {}
+            }
+
+            let frames = len / channels;
+            if frames > 0 {
+                self.try_put_samples(&buffer, frames)
+                    .map_err(|err| match err {
+                        SoundTouchError::NonFiniteInput { index } => {
+                            SoundTouchError::NonFiniteInput { index: put + index }
+                        }
+                        err => err,
+                    })?;
+            }
+            let whole = frames * channels;
+            put += whole;
+            buffer.copy_within(whole..len, 0);
+            len -= whole;
+
+            if exhausted {
+                return match len {
+                    0 => Ok(put / channels),
+                    trailing_samples => Err(SoundTouchError::PartialFrame {
+                        trailing_samples,
+                        channels,
+                    }),
+                };
+            }
+        }
+    }
+}
+
+/// Puts the samples with [`SoundTouch::try_put_iter`].
+///
+/// Through the blanket implementation this also makes `SoundTouch` an [`OutputSink`],
+/// so the output of one instance can be processed by another with the same number of
+/// channels, e.g. with [`generate_audio_extend`].
+///
+/// # Panics
+///
+/// Panics on the errors [`try_put_iter`] returns, including when the samples end in the
+/// middle of a frame, after putting the whole frames.
+/// Flushed output that hasn't been received is discarded instead, as
+/// [`SoundTouch::put_samples`] does.
+///
+/// [`OutputSink`]: crate::OutputSink
+/// [`generate_audio_extend`]: SoundTouch::generate_audio_extend
+/// [`try_put_iter`]: SoundTouch::try_put_iter
+impl Extend<f32> for SoundTouch {
+    fn extend<I: IntoIterator<Item = f32>>(&mut self, samples: I) {
+        let mut samples = samples.into_iter().peekable();
+        if samples.peek().is_some() {
+            self.discard_flushed(1);
+        }
+        self.try_put_iter(samples).or_panic();
+    }
+}
+
+/// Puts the frames with [`SoundTouch::try_put_frames_iter`].
+///
+/// # Panics
+///
+/// Panics on the errors [`try_put_frames_iter`] returns, including when `N` isn't the
+/// number of channels.
+/// Flushed output that hasn't been received is discarded instead, as
+/// [`SoundTouch::put_samples`] does.
+///
+/// [`try_put_frames_iter`]: SoundTouch::try_put_frames_iter
+impl<const N: usize> Extend<[f32; N]> for SoundTouch {
+    fn extend<I: IntoIterator<Item = [f32; N]>>(&mut self, frames: I) {
+        let mut frames = frames.into_iter().peekable();
+        if frames.peek().is_some() {
+            self.discard_flushed(1);
+        }
+        self.try_put_frames_iter(frames).or_panic();
+    }
+}
@@ -0,0 +1,48 @@
+/// A slice that a pointer passed to SoundTouch is taken from, see [`ffi_call!`].
+pub(crate) trait FfiSlice {
+    type Ptr;
+
+    /// Returns a pointer to the first `len` elements, panicking if there are fewer.
+    fn ffi_ptr(self, len: usize) -> Self::Ptr;
+}
+
+impl<T> FfiSlice for &[T] {
+    type Ptr = *const T;
+
+    fn ffi_ptr(self, len: usize) -> *const T {
+        self[..len].as_ptr()
+    }
+}
+
+impl<T> FfiSlice for &mut [T] {
+    type Ptr = *mut T;
+
+    fn ffi_ptr(self, len: usize) -> *mut T {
+        self[..len].as_mut_ptr()
+    }
+}
+
+/// Calls into SoundTouch with pointers taken from slices right before the call, so every
+/// pointer and length pair crossing the FFI comes from a slice in the calling function.
+///
+/// `ffi_call!([ptr = slice, len], ... => call)` binds each `ptr` to a pointer to the
+/// first `len` elements of `slice`, a `&[T]` or a `&mut [T]`, and evaluates `call` in an
+/// `unsafe` block. Taking a pointer panics if the slice is shorter than `len`, so a
+/// missing length check is a panic instead of an out-of-bounds access. The public
+/// methods check the lengths before and return a [`SoundTouchError`] instead.
+///
+/// `call` must not access more than `len` elements through each pointer, and the call
+/// site argues why the call is otherwise sound in a `// SAFETY:` comment.
+///
+/// [`SoundTouchError`]: crate::SoundTouchError
+macro_rules! ffi_call {
+    ($([$ptr:ident = $slice:expr, $len:expr]),+ => $call:expr) => {{
+        $(let $ptr = $crate::ffi_call::FfiSlice::ffi_ptr($slice, $len);)+
+        // SAFETY: Each pointer is valid for the `len` elements it was taken with, for as
+        // long as the slice is borrowed, which covers the call. The rest of the argument
+        // is at the call site.
+        unsafe { $call }
+    }};
+}
+
+pub(crate) use ffi_call;
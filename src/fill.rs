@@ -0,0 +1,92 @@
+use alloc::vec::Vec;
+
+use crate::{SoundTouch, SoundTouchError};
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Fills `out` with exactly as many processed frames as it holds, pulling as much
+    /// input as that takes from `pull_input`, e.g. for an offline render graph that asks
+    /// for blocks of a fixed size.
+    ///
+    /// Output already ready is received first. While more is needed, `pull_input` is
+    /// called with a vector to append interleaved input to, and returns `false` once the
+    /// stream ends, after appending its last samples, which flushes it. The whole frames
+    /// are put and removed after each call, a trailing partial frame stays for the next
+    /// one. Before each call, the vector has room for the input that
+    /// [`input_needed_for_output`] says fills `out`, so a source can read that much at
+    /// once. It is kept between the calls, so nothing is allocated beyond what the
+    /// input needs. `pull_input` may block until input is available; if it keeps
+    /// returning `true` without input, this never returns.
+    ///
+    /// Output beyond `out` stays ready for the next call, and a call after the end of the
+    /// stream starts a new one once its output has been received. A trailing partial
+    /// frame of `out` is left as it is.
+    ///
+    /// Returns [`SoundTouchError::StreamEnded`] with the number of frames written if the
+    /// stream ended before `out` was full, leaving the frames after them as they are,
+    /// and [`SoundTouchError::PartialFrame`] if the last input ended in the middle of a
+    /// frame. Errors of [`try_put_samples`] and [`try_receive_samples`] are returned as
+    /// they are.
+    ///
+    /// ```rust
+    /// use soundtouch::SoundTouch;
+    ///
+    /// let mut soundtouch = SoundTouch::new();
+    /// soundtouch.set_channels(1).set_sample_rate(44100).set_tempo(2.0);
+    /// let mut packets = 0;
+    /// let mut out = [0.0; 4096];
+    /// soundtouch
+    ///     .fill_exact(&mut out, |input| {
+    ///         packets += 1;
+    ///         input.extend_from_slice(&[0.5; 1024]);
+    ///         true
+    ///     })
+    ///     .unwrap();
+    /// // Twice as much input as output, plus the latency.
+    /// assert!(packets > 8);
+    /// ```
+    ///
+    /// [`input_needed_for_output`]: SoundTouch::input_needed_for_output
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    /// [`try_receive_samples`]: SoundTouch::try_receive_samples
+    pub fn fill_exact(
+        &mut self,
+        out: &mut [f32],
+        mut pull_input: impl FnMut(&mut Vec<f32>) -> bool,
+    ) -> Result<(), SoundTouchError> {
+        self.check_configured()?;
+        let channels = self.num_channels() as usize;
+        let frames = out.len() / channels;
+        let mut input = Vec::new();
+        let mut written = 0;
+        let mut ended = false;
+        loop {
+            let rest = &mut out[written * channels..frames * channels];
+            written += self.try_receive_samples(rest, frames - written)?;
+            if written == frames {
+                return Ok(());
+            }
+            if ended {
+                return Err(SoundTouchError::StreamEnded {
+                    written_frames: written,
+                });
+            }
+            let needed = self.input_needed_for_output(frames - written) * channels;
+            input.reserve(needed.saturating_sub(input.len()));
+            ended = !pull_input(&mut input);
+            let whole = input.len() / channels;
+            self.try_put_samples(&input[..whole * channels], whole)?;
+            input.drain(..whole * channels);
+            if ended {
+                if !input.is_empty() {
+                    return Err(SoundTouchError::PartialFrame {
+                        trailing_samples: input.len(),
+                        channels,
+                    });
+                }
+                self.flush();
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+/// The number of low bits of the 16-bit samples that [`fingerprint`] ignores.
+const IGNORED_BITS: u32 = 2;
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Converts `sample` to 16-bit: scales it by 32768, rounds to the nearest integer (half
+/// away from zero) and clamps to the range of `i16`. NaN becomes `0`.
+///
+/// This is the quantization [`fingerprint`] starts from.
+pub fn quantize(sample: f32) -> i16 {
+    let scaled = sample * 32768.0;
+    let rounded = if scaled >= 0.0 {
+        scaled + 0.5
+    } else {
+        scaled - 0.5
+    };
+    // `as` saturates and maps NaN to 0.
+    (rounded as i32).clamp(i16::MIN.into(), i16::MAX.into()) as i16
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns a stable 64-bit hash of `samples`, for checking that processing still gives
+/// the same output, e.g. after upgrading SoundTouch or between two builds of an app.
+///
+/// The samples are [quantized](quantize) to 16-bit and the lowest two bits dropped (an
+/// arithmetic shift), and the resulting values are hashed with 64-bit FNV-1a over their
+/// little-endian bytes, two per sample. Floating point noise far below one 16-bit LSB,
+/// as between compilers or SIMD code paths, only changes the fingerprint if it moves a
+/// sample across a rounding boundary, and a difference of ±1 LSB at 16-bit is ignored
+/// unless it crosses a multiple of four. No hash can ignore every such difference, so
+/// on a mismatch, compare the samples with a tolerance before treating it as a change.
+///
+/// The algorithm is part of the API: the same samples give the same fingerprint on
+/// every target and in every version of this crate. Changing it is a breaking change.
+///
+/// # Stability of processed output
+///
+/// The crate's tests keep a corpus of fingerprints of processed output for a matrix of
+/// configurations, in [deterministic mode]. Those fingerprints only change in a release
+/// that upgrades the bundled SoundTouch or deliberately changes the processing, and the
+/// changelog says so. Deterministic output isn't identical across target
+/// architectures, so compare fingerprints recorded on the same architecture.
+///
+/// ```rust
+/// use soundtouch::fingerprint;
+///
+/// let samples = [0.25, -0.5, 0.125];
+/// assert_eq!(fingerprint(&samples), fingerprint(&samples));
+/// // Noise that doesn't move a sample to another 16-bit value is ignored.
+/// assert_eq!(fingerprint(&samples), fingerprint(&[0.25 + 1e-7, -0.5, 0.125]));
+/// assert_ne!(fingerprint(&samples), fingerprint(&[0.25, -0.5, 0.0]));
+/// ```
+///
+/// [deterministic mode]: crate::SoundTouch::set_deterministic
+pub fn fingerprint(samples: &[f32]) -> u64 {
+    samples.iter().fold(FNV_OFFSET_BASIS, |hash, &sample| {
+        let value = quantize(sample) >> IGNORED_BITS;
+        fnv1a(hash, &value.to_le_bytes())
+    })
+}
+
+/// Continues the 64-bit FNV-1a `hash` with `bytes`.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+
+use crate::math::exp2;
+use crate::SoundTouch;
+
+/// The strongest tilt of the correction, reached at a shift of about 40 semitones.
+const MAX_COEFFICIENT: f32 = 0.9;
+
+/// A first-order spectral tilt applied to the output, opposite to the shift of the
+/// spectral envelope caused by shifting the pitch by `semitones`.
+///
+/// Raising the pitch moves the formants up, so the output is de-emphasized with a
+/// one-pole low-pass with unity gain at DC. Lowering it moves them down, so the output
+/// is pre-emphasized with a one-zero high-pass with unity gain at Nyquist. The
+/// coefficient is `1 - 2^(-|semitones| / 12)`: a shift of an octave tilts the spectrum
+/// by about 9.5 dB between DC and Nyquist.
+#[derive(Debug, Clone)]
+pub(crate) struct FormantCorrection {
+    semitones: f64,
+    coefficient: f32,
+    /// Per channel, the last output sample when raising and the last input sample when
+    /// lowering.
+    previous: Vec<f32>,
+}
+
+impl FormantCorrection {
+    fn new(semitones: f64) -> Self {
+        let coefficient = 1.0 - exp2(-semitones.abs() / 12.0);
+        Self {
+            semitones,
+            coefficient: (coefficient as f32).min(MAX_COEFFICIENT),
+            previous: Vec::new(),
+        }
+    }
+
+    /// Forgets the previous samples, for a new stream.
+    pub(crate) fn reset(&mut self) {
+        self.previous.fill(0.0);
+    }
+
+    /// Filters the interleaved `samples` in place.
+    pub(crate) fn apply(&mut self, samples: &mut [f32], channels: usize) {
+        if self.previous.len() != channels {
+            self.previous.clear();
+            self.previous.resize(channels, 0.0);
+        }
+        let c = self.coefficient;
+        let raising = self.semitones > 0.0;
+        for frame in samples.chunks_exact_mut(channels) {
+            for (sample, previous) in frame.iter_mut().zip(&mut self.previous) {
+                if raising {
+                    *sample = (1.0 - c) * *sample + c * *previous;
+                    *previous = *sample;
+                } else {
+                    let input = *sample;
+                    *sample = (input - c * *previous) / (1.0 + c);
+                    *previous = input;
+                }
+            }
+        }
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Shifts the pitch by `semitones`, fractions included, and filters the output to
+    /// counteract the shift of the formants, so that voices sound less chipmunk-like
+    /// when raised and less muffled when lowered.
+    ///
+    /// SoundTouch has no formant preservation, and this doesn't add it: the output is
+    /// only tilted towards the lows when raising the pitch and towards the highs when
+    /// lowering it, with a first-order filter tuned to the shift. That moves the
+    /// spectral centroid back towards the original but leaves the formant peaks where
+    /// the shift put them, and it changes the loudness, by up to a factor of `1 / 3` at
+    /// the lowest or highest frequencies for a shift of an octave. Treat it as a cheap
+    /// approximation; real formant preservation needs a spectral envelope estimate.
+    ///
+    /// The correction stays tuned to `semitones` when the pitch is later changed
+    /// another way, until this is called again, [`clear_formant_correction`] removes
+    /// it or the instance is [reset]. A shift of `0` semitones removes it.
+    ///
+    /// [`clear_formant_correction`]: SoundTouch::clear_formant_correction
+    /// [reset]: SoundTouch::reset
+    pub fn set_pitch_with_formant_correction(&mut self, semitones: f64) -> &mut Self {
+        self.set_pitch(exp2(semitones / 12.0));
+        self.formant = (semitones != 0.0).then(|| FormantCorrection::new(semitones));
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the shift in semitones the [formant correction] is tuned to, or `None`
+    /// if there is none.
+    ///
+    /// [formant correction]: SoundTouch::set_pitch_with_formant_correction
+    pub fn formant_correction(&self) -> Option<f64> {
+        self.formant.as_ref().map(|formant| formant.semitones)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Removes the [formant correction] filter, keeping the pitch.
+    ///
+    /// [formant correction]: SoundTouch::set_pitch_with_formant_correction
+    pub fn clear_formant_correction(&mut self) -> &mut Self {
+        self.formant = None;
+        self
+    }
+}
@@ -0,0 +1,71 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "registry")]
+use std::sync::Arc;
+
+#[cfg(feature = "registry")]
+use crate::registry::{self, Stats};
+#[cfg(feature = "registry")]
+use crate::BufferLevels;
+
+/// The identity of a [`SoundTouch`] or [`BPMDetect`] instance: a unique ID and, with
+/// the `registry` feature, its entry in the [registry].
+///
+/// Without the `registry` feature the recording methods do nothing.
+///
+/// [`SoundTouch`]: crate::SoundTouch
+/// [`BPMDetect`]: crate::BPMDetect
+/// [registry]: crate::registry
+pub(crate) struct Instance {
+    id: u64,
+    #[cfg(feature = "registry")]
+    stats: Arc<Stats>,
+}
+
+impl Instance {
+    /// Assigns the next ID and registers the instance.
+    #[cfg_attr(not(feature = "registry"), allow(unused_variables))]
+    pub(crate) fn new(kind: &'static str, channels: u32, sample_rate: u32) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Self {
+            id,
+            #[cfg(feature = "registry")]
+            stats: registry::register(id, kind, channels, sample_rate),
+        }
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    #[cfg_attr(not(feature = "registry"), allow(unused_variables))]
+    pub(crate) fn record_format(&self, channels: u32, sample_rate: u32) {
+        #[cfg(feature = "registry")]
+        self.stats.set_format(channels, sample_rate);
+    }
+
+    #[cfg_attr(not(feature = "registry"), allow(unused_variables))]
+    pub(crate) fn record_put(&self, frames: usize) {
+        #[cfg(feature = "registry")]
+        self.stats.add_put(frames);
+    }
+
+    #[cfg_attr(not(feature = "registry"), allow(unused_variables))]
+    pub(crate) fn record_received(&self, frames: usize) {
+        #[cfg(feature = "registry")]
+        self.stats.add_received(frames);
+    }
+
+    #[cfg(feature = "registry")]
+    pub(crate) fn record_levels(&self, levels: BufferLevels) {
+        self.stats.set_levels(levels);
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Drop for Instance {
+    fn drop(&mut self) {
+        registry::deregister(self.id);
+    }
+}
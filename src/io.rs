@@ -0,0 +1,284 @@
+//! Streaming raw PCM from a reader to a writer, to use SoundTouch as a filter between
+//! other programs, e.g. with the `stretch_pipe` example:
+//!
+//! ```sh
+//! ffmpeg -i in.mp3 -f f32le -ac 2 -ar 44100 - \
+//!     | stretch_pipe 1.25 2 44100 \
+//!     | ffmpeg -f f32le -ac 2 -ar 44100 -i - out.wav
+//! ```
+//!
+//! ```rust
+//! use soundtouch::io::{pipe, SampleFormat};
+//! use soundtouch::Config;
+//!
+//! // One second of stereo silence at 44.1 kHz, the default format of `Config`.
+//! let input: Vec<u8> = [0.0f32; 44100 * 2].iter().flat_map(|s| s.to_le_bytes()).collect();
+//! let mut output = Vec::new();
+//! let config = Config::new().set_tempo(2.0).clone();
+//! let stats = pipe(&input[..], &mut output, &config, SampleFormat::F32Le).unwrap();
+//! assert_eq!(stats.frames_in, 44100);
+//! assert_eq!(output.len() as u64, stats.frames_out * 2 * 4);
+//! ```
+
+use std::io::{ErrorKind, Read, Write};
+use std::vec;
+use std::vec::Vec;
+
+use crate::streaming::try_pump_stream;
+use crate::{quantize, Config, PipeError, SoundTouch};
+#[cfg(feature = "bpm")]
+use crate::{BPMDetect, BpmResult};
+
+/// Frames read and received at a time, which bounds the memory used apart from
+/// SoundTouch's own buffers.
+const CHUNK_FRAMES: usize = 4096;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The encoding of the interleaved samples [`pipe`] reads and writes, named after the
+/// matching ffmpeg formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SampleFormat {
+    /// 32-bit little-endian floats, `f32le`.
+    F32Le,
+    /// 16-bit little-endian signed integers, `s16le`. Input is scaled by `1 / 32768` and
+    /// output is converted with [`quantize`].
+    S16Le,
+}
+
+impl SampleFormat {
+    /// Returns the size of one sample in bytes.
+    pub const fn bytes_per_sample(self) -> usize {
+        match self {
+            SampleFormat::F32Le => 4,
+            SampleFormat::S16Le => 2,
+        }
+    }
+
+    fn decode(self, bytes: &[u8], samples: &mut [f32]) {
+        match self {
+            SampleFormat::F32Le => {
+                for (sample, b) in samples.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *sample = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                }
+            }
+            SampleFormat::S16Le => {
+                for (sample, b) in samples.iter_mut().zip(bytes.chunks_exact(2)) {
+                    *sample = i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0;
+                }
+            }
+        }
+    }
+
+    fn encode(self, samples: &[f32], bytes: &mut Vec<u8>) {
+        bytes.clear();
+        match self {
+            SampleFormat::F32Le => {
+                bytes.extend(samples.iter().flat_map(|sample| sample.to_le_bytes()))
+            }
+            SampleFormat::S16Le => bytes.extend(
+                samples
+                    .iter()
+                    .flat_map(|&sample| quantize(sample).to_le_bytes()),
+            ),
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`pipe`] processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PipeStats {
+    /// Frames read from the input.
+    pub frames_in: u64,
+    /// Frames written to the output.
+    pub frames_out: u64,
+    /// Bytes at the end of the input that didn't make up a whole frame, which were
+    /// dropped.
+    pub trailing_bytes: usize,
+    /// Whether the output was closed before all of it was written, e.g. because the
+    /// program reading it exited. Processing stops there.
+    pub output_closed: bool,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Processes the interleaved samples read from `reader` with `config` and writes the
+/// output to `writer`, in `format` on both sides, until the end of the input. The stream
+/// is then flushed, so the output is the same as that of [`generate_audio`].
+///
+/// Memory use is bounded: the input is read and the output written in chunks of a few
+/// thousand frames. Reads can end anywhere, even within a sample; an incomplete frame at
+/// the end of the input is dropped and counted in [`PipeStats::trailing_bytes`].
+///
+/// A writer that fails with [`ErrorKind::BrokenPipe`], as standard output does once the
+/// program reading it has exited, ends the processing without an error, with
+/// [`PipeStats::output_closed`] set. Reads interrupted by a signal are retried. `writer`
+/// is flushed at the end, but not buffered, so wrap it in a [`BufWriter`] if single
+/// writes are expensive.
+///
+/// # Errors
+///
+/// Returns [`PipeError::Config`] if `config` is [invalid], and [`PipeError::Io`] on any
+/// other error of `reader` or `writer`.
+///
+/// [`generate_audio`]: SoundTouch::generate_audio
+/// [`BufWriter`]: std::io::BufWriter
+/// [invalid]: Config::validate
+pub fn pipe(
+    reader: impl Read,
+    writer: impl Write,
+    config: &Config,
+    format: SampleFormat,
+) -> Result<PipeStats, PipeError> {
+    pipe_inner(reader, writer, config, format, |_| {})
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The tempo analysis of the input of [`pipe_with_bpm_analysis`], e.g. to show the BPM
+/// of a song while it is stretched without decoding it a second time. Nothing is
+/// analyzed unless it is passed there.
+///
+/// Each pipe starts a new analysis with the format of its configuration. Once the pipe
+/// returns, [`take_analysis`] hands out the [`BpmResult`] of the whole input.
+///
+/// [`take_analysis`]: BpmAnalysis::take_analysis
+#[cfg(feature = "bpm")]
+#[derive(Debug, Default)]
+pub struct BpmAnalysis {
+    detect: Option<BPMDetect>,
+    result: Option<BpmResult>,
+}
+
+#[cfg(feature = "bpm")]
+impl BpmAnalysis {
+    /// Creates an analysis that hasn't seen any input yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tempo of the input analyzed so far, or `None` if there was none or
+    /// no tempo was found in it yet.
+    pub fn current_bpm_estimate(&mut self) -> Option<f32> {
+        let bpm = self.detect.as_mut()?.get_bpm();
+        (bpm > 0.0).then_some(bpm)
+    }
+
+    /// Returns the result of the last pipe that ended, including one whose output was
+    /// closed early, which covers the input read until then. Returns `None` before that,
+    /// if the pipe failed, and after the result was taken.
+    pub fn take_analysis(&mut self) -> Option<BpmResult> {
+        self.result.take()
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`pipe`], but also forwards the input, before it is stretched, to `analysis`,
+/// which detects its tempo with a [`BPMDetect`]. The samples decoded for SoundTouch are
+/// analyzed as they are, so the analysis copies nothing beyond what [`BPMDetect`]
+/// buffers itself.
+///
+/// # Errors
+///
+/// Like [`pipe`], and [`PipeError::Config`] if [`BPMDetect`] doesn't support the
+/// format of `config`.
+#[cfg(feature = "bpm")]
+pub fn pipe_with_bpm_analysis(
+    reader: impl Read,
+    writer: impl Write,
+    config: &Config,
+    format: SampleFormat,
+    analysis: &mut BpmAnalysis,
+) -> Result<PipeStats, PipeError> {
+    config.validate().map_err(PipeError::Config)?;
+    let detect = analysis.detect.insert(
+        BPMDetect::try_new(config.channels(), config.sample_rate()).map_err(PipeError::Config)?,
+    );
+    analysis.result = None;
+    let stats = pipe_inner(reader, writer, config, format, |samples| {
+        detect.input_samples(samples)
+    })?;
+    analysis.result = Some(detect.result());
+    Ok(stats)
+}
+
+/// Runs [`pipe`], handing the decoded input to `tap` before it is stretched.
+fn pipe_inner(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    config: &Config,
+    format: SampleFormat,
+    mut tap: impl FnMut(&[f32]),
+) -> Result<PipeStats, PipeError> {
+    config.validate().map_err(PipeError::Config)?;
+    let mut soundtouch = SoundTouch::with_config(config);
+    let channels = config.channels() as usize;
+    let frame_bytes = channels * format.bytes_per_sample();
+
+    let mut input = vec![0; CHUNK_FRAMES * frame_bytes];
+    let mut filled = 0;
+    let mut bytes = Vec::with_capacity(CHUNK_FRAMES * frame_bytes);
+    let (mut frames_in, mut frames_out, mut trailing_bytes) = (0, 0, 0);
+
+    // Decodes the whole frames read so far, keeping an incomplete one for the next read.
+    let read = |samples: &mut [f32]| loop {
+        let whole = filled - filled % frame_bytes;
+        if whole > 0 {
+            let samples = &mut samples[..whole / format.bytes_per_sample()];
+            format.decode(&input[..whole], samples);
+            tap(samples);
+            input.copy_within(whole..filled, 0);
+            filled -= whole;
+            frames_in += (whole / frame_bytes) as u64;
+            return Ok(whole / frame_bytes);
+        }
+        match reader.read(&mut input[filled..]) {
+            Ok(0) => {
+                trailing_bytes = filled;
+                return Ok(0);
+            }
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(Stop::Failed(err.into())),
+        }
+    };
+    let write = |samples: &[f32]| {
+        format.encode(samples, &mut bytes);
+        match writer.write_all(&bytes) {
+            Ok(()) => {
+                frames_out += (samples.len() / channels) as u64;
+                Ok(())
+            }
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => Err(Stop::Closed),
+            Err(err) => Err(Stop::Failed(err.into())),
+        }
+    };
+    let output_closed =
+        match try_pump_stream(&mut soundtouch, read, write, CHUNK_FRAMES, |_| Ok(())) {
+            Ok(()) => match writer.flush() {
+                Ok(()) => false,
+                Err(err) if err.kind() == ErrorKind::BrokenPipe => true,
+                Err(err) => return Err(err.into()),
+            },
+            Err(Stop::Closed) => true,
+            Err(Stop::Failed(err)) => return Err(err),
+        };
+    Ok(PipeStats {
+        frames_in,
+        frames_out,
+        trailing_bytes,
+        output_closed,
+    })
+}
+
+/// Why [`pipe_inner`] stopped before the end of the input.
+enum Stop {
+    /// The writer was closed.
+    Closed,
+    /// Reading, writing or processing failed.
+    Failed(PipeError),
+}
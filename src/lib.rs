@@ -1,6 +1,23 @@
 //!A safe utility wrapper around the SoundTouch C++ audio library. The API is very similar to the original C++ API.
 //!
 //!Most of the documentation is copied from the [SoundTouch repository](https://codeberg.org/soundtouch/soundtouch).
+//!## Quick Start
+//!
+//!To shift the pitch while keeping the duration, or change the duration while keeping
+//!the pitch, of a whole recording:
+//!
+//!```rust
+//!// use actual audio samples here, interleaved stereo at 44.1 kHz
+//!let samples = vec![0.0; 44100 * 2];
+//!
+//!// Up two semitones, with the same number of samples as the input.
+//!let shifted = soundtouch::pitch_shift(&samples, 2, 44100, 2.0);
+//!// 25% longer, at the same pitch.
+//!let stretched = soundtouch::time_stretch(&samples, 2, 44100, 1.25);
+//!```
+//!
+//!Both trim the latency of the pipeline, so the output lines up with the input. For
+//!streaming, combined changes and fine control, use [`SoundTouch`] directly as below.
 //!## High Level Example
 //!```rust
 //!use soundtouch::{SoundTouch, Setting};
@@ -39,15 +56,15 @@
 //!let mut new_samples: [f32; BUF_SIZE] = [0.0; BUF_SIZE];
 //!let mut output_samples: Vec<f32> = Vec::with_capacity(samples.len());
 //!soundtouch.put_samples(&samples, samples.len() / 2);
+//!soundtouch.flush();
 //!let mut n_samples = 1;
 //!while n_samples != 0 {
 //!    n_samples = soundtouch.receive_samples(
 //!        new_samples.as_mut_slice(),
 //!        BUF_SIZE / 2
 //!        );
-//!    output_samples.extend_from_slice(&new_samples);
+//!    output_samples.extend_from_slice(&new_samples[..n_samples * 2]);
 //!}
-//!soundtouch.flush();
 //!
 //!// do something with output_samples
 //!
@@ -56,16 +73,262 @@
 //!
 //!## Features
 //!This create is `no_std` but does provide the [`generate_audio`] utility function, which requires the `alloc` feature (enabled by default).
+//![`generate_audio_extend`] writes to any [`OutputSink`] instead and works without it.
 //!
 //!To run in a completely `no_std` environment, disable the default features.
 //!
-//!- `alloc` (enabled by default): Enables the use of the [`generate_audio`] function.
+//!- `std`: Enables `alloc`, the [`SoundTouchPool`] type, [`process_streaming`], [block timing], [adaptive quality] and the [`bench`] and [`io`] modules.
+//!- `alloc` (enabled by default): Enables the use of the [`generate_audio`] function, [`pitch_shift`], [`time_stretch`], [formant correction], the [neutral bypass], [input staging], [`SoundTouch::fill_exact`], [`SoundTouch::lint`], [`compare_quickseek`], [`resample`], [`Scrubber`], [`OfflineStretcher`], [`CancellationToken`] and the [`batch`] and [`segments`] modules.
+//!- `bpm` (enabled by default): Enables [`BPMDetect`] and its [prefilter], the [`BpmAnalysis`] tap of the [`io`] module, and with `alloc` the [`auto`], [`loops`], [`onsets`] and [`verify`] modules.
+//!- `testing`: Enables `alloc` and the [`testing`] module with generators, invariant checks for property tests, a long-run drift check and a stand-in [`TimeStretcher`].
+//!- `registry`: Enables `std` and the [`registry`] module listing live instances by [`id`].
+//!- `alloc-audit`: Enables `std` and the [`alloc_audit`] module for checking that code doesn't allocate.
+//!- `rayon`: Enables `std` and processes the clips of [`batch::stretch_clips`] in parallel.
+//!- `tracing`: Logs a warning through `tracing` when the rate is set to one that aliases, see [`SoundTouch::lint`].
+//!- `capi`: Enables `std` and the [`capi`] module, a C interface with a generated header.
+//!- `wav`: Enables `std` and the [`wav`] module, processing WAV files a chunk at a time with `hound`.
 //!
 //![`generate_audio`]: SoundTouch::generate_audio
+//![`generate_audio_extend`]: SoundTouch::generate_audio_extend
+//![`id`]: SoundTouch::id
+//![`SoundTouchPool`]: crate::SoundTouchPool
+//![`process_streaming`]: crate::process_streaming
+//![block timing]: SoundTouch::set_block_timing
+//![adaptive quality]: crate::realtime::AdaptiveQuality
+//![`pitch_shift`]: crate::pitch_shift
+//![formant correction]: SoundTouch::set_pitch_with_formant_correction
+//![neutral bypass]: SoundTouch::set_bypass_when_neutral
+//![input staging]: SoundTouch::set_input_staging_frames
+//![`time_stretch`]: crate::time_stretch
+//![`compare_quickseek`]: crate::compare_quickseek
+//![`resample`]: crate::resample
+//![`Scrubber`]: crate::Scrubber
+//![`OfflineStretcher`]: crate::OfflineStretcher
+//![`CancellationToken`]: crate::CancellationToken
+//![prefilter]: crate::PrefilterConfig
+//![`BpmAnalysis`]: crate::io::BpmAnalysis
+//!
+//!The C++ sources of SoundTouch are compiled by `soundtouch-ffi`, which always builds all
+//!of them and has no features for leaving out parts such as BPM detection, the SIMD
+//!routines or the anti-alias filter. Without `bpm` the BPM detection is still linked
+//!unless the linker drops it as unused, and the SIMD routines and the anti-alias filter
+//!have no feature here, as [`cpu`] and the [`AaFilterLength`] setting are part of the
+//!core API. Features for trimming the C++ have to be added there before this crate can
+//!forward them.
+//!
+//!## Panics
+//!SoundTouch reports invalid arguments by throwing C++ exceptions, which abort the process
+//!when they reach Rust. This crate checks those arguments before calling into SoundTouch:
+//!invalid channel counts, sample rates and [`AaFilterLength`] values are ignored by the
+//!setters (with a debug assertion), and the `try_` variants return a [`SoundTouchError`].
+//!
+//!With the `try_` methods, nothing in the processing path panics. The remaining panics are:
+//!
+//!- [`put_samples`], [`receive_samples`], [`generate_audio`], [`process_block`],
+//!  [`BPMDetect::new`], [`Scratch::for_channels`] and [`time_stretch_to_duration`] on the
+//!  errors their `try_` variants return.
+//!- Debug assertions in the setters for invalid values.
+//!- [`flush_trimmed`], [`process_streaming`], [`compare_quickseek`], [`pitch_shift`],
+//!  [`time_stretch`], the processing of the [`wav`] module and the [`loops`] and [`segments`]
+//!  modules, which are offline conveniences, on unconfigured instances, invalid
+//!  configurations and the documented misuse of their arguments, such as a `chunk_frames`
+//!  of `0`.
+//!- Checks of arguments outside the processing path, each listed under `# Panics` on the
+//!  item:
+//!  - the generators of the [`signal`] module on a sample rate or channel count of `0`, and
+//!    [`sweep`] and [`click_track`] on frequencies or tempos that aren't positive and finite;
+//!  - [`detect_onsets`] and [`best_alignment`] on `0` channels or a sample rate of `0`;
+//!  - [`Frames::to_duration`] on a sample rate of `0`;
+//!  - [`BeatGrid::new`] on a tempo that isn't positive and finite;
+//!  - [`BPMDetect::set_prefilter`] on a filter that is invalid for the sample rate, and
+//!    [`BPMDetect::input_all_with_progress`] and [`BPMDetect::input_all_cancellable`] on a
+//!    `chunk_frames` of `0`.
+//!- The [`testing`] module, whose generators panic on empty ranges and whose
+//!  [`PassthroughStretcher`] panics on misuse like the assertions of a test.
+//!
+//!Failed allocations abort as usual. Inside the crate `unwrap` and `expect` are denied;
+//!panics on errors that a `try_` variant returns all go through one helper next to it, and
+//!the checks of arguments are plain assertions.
+//!
+//!## Safety
+//!Every pointer handed to SoundTouch is taken from a slice right before the call, with the
+//!length the call reads or writes, and the public methods check those lengths first. A
+//!buffer that is too short is a [`SoundTouchError`] from the `try_` variant, never an
+//!out-of-bounds access. Each remaining `unsafe` block states why it is sound in a
+//!`// SAFETY:` comment.
+//!
+//![`AaFilterLength`]: Setting::AaFilterLength
+//![`put_samples`]: SoundTouch::put_samples
+//![`receive_samples`]: SoundTouch::receive_samples
+//![`process_block`]: SoundTouch::process_block
+//![`flush_trimmed`]: SoundTouch::flush_trimmed
+//![`BPMDetect::new`]: crate::BPMDetect::new
+//![`time_stretch_to_duration`]: crate::time_stretch_to_duration
+//![`sweep`]: crate::signal::sweep
+//![`click_track`]: crate::signal::click_track
+//![`detect_onsets`]: crate::onsets::detect_onsets
+//![`best_alignment`]: crate::align::best_alignment
+//![`Frames::to_duration`]: crate::units::Frames::to_duration
+//![`BeatGrid::new`]: crate::bpm::BeatGrid::new
+//![`BPMDetect::set_prefilter`]: crate::BPMDetect::set_prefilter
+//![`BPMDetect::input_all_with_progress`]: crate::BPMDetect::input_all_with_progress
+//![`BPMDetect::input_all_cancellable`]: crate::BPMDetect::input_all_cancellable
+//![`PassthroughStretcher`]: crate::testing::PassthroughStretcher
+//![`Scratch::for_channels`]: crate::Scratch::for_channels
 
 #![no_std]
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+#![deny(unsafe_op_in_unsafe_fn)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub mod align;
+#[cfg(feature = "alloc-audit")]
+pub mod alloc_audit;
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+pub mod auto;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bench;
+mod block;
+pub mod bpm;
+#[cfg(feature = "bpm")]
 mod bpm_detect;
+#[cfg(feature = "alloc")]
+mod bypass;
+#[cfg(feature = "alloc")]
+mod cancel;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod channel_mask;
+mod config;
+pub mod cpu;
+mod double;
+mod drift;
+mod error;
+mod extend;
+mod ffi_call;
+#[cfg(feature = "alloc")]
+mod fill;
+mod fingerprint;
+#[cfg(feature = "alloc")]
+mod formant;
+mod instance;
+#[cfg(feature = "std")]
+pub mod io;
+mod lint;
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+pub mod loops;
+#[cfg(feature = "alloc")]
+mod loudness;
+mod math;
+mod memory;
+#[cfg(feature = "alloc")]
+mod offline;
+#[cfg(feature = "alloc")]
+mod oneshot;
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+pub mod onsets;
+mod planar;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "bpm")]
+mod prefilter;
+#[cfg(feature = "alloc")]
+mod quickseek;
+pub mod realtime;
+#[cfg(feature = "registry")]
+pub mod registry;
+mod requirements;
+#[cfg(feature = "alloc")]
+mod resample;
+#[cfg(feature = "alloc")]
+mod scrub;
+#[cfg(feature = "alloc")]
+mod seek_stats;
+#[cfg(feature = "alloc")]
+pub mod segments;
+pub mod signal;
+mod sink;
 mod sound_touch;
+#[cfg(feature = "alloc")]
+mod staging;
+mod stereo;
+#[cfg(feature = "std")]
+mod streaming;
+mod stretcher;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "std")]
+mod timing;
+mod tuning;
+pub mod units;
+#[cfg(all(feature = "alloc", feature = "bpm"))]
+pub mod verify;
+#[cfg(feature = "wav")]
+pub mod wav;
 pub use sound_touch::*;
+pub use block::*;
+#[cfg(feature = "bpm")]
 pub use bpm_detect::*;
+#[cfg(feature = "alloc")]
+pub use cancel::*;
+pub use channel_mask::*;
+pub use config::*;
+pub use drift::*;
+pub use error::*;
+pub use fingerprint::*;
+pub use lint::*;
+#[cfg(feature = "alloc")]
+pub use loudness::*;
+#[cfg(feature = "alloc")]
+pub use offline::*;
+#[cfg(feature = "alloc")]
+pub use oneshot::*;
+#[cfg(feature = "std")]
+pub use pool::*;
+#[cfg(feature = "bpm")]
+pub use prefilter::*;
+#[cfg(feature = "alloc")]
+pub use quickseek::*;
+pub use requirements::*;
+#[cfg(feature = "alloc")]
+pub use resample::*;
+#[cfg(feature = "alloc")]
+pub use scrub::*;
+#[cfg(feature = "alloc")]
+pub use seek_stats::*;
+pub use sink::*;
+pub use stereo::*;
+#[cfg(feature = "std")]
+pub use streaming::*;
+pub use stretcher::*;
+#[cfg(feature = "std")]
+pub use timing::*;
+pub use tuning::*;
+
+/// Compile-time checks that the wrapper types are `Send` but not `Sync`.
+#[allow(dead_code)]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<SoundTouch>();
+    #[cfg(feature = "bpm")]
+    assert_send::<BPMDetect>();
+
+    // `some_item` is ambiguous, and thus fails to compile, if `T: Sync`.
+    trait AmbiguousIfSync<A> {
+        fn some_item() {}
+    }
+    impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+    impl<T: ?Sized + Sync> AmbiguousIfSync<u8> for T {}
+
+    fn assert_not_sync() {
+        let _ = <SoundTouch as AmbiguousIfSync<_>>::some_item;
+        #[cfg(feature = "bpm")]
+        let _ = <BPMDetect as AmbiguousIfSync<_>>::some_item;
+    }
+};
@@ -0,0 +1,192 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::SeekStats;
+
+use crate::{Setting, SoundTouch};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A questionable configuration of a [`SoundTouch`] instance, as returned by
+/// [`SoundTouch::lint`]. The configuration works, but likely not as intended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigLint {
+    /// The effective rate is outside [`ConfigLint::ALIAS_FREE_RATES`] with
+    /// [`Setting::UseAaFilter`] disabled, so the rate transposer produces audible
+    /// aliasing.
+    AliasingRate {
+        /// The effective rate, see [`EffectiveParams`](crate::EffectiveParams).
+        rate: f64,
+    },
+    /// [`Setting::UseQuickseek`] is enabled with a [`Setting::SeekwindowMs`] shorter
+    /// than [`ConfigLint::MIN_QUICKSEEK_WINDOW_MS`], which leaves the quick search too
+    /// few candidates to find good splice points.
+    SmallQuickseekWindow {
+        /// The seek window in milliseconds.
+        seek_window_ms: i32,
+    },
+    /// [`Setting::OverlapMs`] is at least as long as an explicitly set
+    /// [`Setting::SequenceMs`], so consecutive sequences are all overlap.
+    OverlapNotShorterThanSequence {
+        /// The overlap in milliseconds.
+        overlap_ms: i32,
+        /// The sequence length in milliseconds.
+        sequence_ms: i32,
+    },
+    /// The sample rate is below [`ConfigLint::MIN_SAMPLE_RATE`], where the default
+    /// sequence and overlap lengths span very few samples.
+    LowSampleRate {
+        /// The sample rate in Hz.
+        sample_rate: u32,
+    },
+    /// More than [`SeekStats::MAX_EDGE_HIT_RATIO`] of the splice points found in the
+    /// [collected seek statistics] were at the edge of the seek window, so
+    /// [`Setting::SeekwindowMs`] is likely too small for the material.
+    ///
+    /// [`SeekStats::MAX_EDGE_HIT_RATIO`]: crate::SeekStats::MAX_EDGE_HIT_RATIO
+    /// [collected seek statistics]: SoundTouch::set_collect_seek_stats
+    SmallSeekWindow {
+        /// The fraction of the searches that ended at the edge.
+        edge_hit_ratio: f64,
+    },
+}
+
+impl ConfigLint {
+    /// Effective rates the rate transposer handles without its anti-alias filter.
+    pub const ALIAS_FREE_RATES: core::ops::RangeInclusive<f64> = 0.5..=2.0;
+
+    /// Shortest seek window, in milliseconds, not reported with quickseek.
+    pub const MIN_QUICKSEEK_WINDOW_MS: i32 = 10;
+
+    /// Lowest sample rate, in Hz, not reported.
+    pub const MIN_SAMPLE_RATE: u32 = 8000;
+
+    /// Returns the kebab-case name of the lint, e.g. `"aliasing-rate"`, which stays the
+    /// same across releases, for use as a key in an app.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ConfigLint::AliasingRate { .. } => "aliasing-rate",
+            ConfigLint::SmallQuickseekWindow { .. } => "small-quickseek-window",
+            ConfigLint::OverlapNotShorterThanSequence { .. } => "overlap-not-shorter-than-sequence",
+            ConfigLint::LowSampleRate { .. } => "low-sample-rate",
+            ConfigLint::SmallSeekWindow { .. } => "small-seek-window",
+        }
+    }
+}
+
+impl fmt::Display for ConfigLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AliasingRate { rate } => write!(
+                f,
+                "effective rate {rate} with the anti-alias filter disabled will alias"
+            ),
+            Self::SmallQuickseekWindow { seek_window_ms } => write!(
+                f,
+                "quickseek with a seek window of {seek_window_ms} ms finds poor splice points"
+            ),
+            Self::OverlapNotShorterThanSequence {
+                overlap_ms,
+                sequence_ms,
+            } => write!(
+                f,
+                "overlap of {overlap_ms} ms isn't shorter than the sequence of {sequence_ms} ms"
+            ),
+            Self::LowSampleRate { sample_rate } => {
+                write!(
+                    f,
+                    "sample rate of {sample_rate} Hz is too low to stretch well"
+                )
+            }
+            Self::SmallSeekWindow { edge_hit_ratio } => write!(
+                f,
+                "seek window likely too small: {:.0}% of the splice points were at its edge",
+                edge_hit_ratio * 100.0
+            ),
+        }
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns warnings about questionable parts of the current configuration, e.g. to
+    /// show them in a UI. An empty list means nothing was found.
+    ///
+    /// [`Setting::SequenceMs`] and [`Setting::SeekwindowMs`] are only checked when they
+    /// are set explicitly, not when SoundTouch picks them automatically. The seek window
+    /// is also checked against the audio processed so far if [seek statistics] are
+    /// collected.
+    ///
+    /// With the `tracing` feature, [`set_rate`], [`set_rate_change`] and
+    /// [`set_setting`] also log a warning for [`ConfigLint::AliasingRate`].
+    ///
+    /// [`set_rate`]: SoundTouch::set_rate
+    /// [`set_rate_change`]: SoundTouch::set_rate_change
+    /// [`set_setting`]: SoundTouch::set_setting
+    /// [seek statistics]: SoundTouch::set_collect_seek_stats
+    #[cfg(feature = "alloc")]
+    pub fn lint(&self) -> Vec<ConfigLint> {
+        let mut lints = Vec::new();
+        lints.extend(self.aliasing_lint());
+
+        let seek_window_ms = self.get_setting(Setting::SeekwindowMs);
+        if self.get_setting(Setting::UseQuickseek) != 0
+            && (1..ConfigLint::MIN_QUICKSEEK_WINDOW_MS).contains(&seek_window_ms)
+        {
+            lints.push(ConfigLint::SmallQuickseekWindow { seek_window_ms });
+        }
+
+        let overlap_ms = self.get_setting(Setting::OverlapMs);
+        let sequence_ms = self.get_setting(Setting::SequenceMs);
+        if sequence_ms > 0 && overlap_ms >= sequence_ms {
+            lints.push(ConfigLint::OverlapNotShorterThanSequence {
+                overlap_ms,
+                sequence_ms,
+            });
+        }
+
+        match self.sample_rate() {
+            Some(sample_rate) if sample_rate < ConfigLint::MIN_SAMPLE_RATE => {
+                lints.push(ConfigLint::LowSampleRate { sample_rate });
+            }
+            _ => {}
+        }
+
+        let stats = self.seek_stats();
+        if stats.blocks >= SeekStats::MIN_LINT_BLOCKS
+            && stats.edge_hit_ratio() > SeekStats::MAX_EDGE_HIT_RATIO
+        {
+            lints.push(ConfigLint::SmallSeekWindow {
+                edge_hit_ratio: stats.edge_hit_ratio(),
+            });
+        }
+        lints
+    }
+
+    #[cfg(any(feature = "alloc", feature = "tracing"))]
+    fn aliasing_lint(&self) -> Option<ConfigLint> {
+        let rate = self.effective_parameters().rate;
+        let aliasing = self.get_setting(Setting::UseAaFilter) == 0
+            && !ConfigLint::ALIAS_FREE_RATES.contains(&rate);
+        aliasing.then_some(ConfigLint::AliasingRate { rate })
+    }
+
+    /// Logs a warning if the effective rate aliases, with the `tracing` feature.
+    pub(crate) fn warn_aliasing(&self) {
+        #[cfg(feature = "tracing")]
+        if let Some(lint) = self.aliasing_lint() {
+            let levels = self.buffer_levels();
+            tracing::warn!(
+                id = self.id(),
+                unprocessed_frames = levels.unprocessed_frames,
+                ready_frames = levels.ready_frames,
+                "{lint}"
+            );
+        }
+    }
+}
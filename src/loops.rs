@@ -0,0 +1,120 @@
+//! Stretching loops to a new tempo so they still loop.
+//!
+//! A loop that is a few frames too long or too short drifts against the beat every
+//! time it repeats, and the splice from its end back to its start clicks unless the
+//! audio happens to line up. [`stretch_loop`] renders the loop to exactly the length of
+//! its beats at the target tempo and crossfades the seam.
+//!
+//! ```rust
+//! use soundtouch::loops::stretch_loop;
+//!
+//! // Two beats at 120 BPM, stretched to 100 BPM.
+//! let samples = vec![0.0; 44100 * 2];
+//! let stretched = stretch_loop(&samples, 2, 44100, Some(120.0), 100.0, 2);
+//! assert_eq!(stretched.len(), 52920 * 2);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::oneshot::{configured, render};
+use crate::BPMDetect;
+
+/// Length of the crossfade at the loop point, in seconds.
+const SEAM_CROSSFADE_SECONDS: f64 = 0.01;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns the number of frames `beats` beats take at `bpm` beats per minute, rounded
+/// to the nearest frame.
+///
+/// # Panics
+///
+/// Panics if `bpm` isn't positive and finite.
+pub fn loop_frames(beats: u32, bpm: f64, sample_rate: u32) -> usize {
+    assert!(
+        bpm > 0.0 && bpm.is_finite(),
+        "invalid tempo of {bpm} beats per minute"
+    );
+    (beats as f64 * 60.0 / bpm * sample_rate as f64 + 0.5) as usize
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Stretches the loop of `beats` beats at the start of the interleaved `samples` with
+/// `channels` channels from `source_bpm` to `target_bpm`, keeping the pitch.
+///
+/// The output has exactly [`loop_frames`]`(beats, target_bpm, sample_rate)` frames, so
+/// it can be repeated without drifting. Its end is rendered on into the start of the
+/// loop and crossfaded over the first 10 ms, so the seam doesn't click when the output
+/// is repeated.
+///
+/// The loop is the first `loop_frames(beats, source_bpm, sample_rate)` frames of
+/// `samples`, or all of them if there are fewer. With `source_bpm` set to `None`, the
+/// tempo is detected with [`BPMDetect`], and if none is found, all of `samples` are
+/// taken as the loop. A trailing partial frame in `samples` is ignored.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`SoundTouch::set_channels`] or
+/// [`SoundTouch::set_sample_rate`], if `samples` has no whole frame, if `beats` is `0`,
+/// if a tempo isn't positive and finite, or if the resulting tempo change is outside
+/// what [`SoundTouch::try_set_tempo`] accepts.
+///
+/// [`SoundTouch::set_channels`]: crate::SoundTouch::set_channels
+/// [`SoundTouch::set_sample_rate`]: crate::SoundTouch::set_sample_rate
+/// [`SoundTouch::try_set_tempo`]: crate::SoundTouch::try_set_tempo
+pub fn stretch_loop(
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+    source_bpm: Option<f64>,
+    target_bpm: f64,
+    beats: u32,
+) -> Vec<f32> {
+    assert!(beats > 0, "a loop needs at least one beat");
+    let mut soundtouch = configured(channels, sample_rate);
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    assert!(frames > 0, "a loop needs at least one frame");
+
+    let source_bpm = source_bpm.or_else(|| {
+        let mut detector = BPMDetect::new(channels as u32, sample_rate);
+        detector.input_samples(&samples[..frames * channels]);
+        let bpm = detector.get_bpm();
+        (bpm > 0.0).then_some(bpm as f64)
+    });
+    let loop_in = match source_bpm {
+        Some(bpm) => loop_frames(beats, bpm, sample_rate).clamp(1, frames),
+        None => frames,
+    };
+    let target = loop_frames(beats, target_bpm, sample_rate);
+    if target == 0 {
+        return Vec::new();
+    }
+    let tempo = loop_in as f64 / target as f64;
+    soundtouch.try_set_tempo(tempo).or_panic();
+
+    // The start of the loop is put again after its end, so the output runs on past the
+    // loop point by the length of the crossfade.
+    let crossfade = ((SEAM_CROSSFADE_SECONDS * sample_rate as f64) as usize).min(target / 2);
+    let wrap = ((crossfade as f64 * tempo) as usize + 1).min(loop_in);
+    let mut input = Vec::with_capacity((loop_in + wrap) * channels);
+    input.extend_from_slice(&samples[..loop_in * channels]);
+    input.extend_from_slice(&samples[..wrap * channels]);
+    let mut output = render(&mut soundtouch, &input, 0, target + crossfade);
+
+    let (head, overhang) = output.split_at_mut(target * channels);
+    for (frame, (head, overhang)) in head
+        .chunks_exact_mut(channels)
+        .zip(overhang.chunks_exact(channels))
+        .enumerate()
+    {
+        let weight = (frame as f32 + 0.5) / crossfade as f32;
+        for (sample, &run_on) in head.iter_mut().zip(overhang) {
+            *sample = weight * *sample + (1.0 - weight) * run_on;
+        }
+    }
+    output.truncate(target * channels);
+    output
+}
@@ -0,0 +1,291 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_PI_2, LN_10, LOG2_10, PI};
+
+use crate::math::{exp2, floor, ln, sin, sqrt};
+use crate::SoundTouch;
+
+/// Steps of 100 ms making up a gating block of 400 ms, which overlap by 75%.
+const BLOCK_STEPS: usize = 4;
+
+/// Blocks at or below this loudness, in LUFS, are left out.
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks this many LU below the loudness of the blocks above the absolute gate are left
+/// out.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Width of the histogram bins the block loudnesses are gated in, in LU.
+const BIN_LU: f64 = 0.1;
+
+/// Bins from the absolute gate up to +10 LUFS. Louder blocks go into the last one.
+const BINS: usize = 800;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Loudness statistics of the input of a [`SoundTouch`] instance, measured with
+/// [`SoundTouch::set_analyze_loudness`], e.g. for replay gain.
+///
+/// [`approx_lufs`](LoudnessReport::approx_lufs) follows ITU-R BS.1770: the input is
+/// K-weighted, the mean square is taken over blocks of 400 ms overlapping by 75%, and
+/// blocks below -70 LUFS and then those 10 LU below the loudness of the rest are gated
+/// out. It isn't certified, and differs from the standard in that all channels are
+/// weighted equally, the surround channels included and the LFE channel not left out,
+/// and that the relative gate is applied in steps of 0.1 LU.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LoudnessReport {
+    /// Largest absolute sample value.
+    pub peak: f32,
+    /// Root mean square of the samples of all channels in dBFS, i.e. `0.0` for a square
+    /// wave at full scale, or negative infinity for silence.
+    pub rms_db: f32,
+    /// Gated loudness in LUFS, or negative infinity if no block of 400 ms passed the
+    /// gates, e.g. for silence or shorter input.
+    pub approx_lufs: f32,
+    /// Number of frames measured.
+    pub frames_measured: u64,
+}
+
+/// The K-weighting filter of BS.1770 at a sample rate: a high shelf of about +4 dB
+/// above 1.5 kHz followed by a high-pass at 38 Hz, each a second-order section.
+#[derive(Debug, Clone, Copy)]
+struct KWeighting {
+    /// Coefficients `b0, b1, b2, a1, a2` of the sections, normalized to `a0 = 1`.
+    sections: [[f64; 5]; 2],
+}
+
+impl KWeighting {
+    /// Designs the filter at `sample_rate`, with the analog prototypes that give the
+    /// coefficients of the standard at 48 kHz.
+    fn new(sample_rate: u32) -> Self {
+        let tan = |x: f64| sin(x) / sin(x + FRAC_PI_2);
+
+        let k = tan(PI * 1681.974450955533 / sample_rate as f64);
+        let q = 0.7071752369554196;
+        let gain_db = 3.999843853973347;
+        let vh = exp2(gain_db / 20.0 * LOG2_10);
+        let vb = exp2(0.4996667741545416 * gain_db / 20.0 * LOG2_10);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        ];
+
+        let k = tan(PI * 38.13547087602444 / sample_rate as f64);
+        let q = 0.5003270373238773;
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = [
+            1.0,
+            -2.0,
+            1.0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        ];
+        Self {
+            sections: [shelf, high_pass],
+        }
+    }
+
+    /// Filters `sample` with the transposed direct form II `state` of the channel.
+    fn apply(&self, sample: f64, state: &mut [[f64; 2]; 2]) -> f64 {
+        let mut x = sample;
+        for ([b0, b1, b2, a1, a2], state) in self.sections.iter().zip(state) {
+            let y = b0 * x + state[0];
+            state[0] = b1 * x - a1 * y + state[1];
+            state[1] = b2 * x - a2 * y;
+            x = y;
+        }
+        x
+    }
+}
+
+/// Returns the loudness in LUFS of the K-weighted mean square `energy`.
+fn loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * ln(energy) / LN_10
+}
+
+/// Measures the input for a [`LoudnessReport`] as it is put.
+#[derive(Debug, Clone)]
+pub(crate) struct LoudnessMeter {
+    channels: usize,
+    sample_rate: u32,
+    filter: KWeighting,
+    /// Per channel, the states of the two sections of the filter.
+    states: Vec<[[f64; 2]; 2]>,
+    step_frames: usize,
+    frames_in_step: usize,
+    /// Sum of the squares of the K-weighted samples of the current step.
+    step_energy: f64,
+    /// Sums of the last steps, of which the last `steps` are complete.
+    last_steps: [f64; BLOCK_STEPS],
+    steps: usize,
+    /// Per bin of block loudness above the absolute gate, the number of blocks and the
+    /// sum of their mean squares.
+    histogram: Vec<(u64, f64)>,
+    peak: f32,
+    /// Kept in `f64` so that long streams don't lose the contribution of new samples.
+    sum_of_squares: f64,
+    samples: u64,
+    frames: u64,
+}
+
+impl LoudnessMeter {
+    fn new() -> Self {
+        Self {
+            channels: 0,
+            sample_rate: 0,
+            filter: KWeighting::new(48000),
+            states: Vec::new(),
+            step_frames: 0,
+            frames_in_step: 0,
+            step_energy: 0.0,
+            last_steps: [0.0; BLOCK_STEPS],
+            steps: 0,
+            histogram: vec![(0, 0.0); BINS],
+            peak: 0.0,
+            sum_of_squares: 0.0,
+            samples: 0,
+            frames: 0,
+        }
+    }
+
+    /// Measures the interleaved `samples`. A change of the format starts a new block
+    /// with a new filter, keeping what was measured so far.
+    fn add(&mut self, samples: &[f32], channels: usize, sample_rate: u32) {
+        if (channels, sample_rate) != (self.channels, self.sample_rate) {
+            self.channels = channels;
+            self.sample_rate = sample_rate;
+            self.filter = KWeighting::new(sample_rate);
+            self.states.clear();
+            self.states.resize(channels, [[0.0; 2]; 2]);
+            self.step_frames = (sample_rate as usize / 10).max(1);
+            self.frames_in_step = 0;
+            self.step_energy = 0.0;
+            self.steps = 0;
+        }
+        for frame in samples.chunks_exact(channels) {
+            for (&sample, state) in frame.iter().zip(&mut self.states) {
+                self.peak = self.peak.max(sample.abs());
+                self.sum_of_squares += f64::from(sample) * f64::from(sample);
+                let weighted = self.filter.apply(f64::from(sample), state);
+                self.step_energy += weighted * weighted;
+            }
+            self.frames_in_step += 1;
+            if self.frames_in_step == self.step_frames {
+                self.end_step();
+            }
+        }
+        self.samples += samples.len() as u64;
+        self.frames += (samples.len() / channels) as u64;
+    }
+
+    /// Completes a step, and with it a block once there are enough steps.
+    fn end_step(&mut self) {
+        self.last_steps.rotate_left(1);
+        self.last_steps[BLOCK_STEPS - 1] = self.step_energy;
+        self.steps = (self.steps + 1).min(BLOCK_STEPS);
+        self.step_energy = 0.0;
+        self.frames_in_step = 0;
+        if self.steps < BLOCK_STEPS {
+            return;
+        }
+        let energy = self.last_steps.iter().sum::<f64>() / (BLOCK_STEPS * self.step_frames) as f64;
+        let block = loudness(energy);
+        if block > ABSOLUTE_GATE {
+            let bin = &mut self.histogram[Self::bin(block)];
+            bin.0 += 1;
+            bin.1 += energy;
+        }
+    }
+
+    /// Returns the histogram bin of a block `loudness` above the absolute gate.
+    fn bin(loudness: f64) -> usize {
+        (floor((loudness - ABSOLUTE_GATE) / BIN_LU).max(0.0) as usize).min(BINS - 1)
+    }
+
+    /// Returns the loudness of the blocks in `bins`, or negative infinity without any.
+    fn mean_loudness(bins: &[(u64, f64)]) -> f64 {
+        let (count, energy) = bins.iter().fold((0, 0.0), |(count, energy), bin| {
+            (count + bin.0, energy + bin.1)
+        });
+        if count == 0 {
+            return f64::NEG_INFINITY;
+        }
+        loudness(energy / count as f64)
+    }
+
+    fn report(&self) -> LoudnessReport {
+        let rms = match self.samples {
+            0 => 0.0,
+            samples => sqrt(self.sum_of_squares / samples as f64),
+        };
+        let rms_db = if rms > 0.0 {
+            20.0 * ln(rms) / LN_10
+        } else {
+            f64::NEG_INFINITY
+        };
+        let ungated = Self::mean_loudness(&self.histogram);
+        let approx_lufs = if ungated.is_finite() {
+            let gate = Self::bin((ungated + RELATIVE_GATE).max(ABSOLUTE_GATE));
+            Self::mean_loudness(&self.histogram[gate..])
+        } else {
+            ungated
+        };
+        LoudnessReport {
+            peak: self.peak,
+            rms_db: rms_db as f32,
+            approx_lufs: approx_lufs as f32,
+            frames_measured: self.frames,
+        }
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables measuring the loudness of the input while it is processed,
+    /// as returned by [`loudness`], so that replay gain needs no separate pass.
+    ///
+    /// Every frame put is measured, by [`put_samples`] and everything built on it,
+    /// such as [`generate_audio`], [`process_streaming`] and the [WAV helpers], and
+    /// across streams. The measurement runs in Rust on the input as it is passed to
+    /// SoundTouch and costs two filters per sample; it doesn't change the output.
+    ///
+    /// Disabling it discards the measurement; enabling it again starts from zero.
+    ///
+    /// [`loudness`]: SoundTouch::loudness
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    /// [`process_streaming`]: crate::process_streaming
+    /// [WAV helpers]: crate::wav::process_file_streaming_with_loudness
+    pub fn set_analyze_loudness(&mut self, analyze: bool) -> &mut Self {
+        match (analyze, &self.loudness) {
+            (true, None) => self.loudness = Some(LoudnessMeter::new()),
+            (false, _) => self.loudness = None,
+            (true, Some(_)) => {}
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the loudness of the input measured since [`set_analyze_loudness`]
+    /// enabled it, or `None` if it isn't measured.
+    ///
+    /// [`set_analyze_loudness`]: SoundTouch::set_analyze_loudness
+    pub fn loudness(&self) -> Option<LoudnessReport> {
+        self.loudness.as_ref().map(LoudnessMeter::report)
+    }
+
+    /// Passes `frames` frames of `samples` put into the instance to the loudness meter.
+    pub(crate) fn measure_loudness(&mut self, samples: &[f32], frames: usize) {
+        let channels = self.num_channels() as usize;
+        let sample_rate = self.sample_rate().unwrap_or_default();
+        if let Some(meter) = &mut self.loudness {
+            meter.add(&samples[..frames * channels], channels, sample_rate);
+        }
+    }
+}
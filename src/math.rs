@@ -0,0 +1,84 @@
+/// `f64::sqrt` needs std, so use Newton's method.
+pub(crate) fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let mut y = if x > 1.0 { x / 2.0 } else { 1.0 };
+    for _ in 0..64 {
+        let next = 0.5 * (y + x / y);
+        if next == y {
+            break;
+        }
+        y = next;
+    }
+    y
+}
+
+/// `f64::exp2` needs std, so scale `e^(fraction * ln 2)`, from its series, by the
+/// integer power of two. Exponents beyond the normal range of `f64` are clamped.
+pub(crate) fn exp2(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    let x = x.clamp(-1022.0, 1023.0);
+    let mut whole = x as i32;
+    if (whole as f64) > x {
+        whole -= 1;
+    }
+    let fraction = (x - whole as f64) * core::f64::consts::LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..24 {
+        term *= fraction / n as f64;
+        sum += term;
+    }
+    sum * f64::from_bits(((whole + 1023) as u64) << 52)
+}
+
+/// `f64::exp` needs std, so use [`exp2`].
+pub(crate) fn exp(x: f64) -> f64 {
+    exp2(x * core::f64::consts::LOG2_E)
+}
+
+/// `f64::floor` needs std. Only exact for values that fit into an `i64`.
+pub(crate) fn floor(x: f64) -> f64 {
+    let whole = x as i64 as f64;
+    if whole > x {
+        whole - 1.0
+    } else {
+        whole
+    }
+}
+
+/// `f64::sin` needs std, so reduce `x` to `-π..=π` and sum the Taylor series, which is
+/// within rounding of the result there after 16 terms.
+pub(crate) fn sin(x: f64) -> f64 {
+    use core::f64::consts::TAU;
+    let turns = x / TAU;
+    let x = (turns - floor(turns + 0.5)) * TAU;
+    let mut term = x;
+    let mut sum = x;
+    for n in 1..16 {
+        term *= -x * x / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+    }
+    sum
+}
+
+/// `f64::ln` needs std, so refine the estimate from the binary exponent with Halley's
+/// method. Returns NaN for values that aren't positive.
+pub(crate) fn ln(x: f64) -> f64 {
+    if x.is_nan() || x <= 0.0 {
+        return f64::NAN;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    let exponent = ((x.to_bits() >> 52) & 0x7ff) as i64 - 1023;
+    let mut y = exponent as f64 * core::f64::consts::LN_2;
+    for _ in 0..8 {
+        let e = exp(y);
+        y += 2.0 * (x - e) / (x + e);
+    }
+    y
+}
@@ -0,0 +1,59 @@
+use core::mem::size_of;
+
+use crate::SoundTouch;
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns an estimate of the bytes held by the instance, for budgeting memory per
+    /// stream.
+    ///
+    /// It counts the ready and unprocessed frames, the working buffers of the
+    /// time-stretcher (about the [initial latency] plus one [output sequence]) and the
+    /// size of the instance itself. SoundTouch's buffers keep their capacity after they
+    /// drain, so a buffer that grew while the consumer stalled can hold more memory than
+    /// this until the instance is dropped.
+    ///
+    /// [initial latency]: crate::BufferRequirements::initial_latency_frames
+    /// [output sequence]: crate::BufferRequirements::nominal_output_frames
+    pub fn approx_memory_usage(&self) -> usize {
+        let requirements = self.buffer_requirements();
+        let frames = self.pipeline_ready_frames()
+            + self.converted_frames()
+            + self.pending_input_frames()
+            + requirements.initial_latency_frames
+            + requirements.nominal_output_frames;
+        size_of::<Self>() + frames * self.num_channels() as usize * size_of::<f32>()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Caps the output waiting to be received, so that a stalled consumer can't make the
+    /// buffers grow without bounds. With `Some(max_frames)`, [`try_put_samples`] and the
+    /// other ways of putting input return [`SoundTouchError::Backpressure`] while more
+    /// than `max_frames` frames are ready, and the panicking variants panic. `None`, the
+    /// default, removes the cap.
+    ///
+    /// The check happens before the input is put, so the output of that input can take
+    /// the backlog past the cap once: it stays below `max_frames` plus the output of the
+    /// largest put, of the input that was still unprocessed (up to the [initial
+    /// latency]) and of one more [output sequence]. Flushing isn't capped.
+    ///
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    /// [`SoundTouchError::Backpressure`]: crate::SoundTouchError::Backpressure
+    /// [initial latency]: crate::BufferRequirements::initial_latency_frames
+    /// [output sequence]: crate::BufferRequirements::nominal_output_frames
+    pub fn set_max_pending_output_frames(&mut self, max_frames: Option<usize>) -> &mut Self {
+        self.max_pending_output = max_frames;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the cap set with [`set_max_pending_output_frames`].
+    ///
+    /// [`set_max_pending_output_frames`]: SoundTouch::set_max_pending_output_frames
+    pub fn max_pending_output_frames(&self) -> Option<usize> {
+        self.max_pending_output
+    }
+}
@@ -0,0 +1,196 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::error::OrPanic;
+use crate::{Config, Setting, SoundTouch};
+
+/// Number of input frames put at a time while waiting for output.
+const INPUT_CHUNK: usize = 4096;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Renders arbitrary ranges of the stretched version of a whole recording, as an
+/// editor does to draw or audition part of the result, without processing everything
+/// before the range.
+///
+/// Output frames are mapped back to input frames with the [input/output ratio] of the
+/// configuration. To render a range, the processing starts a pre-roll before the
+/// corresponding input, so that the time-stretcher has settled by the first requested
+/// frame, and the output up to that frame is dropped. The pipeline stays primed
+/// afterwards: a range starting where the previous one ended, or a little later,
+/// continues from there, and a range overlapping the previous one reuses its output.
+///
+/// Ranges rendered that way from the start are exactly the output of a single
+/// [`generate_audio`] run. A range rendered after jumping elsewhere is the same audio,
+/// but as the time-stretcher picks its splice points anew, it can be offset from the
+/// single run by up to a processing sequence and differs from it sample by sample.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use soundtouch::{Config, OfflineStretcher};
+///
+/// let recording: Arc<[f32]> = vec![0.0; 44100 * 2 * 10].into();
+/// let mut stretcher = OfflineStretcher::new(recording, Config::new().set_tempo(0.5));
+/// // One second of the stretched audio, starting 8 s in.
+/// let region = stretcher.render_range(8 * 44100..9 * 44100);
+/// assert_eq!(region.len(), 44100 * 2);
+/// ```
+///
+/// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+/// [`generate_audio`]: SoundTouch::generate_audio
+pub struct OfflineStretcher {
+    soundtouch: SoundTouch,
+    samples: Arc<[f32]>,
+    channels: usize,
+    /// Output frames per input frame.
+    ratio: f64,
+    /// Input frames processed before the first requested frame after a jump.
+    preroll: usize,
+    /// Next input frame to put.
+    next_input: usize,
+    /// Frame of the output that the next received frame belongs to, or `None` before the
+    /// first range.
+    next_output: Option<usize>,
+    /// The output of the last range that was processed, which ends at `next_output`.
+    last: Vec<f32>,
+    last_start: usize,
+}
+
+impl OfflineStretcher {
+    /// Creates a stretcher for the interleaved `samples`, processed with `config`. A
+    /// trailing partial frame is ignored.
+    ///
+    /// The pre-roll is the [`InitialLatency`] of the configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config` is [invalid].
+    ///
+    /// [`InitialLatency`]: Setting::InitialLatency
+    /// [invalid]: Config::validate
+    pub fn new(samples: Arc<[f32]>, config: &Config) -> Self {
+        config.validate().or_panic();
+        let mut soundtouch = SoundTouch::with_config(config);
+        let ratio = soundtouch.get_input_output_sample_ratio();
+        let preroll = soundtouch.get_setting(Setting::InitialLatency).max(0) as usize;
+        Self {
+            soundtouch,
+            samples,
+            channels: config.channels() as usize,
+            ratio,
+            preroll,
+            next_input: 0,
+            next_output: None,
+            last: Vec::new(),
+            last_start: 0,
+        }
+    }
+
+    /// Returns the number of input frames.
+    pub fn input_frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// Returns the expected number of output frames, the input frames times the
+    /// input/output ratio, rounded. Flushing adds a little silence after them, which
+    /// ranges can extend into.
+    pub fn expected_output_frames(&self) -> usize {
+        (self.input_frames() as f64 * self.ratio + 0.5) as usize
+    }
+
+    /// Returns the interleaved output frames in `out_range`. The output is shorter if
+    /// the range extends past the end of the flushed output.
+    pub fn render_range(&mut self, out_range: Range<usize>) -> Vec<f32> {
+        let Range { start, end } = out_range;
+        let channels = self.channels;
+        let mut out = Vec::with_capacity(end.saturating_sub(start) * channels);
+        if end <= start {
+            return out;
+        }
+
+        let mut position = start;
+        if let Some(next) = self.next_output {
+            if (self.last_start..next).contains(&start) {
+                let cached = end.min(next);
+                out.extend_from_slice(
+                    &self.last[(start - self.last_start) * channels
+                        ..(cached - self.last_start) * channels],
+                );
+                position = cached;
+            }
+        }
+        if position == end {
+            return out;
+        }
+
+        let next = match self.next_output {
+            // Skipping ahead is cheaper than a new pre-roll.
+            Some(next)
+                if position >= next
+                    && ((position - next) as f64) <= self.preroll as f64 * self.ratio =>
+            {
+                next
+            }
+            _ => self.jump_to(position),
+        };
+        self.skip(position - next);
+        let received = self.receive(end - position, &mut out);
+
+        self.next_output = Some(position + received);
+        self.last.clear();
+        self.last.extend_from_slice(&out);
+        self.last_start = start;
+        out
+    }
+
+    /// Restarts the processing a pre-roll before the input of output frame `frame` and
+    /// returns the output frame the pipeline starts at.
+    fn jump_to(&mut self, frame: usize) -> usize {
+        self.soundtouch.clear();
+        let input = ((frame as f64 / self.ratio) as usize).min(self.input_frames());
+        self.next_input = input.saturating_sub(self.preroll);
+        let next = (self.next_input as f64 * self.ratio + 0.5) as usize;
+        // Rounding can't put the start after `frame`, but keep the subtraction safe.
+        next.min(frame)
+    }
+
+    /// Puts input until `frames` output frames are ready, or flushes at the end of the
+    /// input, and returns the number of ready frames, at most `frames`.
+    fn fill(&mut self, frames: usize) -> usize {
+        let channels = self.channels;
+        while self.soundtouch.available_frames() < frames && !self.soundtouch.is_flushed() {
+            let chunk = INPUT_CHUNK.min(self.input_frames() - self.next_input);
+            if chunk == 0 {
+                self.soundtouch.flush();
+                break;
+            }
+            let from = self.next_input * channels;
+            self.soundtouch
+                .put_samples(&self.samples[from..from + chunk * channels], chunk);
+            self.next_input += chunk;
+        }
+        self.soundtouch.available_frames().min(frames)
+    }
+
+    fn skip(&mut self, mut frames: usize) {
+        while frames > 0 {
+            let ready = self.fill(frames.min(INPUT_CHUNK));
+            if ready == 0 {
+                break;
+            }
+            frames -= self.soundtouch.skip_samples(ready);
+        }
+    }
+
+    /// Appends up to `frames` output frames to `out` and returns how many.
+    fn receive(&mut self, frames: usize, out: &mut Vec<f32>) -> usize {
+        let ready = self.fill(frames);
+        let len = out.len();
+        out.resize(len + ready * self.channels, 0.0);
+        let received = self.soundtouch.receive_samples(&mut out[len..], ready);
+        out.truncate(len + received * self.channels);
+        received
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::error::OrPanic;
+use crate::math::exp2;
+use crate::resample::{impulse_delay, PROBE_FRAMES};
+use crate::units::Frames;
+use crate::{SoundTouch, SoundTouchError};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Shifts the pitch of interleaved `samples` with `channels` channels by `semitones`,
+/// keeping their duration. Fractional semitones are fine, e.g. `0.5` for a quarter
+/// tone.
+///
+/// The output has exactly as many frames as the input and is aligned with it: the
+/// delay of the pipeline is trimmed from the start and the end is completed from
+/// silence pushed through after the input. A trailing partial frame in `samples` is
+/// ignored.
+///
+/// ```rust
+/// let samples = vec![0.0; 44100 * 2];
+/// // Up a major third.
+/// let shifted = soundtouch::pitch_shift(&samples, 2, 44100, 4.0);
+/// assert_eq!(shifted.len(), samples.len());
+/// ```
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`SoundTouch::set_channels`] or
+/// [`SoundTouch::set_sample_rate`], or if the pitch is outside what
+/// [`SoundTouch::try_set_pitch`] accepts.
+pub fn pitch_shift(samples: &[f32], channels: u32, sample_rate: u32, semitones: f64) -> Vec<f32> {
+    let pitch = exp2(semitones / 12.0);
+    let mut soundtouch = configured(channels, sample_rate);
+    soundtouch.try_set_pitch(pitch).or_panic();
+
+    // The time-stretcher keeps its output aligned with the input, so the delay is that of
+    // the rate transposer. It runs before the time-stretcher when lowering the pitch,
+    // which then shortens its output, delay included, by the pitch factor.
+    let mut transposer = configured(1, sample_rate);
+    transposer.try_set_rate(pitch).or_panic();
+    let delay = impulse_delay(
+        &mut transposer,
+        ((PROBE_FRAMES / 2) as f64 / pitch) as usize,
+    );
+    let latency = if pitch <= 1.0 {
+        (delay as f64 * pitch + 0.5) as usize
+    } else {
+        delay
+    };
+
+    let frames = samples.len() / channels as usize;
+    render(&mut soundtouch, samples, latency, frames)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Changes the duration of interleaved `samples` with `channels` channels by `factor`,
+/// keeping their pitch: `2.0` makes them twice as long, `0.5` half as long.
+///
+/// The output has `round(frames * factor)` frames and is aligned with the input, the
+/// end completed from silence pushed through after the input. A trailing partial frame
+/// in `samples` is ignored.
+///
+/// ```rust
+/// let samples = vec![0.0; 44100 * 2];
+/// let stretched = soundtouch::time_stretch(&samples, 2, 44100, 1.5);
+/// assert_eq!(stretched.len(), 66150 * 2);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`SoundTouch::set_channels`] or
+/// [`SoundTouch::set_sample_rate`], or if the tempo `1 / factor` is outside what
+/// [`SoundTouch::try_set_tempo`] accepts.
+pub fn time_stretch(samples: &[f32], channels: u32, sample_rate: u32, factor: f64) -> Vec<f32> {
+    let mut soundtouch = configured(channels, sample_rate);
+    soundtouch.try_set_tempo(1.0 / factor).or_panic();
+    let frames = samples.len() / channels as usize;
+    let target = (frames as f64 * factor + 0.5) as usize;
+    render(&mut soundtouch, samples, 0, target)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Changes the duration of interleaved `samples` with `channels` channels to `target`,
+/// keeping their pitch.
+///
+/// `target` is converted to frames with [`Frames::from_duration`], rounding to the
+/// nearest frame with halves rounded up, and the output has exactly that many frames.
+/// Otherwise this is [`time_stretch`] by the ratio of the target frames to the input
+/// frames.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// let samples = vec![0.0; 44100 * 2];
+/// let stretched =
+///     soundtouch::time_stretch_to_duration(&samples, 2, 44100, Duration::from_millis(1500));
+/// assert_eq!(stretched.len(), 66150 * 2);
+/// ```
+///
+/// # Panics
+///
+/// Panics on the errors [`try_time_stretch_to_duration`] returns.
+pub fn time_stretch_to_duration(
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+    target: Duration,
+) -> Vec<f32> {
+    try_time_stretch_to_duration(samples, channels, sample_rate, target).or_panic()
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`time_stretch_to_duration`], but returns an error instead of panicking.
+///
+/// # Errors
+///
+/// Returns [`SoundTouchError::InvalidChannels`] or [`SoundTouchError::InvalidSampleRate`]
+/// for an invalid `channels` or `sample_rate`, [`SoundTouchError::InputTooShort`] if
+/// `samples` holds no whole frame, and [`SoundTouchError::ParametersOutOfRange`] if
+/// reaching `target` takes a tempo outside what [`SoundTouch::try_set_tempo`] accepts,
+/// including a `target` too long to count in frames.
+pub fn try_time_stretch_to_duration(
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+    target: Duration,
+) -> Result<Vec<f32>, SoundTouchError> {
+    let mut soundtouch = try_configured(channels, sample_rate)?;
+    let frames = samples.len() / channels as usize;
+    if frames == 0 {
+        return Err(SoundTouchError::InputTooShort {
+            frames: 1,
+            channels: channels as usize,
+            len: samples.len(),
+        });
+    }
+    // A target that overflows is far beyond the slowest tempo anyway.
+    let target = Frames::from_duration(target, sample_rate).map_or(usize::MAX, |Frames(n)| n);
+    soundtouch.try_set_tempo(frames as f64 / target as f64)?;
+    Ok(render(&mut soundtouch, samples, 0, target))
+}
+
+pub(crate) fn configured(channels: u32, sample_rate: u32) -> SoundTouch {
+    try_configured(channels, sample_rate).or_panic()
+}
+
+fn try_configured(channels: u32, sample_rate: u32) -> Result<SoundTouch, SoundTouchError> {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.try_set_channels(channels)?;
+    soundtouch.try_set_sample_rate(sample_rate)?;
+    Ok(soundtouch)
+}
+/// Processes `samples` and returns `target` output frames, starting `latency` frames
+/// into the output.
+pub(crate) fn render(
+    soundtouch: &mut SoundTouch,
+    samples: &[f32],
+    latency: usize,
+    target: usize,
+) -> Vec<f32> {
+    let channels = soundtouch.num_channels() as usize;
+    let frames = samples.len() / channels;
+    let ratio = soundtouch.get_input_output_sample_ratio();
+
+    // Enough silence to push the last `latency` output frames out of the pipeline.
+    let padding = (latency as f64 / ratio) as usize + 1;
+    soundtouch.put_samples(samples, frames);
+    soundtouch.put_samples(&vec![0.0; padding * channels], padding);
+    soundtouch.flush();
+    soundtouch.skip_samples(latency);
+
+    // `flush` completes the output to its expected length, so at most a frame of
+    // rounding is left as silence here.
+    let mut output = vec![0.0; target * channels];
+    soundtouch.receive_samples(&mut output, target);
+    output
+}
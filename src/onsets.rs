@@ -0,0 +1,190 @@
+//! Finding the onsets of notes and hits, the starts of the sounds a loop can be sliced
+//! at.
+//!
+//! [`BPMDetect`] finds the tempo and coarse beat positions; [`detect_onsets`] finds where
+//! each sound starts to within a few milliseconds, whether it is on the beat or not. Use
+//! [`BeatGrid::snap_onsets`] to line them up with the beat.
+//!
+//! ```rust
+//! use soundtouch::onsets::{detect_onsets, OnsetConfig};
+//!
+//! // A click at 0.25 s in half a second of silence.
+//! let mut samples = vec![0.0; 22050];
+//! for (i, sample) in samples[11025..11200].iter_mut().enumerate() {
+//!     *sample = if i % 20 < 10 { 0.8 } else { -0.8 };
+//! }
+//! let onsets = detect_onsets(&samples, 1, 44100, OnsetConfig::default());
+//! assert_eq!(onsets.len(), 1);
+//! assert!((onsets[0].time_secs - 0.25).abs() < 0.002);
+//! ```
+//!
+//! [`BPMDetect`]: crate::BPMDetect
+//! [`BeatGrid::snap_onsets`]: crate::bpm::BeatGrid::snap_onsets
+
+use alloc::vec::Vec;
+
+use crate::math::sqrt;
+use crate::Downmix;
+
+/// Hops on either side whose mean sets the adaptive part of the threshold.
+const THRESHOLD_WINDOW_HOPS: usize = 16;
+
+/// Fraction of the peak amplitude of the attack at which its onset is placed.
+const ATTACK_LEVEL: f32 = 0.5;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// An onset found by [`detect_onsets`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Onset {
+    /// Position of the onset in seconds from the start of the input.
+    pub time_secs: f64,
+    /// Strength of the onset relative to the strongest one, in `0.0..=1.0`.
+    pub strength: f32,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The parameters of [`detect_onsets`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnsetConfig {
+    /// Length of the hops the level is measured over, in milliseconds. Shorter hops
+    /// separate closer onsets but react more to noise. Defaults to `5.0`.
+    pub hop_ms: f64,
+    /// How many times the mean rise of the level around a hop its rise has to exceed to
+    /// count as an onset. Defaults to `1.5`.
+    pub sensitivity: f64,
+    /// Smallest strength of an onset, relative to the strongest rise in the input, so
+    /// that quiet passages don't produce onsets from noise. Defaults to `0.1`.
+    pub min_strength: f32,
+    /// Shortest time between two onsets in milliseconds; of closer ones, only the
+    /// strongest is kept. Defaults to `50.0`.
+    pub min_interval_ms: f64,
+    /// How multichannel input is mixed down before analysis. Defaults to
+    /// [`Downmix::Average`].
+    pub downmix: Downmix,
+}
+
+impl Default for OnsetConfig {
+    fn default() -> Self {
+        Self {
+            hop_ms: 5.0,
+            sensitivity: 1.5,
+            min_strength: 0.1,
+            min_interval_ms: 50.0,
+            downmix: Downmix::Average,
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Finds the onsets in the interleaved `samples` with `channels` channels, in the order
+/// they occur.
+///
+/// The input is mixed down to mono and its RMS level measured over hops of
+/// [`OnsetConfig::hop_ms`]. A hop whose level rises more than those around it, by the
+/// [sensitivity] and by at least the [minimum strength], is an onset. Its time is then
+/// refined to the first sample of the attack that reaches half its peak amplitude, so
+/// sharp attacks are placed to within a few samples rather than a hop.
+///
+/// A trailing partial frame in `samples` is ignored. Silence and steady sounds have no
+/// onsets.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is `0`.
+///
+/// [sensitivity]: OnsetConfig::sensitivity
+/// [minimum strength]: OnsetConfig::min_strength
+pub fn detect_onsets(
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+    config: OnsetConfig,
+) -> Vec<Onset> {
+    assert!(channels > 0, "onset detection needs at least one channel");
+    assert!(sample_rate > 0, "onset detection needs a sample rate");
+    let mono: Vec<f32> = samples
+        .chunks_exact(channels as usize)
+        .map(|frame| config.downmix.mix(frame))
+        .collect();
+    let hop = ((config.hop_ms * sample_rate as f64 / 1000.0) as usize).max(1);
+
+    let levels: Vec<f64> = mono
+        .chunks(hop)
+        .map(|hop| {
+            let energy: f64 = hop
+                .iter()
+                .map(|&sample| sample as f64 * sample as f64)
+                .sum();
+            sqrt(energy / hop.len() as f64)
+        })
+        .collect();
+    let mut rises = Vec::with_capacity(levels.len());
+    rises.push(levels.first().copied().unwrap_or(0.0));
+    rises.extend(levels.windows(2).map(|pair| (pair[1] - pair[0]).max(0.0)));
+    let max_rise = rises.iter().copied().fold(0.0, f64::max);
+    if max_rise <= 0.0 {
+        return Vec::new();
+    }
+
+    let min_interval = (config.min_interval_ms * sample_rate as f64 / 1000.0) as usize;
+    let mut onsets: Vec<(usize, f64)> = Vec::new();
+    for index in 0..rises.len() {
+        let rise = rises[index];
+        let window = &rises[index.saturating_sub(THRESHOLD_WINDOW_HOPS)
+            ..(index + THRESHOLD_WINDOW_HOPS + 1).min(rises.len())];
+        let mean = window.iter().sum::<f64>() / window.len() as f64;
+        let is_peak = rises
+            .get(index.wrapping_sub(1))
+            .is_none_or(|&before| rise > before)
+            && rises.get(index + 1).is_none_or(|&after| rise >= after);
+        if !is_peak
+            || rise <= config.sensitivity * mean
+            || rise < config.min_strength as f64 * max_rise
+        {
+            continue;
+        }
+
+        // The attack may have begun in the hop before if that already rose.
+        let from_hop = if index > 0 && rises[index - 1] > 0.0 {
+            index - 1
+        } else {
+            index
+        };
+        let frame = attack_start(&mono, from_hop, index, hop);
+        match onsets.last_mut() {
+            Some(last) if frame.saturating_sub(last.0) < min_interval => {
+                if rise > last.1 {
+                    *last = (frame, rise);
+                }
+            }
+            _ => onsets.push((frame, rise)),
+        }
+    }
+
+    onsets
+        .into_iter()
+        .map(|(frame, rise)| Onset {
+            time_secs: frame as f64 / sample_rate as f64,
+            strength: (rise / max_rise) as f32,
+        })
+        .collect()
+}
+
+/// Returns the first frame from the start of hop `from_hop` to the end of hop `to_hop`
+/// that reaches [`ATTACK_LEVEL`] of their peak amplitude.
+fn attack_start(mono: &[f32], from_hop: usize, to_hop: usize, hop: usize) -> usize {
+    let start = from_hop * hop;
+    let end = ((to_hop + 1) * hop).min(mono.len());
+    let attack = &mono[start..end];
+    let peak = attack
+        .iter()
+        .fold(0.0f32, |peak, &sample| peak.max(sample.abs()));
+    let offset = attack
+        .iter()
+        .position(|&sample| sample.abs() >= ATTACK_LEVEL * peak)
+        .unwrap_or(0);
+    start + offset
+}
@@ -0,0 +1,110 @@
+use crate::sound_touch::STACK_BUFFER_SAMPLES;
+use crate::{Sanitize, SoundTouch, SoundTouchError};
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Puts `frames` frames of planar input, one slice per channel, e.g. the planes of a
+    /// decoded packet from a decoder such as symphonia, without interleaving them into a
+    /// buffer of their own first.
+    ///
+    /// The planes are interleaved in chunks on the stack and put like with
+    /// [`try_put_samples`], so the result is bit for bit that of putting the interleaved
+    /// samples. Mono and stereo, the common cases, are interleaved with loops of their
+    /// own that the compiler can vectorize. Putting `0` frames does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::ChannelMismatch`] if there isn't one plane per channel,
+    /// [`SoundTouchError::InputTooShort`] if a plane holds fewer than `frames` samples,
+    /// with `channels` set to `1`, and otherwise the errors of [`try_put_samples`]. A
+    /// [`NonFiniteInput`] index is that of the sample in the interleaved input. These are
+    /// checked before anything is put.
+    ///
+    /// ```rust
+    /// use soundtouch::SoundTouch;
+    ///
+    /// let mut soundtouch = SoundTouch::new();
+    /// soundtouch.set_channels(2).set_sample_rate(48000).set_tempo(1.25);
+    /// let left = [0.25; 1024];
+    /// let right = [-0.25; 1024];
+    /// soundtouch.put_audio_buffer(&[&left, &right], 1024).unwrap();
+    /// soundtouch.flush();
+    /// assert!(soundtouch.available_frames() > 0);
+    /// ```
+    ///
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    /// [`NonFiniteInput`]: SoundTouchError::NonFiniteInput
+    pub fn put_audio_buffer(
+        &mut self,
+        planes: &[&[f32]],
+        frames: usize,
+    ) -> Result<(), SoundTouchError> {
+        if frames == 0 {
+            return Ok(());
+        }
+        self.begin_put(frames)?;
+        let channels = self.num_channels() as usize;
+        if planes.len() != channels {
+            return Err(SoundTouchError::ChannelMismatch {
+                expected: channels as u32,
+                actual: planes.len() as u32,
+            });
+        }
+        if let Some(plane) = planes.iter().find(|plane| plane.len() < frames) {
+            return Err(SoundTouchError::InputTooShort {
+                frames,
+                channels: 1,
+                len: plane.len(),
+            });
+        }
+        if self.input_sanitization() == Sanitize::Error {
+            let index = planes
+                .iter()
+                .enumerate()
+                .filter_map(|(channel, plane)| {
+                    let frame = plane[..frames].iter().position(|s| !s.is_finite())?;
+                    Some(frame * channels + channel)
+                })
+                .min();
+            if let Some(index) = index {
+                return Err(SoundTouchError::NonFiniteInput { index });
+            }
+        }
+
+        let chunk_frames = STACK_BUFFER_SAMPLES / channels;
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        let mut offset = 0;
+        while offset < frames {
+            let chunk = (frames - offset).min(chunk_frames);
+            let interleaved = &mut buffer[..chunk * channels];
+            interleave(planes, offset..offset + chunk, interleaved);
+            self.try_put_samples(interleaved, chunk)?;
+            offset += chunk;
+        }
+        Ok(())
+    }
+}
+
+/// Interleaves the `frames` of `planes` into `out`, which holds exactly that many frames.
+fn interleave(planes: &[&[f32]], frames: core::ops::Range<usize>, out: &mut [f32]) {
+    match planes {
+        [mono] => out.copy_from_slice(&mono[frames]),
+        [left, right] => {
+            let (left, right) = (&left[frames.clone()], &right[frames]);
+            for ((frame, &l), &r) in out.chunks_exact_mut(2).zip(left).zip(right) {
+                frame[0] = l;
+                frame[1] = r;
+            }
+        }
+        _ => {
+            let channels = planes.len();
+            for (channel, plane) in planes.iter().enumerate() {
+                let samples = out[channel..].iter_mut().step_by(channels);
+                for (out, &sample) in samples.zip(&plane[frames.clone()]) {
+                    *out = sample;
+                }
+            }
+        }
+    }
+}
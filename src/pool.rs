@@ -0,0 +1,130 @@
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::vec::Vec;
+
+use crate::{Config, SoundTouch};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A pool of reusable [`SoundTouch`] instances for servers that process many
+/// short-lived streams concurrently.
+///
+/// [`get`] hands out an idle instance that has been [`reset`] and reconfigured,
+/// or creates a new one if none is idle. The instance is returned to the pool
+/// when the [`PooledSoundTouch`] guard is dropped, unless the pool already holds
+/// `max_size` idle instances, in which case it is destroyed.
+///
+/// ```rust
+/// use soundtouch::{Config, SoundTouchPool};
+///
+/// let pool = SoundTouchPool::new(8);
+/// let mut config = Config::new();
+/// config.set_tempo(1.25);
+///
+/// let mut soundtouch = pool.get(&config);
+/// let output = soundtouch.generate_audio(&[0.0; 44100 * 2]);
+/// ```
+///
+/// [`get`]: SoundTouchPool::get
+/// [`reset`]: SoundTouch::reset
+#[derive(Debug)]
+pub struct SoundTouchPool {
+    idle: Mutex<Vec<SoundTouch>>,
+    max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Usage counters of a [`SoundTouchPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolMetrics {
+    /// Number of [`get`](SoundTouchPool::get) calls served by an idle instance.
+    pub hits: u64,
+    /// Number of [`get`](SoundTouchPool::get) calls that had to create a new instance.
+    pub misses: u64,
+    /// Number of instances currently idle in the pool.
+    pub idle: usize,
+}
+
+impl SoundTouchPool {
+    /// Creates an empty pool that keeps at most `max_size` idle instances.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::with_capacity(max_size)),
+            max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the maximum number of idle instances kept by the pool.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Checks out an instance configured with `config`.
+    pub fn get(&self, config: &Config) -> PooledSoundTouch<'_> {
+        let idle = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let soundtouch = match idle {
+            Some(mut soundtouch) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                soundtouch.reset();
+                soundtouch.apply_config(config);
+                soundtouch
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                SoundTouch::with_config(config)
+            }
+        };
+        PooledSoundTouch {
+            pool: self,
+            soundtouch: ManuallyDrop::new(soundtouch),
+        }
+    }
+
+    /// Returns the usage counters of the pool.
+    pub fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            idle: self.idle.lock().unwrap_or_else(|e| e.into_inner()).len(),
+        }
+    }
+}
+
+/// A [`SoundTouch`] instance checked out from a [`SoundTouchPool`].
+///
+/// Dereferences to [`SoundTouch`] and returns the instance to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledSoundTouch<'a> {
+    pool: &'a SoundTouchPool,
+    soundtouch: ManuallyDrop<SoundTouch>,
+}
+
+impl Deref for PooledSoundTouch<'_> {
+    type Target = SoundTouch;
+
+    fn deref(&self) -> &SoundTouch {
+        &self.soundtouch
+    }
+}
+
+impl DerefMut for PooledSoundTouch<'_> {
+    fn deref_mut(&mut self) -> &mut SoundTouch {
+        &mut self.soundtouch
+    }
+}
+
+impl Drop for PooledSoundTouch<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `soundtouch` is not used again after being taken here.
+        let soundtouch = unsafe { ManuallyDrop::take(&mut self.soundtouch) };
+        let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.pool.max_size {
+            idle.push(soundtouch);
+        }
+    }
+}
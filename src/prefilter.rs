@@ -0,0 +1,118 @@
+use core::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, TAU};
+
+use crate::math::{exp, sin};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A low-pass filter [`BPMDetect`] can apply to the mono downmix before analysis, set
+/// with [`BPMDetect::set_prefilter`].
+///
+/// SoundTouch's beat detection keys off the low frequencies, but its decimation only
+/// averages, so broadband noise, e.g. in field recordings, leaks into the envelope and
+/// hides the beat. Filtering it out first helps; a cutoff of about 250 Hz keeps kicks
+/// and bass lines.
+///
+/// The filter is a one-pole low-pass for an `order` of 1 and second-order Butterworth
+/// sections for 2 and 4, with a one-pole low-pass added for 3. Each order adds 6 dB per
+/// octave of attenuation above the cutoff. Every stage is 3 dB down at `cutoff_hz`, so
+/// higher orders attenuate the cutoff itself more.
+///
+/// [`BPMDetect`]: crate::BPMDetect
+/// [`BPMDetect::set_prefilter`]: crate::BPMDetect::set_prefilter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrefilterConfig {
+    /// Cutoff frequency in Hz, below half the sample rate.
+    pub cutoff_hz: f32,
+    /// Order of the filter, in `1..=`[`MAX_ORDER`](PrefilterConfig::MAX_ORDER).
+    pub order: u32,
+}
+
+impl PrefilterConfig {
+    /// Highest supported order.
+    pub const MAX_ORDER: u32 = 4;
+
+    /// Returns a filter with the given cutoff and order.
+    pub const fn new(cutoff_hz: f32, order: u32) -> Self {
+        Self { cutoff_hz, order }
+    }
+
+    /// Returns `true` if the filter can run at `sample_rate`.
+    pub(crate) fn is_valid_for(&self, sample_rate: u32) -> bool {
+        self.cutoff_hz > 0.0
+            && (self.cutoff_hz as f64) < sample_rate as f64 / 2.0
+            && (1..=Self::MAX_ORDER).contains(&self.order)
+    }
+}
+
+impl Default for PrefilterConfig {
+    /// A second-order low-pass at 250 Hz.
+    fn default() -> Self {
+        Self::new(250.0, 2)
+    }
+}
+
+/// The state of a [`PrefilterConfig`] running at a sample rate.
+#[derive(Debug, Clone)]
+pub(crate) struct Prefilter {
+    config: PrefilterConfig,
+    /// Coefficients `b0, b1, b2, a1, a2` of the second-order sections, normalized to
+    /// `a0 = 1`.
+    biquad: [f64; 5],
+    /// Per second-order section, the two states of its transposed direct form II.
+    sections: [[f64; 2]; (PrefilterConfig::MAX_ORDER / 2) as usize],
+    /// Feedback coefficient of the one-pole low-pass.
+    pole: f64,
+    /// Last output of the one-pole low-pass.
+    previous: f64,
+}
+
+impl Prefilter {
+    /// Creates the filter for a `config` that is valid for `sample_rate`.
+    pub(crate) fn new(config: PrefilterConfig, sample_rate: u32) -> Self {
+        let w0 = TAU * config.cutoff_hz as f64 / sample_rate as f64;
+        let (sin_w0, cos_w0) = (sin(w0), sin(w0 + FRAC_PI_2));
+        // The low-pass of the Audio EQ Cookbook with a Q of 1/√2.
+        let alpha = sin_w0 * FRAC_1_SQRT_2;
+        let a0 = 1.0 + alpha;
+        let b0 = (1.0 - cos_w0) / 2.0 / a0;
+        let (a1, a2) = (-2.0 * cos_w0 / a0, (1.0 - alpha) / a0);
+        Self {
+            config,
+            biquad: [b0, 2.0 * b0, b0, a1, a2],
+            sections: Default::default(),
+            pole: exp(-w0),
+            previous: 0.0,
+        }
+    }
+
+    pub(crate) fn config(&self) -> PrefilterConfig {
+        self.config
+    }
+
+    /// Forgets the previous samples, for a new stream.
+    pub(crate) fn reset(&mut self) {
+        self.sections = Default::default();
+        self.previous = 0.0;
+    }
+
+    /// Filters the mono `samples` in place.
+    pub(crate) fn apply(&mut self, samples: &mut [f32]) {
+        let [b0, b1, b2, a1, a2] = self.biquad;
+        let sections = (self.config.order / 2) as usize;
+        let one_pole = self.config.order % 2 == 1;
+        for sample in samples {
+            let mut x = f64::from(*sample);
+            for state in &mut self.sections[..sections] {
+                let y = b0 * x + state[0];
+                state[0] = b1 * x - a1 * y + state[1];
+                state[1] = b2 * x - a2 * y;
+                x = y;
+            }
+            if one_pole {
+                x = (1.0 - self.pole) * x + self.pole * self.previous;
+                self.previous = x;
+            }
+            *sample = x as f32;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::align::{aligned, best_alignment};
+use crate::math::sqrt;
+use crate::{Config, Setting, SoundTouch};
+
+/// Largest offset, in frames, searched when aligning the two outputs.
+const MAX_LAG: usize = 64;
+
+/// The result of [`compare_quickseek`].
+///
+/// # Interpreting the report
+///
+/// Quickseek only changes where the time-stretcher splices the sequences of the
+/// input together, so the two outputs are made of the same audio at slightly
+/// different offsets. Small differences are expected and not necessarily audible.
+///
+/// - [`relative_rms_difference`] is the most useful single number. Below about
+///   `0.05` (-26 dB) the difference is typically inaudible; above `0.3`
+///   (-10 dB) the splice points differ enough that transients and tonal
+///   material may sound rougher with quickseek enabled.
+/// - [`peak_difference`] flags isolated clicks that the RMS value averages away.
+/// - [`cpu_time_ratio`] tells how much faster quickseek was. If it is close to
+///   `1.0` there is little reason to enable it.
+///
+/// [`relative_rms_difference`]: QuickseekReport::relative_rms_difference
+/// [`peak_difference`]: QuickseekReport::peak_difference
+/// [`cpu_time_ratio`]: QuickseekReport::cpu_time_ratio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuickseekReport {
+    /// Offset in frames of the quickseek output relative to the full-search
+    /// output that aligns them best. Positive means the quickseek output lags.
+    pub lag: isize,
+    /// Number of aligned frames the differences were measured over.
+    pub frames: usize,
+    /// RMS of the full-search output over the aligned frames.
+    pub reference_rms: f64,
+    /// RMS of the difference between the aligned outputs.
+    pub rms_difference: f64,
+    /// Largest absolute difference of a single sample between the aligned outputs.
+    pub peak_difference: f32,
+    /// Processing time with quickseek divided by the time without, or `None`
+    /// without the `std` feature, which is needed to measure time.
+    pub cpu_time_ratio: Option<f64>,
+}
+
+impl QuickseekReport {
+    /// Returns [`rms_difference`] relative to [`reference_rms`], or `0.0` if the
+    /// reference is silent.
+    ///
+    /// [`rms_difference`]: QuickseekReport::rms_difference
+    /// [`reference_rms`]: QuickseekReport::reference_rms
+    pub fn relative_rms_difference(&self) -> f64 {
+        if self.reference_rms > 0.0 {
+            self.rms_difference / self.reference_rms
+        } else {
+            0.0
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Processes `samples` with `config` twice, with [`Setting::UseQuickseek`] disabled
+/// and enabled, and reports how much the outputs differ and how much faster
+/// quickseek was. See [`QuickseekReport`] for how to read the result.
+///
+/// The outputs can be offset from each other by a few frames, so they are aligned
+/// first with [`best_alignment`], searching offsets of up to 64 frames.
+///
+/// [`best_alignment`]: crate::align::best_alignment
+pub fn compare_quickseek(samples: &[f32], config: &Config) -> QuickseekReport {
+    let (reference, reference_time) = process(samples, config, false);
+    let (quick, quick_time) = process(samples, config, true);
+    let channels = config.channels() as usize;
+
+    let lag = best_alignment(&reference, &quick, channels, MAX_LAG).offset;
+    let (reference, quick) = aligned(&reference, &quick, channels, lag);
+
+    let mut reference_energy = 0.0;
+    let mut difference_energy = 0.0;
+    let mut peak_difference: f32 = 0.0;
+    for (&a, &b) in reference.iter().zip(quick) {
+        let difference = a - b;
+        reference_energy += f64::from(a) * f64::from(a);
+        difference_energy += f64::from(difference) * f64::from(difference);
+        peak_difference = peak_difference.max(difference.abs());
+    }
+    let len = reference.len().max(1) as f64;
+
+    QuickseekReport {
+        lag,
+        frames: reference.len() / channels,
+        reference_rms: sqrt(reference_energy / len),
+        rms_difference: sqrt(difference_energy / len),
+        peak_difference,
+        cpu_time_ratio: match (quick_time, reference_time) {
+            (Some(quick), Some(reference)) if reference > 0.0 => Some(quick / reference),
+            _ => None,
+        },
+    }
+}
+
+/// Processes `samples`, returning the output and the time it took in seconds.
+fn process(
+    samples: &[f32],
+    config: &Config,
+    quickseek: bool,
+) -> (alloc::vec::Vec<f32>, Option<f64>) {
+    let mut soundtouch = SoundTouch::with_config(config);
+    soundtouch.set_setting(Setting::UseQuickseek, quickseek as i32);
+    #[cfg(feature = "std")]
+    {
+        let start = std::time::Instant::now();
+        let output = soundtouch.generate_audio(samples);
+        (output, Some(start.elapsed().as_secs_f64()))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        (soundtouch.generate_audio(samples), None)
+    }
+}
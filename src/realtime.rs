@@ -0,0 +1,229 @@
+//! Glue for driving a [`SoundTouch`] instance from an audio callback.
+//!
+//! [`ParamMailbox`] carries tempo, pitch and rate changes from UI or control threads to
+//! the audio thread without locks: the audio thread never waits for a writer, so a
+//! preempted low-priority writer can't stall it.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use soundtouch::realtime::{ParamMailbox, Params};
+//! use soundtouch::{Scratch, SoundTouch};
+//!
+//! let mailbox = Arc::new(ParamMailbox::new(Params::default()));
+//! let mut soundtouch = SoundTouch::new();
+//! soundtouch.set_channels(2).set_sample_rate(44100);
+//!
+//! // On the UI thread:
+//! mailbox.set_tempo(1.25);
+//!
+//! // In the audio callback, before every block:
+//! let mut version = 0;
+//! let mut scratch = Scratch::for_channels(2);
+//! let (input, mut output) = ([0.0; 512], [0.0; 512]);
+//! mailbox.apply_to(&mut soundtouch, &mut version).unwrap();
+//! soundtouch.process_block(&input, &mut output, &mut scratch);
+//! assert_eq!(soundtouch.effective_parameters().virtual_tempo, 1.25);
+//! ```
+//!
+//! With the `std` feature, [`AdaptiveQuality`] times the blocks and trades quality for
+//! speed while processing falls behind real time.
+
+use core::hint::spin_loop;
+use core::sync::atomic::{fence, AtomicU64, Ordering};
+
+use crate::{EffectiveParams, SoundTouch, SoundTouchError};
+
+#[cfg(feature = "std")]
+mod adaptive;
+#[cfg(feature = "std")]
+pub use adaptive::*;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The parameters carried by a [`ParamMailbox`], as set with [`SoundTouch::set_tempo`],
+/// [`SoundTouch::set_pitch`] and [`SoundTouch::set_rate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    /// The tempo.
+    pub tempo: f64,
+    /// The pitch.
+    pub pitch: f64,
+    /// The rate.
+    pub rate: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            tempo: 1.0,
+            pitch: 1.0,
+            rate: 1.0,
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A lock-free mailbox holding the latest [`Params`], written from any thread and
+/// read by the processing thread at block boundaries.
+///
+/// It is a sequence lock over atomics. Writers take turns, spinning briefly while
+/// another writer is storing. The reader never waits: [`read`] returns `None` if it
+/// overlaps a write, and the processing side keeps its current parameters and picks
+/// the new ones up at the next block. Every successful read sees the values of a single
+/// write, never a mix of two.
+///
+/// [`read`]: ParamMailbox::read
+#[derive(Debug)]
+pub struct ParamMailbox {
+    /// Twice the number of completed writes, plus one while a write is in progress.
+    sequence: AtomicU64,
+    tempo: AtomicU64,
+    pitch: AtomicU64,
+    rate: AtomicU64,
+}
+
+impl ParamMailbox {
+    /// Creates a mailbox holding `params` as version `0`.
+    pub fn new(params: Params) -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            tempo: AtomicU64::new(params.tempo.to_bits()),
+            pitch: AtomicU64::new(params.pitch.to_bits()),
+            rate: AtomicU64::new(params.rate.to_bits()),
+        }
+    }
+
+    /// Stores a new tempo, keeping the pitch and rate.
+    pub fn set_tempo(&self, tempo: f64) {
+        self.update(|params| params.tempo = tempo);
+    }
+
+    /// Stores a new pitch, keeping the tempo and rate.
+    pub fn set_pitch(&self, pitch: f64) {
+        self.update(|params| params.pitch = pitch);
+    }
+
+    /// Stores a new rate, keeping the tempo and pitch.
+    pub fn set_rate(&self, rate: f64) {
+        self.update(|params| params.rate = rate);
+    }
+
+    /// Stores all parameters at once.
+    pub fn set(&self, params: Params) {
+        self.update(|current| *current = params);
+    }
+
+    /// Returns the number of writes so far.
+    pub fn version(&self) -> u64 {
+        self.sequence.load(Ordering::Acquire) / 2
+    }
+
+    /// Returns the latest parameters and their [version], or `None` if a write is in
+    /// progress. Never waits.
+    ///
+    /// [version]: ParamMailbox::version
+    pub fn read(&self) -> Option<(u64, Params)> {
+        let before = self.sequence.load(Ordering::Acquire);
+        if before % 2 == 1 {
+            return None;
+        }
+        let params = self.load();
+        fence(Ordering::Acquire);
+        let after = self.sequence.load(Ordering::Relaxed);
+        (before == after).then_some((before / 2, params))
+    }
+
+    /// Returns the latest parameters if they are newer than `*last_version`, and
+    /// updates it. Returns `None` if nothing changed or a write is in progress.
+    ///
+    /// Start with the [version] at the time the processing side was configured, or `0`
+    /// for a new mailbox, to only get later changes.
+    ///
+    /// [version]: ParamMailbox::version
+    pub fn read_new(&self, last_version: &mut u64) -> Option<Params> {
+        match self.read() {
+            Some((version, params)) if version != *last_version => {
+                *last_version = version;
+                Some(params)
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the latest parameters to `soundtouch` if they are newer than
+    /// `*last_version`, see [`read_new`], and returns whether it did. Call it at every
+    /// block boundary.
+    ///
+    /// The setters run on the calling thread, including the callback set with
+    /// [`SoundTouch::on_settings_changed`] when the buffer requirements change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::ParametersOutOfRange`] if the new parameters are
+    /// outside what [`SoundTouch::try_set_tempo`] and its siblings accept, e.g. NaN.
+    /// `soundtouch` then keeps its current parameters. `*last_version` is updated all
+    /// the same, so the rejected parameters aren't tried again at every block.
+    ///
+    /// [`read_new`]: ParamMailbox::read_new
+    pub fn apply_to(
+        &self,
+        soundtouch: &mut SoundTouch,
+        last_version: &mut u64,
+    ) -> Result<bool, SoundTouchError> {
+        let Some(params) = self.read_new(last_version) else {
+            return Ok(false);
+        };
+        EffectiveParams::new(params.tempo, params.rate, params.pitch).check()?;
+        soundtouch
+            .set_tempo(params.tempo)
+            .set_pitch(params.pitch)
+            .set_rate(params.rate);
+        Ok(true)
+    }
+
+    fn load(&self) -> Params {
+        Params {
+            tempo: f64::from_bits(self.tempo.load(Ordering::Relaxed)),
+            pitch: f64::from_bits(self.pitch.load(Ordering::Relaxed)),
+            rate: f64::from_bits(self.rate.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Params)) {
+        // An odd sequence number marks the write in progress and keeps other writers out.
+        let mut sequence = self.sequence.load(Ordering::Relaxed);
+        loop {
+            if sequence % 2 == 1 {
+                spin_loop();
+                sequence = self.sequence.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.sequence.compare_exchange_weak(
+                sequence,
+                sequence + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => sequence = current,
+            }
+        }
+        // Orders the odd sequence number before the stores of the values.
+        fence(Ordering::Release);
+
+        let mut params = self.load();
+        change(&mut params);
+        self.tempo.store(params.tempo.to_bits(), Ordering::Relaxed);
+        self.pitch.store(params.pitch.to_bits(), Ordering::Relaxed);
+        self.rate.store(params.rate.to_bits(), Ordering::Relaxed);
+        self.sequence.store(sequence + 2, Ordering::Release);
+    }
+}
+
+impl Default for ParamMailbox {
+    fn default() -> Self {
+        Self::new(Params::default())
+    }
+}
@@ -0,0 +1,354 @@
+use std::boxed::Box;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::error::OrPanic;
+use crate::{BlockResult, Scratch, Setting, SoundTouch, SoundTouchError};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The source of time an [`AdaptiveQuality`] measures blocks with. [`SystemClock`]
+/// reads the monotonic system clock; tests can substitute a clock they advance by hand.
+pub trait Clock {
+    /// Returns the time since an arbitrary fixed origin.
+    fn now(&mut self) -> Duration;
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A [`Clock`] reading [`Instant::now`].
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How far an [`AdaptiveQuality`] has lowered the quality, from the best to the
+/// cheapest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum QualityLevel {
+    /// The settings as configured.
+    #[default]
+    Full,
+    /// [`Setting::UseQuickseek`] enabled.
+    Quickseek,
+    /// [`Setting::UseQuickseek`] enabled and [`Setting::SeekwindowMs`] shortened to
+    /// [`AdaptiveConfig::short_seek_window_ms`].
+    ShortSeekWindow,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A change of the [`QualityLevel`], passed to the callback set with
+/// [`AdaptiveQuality::on_transition`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityTransition {
+    /// The level before the change.
+    pub from: QualityLevel,
+    /// The level after the change.
+    pub to: QualityLevel,
+    /// The load of the block that triggered the change: its processing time divided by
+    /// its duration.
+    pub load: f64,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The thresholds of an [`AdaptiveQuality`]. The gap between [`degrade_load`] and
+/// [`recover_load`] and the counts of blocks keep it from switching back and forth.
+///
+/// [`degrade_load`]: AdaptiveConfig::degrade_load
+/// [`recover_load`]: AdaptiveConfig::recover_load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveConfig {
+    /// Load above which a block counts as overloaded. Defaults to `0.8`.
+    pub degrade_load: f64,
+    /// Load below which a block counts as having headroom. Defaults to `0.5`.
+    pub recover_load: f64,
+    /// Consecutive overloaded blocks before the quality is lowered one level. Defaults
+    /// to `4`.
+    pub degrade_after: u32,
+    /// Consecutive blocks with headroom before the quality is raised one level.
+    /// Defaults to `64`.
+    pub recover_after: u32,
+    /// Seek window length in milliseconds of [`QualityLevel::ShortSeekWindow`], or
+    /// `None` to stop at [`QualityLevel::Quickseek`]. Defaults to `Some(10)`.
+    pub short_seek_window_ms: Option<i32>,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            degrade_load: 0.8,
+            recover_load: 0.5,
+            degrade_after: 4,
+            recover_after: 64,
+            short_seek_window_ms: Some(10),
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Lowers the quality of a [`SoundTouch`] instance while processing can't keep up with
+/// real time, and raises it again once it can, so that a slow device degrades the
+/// sound rather than dropping out.
+///
+/// Process every block through [`process_block`], or time blocks yourself and pass
+/// them to [`observe`]. The load of a block is its processing time divided by its
+/// duration at the sample rate. After [`degrade_after`] consecutive blocks above
+/// [`degrade_load`], the quality drops one [`QualityLevel`]: first
+/// [`Setting::UseQuickseek`] is enabled, then [`Setting::SeekwindowMs`] is shortened.
+/// After [`recover_after`] consecutive blocks below [`recover_load`], it goes back up
+/// one level and the setting it changed is restored to the value it had before. Each
+/// change is passed to the [transition callback].
+///
+/// Changing these settings doesn't allocate, so neither does processing through the
+/// controller, apart from whatever the callback does. With the automatic seek window,
+/// restoring it fixes it at the length it had when it was shortened.
+///
+/// ```rust
+/// use soundtouch::realtime::{AdaptiveConfig, AdaptiveQuality};
+/// use soundtouch::{Scratch, SoundTouch};
+///
+/// let mut soundtouch = SoundTouch::new();
+/// soundtouch.set_channels(2).set_sample_rate(44100).set_tempo(2.0);
+/// let mut adaptive = AdaptiveQuality::new(AdaptiveConfig::default());
+/// adaptive.on_transition(|transition| eprintln!("quality {:?}", transition.to));
+///
+/// // In the audio callback:
+/// let mut scratch = Scratch::for_channels(2);
+/// let (input, mut output) = ([0.0; 1024], [0.0; 1024]);
+/// adaptive.process_block(&mut soundtouch, &input, &mut output, &mut scratch);
+/// ```
+///
+/// [`process_block`]: AdaptiveQuality::process_block
+/// [`observe`]: AdaptiveQuality::observe
+/// [`degrade_after`]: AdaptiveConfig::degrade_after
+/// [`degrade_load`]: AdaptiveConfig::degrade_load
+/// [`recover_after`]: AdaptiveConfig::recover_after
+/// [`recover_load`]: AdaptiveConfig::recover_load
+/// [transition callback]: AdaptiveQuality::on_transition
+pub struct AdaptiveQuality<C = SystemClock> {
+    config: AdaptiveConfig,
+    clock: C,
+    level: QualityLevel,
+    /// Consecutive blocks above the degrade load, or below the recover load.
+    overloaded: u32,
+    relaxed: u32,
+    /// The settings as they were before they were changed.
+    saved_quickseek: i32,
+    saved_seek_window_ms: i32,
+    callback: Option<Box<dyn FnMut(QualityTransition) + Send>>,
+}
+
+impl AdaptiveQuality {
+    /// Creates a controller with `config` that measures blocks with the system clock.
+    pub fn new(config: AdaptiveConfig) -> Self {
+        Self::with_clock(config, SystemClock::default())
+    }
+}
+
+impl<C: Clock> AdaptiveQuality<C> {
+    /// Creates a controller with `config` that measures blocks with `clock`.
+    pub fn with_clock(config: AdaptiveConfig, clock: C) -> Self {
+        Self {
+            config,
+            clock,
+            level: QualityLevel::Full,
+            overloaded: 0,
+            relaxed: 0,
+            saved_quickseek: 0,
+            saved_seek_window_ms: 0,
+            callback: None,
+        }
+    }
+
+    /// Sets a callback that is called with every change of the [level], from the
+    /// thread processing the blocks. It replaces any previous callback.
+    ///
+    /// [level]: AdaptiveQuality::level
+    pub fn on_transition(
+        &mut self,
+        callback: impl FnMut(QualityTransition) + Send + 'static,
+    ) -> &mut Self {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the thresholds.
+    pub fn config(&self) -> &AdaptiveConfig {
+        &self.config
+    }
+
+    /// Returns the current quality level.
+    pub fn level(&self) -> QualityLevel {
+        self.level
+    }
+
+    /// Returns the clock, e.g. to advance a test clock.
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Processes a block with [`SoundTouch::process_block`] and adapts the quality to
+    /// the time it took. The duration of the block is that of the longer of `input` and
+    /// `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`SoundTouch::process_block`] does.
+    pub fn process_block(
+        &mut self,
+        soundtouch: &mut SoundTouch,
+        input: &[f32],
+        output: &mut [f32],
+        scratch: &mut Scratch,
+    ) -> BlockResult {
+        self.try_process_block(soundtouch, input, output, scratch)
+            .or_panic()
+    }
+
+    /// Like [`process_block`], but returns an error instead of panicking. Failed blocks
+    /// aren't counted.
+    ///
+    /// [`process_block`]: AdaptiveQuality::process_block
+    pub fn try_process_block(
+        &mut self,
+        soundtouch: &mut SoundTouch,
+        input: &[f32],
+        output: &mut [f32],
+        scratch: &mut Scratch,
+    ) -> Result<BlockResult, SoundTouchError> {
+        let start = self.clock.now();
+        let result = soundtouch.try_process_block(input, output, scratch)?;
+        let elapsed = self.clock.now().saturating_sub(start);
+        let channels = soundtouch.num_channels().max(1) as usize;
+        let frames = input.len().max(output.len()) / channels;
+        self.observe(soundtouch, frames, elapsed);
+        Ok(result)
+    }
+
+    /// Counts a block of `frames` frames that took `elapsed` to process, and changes the
+    /// quality of `soundtouch` if that was the last block needed to. Blocks of no frames
+    /// and instances without a sample rate are ignored.
+    pub fn observe(&mut self, soundtouch: &mut SoundTouch, frames: usize, elapsed: Duration) {
+        let sample_rate = soundtouch.sample_rate().unwrap_or(0);
+        if frames == 0 || sample_rate == 0 {
+            return;
+        }
+        let budget = frames as f64 / sample_rate as f64;
+        let load = elapsed.as_secs_f64() / budget;
+
+        if load > self.config.degrade_load {
+            self.overloaded += 1;
+            self.relaxed = 0;
+        } else if load < self.config.recover_load {
+            self.relaxed += 1;
+            self.overloaded = 0;
+        } else {
+            self.overloaded = 0;
+            self.relaxed = 0;
+        }
+
+        if self.overloaded >= self.config.degrade_after.max(1) {
+            if let Some(to) = self.lower_level() {
+                self.transition(soundtouch, to, load);
+            }
+            self.overloaded = 0;
+        } else if self.relaxed >= self.config.recover_after.max(1) {
+            if let Some(to) = self.higher_level() {
+                self.transition(soundtouch, to, load);
+            }
+            self.relaxed = 0;
+        }
+    }
+
+    /// Restores the settings of `soundtouch` changed by the controller and goes back to
+    /// [`QualityLevel::Full`] without calling the callback, e.g. before reconfiguring the
+    /// instance or handing it to code that doesn't use the controller.
+    pub fn restore(&mut self, soundtouch: &mut SoundTouch) {
+        if self.level >= QualityLevel::ShortSeekWindow {
+            soundtouch.set_setting(Setting::SeekwindowMs, self.saved_seek_window_ms);
+        }
+        if self.level >= QualityLevel::Quickseek {
+            soundtouch.set_setting(Setting::UseQuickseek, self.saved_quickseek);
+        }
+        self.level = QualityLevel::Full;
+        self.overloaded = 0;
+        self.relaxed = 0;
+    }
+
+    fn lower_level(&self) -> Option<QualityLevel> {
+        match self.level {
+            QualityLevel::Full => Some(QualityLevel::Quickseek),
+            QualityLevel::Quickseek => self
+                .config
+                .short_seek_window_ms
+                .map(|_| QualityLevel::ShortSeekWindow),
+            QualityLevel::ShortSeekWindow => None,
+        }
+    }
+
+    fn higher_level(&self) -> Option<QualityLevel> {
+        match self.level {
+            QualityLevel::Full => None,
+            QualityLevel::Quickseek => Some(QualityLevel::Full),
+            QualityLevel::ShortSeekWindow => Some(QualityLevel::Quickseek),
+        }
+    }
+
+    fn transition(&mut self, soundtouch: &mut SoundTouch, to: QualityLevel, load: f64) {
+        let from = self.level;
+        match (from, to) {
+            (QualityLevel::Full, QualityLevel::Quickseek) => {
+                self.saved_quickseek = soundtouch.get_setting(Setting::UseQuickseek);
+                soundtouch.set_setting(Setting::UseQuickseek, 1);
+            }
+            (QualityLevel::Quickseek, QualityLevel::ShortSeekWindow) => {
+                self.saved_seek_window_ms = soundtouch.get_setting(Setting::SeekwindowMs);
+                let short = self.config.short_seek_window_ms.unwrap_or(0).max(1);
+                soundtouch.set_setting(Setting::SeekwindowMs, short);
+            }
+            (QualityLevel::ShortSeekWindow, QualityLevel::Quickseek) => {
+                soundtouch.set_setting(Setting::SeekwindowMs, self.saved_seek_window_ms);
+            }
+            (QualityLevel::Quickseek, QualityLevel::Full) => {
+                soundtouch.set_setting(Setting::UseQuickseek, self.saved_quickseek);
+            }
+            _ => return,
+        }
+        self.level = to;
+        if let Some(callback) = &mut self.callback {
+            callback(QualityTransition { from, to, load });
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for AdaptiveQuality<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdaptiveQuality")
+            .field("config", &self.config)
+            .field("clock", &self.clock)
+            .field("level", &self.level)
+            .field("overloaded", &self.overloaded)
+            .field("relaxed", &self.relaxed)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
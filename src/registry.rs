@@ -0,0 +1,153 @@
+//! A process-wide list of live [`SoundTouch`] and [`BPMDetect`] instances, behind the
+//! `registry` feature, for telling streams apart in logs and debugging sessions.
+//!
+//! Every instance registers itself when constructed and deregisters when dropped.
+//! [`snapshot`] lists the live ones with their [ID], format, how many frames went
+//! through them and how many are buffered in them. The per-instance counters are updated
+//! with relaxed atomic operations, so the only other cost is a short lock on
+//! construction and drop.
+//!
+//! ```rust
+//! use soundtouch::{registry, SoundTouch};
+//!
+//! let mut soundtouch = SoundTouch::new();
+//! soundtouch.set_channels(2).set_sample_rate(44100);
+//! soundtouch.put_samples(&[0.0; 2048], 1024);
+//!
+//! let info = registry::snapshot()
+//!     .into_iter()
+//!     .find(|info| info.id == soundtouch.id())
+//!     .unwrap();
+//! assert_eq!(info.kind, "SoundTouch");
+//! assert_eq!(info.frames_put, 1024);
+//! ```
+//!
+//! [`SoundTouch`]: crate::SoundTouch
+//! [`BPMDetect`]: crate::BPMDetect
+//! [ID]: crate::SoundTouch::id
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::vec::Vec;
+
+use crate::BufferLevels;
+
+/// A live instance as listed by [`snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceInfo {
+    /// The ID of the instance, see [`SoundTouch::id`].
+    ///
+    /// [`SoundTouch::id`]: crate::SoundTouch::id
+    pub id: u64,
+    /// `"SoundTouch"` or `"BPMDetect"`.
+    pub kind: &'static str,
+    /// The number of channels, or `0` if it hasn't been set.
+    pub channels: u32,
+    /// The sample rate, or `0` if it hasn't been set.
+    pub sample_rate: u32,
+    /// Frames put into the instance so far.
+    pub frames_put: u64,
+    /// Frames received from the instance so far. Always `0` for [`BPMDetect`].
+    ///
+    /// [`BPMDetect`]: crate::BPMDetect
+    pub frames_received: u64,
+    /// The [buffer levels] after the last put, receive, flush or clear, read together.
+    /// Levels above [`u32::MAX`] frames are listed as that. Always zero for
+    /// [`BPMDetect`].
+    ///
+    /// [buffer levels]: crate::SoundTouch::buffer_levels
+    /// [`BPMDetect`]: crate::BPMDetect
+    pub buffer_levels: BufferLevels,
+}
+
+/// Counters shared between an instance and the registry.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    channels: AtomicU32,
+    sample_rate: AtomicU32,
+    frames_put: AtomicU64,
+    frames_received: AtomicU64,
+    /// Unprocessed frames in the high and ready frames in the low 32 bits, so that a
+    /// snapshot reads both from the same update.
+    buffer_levels: AtomicU64,
+}
+
+impl Stats {
+    pub(crate) fn set_format(&self, channels: u32, sample_rate: u32) {
+        self.channels.store(channels, Ordering::Relaxed);
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_put(&self, frames: usize) {
+        self.frames_put.fetch_add(frames as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_received(&self, frames: usize) {
+        self.frames_received
+            .fetch_add(frames as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_levels(&self, levels: BufferLevels) {
+        let clamp = |frames: usize| frames.min(u32::MAX as usize) as u64;
+        let packed = (clamp(levels.unprocessed_frames) << 32) | clamp(levels.ready_frames);
+        self.buffer_levels.store(packed, Ordering::Relaxed);
+    }
+
+    fn levels(&self) -> BufferLevels {
+        let packed = self.buffer_levels.load(Ordering::Relaxed);
+        BufferLevels {
+            unprocessed_frames: (packed >> 32) as usize,
+            ready_frames: (packed & u32::MAX as u64) as usize,
+        }
+    }
+}
+
+struct Entry {
+    kind: &'static str,
+    stats: Weak<Stats>,
+}
+
+static REGISTRY: Mutex<BTreeMap<u64, Entry>> = Mutex::new(BTreeMap::new());
+
+/// Locks the registry. A panic while it was locked can't leave the map inconsistent,
+/// so poisoning is ignored.
+fn lock() -> MutexGuard<'static, BTreeMap<u64, Entry>> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn register(id: u64, kind: &'static str, channels: u32, sample_rate: u32) -> Arc<Stats> {
+    let stats = Arc::new(Stats::default());
+    stats.set_format(channels, sample_rate);
+    lock().insert(
+        id,
+        Entry {
+            kind,
+            stats: Arc::downgrade(&stats),
+        },
+    );
+    stats
+}
+
+pub(crate) fn deregister(id: u64) {
+    lock().remove(&id);
+}
+
+/// Returns the live instances, ordered by ID, i.e. by construction.
+pub fn snapshot() -> Vec<InstanceInfo> {
+    lock()
+        .iter()
+        .filter_map(|(&id, entry)| {
+            let stats = entry.stats.upgrade()?;
+            Some(InstanceInfo {
+                id,
+                kind: entry.kind,
+                channels: stats.channels.load(Ordering::Relaxed),
+                sample_rate: stats.sample_rate.load(Ordering::Relaxed),
+                frames_put: stats.frames_put.load(Ordering::Relaxed),
+                frames_received: stats.frames_received.load(Ordering::Relaxed),
+                buffer_levels: stats.levels(),
+            })
+        })
+        .collect()
+}
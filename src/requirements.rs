@@ -0,0 +1,182 @@
+use crate::{Setting, SoundTouch};
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The batch sizes and latency of a [`SoundTouch`] instance with its current tempo,
+/// rate, sample rate and settings, for sizing buffers. See
+/// [`SoundTouch::buffer_requirements`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferRequirements {
+    /// Number of channels.
+    pub channels: u32,
+    /// Input frames consumed per processing batch, [`Setting::NominalInputSequence`].
+    pub nominal_input_frames: usize,
+    /// Output frames produced per processing batch, [`Setting::NominalOutputSequence`].
+    pub nominal_output_frames: usize,
+    /// Input frames put before the first output frame, [`Setting::InitialLatency`].
+    pub initial_latency_frames: usize,
+}
+
+impl BufferRequirements {
+    /// Returns the length in samples of a receive buffer that holds one processing
+    /// batch, so that receiving after every batch takes one call.
+    pub fn recommended_buffer_len(&self) -> usize {
+        self.nominal_output_frames * self.channels as usize
+    }
+}
+
+/// The callback set with [`SoundTouch::on_settings_changed`] and the requirements it was
+/// last called with.
+#[cfg(feature = "alloc")]
+pub(crate) struct SettingsHook {
+    callback: Box<dyn FnMut(BufferRequirements) + Send>,
+    last: BufferRequirements,
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the current batch sizes and latency, read from the
+    /// [`NominalInputSequence`], [`NominalOutputSequence`] and [`InitialLatency`]
+    /// settings. They change with the tempo, rate, sample rate and the sequence
+    /// settings.
+    ///
+    /// The latency is `0` while the input is passed around SoundTouch by the [bypass].
+    ///
+    /// [`NominalInputSequence`]: Setting::NominalInputSequence
+    /// [`NominalOutputSequence`]: Setting::NominalOutputSequence
+    /// [`InitialLatency`]: Setting::InitialLatency
+    /// [bypass]: SoundTouch::set_bypass_when_neutral
+    pub fn buffer_requirements(&self) -> BufferRequirements {
+        let frames = |setting| self.get_setting(setting).max(0) as usize;
+        #[cfg(feature = "alloc")]
+        let bypassed = self.bypass_wanted();
+        #[cfg(not(feature = "alloc"))]
+        let bypassed = false;
+        BufferRequirements {
+            channels: self.num_channels(),
+            nominal_input_frames: frames(Setting::NominalInputSequence),
+            nominal_output_frames: frames(Setting::NominalOutputSequence),
+            initial_latency_frames: if bypassed {
+                0
+            } else {
+                frames(Setting::InitialLatency)
+            },
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns how many more input frames to put so that at least
+    /// `desired_output_frames` frames are [available], e.g. to size the reads from the
+    /// source of a pull-model integration rather than feeding it one block at a time.
+    /// It is `0` if enough output is available already.
+    ///
+    /// It is computed from the output already available, the input still pending in
+    /// SoundTouch and the [staging buffer], the [`NominalInputSequence`], the
+    /// [`InitialLatency`] and the ratio of input to output frames. SoundTouch processes
+    /// the input in batches, so it is an upper bound that may exceed the exact count by
+    /// a few batches, but putting that many frames with the current parameters and
+    /// settings always makes at least `desired_output_frames` frames available. If the
+    /// input ends before, [`flush`] the stream to receive the rest.
+    ///
+    /// With an [output block size], the desired frames are rounded up to whole blocks.
+    ///
+    /// [available]: SoundTouch::available_frames
+    /// [staging buffer]: SoundTouch::set_input_staging_frames
+    /// [`NominalInputSequence`]: Setting::NominalInputSequence
+    /// [`InitialLatency`]: Setting::InitialLatency
+    /// [`flush`]: SoundTouch::flush
+    /// [output block size]: SoundTouch::set_output_block_frames
+    pub fn input_needed_for_output(&self, desired_output_frames: usize) -> usize {
+        if self.available_frames() >= desired_output_frames {
+            return 0;
+        }
+        let block = self.output_block_frames().unwrap_or(1).max(1);
+        let desired = desired_output_frames.div_ceil(block) * block;
+        let missing = desired.saturating_sub(self.ready_frames());
+
+        // Frames left in the staging buffer are only put once a batch is full.
+        #[cfg(feature = "alloc")]
+        let staging = self.input_staging_frames().unwrap_or(0);
+        #[cfg(not(feature = "alloc"))]
+        let staging = 0;
+        // The bypass outputs every frame as it is put.
+        #[cfg(feature = "alloc")]
+        if self.bypass_wanted() {
+            return missing + staging;
+        }
+
+        let requirements = self.buffer_requirements();
+        let params = self.effective_parameters();
+        let input = (missing as f64 * params.tempo * params.rate) as usize + 1;
+        // The time-stretcher skips a fractional number of frames per batch, and its
+        // first batch outputs less than the others, so two batches more are put.
+        let batches = 2 * (requirements.nominal_input_frames + 1);
+        let needed = requirements.initial_latency_frames + input + batches;
+
+        // The time-stretcher counts its pending input after the rate transposer when
+        // that comes first, so it is scaled to input frames. Which of the two comes
+        // first depends on the rate and SoundTouch's build, so the scale is the smaller
+        // of the two possible ones.
+        let staged = self.staged_frames();
+        let stretching = self.num_unprocessed_samples() - staged;
+        let scale = params.rate.min(1.0 / params.rate);
+        let pending = staged + (stretching as f64 * scale) as usize;
+        needed.saturating_sub(pending) + staging
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets a callback that is called with the new [buffer requirements] whenever a
+    /// change of the tempo, pitch, rate, channels, sample rate or a setting alters them,
+    /// so that buffers can be resized before the next batch is processed, e.g. by the
+    /// thread changing the parameters rather than the audio callback. It replaces any
+    /// previous callback and isn't called for the current requirements.
+    ///
+    /// The callback runs inside the setter, so it can't call back into the instance.
+    /// [`reset`] removes it.
+    ///
+    /// [buffer requirements]: SoundTouch::buffer_requirements
+    /// [`reset`]: SoundTouch::reset
+    #[cfg(feature = "alloc")]
+    pub fn on_settings_changed(
+        &mut self,
+        callback: impl FnMut(BufferRequirements) + Send + 'static,
+    ) -> &mut Self {
+        self.settings_hook = Some(SettingsHook {
+            callback: Box::new(callback),
+            last: self.buffer_requirements(),
+        });
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Removes the callback set with [`on_settings_changed`].
+    ///
+    /// [`on_settings_changed`]: SoundTouch::on_settings_changed
+    #[cfg(feature = "alloc")]
+    pub fn clear_on_settings_changed(&mut self) -> &mut Self {
+        self.settings_hook = None;
+        self
+    }
+
+    /// Calls the callback set with [`SoundTouch::on_settings_changed`] if the buffer
+    /// requirements changed since it was last called.
+    pub(crate) fn notify_settings_changed(&mut self) {
+        #[cfg(feature = "alloc")]
+        if self.settings_hook.is_some() {
+            let requirements = self.buffer_requirements();
+            if let Some(hook) = &mut self.settings_hook {
+                if hook.last != requirements {
+                    hook.last = requirements;
+                    (hook.callback)(requirements);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::{Setting, SoundTouch};
+
+/// Number of frames of the impulse used to measure the latency of the pipeline.
+pub(crate) const PROBE_FRAMES: usize = 8192;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Converts interleaved `samples` with `channels` channels from `from_hz` to `to_hz`
+/// using SoundTouch's rate transposer, with its anti-alias filter enabled.
+///
+/// The output has `round(frames * to_hz / from_hz)` frames and is aligned with the
+/// input: the latency of the pipeline is measured with an impulse and trimmed from
+/// the start, and the end is completed from zero padding rather than SoundTouch's
+/// flush.
+///
+/// This is a convenience, not a high quality sample rate converter. SoundTouch
+/// interpolates linearly and only has a short anti-alias filter, so some aliasing
+/// and high-frequency loss are audible on critical material. It is fine for speech,
+/// previews and analysis; for mastering or archival conversions use a dedicated
+/// resampler.
+///
+/// # Panics
+///
+/// Panics if `channels` or `from_hz` is invalid for [`SoundTouch::set_channels`] or
+/// [`SoundTouch::set_sample_rate`], or if the conversion ratio is outside what
+/// [`SoundTouch::try_set_rate`] accepts (more than a factor of 8).
+pub fn resample(samples: &[f32], channels: u32, from_hz: u32, to_hz: u32) -> Vec<f32> {
+    let mut soundtouch = resampler(channels, from_hz, to_hz);
+    if from_hz == to_hz {
+        return samples.to_vec();
+    }
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    let target = (frames as u64 * to_hz as u64 + from_hz as u64 / 2) / from_hz as u64;
+    let latency = latency(from_hz, to_hz);
+
+    // Enough silence to push the last `latency` output frames out of the pipeline.
+    let padding = (latency as u64 + 1) * from_hz as u64 / to_hz as u64 + 64;
+    soundtouch.put_samples(samples, frames);
+    soundtouch.put_samples(&vec![0.0; padding as usize * channels], padding as usize);
+    soundtouch.flush();
+    soundtouch.skip_samples(latency);
+
+    let mut output = vec![0.0; target as usize * channels];
+    let received = soundtouch.receive_samples(&mut output, target as usize);
+    output.truncate(received * channels);
+    output
+}
+
+/// Creates a `SoundTouch` converting from `from_hz` to `to_hz`.
+pub(crate) fn resampler(channels: u32, from_hz: u32, to_hz: u32) -> SoundTouch {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.try_set_channels(channels).or_panic();
+    soundtouch.try_set_sample_rate(from_hz).or_panic();
+    soundtouch.set_setting(Setting::UseAaFilter, 1);
+    soundtouch
+        .try_set_rate(from_hz as f64 / to_hz as f64)
+        .or_panic();
+    soundtouch
+}
+
+/// Measures the delay, in output frames, of an impulse through the pipeline.
+fn latency(from_hz: u32, to_hz: u32) -> usize {
+    let expected = (PROBE_FRAMES / 2) as u64 * to_hz as u64 / from_hz as u64;
+    impulse_delay(&mut resampler(1, from_hz, to_hz), expected as usize)
+}
+
+/// Returns how many frames after output frame `expected` an impulse in the middle of
+/// the probe comes out of `soundtouch`, a mono instance.
+pub(crate) fn impulse_delay(soundtouch: &mut SoundTouch, expected: usize) -> usize {
+    let mut impulse = vec![0.0; PROBE_FRAMES];
+    impulse[PROBE_FRAMES / 2] = 1.0;
+    let response = soundtouch.generate_audio(&impulse);
+    let peak = response
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+        .map_or(0, |(index, _)| index);
+    peak.saturating_sub(expected)
+}
@@ -0,0 +1,171 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::{Config, SoundTouch};
+
+/// Default length of the fades at both ends of a window, in milliseconds.
+const DEFAULT_FADE_MS: u32 = 5;
+
+/// Number of frames of silence put at a time to push the last window out of the
+/// pipeline.
+const PADDING_FRAMES: usize = 256;
+
+/// Extra silence pushed through when the cursor stops, in milliseconds, as the
+/// time-stretcher can place the end of its input up to a sequence later than the
+/// expected output length.
+const TAIL_MS: u32 = 100;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Plays short windows of audio around a moving cursor at varying speed, as audio
+/// editors do while the user drags the playhead.
+///
+/// Every window passed to [`scrub_to`] is faded in and out and put into a pipeline
+/// that stays primed between calls, so the windows are joined by short dips
+/// instead of clicks however far apart they are in the source, and nothing has to
+/// be flushed or re-created while the cursor moves. When the cursor stops, the rest
+/// of the last window is pushed out with silence and the pipeline is cleared.
+///
+/// # Latency
+///
+/// Once primed, a window of `n` frames at speed `s` yields about `n / s` frames per
+/// call. The audio of a window starts coming out once the time-stretcher has buffered
+/// the input of one processing sequence, so it lags the input by that much: around
+/// 100 ms with the default settings (see [`Setting::SequenceMs`] and
+/// [`Setting::SeekwindowMs`]), regardless of the window length.
+///
+/// [`Setting::SequenceMs`]: crate::Setting::SequenceMs
+/// [`Setting::SeekwindowMs`]: crate::Setting::SeekwindowMs
+///
+/// ```rust
+/// use soundtouch::{Config, Scrubber};
+///
+/// let source = vec![0.0; 44100 * 2];
+/// let mut scrubber = Scrubber::new(Config::new().set_channels(2));
+/// // 100 ms around the cursor, dragged at half speed.
+/// let cursor = 22050;
+/// let window = &source[(cursor - 2205) * 2..(cursor + 2205) * 2];
+/// let output = scrubber.scrub_to(window, 0.5).to_vec();
+/// // The cursor stopped, so push out the rest.
+/// let rest = scrubber.scrub_to(&[], 0.0);
+/// assert!(output.len() + rest.len() >= 4410 * 2 * 2);
+/// ```
+///
+/// [`scrub_to`]: Scrubber::scrub_to
+pub struct Scrubber {
+    soundtouch: SoundTouch,
+    channels: usize,
+    fade_frames: usize,
+    tail_frames: usize,
+    window: Vec<f32>,
+    silence: Vec<f32>,
+    output: Vec<f32>,
+}
+
+impl Scrubber {
+    /// Creates a scrubber processing with `config`, whose tempo is replaced by the
+    /// speed of every [`scrub_to`] call. The fades are 5 ms long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config` is [invalid].
+    ///
+    /// [`scrub_to`]: Scrubber::scrub_to
+    /// [invalid]: Config::validate
+    pub fn new(config: &Config) -> Self {
+        config.validate().or_panic();
+        let channels = config.channels() as usize;
+        Self {
+            soundtouch: SoundTouch::with_config(config),
+            channels,
+            fade_frames: (config.sample_rate() * DEFAULT_FADE_MS / 1000) as usize,
+            tail_frames: (config.sample_rate() * TAIL_MS / 1000) as usize,
+            window: Vec::new(),
+            silence: vec![0.0; PADDING_FRAMES * channels],
+            output: Vec::new(),
+        }
+    }
+
+    /// Sets the length of the fade-in and fade-out applied to every window, in frames.
+    /// Windows shorter than two fades are faded over half their length each way.
+    pub fn set_fade_frames(&mut self, fade_frames: usize) -> &mut Self {
+        self.fade_frames = fade_frames;
+        self
+    }
+
+    /// Processes `window`, interleaved, at `speed` times the original speed and returns
+    /// the output, which stays valid until the next call.
+    ///
+    /// A negative `speed` plays the window backwards. The magnitude is clamped to
+    /// [`SoundTouch::MIN_EFFECTIVE_FACTOR`]`..=`[`SoundTouch::MAX_EFFECTIVE_FACTOR`]. A
+    /// `speed` of `0.0` or NaN, or an empty window, means the cursor stopped: `window`
+    /// is ignored and the call returns the rest of the previous windows, followed by up
+    /// to 100 ms of silence. A trailing partial frame is ignored.
+    pub fn scrub_to(&mut self, window: &[f32], speed: f64) -> &[f32] {
+        let channels = self.channels;
+        let frames = window.len() / channels;
+        self.output.clear();
+
+        let stopped = !(speed.abs() > 0.0) || frames == 0;
+        if !stopped {
+            self.window.clear();
+            self.window.extend_from_slice(&window[..frames * channels]);
+            if speed < 0.0 {
+                reverse_frames(&mut self.window, channels);
+            }
+            self.fade();
+
+            let tempo = speed.abs().clamp(
+                SoundTouch::MIN_EFFECTIVE_FACTOR,
+                SoundTouch::MAX_EFFECTIVE_FACTOR,
+            );
+            self.soundtouch.set_tempo(tempo);
+            self.soundtouch.put_samples(&self.window, frames);
+        }
+
+        if stopped && self.soundtouch.expected_flush_padding() > 0 {
+            // Push everything put so far out of the pipeline with silence and drop the
+            // silence still inside it, so the next window starts right away.
+            let target = self.soundtouch.available_frames()
+                + self.soundtouch.expected_flush_padding()
+                + self.tail_frames;
+            while self.soundtouch.available_frames() < target {
+                self.soundtouch.put_samples(&self.silence, PADDING_FRAMES);
+            }
+        }
+
+        let available = self.soundtouch.available_frames();
+        self.output.resize(available * channels, 0.0);
+        let received = self.soundtouch.receive_samples(&mut self.output, available);
+        self.output.truncate(received * channels);
+        if stopped {
+            self.soundtouch.clear();
+        }
+        &self.output
+    }
+
+    fn fade(&mut self) {
+        let channels = self.channels;
+        let frames = self.window.len() / channels;
+        let fade = self.fade_frames.min(frames / 2);
+        for i in 0..fade {
+            let gain = i as f32 / fade as f32;
+            for sample in &mut self.window[i * channels..(i + 1) * channels] {
+                *sample *= gain;
+            }
+            let end = frames - 1 - i;
+            for sample in &mut self.window[end * channels..(end + 1) * channels] {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+/// Reverses the order of the frames in `samples`, keeping the channel order.
+fn reverse_frames(samples: &mut [f32], channels: usize) {
+    samples.reverse();
+    for frame in samples.chunks_exact_mut(channels) {
+        frame.reverse();
+    }
+}
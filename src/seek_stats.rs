@@ -0,0 +1,259 @@
+use alloc::vec::Vec;
+
+use crate::math::sqrt;
+use crate::SoundTouch;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How well the time-stretcher found splice points, as collected by
+/// [`SoundTouch::set_collect_seek_stats`].
+///
+/// For every processed sequence, SoundTouch searches the seek window for the position
+/// where the next sequence matches the end of the last one best. When the best match
+/// is at the very edge of the window, the position it wanted was likely outside of
+/// it, and the splice is audible. If that happens often, increase
+/// [`Setting::SeekwindowMs`](crate::Setting::SeekwindowMs).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SeekStats {
+    /// Sequences whose splice point was searched.
+    pub blocks: u64,
+    /// Searches whose best position was within [`SeekStats::EDGE_FRACTION`] of the
+    /// seek window from either end.
+    pub edge_hits: u64,
+    /// Mean normalized correlation of the spliced parts at the chosen positions, in
+    /// `-1.0..=1.0`. Higher is smoother.
+    pub avg_correlation: f64,
+}
+
+impl SeekStats {
+    /// Fraction of the seek window at either end in which a best position counts as an
+    /// edge hit.
+    pub const EDGE_FRACTION: f64 = 0.05;
+
+    /// Fraction of the blocks above which [`SoundTouch::lint`] reports
+    /// [`ConfigLint::SmallSeekWindow`](crate::ConfigLint::SmallSeekWindow).
+    pub const MAX_EDGE_HIT_RATIO: f64 = 0.25;
+
+    /// Fewest blocks for which [`SoundTouch::lint`] considers the statistics.
+    pub const MIN_LINT_BLOCKS: u64 = 20;
+
+    /// Returns the fraction of the blocks that were edge hits, or `0.0` without blocks.
+    pub fn edge_hit_ratio(&self) -> f64 {
+        match self.blocks {
+            0 => 0.0,
+            blocks => self.edge_hits as f64 / blocks as f64,
+        }
+    }
+}
+
+/// The lengths the time-stretcher works with, in frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SeekLengths {
+    pub(crate) sequence: usize,
+    pub(crate) seek_window: usize,
+    pub(crate) overlap: usize,
+}
+
+/// Follows the input through the sequences of SoundTouch's `TDStretch` and repeats its
+/// full search for the best overlap position, on the input mixed down to mono.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SeekAnalyzer {
+    /// The mono input from the start of the next seek window on.
+    input: Vec<f32>,
+    /// The end of the last sequence, which the next one is overlapped with.
+    mid: Vec<f32>,
+    /// Whether the next sequence is the first of the stream, which isn't spliced.
+    started: bool,
+    skip_fract: f64,
+    blocks: u64,
+    edge_hits: u64,
+    correlation_sum: f64,
+}
+
+impl SeekAnalyzer {
+    /// Forgets the input, for a new stream, keeping the statistics.
+    pub(crate) fn restart(&mut self) {
+        self.input.clear();
+        self.mid.clear();
+        self.started = false;
+        self.skip_fract = 0.0;
+    }
+
+    pub(crate) fn stats(&self) -> SeekStats {
+        SeekStats {
+            blocks: self.blocks,
+            edge_hits: self.edge_hits,
+            avg_correlation: match self.blocks {
+                0 => 0.0,
+                blocks => self.correlation_sum / blocks as f64,
+            },
+        }
+    }
+
+    /// Analyzes the interleaved `samples` stretched by `tempo` with `lengths`.
+    pub(crate) fn put(
+        &mut self,
+        samples: &[f32],
+        channels: usize,
+        tempo: f64,
+        lengths: SeekLengths,
+    ) {
+        let SeekLengths {
+            sequence,
+            seek_window,
+            overlap,
+        } = lengths;
+        self.input.extend(
+            samples
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+        if self.mid.len() != overlap {
+            self.mid.clear();
+            self.mid.resize(overlap, 0.0);
+        }
+
+        // As in `TDStretch::processSamples`.
+        let nominal_skip = tempo * (sequence - overlap) as f64;
+        let required = ((nominal_skip + 0.5) as usize + overlap).max(sequence) + seek_window;
+        let mut start = 0;
+        while self.input.len() - start >= required {
+            let mut offset = 0;
+            if self.started {
+                let window = &self.input[start..start + seek_window + overlap];
+                let (best, correlation) = best_overlap(window, &self.mid, seek_window);
+                let margin = ((seek_window as f64 * SeekStats::EDGE_FRACTION) as usize).max(1);
+                self.blocks += 1;
+                self.edge_hits += u64::from(best < margin || best + margin >= seek_window);
+                self.correlation_sum += correlation;
+                offset = best + overlap;
+            } else {
+                self.started = true;
+                let skip = (tempo * overlap as f64 + 0.5 * seek_window as f64 + 0.5) as usize;
+                self.skip_fract = (self.skip_fract - skip as f64).max(-nominal_skip);
+            }
+            let mid_start = start + offset + sequence - 2 * overlap;
+            self.mid
+                .copy_from_slice(&self.input[mid_start..mid_start + overlap]);
+            self.skip_fract += nominal_skip;
+            let skip = self.skip_fract as usize;
+            self.skip_fract -= skip as f64;
+            start += skip;
+        }
+        self.input.drain(..start);
+    }
+}
+
+/// Returns the position in `window` where `mid` matches best, by SoundTouch's weighted
+/// measure that favors the middle of the seek window, and the normalized correlation
+/// there.
+fn best_overlap(window: &[f32], mid: &[f32], seek_window: usize) -> (usize, f64) {
+    let mid_energy = energy(mid);
+    let mut best = (0, f64::MIN, 0.0);
+    for position in 0..seek_window {
+        let candidate = &window[position..position + mid.len()];
+        let product: f64 = candidate
+            .iter()
+            .zip(mid)
+            .map(|(&a, &b)| a as f64 * b as f64)
+            .sum();
+        let candidate_energy = energy(candidate);
+        // SoundTouch only normalizes by the energy of the candidate.
+        let scale = if candidate_energy < 1e-9 {
+            1.0
+        } else {
+            candidate_energy
+        };
+        let measure = product / sqrt(scale);
+        let distance = (2.0 * position as f64 - seek_window as f64) / seek_window as f64;
+        let weighted = (measure + 0.1) * (1.0 - 0.25 * distance * distance);
+        if weighted > best.1 {
+            let norm = sqrt(candidate_energy * mid_energy);
+            let correlation = if norm < 1e-9 { 0.0 } else { product / norm };
+            best = (position, weighted, correlation);
+        }
+    }
+    (best.0, best.2)
+}
+
+fn energy(samples: &[f32]) -> f64 {
+    samples
+        .iter()
+        .map(|&sample| sample as f64 * sample as f64)
+        .sum()
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables collecting [`SeekStats`] about the splice points the
+    /// time-stretcher finds, e.g. to tune [`Setting::SeekwindowMs`] per file.
+    ///
+    /// SoundTouch doesn't expose its search, so it is repeated here on the input mixed
+    /// down to mono, with the sequence, seek window and overlap lengths SoundTouch uses
+    /// and the full search of [`Setting::UseQuickseek`] off. The rate transposer isn't
+    /// taken into account. The search costs about as much again as the time-stretching
+    /// of a mono stream, so leave it off when not tuning.
+    ///
+    /// Disabling it discards the statistics; enabling it again starts from zero.
+    ///
+    /// [`Setting::SeekwindowMs`]: crate::Setting::SeekwindowMs
+    /// [`Setting::UseQuickseek`]: crate::Setting::UseQuickseek
+    pub fn set_collect_seek_stats(&mut self, collect: bool) -> &mut Self {
+        match (collect, &self.seek_analyzer) {
+            (true, None) => self.seek_analyzer = Some(SeekAnalyzer::default()),
+            (false, _) => self.seek_analyzer = None,
+            (true, Some(_)) => {}
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the statistics collected since [`set_collect_seek_stats`] enabled them,
+    /// across streams, or all zeros if they aren't collected.
+    ///
+    /// [`set_collect_seek_stats`]: SoundTouch::set_collect_seek_stats
+    pub fn seek_stats(&self) -> SeekStats {
+        self.seek_analyzer
+            .as_ref()
+            .map(SeekAnalyzer::stats)
+            .unwrap_or_default()
+    }
+
+    /// Passes `frames` frames of `samples` put into the pipeline to the seek analyzer.
+    pub(crate) fn analyze_seek(&mut self, samples: &[f32], frames: usize) {
+        let Some(lengths) = self.seek_lengths() else {
+            return;
+        };
+        let tempo = self.effective_parameters().tempo;
+        let channels = self.num_channels() as usize;
+        if let Some(analyzer) = &mut self.seek_analyzer {
+            analyzer.put(&samples[..frames * channels], channels, tempo, lengths);
+        }
+    }
+
+    /// Returns the lengths `TDStretch` uses, including the automatic ones of
+    /// `TDStretch::calcSeqParameters`, or `None` if the sample rate isn't set.
+    fn seek_lengths(&self) -> Option<SeekLengths> {
+        let sample_rate = self.sample_rate()? as usize;
+        let tempo = self.effective_parameters().tempo;
+        let auto = (tempo.clamp(0.5, 2.0) - 0.5) / 1.5;
+        let auto_frames = |at_slowest: f64, at_fastest: f64| {
+            let ms = (at_slowest + (at_fastest - at_slowest) * auto + 0.5) as usize;
+            sample_rate * ms / 1000
+        };
+        let overlap = self.overlap_frames()?;
+        let sequence = self
+            .sequence_frames()
+            .unwrap_or_else(|| auto_frames(90.0, 40.0).max(2 * overlap));
+        let seek_window = self
+            .seek_window_frames()
+            .unwrap_or_else(|| auto_frames(20.0, 15.0));
+        Some(SeekLengths {
+            sequence,
+            seek_window: seek_window.max(1),
+            overlap,
+        })
+    }
+}
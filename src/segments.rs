@@ -0,0 +1,179 @@
+//! Joining separately stretched segments into one continuous stream.
+//!
+//! Flushing a [`SoundTouch`] instance between segments pushes blank samples
+//! through the pipeline, which leaves a short silence at the end of every
+//! segment and makes the joints audible. [`Concatenator`] avoids that by
+//! pushing each segment's tail out with the beginning of the next segment
+//! instead, and crossfades the two renderings of that shared audio at the
+//! joint. Only the very last segment is flushed.
+//!
+//! Segment boundaries are placed at the rounded cumulative expected output
+//! length, so rounding errors never add up to more than one frame.
+//!
+//! ```rust
+//! use soundtouch::{segments::Concatenator, Config};
+//!
+//! let chapter = vec![0.0; 44100 * 2];
+//! let mut concatenator = Concatenator::new(256);
+//! concatenator
+//!     .push(&chapter, Config::new().set_tempo(1.25))
+//!     .push(&chapter, Config::new().set_tempo(1.5));
+//! let concatenated = concatenator.finish();
+//! assert_eq!(concatenated.joints.len(), 1);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::OrPanic;
+use crate::{Config, SoundTouch};
+
+/// Where two segments were joined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Joint {
+    /// Frame index in the output where the later segment starts.
+    pub position: usize,
+    /// Exact expected start of the later segment, in frames. `position` is
+    /// this value rounded to the nearest frame.
+    pub expected: f64,
+    /// Length of the crossfade starting at `position`, in frames.
+    pub crossfade: usize,
+}
+
+/// The result of [`Concatenator::finish`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Concatenated {
+    /// The interleaved output of all segments.
+    pub samples: Vec<f32>,
+    /// One joint for every pair of consecutive segments.
+    pub joints: Vec<Joint>,
+    /// The exact expected output length in frames, i.e. the sum of every
+    /// segment's input length times its input/output ratio.
+    pub expected_frames: f64,
+}
+
+/// Processes successive segments with their own [`Config`] and joins the
+/// results without gaps. See the [module documentation](self).
+pub struct Concatenator {
+    soundtouch: SoundTouch,
+    crossfade: usize,
+    format: Option<(u32, u32)>,
+    pending: Option<(Vec<f32>, Config)>,
+    samples: Vec<f32>,
+    overhang: Vec<f32>,
+    expected: f64,
+    joints: Vec<Joint>,
+}
+
+impl Concatenator {
+    /// Creates a concatenator that crossfades over `crossfade` frames at each
+    /// joint.
+    pub fn new(crossfade: usize) -> Self {
+        Self {
+            soundtouch: SoundTouch::new(),
+            crossfade,
+            format: None,
+            pending: None,
+            samples: Vec::new(),
+            overhang: Vec::new(),
+            expected: 0.0,
+            joints: Vec::new(),
+        }
+    }
+
+    /// Adds a segment of interleaved samples, processed with `config`.
+    ///
+    /// A segment is processed once the next one is pushed, or by [`finish`].
+    /// A trailing partial frame in `samples` is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config` is [invalid], or if its number of channels or sample rate
+    /// differs from the first segment's.
+    ///
+    /// [`finish`]: Concatenator::finish
+    /// [invalid]: Config::validate
+    pub fn push(&mut self, samples: &[f32], config: &Config) -> &mut Self {
+        config.validate().or_panic();
+        let format = (config.channels(), config.sample_rate());
+        assert_eq!(
+            *self.format.get_or_insert(format),
+            format,
+            "all segments must have the same number of channels and sample rate"
+        );
+        if let Some((previous, previous_config)) = self.pending.take() {
+            self.process(&previous, &previous_config, Some(samples));
+        }
+        self.pending = Some((samples.to_vec(), config.clone()));
+        self
+    }
+
+    /// Processes the last segment and returns the joined output.
+    pub fn finish(mut self) -> Concatenated {
+        if let Some((last, config)) = self.pending.take() {
+            self.process(&last, &config, None);
+        }
+        self.samples.append(&mut self.overhang);
+        Concatenated {
+            samples: self.samples,
+            joints: self.joints,
+            expected_frames: self.expected,
+        }
+    }
+
+    fn process(&mut self, samples: &[f32], config: &Config, next: Option<&[f32]>) {
+        let st = &mut self.soundtouch;
+        st.reset();
+        st.apply_config(config);
+        let channels = config.channels() as usize;
+        let frames = samples.len() / channels;
+
+        let start = self.samples.len() / channels;
+        let expected_start = self.expected;
+        self.expected += frames as f64 * st.get_input_output_sample_ratio();
+        let end = (self.expected + 0.5) as usize;
+        let overlap = if next.is_some() { self.crossfade } else { 0 };
+        let needed = end.saturating_sub(start) + overlap;
+
+        st.put_samples(samples, frames);
+        // The next segment pushes out the tail of this one, as much of it as it takes.
+        let mut lookahead = next.unwrap_or(&[]);
+        while st.available_frames() < needed {
+            let frames = st
+                .input_needed_for_output(needed)
+                .min(lookahead.len() / channels);
+            if frames == 0 {
+                st.flush();
+                break;
+            }
+            let (chunk, rest) = lookahead.split_at(frames * channels);
+            st.put_samples(chunk, frames);
+            lookahead = rest;
+        }
+
+        // Should the pipeline come up short, the missing frames stay silent.
+        let mut output = vec![0.0; needed * channels];
+        st.receive_samples(&mut output, needed);
+
+        let crossfade = (self.overhang.len() / channels).min(needed);
+        for frame in 0..crossfade {
+            let weight = (frame as f32 + 0.5) / crossfade as f32;
+            for c in 0..channels {
+                let i = frame * channels + c;
+                output[i] = self.overhang[i] * (1.0 - weight) + output[i] * weight;
+            }
+        }
+        if start > 0 {
+            self.joints.push(Joint {
+                position: start,
+                expected: expected_start,
+                crossfade,
+            });
+        }
+
+        let commit = (needed - overlap) * channels;
+        self.samples.extend_from_slice(&output[..commit]);
+        self.overhang.clear();
+        self.overhang.extend_from_slice(&output[commit..]);
+    }
+}
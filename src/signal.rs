@@ -0,0 +1,312 @@
+//! Deterministic test signals: sines, sweeps, click tracks and noise.
+//!
+//! Every generator returns a mono [`Signal`], an iterator over a given number of
+//! samples that needs neither std nor an allocator. [`Signal::stereo`] and
+//! [`Signal::channels`] repeat each sample across the channels to make interleaved
+//! input, and with the `alloc` feature [`Signal::into_vec`] collects it. The tones peak
+//! at `0.5` and the white noise stays within `-0.5..=0.5`, which leaves headroom for
+//! pitch shifting and mixing. All generators panic if the sample rate is `0`.
+//!
+//! The samples depend only on the arguments and their position, so two signals made
+//! with the same arguments are equal bit for bit, on every platform.
+//!
+//! ```rust
+//! use soundtouch::{signal, SoundTouch};
+//!
+//! let input = signal::click_track(120.0, 44100, 10 * 44100).stereo().into_vec();
+//! let mut soundtouch = SoundTouch::new();
+//! soundtouch.set_channels(2).set_sample_rate(44100).set_tempo(2.0);
+//! let output = soundtouch.generate_audio(&input);
+//! assert!((output.len() / 2).abs_diff(5 * 44100) <= 1);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::f64::consts::TAU;
+
+use crate::math::{exp, floor, ln, sin};
+
+/// Peak level of the tones and half the range of the white noise.
+const AMPLITUDE: f64 = 0.5;
+
+/// Frequency of the decaying tone burst of a click, in Hz.
+const CLICK_FREQUENCY: f64 = 100.0;
+
+/// Decay rate of a click, per second.
+const CLICK_DECAY: f64 = 40.0;
+
+/// Gain of the pink noise filter output, which keeps its peaks below full scale.
+const PINK_GAIN: f64 = 0.2;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A test signal made by one of the generators in [`signal`](crate::signal): an
+/// iterator over its interleaved samples.
+#[derive(Debug, Clone)]
+pub struct Signal {
+    source: Source,
+    sample_rate: u32,
+    frames: usize,
+    channels: usize,
+    /// The next frame to compute.
+    frame: usize,
+    /// The channel of the next sample within the current frame.
+    channel: usize,
+    sample: f32,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Sine {
+        frequency: f64,
+    },
+    /// Exponential sweep from `start` Hz, whose frequency grows by `e^rate` per second.
+    Sweep {
+        start: f64,
+        rate: f64,
+    },
+    Clicks {
+        period_frames: f64,
+    },
+    White(Noise),
+    Pink {
+        noise: Noise,
+        state: [f64; 3],
+    },
+}
+
+/// The xorshift generator of the noise.
+#[derive(Debug, Clone)]
+struct Noise(u64);
+
+impl Noise {
+    fn new(seed: u64) -> Self {
+        // Xorshift would only ever return `0` from a seed of `0`.
+        Self(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    /// Returns a uniformly distributed sample in `-0.5..0.5`.
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    }
+}
+
+impl Source {
+    fn sample(&mut self, frame: usize, sample_rate: u32) -> f64 {
+        let t = frame as f64 / sample_rate as f64;
+        match self {
+            Source::Sine { frequency } => AMPLITUDE * sin_turns(*frequency * t),
+            Source::Sweep { start, rate } => {
+                let turns = if *rate == 0.0 {
+                    *start * t
+                } else {
+                    *start * (exp(*rate * t) - 1.0) / *rate
+                };
+                AMPLITUDE * sin_turns(turns)
+            }
+            Source::Clicks { period_frames } => {
+                let beats = frame as f64 / *period_frames;
+                let t = (beats - floor(beats)) * *period_frames / sample_rate as f64;
+                AMPLITUDE * exp(-CLICK_DECAY * t) * sin_turns(CLICK_FREQUENCY * t)
+            }
+            Source::White(noise) => noise.next(),
+            // Paul Kellet's economy filter, within 0.05 dB of -3 dB per octave above
+            // 9.2 Hz at 44.1 kHz.
+            Source::Pink { noise, state } => {
+                let white = 2.0 * noise.next();
+                state[0] = 0.99765 * state[0] + white * 0.0990460;
+                state[1] = 0.96300 * state[1] + white * 0.2965164;
+                state[2] = 0.57000 * state[2] + white * 1.0526913;
+                PINK_GAIN * (state[0] + state[1] + state[2] + white * 0.1848)
+            }
+        }
+    }
+}
+
+/// Returns the sine of `turns` whole turns, reduced before scaling by `2π` so that the
+/// phase stays exact for long signals.
+fn sin_turns(turns: f64) -> f64 {
+    sin((turns - floor(turns)) * TAU)
+}
+
+impl Signal {
+    fn new(source: Source, sample_rate: u32, frames: usize) -> Self {
+        assert!(sample_rate > 0, "the sample rate must not be 0");
+        Self {
+            source,
+            sample_rate,
+            frames,
+            channels: 1,
+            frame: 0,
+            channel: 0,
+            sample: 0.0,
+        }
+    }
+
+    /// Repeats every sample on 2 channels.
+    pub fn stereo(self) -> Self {
+        self.channels(2)
+    }
+
+    /// Repeats every sample on `channels` channels. Call it before iterating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is `0`.
+    pub fn channels(mut self, channels: u32) -> Self {
+        assert!(channels > 0, "a signal needs at least one channel");
+        self.channels = channels as usize;
+        self
+    }
+
+    /// Returns the sample rate the signal was made for.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the number of frames, including those already iterated over.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Returns the number of channels set with [`channels`], `1` by default.
+    ///
+    /// [`channels`]: Signal::channels
+    pub fn num_channels(&self) -> u32 {
+        self.channels as u32
+    }
+
+    /// Collects the remaining samples into a vector, allocated once.
+    #[cfg(feature = "alloc")]
+    pub fn into_vec(self) -> Vec<f32> {
+        let mut samples = Vec::with_capacity(self.len());
+        samples.extend(self);
+        samples
+    }
+}
+
+impl Iterator for Signal {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 0 {
+            if self.frame == self.frames {
+                return None;
+            }
+            self.sample = self.source.sample(self.frame, self.sample_rate) as f32;
+            self.frame += 1;
+        }
+        self.channel = (self.channel + 1) % self.channels;
+        Some(self.sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = if self.channel == 0 {
+            0
+        } else {
+            self.channels - self.channel
+        };
+        let len = (self.frames - self.frame) * self.channels + pending;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Signal {}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns `frames` frames of a sine wave of `frequency` Hz at `sample_rate`, starting
+/// at phase `0`.
+///
+/// # Panics
+///
+/// Panics if `sample_rate` is `0`.
+pub fn sine(frequency: f64, sample_rate: u32, frames: usize) -> Signal {
+    Signal::new(Source::Sine { frequency }, sample_rate, frames)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns `frames` frames of a sine sweep from `start` to `end` Hz at `sample_rate`.
+/// The frequency rises or falls exponentially, so every octave takes the same time,
+/// and reaches `end` at the end of the signal.
+///
+/// # Panics
+///
+/// Panics if `sample_rate` is `0`, or if `start` or `end` isn't positive and finite.
+pub fn sweep(start: f64, end: f64, sample_rate: u32, frames: usize) -> Signal {
+    let valid = |frequency: f64| frequency > 0.0 && frequency.is_finite();
+    assert!(
+        valid(start) && valid(end),
+        "invalid sweep from {start} Hz to {end} Hz"
+    );
+    let seconds = frames as f64 / sample_rate.max(1) as f64;
+    let rate = if frames == 0 || start == end {
+        0.0
+    } else {
+        ln(end / start) / seconds
+    };
+    Signal::new(Source::Sweep { start, rate }, sample_rate, frames)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns `frames` frames of a click track at `bpm` beats per minute at
+/// `sample_rate`: a 100 Hz tone burst decaying within a few tens of milliseconds at
+/// every beat, starting with the first frame. The beats are placed at their exact,
+/// fractional positions, so long tracks don't drift.
+///
+/// This is the kind of signal [`BPMDetect`] picks up reliably, unlike the short
+/// 1 kHz clicks of [`BeatGrid::to_click_track`], which are meant for listening.
+///
+/// # Panics
+///
+/// Panics if `sample_rate` is `0`, or if `bpm` isn't positive and finite.
+///
+/// [`BPMDetect`]: crate::BPMDetect
+/// [`BeatGrid::to_click_track`]: crate::bpm::BeatGrid::to_click_track
+pub fn click_track(bpm: f64, sample_rate: u32, frames: usize) -> Signal {
+    assert!(
+        bpm > 0.0 && bpm.is_finite(),
+        "invalid tempo of {bpm} beats per minute"
+    );
+    let period_frames = 60.0 * sample_rate as f64 / bpm;
+    Signal::new(Source::Clicks { period_frames }, sample_rate, frames)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns `frames` frames of white noise, uniformly distributed in `-0.5..=0.5`. The
+/// same `seed` always gives the same noise, whatever the `sample_rate`.
+///
+/// # Panics
+///
+/// Panics if `sample_rate` is `0`.
+pub fn noise(seed: u64, sample_rate: u32, frames: usize) -> Signal {
+    Signal::new(Source::White(Noise::new(seed)), sample_rate, frames)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns `frames` frames of pink noise, with equal energy per octave, made by
+/// filtering the white noise of [`noise`] with the same `seed`. It is quieter than the
+/// white noise, so that its peaks stay below full scale. The filter is designed for
+/// 44.1 kHz; at other rates its corner frequencies move with the rate.
+///
+/// # Panics
+///
+/// Panics if `sample_rate` is `0`.
+pub fn pink_noise(seed: u64, sample_rate: u32, frames: usize) -> Signal {
+    let source = Source::Pink {
+        noise: Noise::new(seed),
+        state: [0.0; 3],
+    };
+    Signal::new(source, sample_rate, frames)
+}
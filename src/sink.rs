@@ -0,0 +1,101 @@
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A destination for processed samples, used by [`SoundTouch::generate_audio_extend`].
+///
+/// Every collection implementing [`Extend<f32>`] is a sink, including `Vec<f32>`,
+/// `VecDeque<f32>` and the fixed-capacity vectors of `heapless` and `smallvec`. Use
+/// [`SliceSink`] to write into a preallocated slice.
+///
+/// [`SoundTouch::generate_audio_extend`]: crate::SoundTouch::generate_audio_extend
+pub trait OutputSink {
+    /// Returns how many more samples the sink accepts, or `None` if it grows as needed.
+    fn remaining(&self) -> Option<usize>;
+
+    /// Appends `samples`, which are never more than [`remaining`] and always a whole
+    /// number of frames.
+    ///
+    /// [`remaining`]: OutputSink::remaining
+    fn write(&mut self, samples: &[f32]);
+}
+
+impl<E: Extend<f32>> OutputSink for E {
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        self.extend(samples.iter().copied());
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// An [`OutputSink`] filling a slice from the start.
+///
+/// Output that doesn't fit isn't received, so it stays ready in the pipeline.
+///
+/// ```rust
+/// use soundtouch::{SliceSink, SoundTouch};
+///
+/// let mut soundtouch = SoundTouch::new();
+/// soundtouch.set_channels(1).set_sample_rate(44100);
+/// let mut buffer = [0.0; 512];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// soundtouch.generate_audio_extend(&[0.25; 2048], &mut sink);
+/// assert_eq!(sink.written().len(), 512);
+/// assert!(soundtouch.num_samples() > 0);
+/// ```
+#[derive(Debug)]
+pub struct SliceSink<'a> {
+    slice: &'a mut [f32],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Creates a sink writing to `slice`.
+    pub fn new(slice: &'a mut [f32]) -> Self {
+        Self { slice, len: 0 }
+    }
+
+    /// Returns the samples written so far.
+    pub fn written(&self) -> &[f32] {
+        &self.slice[..self.len]
+    }
+
+    /// Returns `true` if the slice is full.
+    pub fn is_full(&self) -> bool {
+        self.len == self.slice.len()
+    }
+}
+
+impl<'a> From<&'a mut [f32]> for SliceSink<'a> {
+    fn from(slice: &'a mut [f32]) -> Self {
+        Self::new(slice)
+    }
+}
+
+impl OutputSink for SliceSink<'_> {
+    fn remaining(&self) -> Option<usize> {
+        Some(self.slice.len() - self.len)
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        self.slice[self.len..self.len + samples.len()].copy_from_slice(samples);
+        self.len += samples.len();
+    }
+}
+
+/// Hands every batch to a closure, for [`SoundTouch::process_with_callback`].
+///
+/// [`SoundTouch::process_with_callback`]: crate::SoundTouch::process_with_callback
+pub(crate) struct Callback<F>(pub F);
+
+impl<F: FnMut(&[f32])> OutputSink for Callback<F> {
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        (self.0)(samples);
+    }
+}
@@ -1,22 +1,122 @@
+use core::ffi::{c_int, c_void};
+use core::fmt;
+use core::ops::RangeInclusive;
+use core::slice;
 use ffi::{uint, SoundTouch as SoundTouchSys};
 use soundtouch_ffi as ffi;
-use core::ffi::{c_int, c_void};
 
+use crate::cpu::{self, Extensions};
+use crate::drift::DriftTracker;
+use crate::error::OrPanic;
+use crate::ffi_call::ffi_call;
+use crate::instance::Instance;
+use crate::math::sqrt;
+use crate::sink::{Callback, OutputSink};
+use crate::units::Frames;
+use crate::{ParseSettingError, SoundTouchError};
+
+#[cfg(feature = "alloc")]
+use crate::bypass::Bypass;
+#[cfg(feature = "alloc")]
+use crate::cancel::CancellationToken;
+#[cfg(feature = "alloc")]
+use crate::formant::FormantCorrection;
+#[cfg(feature = "alloc")]
+use crate::loudness::LoudnessMeter;
+#[cfg(feature = "alloc")]
+use crate::requirements::SettingsHook;
 #[cfg(feature = "alloc")]
-use ffi::{SoundTouch_putSamples as putSamples, SoundTouch_receiveSamples as receiveSamples};
+use crate::seek_stats::SeekAnalyzer;
 #[cfg(feature = "alloc")]
-extern crate alloc;
+use crate::staging::Staging;
+#[cfg(feature = "std")]
+use crate::timing::{BlockKind, TimingRing};
+#[cfg(feature = "alloc")]
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+/// Largest number of interleaved samples handed to SoundTouch in one call.
+///
+/// SoundTouch counts frames in `uint` and computes its buffer sizes in bytes with `uint`
+/// arithmetic, so counts anywhere near `u32::MAX / channels` would wrap around and
+/// corrupt the output. Longer buffers are split into calls of at most this many samples.
+pub(crate) const MAX_SAMPLES_PER_CALL: usize = 1 << 20;
+
+/// Length of the stack buffers that output is received into and sanitized input is
+/// copied into, in samples.
+pub(crate) const STACK_BUFFER_SAMPLES: usize = 4096;
+
+/// Splits `frames` frames of `channels` channels into the frame counts of consecutive
+/// calls into SoundTouch, none of which is longer than `MAX_SAMPLES_PER_CALL` samples.
+/// `0` frames make no calls, so SoundTouch is never passed an empty buffer.
+///
+/// Public only so that the chunking can be tested without multi-gigabyte buffers.
+#[doc(hidden)]
+pub fn ffi_chunks(frames: usize, channels: u32) -> impl Iterator<Item = usize> {
+    let max = (MAX_SAMPLES_PER_CALL / channels.max(1) as usize).max(1);
+    let mut remaining = frames;
+    core::iter::from_fn(move || {
+        let chunk = remaining.min(max);
+        remaining -= chunk;
+        (chunk > 0).then_some(chunk)
+    })
+}
+
+/// Returns the index of the first NaN or infinite sample.
+///
+/// Blocks are checked without branching on every sample, so the check vectorizes, and
+/// only a block that fails is searched for the index.
+fn first_non_finite(samples: &[f32]) -> Option<usize> {
+    const BLOCK: usize = 64;
+    samples
+        .chunks(BLOCK)
+        .enumerate()
+        .find(|(_, block)| !block.iter().fold(true, |finite, s| finite & s.is_finite()))
+        .and_then(|(i, block)| {
+            let index = block.iter().position(|s| !s.is_finite())?;
+            Some(i * BLOCK + index)
+        })
+}
+
+/// Converts interleaved frames of `from` channels to `to` channels as described for
+/// [`ChannelChangePolicy::UpmixDownmixPending`].
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+fn remix(samples: &[f32], from: usize, to: usize) -> VecDeque<f32> {
+    let mut remixed = VecDeque::with_capacity(samples.len() / from * to);
+    for frame in samples.chunks_exact(from) {
+        if to == 1 {
+            remixed.push_back(frame.iter().sum::<f32>() / from as f32);
+        } else if from == 1 {
+            remixed.extend(core::iter::repeat(frame[0]).take(to));
+        } else {
+            remixed.extend((0..to).map(|channel| frame.get(channel).copied().unwrap_or(0.0)));
+        }
+    }
+    remixed
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A speed change as set with [`SoundTouch::set_varispeed`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Varispeed {
+    /// Speed change in percent.
+    pub percent: f64,
+    /// Whether the pitch is kept, i.e. the change applies to the tempo instead of the
+    /// rate.
+    pub key_lock: bool,
+}
 
 /// A list of settings that can be enabled or disabled.
-#[derive(Debug, Clone, Copy)]
+///
+/// To change several at once, e.g. the sequence and overlap lengths that have to fit
+/// together, use [`TuningParams`](crate::TuningParams) with [`SoundTouch::set_tuning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Setting {
     /// Enable/disable anti-alias filter in pitch transposer (0 = disable)
     UseAaFilter = 0,
 
-    /// Pitch transposer anti-alias filter length (8 .. 128 taps, default = 32)
+    /// Pitch transposer anti-alias filter length (8 .. 128 taps, default = 64)
     AaFilterLength = 1,
 
     /// Enable/disable quick seeking algorithm in tempo changer routine
@@ -103,6 +203,440 @@ pub enum Setting {
     InitialLatency = 8,
 }
 
+impl Setting {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Every setting, in the order of their IDs.
+    pub const ALL: [Setting; 9] = [
+        Setting::UseAaFilter,
+        Setting::AaFilterLength,
+        Setting::UseQuickseek,
+        Setting::SequenceMs,
+        Setting::SeekwindowMs,
+        Setting::OverlapMs,
+        Setting::NominalInputSequence,
+        Setting::NominalOutputSequence,
+        Setting::InitialLatency,
+    ];
+
+    /// The settings that can be changed with [`SoundTouch::set_setting`].
+    pub(crate) const WRITABLE: [Setting; 6] = [
+        Setting::UseAaFilter,
+        Setting::AaFilterLength,
+        Setting::UseQuickseek,
+        Setting::SequenceMs,
+        Setting::SeekwindowMs,
+        Setting::OverlapMs,
+    ];
+
+    /// Returns the index of the setting in [`Setting::WRITABLE`], or `None` if it is read-only.
+    pub(crate) const fn writable_index(setting: Setting) -> Option<usize> {
+        match setting as u8 {
+            i @ 0..=5 => Some(i as usize),
+            _ => None,
+        }
+    }
+
+    /// Returns the value this setting has on a newly created [`SoundTouch`] instance:
+    /// - [`UseAaFilter`]: `1`, i.e. enabled
+    /// - [`AaFilterLength`]: `64` taps
+    /// - [`UseQuickseek`]: `0`, i.e. disabled
+    /// - [`SequenceMs`] and [`SeekwindowMs`]: `0`, i.e. chosen automatically from the tempo
+    /// - [`OverlapMs`]: `8` ms
+    ///
+    /// Read-only settings depend on the processing parameters and have no default,
+    /// so `0` is returned for them.
+    ///
+    /// [`UseAaFilter`]: Setting::UseAaFilter
+    /// [`AaFilterLength`]: Setting::AaFilterLength
+    /// [`UseQuickseek`]: Setting::UseQuickseek
+    /// [`SequenceMs`]: Setting::SequenceMs
+    /// [`SeekwindowMs`]: Setting::SeekwindowMs
+    /// [`OverlapMs`]: Setting::OverlapMs
+    pub const fn default_value(&self) -> i32 {
+        match self {
+            Setting::UseAaFilter => 1,
+            Setting::AaFilterLength => 64,
+            Setting::UseQuickseek => 0,
+            Setting::SequenceMs => 0,
+            Setting::SeekwindowMs => 0,
+            Setting::OverlapMs => 8,
+            Setting::NominalInputSequence
+            | Setting::NominalOutputSequence
+            | Setting::InitialLatency => 0,
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns `true` if the setting can't be changed with [`SoundTouch::set_setting`].
+    pub const fn is_read_only(&self) -> bool {
+        Setting::writable_index(*self).is_none()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the values that are useful for the setting, or `None` if it is
+    /// [read-only]:
+    /// - [`UseAaFilter`] and [`UseQuickseek`]: `0..=1`, i.e. disabled or enabled
+    /// - [`AaFilterLength`]: `8..=128` taps, in steps of 8
+    /// - [`SequenceMs`], [`SeekwindowMs`] and [`OverlapMs`]: `0..=`[`MAX_MS`], where `0`
+    ///   chooses the sequence and seek window lengths automatically
+    ///
+    /// The [default value] is always in the range. [`TuningParams::validate`] checks how
+    /// the lengths fit together.
+    ///
+    /// [read-only]: Setting::is_read_only
+    /// [`UseAaFilter`]: Setting::UseAaFilter
+    /// [`UseQuickseek`]: Setting::UseQuickseek
+    /// [`AaFilterLength`]: Setting::AaFilterLength
+    /// [`SequenceMs`]: Setting::SequenceMs
+    /// [`SeekwindowMs`]: Setting::SeekwindowMs
+    /// [`OverlapMs`]: Setting::OverlapMs
+    /// [`MAX_MS`]: crate::TuningParams::MAX_MS
+    /// [default value]: Setting::default_value
+    /// [`TuningParams::validate`]: crate::TuningParams::validate
+    pub const fn valid_range(&self) -> Option<RangeInclusive<i32>> {
+        const MAX_MS: i32 = crate::TuningParams::MAX_MS as i32;
+        match self {
+            Setting::UseAaFilter | Setting::UseQuickseek => Some(0..=1),
+            Setting::AaFilterLength => Some(8..=128),
+            Setting::SequenceMs | Setting::SeekwindowMs | Setting::OverlapMs => Some(0..=MAX_MS),
+            Setting::NominalInputSequence
+            | Setting::NominalOutputSequence
+            | Setting::InitialLatency => None,
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns a short description of the setting, the gist of the first sentence of its
+    /// documentation, e.g. for a tooltip.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Setting::UseAaFilter => "Enable/disable anti-alias filter in pitch transposer",
+            Setting::AaFilterLength => "Pitch transposer anti-alias filter length",
+            Setting::UseQuickseek => {
+                "Enable/disable quick seeking algorithm in tempo changer routine"
+            }
+            Setting::SequenceMs => {
+                "Time-stretch algorithm single processing sequence length in milliseconds"
+            }
+            Setting::SeekwindowMs => "Time-stretch algorithm seeking window length in milliseconds",
+            Setting::OverlapMs => "Time-stretch algorithm overlap length in milliseconds",
+            Setting::NominalInputSequence => "Processing sequence size in samples",
+            Setting::NominalOutputSequence => "Nominal average processing output size in samples",
+            Setting::InitialLatency => "Initial processing latency in samples",
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the kebab-case name of the setting, e.g. `"aa-filter-length"`, as used by
+    /// its [`Display`] and [`FromStr`] implementations.
+    ///
+    /// [`Display`]: fmt::Display
+    /// [`FromStr`]: core::str::FromStr
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Setting::UseAaFilter => "use-aa-filter",
+            Setting::AaFilterLength => "aa-filter-length",
+            Setting::UseQuickseek => "use-quickseek",
+            Setting::SequenceMs => "sequence-ms",
+            Setting::SeekwindowMs => "seekwindow-ms",
+            Setting::OverlapMs => "overlap-ms",
+            Setting::NominalInputSequence => "nominal-input-sequence",
+            Setting::NominalOutputSequence => "nominal-output-sequence",
+            Setting::InitialLatency => "initial-latency",
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Looks a setting up by its [name], ignoring case.
+    ///
+    /// [name]: Setting::name
+    pub fn from_name(name: &str) -> Option<Setting> {
+        Setting::ALL
+            .into_iter()
+            .find(|setting| setting.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl core::str::FromStr for Setting {
+    type Err = ParseSettingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Setting::from_name(s).ok_or(ParseSettingError)
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The tempo, rate and pitch as set by the user (the "virtual" values) and the
+/// effective tempo and rate SoundTouch actually processes with.
+///
+/// Pitch is implemented by transposing the rate and compensating with the tempo,
+/// so the effective tempo is `virtual_tempo / virtual_pitch` and the effective
+/// rate is `virtual_pitch * virtual_rate`, as in SoundTouch's
+/// `calcEffectiveRateAndTempo`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectiveParams {
+    /// The tempo as set with [`SoundTouch::set_tempo`].
+    pub virtual_tempo: f64,
+    /// The rate as set with [`SoundTouch::set_rate`].
+    pub virtual_rate: f64,
+    /// The pitch as set with [`SoundTouch::set_pitch`].
+    pub virtual_pitch: f64,
+    /// The tempo used by the time-stretcher.
+    pub tempo: f64,
+    /// The rate used by the rate transposer.
+    pub rate: f64,
+}
+
+impl EffectiveParams {
+    /// Computes the effective tempo and rate from the virtual values.
+    pub fn new(virtual_tempo: f64, virtual_rate: f64, virtual_pitch: f64) -> Self {
+        Self {
+            virtual_tempo,
+            virtual_rate,
+            virtual_pitch,
+            tempo: virtual_tempo / virtual_pitch,
+            rate: virtual_pitch * virtual_rate,
+        }
+    }
+
+    /// Returns the ratio between input and output durations, see
+    /// [`SoundTouch::get_input_output_sample_ratio`].
+    pub fn input_output_ratio(&self) -> f64 {
+        1.0 / (self.tempo * self.rate)
+    }
+
+    /// Returns `true` if both the effective tempo and rate are within
+    /// [`SoundTouch::MIN_EFFECTIVE_FACTOR`]`..=`[`SoundTouch::MAX_EFFECTIVE_FACTOR`].
+    pub fn is_in_range(&self) -> bool {
+        let range = SoundTouch::MIN_EFFECTIVE_FACTOR..=SoundTouch::MAX_EFFECTIVE_FACTOR;
+        range.contains(&self.tempo) && range.contains(&self.rate)
+    }
+
+    pub(crate) fn check(self) -> Result<(), SoundTouchError> {
+        if self.is_in_range() {
+            Ok(())
+        } else {
+            Err(SoundTouchError::ParametersOutOfRange {
+                tempo: self.tempo,
+                rate: self.rate,
+            })
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How many frames are buffered in a [`SoundTouch`] instance, see
+/// [`SoundTouch::buffer_levels`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferLevels {
+    /// Frames waiting in the time-stretcher's input, as returned by
+    /// [`SoundTouch::num_unprocessed_samples`]. A few more may be held by the rate
+    /// transposer's filter.
+    pub unprocessed_frames: usize,
+    /// Processed frames ready to be received, as returned by
+    /// [`SoundTouch::num_samples`].
+    pub ready_frames: usize,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Levels of the output received from a [`SoundTouch`] instance, see
+/// [`SoundTouch::set_metering`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Meter {
+    /// Largest absolute sample value.
+    pub peak: f32,
+    /// Root mean square of the samples of all channels.
+    pub rms: f32,
+    /// Number of frames measured.
+    pub frames_measured: u64,
+}
+
+/// The running sums behind a [`Meter`].
+#[derive(Debug, Clone, Copy, Default)]
+struct MeterSums {
+    peak: f32,
+    /// Kept in `f64` so that long streams don't lose the contribution of new samples.
+    sum_of_squares: f64,
+    samples: u64,
+    frames: u64,
+}
+
+impl MeterSums {
+    fn add(&mut self, samples: &[f32], channels: usize) {
+        for &sample in samples {
+            self.peak = self.peak.max(sample.abs());
+            self.sum_of_squares += f64::from(sample) * f64::from(sample);
+        }
+        self.samples += samples.len() as u64;
+        self.frames += (samples.len() / channels) as u64;
+    }
+
+    fn meter(&self) -> Meter {
+        let rms = match self.samples {
+            0 => 0.0,
+            samples => sqrt(self.sum_of_squares / samples as f64) as f32,
+        };
+        Meter {
+            peak: self.peak,
+            rms,
+            frames_measured: self.frames,
+        }
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Where a [`SoundTouch`] instance is in its stream, see [`SoundTouch::state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StreamState {
+    /// Input can be put. This is the state of a new or [cleared] instance.
+    ///
+    /// [cleared]: SoundTouch::clear
+    #[default]
+    Streaming,
+    /// The stream was [flushed]. Further flushes do nothing, and the next input starts
+    /// a new stream, see [`SoundTouch::put_samples`].
+    ///
+    /// [flushed]: SoundTouch::flush
+    Flushed,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What is left to receive, see [`SoundTouch::pending_output`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pending {
+    /// Nothing is ready. More output needs more input or a [flush].
+    ///
+    /// [flush]: SoundTouch::flush
+    #[default]
+    None,
+    /// Frames are ready but fewer than an [output block], so they are held back until
+    /// the block is complete or the stream is flushed.
+    ///
+    /// [output block]: SoundTouch::set_output_block_frames
+    PartialBlockHeld,
+    /// Whole output blocks are ready, but the buffer of a [`ReceiveStatus`] or
+    /// [`BlockResult`] had no room for one, so receiving with it gets nothing.
+    ///
+    /// [`BlockResult`]: crate::BlockResult
+    BufferTooSmall {
+        /// The frames a buffer needs room for to receive any: the [output block] size,
+        /// or `1` without one.
+        ///
+        /// [output block]: SoundTouch::set_output_block_frames
+        block_frames: usize,
+    },
+    /// More frames can be received right away, at least a whole output block if a block
+    /// size is set. In a [`ReceiveStatus`], a call with the same buffer length receives
+    /// at least one frame, so a loop receiving until this isn't reported ends.
+    MoreAvailable,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The result of [`SoundTouch::receive_frames`]: the frames received and what is left.
+///
+/// A zero frame count alone doesn't tell whether the pipeline is drained: frames of an
+/// incomplete output block may be held back, and a buffer shorter than a block receives
+/// nothing while whole blocks are ready. The pending state is for the buffer length
+/// passed, so the latter is reported as [`Pending::BufferTooSmall`] and not as
+/// [`Pending::MoreAvailable`], which would make a receive loop spin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReceiveStatus {
+    /// The frames written to the start of the buffer.
+    pub frames: Frames,
+    /// What is left to receive after the call.
+    pub pending: Pending,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`SoundTouch::change_sample_rate`] does with the audio of the old rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateChangePolicy {
+    /// Flush the stream, leaving all of its output ready to be received.
+    FlushThenChange,
+    /// Discard everything buffered, including output that hasn't been received.
+    DropPending,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`SoundTouch::change_channels`] does with the audio of the old channel layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelChangePolicy {
+    /// Flush the stream, leaving all of its output ready to be received in the old
+    /// layout. The new layout takes effect once that output has been received.
+    FlushThenChange,
+    /// Discard everything buffered, including output that hasn't been received.
+    DropPending,
+    /// Flush the stream and convert all of its output to the new layout, so that it is
+    /// received ahead of the output of the new stream.
+    ///
+    /// Converting to mono averages the channels and converting from mono copies the
+    /// channel into all of the new ones. Otherwise channels are kept by position,
+    /// dropping the extra ones or filling the new ones with silence.
+    #[cfg(feature = "alloc")]
+    UpmixDownmixPending,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`SoundTouch::put_samples`] does with NaN and infinite input samples, see
+/// [`SoundTouch::set_input_sanitization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sanitize {
+    /// Pass them on. A single NaN spreads through the overlap of the time-stretcher
+    /// and the filters until the instance is [cleared].
+    ///
+    /// [cleared]: SoundTouch::clear
+    #[default]
+    Off,
+    /// Replace them with `0.0`.
+    ZeroNonFinite,
+    /// Reject the whole input with [`SoundTouchError::NonFiniteInput`].
+    ///
+    /// [`SoundTouchError::NonFiniteInput`]: crate::SoundTouchError::NonFiniteInput
+    Error,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How [`SoundTouch::flush`] ends the stream, see [`SoundTouch::set_flush_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlushMode {
+    /// SoundTouch's flush, with silence added where it stops short of the length it
+    /// expects, as described on [`SoundTouch::flush`].
+    #[default]
+    Raw,
+    /// Only the audio SoundTouch flushes out, with no silence added and the silent frames
+    /// at its end dropped, for live streams that shouldn't play a gap at the end.
+    DrainOnly,
+    /// Exactly `round(frames put * ratio)` frames for the whole stream, where the ratio
+    /// is the [input/output ratio] at the time of the flush: the flushed output is
+    /// completed with silence or cut at the end, for file converters that need an exact
+    /// length. With a constant ratio this is the length of [`Raw`](FlushMode::Raw).
+    ///
+    /// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+    PadToRatio,
+}
+
 /// Main class for tempo/pitch/rate adjusting routines.
 ///
 /// Notes:
@@ -144,100 +678,600 @@ pub enum Setting {
 /// [`put_samples`]: SoundTouch::put_samples
 /// [`receive_samples`]: SoundTouch::receive_samples
 /// [`generate_audio`]: SoundTouch::generate_audio
-#[derive(Debug)]
-pub struct SoundTouch(SoundTouchSys);
+pub struct SoundTouch {
+    inner: SoundTouchSys,
+    /// SoundTouch doesn't allow reading the sample rate back, so it's cached here.
+    sample_rate: Option<u32>,
+    deterministic: bool,
+    /// Mode of the last [`SoundTouch::set_varispeed`].
+    key_lock: bool,
+    pub(crate) state: StreamState,
+    sanitize: Sanitize,
+    /// Number of channels that [`SoundTouch::change_channels`] switches to once the
+    /// flushed output of the old layout has been received.
+    pending_channels: Option<u32>,
+    /// Output converted by [`ChannelChangePolicy::UpmixDownmixPending`] or passed around
+    /// SoundTouch by the [bypass], received before the output of SoundTouch.
+    ///
+    /// [bypass]: SoundTouch::set_bypass_when_neutral
+    #[cfg(feature = "alloc")]
+    pub(crate) converted: VecDeque<f32>,
+    /// Block size set with [`SoundTouch::set_output_block_frames`].
+    output_block: Option<usize>,
+    /// Silent frames the last flush added to complete the final block.
+    block_padding: usize,
+    /// Silent frames the last flush added, for the final block or where SoundTouch
+    /// stopped short of the expected length, that haven't been received yet.
+    padding_left: usize,
+    /// Set with [`SoundTouch::set_max_pending_output_frames`].
+    pub(crate) max_pending_output: Option<usize>,
+    /// Set with [`SoundTouch::set_flush_mode`].
+    flush_mode: FlushMode,
+    /// Frames put since the stream started, for [`FlushMode::PadToRatio`].
+    pub(crate) stream_frames_in: u64,
+    /// Frames received, skipped or discarded since the stream started, for
+    /// [`FlushMode::PadToRatio`].
+    pub(crate) stream_frames_out: u64,
+    /// Counts for [`SoundTouch::drift`], across streams.
+    pub(crate) drift: DriftTracker,
+    metering: bool,
+    meter: MeterSums,
+    /// Set with [`SoundTouch::set_correlation_channel_mask`].
+    pub(crate) correlation_mask: u32,
+    /// The channels scaled inside the pipeline, the correlation mask at the start of the
+    /// stream.
+    pub(crate) weighted_channels: u32,
+    #[cfg(feature = "alloc")]
+    pub(crate) settings_hook: Option<SettingsHook>,
+    /// Set with [`SoundTouch::set_pitch_with_formant_correction`].
+    #[cfg(feature = "alloc")]
+    pub(crate) formant: Option<FormantCorrection>,
+    /// Set with [`SoundTouch::set_collect_seek_stats`].
+    #[cfg(feature = "alloc")]
+    pub(crate) seek_analyzer: Option<SeekAnalyzer>,
+    /// Set with [`SoundTouch::set_analyze_loudness`].
+    #[cfg(feature = "alloc")]
+    pub(crate) loudness: Option<LoudnessMeter>,
+    /// Set with [`SoundTouch::set_bypass_when_neutral`].
+    #[cfg(feature = "alloc")]
+    pub(crate) bypass: Bypass,
+    /// Set with [`SoundTouch::set_input_staging_frames`].
+    #[cfg(feature = "alloc")]
+    pub(crate) staging: Staging,
+    /// Set with [`SoundTouch::set_block_timing`].
+    #[cfg(feature = "std")]
+    pub(crate) timings: Option<TimingRing>,
+    instance: Instance,
+}
 
+// SAFETY: The C++ object exclusively owns everything it points to (the rate
+// transposer, the tempo changer and their FIFO buffers are allocated by the
+// constructor and freed by the destructor), and neither it nor the vendored
+// sources use thread-local storage, so it can be moved to another thread.
+//
+// The only global mutable state is the CPU extension mask read during
+// construction, which the `cpu` module guards with a lock, and the
+// interpolation algorithm in `TransposerBase`, which is never written as its
+// setter isn't bound. The version string is a static literal.
+//
+// The type is deliberately not `Sync` (the raw pointers inside already prevent
+// it): nothing in SoundTouch is synchronized, so shared access from multiple
+// threads must go through a `Mutex`.
 unsafe impl Send for SoundTouch {}
 
 impl Default for SoundTouch {
     fn default() -> Self {
-        Self(unsafe { SoundTouchSys::new() })
+        Self::new()
     }
 }
 
 impl SoundTouch {
+    /// Maximum number of channels supported by SoundTouch.
+    pub const MAX_CHANNELS: u32 = 16;
+
+    /// Maximum sample rate supported by SoundTouch.
+    pub const MAX_SAMPLE_RATE: u32 = 192_000;
+
+    /// Smallest effective tempo or rate accepted by the `try_set_*` methods for tempo,
+    /// rate and pitch. See [`EffectiveParams`].
+    pub const MIN_EFFECTIVE_FACTOR: f64 = 0.125;
+
+    /// Largest effective tempo or rate accepted by the `try_set_*` methods for tempo,
+    /// rate and pitch. Beyond it the time-stretcher skips or repeats most of the input
+    /// and the internal buffers grow large. See [`EffectiveParams`].
+    ///
+    /// Up to it, e.g. to compress speech eight times, the output keeps coming: with the
+    /// automatic [sequence] and [seek window] lengths, the default, the sequences are at
+    /// their shortest from a tempo of `2.0` on, and each one yields a batch of output
+    /// for `tempo` times as much input. Explicit lengths are used as they are, so long
+    /// sequences make the output sparser and choppier at high tempos.
+    ///
+    /// [sequence]: crate::Setting::SequenceMs
+    /// [seek window]: crate::Setting::SeekwindowMs
+    pub const MAX_EFFECTIVE_FACTOR: f64 = 8.0;
+
     /// Crate a new SoundTouch instance.
     pub fn new() -> Self {
-        Self(unsafe { SoundTouchSys::new() })
+        Self {
+            // SAFETY: The constructor takes no arguments and doesn't throw.
+            inner: cpu::with_extensions_locked(|| unsafe { SoundTouchSys::new() }),
+            sample_rate: None,
+            deterministic: false,
+            key_lock: false,
+            state: StreamState::Streaming,
+            sanitize: Sanitize::Off,
+            pending_channels: None,
+            #[cfg(feature = "alloc")]
+            converted: VecDeque::new(),
+            output_block: None,
+            block_padding: 0,
+            padding_left: 0,
+            max_pending_output: None,
+            flush_mode: FlushMode::Raw,
+            stream_frames_in: 0,
+            stream_frames_out: 0,
+            drift: DriftTracker::default(),
+            metering: false,
+            meter: MeterSums::default(),
+            correlation_mask: 0,
+            weighted_channels: 0,
+            #[cfg(feature = "alloc")]
+            settings_hook: None,
+            #[cfg(feature = "alloc")]
+            formant: None,
+            #[cfg(feature = "alloc")]
+            seek_analyzer: None,
+            #[cfg(feature = "alloc")]
+            loudness: None,
+            #[cfg(feature = "alloc")]
+            bypass: Bypass::default(),
+            #[cfg(feature = "alloc")]
+            staging: Staging::default(),
+            #[cfg(feature = "std")]
+            timings: None,
+            instance: Instance::new("SoundTouch", 0, 0),
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the ID of the instance, unique within the process and increasing in order
+    /// of construction, for telling streams apart in logs. [`BPMDetect`] instances draw
+    /// from the same sequence. With the `registry` feature, the [registry] lists live
+    /// instances by ID.
+    ///
+    /// [`BPMDetect`]: crate::BPMDetect
+    /// [registry]: crate::registry
+    pub fn id(&self) -> u64 {
+        self.instance.id()
     }
 
     /// Set the number of channels.
     /// - 1 = mono
     /// - 2 = stereo
+    ///
+    /// Values outside `1..=`[`MAX_CHANNELS`] are ignored and trigger a debug assertion.
+    /// SoundTouch would throw a C++ exception for them, which aborts the process. Use
+    /// [`try_set_channels`] to handle them.
+    ///
+    /// Changing the number of channels while samples are buffered triggers a debug
+    /// assertion too, as SoundTouch would reinterpret them in the new layout. Use
+    /// [`change_channels`] to change it mid-stream.
+    ///
+    /// [`MAX_CHANNELS`]: SoundTouch::MAX_CHANNELS
+    /// [`try_set_channels`]: SoundTouch::try_set_channels
+    /// [`change_channels`]: SoundTouch::change_channels
     pub fn set_channels(&mut self, num_channels: u32) -> &mut Self {
+        debug_assert!(
+            Self::validate_channels(num_channels).is_ok(),
+            "invalid number of channels: {num_channels}"
+        );
+        debug_assert!(
+            self.inner.channels == 0
+                || self.inner.channels == num_channels
+                || self.pending_input_frames() == 0 && self.pipeline_ready_frames() == 0,
+            "number of channels changed to {num_channels} with samples buffered, use \
+             `change_channels`"
+        );
+        if Self::validate_channels(num_channels).is_err() {
+            return self;
+        }
+        self.pending_channels = None;
+        // SAFETY: `num_channels` was validated above, so `setChannels` doesn't throw.
         unsafe {
-            self.0.setChannels(num_channels);
+            self.inner.setChannels(num_channels);
         }
+        self.instance
+            .record_format(num_channels, self.sample_rate.unwrap_or(0));
+        self.notify_settings_changed();
         self
     }
 
+    /// Set the number of channels, returning an error if it is outside
+    /// `1..=`[`MAX_CHANNELS`].
+    ///
+    /// [`MAX_CHANNELS`]: SoundTouch::MAX_CHANNELS
+    pub fn try_set_channels(&mut self, num_channels: u32) -> Result<&mut Self, SoundTouchError> {
+        Self::validate_channels(num_channels)?;
+        Ok(self.set_channels(num_channels))
+    }
+
     /// Set the sample rate.
+    ///
+    /// Values outside `1..=`[`MAX_SAMPLE_RATE`] are ignored and trigger a debug
+    /// assertion. SoundTouch would throw a C++ exception for sample rates above the
+    /// maximum, which aborts the process. Use [`try_set_sample_rate`] to handle them.
+    ///
+    /// Changing the sample rate while input is waiting to be processed triggers a debug
+    /// assertion too, as that input would be processed as if it had the new rate. Use
+    /// [`change_sample_rate`] to change it mid-stream.
+    ///
+    /// [`MAX_SAMPLE_RATE`]: SoundTouch::MAX_SAMPLE_RATE
+    /// [`try_set_sample_rate`]: SoundTouch::try_set_sample_rate
+    /// [`change_sample_rate`]: SoundTouch::change_sample_rate
     pub fn set_sample_rate(&mut self, sample_rate: u32) -> &mut Self {
+        debug_assert!(
+            Self::validate_sample_rate(sample_rate).is_ok(),
+            "invalid sample rate: {sample_rate}"
+        );
+        debug_assert!(
+            self.sample_rate.unwrap_or(sample_rate) == sample_rate
+                || self.pending_input_frames() == 0,
+            "sample rate changed to {sample_rate} with input pending, use `change_sample_rate`"
+        );
+        if Self::validate_sample_rate(sample_rate).is_err() {
+            return self;
+        }
+        // SAFETY: `sample_rate` was validated above, so `setSampleRate` doesn't throw.
         unsafe {
-            self.0.setSampleRate(sample_rate);
+            self.inner.setSampleRate(sample_rate);
         }
+        self.sample_rate = Some(sample_rate);
+        self.instance
+            .record_format(self.inner.channels, sample_rate);
+        self.notify_settings_changed();
         self
     }
 
+    /// Set the sample rate, returning an error if it is outside
+    /// `1..=`[`MAX_SAMPLE_RATE`].
+    ///
+    /// [`MAX_SAMPLE_RATE`]: SoundTouch::MAX_SAMPLE_RATE
+    pub fn try_set_sample_rate(&mut self, sample_rate: u32) -> Result<&mut Self, SoundTouchError> {
+        Self::validate_sample_rate(sample_rate)?;
+        Ok(self.set_sample_rate(sample_rate))
+    }
+
+    pub(crate) fn validate_channels(num_channels: u32) -> Result<(), SoundTouchError> {
+        match num_channels {
+            1..=Self::MAX_CHANNELS => Ok(()),
+            _ => Err(SoundTouchError::InvalidChannels(num_channels)),
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Changes the sample rate in the middle of a stream, e.g. when an internet radio
+    /// stream switches rates between songs, without processing any input of the old
+    /// rate as if it had the new one.
+    ///
+    /// With [`RateChangePolicy::FlushThenChange`] the stream is [flushed] first, so all
+    /// of the old-rate audio stays ready to be received. Input put afterwards starts a
+    /// new stream at the new rate, see [`put_samples`] for what happens to output that
+    /// hasn't been received by then. With [`RateChangePolicy::DropPending`] the instance is
+    /// [cleared] instead.
+    ///
+    /// Returns [`SoundTouchError::InvalidSampleRate`] without changing anything if
+    /// `new_rate` is outside `1..=`[`MAX_SAMPLE_RATE`].
+    ///
+    /// [flushed]: SoundTouch::flush
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [cleared]: SoundTouch::clear
+    /// [`MAX_SAMPLE_RATE`]: SoundTouch::MAX_SAMPLE_RATE
+    pub fn change_sample_rate(
+        &mut self,
+        new_rate: u32,
+        policy: RateChangePolicy,
+    ) -> Result<&mut Self, SoundTouchError> {
+        Self::validate_sample_rate(new_rate)?;
+        match policy {
+            // Without a format nothing can have been put, so there is nothing to flush.
+            RateChangePolicy::FlushThenChange if self.check_configured().is_ok() => self.flush(),
+            RateChangePolicy::FlushThenChange => {}
+            RateChangePolicy::DropPending => self.clear(),
+        }
+        Ok(self.set_sample_rate(new_rate))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Changes the number of channels in the middle of a stream, without SoundTouch
+    /// reinterpreting the samples it has buffered in the new layout.
+    ///
+    /// With [`ChannelChangePolicy::FlushThenChange`] the stream is [flushed] first and
+    /// its output stays ready to be received in the old layout, which is what
+    /// [`num_channels`] returns until then. The new layout takes effect when input is
+    /// put after that output has been received (see [`put_samples`]), or when the
+    /// instance is [cleared]. With [`ChannelChangePolicy::DropPending`] the instance is
+    /// cleared instead, and with [`ChannelChangePolicy::UpmixDownmixPending`] the flushed
+    /// output is converted to the new layout and received before the output of the new
+    /// stream. In both cases the new layout takes effect right away.
+    ///
+    /// Returns [`SoundTouchError::InvalidChannels`] without changing anything if
+    /// `new_channels` is outside `1..=`[`MAX_CHANNELS`].
+    ///
+    /// [flushed]: SoundTouch::flush
+    /// [`num_channels`]: SoundTouch::num_channels
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [cleared]: SoundTouch::clear
+    /// [`MAX_CHANNELS`]: SoundTouch::MAX_CHANNELS
+    pub fn change_channels(
+        &mut self,
+        new_channels: u32,
+        policy: ChannelChangePolicy,
+    ) -> Result<&mut Self, SoundTouchError> {
+        Self::validate_channels(new_channels)?;
+        if self.check_configured().is_err() {
+            // Without a format nothing can have been put, so there is nothing to keep.
+            return Ok(self.set_channels(new_channels));
+        }
+        match policy {
+            ChannelChangePolicy::FlushThenChange => {
+                self.flush();
+                self.pending_channels = Some(new_channels);
+                if self.available_frames() == 0 {
+                    self.clear();
+                }
+            }
+            ChannelChangePolicy::DropPending => {
+                self.clear();
+                self.set_channels(new_channels);
+            }
+            #[cfg(feature = "alloc")]
+            ChannelChangePolicy::UpmixDownmixPending => {
+                let old_channels = self.num_channels() as usize;
+                let mut pending = Vec::from(core::mem::take(&mut self.converted));
+                // The stream goes on in the new layout, with the converted output counted
+                // when it is received.
+                let counters = (self.stream_frames_in, self.stream_frames_out, self.drift);
+                self.flush_with_mode(FlushMode::Raw);
+                let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+                loop {
+                    let frames = self
+                        .receive_from_pipeline(&mut buffer, STACK_BUFFER_SAMPLES / old_channels);
+                    if frames == 0 {
+                        break;
+                    }
+                    pending.extend_from_slice(&buffer[..frames * old_channels]);
+                }
+                self.clear();
+                (self.stream_frames_in, self.stream_frames_out, self.drift) = counters;
+                self.set_channels(new_channels);
+                self.converted = remix(&pending, old_channels, new_channels as usize);
+            }
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn validate_sample_rate(sample_rate: u32) -> Result<(), SoundTouchError> {
+        match sample_rate {
+            1..=Self::MAX_SAMPLE_RATE => Ok(()),
+            _ => Err(SoundTouchError::InvalidSampleRate(sample_rate)),
+        }
+    }
+
     /// Set the tempo of the audio to generate.
     pub fn set_tempo(&mut self, tempo: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setTempo(tempo);
+            self.inner.setTempo(tempo);
         }
+        self.notify_settings_changed();
         self
     }
 
     /// Sets new pitch control value. Original pitch = 1.0, smaller values
     /// represent lower pitches, larger values higher pitch.
     pub fn set_pitch(&mut self, pitch: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setPitch(pitch);
+            self.inner.setPitch(pitch);
         }
+        self.notify_settings_changed();
         self
     }
 
     /// Sets new rate control value. Normal rate = 1.0, smaller values
     /// represent slower rate, larger faster rates.
     pub fn set_rate(&mut self, rate: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setRate(rate);
+            self.inner.setRate(rate);
         }
+        self.notify_settings_changed();
+        self.warn_aliasing();
         self
     }
 
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the tempo, returning an error if the resulting effective tempo or rate would be
+    /// outside [`MIN_EFFECTIVE_FACTOR`]`..=`[`MAX_EFFECTIVE_FACTOR`]. Nothing is changed
+    /// in that case.
+    ///
+    /// [`MIN_EFFECTIVE_FACTOR`]: SoundTouch::MIN_EFFECTIVE_FACTOR
+    /// [`MAX_EFFECTIVE_FACTOR`]: SoundTouch::MAX_EFFECTIVE_FACTOR
+    pub fn try_set_tempo(&mut self, tempo: f64) -> Result<&mut Self, SoundTouchError> {
+        let params = self.effective_parameters();
+        EffectiveParams::new(tempo, params.virtual_rate, params.virtual_pitch).check()?;
+        Ok(self.set_tempo(tempo))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the pitch, returning an error if the resulting effective tempo or rate would be
+    /// outside [`MIN_EFFECTIVE_FACTOR`]`..=`[`MAX_EFFECTIVE_FACTOR`]. Nothing is changed
+    /// in that case.
+    ///
+    /// [`MIN_EFFECTIVE_FACTOR`]: SoundTouch::MIN_EFFECTIVE_FACTOR
+    /// [`MAX_EFFECTIVE_FACTOR`]: SoundTouch::MAX_EFFECTIVE_FACTOR
+    pub fn try_set_pitch(&mut self, pitch: f64) -> Result<&mut Self, SoundTouchError> {
+        let params = self.effective_parameters();
+        EffectiveParams::new(params.virtual_tempo, params.virtual_rate, pitch).check()?;
+        Ok(self.set_pitch(pitch))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Set the rate, returning an error if the resulting effective tempo or rate would be
+    /// outside [`MIN_EFFECTIVE_FACTOR`]`..=`[`MAX_EFFECTIVE_FACTOR`]. Nothing is changed
+    /// in that case.
+    ///
+    /// [`MIN_EFFECTIVE_FACTOR`]: SoundTouch::MIN_EFFECTIVE_FACTOR
+    /// [`MAX_EFFECTIVE_FACTOR`]: SoundTouch::MAX_EFFECTIVE_FACTOR
+    pub fn try_set_rate(&mut self, rate: f64) -> Result<&mut Self, SoundTouchError> {
+        let params = self.effective_parameters();
+        EffectiveParams::new(params.virtual_tempo, rate, params.virtual_pitch).check()?;
+        Ok(self.set_rate(rate))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets the tempo and returns the one set before, e.g. to undo the change later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_set_tempo`] would return an error, in which case nothing is
+    /// changed.
+    ///
+    /// [`try_set_tempo`]: SoundTouch::try_set_tempo
+    pub fn replace_tempo(&mut self, tempo: f64) -> f64 {
+        let old = self.effective_parameters().virtual_tempo;
+        self.try_set_tempo(tempo).or_panic();
+        old
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets the pitch and returns the one set before, e.g. to undo the change later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_set_pitch`] would return an error, in which case nothing is
+    /// changed.
+    ///
+    /// [`try_set_pitch`]: SoundTouch::try_set_pitch
+    pub fn replace_pitch(&mut self, pitch: f64) -> f64 {
+        let old = self.effective_parameters().virtual_pitch;
+        self.try_set_pitch(pitch).or_panic();
+        old
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets the rate and returns the one set before, e.g. to undo the change later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_set_rate`] would return an error, in which case nothing is
+    /// changed.
+    ///
+    /// [`try_set_rate`]: SoundTouch::try_set_rate
+    pub fn replace_rate(&mut self, rate: f64) -> f64 {
+        let old = self.effective_parameters().virtual_rate;
+        self.try_set_rate(rate).or_panic();
+        old
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the tempo, rate and pitch as set, together with the effective tempo and
+    /// rate derived from them.
+    pub fn effective_parameters(&self) -> EffectiveParams {
+        EffectiveParams::new(
+            self.inner.virtualTempo,
+            self.inner.virtualRate,
+            self.inner.virtualPitch,
+        )
+    }
+
     /// Sets new tempo control value as a difference in percents compared
     /// to the original tempo (-50 .. +100 %).
     pub fn set_tempo_change(&mut self, new_tempo: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setTempoChange(new_tempo);
+            self.inner.setTempoChange(new_tempo);
         }
+        self.notify_settings_changed();
         self
     }
 
     /// Sets new rate control value as a difference in percents compared
     /// to the original rate (-50 .. +100 %).
     pub fn set_rate_change(&mut self, new_rate: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setTempoChange(new_rate);
+            self.inner.setRateChange(new_rate);
         }
+        self.notify_settings_changed();
+        self.warn_aliasing();
         self
     }
 
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets a turntable-style speed change of `percent` percent, e.g. `8.0` for +8 %.
+    ///
+    /// Without `key_lock` this changes the rate, so pitch follows speed like on a
+    /// turntable. With `key_lock` it changes the tempo instead and keeps the pitch.
+    /// The other of the two is reset to its original value, and the pitch setting
+    /// isn't touched, so a pitch shift applies on top in both modes.
+    pub fn set_varispeed(&mut self, percent: f64, key_lock: bool) -> &mut Self {
+        self.key_lock = key_lock;
+        if key_lock {
+            self.set_rate(1.0).set_tempo_change(percent)
+        } else {
+            self.set_tempo(1.0).set_rate_change(percent)
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the speed change and mode last set with [`set_varispeed`], reading the
+    /// percentage back from the current rate or tempo.
+    ///
+    /// [`set_varispeed`]: SoundTouch::set_varispeed
+    pub fn varispeed(&self) -> Varispeed {
+        let factor = if self.key_lock {
+            self.inner.virtualTempo
+        } else {
+            self.inner.virtualRate
+        };
+        Varispeed {
+            percent: (factor - 1.0) * 100.0,
+            key_lock: self.key_lock,
+        }
+    }
+
     /// Sets pitch change in octaves compared to the original pitch
     /// `(-1.00 .. +1.00)`.
     pub fn set_pitch_octaves(&mut self, pitch_octaves: f64) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setPitchOctaves(pitch_octaves);
+            self.inner.setPitchOctaves(pitch_octaves);
         }
+        self.notify_settings_changed();
         self
     }
 
     /// Sets pitch change in semi-tones compared to the original pitch
     /// (-12 .. +12).
     pub fn set_pitch_semitones(&mut self, pitch_semitones: i32) -> &mut Self {
+        // SAFETY: Parameter setters store the value and don't throw.
         unsafe {
-            self.0.setPitchSemiTones(pitch_semitones);
+            self.inner.setPitchSemiTones(pitch_semitones);
         }
+        self.notify_settings_changed();
         self
     }
 
@@ -245,105 +1279,1366 @@ impl SoundTouch {
     /// [`Setting`] enum for available settings.
     ///
     ///[`Setting`]: Setting
+    ///
+    /// While [deterministic mode] is enabled, [`Setting::UseQuickseek`] is always kept disabled.
+    ///
+    /// An [`AaFilterLength`] that isn't a positive multiple of 8 is ignored and triggers a
+    /// debug assertion, as SoundTouch would throw a C++ exception for it, which aborts the
+    /// process. Use [`try_set_setting`] to handle it.
+    ///
+    /// [deterministic mode]: SoundTouch::set_deterministic
+    /// [`AaFilterLength`]: Setting::AaFilterLength
+    /// [`try_set_setting`]: SoundTouch::try_set_setting
     pub fn set_setting(&mut self, setting: Setting, value: i32) -> &mut Self {
+        debug_assert!(
+            Self::validate_setting(setting, value).is_ok(),
+            "invalid value {value} for setting {setting:?}"
+        );
+        if Self::validate_setting(setting, value).is_err() {
+            return self;
+        }
+        let value = match setting {
+            Setting::UseQuickseek if self.deterministic => 0,
+            _ => value,
+        };
+        // SAFETY: The values `setSetting` throws for were rejected above.
         unsafe {
-            self.0.setSetting(setting as c_int, value as c_int);
+            self.inner.setSetting(setting as c_int, value as c_int);
+        }
+        self.notify_settings_changed();
+        if setting == Setting::UseAaFilter {
+            self.warn_aliasing();
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Changes a setting, returning an error if the value is invalid for it. See
+    /// [`set_setting`].
+    ///
+    /// [`set_setting`]: SoundTouch::set_setting
+    pub fn try_set_setting(
+        &mut self,
+        setting: Setting,
+        value: i32,
+    ) -> Result<&mut Self, SoundTouchError> {
+        Self::validate_setting(setting, value)?;
+        Ok(self.set_setting(setting, value))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Changes a setting and returns the value it had before, as [`get_setting`] does,
+    /// or an error if the new value is invalid, as with [`try_set_setting`]. Nothing is
+    /// changed in that case.
+    ///
+    /// [`get_setting`]: SoundTouch::get_setting
+    /// [`try_set_setting`]: SoundTouch::try_set_setting
+    pub fn replace_setting(
+        &mut self,
+        setting: Setting,
+        value: i32,
+    ) -> Result<i32, SoundTouchError> {
+        let old = self.get_setting(setting);
+        self.try_set_setting(setting, value)?;
+        Ok(old)
+    }
+
+    fn validate_setting(setting: Setting, value: i32) -> Result<(), SoundTouchError> {
+        match setting {
+            Setting::AaFilterLength if value <= 0 || value % 8 != 0 => {
+                Err(SoundTouchError::InvalidSetting { setting, value })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Restores every writable [`Setting`] to its [default value], leaving tempo,
+    /// pitch, rate, sample rate and channels untouched.
+    ///
+    /// [default value]: Setting::default_value
+    pub fn reset_settings(&mut self) -> &mut Self {
+        for setting in Setting::WRITABLE {
+            self.set_setting(setting, setting.default_value());
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Resets the instance to the state of a newly created one without re-creating
+    /// the underlying SoundTouch object, which is useful when pooling instances.
+    ///
+    /// This clears all buffered samples, sets tempo, pitch and rate back to `1.0`
+    /// and restores the default settings (see [`reset_settings`]). It is equivalent
+    /// to `*self = SoundTouch::new()` minus the allocation, except that the number
+    /// of channels, the sample rate and [deterministic mode] keep their values,
+    /// as SoundTouch has no way of unsetting them. The callback set with
+    /// [`on_settings_changed`] is removed without being called, the [formant
+    /// correction] is removed and [seek statistics] and the [loudness] are no longer
+    /// collected.
+    ///
+    /// [`reset_settings`]: SoundTouch::reset_settings
+    /// [deterministic mode]: SoundTouch::set_deterministic
+    /// [`on_settings_changed`]: SoundTouch::on_settings_changed
+    /// [formant correction]: SoundTouch::set_pitch_with_formant_correction
+    /// [seek statistics]: SoundTouch::set_collect_seek_stats
+    /// [loudness]: SoundTouch::set_analyze_loudness
+    pub fn reset(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            self.settings_hook = None;
+            self.formant = None;
+            self.seek_analyzer = None;
+            self.loudness = None;
+            self.bypass = Bypass::default();
+            self.staging = Staging::default();
+        }
+        #[cfg(feature = "std")]
+        self.timings = None;
+        self.key_lock = false;
+        self.sanitize = Sanitize::Off;
+        self.output_block = None;
+        self.max_pending_output = None;
+        self.flush_mode = FlushMode::Raw;
+        self.metering = false;
+        self.meter = MeterSums::default();
+        self.correlation_mask = 0;
+        self.set_tempo(1.0)
+            .set_rate(1.0)
+            .set_pitch(1.0)
+            .reset_settings()
+            .clear();
+        self.drift = DriftTracker::default();
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables deterministic mode, for reproducible output such as
+    /// snapshot tests.
+    ///
+    /// When enabled, the SoundTouch object is re-created with all CPU-specific
+    /// (SIMD) code paths disabled (see the [`cpu`] module) and
+    /// [`Setting::UseQuickseek`] is forced off. Disabling it re-creates the
+    /// object with the extensions that are currently enabled process-wide.
+    /// Tempo, pitch, rate, sample rate, channels and settings are preserved, but
+    /// any buffered samples are discarded, so call this before processing.
+    ///
+    /// Guarantees: with deterministic mode enabled, identical input, settings
+    /// and call sequence produce bit-identical output on any machine running the
+    /// same build for the same target architecture, regardless of which SIMD
+    /// extensions the CPU supports.
+    ///
+    /// Output is **not** guaranteed to be bit-identical across target
+    /// architectures (e.g. `x86_64` vs `aarch64`) or across builds made with
+    /// different C++ compilers or flags. The remaining sources of difference are:
+    /// - floating point contraction, since the compiler may fuse multiply-adds
+    ///   into FMA instructions on some targets;
+    /// - the platform's `libm`, used to compute the anti-alias filter
+    ///   coefficients and the pitch factor in [`set_pitch_octaves`] and
+    ///   [`set_pitch_semitones`].
+    ///
+    /// [`cpu`]: crate::cpu
+    /// [`set_pitch_octaves`]: SoundTouch::set_pitch_octaves
+    /// [`set_pitch_semitones`]: SoundTouch::set_pitch_semitones
+    pub fn set_deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.deterministic = deterministic;
+        // SAFETY: The constructor takes no arguments and doesn't throw.
+        let inner = if deterministic {
+            cpu::with_extensions_disabled(Extensions::ALL, || unsafe { SoundTouchSys::new() })
+        } else {
+            cpu::with_extensions_locked(|| unsafe { SoundTouchSys::new() })
+        };
+        self.replace_inner(inner);
+        if deterministic {
+            self.set_setting(Setting::UseQuickseek, 0);
         }
         self
     }
 
+    /// Returns `true` if [deterministic mode] is enabled.
+    ///
+    /// [deterministic mode]: SoundTouch::set_deterministic
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Replaces the SoundTouch object with a newly constructed one, carrying over
+    /// the parameters and settings but not the buffered samples.
+    fn replace_inner(&mut self, inner: SoundTouchSys) {
+        // Restoring the parameters goes through states that are of no interest.
+        #[cfg(feature = "alloc")]
+        let settings_hook = self.settings_hook.take();
+        let values = Setting::WRITABLE.map(|setting| self.get_setting(setting));
+        self.end_drift_stream();
+        let channels = self.pending_channels.take().unwrap_or(self.inner.channels);
+        let (tempo, rate, pitch) = (
+            self.inner.virtualTempo,
+            self.inner.virtualRate,
+            self.inner.virtualPitch,
+        );
+
+        // SAFETY: The old object is destroyed exactly once and immediately overwritten
+        // without being dropped, so it is never used after destruction.
+        unsafe {
+            #[cfg(not(windows))]
+            ffi::SoundTouch_SoundTouch_destructor(&mut self.inner);
+            core::ptr::write(&mut self.inner, inner);
+        }
+        self.state = StreamState::Streaming;
+        #[cfg(feature = "alloc")]
+        {
+            self.converted.clear();
+            self.bypass.restart();
+        }
+        self.block_padding = 0;
+        self.padding_left = 0;
+        self.stream_frames_in = 0;
+        self.stream_frames_out = 0;
+        self.weighted_channels = self.correlation_mask;
+
+        if channels != 0 {
+            self.set_channels(channels);
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            self.set_sample_rate(sample_rate);
+        }
+        self.set_tempo(tempo).set_rate(rate).set_pitch(pitch);
+        for (setting, value) in Setting::WRITABLE.into_iter().zip(values) {
+            self.set_setting(setting, value);
+        }
+        #[cfg(feature = "alloc")]
+        {
+            self.settings_hook = settings_hook;
+        }
+        self.notify_settings_changed();
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Generates audio samples from given input samples using the settings set in the SoundTouch struct
+    /// and returns them in a vector.
+    ///
+    /// This is equivalent to calling [`put_samples`] with all the samples, [`flush`], and then
+    /// [`receive_samples`] until no more samples are ready. A trailing partial frame in `samples`
+    /// (when its length isn't a multiple of the number of channels) is ignored.
+    ///
+    /// The output is the input length times [`get_input_output_sample_ratio`] frames,
+    /// rounded, however short the input is. Inputs shorter than about one processing
+    /// sequence (see [`Setting::InitialLatency`]), like a 100 ms sound effect, aren't
+    /// stretched in a meaningful way though: the time-stretcher works by splicing
+    /// sequences of tens of milliseconds, so the output is mostly a cut or repeated part
+    /// of the input, ending in the silence that [`flush`] pushes through. Changing the
+    /// [rate] instead resamples short sounds without that problem, at the cost of their
+    /// pitch.
+    ///
+    /// The pipeline must be empty: samples put earlier, or output not received, would
+    /// otherwise end up in the output too. [`clear`] the instance first, or use
+    /// [`generate_audio_appending`] to process `samples` as the end of the stream that
+    /// was put so far. After a [`flush`] whose output has all been received, it is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set, or if the
+    /// pipeline isn't empty.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [`flush`]: SoundTouch::flush
+    /// [`get_input_output_sample_ratio`]: SoundTouch::get_input_output_sample_ratio
+    /// [rate]: SoundTouch::set_rate
+    /// [`clear`]: SoundTouch::clear
+    /// [`generate_audio_appending`]: SoundTouch::generate_audio_appending
+    #[cfg(feature = "alloc")]
+    pub fn generate_audio(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.try_generate_audio(samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio`], but returns an error instead of panicking.
+    ///
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    #[cfg(feature = "alloc")]
+    pub fn try_generate_audio(&mut self, samples: &[f32]) -> Result<Vec<f32>, SoundTouchError> {
+        let mut out_data = Vec::new();
+        self.try_generate_audio_extend(samples, &mut out_data)?;
+        Ok(out_data)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio`], but appends `samples` to whatever the pipeline holds.
+    ///
+    /// The output starts with the output of the earlier input that hasn't been received
+    /// yet, followed by that of `samples`, processed as one stream with it, and is then
+    /// flushed. This is [`process_chunk`] and [`finish`] into a new vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    /// [`process_chunk`]: SoundTouch::process_chunk
+    /// [`finish`]: SoundTouch::finish
+    #[cfg(feature = "alloc")]
+    pub fn generate_audio_appending(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.discard_flushed(samples.len());
+        self.try_generate_audio_appending(samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio_appending`], but returns an error instead of panicking.
+    ///
+    /// [`generate_audio_appending`]: SoundTouch::generate_audio_appending
+    #[cfg(feature = "alloc")]
+    pub fn try_generate_audio_appending(
+        &mut self,
+        samples: &[f32],
+    ) -> Result<Vec<f32>, SoundTouchError> {
+        let mut out_data = Vec::new();
+        self.try_process_chunk(samples, &mut out_data)?;
+        self.finish(&mut out_data);
+        Ok(out_data)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio`], but appends the output to `out`, which can be any
+    /// [`Extend<f32>`] collection or a [`SliceSink`].
+    ///
+    /// If `out` fills up, the rest of the output isn't received and stays ready in the
+    /// pipeline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set, or if the
+    /// pipeline isn't empty.
+    ///
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    /// [`SliceSink`]: crate::SliceSink
+    pub fn generate_audio_extend<S: OutputSink + ?Sized>(&mut self, samples: &[f32], out: &mut S) {
+        self.try_generate_audio_extend(samples, out).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`generate_audio_extend`], but returns an error instead of panicking.
+    ///
+    /// [`generate_audio_extend`]: SoundTouch::generate_audio_extend
+    pub fn try_generate_audio_extend<S: OutputSink + ?Sized>(
+        &mut self,
+        samples: &[f32],
+        out: &mut S,
+    ) -> Result<(), SoundTouchError> {
+        self.check_pipeline_empty()?;
+        self.try_process_chunk(samples, out)?;
+        self.finish(out);
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`try_generate_audio`], but checks `token` before every
+    /// [`CancellationToken::CHECK_FRAMES`] frames of input and before the flush.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::Cancelled`] once `token` is cancelled. The instance is
+    /// then [cleared], keeping its settings, and ready for a new stream. Otherwise
+    /// returns the errors of [`try_generate_audio`].
+    ///
+    /// [`try_generate_audio`]: SoundTouch::try_generate_audio
+    /// [cleared]: SoundTouch::clear
+    #[cfg(feature = "alloc")]
+    pub fn generate_audio_cancellable(
+        &mut self,
+        samples: &[f32],
+        token: &CancellationToken,
+    ) -> Result<Vec<f32>, SoundTouchError> {
+        self.check_configured()?;
+        self.check_pipeline_empty()?;
+        let block_len = CancellationToken::CHECK_FRAMES * self.num_channels() as usize;
+        let mut out_data = Vec::new();
+        for (i, block) in samples.chunks(block_len).enumerate() {
+            self.check_cancelled(token)?;
+            self.try_process_chunk(block, &mut out_data)
+                .map_err(|err| match err {
+                    SoundTouchError::NonFiniteInput { index } => SoundTouchError::NonFiniteInput {
+                        index: i * block_len + index,
+                    },
+                    err => err,
+                })?;
+        }
+        self.check_cancelled(token)?;
+        self.finish(&mut out_data);
+        Ok(out_data)
+    }
+
+    /// Clears the instance if `token` has been cancelled and returns
+    /// [`SoundTouchError::Cancelled`].
+    #[cfg(feature = "alloc")]
+    pub(crate) fn check_cancelled(
+        &mut self,
+        token: &CancellationToken,
+    ) -> Result<(), SoundTouchError> {
+        token.check().inspect_err(|_| self.clear())
+    }
+
+    /// Returns [`SoundTouchError::PipelineNotEmpty`] if input or output of the current
+    /// stream is buffered. Flushed output that has all been received doesn't count, as
+    /// the next input starts a new stream.
+    pub(crate) fn check_pipeline_empty(&self) -> Result<(), SoundTouchError> {
+        if self.state == StreamState::Flushed && self.ready_frames() == 0 {
+            return Ok(());
+        }
+        let pending_frames = self.pending_input_frames();
+        let ready_frames = self.ready_frames();
+        if pending_frames > 0 || ready_frames > 0 {
+            return Err(SoundTouchError::PipelineNotEmpty {
+                pending_frames,
+                ready_frames,
+            });
+        }
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Puts `input` and writes the ready output to `out`, without flushing. A trailing
+    /// partial frame in `input` is ignored. Call [`finish`] at the end of the stream.
+    ///
+    /// If `out` fills up, the rest of the output isn't received and stays ready in the
+    /// pipeline. Input after [`finish`] starts a new stream, see [`put_samples`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [`finish`]: SoundTouch::finish
+    /// [`put_samples`]: SoundTouch::put_samples
+    pub fn process_chunk<S: OutputSink + ?Sized>(&mut self, input: &[f32], out: &mut S) {
+        self.discard_flushed(input.len());
+        self.try_process_chunk(input, out).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`process_chunk`], but returns an error instead of panicking.
+    ///
+    /// [`process_chunk`]: SoundTouch::process_chunk
+    pub fn try_process_chunk<S: OutputSink + ?Sized>(
+        &mut self,
+        input: &[f32],
+        out: &mut S,
+    ) -> Result<(), SoundTouchError> {
+        self.check_configured()?;
+        let channels = self.num_channels() as usize;
+        // Receive after every chunk so that the output buffer inside SoundTouch stays small
+        // however long the input is.
+        let mut offset = 0;
+        for frames in ffi_chunks(input.len() / channels, self.num_channels()) {
+            self.try_put_samples(&input[offset * channels..], frames)
+                .map_err(|err| match err {
+                    SoundTouchError::NonFiniteInput { index } => SoundTouchError::NonFiniteInput {
+                        index: offset * channels + index,
+                    },
+                    err => err,
+                })?;
+            offset += frames;
+            self.receive_ready(out);
+        }
+        Ok(())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Ends the stream: [flushes] it and writes the rest of the output to `out`.
+    ///
+    /// As the stream is only flushed once, calling this again writes whatever didn't fit
+    /// into `out` before and nothing else.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [flushes]: SoundTouch::flush
+    pub fn finish<S: OutputSink + ?Sized>(&mut self, out: &mut S) {
+        self.check_configured().or_panic();
+        self.receive_ready(out);
+        self.flush();
+        self.receive_ready(out);
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Puts `input` and calls `on_output` with every batch of ready samples, without
+    /// flushing. A trailing partial frame in `input` is ignored.
+    ///
+    /// The batches borrow a buffer on the stack, so nothing is allocated. Each is a whole
+    /// number of frames and at most 4096 samples long. Call [`flush_with_callback`] at the
+    /// end of the stream.
+    ///
+    /// A panic in `on_output` unwinds out of this call, or aborts with `panic = "abort"`.
+    /// The batch being delivered and the rest of `input` are lost, but the instance stays
+    /// usable and keeps the input put before the panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [`flush_with_callback`]: SoundTouch::flush_with_callback
+    pub fn process_with_callback(&mut self, input: &[f32], on_output: impl FnMut(&[f32])) {
+        self.try_process_with_callback(input, on_output).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`process_with_callback`], but returns an error instead of panicking.
+    ///
+    /// [`process_with_callback`]: SoundTouch::process_with_callback
+    pub fn try_process_with_callback(
+        &mut self,
+        input: &[f32],
+        on_output: impl FnMut(&[f32]),
+    ) -> Result<(), SoundTouchError> {
+        self.try_process_chunk(input, &mut Callback(on_output))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`flush`], but also calls `on_output` with every batch of ready samples, as
+    /// [`process_with_callback`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [`process_with_callback`]: SoundTouch::process_with_callback
+    pub fn flush_with_callback(&mut self, on_output: impl FnMut(&[f32])) {
+        self.finish(&mut Callback(on_output));
+    }
+
+    /// Writes ready frames to `out` until none are left or `out` is full, and returns how
+    /// many there were.
+    pub(crate) fn receive_ready<S: OutputSink + ?Sized>(&mut self, out: &mut S) -> usize {
+        let channels = self.num_channels() as usize;
+        let room = out
+            .remaining()
+            .map_or(usize::MAX, |samples| samples / channels);
+        // Received in pieces of any length, so that blocks longer than the buffer get out.
+        let total = self.receivable_frames(room);
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        let buffer = &mut buffer[..STACK_BUFFER_SAMPLES / channels * channels];
+        let mut received = 0;
+        while received < total {
+            let frames = (total - received).min(buffer.len() / channels);
+            let chunk = self.take_ready(buffer, frames);
+            out.write(&buffer[..chunk * channels]);
+            received += chunk;
+            if chunk < frames {
+                break;
+            }
+        }
+        received
+    }
+
+    /// Adds `num_samples` pcs of samples from the `samples` memory position into
+    /// the input of the object.
+    ///
+    /// Note: `num_samples` should contain the number of samples per channel, i.e.
+    /// [`Frames`], not the length of `samples`, which is a number of [`Samples`].
+    /// Ex: If `samples.len()` is `6720` and there are `2` channels, then
+    /// `num_samples` should be `3360`. [`put_frames`] takes a typed frame count.
+    ///
+    /// After a [`flush`], input starts a new stream: the instance is [cleared] first, so
+    /// nothing of the previous stream is mixed into the new one. Flushed output that
+    /// hasn't been received by then is discarded; [`try_put_samples`] returns
+    /// [`SoundTouchError::AlreadyFlushed`] instead.
+    ///
+    /// **NOT FROM SOUNDTOUCH**: Putting `0` frames does nothing, whatever `samples` holds,
+    /// and neither calls into SoundTouch nor checks the configuration.
+    ///
+    /// # Panics
+    ///
+    /// Unless `num_samples` is `0`, panics if the number of channels or the sample rate
+    /// hasn't been set, if `samples` holds fewer than `num_samples` frames, or if more
+    /// output is waiting than the [cap].
+    ///
+    /// [`Frames`]: crate::units::Frames
+    /// [`Samples`]: crate::units::Samples
+    /// [`put_frames`]: SoundTouch::put_frames
+    /// [`flush`]: SoundTouch::flush
+    /// [cleared]: SoundTouch::clear
+    /// [`try_put_samples`]: SoundTouch::try_put_samples
+    /// [cap]: SoundTouch::set_max_pending_output_frames
+    pub fn put_samples(&mut self, samples: &[f32], num_samples: usize) {
+        self.discard_flushed(num_samples);
+        self.try_put_samples(samples, num_samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`put_samples`], but returns an error instead of panicking, and
+    /// [`SoundTouchError::AlreadyFlushed`] instead of discarding flushed output that
+    /// hasn't been received.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    pub fn try_put_samples(
+        &mut self,
+        samples: &[f32],
+        num_samples: usize,
+    ) -> Result<(), SoundTouchError> {
+        if num_samples == 0 {
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        let started = self.timing_start();
+        self.begin_put(num_samples)?;
+        let channels = self.num_channels() as usize;
+        if num_samples.saturating_mul(channels) > samples.len() {
+            return Err(SoundTouchError::InputTooShort {
+                frames: num_samples,
+                channels,
+                len: samples.len(),
+            });
+        }
+        let samples = &samples[..num_samples * channels];
+        let non_finite = match self.sanitize {
+            Sanitize::Off => None,
+            Sanitize::ZeroNonFinite | Sanitize::Error => first_non_finite(samples),
+        };
+        if let (Sanitize::Error, Some(index)) = (self.sanitize, non_finite) {
+            return Err(SoundTouchError::NonFiniteInput { index });
+        }
+
+        if non_finite.is_some() {
+            // Only copy when there is something to replace, so clean input costs no more
+            // than the scan.
+            let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+            for chunk in samples.chunks(STACK_BUFFER_SAMPLES / channels * channels) {
+                for (sanitized, &sample) in buffer.iter_mut().zip(chunk) {
+                    *sanitized = if sample.is_finite() { sample } else { 0.0 };
+                }
+                self.put_staged(&buffer, chunk.len() / channels);
+            }
+        } else {
+            self.put_staged(samples, num_samples);
+        }
+        #[cfg(feature = "std")]
+        self.record_timing(BlockKind::Put, num_samples, started);
+        Ok(())
+    }
+
+    /// Checks that `num_samples` frames can be put and starts a new stream if the last
+    /// one was flushed.
+    pub(crate) fn begin_put(&mut self, num_samples: usize) -> Result<(), SoundTouchError> {
+        self.check_configured()?;
+        if num_samples > 0 && self.state == StreamState::Flushed {
+            let ready_frames = self.available_frames();
+            if ready_frames > 0 {
+                return Err(SoundTouchError::AlreadyFlushed { ready_frames });
+            }
+            // Switches to the layout of a pending `change_channels` before the input is
+            // measured in it.
+            self.clear();
+        }
+        match self.max_pending_output {
+            Some(max_frames) if num_samples > 0 && self.ready_frames() > max_frames => {
+                Err(SoundTouchError::Backpressure {
+                    ready_frames: self.ready_frames(),
+                    max_frames,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts a new stream before `num_samples` frames are put if the last one was
+    /// flushed, discarding the flushed output that hasn't been received. The infallible
+    /// puts call this so that they never fail with [`SoundTouchError::AlreadyFlushed`].
+    pub(crate) fn discard_flushed(&mut self, num_samples: usize) {
+        if num_samples > 0 && self.state == StreamState::Flushed {
+            self.clear();
+        }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn put_staged(&mut self, samples: &[f32], frames: usize) {
+        self.put_unchecked(samples, frames);
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn staged_frames(&self) -> usize {
+        0
+    }
+
+    /// Puts `frames` frames of `samples` without any checks beyond the chunking.
+    pub(crate) fn put_unchecked(&mut self, samples: &[f32], frames: usize) {
+        self.record_drift_put(frames);
+        #[cfg(feature = "alloc")]
+        if self.loudness.is_some() {
+            self.measure_loudness(samples, frames);
+        }
+        #[cfg(feature = "alloc")]
+        let bypassed = self.bypass_put(samples, frames);
+        #[cfg(not(feature = "alloc"))]
+        let bypassed = false;
+        if !bypassed {
+            self.put_to_pipeline(samples, frames);
+            #[cfg(feature = "alloc")]
+            self.settle_bypass();
+        }
+        self.stream_frames_in += frames as u64;
+        self.instance.record_put(frames);
+        self.record_levels();
+    }
+
+    /// Puts `frames` frames of `samples` into SoundTouch, without counting them.
+    pub(crate) fn put_to_pipeline(&mut self, samples: &[f32], frames: usize) {
+        if !self.has_pipeline_weights() {
+            self.feed_pipeline(samples, frames);
+            return;
+        }
+        let channels = self.num_channels() as usize;
+        let mut buffer = [0.0; STACK_BUFFER_SAMPLES];
+        let chunk_len = STACK_BUFFER_SAMPLES / channels * channels;
+        for chunk in samples[..frames * channels].chunks(chunk_len) {
+            let weighted = &mut buffer[..chunk.len()];
+            weighted.copy_from_slice(chunk);
+            self.weigh(weighted);
+            self.feed_pipeline(weighted, chunk.len() / channels);
+        }
+    }
+
+    /// Puts `frames` frames of `samples`, weighted for the correlation mask, into
+    /// SoundTouch.
+    fn feed_pipeline(&mut self, samples: &[f32], frames: usize) {
+        #[cfg(feature = "alloc")]
+        if self.seek_analyzer.is_some() {
+            self.analyze_seek(samples, frames);
+        }
+        let channels = self.num_channels() as usize;
+        let mut offset = 0;
+        for chunk in ffi_chunks(frames, self.num_channels()) {
+            let inner = &mut self.inner as *mut _ as *mut c_void;
+            // SAFETY: `begin_put` checked that the channels are set, so `putSamples` doesn't
+            // throw, and it reads `chunk` frames of them.
+            ffi_call!([input = &samples[offset * channels..], chunk * channels] => {
+                ffi::SoundTouch_putSamples(inner, input, chunk as uint)
+            });
+            offset += chunk;
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets what [`put_samples`] and everything built on it do with NaN and infinite
+    /// input samples. The default is [`Sanitize::Off`], which skips the scan.
+    ///
+    /// Once a NaN is in the pipeline it spreads to all of the following output, so
+    /// use [`Sanitize::ZeroNonFinite`] or [`Sanitize::Error`] for input from decoders
+    /// or other code that isn't trusted. The scan is a single pass over the input that
+    /// the compiler can vectorize, and input is only copied when something has to be
+    /// replaced.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    pub fn set_input_sanitization(&mut self, sanitize: Sanitize) -> &mut Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the policy set with [`set_input_sanitization`].
+    ///
+    /// [`set_input_sanitization`]: SoundTouch::set_input_sanitization
+    pub fn input_sanitization(&self) -> Sanitize {
+        self.sanitize
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Makes [`receive_samples`] and everything built on it hand out output only in whole
+    /// blocks of `block_frames` frames, e.g. for an encoder that takes fixed-size packets.
+    /// Ready frames that don't fill a block wait in the output buffer for the next batch.
+    /// [`flush`] pads the output with silence to a whole number of blocks, see
+    /// [`output_block_padding`]. `None` and `Some(0)` hand out output as it gets ready,
+    /// which is the default.
+    ///
+    /// Set it before [flushing], as the padding is added by the flush.
+    ///
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [`flush`]: SoundTouch::flush
+    /// [`output_block_padding`]: SoundTouch::output_block_padding
+    /// [flushing]: SoundTouch::flush
+    pub fn set_output_block_frames(&mut self, block_frames: Option<usize>) -> &mut Self {
+        self.output_block = block_frames.filter(|&frames| frames > 0);
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables measuring the peak and RMS level of the output, as returned
+    /// by [`meter`]. Every frame received by [`receive_samples`] and everything built on
+    /// it is measured, including the silence that pads the last [output block]. Frames
+    /// discarded with [`skip_samples`] aren't.
+    ///
+    /// Disabled by default, which costs one branch per batch of output. Disabling it
+    /// keeps the levels measured so far, see [`reset_meter`].
+    ///
+    /// [`meter`]: SoundTouch::meter
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [output block]: SoundTouch::set_output_block_frames
+    /// [`skip_samples`]: SoundTouch::skip_samples
+    /// [`reset_meter`]: SoundTouch::reset_meter
+    pub fn set_metering(&mut self, enabled: bool) -> &mut Self {
+        self.metering = enabled;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns `true` if [metering] is enabled.
+    ///
+    /// [metering]: SoundTouch::set_metering
+    pub fn is_metering(&self) -> bool {
+        self.metering
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the levels of the output measured while [metering] was enabled, since the
+    /// meter was last [reset]. [`clear`] doesn't reset it.
+    ///
+    /// [metering]: SoundTouch::set_metering
+    /// [reset]: SoundTouch::reset_meter
+    /// [`clear`]: SoundTouch::clear
+    pub fn meter(&self) -> Meter {
+        self.meter.meter()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Resets the [meter] to no frames measured.
+    ///
+    /// [meter]: SoundTouch::meter
+    pub fn reset_meter(&mut self) {
+        self.meter = MeterSums::default();
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the block size set with [`set_output_block_frames`].
+    ///
+    /// [`set_output_block_frames`]: SoundTouch::set_output_block_frames
+    pub fn output_block_frames(&self) -> Option<usize> {
+        self.output_block
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of silent frames that the last [`flush`] added to complete the
+    /// final [output block], or `0` if there is no block size or the stream hasn't been
+    /// flushed since it was last [cleared].
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [output block]: SoundTouch::set_output_block_frames
+    /// [cleared]: SoundTouch::clear
+    pub fn output_block_padding(&self) -> usize {
+        self.block_padding
+    }
+
+    /// Output samples from beginning of the sample buffer. Copies requested samples to
+    /// output buffer and removes them from the sample buffer. If there are less than
+    /// `max_samples` samples in the buffer, returns all that available.
+    ///
+    /// Note: like with [`put_samples`], `max_samples` and the return value are in
+    /// samples per channel, i.e. [`Frames`]. [`receive_frames`] takes typed frame counts
+    /// and returns a [`ReceiveStatus`], whose frames are the number returned here.
+    ///
+    /// With an [output block size], only whole blocks are received, so this returns `0`
+    /// if `max_samples` is shorter than a block, and also while fewer frames than a block
+    /// are ready. `0` therefore doesn't mean that the pipeline is drained; see
+    /// [`pending_output`] for what is left.
+    ///
+    /// **NOT FROM SOUNDTOUCH**: A `max_samples` of `0` returns `0` and changes nothing,
+    /// with any buffer, including an empty one, and on an instance that isn't configured.
+    /// Otherwise `samples` must hold a whole number of frames, as a buffer whose length
+    /// isn't a multiple of the channels is usually sized for another number of channels
+    /// or handed on whole, shifting the channels of everything after it.
+    /// [`receive_frames`] receives into the whole frames of any buffer, and is the
+    /// recommended way to receive.
+    ///
+    /// The output doesn't depend on how it is received: pulling one frame at a time,
+    /// thousands at a time or everything with [`receive_all`] yields the same samples,
+    /// bit for bit, in the same order. The wrapper's own processing of the output, such
+    /// as the [formant correction], carries its state from one call to the next.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` has room for fewer than `max_samples` frames, or doesn't hold
+    /// a whole number of frames.
+    ///
+    /// [output block size]: SoundTouch::set_output_block_frames
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`Frames`]: crate::units::Frames
+    /// [`receive_frames`]: SoundTouch::receive_frames
+    /// [`receive_all`]: SoundTouch::receive_all
+    /// [formant correction]: SoundTouch::set_pitch_with_formant_correction
+    /// [`pending_output`]: SoundTouch::pending_output
+    pub fn receive_samples(&mut self, samples: &mut [f32], max_samples: usize) -> usize {
+        self.try_receive_samples(samples, max_samples).or_panic()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`receive_samples`], but returns an error instead of panicking.
+    ///
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    pub fn try_receive_samples(
+        &mut self,
+        samples: &mut [f32],
+        max_samples: usize,
+    ) -> Result<usize, SoundTouchError> {
+        if max_samples == 0 {
+            return Ok(0);
+        }
+        let channels = self.num_channels() as usize;
+        if max_samples.saturating_mul(channels) > samples.len() {
+            return Err(SoundTouchError::OutputTooSmall {
+                frames: max_samples,
+                channels,
+                len: samples.len(),
+            });
+        }
+        if samples.len() % channels.max(1) != 0 {
+            return Err(SoundTouchError::MisalignedOutput {
+                len: samples.len(),
+                channels,
+            });
+        }
+        #[cfg(feature = "std")]
+        let started = self.timing_start();
+        let frames = self.receivable_frames(max_samples);
+        let received = self.take_ready(samples, frames);
+        #[cfg(feature = "std")]
+        self.record_timing(BlockKind::Receive, received, started);
+        Ok(received)
+    }
+
+    /// Moves `frames` ready frames to `samples`, first the output converted by
+    /// [`change_channels`], then that of SoundTouch and then the padding of the last
+    /// block. Returns how many there were.
+    ///
+    /// [`change_channels`]: SoundTouch::change_channels
+    fn take_ready(&mut self, samples: &mut [f32], frames: usize) -> usize {
+        let channels = self.num_channels() as usize;
+        // Every receive goes through here, so a buffer handed on with a partial frame at
+        // its end, which would shift the channels of the output after it, is caught in
+        // debug builds whichever way it came in.
+        debug_assert!(
+            samples.len() % channels.max(1) == 0 && frames * channels <= samples.len(),
+            "receiving {frames} frames of {channels} channels into {} samples",
+            samples.len()
+        );
+        let converted = self.receive_converted(samples, frames);
+        let received = converted
+            + self.receive_from_pipeline(&mut samples[converted * channels..], frames - converted);
+        let padding = (frames - received).min(self.padding_left);
+        samples[received * channels..(received + padding) * channels].fill(0.0);
+        self.padding_left -= padding;
+        #[cfg(feature = "alloc")]
+        if let Some(formant) = &mut self.formant {
+            formant.apply(&mut samples[..(received + padding) * channels], channels);
+        }
+        if self.metering {
+            self.meter
+                .add(&samples[..(received + padding) * channels], channels);
+        }
+        self.stream_frames_out += (received + padding) as u64;
+        self.instance.record_received(received + padding);
+        self.record_levels();
+        received + padding
+    }
+
+    /// Returns how many of `max_frames` can be received: all ready frames up to
+    /// `max_frames`, but only whole [output blocks].
+    ///
+    /// After a flush the output ends with a whole block, unless the block size was
+    /// changed since, in which case the last frames are let out anyway.
+    ///
+    /// [output blocks]: SoundTouch::set_output_block_frames
+    fn receivable_frames(&self, max_frames: usize) -> usize {
+        let ready = self.ready_frames();
+        if self.state == StreamState::Flushed {
+            self.whole_blocks(max_frames).min(ready)
+        } else {
+            self.whole_blocks(max_frames.min(ready))
+        }
+    }
+
+    /// Rounds `frames` down to a multiple of the [output block size].
+    ///
+    /// [output block size]: SoundTouch::set_output_block_frames
+    fn whole_blocks(&self, frames: usize) -> usize {
+        match self.output_block {
+            Some(block) => frames - frames % block,
+            None => frames,
+        }
+    }
+
+    /// Receives up to `max_frames` frames from SoundTouch, bypassing the output converted
+    /// by [`change_channels`].
+    ///
+    /// [`change_channels`]: SoundTouch::change_channels
+    pub(crate) fn receive_from_pipeline(
+        &mut self,
+        samples: &mut [f32],
+        max_frames: usize,
+    ) -> usize {
+        let channels = self.num_channels() as usize;
+        let mut received = 0;
+        for frames in ffi_chunks(max_frames, self.num_channels()) {
+            let inner = &mut self.inner as *mut _ as *mut c_void;
+            let output = &mut samples[received * channels..];
+            // SAFETY: `receiveSamples` writes at most `frames` frames of the channels the
+            // pipeline holds, which are those of the instance.
+            let chunk = ffi_call!([output = output, frames * channels] => {
+                ffi::SoundTouch_receiveSamples(inner, output, frames as uint) as usize
+            });
+            received += chunk;
+            if chunk < frames {
+                break;
+            }
+        }
+        self.unweigh(&mut samples[..received * channels]);
+        received
+    }
+
+    /// Moves up to `max_frames` frames of the output converted by [`change_channels`]
+    /// to `samples`.
+    ///
+    /// [`change_channels`]: SoundTouch::change_channels
+    #[cfg(feature = "alloc")]
+    fn receive_converted(&mut self, samples: &mut [f32], max_frames: usize) -> usize {
+        let frames = self.converted_frames().min(max_frames);
+        let len = frames * self.num_channels() as usize;
+        for (out, sample) in samples.iter_mut().zip(self.converted.drain(..len)) {
+            *out = sample;
+        }
+        frames
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn receive_converted(&mut self, _samples: &mut [f32], _max_frames: usize) -> usize {
+        0
+    }
+
+    /// Returns the number of frames converted by [`change_channels`] that haven't been
+    /// received yet.
+    ///
+    /// [`change_channels`]: SoundTouch::change_channels
+    #[cfg(feature = "alloc")]
+    pub(crate) fn converted_frames(&self) -> usize {
+        self.converted
+            .len()
+            .checked_div(self.num_channels() as usize)
+            .unwrap_or(0)
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn converted_frames(&self) -> usize {
+        0
+    }
+
+    /// Returns an error instead of letting SoundTouch throw a C++ exception, which would
+    /// abort the process.
+    pub(crate) fn check_configured(&self) -> Result<(), SoundTouchError> {
+        if self.inner.channels != 0 && self.sample_rate.is_some() {
+            Ok(())
+        } else {
+            Err(SoundTouchError::NotConfigured)
+        }
+    }
+
+    /// Adjusts book-keeping so that given number of samples are removed from beginning of the
+    /// sample buffer without copying them anywhere.
+    #[deprecated(note = "use `skip_samples` instead")]
+    pub fn receive_samples_no_in(&mut self, max_samples: usize) -> usize {
+        self.skip_samples(max_samples)
+    }
+
+    /// Discards up to `frames` ready frames (samples per channel) from the beginning of the
+    /// output buffer without copying them anywhere.
+    ///
+    /// Returns the number of frames actually discarded, which is less than `frames` if fewer
+    /// were ready. The next call to [`receive_samples`] continues right after the discarded
+    /// frames. [`skip_frames`] takes and returns typed frame counts. With an [output block
+    /// size], only whole blocks are discarded. Skipping `0` frames does nothing.
+    ///
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [output block size]: SoundTouch::set_output_block_frames
+    /// [`skip_frames`]: SoundTouch::skip_frames
+    pub fn skip_samples(&mut self, frames: usize) -> usize {
+        if frames == 0 {
+            return 0;
+        }
+        let frames = self.receivable_frames(frames);
+        #[cfg(feature = "alloc")]
+        let mut skipped = {
+            let converted = self.converted_frames().min(frames);
+            self.converted
+                .drain(..converted * self.num_channels() as usize);
+            converted
+        };
+        #[cfg(not(feature = "alloc"))]
+        let mut skipped = 0;
+        for chunk in ffi_chunks(frames - skipped, self.num_channels()) {
+            // SAFETY: `receiveSamples1` only discards output, there is no buffer to overrun.
+            let chunk_skipped = unsafe {
+                ffi::SoundTouch_receiveSamples1(
+                    &mut self.inner as *mut _ as *mut c_void,
+                    chunk as uint,
+                ) as usize
+            };
+            skipped += chunk_skipped;
+            if chunk_skipped < chunk {
+                break;
+            }
+        }
+        let padding = (frames - skipped).min(self.padding_left);
+        self.padding_left -= padding;
+        self.stream_frames_out += (skipped + padding) as u64;
+        self.record_levels();
+        skipped + padding
+    }
+
     /// **NOT FROM SOUNDTOUCH**
     ///
-    /// Generates audio samples from given input samples using the settings set in the SoundTouch struct
-    /// and returns them in a vector.
+    /// Like [`put_samples`], but with the number of frames typed as [`Frames`].
     ///
-    /// This is equivalent to calling [`put_samples`] and [`receive_samples`]
-    /// until the unprocessed sample buffer is empty.
+    /// # Panics
     ///
-    /// Do not use [`put_samples`] or [`receive_samples`] with this function.
+    /// Unless `frames` is `0`, panics if the number of channels or the sample rate
+    /// hasn't been set, or if `samples` holds fewer than `frames`.
     ///
     /// [`put_samples`]: SoundTouch::put_samples
+    pub fn put_frames(&mut self, samples: &[f32], frames: Frames) {
+        self.put_samples(samples, frames.0);
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`receive_samples`], but with the frame counts typed as [`Frames`], and
+    /// returning what is left to receive together with the frames received, so that a
+    /// loop can stop once [`Pending::MoreAvailable`] is no longer reported instead of
+    /// relying on a call that receives nothing.
+    ///
+    /// Unlike [`receive_samples`], `samples` doesn't have to hold a whole number of
+    /// frames: the frames are received into its whole frames and a trailing partial
+    /// frame is left as it is. This is the recommended way to receive, e.g. into a
+    /// buffer of an odd number of samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` has room for fewer than `max_frames`.
+    ///
     /// [`receive_samples`]: SoundTouch::receive_samples
-    #[cfg(feature = "alloc")]
-    pub fn generate_audio(&mut self, samples: &[f32]) -> Vec<f32> {
-        const BUF_SIZE: usize = 6720;
-        let mut new_samples: [f32; BUF_SIZE] = [0.0; BUF_SIZE];
-        let mut out_data: Vec<f32> = Vec::with_capacity(samples.len());
-        unsafe {
-            let ptr: *mut c_void = &mut self.0 as *mut _ as *mut c_void;
-            putSamples(
-                ptr,
-                samples.as_ptr(),
-                samples.len() as u32 / self.0.channels,
-            );
-            let mut n_samples: u32 = 1;
-            while n_samples != 0 {
-                n_samples = receiveSamples(
-                    ptr,
-                    new_samples.as_mut_ptr(),
-                    BUF_SIZE as u32 / self.0.channels,
-                );
-                out_data.extend_from_slice(&new_samples);
-            }
-            self.0.flush();
+    pub fn receive_frames(&mut self, samples: &mut [f32], max_frames: Frames) -> ReceiveStatus {
+        let channels = (self.num_channels() as usize).max(1);
+        let whole = samples.len() / channels * channels;
+        let frames = Frames(self.receive_samples(&mut samples[..whole], max_frames.0));
+        ReceiveStatus {
+            frames,
+            pending: self.pending_within(max_frames.0),
         }
-        out_data
     }
 
-
-    /// Adds `num_samples` pcs of samples from the `samples` memory position into
-    /// the input of the object. Notice that sample rate **must** be set before
-    /// calling this function, otherwise throws a runtime_error exception.
+    /// **NOT FROM SOUNDTOUCH**
     ///
-    /// Note: `num_samples` should contain the number of samples per channel.
-    /// Ex: If `samples.len()` is `6720` and there are `2` channels, then
-    /// `num_samples` should be `3360`.
-    pub fn put_samples(&mut self, samples: &[f32], num_samples: usize) {
-        unsafe {
-            ffi::SoundTouch_putSamples(
-                &mut self.0 as *mut _ as *mut c_void,
-                samples.as_ptr(),
-                num_samples as uint,
-            );
-        }
+    /// Returns what is left to receive: [`Pending::MoreAvailable`] if
+    /// [`available_frames`] isn't `0`, [`Pending::PartialBlockHeld`] if frames are only
+    /// held back for an incomplete [output block], and [`Pending::None`] otherwise.
+    ///
+    /// [`available_frames`]: SoundTouch::available_frames
+    /// [output block]: SoundTouch::set_output_block_frames
+    pub fn pending_output(&self) -> Pending {
+        self.pending_within(usize::MAX)
     }
 
-    /// Output samples from beginning of the sample buffer. Copies requested samples to
-    /// output buffer and removes them from the sample buffer. If there are less than
-    /// `max_samples` samples in the buffer, returns all that available.
-    pub fn receive_samples(&mut self, samples: &mut [f32], max_samples: usize) -> usize {
-        unsafe {
-            ffi::SoundTouch_receiveSamples(
-                &mut self.0 as *mut _ as *mut c_void,
-                samples.as_mut_ptr(),
-                max_samples as uint,
-            ) as usize
+    /// Returns what is left to receive with room for `max_frames` frames:
+    /// [`Pending::MoreAvailable`] only if a receive of `max_frames` gets any, and
+    /// [`Pending::BufferTooSmall`] if a larger one would.
+    pub(crate) fn pending_within(&self, max_frames: usize) -> Pending {
+        if self.receivable_frames(max_frames) > 0 {
+            Pending::MoreAvailable
+        } else if self.receivable_frames(usize::MAX) > 0 {
+            Pending::BufferTooSmall {
+                block_frames: self.output_block.unwrap_or(1),
+            }
+        } else if self.ready_frames() > 0 {
+            Pending::PartialBlockHeld
+        } else {
+            Pending::None
         }
     }
 
-    /// Adjusts book-keeping so that given number of samples are removed from beginning of the
-    /// sample buffer without copying them anywhere.
-    pub fn receive_samples_no_in(&mut self, max_samples: usize) -> usize {
-        unsafe {
-            ffi::SoundTouch_receiveSamples1(
-                &mut self.0 as *mut _ as *mut c_void,
-                max_samples as uint,
-            ) as usize
-        }
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Receives all [available] output into a new vector of interleaved samples, the
+    /// same as [`receive_samples`] with a buffer large enough for it.
+    ///
+    /// [available]: SoundTouch::available_frames
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    #[cfg(feature = "alloc")]
+    pub fn receive_all(&mut self) -> Vec<f32> {
+        let frames = self.available_frames();
+        let mut samples = alloc::vec![0.0; frames * self.num_channels() as usize];
+        let received = self.receive_samples(&mut samples, frames);
+        samples.truncate(received * self.num_channels() as usize);
+        samples
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`skip_samples`], but with the frame counts typed as [`Frames`].
+    ///
+    /// [`skip_samples`]: SoundTouch::skip_samples
+    pub fn skip_frames(&mut self, frames: Frames) -> Frames {
+        Frames(self.skip_samples(frames.0))
     }
 
     /// Returns number of samples currently unprocessed.
+    ///
+    /// **NOT FROM SOUNDTOUCH**: This includes the frames waiting in the [staging buffer].
+    ///
+    /// [staging buffer]: SoundTouch::set_input_staging_frames
     pub fn num_unprocessed_samples(&self) -> usize {
-        unsafe {
-            ffi::SoundTouch_numUnprocessedSamples(&self.0 as *const _ as *mut c_void) as usize
-        }
+        // SAFETY: `numUnprocessedSamples` is a `const` method, so the pointer isn't
+        // written through.
+        let unprocessed = unsafe {
+            ffi::SoundTouch_numUnprocessedSamples(&self.inner as *const _ as *mut c_void) as usize
+        };
+        unprocessed + self.staged_frames()
     }
 
     /// Clears all the samples in the object's output and internal processing
-    /// buffers.
+    /// buffers, which starts a new stream after a [`flush`].
+    ///
+    /// Besides SoundTouch's buffers and the counts it bases the [flush padding] on, this
+    /// clears the output converted by [`change_channels`] and the padding of the last
+    /// [output block], sets the [state] back to [`StreamState::Streaming`] and applies a
+    /// pending [`change_channels`] and [correlation channel mask]. Parameters, settings,
+    /// the [input sanitization], the output block size, the [ID], the frame counts in the
+    /// [registry] and the counts of the [drift report] are kept. Use [`clear_output_only`]
+    /// to keep the input.
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [flush padding]: SoundTouch::expected_flush_padding
+    /// [`change_channels`]: SoundTouch::change_channels
+    /// [output block]: SoundTouch::set_output_block_frames
+    /// [state]: SoundTouch::state
+    /// [input sanitization]: SoundTouch::set_input_sanitization
+    /// [ID]: SoundTouch::id
+    /// [registry]: crate::registry
+    /// [`clear_output_only`]: SoundTouch::clear_output_only
+    /// [correlation channel mask]: SoundTouch::set_correlation_channel_mask
+    /// [drift report]: SoundTouch::drift
     pub fn clear(&mut self) {
+        self.end_drift_stream();
+        // SAFETY: `clear` only empties the buffers of a live object.
+        unsafe {
+            ffi::SoundTouch_clear(&mut self.inner as *mut _ as *mut c_void);
+        }
+        #[cfg(feature = "alloc")]
+        {
+            self.converted.clear();
+            self.bypass.restart();
+            self.discard_staged();
+            if let Some(formant) = &mut self.formant {
+                formant.reset();
+            }
+            if let Some(analyzer) = &mut self.seek_analyzer {
+                analyzer.restart();
+            }
+        }
+        self.block_padding = 0;
+        self.padding_left = 0;
+        self.stream_frames_in = 0;
+        self.stream_frames_out = 0;
+        self.weighted_channels = self.correlation_mask;
+        self.state = StreamState::Streaming;
+        if let Some(channels) = self.pending_channels {
+            self.set_channels(channels);
+        }
+        self.record_levels();
+    }
+
+    /// Empties SoundTouch's buffers after a flush whose output was received and goes on
+    /// with the stream, unlike [`clear`] keeping the output received before SoundTouch's,
+    /// the stream counters and the state of the formant correction.
+    ///
+    /// Returns the number of silent frames the flush still had to add, leaving out those
+    /// completing an [output block].
+    ///
+    /// [`clear`]: SoundTouch::clear
+    /// [output block]: SoundTouch::set_output_block_frames
+    #[cfg(feature = "alloc")]
+    pub(crate) fn restart_pipeline(&mut self) -> usize {
+        // SAFETY: `clear` only empties the buffers of a live object.
         unsafe {
-            ffi::SoundTouch_clear(&mut self.0 as *mut _ as *mut c_void);
+            ffi::SoundTouch_clear(&mut self.inner as *mut _ as *mut c_void);
+        }
+        let padding = self.padding_left.saturating_sub(self.block_padding);
+        self.block_padding = 0;
+        self.padding_left = 0;
+        self.weighted_channels = self.correlation_mask;
+        self.state = StreamState::Streaming;
+        padding
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Discards all ready output but keeps the input waiting to be processed, e.g. to
+    /// drop stale audio after an underrun without losing what is queued behind it.
+    ///
+    /// This discards what [`available_frames`] counts plus the frames held back for an
+    /// incomplete [output block], i.e. SoundTouch's output buffer, the output converted
+    /// by [`change_channels`] and the padding of the last block. The frames are skipped,
+    /// so SoundTouch counts them as output, and a later [`flush`] only adds what is
+    /// still expected for the kept input. Everything else, including the [state], the
+    /// input and overlap buffers of the time-stretcher and the rate transposer, and the
+    /// frame counts in the [registry], is left as it is. After a [`flush`] this makes
+    /// the instance ready for a new stream, as nothing is left to receive.
+    ///
+    /// [`available_frames`]: SoundTouch::available_frames
+    /// [output block]: SoundTouch::set_output_block_frames
+    /// [`change_channels`]: SoundTouch::change_channels
+    /// [`flush`]: SoundTouch::flush
+    /// [state]: SoundTouch::state
+    /// [registry]: crate::registry
+    pub fn clear_output_only(&mut self) {
+        self.stream_frames_out += self.ready_frames() as u64;
+        #[cfg(feature = "alloc")]
+        self.converted.clear();
+        self.padding_left = 0;
+        for chunk in ffi_chunks(self.pipeline_ready_frames(), self.num_channels()) {
+            // SAFETY: `receiveSamples1` only discards output, there is no buffer to overrun.
+            let skipped = unsafe {
+                ffi::SoundTouch_receiveSamples1(
+                    &mut self.inner as *mut _ as *mut c_void,
+                    chunk as uint,
+                ) as usize
+            };
+            if skipped < chunk {
+                break;
+            }
         }
     }
 
@@ -354,15 +2649,221 @@ impl SoundTouch {
     /// stream. This function may introduce additional blank samples in the end
     /// of the sound stream, and thus it's not recommended to call this function
     /// in the middle of a sound stream.
+    ///
+    /// Flushing an already flushed stream does nothing, see [`state`].
+    ///
+    /// The stream's total output is the input length times
+    /// [`get_input_output_sample_ratio`], rounded, even for inputs shorter than the
+    /// [initial latency]: SoundTouch pushes at most 25600 blank frames through the
+    /// pipeline, and should that not be enough, the rest is silence added here. This is
+    /// [`expected_flush_padding`] frames more than were ready before.
+    ///
+    /// This is [`FlushMode::Raw`], the default. Other [flush modes] leave out the
+    /// silence or cut the output to an exact length.
+    ///
+    /// With an [output block size], the output is padded with silence to a whole number
+    /// of blocks, see [`output_block_padding`], in every flush mode.
+    ///
+    /// [`state`]: SoundTouch::state
+    /// [`get_input_output_sample_ratio`]: SoundTouch::get_input_output_sample_ratio
+    /// [initial latency]: Setting::InitialLatency
+    /// [`expected_flush_padding`]: SoundTouch::expected_flush_padding
+    /// [flush modes]: SoundTouch::set_flush_mode
+    /// [output block size]: SoundTouch::set_output_block_frames
+    /// [`output_block_padding`]: SoundTouch::output_block_padding
     pub fn flush(&mut self) {
+        self.flush_with_mode(self.flush_mode);
+    }
+
+    pub(crate) fn flush_with_mode(&mut self, mode: FlushMode) {
+        if self.state == StreamState::Flushed {
+            return;
+        }
+        #[cfg(feature = "alloc")]
+        self.forward_staged();
+        let ready_before = self.pipeline_ready_frames();
+        let expected = ready_before + self.expected_flush_padding();
+        // SAFETY: `flush` only pushes silence through the pipeline of a live object.
         unsafe {
-            ffi::SoundTouch_flush(&mut self.0);
+            ffi::SoundTouch_flush(&mut self.inner);
+        }
+        self.state = StreamState::Flushed;
+        let flushed = self.pipeline_ready_frames();
+        #[cfg(feature = "alloc")]
+        self.settle_bypass();
+        match mode {
+            FlushMode::Raw => self.padding_left = expected.saturating_sub(flushed),
+            FlushMode::DrainOnly => {
+                self.padding_left = 0;
+                self.trim_silent_tail(ready_before);
+            }
+            FlushMode::PadToRatio => {
+                let ratio = self.get_input_output_sample_ratio();
+                let target = (self.stream_frames_in as f64 * ratio + 0.5) as u64;
+                let have = self.stream_frames_out
+                    + self.converted_frames() as u64
+                    + self.pipeline_ready_frames() as u64;
+                if have < target {
+                    self.padding_left = (target - have) as usize;
+                } else {
+                    self.padding_left = 0;
+                    let pipeline = self.pipeline_ready_frames();
+                    let excess = ((have - target) as usize).min(pipeline);
+                    self.truncate_pipeline(pipeline - excess);
+                }
+            }
+        }
+        if let Some(block) = self.output_block {
+            let partial = self.ready_frames() % block;
+            self.block_padding = if partial == 0 { 0 } else { block - partial };
+            self.padding_left += self.block_padding;
+        }
+        self.record_levels();
+    }
+
+    /// Drops the silent frames at the end of SoundTouch's output buffer, but none of the
+    /// first `keep` frames.
+    fn trim_silent_tail(&mut self, keep: usize) {
+        const SILENCE: f32 = 1.0e-6;
+        let channels = self.num_channels() as usize;
+        let frames = self.pipeline_ready_frames();
+        if frames <= keep {
+            return;
+        }
+        // SAFETY: `ptrBegin` points to the `frames * channels` ready samples of the output
+        // buffer, which nothing else accesses while `self` is borrowed.
+        let samples = unsafe {
+            let begin = ffi::FIFOSampleBuffer_ptrBegin(self.output_buffer());
+            slice::from_raw_parts(begin, frames * channels)
+        };
+        let mut end = frames;
+        while end > keep
+            && samples[(end - 1) * channels..end * channels]
+                .iter()
+                .enumerate()
+                .all(|(channel, s)| s.abs() < SILENCE * self.pipeline_weight(channel))
+        {
+            end -= 1;
+        }
+        self.truncate_pipeline(end);
+    }
+
+    /// Drops the frames of SoundTouch's output buffer after the first `frames`.
+    fn truncate_pipeline(&mut self, frames: usize) {
+        if frames < self.pipeline_ready_frames() {
+            // SAFETY: `adjustAmountOfSamples` only lowers the sample count of the live
+            // output buffer, which stays valid.
+            unsafe {
+                ffi::FIFOSampleBuffer_adjustAmountOfSamples(
+                    self.output_buffer() as *mut ffi::FIFOSampleBuffer,
+                    frames as uint,
+                );
+            }
+        }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets how [`flush`] ends the stream, see [`FlushMode`]. This is also used by
+    /// everything that flushes, e.g. [`finish`], [`generate_audio`] and [`flush_trimmed`].
+    /// The default is [`FlushMode::Raw`], and [`reset`] restores
+    /// it.
+    ///
+    /// [`FlushMode::PadToRatio`] counts the frames put and received since the stream
+    /// started, i.e. since the instance was created or last [cleared].
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [`finish`]: SoundTouch::finish
+    /// [`generate_audio`]: SoundTouch::generate_audio
+    /// [`flush_trimmed`]: SoundTouch::flush_trimmed
+    /// [`reset`]: SoundTouch::reset
+    /// [cleared]: SoundTouch::clear
+    pub fn set_flush_mode(&mut self, mode: FlushMode) -> &mut Self {
+        self.flush_mode = mode;
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the [flush mode].
+    ///
+    /// [flush mode]: SoundTouch::set_flush_mode
+    pub fn flush_mode(&self) -> FlushMode {
+        self.flush_mode
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns whether the stream is still running or has been [flushed].
+    ///
+    /// [flushed]: SoundTouch::flush
+    pub fn state(&self) -> StreamState {
+        self.state
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns `true` if the stream has been [flushed] and no input has been put since.
+    ///
+    /// [flushed]: SoundTouch::flush
+    pub fn is_flushed(&self) -> bool {
+        self.state == StreamState::Flushed
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Like [`flush`], but also receives all ready samples into `out` and drops the silent
+    /// frames at the end of the flushed tail.
+    ///
+    /// Only frames appended by the flush itself are considered, so quiet audio that was
+    /// already ready before the call is never trimmed. A frame counts as silent when every
+    /// channel is below `1e-6` in absolute value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of channels or the sample rate hasn't been set.
+    ///
+    /// [`flush`]: SoundTouch::flush
+    #[cfg(feature = "alloc")]
+    pub fn flush_trimmed(&mut self, out: &mut Vec<f32>) {
+        const SILENCE: f32 = 1.0e-6;
+        self.check_configured().or_panic();
+        let channels = self.num_channels() as usize;
+        let start = out.len();
+        let ready = self.available_frames();
+        self.flush();
+        let received = self.receive_ready(out);
+
+        let tail_start = start + ready.min(received) * channels;
+        let mut end = out.len();
+        while end > tail_start && out[end - channels..end].iter().all(|s| s.abs() < SILENCE) {
+            end -= channels;
         }
+        out.truncate(end);
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of frames that [`flush`] will append to the ready output.
+    ///
+    /// SoundTouch trims the flushed output so that the total output length matches the
+    /// input length times [`get_input_output_sample_ratio`], so this is that expected
+    /// length minus the frames already received or ready. The frames are produced by
+    /// pushing blank samples through the pipeline, so whatever part of them isn't covered
+    /// by input still in the pipeline is silence. Note that SoundTouch counts those blank
+    /// samples as input, so the value is not `0` right after a flush.
+    ///
+    /// [`flush`]: SoundTouch::flush
+    /// [`get_input_output_sample_ratio`]: SoundTouch::get_input_output_sample_ratio
+    pub fn expected_flush_padding(&self) -> usize {
+        let expected = (self.inner.samplesExpectedOut + 0.5) as u64;
+        let produced = self.inner.samplesOutput as u64 + self.pipeline_ready_frames() as u64;
+        expected.saturating_sub(produced) as usize
     }
 
     /// Returns number of channels.
     pub fn num_channels(&self) -> u32 {
-        self.0.channels
+        self.inner.channels
     }
 
     /// Gets a setting controlling the processing system behaviour. See the
@@ -370,7 +2871,8 @@ impl SoundTouch {
     ///
     ///[`Setting`]: Setting
     pub fn get_setting(&self, setting: Setting) -> i32 {
-        unsafe { self.0.getSetting(setting as c_int) }
+        // SAFETY: `getSetting` returns 0 for unknown settings instead of throwing.
+        unsafe { self.inner.getSetting(setting as c_int) }
     }
 
     /// Get ratio between input and output audio durations, useful for calculating
@@ -392,26 +2894,208 @@ impl SoundTouch {
     ///
     /// [`get_input_output_sample_ratio`]: SoundTouch::get_input_output_sample_ratio
     pub fn get_input_output_sample_ratio(&mut self) -> f64 {
-        unsafe { self.0.getInputOutputSampleRatio() }
+        // SAFETY: Only reads the effective parameters.
+        unsafe { self.inner.getInputOutputSampleRatio() }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the sample rate, or `None` if it hasn't been set.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of frames (samples per channel) that make up `secs`
+    /// seconds at the configured sample rate, rounded to the nearest frame.
+    /// Negative durations give `0`.
+    ///
+    /// Returns `None` if the sample rate hasn't been set.
+    pub fn frames_for_duration(&self, secs: f64) -> Option<usize> {
+        let sample_rate = self.sample_rate?;
+        // f64::round needs std; the cast saturates negative values to 0.
+        Some((secs * sample_rate as f64 + 0.5) as usize)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the duration in seconds of `frames` frames (samples per channel) at
+    /// the configured sample rate.
+    ///
+    /// Returns `None` if the sample rate hasn't been set.
+    pub fn duration_for_frames(&self, frames: usize) -> Option<f64> {
+        let sample_rate = self.sample_rate?;
+        Some(frames as f64 / sample_rate as f64)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the expected duration in seconds of the output produced from
+    /// `secs` seconds of input with the current settings, using
+    /// [`get_input_output_sample_ratio`].
+    ///
+    /// Returns `None` if the sample rate hasn't been set.
+    ///
+    /// [`get_input_output_sample_ratio`]: SoundTouch::get_input_output_sample_ratio
+    pub fn output_duration_for_input(&mut self, secs: f64) -> Option<f64> {
+        self.sample_rate?;
+        Some(secs * self.get_input_output_sample_ratio())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets [`Setting::SequenceMs`] to the length of `frames` frames at the configured
+    /// sample rate.
+    ///
+    /// SoundTouch only takes whole milliseconds, so the length is rounded to the nearest
+    /// millisecond, i.e. it is off by at most half a millisecond (22 frames at 44.1 kHz).
+    /// [`sequence_frames`] returns the length actually used. `0` lets SoundTouch choose
+    /// the length from the tempo.
+    ///
+    /// Returns [`SoundTouchError::NotConfigured`] if the sample rate hasn't been set.
+    ///
+    /// [`sequence_frames`]: SoundTouch::sequence_frames
+    pub fn set_sequence_frames(&mut self, frames: usize) -> Result<&mut Self, SoundTouchError> {
+        self.set_setting_frames(Setting::SequenceMs, frames)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets [`Setting::SeekwindowMs`] to the length of `frames` frames at the configured
+    /// sample rate, rounded to the nearest millisecond as with [`set_sequence_frames`].
+    /// [`seek_window_frames`] returns the length actually used. `0` lets SoundTouch
+    /// choose the length from the tempo.
+    ///
+    /// Returns [`SoundTouchError::NotConfigured`] if the sample rate hasn't been set.
+    ///
+    /// [`set_sequence_frames`]: SoundTouch::set_sequence_frames
+    /// [`seek_window_frames`]: SoundTouch::seek_window_frames
+    pub fn set_seek_window_frames(&mut self, frames: usize) -> Result<&mut Self, SoundTouchError> {
+        self.set_setting_frames(Setting::SeekwindowMs, frames)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Sets [`Setting::OverlapMs`] to the length of `frames` frames at the configured
+    /// sample rate, rounded to the nearest millisecond as with [`set_sequence_frames`].
+    /// SoundTouch further rounds the overlap down to a multiple of 8 frames and makes it
+    /// at least 16 frames long; [`overlap_frames`] returns the length actually used.
+    ///
+    /// Returns [`SoundTouchError::NotConfigured`] if the sample rate hasn't been set.
+    ///
+    /// [`set_sequence_frames`]: SoundTouch::set_sequence_frames
+    /// [`overlap_frames`]: SoundTouch::overlap_frames
+    pub fn set_overlap_frames(&mut self, frames: usize) -> Result<&mut Self, SoundTouchError> {
+        self.set_setting_frames(Setting::OverlapMs, frames)
+    }
+
+    fn set_setting_frames(
+        &mut self,
+        setting: Setting,
+        frames: usize,
+    ) -> Result<&mut Self, SoundTouchError> {
+        let sample_rate = self.sample_rate.ok_or(SoundTouchError::NotConfigured)? as u64;
+        let ms = (frames as u64)
+            .saturating_mul(1000)
+            .saturating_add(sample_rate / 2)
+            / sample_rate;
+        Ok(self.set_setting(setting, ms.min(i32::MAX as u64) as i32))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the length of a processing sequence in frames, computed from
+    /// [`Setting::SequenceMs`] as SoundTouch does: truncated to whole frames and at least
+    /// twice the [overlap].
+    ///
+    /// Returns `None` if the sample rate hasn't been set or the length is chosen
+    /// automatically from the tempo.
+    ///
+    /// [overlap]: SoundTouch::overlap_frames
+    pub fn sequence_frames(&self) -> Option<usize> {
+        let frames = self.setting_frames(Setting::SequenceMs)?;
+        if frames == 0 {
+            return None;
+        }
+        Some(frames.max(2 * self.overlap_frames()?))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the length of the seek window in frames, computed from
+    /// [`Setting::SeekwindowMs`] as SoundTouch does: truncated to whole frames.
+    ///
+    /// Returns `None` if the sample rate hasn't been set or the length is chosen
+    /// automatically from the tempo.
+    pub fn seek_window_frames(&self) -> Option<usize> {
+        self.setting_frames(Setting::SeekwindowMs)
+            .filter(|&frames| frames > 0)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the length of the overlap in frames, computed from [`Setting::OverlapMs`]
+    /// as SoundTouch does: truncated to whole frames, at least 16 and rounded down to a
+    /// multiple of 8.
+    ///
+    /// Returns `None` if the sample rate hasn't been set.
+    pub fn overlap_frames(&self) -> Option<usize> {
+        let frames = self.setting_frames(Setting::OverlapMs)?.max(16);
+        Some(frames - frames % 8)
+    }
+
+    /// Converts the millisecond value of `setting` to frames, truncating like SoundTouch.
+    fn setting_frames(&self, setting: Setting) -> Option<usize> {
+        let sample_rate = self.sample_rate? as usize;
+        let ms = self.get_setting(setting).max(0) as usize;
+        Some(sample_rate * ms / 1000)
     }
 
     /// Returns the SoundTouch library version Id.
     pub fn get_version_id() -> u32 {
+        // SAFETY: Returns a constant.
         unsafe { ffi::SoundTouch_getVersionId() }
     }
 
     /// Returns SoundTouch library version string.
-    pub fn get_version_string() -> &'static str {
-        unsafe {
-            let ptr = ffi::SoundTouch_getVersionString();
-            let c_str = core::ffi::CStr::from_ptr(ptr);
-            c_str.to_str().unwrap()
+    ///
+    /// The string is copied, with invalid UTF-8 replaced by `U+FFFD`, so it needn't
+    /// outlive the call. If the library returns no string, the version is formatted
+    /// from [`get_version_id`] with [`version_string_from_id`].
+    ///
+    /// [`get_version_id`]: SoundTouch::get_version_id
+    /// [`version_string_from_id`]: SoundTouch::version_string_from_id
+    #[cfg(feature = "alloc")]
+    pub fn get_version_string() -> String {
+        // SAFETY: Returns null or a pointer to a NUL-terminated string, the version
+        // literal in current versions.
+        let ptr = unsafe { ffi::SoundTouch_getVersionString() };
+        if !ptr.is_null() {
+            // SAFETY: `ptr` points to a NUL-terminated string that SoundTouch neither
+            // changes nor frees, and it is copied before returning.
+            let version = unsafe { core::ffi::CStr::from_ptr(ptr) }.to_string_lossy();
+            if !version.is_empty() {
+                return version.into_owned();
+            }
         }
+        Self::version_string_from_id(Self::get_version_id())
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Formats a version id as returned by [`get_version_id`], `10000 * major + 100 *
+    /// minor + patch`, as `major.minor.patch`.
+    ///
+    /// [`get_version_id`]: SoundTouch::get_version_id
+    #[cfg(feature = "alloc")]
+    pub fn version_string_from_id(id: u32) -> String {
+        alloc::format!("{}.{}.{}", id / 10000, id / 100 % 100, id % 100)
     }
 
     /// Returns nonzero if there aren't any `ready` samples.
     pub fn is_empty(&mut self) -> i32 {
-        unsafe { ffi::FIFOSampleBuffer_isEmpty(&mut self.0 as *mut _ as *mut c_void) as i32 }
+        (self.available_frames() == 0) as i32
     }
 
     /// Get number of `ready` samples that can be received with
@@ -419,13 +3103,119 @@ impl SoundTouch {
     ///
     /// [`receive_samples`]: SoundTouch::receive_samples
     pub fn num_samples(&mut self) -> i32 {
-        unsafe { ffi::FIFOSampleBuffer_numSamples(&mut self.0 as *mut _ as *mut c_void) as i32 }
+        self.available_frames() as i32
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of unprocessed and ready frames together.
+    ///
+    /// [`SoundTouch`] isn't [`Sync`], so nothing can put or receive samples while this
+    /// borrow is held and the two values always belong to the same state of the
+    /// pipeline. When sharing an instance between threads behind a lock, read the
+    /// levels while holding the lock rather than calling [`num_unprocessed_samples`]
+    /// and [`num_samples`] separately.
+    ///
+    /// [`num_unprocessed_samples`]: SoundTouch::num_unprocessed_samples
+    /// [`num_samples`]: SoundTouch::num_samples
+    pub fn buffer_levels(&self) -> BufferLevels {
+        BufferLevels {
+            unprocessed_frames: self.pending_input_frames(),
+            ready_frames: self.available_frames(),
+        }
+    }
+
+    /// Updates the buffer levels listed in the [registry](crate::registry).
+    fn record_levels(&self) {
+        #[cfg(feature = "registry")]
+        self.instance.record_levels(self.buffer_levels());
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of frames (samples per channel) ready to be received with
+    /// [`receive_samples`]. This is [`num_samples`] without needing `&mut self`.
+    ///
+    /// With an [output block size], only whole blocks are counted until the stream is
+    /// flushed.
+    ///
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [`num_samples`]: SoundTouch::num_samples
+    /// [output block size]: SoundTouch::set_output_block_frames
+    pub fn available_frames(&self) -> usize {
+        self.receivable_frames(usize::MAX)
+    }
+
+    /// Returns the number of ready frames, including those that can't be received yet
+    /// because they don't fill an [output block].
+    ///
+    /// [output block]: SoundTouch::set_output_block_frames
+    pub(crate) fn ready_frames(&self) -> usize {
+        self.converted_frames() + self.pipeline_ready_frames() + self.padding_left
+    }
+
+    /// Returns the number of frames ready in SoundTouch's output buffer, which doesn't
+    /// include the output converted by [`change_channels`].
+    ///
+    /// [`change_channels`]: SoundTouch::change_channels
+    pub(crate) fn pipeline_ready_frames(&self) -> usize {
+        // SAFETY: `output_buffer` points to the live output buffer of the pipeline.
+        unsafe { ffi::FIFOSampleBuffer_numSamples(self.output_buffer()) as usize }
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the number of input frames (samples per channel) waiting to be processed.
+    /// This is the same as [`num_unprocessed_samples`].
+    ///
+    /// [`num_unprocessed_samples`]: SoundTouch::num_unprocessed_samples
+    pub fn pending_input_frames(&self) -> usize {
+        self.num_unprocessed_samples()
+    }
+
+    /// Returns the `FIFOSampleBuffer` holding the ready samples.
+    ///
+    /// `numSamples` and `isEmpty` are virtual and not bound for `SoundTouch`, so the
+    /// buffer is found by following the pipeline: the SoundTouch output is the last
+    /// processing stage (`TDStretch` or `RateTransposer`, both `FIFOProcessor`s),
+    /// whose own output is its `FIFOSampleBuffer`.
+    fn output_buffer(&self) -> *mut c_void {
+        // SAFETY: SoundTouch sets `output` to one of its processing stages in its
+        // constructor and never frees it before its destructor, and every stage's
+        // `output` is its own `FIFOSampleBuffer`.
+        unsafe {
+            let stage = self.inner._base.output as *const ffi::FIFOProcessor;
+            (*stage).output as *mut c_void
+        }
+    }
+}
+
+impl fmt::Debug for SoundTouch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self.effective_parameters();
+        f.debug_struct("SoundTouch")
+            .field("id", &self.id())
+            .field("channels", &self.num_channels())
+            .field("sample_rate", &self.sample_rate)
+            .field("tempo", &params.virtual_tempo)
+            .field("rate", &params.virtual_rate)
+            .field("pitch", &params.virtual_pitch)
+            .field("deterministic", &self.deterministic)
+            .field("buffer_levels", &self.buffer_levels())
+            .field("state", &self.state)
+            .field("sanitize", &self.sanitize)
+            .field("pending_channels", &self.pending_channels)
+            .field("output_block", &self.output_block)
+            .field("metering", &self.metering)
+            .field("buffer_requirements", &self.buffer_requirements())
+            .finish()
     }
 }
 
 #[cfg(not(windows))]
 impl Drop for SoundTouch {
     fn drop(&mut self) {
-        unsafe { ffi::SoundTouch_SoundTouch_destructor(&mut self.0) };
+        // SAFETY: The object is destroyed once, when it is dropped.
+        unsafe { ffi::SoundTouch_SoundTouch_destructor(&mut self.inner) };
     }
 }
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+
+use crate::SoundTouch;
+
+/// The input buffer set with [`SoundTouch::set_input_staging_frames`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Staging {
+    /// Frames per batch forwarded to SoundTouch, `0` when staging is off.
+    batch_frames: usize,
+    /// Interleaved input put but not forwarded yet, shorter than a batch.
+    samples: Vec<f32>,
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Collects the input of [`put_samples`] and everything built on it in a buffer and
+    /// puts it into SoundTouch only in batches of `frames` frames, e.g. for a decoder that
+    /// hands out packets of a few hundred frames, each of which would otherwise be put on
+    /// its own. `None` and `Some(0)` put the input right away, which is the default.
+    ///
+    /// SoundTouch's output doesn't depend on the sizes of the calls, so staging only
+    /// delays it by up to a batch: the output is the same as without it as long as the
+    /// parameters don't change. Staged frames are processed with the tempo, pitch and
+    /// rate in effect when they are put into SoundTouch. [`num_unprocessed_samples`]
+    /// counts them, [`flush`] puts them before flushing and [`clear`] discards them.
+    /// Changing the batch size puts the staged frames right away.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`num_unprocessed_samples`]: SoundTouch::num_unprocessed_samples
+    /// [`flush`]: SoundTouch::flush
+    /// [`clear`]: SoundTouch::clear
+    pub fn set_input_staging_frames(&mut self, frames: Option<usize>) -> &mut Self {
+        self.forward_staged();
+        let batch_frames = frames.unwrap_or(0);
+        self.staging.batch_frames = batch_frames;
+        let len = batch_frames * self.num_channels() as usize;
+        if self.staging.samples.capacity() < len {
+            self.staging.samples.reserve_exact(len);
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the batch size set with [`set_input_staging_frames`].
+    ///
+    /// [`set_input_staging_frames`]: SoundTouch::set_input_staging_frames
+    pub fn input_staging_frames(&self) -> Option<usize> {
+        Some(self.staging.batch_frames).filter(|&frames| frames > 0)
+    }
+
+    /// Returns the number of frames waiting in the staging buffer.
+    pub(crate) fn staged_frames(&self) -> usize {
+        self.staging
+            .samples
+            .len()
+            .checked_div(self.num_channels() as usize)
+            .unwrap_or(0)
+    }
+
+    /// Puts `frames` frames of `samples` through the staging buffer.
+    pub(crate) fn put_staged(&mut self, samples: &[f32], frames: usize) {
+        let batch_frames = self.staging.batch_frames;
+        if batch_frames == 0 {
+            self.put_unchecked(samples, frames);
+            return;
+        }
+        let channels = self.num_channels() as usize;
+        let batch = batch_frames * channels;
+        let mut samples = &samples[..frames * channels];
+        if !self.staging.samples.is_empty() {
+            let missing = batch - self.staging.samples.len();
+            let (head, rest) = samples.split_at(missing.min(samples.len()));
+            self.staging.samples.extend_from_slice(head);
+            samples = rest;
+            if self.staging.samples.len() < batch {
+                return;
+            }
+            self.forward_staged();
+        }
+        // Whole batches are put straight from the input.
+        let whole = samples.len() / batch * batch;
+        if whole > 0 {
+            self.put_unchecked(&samples[..whole], whole / channels);
+        }
+        self.staging.samples.extend_from_slice(&samples[whole..]);
+    }
+
+    /// Puts the staged frames into SoundTouch.
+    pub(crate) fn forward_staged(&mut self) {
+        if self.staging.samples.is_empty() {
+            return;
+        }
+        let frames = self.staged_frames();
+        // Taken for the call and put back, keeping its capacity.
+        let mut staged = core::mem::take(&mut self.staging.samples);
+        self.put_unchecked(&staged, frames);
+        staged.clear();
+        self.staging.samples = staged;
+    }
+
+    /// Discards the staged frames.
+    pub(crate) fn discard_staged(&mut self) {
+        self.staging.samples.clear();
+    }
+}
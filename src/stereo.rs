@@ -0,0 +1,262 @@
+use crate::error::OrPanic;
+use crate::{Config, Setting, SoundTouch, SoundTouchError, TimeStretcher};
+
+/// Frames converted at a time on the stack.
+const BLOCK_FRAMES: usize = 1024;
+
+/// Sequence length the linked instances of [`StereoMode::MidSide`] use when the
+/// configuration leaves it automatic, SoundTouch's choice at a tempo of about 1.
+const FIXED_SEQUENCE_MS: i32 = 40;
+
+/// Seek window length the linked instances use when the configuration leaves it
+/// automatic.
+const FIXED_SEEKWINDOW_MS: i32 = 15;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// How a [`StereoStretcher`] processes its two channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StereoMode {
+    /// Left and right through one stereo instance, as [`SoundTouch`] does on its own.
+    /// The time-stretcher picks one splice point for both channels from their sum.
+    #[default]
+    LeftRight,
+    /// Mid, `(L + R) / 2`, and side, `(L - R) / 2`, each through its own mono instance,
+    /// converted back to left and right afterwards. The side channel gets splice points
+    /// that fit it rather than the mid channel, so the stereo width of wide material
+    /// survives transient-heavy passages better.
+    MidSide,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// A stereo time-stretcher that can process mid and side separately, see
+/// [`StereoMode`].
+///
+/// In [`StereoMode::MidSide`] the two linked instances are configured identically, with
+/// quickseek off and fixed sequence and seek window lengths instead of automatic ones,
+/// so that they always produce the same number of frames for the same input. Only as
+/// many frames as both have ready are received, so their outputs stay sample-aligned.
+/// Should their counts ever differ after a block of input anyway, it is counted in
+/// [`misaligned_blocks`], and once the stream is flushed the shorter one is padded with
+/// silent frames, counted in [`padded_frames`], so no output is lost.
+///
+/// ```rust
+/// use soundtouch::{Config, StereoMode, StereoStretcher, TimeStretcher};
+///
+/// let config = Config::new().set_tempo(1.25).clone();
+/// let mut stretcher = StereoStretcher::new(&config, StereoMode::MidSide).unwrap();
+/// stretcher.put(&[0.0; 44100 * 2]);
+/// stretcher.flush();
+/// let mut output = [0.0; 4096];
+/// while stretcher.receive(&mut output) > 0 {}
+/// assert_eq!(stretcher.misaligned_blocks(), 0);
+/// ```
+///
+/// [`misaligned_blocks`]: StereoStretcher::misaligned_blocks
+/// [`padded_frames`]: StereoStretcher::padded_frames
+#[derive(Debug)]
+pub struct StereoStretcher {
+    mode: StereoMode,
+    /// The stereo instance, or the mid channel's in [`StereoMode::MidSide`].
+    main: SoundTouch,
+    /// The side channel's instance in [`StereoMode::MidSide`].
+    side: Option<SoundTouch>,
+    flushed: bool,
+    misaligned_blocks: u64,
+    padded_frames: u64,
+}
+
+impl StereoStretcher {
+    /// Creates a stretcher processing stereo input with `config` in `mode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::ChannelMismatch`] if `config` isn't for 2 channels, and
+    /// the error of [`Config::validate`] if it is invalid otherwise.
+    pub fn new(config: &Config, mode: StereoMode) -> Result<Self, SoundTouchError> {
+        Self::check(config)?;
+        let (main, side) = match mode {
+            StereoMode::LeftRight => (SoundTouch::with_config(config), None),
+            StereoMode::MidSide => {
+                let linked = Self::linked_config(config);
+                (
+                    SoundTouch::with_config(&linked),
+                    Some(SoundTouch::with_config(&linked)),
+                )
+            }
+        };
+        Ok(Self {
+            mode,
+            main,
+            side,
+            flushed: false,
+            misaligned_blocks: 0,
+            padded_frames: 0,
+        })
+    }
+
+    /// Returns the mode the stretcher was created with.
+    pub fn mode(&self) -> StereoMode {
+        self.mode
+    }
+
+    /// Returns the number of blocks of input after which the linked instances of
+    /// [`StereoMode::MidSide`] had different numbers of frames ready. This is `0` unless
+    /// SoundTouch processes the two channels inconsistently.
+    pub fn misaligned_blocks(&self) -> u64 {
+        self.misaligned_blocks
+    }
+
+    /// Returns the number of silent frames added to the shorter output of the linked
+    /// instances at the end of the stream to keep them aligned.
+    pub fn padded_frames(&self) -> u64 {
+        self.padded_frames
+    }
+
+    /// Puts the interleaved stereo `samples`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::PartialFrame`] if `samples` doesn't hold a whole number
+    /// of frames, and the errors of [`SoundTouch::try_put_samples`].
+    pub fn try_put(&mut self, samples: &[f32]) -> Result<(), SoundTouchError> {
+        if samples.len() % 2 != 0 {
+            return Err(SoundTouchError::PartialFrame {
+                trailing_samples: 1,
+                channels: 2,
+            });
+        }
+        let Some(side) = &mut self.side else {
+            return self.main.try_put_samples(samples, samples.len() / 2);
+        };
+        let mut mid_block = [0.0; BLOCK_FRAMES];
+        let mut side_block = [0.0; BLOCK_FRAMES];
+        for block in samples.chunks(2 * BLOCK_FRAMES) {
+            let frames = block.len() / 2;
+            for (i, frame) in block.chunks_exact(2).enumerate() {
+                mid_block[i] = (frame[0] + frame[1]) * 0.5;
+                side_block[i] = (frame[0] - frame[1]) * 0.5;
+            }
+            self.main.try_put_samples(&mid_block[..frames], frames)?;
+            side.try_put_samples(&side_block[..frames], frames)?;
+            if self.main.available_frames() != side.available_frames() {
+                self.misaligned_blocks += 1;
+            }
+        }
+        self.flushed = false;
+        Ok(())
+    }
+
+    /// Checks that `config` is valid and for stereo.
+    fn check(config: &Config) -> Result<(), SoundTouchError> {
+        config.validate()?;
+        match config.channels() {
+            2 => Ok(()),
+            actual => Err(SoundTouchError::ChannelMismatch {
+                expected: 2,
+                actual,
+            }),
+        }
+    }
+
+    /// Returns the mono configuration of the linked instances.
+    fn linked_config(config: &Config) -> Config {
+        let mut linked = config.clone();
+        linked.set_channels(1).set_setting(Setting::UseQuickseek, 0);
+        if config.setting(Setting::SequenceMs) == 0 {
+            linked.set_setting(Setting::SequenceMs, FIXED_SEQUENCE_MS);
+        }
+        if config.setting(Setting::SeekwindowMs) == 0 {
+            linked.set_setting(Setting::SeekwindowMs, FIXED_SEEKWINDOW_MS);
+        }
+        linked
+    }
+}
+
+/// # Panics
+///
+/// [`configure`] panics if the configuration isn't for 2 channels or is invalid, and
+/// [`put`] on the errors of [`try_put`].
+///
+/// [`configure`]: TimeStretcher::configure
+/// [`put`]: TimeStretcher::put
+/// [`try_put`]: StereoStretcher::try_put
+impl TimeStretcher for StereoStretcher {
+    fn configure(&mut self, config: &Config) {
+        Self::check(config).or_panic();
+        match &mut self.side {
+            None => {
+                self.main.apply_config(config);
+            }
+            Some(side) => {
+                let linked = Self::linked_config(config);
+                self.main.apply_config(&linked);
+                side.apply_config(&linked);
+            }
+        }
+    }
+
+    fn channels(&self) -> u32 {
+        match self.main.channels() {
+            0 => 0,
+            _ => 2,
+        }
+    }
+
+    fn put(&mut self, samples: &[f32]) {
+        self.try_put(samples).or_panic();
+    }
+
+    fn receive(&mut self, out: &mut [f32]) -> usize {
+        let Some(side) = &mut self.side else {
+            return self.main.receive(out);
+        };
+        let capacity = out.len() / 2;
+        let mut done = 0;
+        while done < capacity {
+            let (mid_ready, side_ready) = (self.main.available_frames(), side.available_frames());
+            let mut frames = mid_ready.min(side_ready);
+            // At the end of the stream, the shorter output is padded to the longer one.
+            if frames == 0 && self.flushed {
+                frames = mid_ready.max(side_ready);
+            }
+            let frames = frames.min(capacity - done).min(BLOCK_FRAMES);
+            if frames == 0 {
+                break;
+            }
+            let mut mid_block = [0.0; BLOCK_FRAMES];
+            let mut side_block = [0.0; BLOCK_FRAMES];
+            let received = self.main.receive_samples(&mut mid_block, frames)
+                + side.receive_samples(&mut side_block, frames);
+            self.padded_frames += (2 * frames - received) as u64;
+            let out = &mut out[2 * done..2 * (done + frames)];
+            for ((frame, mid), side) in out.chunks_exact_mut(2).zip(mid_block).zip(side_block) {
+                frame[0] = mid + side;
+                frame[1] = mid - side;
+            }
+            done += frames;
+        }
+        done
+    }
+
+    fn flush(&mut self) {
+        self.main.flush();
+        if let Some(side) = &mut self.side {
+            side.flush();
+        }
+        self.flushed = true;
+    }
+
+    fn clear(&mut self) {
+        self.main.clear();
+        if let Some(side) = &mut self.side {
+            side.clear();
+        }
+        self.flushed = false;
+    }
+
+    fn io_ratio(&mut self) -> f64 {
+        self.main.get_input_output_sample_ratio()
+    }
+}
@@ -0,0 +1,158 @@
+use std::vec;
+
+use crate::error::OrPanic;
+use crate::{CancellationToken, Pending, ReceiveStatus, SoundTouchError, TimeStretcher};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Processes a whole stream with bounded memory by pumping samples from `read` through
+/// `stretcher` to `write`, then flushing the pipeline.
+///
+/// `read` is given a buffer with room for `chunk_frames` interleaved frames and returns
+/// the number of whole frames it wrote into it, or `0` at the end of the stream. `write`
+/// is called with every batch of ready interleaved samples, at most `chunk_frames`
+/// frames at a time. Apart from the stretcher's own buffers, memory use is bounded by
+/// two buffers of `chunk_frames` frames.
+///
+/// With a [`SoundTouch`] instance, the output is the same as [`generate_audio`] on all
+/// of the input. Any other [`TimeStretcher`] works too, e.g. a stand-in in tests.
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`, if the stretcher isn't configured (for
+/// [`SoundTouch`], the number of channels or the sample rate hasn't been set), or if
+/// `read` returns more than `chunk_frames`.
+///
+/// [`SoundTouch`]: crate::SoundTouch
+/// [`generate_audio`]: crate::SoundTouch::generate_audio
+pub fn process_streaming<S, R, W>(stretcher: &mut S, read: R, write: W, chunk_frames: usize)
+where
+    S: TimeStretcher + ?Sized,
+    R: FnMut(&mut [f32]) -> usize,
+    W: FnMut(&[f32]),
+{
+    pump_stream(stretcher, read, write, chunk_frames, None).or_panic();
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`process_streaming`], but checks `token` before every chunk and before the
+/// flush.
+///
+/// # Errors
+///
+/// Returns [`SoundTouchError::Cancelled`] once `token` is cancelled. `read` and `write`
+/// aren't called again, and the stretcher is [cleared], keeping its configuration, and
+/// ready for a new stream. Returns [`SoundTouchError::NotConfigured`] if the stretcher
+/// isn't configured.
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0` or if `read` returns more than `chunk_frames`.
+///
+/// [cleared]: TimeStretcher::clear
+pub fn process_streaming_cancellable<S, R, W>(
+    stretcher: &mut S,
+    read: R,
+    write: W,
+    chunk_frames: usize,
+    token: &CancellationToken,
+) -> Result<(), SoundTouchError>
+where
+    S: TimeStretcher + ?Sized,
+    R: FnMut(&mut [f32]) -> usize,
+    W: FnMut(&[f32]),
+{
+    pump_stream(stretcher, read, write, chunk_frames, Some(token))
+}
+
+/// Pumps the stream of [`process_streaming`], checking `token` if there is one.
+fn pump_stream<S, R, W>(
+    stretcher: &mut S,
+    mut read: R,
+    mut write: W,
+    chunk_frames: usize,
+    token: Option<&CancellationToken>,
+) -> Result<(), SoundTouchError>
+where
+    S: TimeStretcher + ?Sized,
+    R: FnMut(&mut [f32]) -> usize,
+    W: FnMut(&[f32]),
+{
+    assert!(chunk_frames > 0, "chunk_frames must be greater than 0");
+    if stretcher.channels() == 0 {
+        return Err(SoundTouchError::NotConfigured);
+    }
+    try_pump_stream(
+        stretcher,
+        |input| Ok(read(input)),
+        |output| {
+            write(output);
+            Ok(())
+        },
+        chunk_frames,
+        |stretcher| {
+            if token.is_some_and(CancellationToken::is_cancelled) {
+                stretcher.clear();
+                return Err(SoundTouchError::Cancelled);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// The loop behind [`process_streaming`] and the file and pipe adapters built on it, with
+/// fallible callbacks: puts what `read` returns until it returns `0`, hands all ready
+/// output to `write`, and flushes at the end.
+///
+/// `checkpoint` is called before every read and before the flush. An error of any of
+/// the callbacks stops the stream there, without flushing, and is returned. The
+/// stretcher has to be configured.
+pub(crate) fn try_pump_stream<S, R, W, C, E>(
+    stretcher: &mut S,
+    mut read: R,
+    mut write: W,
+    chunk_frames: usize,
+    mut checkpoint: C,
+) -> Result<(), E>
+where
+    S: TimeStretcher + ?Sized,
+    R: FnMut(&mut [f32]) -> Result<usize, E>,
+    W: FnMut(&[f32]) -> Result<(), E>,
+    C: FnMut(&mut S) -> Result<(), E>,
+{
+    assert!(chunk_frames > 0, "chunk_frames must be greater than 0");
+    let channels = stretcher.channels() as usize;
+    debug_assert!(channels > 0, "the stretcher isn't configured");
+    let mut input = vec![0.0; chunk_frames * channels];
+    let mut output = vec![0.0; chunk_frames * channels];
+
+    // Stops once nothing more can be received, leaving frames of an incomplete output
+    // block to the next input or the flush.
+    let mut pump = |stretcher: &mut S| loop {
+        let ReceiveStatus { frames, pending } = stretcher.receive_with_status(&mut output);
+        if frames.0 > 0 {
+            write(&output[..frames.0 * channels])?;
+        }
+        if frames.0 == 0 || pending != Pending::MoreAvailable {
+            break Ok(());
+        }
+    };
+
+    loop {
+        checkpoint(stretcher)?;
+        let frames = read(&mut input)?;
+        if frames == 0 {
+            break;
+        }
+        assert!(
+            frames <= chunk_frames,
+            "read returned {frames} frames, but the buffer only holds {chunk_frames}"
+        );
+        stretcher.put(&input[..frames * channels]);
+        pump(stretcher)?;
+    }
+    checkpoint(stretcher)?;
+    stretcher.flush();
+    pump(stretcher)
+}
@@ -0,0 +1,130 @@
+use crate::error::OrPanic;
+use crate::units::Frames;
+use crate::{Config, Pending, ReceiveStatus, SoundTouch, SoundTouchError};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The core operations of a time-stretcher, so that code driving one, such as
+/// [`process_streaming`], can be tested against a stand-in instead of the real
+/// processing. [`SoundTouch`] implements it, and the `testing` feature adds
+/// `testing::PassthroughStretcher`, which only changes the length of the audio.
+///
+/// Samples are interleaved and counted in frames, as with [`SoundTouch::put_samples`]
+/// and [`SoundTouch::receive_samples`].
+///
+/// [`process_streaming`]: crate::process_streaming
+pub trait TimeStretcher {
+    /// Applies the channels, sample rate, tempo, pitch and rate of `config`.
+    fn configure(&mut self, config: &Config);
+
+    /// Returns the number of channels of the samples [`put`] takes, or `0` if the
+    /// stretcher isn't ready to process.
+    ///
+    /// [`put`]: TimeStretcher::put
+    fn channels(&self) -> u32;
+
+    /// Puts the interleaved `samples`, which must be a whole number of frames.
+    fn put(&mut self, samples: &[f32]);
+
+    /// Moves ready frames into `out`, as many as fit, and returns the number of frames
+    /// moved. `0` means that more input or a [`flush`] is needed first.
+    ///
+    /// [`flush`]: TimeStretcher::flush
+    fn receive(&mut self, out: &mut [f32]) -> usize;
+
+    /// Like [`receive`], but also returns what is left to receive, as
+    /// [`SoundTouch::receive_frames`] does. The default only knows the frames moved, so
+    /// it reports [`Pending::MoreAvailable`] after any and [`Pending::None`] otherwise.
+    ///
+    /// [`receive`]: TimeStretcher::receive
+    fn receive_with_status(&mut self, out: &mut [f32]) -> ReceiveStatus {
+        let frames = self.receive(out);
+        ReceiveStatus {
+            frames: Frames(frames),
+            pending: if frames > 0 {
+                Pending::MoreAvailable
+            } else {
+                Pending::None
+            },
+        }
+    }
+
+    /// Ends the stream, making the output of everything put so far ready.
+    fn flush(&mut self);
+
+    /// Discards all input and output, starting a new stream with the same
+    /// configuration.
+    fn clear(&mut self);
+
+    /// Returns the number of output frames per input frame.
+    fn io_ratio(&mut self) -> f64;
+}
+
+/// # Panics
+///
+/// [`put`] panics if the stretcher isn't configured or `samples` isn't a whole number of
+/// frames, and otherwise on the errors of [`SoundTouch::try_put_samples`]. Like
+/// [`SoundTouch::put_samples`] it discards flushed output that hasn't been received
+/// instead of failing. [`receive`]
+/// and [`receive_with_status`] panic if the stretcher isn't configured.
+///
+/// [`put`]: TimeStretcher::put
+/// [`receive`]: TimeStretcher::receive
+/// [`receive_with_status`]: TimeStretcher::receive_with_status
+impl TimeStretcher for SoundTouch {
+    fn configure(&mut self, config: &Config) {
+        self.apply_config(config);
+    }
+
+    fn channels(&self) -> u32 {
+        match self.check_configured() {
+            Ok(()) => self.num_channels(),
+            Err(_) => 0,
+        }
+    }
+
+    fn put(&mut self, samples: &[f32]) {
+        self.discard_flushed(samples.len());
+        self.put_frames(samples).or_panic();
+    }
+
+    fn receive(&mut self, out: &mut [f32]) -> usize {
+        self.check_configured().or_panic();
+        let frames = out.len() / self.num_channels() as usize;
+        self.receive_frames(out, Frames(frames)).frames.0
+    }
+
+    fn receive_with_status(&mut self, out: &mut [f32]) -> ReceiveStatus {
+        self.check_configured().or_panic();
+        let frames = out.len() / self.num_channels() as usize;
+        self.receive_frames(out, Frames(frames))
+    }
+
+    fn flush(&mut self) {
+        SoundTouch::flush(self);
+    }
+
+    fn clear(&mut self) {
+        SoundTouch::clear(self);
+    }
+
+    fn io_ratio(&mut self) -> f64 {
+        self.get_input_output_sample_ratio()
+    }
+}
+
+impl SoundTouch {
+    /// Puts `samples` as whole frames of the channels the stream is put in.
+    fn put_frames(&mut self, samples: &[f32]) -> Result<(), SoundTouchError> {
+        // Resolves a pending channel change before the samples are split into frames.
+        self.begin_put(samples.len())?;
+        let channels = self.num_channels() as usize;
+        match samples.len() % channels {
+            0 => self.try_put_samples(samples, samples.len() / channels),
+            trailing_samples => Err(SoundTouchError::PartialFrame {
+                trailing_samples,
+                channels,
+            }),
+        }
+    }
+}
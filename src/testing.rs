@@ -0,0 +1,509 @@
+//! Generators and invariant checks for property tests, behind the `testing` feature.
+//!
+//! The crate's own property tests use these, and downstream wrappers can run the same
+//! invariants against their integration code. The generators are deterministic for a
+//! given seed and favour the edge cases that are easy to miss: empty input, a single
+//! frame, input shorter than the latency and input that isn't a whole number of frames.
+//!
+//! [`PassthroughStretcher`] stands in for [`SoundTouch`] where code is generic over
+//! [`TimeStretcher`], to test it without depending on the exact processed output.
+//!
+//! ```rust
+//! use soundtouch::testing::{arbitrary_config, arbitrary_samples, check_roundtrip_invariants, Rng};
+//!
+//! let mut rng = Rng::new(42);
+//! for _ in 0..4 {
+//!     let config = arbitrary_config(&mut rng);
+//!     let samples = arbitrary_samples(&mut rng, config.channels(), 20_000);
+//!     check_roundtrip_invariants(&config, &samples).unwrap();
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+
+use crate::{Config, DriftReport, Setting, SoundTouch, TimeStretcher};
+
+/// Output samples may exceed the peak of the input by this factor, as the overlap of
+/// the time-stretcher and the ringing of the anti-alias filter can overshoot.
+pub const MAX_OVERSHOOT: f32 = 2.0;
+
+/// A small xorshift generator, so property tests are reproducible without extra
+/// dependencies.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from `seed`. A seed of `0` is replaced, as xorshift would
+    /// only ever return `0` from it.
+    pub fn new(seed: u64) -> Self {
+        Self(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `low..high`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low >= high`.
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        assert!(low < high, "empty range {low}..{high}");
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+
+    /// Returns a number in `low..high`.
+    pub fn float(&mut self, low: f64, high: f64) -> f64 {
+        low + (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * (high - low)
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.float(0.0, 1.0) < p
+    }
+}
+
+/// Returns a valid configuration with 1 to [`SoundTouch::MAX_CHANNELS`] channels, a
+/// common sample rate, tempo, rate and pitch between `0.5` and `2.0`, and random but
+/// sensible settings.
+pub fn arbitrary_config(rng: &mut Rng) -> Config {
+    const SAMPLE_RATES: [u32; 6] = [8000, 16000, 22050, 44100, 48000, 96000];
+    let mut config = Config::new();
+    let channels = if rng.chance(0.5) {
+        rng.range(1, 3)
+    } else {
+        rng.range(1, SoundTouch::MAX_CHANNELS as usize + 1)
+    };
+    config
+        .set_channels(channels as u32)
+        .set_sample_rate(SAMPLE_RATES[rng.range(0, SAMPLE_RATES.len())])
+        .set_tempo(factor(rng))
+        .set_rate(factor(rng))
+        .set_pitch(factor(rng))
+        .set_setting(Setting::UseAaFilter, rng.range(0, 2) as i32)
+        .set_setting(Setting::AaFilterLength, 8 * rng.range(1, 17) as i32)
+        .set_setting(Setting::UseQuickseek, rng.range(0, 2) as i32)
+        .set_setting(Setting::OverlapMs, rng.range(4, 17) as i32);
+    if rng.chance(0.5) {
+        config
+            .set_setting(Setting::SequenceMs, rng.range(40, 101) as i32)
+            .set_setting(Setting::SeekwindowMs, rng.range(10, 31) as i32);
+    }
+    config
+}
+
+/// Returns `1.0` a quarter of the time and a factor between `0.5` and `2.0` otherwise.
+fn factor(rng: &mut Rng) -> f64 {
+    if rng.chance(0.25) {
+        1.0
+    } else {
+        rng.float(0.5, 2.0)
+    }
+}
+
+/// Returns interleaved noise of up to `max_frames` frames of `channels` channels, with
+/// samples in `-1.0..1.0`.
+///
+/// The length is often one of the edge cases: no frames, a single frame, fewer frames
+/// than the latency of the pipeline, or a trailing partial frame.
+pub fn arbitrary_samples(rng: &mut Rng, channels: u32, max_frames: usize) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    let frames = match rng.range(0, 5) {
+        0 => 0,
+        1 => 1.min(max_frames),
+        2 => rng.range(0, 2048.min(max_frames) + 1),
+        _ => rng.range(0, max_frames + 1),
+    };
+    let partial = if rng.chance(0.2) {
+        rng.range(0, channels)
+    } else {
+        0
+    };
+    (0..frames * channels + partial)
+        .map(|_| rng.float(-1.0, 1.0) as f32)
+        .collect()
+}
+
+/// An invariant broken by [`check_roundtrip_invariants`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    /// The output isn't a whole number of frames.
+    PartialFrame {
+        /// The length of the output in samples.
+        len: usize,
+    },
+    /// The number of output frames is more than one frame off the number of input
+    /// frames times the [input/output ratio].
+    ///
+    /// [input/output ratio]: SoundTouch::get_input_output_sample_ratio
+    Length {
+        /// The number of frames expected.
+        expected: f64,
+        /// The number of frames produced.
+        actual: usize,
+    },
+    /// An output sample is NaN or infinite.
+    NotFinite {
+        /// The index of the sample in the output.
+        index: usize,
+    },
+    /// An output sample exceeds the peak of the input times [`MAX_OVERSHOOT`].
+    OutOfBounds {
+        /// The index of the sample in the output.
+        index: usize,
+        /// The sample.
+        value: f32,
+        /// The largest magnitude allowed.
+        bound: f32,
+    },
+    /// Frames were left in the pipeline after the end of the stream.
+    NotEmpty {
+        /// Input frames still waiting to be processed.
+        unprocessed_frames: usize,
+        /// Output frames still ready.
+        ready_frames: usize,
+    },
+    /// The [residual drift] of a simulated stream exceeded the bound of
+    /// [`check_long_run_drift`].
+    ///
+    /// [residual drift]: DriftReport::residual_frames
+    Drift {
+        /// The report when the bound was exceeded.
+        report: DriftReport,
+        /// The largest residual drift allowed then, in frames.
+        bound: u64,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PartialFrame { len } => {
+                write!(f, "output of {len} samples isn't a whole number of frames")
+            }
+            Self::Length { expected, actual } => {
+                write!(f, "{actual} frames out, expected {expected}")
+            }
+            Self::NotFinite { index } => write!(f, "output sample {index} isn't finite"),
+            Self::OutOfBounds {
+                index,
+                value,
+                bound,
+            } => write!(f, "output sample {index} is {value}, beyond {bound}"),
+            Self::NotEmpty {
+                unprocessed_frames,
+                ready_frames,
+            } => write!(
+                f,
+                "{unprocessed_frames} unprocessed and {ready_frames} ready frames left after the end of the stream"
+            ),
+            Self::Drift { report, bound } => write!(
+                f,
+                "residual drift of {} frames, beyond {bound}",
+                report.residual_frames()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+/// Processes `samples` with `config` in one go, as [`generate_audio`] does, and checks
+/// that:
+/// - the output is a whole number of frames,
+/// - its length is within one frame of the input length times the input/output ratio,
+/// - every sample is finite and at most [`MAX_OVERSHOOT`] times the input peak,
+/// - nothing is left in the pipeline afterwards.
+///
+/// A trailing partial frame in `samples` is ignored.
+///
+/// # Panics
+///
+/// Panics if `config` is [invalid].
+///
+/// [`generate_audio`]: SoundTouch::generate_audio
+/// [invalid]: Config::validate
+pub fn check_roundtrip_invariants(
+    config: &Config,
+    samples: &[f32],
+) -> Result<(), InvariantViolation> {
+    let mut soundtouch = SoundTouch::with_config(config);
+    let channels = config.channels() as usize;
+    let frames = samples.len() / channels;
+    let expected = frames as f64 * soundtouch.get_input_output_sample_ratio();
+    let output = soundtouch.generate_audio(samples);
+
+    if output.len() % channels != 0 {
+        return Err(InvariantViolation::PartialFrame { len: output.len() });
+    }
+    let actual = output.len() / channels;
+    if (actual as f64 - expected).abs() > 1.0 {
+        return Err(InvariantViolation::Length { expected, actual });
+    }
+
+    let peak = samples[..frames * channels]
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    let bound = peak * MAX_OVERSHOOT;
+    for (index, &value) in output.iter().enumerate() {
+        if !value.is_finite() {
+            return Err(InvariantViolation::NotFinite { index });
+        }
+        if value.abs() > bound {
+            return Err(InvariantViolation::OutOfBounds {
+                index,
+                value,
+                bound,
+            });
+        }
+    }
+
+    let levels = soundtouch.buffer_levels();
+    if levels.unprocessed_frames > 0 || levels.ready_frames > 0 {
+        return Err(InvariantViolation::NotEmpty {
+            unprocessed_frames: levels.unprocessed_frames,
+            ready_frames: levels.ready_frames,
+        });
+    }
+    Ok(())
+}
+
+/// Largest factor [`check_long_run_drift`] changes the tempo of the configuration by,
+/// either way.
+const DRIFT_TEMPO_SPREAD: f64 = 1.25;
+
+/// Simulates a stream of `duration` processed with `config`, e.g. a day of internet
+/// radio, and checks that the [residual drift] of [`SoundTouch::drift`] stays within
+/// [`drift_bound`] plus one frame per tempo change throughout.
+///
+/// The input is the same short block of noise over and over, put in pieces of random
+/// length with all output received after each. Every ten simulated minutes the tempo
+/// changes to that of `config` times a factor between `1 / 1.25` and `1.25`, and every
+/// hour the stream is flushed and drained or cleared, both of which end it. The drift
+/// those leave is attributed to them and doesn't count against the bound. Returns the
+/// final report, after the last stream was flushed and drained.
+///
+/// All of the audio is processed, so a simulated day takes a while; a low sample rate
+/// and a single channel get through it fastest.
+///
+/// # Panics
+///
+/// Panics if `config` is [invalid].
+///
+/// [residual drift]: DriftReport::residual_frames
+/// [invalid]: Config::validate
+pub fn check_long_run_drift(
+    config: &Config,
+    duration: Duration,
+    seed: u64,
+) -> Result<DriftReport, InvariantViolation> {
+    const BLOCK_FRAMES: usize = 4096;
+    let mut rng = Rng::new(seed);
+    let mut soundtouch = SoundTouch::with_config(config);
+    let channels = config.channels() as usize;
+    let sample_rate = config.sample_rate() as u64;
+    let block: Vec<f32> = (0..BLOCK_FRAMES * channels)
+        .map(|_| rng.float(-0.5, 0.5) as f32)
+        .collect();
+    let mut out = alloc::vec![0.0; BLOCK_FRAMES * channels];
+
+    let total = (duration.as_secs_f64() * sample_rate as f64) as u64;
+    let (minute, hour) = (60 * sample_rate, 3600 * sample_rate);
+    let base = drift_bound(config);
+    let mut changes = 0;
+    let mut put = 0;
+    let mut checked = 0;
+    while put < total {
+        let frames = rng.range(1, BLOCK_FRAMES + 1).min((total - put) as usize);
+        soundtouch.put_samples(&block[..frames * channels], frames);
+        while soundtouch.receive_samples(&mut out, BLOCK_FRAMES) > 0 {}
+        let before = put;
+        put += frames as u64;
+
+        if put / hour > before / hour {
+            if rng.chance(0.5) {
+                soundtouch.flush();
+                while soundtouch.receive_samples(&mut out, BLOCK_FRAMES) > 0 {}
+            } else {
+                soundtouch.clear();
+            }
+        }
+        if put / (10 * minute) > before / (10 * minute) {
+            let factor = rng.float(1.0 / DRIFT_TEMPO_SPREAD, DRIFT_TEMPO_SPREAD);
+            if soundtouch.try_set_tempo(config.tempo() * factor).is_ok() {
+                changes += 1;
+            }
+        }
+        // Once per simulated second, as the report asks SoundTouch for its buffers.
+        if put - checked >= sample_rate {
+            checked = put;
+            check_drift(&soundtouch, base + changes)?;
+        }
+    }
+    soundtouch.flush();
+    while soundtouch.receive_samples(&mut out, BLOCK_FRAMES) > 0 {}
+    check_drift(&soundtouch, base + changes)
+}
+
+fn check_drift(soundtouch: &SoundTouch, bound: u64) -> Result<DriftReport, InvariantViolation> {
+    let report = soundtouch.drift();
+    if report.residual_frames().unsigned_abs() > bound {
+        return Err(InvariantViolation::Drift { report, bound });
+    }
+    Ok(report)
+}
+
+/// Returns the residual drift in frames that [`check_long_run_drift`] allows for
+/// `config`, besides one frame per tempo change: the output of a sequence, a seek
+/// window and two overlaps of the time-stretcher and of the anti-alias filter of the
+/// rate transposer, at the slowest tempo of the simulation. This is the most the
+/// output can lag behind the pending input in the middle of a stream.
+pub fn drift_bound(config: &Config) -> u64 {
+    // The longest automatic lengths, at the slowest tempo.
+    let ms = |setting, automatic| match config.setting(setting) {
+        0 => automatic,
+        ms => ms,
+    };
+    let window_ms = ms(Setting::SequenceMs, 90)
+        + ms(Setting::SeekwindowMs, 20)
+        + 2 * config.setting(Setting::OverlapMs);
+    let frames = config.sample_rate() as f64 * window_ms as f64 / 1000.0
+        + config.setting(Setting::AaFilterLength) as f64;
+    let ratio = DRIFT_TEMPO_SPREAD / (config.tempo() * config.rate());
+    (frames * ratio.max(1.0)) as u64 + 2
+}
+
+/// A [`TimeStretcher`] that only changes the length of the audio, for testing code that
+/// drives a time-stretcher.
+///
+/// Output frame `k` is a copy of input frame `k / ratio`, rounded down, where the ratio
+/// is the [`io_ratio`], `1 / (tempo * rate)` of the configuration. Pitch is ignored.
+/// There is no latency: the output of every put frame is ready right away, and a
+/// stream of `n` frames gives `n * ratio` frames, rounded up. [`flush`] ends the
+/// stream, so the next frame put is again the first of one.
+///
+/// ```rust
+/// use soundtouch::testing::PassthroughStretcher;
+/// use soundtouch::{Config, TimeStretcher};
+///
+/// let mut stretcher = PassthroughStretcher::new();
+/// stretcher.configure(Config::new().set_channels(1).set_tempo(0.5));
+/// stretcher.put(&[1.0, 2.0]);
+/// let mut out = [0.0; 8];
+/// assert_eq!(stretcher.receive(&mut out), 4);
+/// assert_eq!(out[..4], [1.0, 1.0, 2.0, 2.0]);
+/// ```
+///
+/// [`io_ratio`]: TimeStretcher::io_ratio
+/// [`flush`]: TimeStretcher::flush
+#[derive(Debug, Clone, Default)]
+pub struct PassthroughStretcher {
+    channels: usize,
+    /// Output frames per input frame.
+    ratio: f64,
+    /// The input frames from `input_start` on, which later output frames may copy.
+    input: Vec<f32>,
+    input_start: usize,
+    /// Frames put in the current stream.
+    input_frames: usize,
+    /// Output frames generated in the current stream.
+    output_frames: usize,
+    /// Output that hasn't been received yet.
+    ready: Vec<f32>,
+}
+
+impl PassthroughStretcher {
+    /// Creates a stretcher that isn't configured yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// # Panics
+///
+/// [`put`] panics if the stretcher isn't configured or `samples` isn't a whole number of
+/// frames. [`configure`] panics if the tempo or rate isn't positive.
+///
+/// [`put`]: TimeStretcher::put
+/// [`configure`]: TimeStretcher::configure
+impl TimeStretcher for PassthroughStretcher {
+    fn configure(&mut self, config: &Config) {
+        let ratio = 1.0 / (config.tempo() * config.rate());
+        assert!(
+            ratio.is_finite() && ratio > 0.0,
+            "tempo and rate must be positive"
+        );
+        *self = Self {
+            channels: config.channels() as usize,
+            ratio,
+            ..Self::default()
+        };
+    }
+
+    fn channels(&self) -> u32 {
+        self.channels as u32
+    }
+
+    fn put(&mut self, samples: &[f32]) {
+        let channels = self.channels;
+        assert!(channels > 0, "the number of channels must be set");
+        assert!(
+            samples.len() % channels == 0,
+            "{} samples aren't a whole number of frames of {channels} channels",
+            samples.len()
+        );
+        self.input.extend_from_slice(samples);
+        self.input_frames += samples.len() / channels;
+
+        let source = |frame: usize| (frame as f64 / self.ratio) as usize;
+        while (self.output_frames as f64) < self.input_frames as f64 * self.ratio {
+            let frame = source(self.output_frames).min(self.input_frames - 1) - self.input_start;
+            self.ready
+                .extend_from_slice(&self.input[frame * channels..(frame + 1) * channels]);
+            self.output_frames += 1;
+        }
+        // Drops the input before the source of the next output frame.
+        let keep_from = source(self.output_frames).min(self.input_frames);
+        self.input
+            .drain(..(keep_from - self.input_start) * channels);
+        self.input_start = keep_from;
+    }
+
+    fn receive(&mut self, out: &mut [f32]) -> usize {
+        let channels = self.channels.max(1);
+        let frames = (out.len() / channels).min(self.ready.len() / channels);
+        let len = frames * channels;
+        out[..len].copy_from_slice(&self.ready[..len]);
+        self.ready.drain(..len);
+        frames
+    }
+
+    fn flush(&mut self) {
+        self.input.clear();
+        self.input_start = 0;
+        self.input_frames = 0;
+        self.output_frames = 0;
+    }
+
+    fn clear(&mut self) {
+        self.flush();
+        self.ready.clear();
+    }
+
+    fn io_ratio(&mut self) -> f64 {
+        self.ratio
+    }
+}
@@ -0,0 +1,189 @@
+use std::boxed::Box;
+use std::time::Instant;
+
+use crate::SoundTouch;
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The call a [`BlockTiming`] measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlockKind {
+    /// [`SoundTouch::put_samples`] or its `try_` variant.
+    #[default]
+    Put,
+    /// [`SoundTouch::receive_samples`] or its `try_` variant.
+    Receive,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The wall-clock duration of one call, recorded with [block timing] enabled.
+///
+/// [block timing]: SoundTouch::set_block_timing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BlockTiming {
+    /// The call that was measured.
+    pub kind: BlockKind,
+    /// Frames put or received.
+    pub frames: usize,
+    /// Duration of the call in microseconds.
+    pub micros: u64,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The distribution of call durations in microseconds, as returned by
+/// [`SoundTouch::timing_percentiles`]. Percentiles use the nearest rank: `p90` is the
+/// smallest duration at least 90% of the calls didn't exceed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimingPercentiles {
+    /// Number of durations the percentiles are computed from.
+    pub count: usize,
+    /// The median.
+    pub p50: u64,
+    /// The 90th percentile.
+    pub p90: u64,
+    /// The 99th percentile.
+    pub p99: u64,
+    /// The longest duration.
+    pub max: u64,
+}
+
+impl TimingPercentiles {
+    /// Computes the percentiles of the durations in `micros`, sorting it in place.
+    /// Returns `None` if it is empty.
+    pub fn from_micros(micros: &mut [u64]) -> Option<Self> {
+        micros.sort_unstable();
+        let max = *micros.last()?;
+        let count = micros.len();
+        let rank = |percent: usize| micros[(count * percent).div_ceil(100) - 1];
+        Some(Self {
+            count,
+            p50: rank(50),
+            p90: rank(90),
+            p99: rank(99),
+            max,
+        })
+    }
+}
+
+/// The last [`SoundTouch::BLOCK_TIMING_CAPACITY`] timings, allocated when block timing
+/// is enabled so that recording never allocates.
+#[derive(Debug, Clone)]
+pub(crate) struct TimingRing {
+    entries: Box<[BlockTiming; SoundTouch::BLOCK_TIMING_CAPACITY]>,
+    /// Index the next timing is written to.
+    next: usize,
+    len: usize,
+}
+
+impl TimingRing {
+    fn new() -> Self {
+        Self {
+            entries: Box::new([BlockTiming::default(); SoundTouch::BLOCK_TIMING_CAPACITY]),
+            next: 0,
+            len: 0,
+        }
+    }
+
+    fn record(&mut self, timing: BlockTiming) {
+        self.entries[self.next] = timing;
+        self.next = (self.next + 1) % self.entries.len();
+        self.len = (self.len + 1).min(self.entries.len());
+    }
+
+    /// Returns the timings from the oldest to the newest.
+    fn iter(&self) -> impl Iterator<Item = BlockTiming> + '_ {
+        let (newer, older) = self.entries[..self.len].split_at(self.next);
+        older.iter().chain(newer).copied()
+    }
+}
+
+impl SoundTouch {
+    /// Number of timings kept with [block timing] enabled.
+    ///
+    /// [block timing]: SoundTouch::set_block_timing
+    pub const BLOCK_TIMING_CAPACITY: usize = 256;
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Enables or disables recording how long every [`put_samples`] and
+    /// [`receive_samples`] call takes, e.g. to find the blocks behind dropouts in a
+    /// real-time app. The last [`BLOCK_TIMING_CAPACITY`] timings are kept, see
+    /// [`recent_timings`] and [`timing_percentiles`]. Calls that fail aren't recorded.
+    ///
+    /// Enabling allocates the ring of timings, and recording never allocates. Enabling
+    /// again keeps the timings, disabling drops them, and so does [`reset`]. When
+    /// disabled, each call only checks that it is.
+    ///
+    /// [`put_samples`]: SoundTouch::put_samples
+    /// [`receive_samples`]: SoundTouch::receive_samples
+    /// [`BLOCK_TIMING_CAPACITY`]: SoundTouch::BLOCK_TIMING_CAPACITY
+    /// [`recent_timings`]: SoundTouch::recent_timings
+    /// [`timing_percentiles`]: SoundTouch::timing_percentiles
+    /// [`reset`]: SoundTouch::reset
+    pub fn set_block_timing(&mut self, enabled: bool) -> &mut Self {
+        match (enabled, &self.timings) {
+            (true, None) => self.timings = Some(TimingRing::new()),
+            (false, _) => self.timings = None,
+            (true, Some(_)) => {}
+        }
+        self
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns `true` if [block timing] is enabled.
+    ///
+    /// [block timing]: SoundTouch::set_block_timing
+    pub fn is_block_timing(&self) -> bool {
+        self.timings.is_some()
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the recorded [block timings], from the oldest to the newest.
+    ///
+    /// [block timings]: SoundTouch::set_block_timing
+    pub fn recent_timings(&self) -> impl Iterator<Item = BlockTiming> + '_ {
+        self.timings.iter().flat_map(TimingRing::iter)
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the percentiles of the durations of the [recent timings] of both puts and
+    /// receives, or `None` if there are none. Doesn't allocate.
+    ///
+    /// [recent timings]: SoundTouch::recent_timings
+    pub fn timing_percentiles(&self) -> Option<TimingPercentiles> {
+        let mut micros = [0; Self::BLOCK_TIMING_CAPACITY];
+        let mut count = 0;
+        for (micros, timing) in micros.iter_mut().zip(self.recent_timings()) {
+            *micros = timing.micros;
+            count += 1;
+        }
+        TimingPercentiles::from_micros(&mut micros[..count])
+    }
+
+    /// Returns the start of a call to time, or `None` with block timing disabled.
+    pub(crate) fn timing_start(&self) -> Option<Instant> {
+        self.timings.as_ref().map(|_| Instant::now())
+    }
+
+    /// Records a call started at `started` that put or received `frames`.
+    pub(crate) fn record_timing(
+        &mut self,
+        kind: BlockKind,
+        frames: usize,
+        started: Option<Instant>,
+    ) {
+        if let (Some(timings), Some(started)) = (&mut self.timings, started) {
+            let micros = started.elapsed().as_micros().min(u64::MAX as u128) as u64;
+            timings.record(BlockTiming {
+                kind,
+                frames,
+                micros,
+            });
+        }
+    }
+}
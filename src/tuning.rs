@@ -0,0 +1,272 @@
+use core::fmt;
+
+use crate::fingerprint::{fnv1a, FNV_OFFSET_BASIS};
+use crate::{ParsePresetError, Setting, SoundTouch, SoundTouchError};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// The writable [`Setting`]s as one typed value, to check and apply together with
+/// [`SoundTouch::set_tuning`] and read back with [`SoundTouch::tuning`].
+///
+/// The default is every setting at its [default value].
+///
+/// [default value]: Setting::default_value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TuningParams {
+    /// [`Setting::SequenceMs`], or `None` to choose it automatically from the tempo.
+    pub sequence_ms: Option<u32>,
+    /// [`Setting::SeekwindowMs`], or `None` to choose it automatically from the tempo.
+    pub seek_window_ms: Option<u32>,
+    /// [`Setting::OverlapMs`].
+    pub overlap_ms: u32,
+    /// [`Setting::UseQuickseek`].
+    pub quickseek: bool,
+    /// [`Setting::UseAaFilter`].
+    pub aa_filter: bool,
+    /// [`Setting::AaFilterLength`] in taps.
+    pub aa_filter_length: u32,
+}
+
+impl Default for TuningParams {
+    fn default() -> Self {
+        Self {
+            sequence_ms: None,
+            seek_window_ms: None,
+            overlap_ms: Setting::OverlapMs.default_value() as u32,
+            quickseek: Setting::UseQuickseek.default_value() != 0,
+            aa_filter: Setting::UseAaFilter.default_value() != 0,
+            aa_filter_length: Setting::AaFilterLength.default_value() as u32,
+        }
+    }
+}
+
+impl TuningParams {
+    /// The longest sequence, seek window and overlap accepted, in milliseconds. Useful
+    /// lengths are tens of milliseconds.
+    pub const MAX_MS: u32 = 1000;
+
+    /// The shortest sequence SoundTouch chooses automatically, in milliseconds, which
+    /// the overlap has to stay below when the sequence length is automatic.
+    pub const MIN_AUTO_SEQUENCE_MS: u32 = 40;
+
+    /// Checks the values without applying them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoundTouchError::InvalidSetting`] if a length is `Some(0)` or above
+    /// [`MAX_MS`], or if the anti-alias filter length isn't a multiple of 8 in `8..=128`,
+    /// and [`SoundTouchError::OverlapTooLong`] if the overlap isn't shorter than the
+    /// sequence, or than [`MIN_AUTO_SEQUENCE_MS`] with the automatic sequence length.
+    ///
+    /// [`MAX_MS`]: TuningParams::MAX_MS
+    /// [`MIN_AUTO_SEQUENCE_MS`]: TuningParams::MIN_AUTO_SEQUENCE_MS
+    pub fn validate(&self) -> Result<(), SoundTouchError> {
+        let check = |setting, value: u32, valid: bool| {
+            if valid {
+                Ok(())
+            } else {
+                Err(SoundTouchError::InvalidSetting {
+                    setting,
+                    value: value.min(i32::MAX as u32) as i32,
+                })
+            }
+        };
+        let ms = |value| (1..=Self::MAX_MS).contains(&value);
+        if let Some(sequence_ms) = self.sequence_ms {
+            check(Setting::SequenceMs, sequence_ms, ms(sequence_ms))?;
+        }
+        if let Some(seek_window_ms) = self.seek_window_ms {
+            check(Setting::SeekwindowMs, seek_window_ms, ms(seek_window_ms))?;
+        }
+        check(
+            Setting::OverlapMs,
+            self.overlap_ms,
+            self.overlap_ms <= Self::MAX_MS,
+        )?;
+        check(
+            Setting::AaFilterLength,
+            self.aa_filter_length,
+            (8..=128).contains(&self.aa_filter_length) && self.aa_filter_length % 8 == 0,
+        )?;
+
+        let sequence_ms = self.sequence_ms.unwrap_or(Self::MIN_AUTO_SEQUENCE_MS);
+        if self.overlap_ms >= sequence_ms {
+            return Err(SoundTouchError::OverlapTooLong {
+                overlap_ms: self.overlap_ms,
+                sequence_ms: self.sequence_ms,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns a stable 64-bit key of the parameters for caching processed audio, like
+    /// [`Config::cache_key`]: the 64-bit FNV-1a hash of the little-endian bytes of the
+    /// fields in the order they are declared in. An `Option` is a `1` byte and the value
+    /// for `Some`, and five `0` bytes for `None`; a `bool` is one byte.
+    ///
+    /// [`Config::cache_key`]: crate::Config::cache_key
+    pub fn cache_key(&self) -> u64 {
+        let optional = |value: Option<u32>| {
+            let [a, b, c, d] = value.unwrap_or(0).to_le_bytes();
+            [u8::from(value.is_some()), a, b, c, d]
+        };
+        let mut key = FNV_OFFSET_BASIS;
+        key = fnv1a(key, &optional(self.sequence_ms));
+        key = fnv1a(key, &optional(self.seek_window_ms));
+        key = fnv1a(key, &self.overlap_ms.to_le_bytes());
+        key = fnv1a(key, &[u8::from(self.quickseek), u8::from(self.aa_filter)]);
+        fnv1a(key, &self.aa_filter_length.to_le_bytes())
+    }
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Named [`TuningParams`] for common kinds of material, e.g. to choose with
+/// `--preset speech` on a command line.
+///
+/// The names are kebab-case and parsed ignoring case, as those of [`Setting`].
+///
+/// ```rust
+/// use soundtouch::{Preset, SoundTouch};
+///
+/// let preset: Preset = "Speech".parse().unwrap();
+/// assert_eq!(preset, Preset::Speech);
+/// let mut soundtouch = SoundTouch::new();
+/// soundtouch.set_tuning(&preset.tuning())?;
+/// # Ok::<(), soundtouch::SoundTouchError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Preset {
+    /// Every setting at its [default value], the same as [`TuningParams::default`].
+    ///
+    /// [default value]: Setting::default_value
+    #[default]
+    Default,
+    /// The lengths `soundstretch -speech` uses: a 40 ms sequence, a 15 ms seek window
+    /// and an 8 ms overlap, which keep speech from sounding echoey.
+    Speech,
+    /// The fixed lengths SoundTouch used for music before it chose them from the tempo:
+    /// an 82 ms sequence, a 28 ms seek window and a 12 ms overlap.
+    Music,
+}
+
+impl Preset {
+    /// All presets, in the order they are declared in.
+    pub const ALL: [Preset; 3] = [Preset::Default, Preset::Speech, Preset::Music];
+
+    /// Returns the kebab-case name of the preset, as [`Display`](fmt::Display) prints it
+    /// and [`FromStr`](core::str::FromStr) parses it.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Preset::Default => "default",
+            Preset::Speech => "speech",
+            Preset::Music => "music",
+        }
+    }
+
+    /// Looks a preset up by its [name], ignoring case.
+    ///
+    /// [name]: Preset::name
+    pub fn from_name(name: &str) -> Option<Preset> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the settings of the preset. They pass [`TuningParams::validate`].
+    pub fn tuning(&self) -> TuningParams {
+        let lengths = |sequence_ms, seek_window_ms, overlap_ms| TuningParams {
+            sequence_ms: Some(sequence_ms),
+            seek_window_ms: Some(seek_window_ms),
+            overlap_ms,
+            ..TuningParams::default()
+        };
+        match self {
+            Preset::Default => TuningParams::default(),
+            Preset::Speech => lengths(40, 15, 8),
+            Preset::Music => lengths(82, 28, 12),
+        }
+    }
+
+    /// Returns the [`TuningParams::cache_key`] of the [settings] of the preset, so that
+    /// audio processed with a preset and with the same settings given one by one share
+    /// a key.
+    ///
+    /// [settings]: Preset::tuning
+    pub fn cache_key(&self) -> u64 {
+        self.tuning().cache_key()
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl core::str::FromStr for Preset {
+    type Err = ParsePresetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::from_name(s).ok_or(ParsePresetError)
+    }
+}
+
+impl SoundTouch {
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Checks `tuning` and applies all of it, or returns an error and changes nothing.
+    ///
+    /// SoundTouch reconfigures the time-stretcher for each of the sequence, seek window
+    /// and overlap lengths, so they are applied in an order that never combines an
+    /// overlap with a sequence that isn't longer than it, whatever the previous values
+    /// were. The callback set with [`on_settings_changed`] may be called for each step.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`TuningParams::validate`].
+    ///
+    /// [`on_settings_changed`]: SoundTouch::on_settings_changed
+    pub fn set_tuning(&mut self, tuning: &TuningParams) -> Result<&mut Self, SoundTouchError> {
+        tuning.validate()?;
+        let ms = |value: Option<u32>| value.unwrap_or(0) as i32;
+
+        // Shortening the overlap first keeps it below the old sequence, and lengthening
+        // the sequence first keeps the new overlap below it.
+        let current_sequence_ms = match self.get_setting(Setting::SequenceMs) {
+            0 => TuningParams::MIN_AUTO_SEQUENCE_MS,
+            ms => ms.max(0) as u32,
+        };
+        if tuning.overlap_ms < current_sequence_ms {
+            self.set_setting(Setting::OverlapMs, tuning.overlap_ms as i32)
+                .set_setting(Setting::SequenceMs, ms(tuning.sequence_ms));
+        } else {
+            self.set_setting(Setting::SequenceMs, ms(tuning.sequence_ms))
+                .set_setting(Setting::OverlapMs, tuning.overlap_ms as i32);
+        }
+        Ok(self
+            .set_setting(Setting::SeekwindowMs, ms(tuning.seek_window_ms))
+            .set_setting(Setting::UseQuickseek, tuning.quickseek as i32)
+            .set_setting(Setting::AaFilterLength, tuning.aa_filter_length as i32)
+            .set_setting(Setting::UseAaFilter, tuning.aa_filter as i32))
+    }
+
+    /// **NOT FROM SOUNDTOUCH**
+    ///
+    /// Returns the current writable settings as [`TuningParams`]. Automatic sequence and
+    /// seek window lengths are read back as `None`.
+    pub fn tuning(&self) -> TuningParams {
+        let ms = |setting| match self.get_setting(setting) {
+            ms if ms > 0 => Some(ms as u32),
+            _ => None,
+        };
+        TuningParams {
+            sequence_ms: ms(Setting::SequenceMs),
+            seek_window_ms: ms(Setting::SeekwindowMs),
+            overlap_ms: self.get_setting(Setting::OverlapMs).max(0) as u32,
+            quickseek: self.get_setting(Setting::UseQuickseek) != 0,
+            aa_filter: self.get_setting(Setting::UseAaFilter) != 0,
+            aa_filter_length: self.get_setting(Setting::AaFilterLength).max(0) as u32,
+        }
+    }
+}
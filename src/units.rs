@@ -0,0 +1,158 @@
+//! Types that tell frames and samples apart.
+//!
+//! SoundTouch works on interleaved audio. A *sample* is a single value of one
+//! channel and a *frame* is one sample of every channel, so a stereo buffer of
+//! `6720` samples holds `3360` frames. The C++ API, and the methods of
+//! [`SoundTouch`] that mirror it, call frames "samples" and take them as plain
+//! numbers, which makes it easy to pass a slice length where a frame count is
+//! expected and process twice as much (or half as much) as intended.
+//!
+//! [`Frames`] and [`Samples`] make the unit part of the type. The frame-based
+//! methods [`put_frames`], [`receive_frames`] and [`skip_frames`] only accept
+//! [`Frames`]:
+//!
+//! ```rust
+//! use soundtouch::units::{Frames, Samples};
+//! use soundtouch::SoundTouch;
+//!
+//! let mut soundtouch = SoundTouch::new();
+//! soundtouch.set_channels(2).set_sample_rate(44100);
+//!
+//! let input = vec![0.0; 6720];
+//! let frames = Samples::len_of(&input).frames(2);
+//! assert_eq!(frames, Frames(3360));
+//! soundtouch.put_frames(&input, frames);
+//! ```
+//!
+//! Passing a sample count where frames are expected doesn't compile:
+//!
+//! ```compile_fail
+//! use soundtouch::units::Samples;
+//! use soundtouch::SoundTouch;
+//!
+//! let mut soundtouch = SoundTouch::new();
+//! let input = vec![0.0; 6720];
+//! soundtouch.put_frames(&input, Samples::len_of(&input));
+//! ```
+//!
+//! [`SoundTouch`]: crate::SoundTouch
+//! [`put_frames`]: crate::SoundTouch::put_frames
+//! [`receive_frames`]: crate::SoundTouch::receive_frames
+//! [`skip_frames`]: crate::SoundTouch::skip_frames
+
+use core::ops::{Add, Sub};
+use core::time::Duration;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A number of frames, i.e. samples per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Frames(pub usize);
+
+/// A number of interleaved samples, i.e. frames times channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Samples(pub usize);
+
+impl Frames {
+    /// Returns the number of samples these frames hold with `channels` channels.
+    ///
+    /// Saturates at `usize::MAX`.
+    pub const fn samples(self, channels: u32) -> Samples {
+        Samples(self.0.saturating_mul(channels as usize))
+    }
+
+    /// Returns the number of frames `duration` lasts at `sample_rate`, rounded to the
+    /// nearest frame with halves rounded up, or `None` if that doesn't fit in a `usize`
+    /// or `sample_rate` is `0`.
+    ///
+    /// ```rust
+    /// use soundtouch::units::Frames;
+    /// use std::time::Duration;
+    ///
+    /// // 220.5 frames.
+    /// assert_eq!(Frames::from_duration(Duration::from_millis(5), 44100), Some(Frames(221)));
+    /// ```
+    pub fn from_duration(duration: Duration, sample_rate: u32) -> Option<Frames> {
+        if sample_rate == 0 {
+            return None;
+        }
+        // At most 2^94 nanoseconds times 2^32 Hz, so this doesn't overflow.
+        let scaled = duration.as_nanos() * sample_rate as u128;
+        let frames = (scaled + NANOS_PER_SEC / 2) / NANOS_PER_SEC;
+        usize::try_from(frames).ok().map(Frames)
+    }
+
+    /// Returns how long these frames last at `sample_rate`, rounded to the nearest
+    /// nanosecond with halves rounded up.
+    ///
+    /// Converting the result back with [`from_duration`] gives the same frames for any
+    /// sample rate up to a billion Hz.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is `0`.
+    ///
+    /// [`from_duration`]: Frames::from_duration
+    pub fn to_duration(self, sample_rate: u32) -> Duration {
+        assert!(sample_rate > 0, "a duration needs a sample rate");
+        let sample_rate = sample_rate as u128;
+        let frames = self.0 as u128;
+        let secs = frames / sample_rate;
+        let nanos = ((frames % sample_rate) * NANOS_PER_SEC + sample_rate / 2) / sample_rate;
+        // `nanos` may round up to a whole second, which `Duration::new` carries over.
+        Duration::new(secs as u64, 0) + Duration::from_nanos(nanos as u64)
+    }
+}
+
+impl Samples {
+    /// Returns the number of samples in an interleaved buffer.
+    pub const fn len_of(samples: &[f32]) -> Self {
+        Self(samples.len())
+    }
+
+    /// Returns the number of whole frames these samples make up with `channels`
+    /// channels, or `Frames(0)` if `channels` is `0`.
+    pub const fn frames(self, channels: u32) -> Frames {
+        match self.0.checked_div(channels as usize) {
+            Some(frames) => Frames(frames),
+            None => Frames(0),
+        }
+    }
+
+    /// Returns the number of samples left over after the whole frames, i.e. the
+    /// length of an incomplete trailing frame, or `0` if `channels` is `0`.
+    pub const fn remainder(self, channels: u32) -> usize {
+        match self.0.checked_rem(channels as usize) {
+            Some(remainder) => remainder,
+            None => 0,
+        }
+    }
+}
+
+macro_rules! impl_arithmetic {
+    ($($unit:ident),*) => {$(
+        impl Add for $unit {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $unit {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl From<$unit> for usize {
+            fn from(value: $unit) -> usize {
+                value.0
+            }
+        }
+    )*};
+}
+
+impl_arithmetic!(Frames, Samples);
@@ -0,0 +1,85 @@
+//! End-to-end checks of the processing that downstream test suites can call.
+//!
+//! Stretching audio by a tempo factor scales its BPM by exactly that factor, so
+//! detecting the BPM before and after stretching checks the time-stretcher and
+//! the BPM detection against each other on real material.
+//!
+//! ```rust
+//! use soundtouch::verify::stretch_and_check_bpm;
+//!
+//! # let samples = vec![0.0; 44100 * 2];
+//! let check = stretch_and_check_bpm(&samples, 2, 44100, 1.25);
+//! if check.input_bpm > 0.0 {
+//!     assert!(check.relative_error < 0.02);
+//! }
+//! ```
+
+use crate::error::OrPanic;
+use crate::{BPMDetect, SoundTouch};
+
+/// The result of [`stretch_and_check_bpm`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StretchCheck {
+    /// BPM detected in the input, or `0.0` if none was detected.
+    pub input_bpm: f64,
+    /// BPM detected in the stretched output, or `0.0` if none was detected.
+    pub output_bpm: f64,
+    /// `input_bpm` times the tempo.
+    pub expected_output_bpm: f64,
+    /// Absolute difference of `output_bpm` and `expected_output_bpm`, relative to the
+    /// latter, or infinity if no BPM was detected in the input.
+    pub relative_error: f64,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Stretches `samples` (interleaved, with `channels` channels at `sample_rate`) by
+/// `tempo` with [`generate_audio`], detects the BPM of the input and the output with
+/// [`BPMDetect`], and reports how well the output BPM matches the input BPM times
+/// `tempo`.
+///
+/// BPM detection needs a clear, steady beat and at least several seconds of audio.
+/// It can also lock onto half or double the tempo, which shows up as a relative
+/// error of about `0.5` or `1.0`.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is invalid for [`BPMDetect::new`], or if
+/// [`SoundTouch::try_set_tempo`] rejects `tempo`.
+///
+/// [`generate_audio`]: SoundTouch::generate_audio
+pub fn stretch_and_check_bpm(
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+    tempo: f64,
+) -> StretchCheck {
+    let input_bpm = detect_bpm(samples, channels, sample_rate);
+
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(channels)
+        .set_sample_rate(sample_rate);
+    soundtouch.try_set_tempo(tempo).or_panic();
+    let output = soundtouch.generate_audio(samples);
+    let output_bpm = detect_bpm(&output, channels, sample_rate);
+
+    let expected_output_bpm = input_bpm * tempo;
+    let relative_error = if expected_output_bpm > 0.0 {
+        (output_bpm - expected_output_bpm).abs() / expected_output_bpm
+    } else {
+        f64::INFINITY
+    };
+    StretchCheck {
+        input_bpm,
+        output_bpm,
+        expected_output_bpm,
+        relative_error,
+    }
+}
+
+fn detect_bpm(samples: &[f32], channels: u32, sample_rate: u32) -> f64 {
+    let mut bpm = BPMDetect::new(channels, sample_rate);
+    bpm.input_all(samples, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    bpm.get_bpm() as f64
+}
@@ -0,0 +1,452 @@
+//! Processing WAV files with [hound], reading, processing and writing them a chunk at a
+//! time so that the memory use doesn't depend on their length.
+//!
+//! ```rust,no_run
+//! use soundtouch::wav::process_file_streaming;
+//! use soundtouch::Config;
+//!
+//! let config = Config::new().set_tempo(1.25).clone();
+//! let stats = process_file_streaming("in.wav", "out.wav", &config, 4096)?;
+//! println!("{} frames in, {} frames out", stats.frames_in, stats.frames_out);
+//! # Ok::<(), soundtouch::WavError>(())
+//! ```
+//!
+//! The channel mask of `WAVE_FORMAT_EXTENSIBLE` files, which `hound` doesn't expose, is
+//! read with [`read_channel_mask`] and carried over to the output.
+//!
+//! [hound]: https://docs.rs/hound
+
+use core::cell::Cell;
+use core::ops::ControlFlow;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+use crate::streaming::try_pump_stream;
+use crate::{CancellationToken, ChannelMask, Config, LoudnessReport, SoundTouch, WavError};
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// What [`process_file_streaming`] processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WavStats {
+    /// Frames read from the input.
+    pub frames_in: u64,
+    /// Frames written to the output.
+    pub frames_out: u64,
+    /// Most frames held inside SoundTouch at once, unprocessed and ready together. Apart
+    /// from this, memory use is bounded by two buffers of `chunk_frames` frames.
+    pub peak_pipeline_frames: usize,
+    /// Whether the progress callback stopped the processing before the end of the input.
+    pub cancelled: bool,
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Processes the WAV file at `input` with `config` and writes the result to a new WAV
+/// file at `output`, reading and writing `chunk_frames` frames at a time.
+///
+/// The number of channels and the sample rate are those of the input, whatever `config`
+/// sets them to. The output has the format of the input, with integer samples rounded
+/// to the nearest value and clamped, and the [channel mask] of the input, unless `hound`
+/// writes a plain header, as for mono and stereo files of up to 16 bits. It is the same
+/// as that of [`generate_audio`] on all of the input, as SoundTouch's output doesn't
+/// depend on the sizes of the calls.
+///
+/// # Errors
+///
+/// Returns [`WavError::Config`] if `config` is [invalid] with the format of the input,
+/// and [`WavError::Wav`] if reading or writing a file fails.
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`.
+///
+/// [`generate_audio`]: SoundTouch::generate_audio
+/// [invalid]: Config::validate
+/// [channel mask]: read_channel_mask
+pub fn process_file_streaming(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &Config,
+    chunk_frames: usize,
+) -> Result<WavStats, WavError> {
+    process_file_streaming_with_progress(input, output, config, chunk_frames, |_, _| {
+        ControlFlow::Continue(())
+    })
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`process_file_streaming`], but calls `progress` after every chunk with the
+/// number of frames read so far and the total number of frames of the input.
+///
+/// Returning [`ControlFlow::Break`] stops the processing without flushing the pipeline.
+/// The output is still finalized, as a valid WAV file of the frames written until then,
+/// and [`WavStats::cancelled`] is set.
+///
+/// # Errors
+///
+/// See [`process_file_streaming`].
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`.
+pub fn process_file_streaming_with_progress<F>(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &Config,
+    chunk_frames: usize,
+    progress: F,
+) -> Result<WavStats, WavError>
+where
+    F: FnMut(u64, u64) -> ControlFlow<()>,
+{
+    process_file(
+        input.as_ref(),
+        output.as_ref(),
+        config,
+        chunk_frames,
+        ChannelMask::default(),
+        progress,
+        None,
+    )
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`process_file_streaming`], but also measures the loudness of the input while
+/// it is processed, with [`SoundTouch::set_analyze_loudness`], e.g. to compute the
+/// replay gain of the output without reading the file again.
+///
+/// # Errors
+///
+/// See [`process_file_streaming`].
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`.
+pub fn process_file_streaming_with_loudness(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &Config,
+    chunk_frames: usize,
+) -> Result<(WavStats, LoudnessReport), WavError> {
+    let mut loudness = LoudnessReport::default();
+    let stats = process_file(
+        input.as_ref(),
+        output.as_ref(),
+        config,
+        chunk_frames,
+        ChannelMask::default(),
+        |_, _| ControlFlow::Continue(()),
+        Some(&mut loudness),
+    )?;
+    Ok((stats, loudness))
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`process_file_streaming`], but leaves the speakers of `speakers` out of the
+/// time-stretcher's search for splice points, e.g. [`ChannelMask::LOW_FREQUENCY`] for a
+/// surround file whose LFE channel would otherwise decide them. The channels are found
+/// with the [channel mask] of the input, or [`ChannelMask::default_for`] its number of
+/// channels if it has none. See [`SoundTouch::set_correlation_channel_mask`].
+///
+/// # Errors
+///
+/// See [`process_file_streaming`].
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`.
+///
+/// [channel mask]: read_channel_mask
+pub fn process_file_streaming_excluding(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &Config,
+    chunk_frames: usize,
+    speakers: ChannelMask,
+) -> Result<WavStats, WavError> {
+    process_file(
+        input.as_ref(),
+        output.as_ref(),
+        config,
+        chunk_frames,
+        speakers,
+        |_, _| ControlFlow::Continue(()),
+        None,
+    )
+}
+
+fn process_file<F>(
+    input: &Path,
+    output: &Path,
+    config: &Config,
+    chunk_frames: usize,
+    excluded: ChannelMask,
+    mut progress: F,
+    loudness: Option<&mut LoudnessReport>,
+) -> Result<WavStats, WavError>
+where
+    F: FnMut(u64, u64) -> ControlFlow<()>,
+{
+    assert!(chunk_frames > 0, "chunk_frames must be greater than 0");
+    let mask = read_channel_mask(input)?;
+    let mut reader = WavReader::open(input)?;
+    let spec = reader.spec();
+    let mut config = config.clone();
+    config
+        .set_channels(spec.channels.into())
+        .set_sample_rate(spec.sample_rate);
+    config.validate().map_err(WavError::Config)?;
+    let mut soundtouch = SoundTouch::with_config(&config);
+    let layout = mask.unwrap_or(ChannelMask::default_for(spec.channels.into()));
+    soundtouch
+        .set_correlation_channel_mask(layout.channel_bits(excluded))
+        .set_analyze_loudness(loudness.is_some());
+
+    let channels = spec.channels as usize;
+    let total = reader.duration() as u64;
+    let mut writer = WavWriter::create(output, spec)?;
+    let frames_in = Cell::new(0);
+    let frames_out = Cell::new(0);
+    let mut reported = 0;
+    let mut peak_pipeline_frames = 0;
+    let mut out_at_checkpoint = 0;
+    // Receiving only takes ready frames, so what was received since the last checkpoint
+    // is added back to get the frames held right after the put or flush before it.
+    let mut track_peak = |soundtouch: &SoundTouch| {
+        let levels = soundtouch.buffer_levels();
+        let received = (frames_out.get() - out_at_checkpoint) as usize;
+        out_at_checkpoint = frames_out.get();
+        peak_pipeline_frames =
+            peak_pipeline_frames.max(levels.unprocessed_frames + levels.ready_frames + received);
+    };
+
+    let read = |buffer: &mut [f32]| -> Result<usize, WavError> {
+        let frames = read_samples(&mut reader, spec, buffer)?;
+        frames_in.set(frames_in.get() + frames as u64);
+        Ok(frames)
+    };
+    let write = |samples: &[f32]| -> Result<(), WavError> {
+        write_samples(&mut writer, spec, samples)?;
+        frames_out.set(frames_out.get() + (samples.len() / channels) as u64);
+        Ok(())
+    };
+    // Called before every read, so progress is reported once per chunk after it went
+    // through, and not again before the flush.
+    let checkpoint = |soundtouch: &mut SoundTouch| {
+        track_peak(soundtouch);
+        if frames_in.get() > reported {
+            reported = frames_in.get();
+            if progress(reported, total).is_break() {
+                return Err(WavError::Cancelled);
+            }
+        }
+        Ok(())
+    };
+    let cancelled = match try_pump_stream(&mut soundtouch, read, write, chunk_frames, checkpoint) {
+        Ok(()) => false,
+        Err(WavError::Cancelled) => true,
+        Err(err) => return Err(err),
+    };
+    track_peak(&soundtouch);
+    let stats = WavStats {
+        frames_in: frames_in.get(),
+        frames_out: frames_out.get(),
+        peak_pipeline_frames,
+        cancelled,
+    };
+    writer.finalize()?;
+    // `hound` writes the default mask, or none with a plain header.
+    if let Some(mask) = mask.filter(|&mask| mask != ChannelMask::default_for(channels as u32)) {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(output)
+            .map_err(hound::Error::from)?;
+        patch_channel_mask(&mut file, mask)?;
+    }
+    if let Some(loudness) = loudness {
+        *loudness = soundtouch.loudness().unwrap_or_default();
+    }
+    Ok(stats)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Like [`process_file_streaming`], but checks `token` after every chunk.
+///
+/// # Errors
+///
+/// Returns [`WavError::Cancelled`] once `token` is cancelled, after finalizing the
+/// output as a valid WAV file of the frames written until then. Otherwise returns the
+/// errors of [`process_file_streaming`].
+///
+/// # Panics
+///
+/// Panics if `chunk_frames` is `0`.
+pub fn process_file_streaming_cancellable(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &Config,
+    chunk_frames: usize,
+    token: &CancellationToken,
+) -> Result<WavStats, WavError> {
+    let progress = |_: u64, _: u64| {
+        if token.is_cancelled() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let stats =
+        process_file_streaming_with_progress(input, output, config, chunk_frames, progress)?;
+    if stats.cancelled {
+        return Err(WavError::Cancelled);
+    }
+    Ok(stats)
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Returns the channel mask of the WAV file at `path`, or `None` if its header isn't
+/// `WAVE_FORMAT_EXTENSIBLE`, as with most mono and stereo files, which then have the
+/// [default] layout.
+///
+/// # Errors
+///
+/// Returns [`WavError::Wav`] if the file can't be read or isn't a WAV file.
+///
+/// [default]: ChannelMask::default_for
+pub fn read_channel_mask(path: impl AsRef<Path>) -> Result<Option<ChannelMask>, WavError> {
+    let mut file = File::open(path).map_err(hound::Error::from)?;
+    Ok(find_channel_mask(&mut file)?.map(|(_, mask)| mask))
+}
+
+/// **NOT FROM SOUNDTOUCH**
+///
+/// Sets the channel mask in the `WAVE_FORMAT_EXTENSIBLE` header of the WAV file at
+/// `path`, e.g. in a file written by `hound`, which always writes the
+/// [default](ChannelMask::default_for) one.
+///
+/// # Errors
+///
+/// Returns [`WavError::Wav`] if the file can't be read or written, isn't a WAV file or
+/// doesn't have a `WAVE_FORMAT_EXTENSIBLE` header.
+pub fn write_channel_mask(path: impl AsRef<Path>, mask: ChannelMask) -> Result<(), WavError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(hound::Error::from)?;
+    if !patch_channel_mask(&mut file, mask)? {
+        return Err(hound::Error::FormatError("no WAVE_FORMAT_EXTENSIBLE header").into());
+    }
+    Ok(())
+}
+
+/// Overwrites the channel mask of the WAV file `file`, returning `false` if its header
+/// isn't `WAVE_FORMAT_EXTENSIBLE`.
+fn patch_channel_mask(file: &mut File, mask: ChannelMask) -> Result<bool, hound::Error> {
+    let Some((offset, _)) = find_channel_mask(file)? else {
+        return Ok(false);
+    };
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(&mask.0.to_le_bytes())?;
+    Ok(true)
+}
+
+/// Finds the `fmt ` chunk of a WAV file and returns the offset and value of its
+/// `dwChannelMask` if it is a `WAVE_FORMAT_EXTENSIBLE` one.
+fn find_channel_mask<R: Read + Seek>(
+    reader: &mut R,
+) -> Result<Option<(u64, ChannelMask)>, hound::Error> {
+    const EXTENSIBLE: u16 = 0xfffe;
+    // The offset of `dwChannelMask` in the chunk and the size of a chunk that has it.
+    const MASK_OFFSET: u64 = 20;
+    const EXTENSIBLE_LEN: u32 = 40;
+
+    let mut header = [0; 12];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+    if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+        return Err(hound::Error::FormatError("no RIFF WAVE header"));
+    }
+    loop {
+        let mut chunk = [0; 8];
+        reader.read_exact(&mut chunk)?;
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        if &chunk[..4] != b"fmt " {
+            // Chunks are padded to an even length.
+            reader.seek(SeekFrom::Current(i64::from(len) + i64::from(len & 1)))?;
+            continue;
+        }
+        let start = reader.stream_position()?;
+        let mut tag = [0; 2];
+        reader.read_exact(&mut tag)?;
+        if u16::from_le_bytes(tag) != EXTENSIBLE || len < EXTENSIBLE_LEN {
+            return Ok(None);
+        }
+        let offset = start + MASK_OFFSET;
+        let mut mask = [0; 4];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut mask)?;
+        return Ok(Some((offset, ChannelMask(u32::from_le_bytes(mask)))));
+    }
+}
+
+/// Reads up to `buffer.len()` samples, scaling integers to `-1.0..1.0`, and returns the
+/// number of whole frames read.
+fn read_samples<R: Read>(
+    reader: &mut WavReader<R>,
+    spec: WavSpec,
+    buffer: &mut [f32],
+) -> Result<usize, hound::Error> {
+    let mut len = 0;
+    match spec.sample_format {
+        SampleFormat::Float => {
+            for (slot, sample) in buffer.iter_mut().zip(reader.samples::<f32>()) {
+                *slot = sample?;
+                len += 1;
+            }
+        }
+        SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            for (slot, sample) in buffer.iter_mut().zip(reader.samples::<i32>()) {
+                *slot = sample? as f32 * scale;
+                len += 1;
+            }
+        }
+    }
+    Ok(len / spec.channels as usize)
+}
+
+/// Writes `samples` in the format of `spec`, rounding integers half away from zero and
+/// clamping them to their range.
+fn write_samples<W: Write + Seek>(
+    writer: &mut WavWriter<W>,
+    spec: WavSpec,
+    samples: &[f32],
+) -> Result<(), hound::Error> {
+    match spec.sample_format {
+        SampleFormat::Float => {
+            for &sample in samples {
+                writer.write_sample(sample)?;
+            }
+        }
+        SampleFormat::Int => {
+            let max = 1i64 << (spec.bits_per_sample - 1);
+            for &sample in samples {
+                // `as` saturates and maps NaN to 0.
+                let scaled = (sample as f64 * max as f64).round() as i64;
+                writer.write_sample(scaled.clamp(-max, max - 1) as i32)?;
+            }
+        }
+    }
+    Ok(())
+}
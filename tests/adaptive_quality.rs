@@ -0,0 +1,193 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::stretcher;
+use soundtouch::realtime::{
+    AdaptiveConfig, AdaptiveQuality, Clock, QualityLevel, QualityTransition,
+};
+use soundtouch::{signal, Scratch, Setting, SoundTouch};
+
+/// Blocks of 441 frames, i.e. 10 ms at 44.1 kHz.
+const BLOCK_FRAMES: usize = 441;
+
+/// A clock that advances by `step` every time it is read, so that every block takes
+/// `step` to process.
+#[derive(Debug, Default)]
+struct StepClock {
+    now: Duration,
+    step: Duration,
+}
+
+impl Clock for StepClock {
+    fn now(&mut self) -> Duration {
+        self.now += self.step;
+        self.now
+    }
+}
+
+fn config() -> AdaptiveConfig {
+    AdaptiveConfig {
+        degrade_load: 0.8,
+        recover_load: 0.5,
+        degrade_after: 3,
+        recover_after: 5,
+        short_seek_window_ms: Some(8),
+    }
+}
+
+struct Harness {
+    soundtouch: SoundTouch,
+    adaptive: AdaptiveQuality<StepClock>,
+    scratch: Scratch,
+    transitions: Arc<Mutex<Vec<QualityTransition>>>,
+}
+
+impl Harness {
+    fn new(config: AdaptiveConfig) -> Self {
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let mut adaptive = AdaptiveQuality::with_clock(config, StepClock::default());
+        let sink = Arc::clone(&transitions);
+        adaptive.on_transition(move |transition| sink.lock().unwrap().push(transition));
+        let mut soundtouch = stretcher(1, 44100, 1.5);
+        soundtouch.set_setting(Setting::SeekwindowMs, 20);
+        Self {
+            soundtouch,
+            adaptive,
+            scratch: Scratch::for_channels(1),
+            transitions,
+        }
+    }
+
+    /// Processes `blocks` blocks that each take `millis` of their 10 ms.
+    fn run(&mut self, blocks: usize, millis: u64) {
+        self.adaptive.clock_mut().step = Duration::from_millis(millis);
+        let input = signal::sine(220.0, 44100, BLOCK_FRAMES).into_vec();
+        let mut output = [0.0; BLOCK_FRAMES];
+        for _ in 0..blocks {
+            self.adaptive.process_block(
+                &mut self.soundtouch,
+                &input,
+                &mut output,
+                &mut self.scratch,
+            );
+        }
+    }
+
+    fn levels(&self) -> Vec<(QualityLevel, QualityLevel)> {
+        let transitions = self.transitions.lock().unwrap();
+        transitions.iter().map(|t| (t.from, t.to)).collect()
+    }
+}
+
+#[test]
+fn degrades_after_consecutive_overloaded_blocks() {
+    let mut harness = Harness::new(config());
+    harness.run(2, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Full);
+    assert_eq!(harness.soundtouch.get_setting(Setting::UseQuickseek), 0);
+
+    harness.run(1, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Quickseek);
+    assert_eq!(harness.soundtouch.get_setting(Setting::UseQuickseek), 1);
+    assert_eq!(harness.soundtouch.get_setting(Setting::SeekwindowMs), 20);
+
+    harness.run(3, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::ShortSeekWindow);
+    assert_eq!(harness.soundtouch.get_setting(Setting::SeekwindowMs), 8);
+
+    // There is no lower level.
+    harness.run(10, 9);
+    assert_eq!(
+        harness.levels(),
+        [
+            (QualityLevel::Full, QualityLevel::Quickseek),
+            (QualityLevel::Quickseek, QualityLevel::ShortSeekWindow),
+        ]
+    );
+    let load = harness.transitions.lock().unwrap()[0].load;
+    assert!((load - 0.9).abs() < 1e-9, "load {load}");
+}
+
+#[test]
+fn recovers_and_restores_the_settings() {
+    let mut harness = Harness::new(config());
+    harness.run(6, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::ShortSeekWindow);
+
+    harness.run(4, 2);
+    assert_eq!(harness.adaptive.level(), QualityLevel::ShortSeekWindow);
+    harness.run(1, 2);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Quickseek);
+    assert_eq!(harness.soundtouch.get_setting(Setting::SeekwindowMs), 20);
+    assert_eq!(harness.soundtouch.get_setting(Setting::UseQuickseek), 1);
+
+    harness.run(5, 2);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Full);
+    assert_eq!(harness.soundtouch.get_setting(Setting::UseQuickseek), 0);
+    assert_eq!(
+        harness.levels()[2..],
+        [
+            (QualityLevel::ShortSeekWindow, QualityLevel::Quickseek),
+            (QualityLevel::Quickseek, QualityLevel::Full),
+        ]
+    );
+}
+
+#[test]
+fn blocks_between_the_thresholds_reset_the_counts() {
+    let mut harness = Harness::new(config());
+    for _ in 0..10 {
+        harness.run(2, 9);
+        harness.run(1, 6);
+    }
+    assert_eq!(harness.adaptive.level(), QualityLevel::Full);
+
+    harness.run(3, 9);
+    for _ in 0..10 {
+        harness.run(4, 2);
+        harness.run(1, 6);
+    }
+    assert_eq!(harness.adaptive.level(), QualityLevel::Quickseek);
+    assert_eq!(harness.levels().len(), 1);
+}
+
+#[test]
+fn seek_window_is_kept_without_a_short_one() {
+    let mut harness = Harness::new(AdaptiveConfig {
+        short_seek_window_ms: None,
+        ..config()
+    });
+    harness.run(20, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Quickseek);
+    assert_eq!(harness.soundtouch.get_setting(Setting::SeekwindowMs), 20);
+}
+
+#[test]
+fn restore_undoes_the_changes_silently() {
+    let mut harness = Harness::new(config());
+    harness.soundtouch.set_setting(Setting::UseQuickseek, 1);
+    harness.run(6, 9);
+    assert_eq!(harness.adaptive.level(), QualityLevel::ShortSeekWindow);
+
+    harness.adaptive.restore(&mut harness.soundtouch);
+    assert_eq!(harness.adaptive.level(), QualityLevel::Full);
+    assert_eq!(harness.soundtouch.get_setting(Setting::SeekwindowMs), 20);
+    // Quickseek was already enabled before the controller changed it.
+    assert_eq!(harness.soundtouch.get_setting(Setting::UseQuickseek), 1);
+    assert_eq!(harness.levels().len(), 2);
+}
+
+#[test]
+fn empty_blocks_are_ignored() {
+    let mut harness = Harness::new(config());
+    let mut soundtouch = stretcher(1, 44100, 1.5);
+    soundtouch.set_setting(Setting::SeekwindowMs, 20);
+    for _ in 0..10 {
+        harness
+            .adaptive
+            .observe(&mut soundtouch, 0, Duration::from_secs(1));
+    }
+    assert_eq!(harness.adaptive.level(), QualityLevel::Full);
+}
@@ -0,0 +1,78 @@
+use soundtouch::align::{aligned, best_alignment, Alignment};
+use soundtouch::signal;
+
+/// Returns `samples` delayed by `frames` frames of `channels` channels of silence.
+fn delayed(samples: &[f32], channels: usize, frames: usize) -> Vec<f32> {
+    let mut delayed = vec![0.0; frames * channels];
+    delayed.extend_from_slice(samples);
+    delayed
+}
+
+#[test]
+fn recovers_known_offsets() {
+    for channels in [1, 2, 5] {
+        let a = signal::pink_noise(9, 44100, 10_000)
+            .channels(channels as u32)
+            .into_vec();
+        for frames in [0, 1, 7, 40] {
+            let b = delayed(&a, channels, frames);
+            assert_eq!(
+                best_alignment(&a, &b, channels, 64),
+                Alignment {
+                    offset: frames as isize,
+                    rms_error: 0.0
+                },
+                "{channels} channels, delayed by {frames}"
+            );
+            assert_eq!(
+                best_alignment(&b, &a, channels, 64).offset,
+                -(frames as isize)
+            );
+        }
+    }
+}
+
+#[test]
+fn offsets_are_in_frames() {
+    // Channels differ, so a shift by a sample instead of a frame would mix them up.
+    let left = signal::noise(1, 44100, 5000);
+    let right = signal::noise(2, 44100, 5000);
+    let a: Vec<f32> = left.zip(right).flat_map(|(l, r)| [l, r]).collect();
+    let b = delayed(&a, 2, 3);
+    let alignment = best_alignment(&a, &b, 2, 10);
+    assert_eq!(alignment.offset, 3);
+    assert_eq!(alignment.rms_error, 0.0);
+    let (x, y) = aligned(&a, &b, 2, alignment.offset);
+    assert_eq!(x, y);
+    assert_eq!(x.len(), a.len());
+}
+
+#[test]
+fn levels_do_not_move_the_offset() {
+    let a = signal::noise(3, 44100, 8000).into_vec();
+    let b: Vec<f32> = delayed(&a, 1, 12).iter().map(|s| 0.5 * s).collect();
+    let alignment = best_alignment(&a, &b, 1, 20);
+    assert_eq!(alignment.offset, 12);
+    let rms = (a.iter().map(|s| s * s).sum::<f32>() / a.len() as f32).sqrt();
+    assert!(
+        (alignment.rms_error - 0.5 * rms).abs() < 1e-4,
+        "{alignment:?}"
+    );
+}
+
+#[test]
+fn search_is_bounded() {
+    let a = signal::noise(4, 44100, 4000).into_vec();
+    let b = delayed(&a, 1, 30);
+    assert_ne!(best_alignment(&a, &b, 1, 20).offset, 30);
+    assert_eq!(best_alignment(&a, &b, 1, usize::MAX).offset, 30);
+
+    // Silence aligns at 0, and signals that don't overlap have no error.
+    let silence = [0.0; 100];
+    assert_eq!(
+        best_alignment(&silence, &silence, 1, 10),
+        Alignment::default()
+    );
+    assert_eq!(best_alignment(&[], &a, 1, 10).rms_error, 0.0);
+    assert_eq!(aligned(&a, &b, 1, 10_000), (&[][..], &[][..]));
+}
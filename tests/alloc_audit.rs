@@ -0,0 +1,95 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::alloc_audit::{assert_no_alloc, count_allocations, CountingAllocator};
+use soundtouch::{signal, Config, Sanitize, Scratch, SliceSink, SoundTouch};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const FRAMES: usize = 1024;
+
+#[test]
+fn put_and_receive() {
+    let input = signal::sine(220.0, 44100, FRAMES).stereo().into_vec();
+    let mut output = vec![0.0; 4 * 2 * FRAMES];
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_pitch_semitones(2.0);
+    st.set_input_sanitization(Sanitize::ZeroNonFinite)
+        .set_metering(true)
+        .set_output_block_frames(Some(480));
+    let mut run = |st: &mut SoundTouch| {
+        for _ in 0..50 {
+            st.put_samples(&input, FRAMES);
+            st.receive_samples(&mut output, 4 * FRAMES);
+        }
+    };
+    run(&mut st);
+    assert_no_alloc(|| run(&mut st));
+}
+
+#[test]
+fn process_block() {
+    let config = Config::new();
+    let mut st = SoundTouch::with_config(config.clone().set_tempo(0.8));
+    let mut scratch = Scratch::for_config(&config);
+    let input = signal::sine(220.0, 44100, FRAMES).stereo().into_vec();
+    let mut output = vec![0.0; 4 * 2 * FRAMES];
+    let mut run = |st: &mut SoundTouch| {
+        for _ in 0..50 {
+            st.process_block(&input, &mut output, &mut scratch);
+        }
+    };
+    run(&mut st);
+    assert_no_alloc(|| run(&mut st));
+}
+
+#[test]
+fn process_chunk_into_a_slice() {
+    let input = signal::sine(220.0, 44100, FRAMES).stereo().into_vec();
+    let mut output = vec![0.0; 4 * 2 * FRAMES];
+    let mut run = |st: &mut SoundTouch| {
+        for _ in 0..50 {
+            st.process_chunk(&input, &mut SliceSink::new(&mut output));
+        }
+    };
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_pitch_semitones(2.0);
+    run(&mut st);
+    assert_no_alloc(|| run(&mut st));
+}
+
+#[test]
+fn f64() {
+    let input: Vec<f64> = signal::sine(220.0, 44100, FRAMES)
+        .stereo()
+        .map(f64::from)
+        .collect();
+    let mut output = vec![0.0; 4 * 2 * FRAMES];
+    let mut run = |st: &mut SoundTouch| {
+        for _ in 0..50 {
+            st.put_samples_f64(&input, FRAMES);
+            st.receive_samples_f64(&mut output, 4 * FRAMES);
+        }
+    };
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_pitch_semitones(2.0);
+    run(&mut st);
+    assert_no_alloc(|| run(&mut st));
+}
+
+#[test]
+fn generate_audio_allocates() {
+    let input = signal::sine(220.0, 44100, FRAMES).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_pitch_semitones(2.0);
+    let (output, allocations) = count_allocations(|| st.generate_audio(&input));
+    assert!(!output.is_empty());
+    assert!(allocations > 0);
+}
+
+#[test]
+#[should_panic(expected = "allocations where none were expected")]
+fn detects_allocations() {
+    assert_no_alloc(|| vec![0u8; 16]);
+}
@@ -0,0 +1,120 @@
+use soundtouch::auto::{analyze, auto_stretch, MaterialProfile, RecommendationPolicy};
+use soundtouch::signal;
+
+const SAMPLE_RATE: u32 = 44100;
+
+fn percussive() -> Vec<f32> {
+    signal::click_track(300.0, SAMPLE_RATE, 10 * 44100)
+        .stereo()
+        .into_vec()
+}
+
+fn sustained() -> Vec<f32> {
+    signal::sine(220.0, SAMPLE_RATE, 10 * 44100)
+        .stereo()
+        .into_vec()
+}
+
+#[test]
+fn percussive_material_gets_shorter_sequences() {
+    let clicks = analyze(&percussive(), 2, SAMPLE_RATE);
+    let tone = analyze(&sustained(), 2, SAMPLE_RATE);
+    assert!(
+        clicks.transient_density > tone.transient_density + 3.0,
+        "{clicks:?} {tone:?}"
+    );
+
+    for tempo in [0.5, 1.0, 1.5, 2.0] {
+        let short = clicks.recommended_tuning(tempo).sequence_ms.unwrap();
+        let long = tone.recommended_tuning(tempo).sequence_ms.unwrap();
+        assert!(short < long, "tempo {tempo}: {short} ms, {long} ms");
+        assert!(clicks.recommended_tuning(tempo).validate().is_ok());
+        assert!(tone.recommended_tuning(tempo).validate().is_ok());
+    }
+}
+
+#[test]
+fn silence_ratio() {
+    let mut samples = sustained();
+    samples[..10 * 44100].fill(0.0);
+    let profile = analyze(&samples, 2, SAMPLE_RATE);
+    assert!(
+        (profile.silence_ratio - 0.5).abs() < 0.01,
+        "{}",
+        profile.silence_ratio
+    );
+    assert!((profile.duration_secs - 10.0).abs() < 1e-9);
+
+    let silent = analyze(&[0.0; 44100], 1, SAMPLE_RATE);
+    assert_eq!(silent.silence_ratio, 1.0);
+    assert_eq!(silent.transient_density, 0.0);
+    assert_eq!(silent.bpm, None);
+}
+
+#[test]
+fn bpm_is_detected() {
+    let clicks = signal::click_track(128.0, SAMPLE_RATE, 20 * 44100).into_vec();
+    let bpm = analyze(&clicks, 1, SAMPLE_RATE).bpm.unwrap();
+    assert!((bpm - 128.0).abs() < 1.0, "{bpm}");
+}
+
+#[test]
+fn policy() {
+    let policy = RecommendationPolicy::default();
+    let profile = |transient_density| MaterialProfile {
+        transient_density,
+        ..MaterialProfile::default()
+    };
+    let sequence =
+        |profile: MaterialProfile, tempo| policy.recommend(&profile, tempo).sequence_ms.unwrap();
+    assert_eq!(sequence(profile(0.0), 1.0), 100);
+    assert_eq!(sequence(profile(2.0), 1.0), 70);
+    assert_eq!(sequence(profile(4.0), 1.0), 40);
+    assert_eq!(sequence(profile(40.0), 1.0), 40);
+    // Scaled by `1 / √tempo`, with the tempo clamped.
+    assert_eq!(sequence(profile(0.0), 2.0), 71);
+    assert_eq!(sequence(profile(0.0), 4.0), 71);
+    assert_eq!(sequence(profile(0.0), 0.5), 141);
+
+    // An eighth of a beat at 300 BPM, twice that when only half as percussive, and
+    // nothing for sustained material.
+    let beat = |transient_density| MaterialProfile {
+        bpm: Some(300.0),
+        ..profile(transient_density)
+    };
+    assert_eq!(sequence(beat(4.0), 1.0), 25);
+    assert_eq!(sequence(beat(2.0), 1.0), 50);
+    assert_eq!(sequence(beat(0.0), 1.0), 100);
+
+    let custom = RecommendationPolicy {
+        sustained_sequence_ms: 200,
+        overlap_ms: 12,
+        ..policy
+    };
+    let tuning = custom.recommend(&profile(0.0), 1.0);
+    assert_eq!(tuning.sequence_ms, Some(200));
+    assert_eq!(tuning.seek_window_ms, Some(25));
+    assert_eq!(tuning.overlap_ms, 12);
+    // The sequence stays longer than the overlap.
+    let tiny = RecommendationPolicy {
+        percussive_sequence_ms: 1,
+        ..custom
+    };
+    assert_eq!(tiny.recommend(&profile(8.0), 1.0).sequence_ms, Some(13));
+}
+
+#[test]
+fn auto_stretch_length_matches_the_ratio() {
+    for input in [percussive(), sustained()] {
+        for tempo in [0.5, 0.8, 1.25, 2.0] {
+            let output = auto_stretch(&input, 2, SAMPLE_RATE, tempo);
+            assert_eq!(output.len() % 2, 0);
+            let expected = (input.len() / 2) as f64 / tempo;
+            let actual = (output.len() / 2) as f64;
+            assert!(
+                (actual - expected).abs() <= 1.0,
+                "tempo {tempo}: {actual} frames, expected {expected}"
+            );
+        }
+    }
+}
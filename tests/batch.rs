@@ -0,0 +1,60 @@
+use soundtouch::{batch, signal, Config, SoundTouch};
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.set_tempo(1.3).set_rate(0.9);
+    config
+}
+
+fn clips() -> Vec<Vec<f32>> {
+    [0, 1, 100, 2000, 4410, 11_025, 300, 1]
+        .into_iter()
+        .map(|frames| signal::sine(220.0, 44100, frames).stereo().into_vec())
+        .collect()
+}
+
+#[test]
+fn matches_instance_per_clip() {
+    let config = config();
+    let clips = clips();
+    let clips: Vec<&[f32]> = clips.iter().map(Vec::as_slice).collect();
+    let outputs = batch::stretch_clips(&config, &clips);
+    assert_eq!(outputs.len(), clips.len());
+    for (index, (clip, output)) in clips.iter().zip(&outputs).enumerate() {
+        let expected = SoundTouch::with_config(&config).generate_audio(clip);
+        assert!(*output == expected, "clip {index}");
+    }
+}
+
+#[test]
+fn exact_lengths_in_order() {
+    for tempo in [0.5, 0.8, 1.0, 1.7] {
+        let mut config = config();
+        config.set_tempo(tempo);
+        let ratio = SoundTouch::with_config(&config).get_input_output_sample_ratio();
+        let clips = clips();
+        let clips: Vec<&[f32]> = clips.iter().map(Vec::as_slice).collect();
+        let outputs = batch::stretch_clips(&config, &clips);
+        for (clip, output) in clips.iter().zip(&outputs) {
+            let frames = clip.len() / 2;
+            let expected = (frames as f64 * ratio).round() as usize;
+            assert_eq!(
+                output.len(),
+                expected * 2,
+                "{frames} frames at tempo {tempo}"
+            );
+        }
+    }
+}
+
+#[test]
+fn trailing_partial_frame_is_ignored() {
+    let clip = signal::sine(220.0, 44100, 1000).stereo().into_vec();
+    let outputs = batch::stretch_clips(&config(), &[&clip[..1999], &clip[..1998]]);
+    assert!(outputs[0] == outputs[1]);
+}
+
+#[test]
+fn no_clips() {
+    assert!(batch::stretch_clips(&config(), &[]).is_empty());
+}
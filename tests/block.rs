@@ -0,0 +1,82 @@
+use soundtouch::{signal, Config, Pending, Scratch, SoundTouch};
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.set_channels(3).set_tempo(0.9).set_pitch(1.1);
+    config
+}
+
+#[test]
+fn odd_blocks_match_generate_audio() {
+    let config = config();
+    let input = signal::sine(220.0, 44100, 20_000).channels(3).into_vec();
+    let expected = SoundTouch::with_config(&config).generate_audio(&input);
+
+    for (block, room) in [(1, 1), (7, 5), (1000, 64), (4097, 10_000)] {
+        let mut soundtouch = SoundTouch::with_config(&config);
+        let mut scratch = Scratch::for_config(&config);
+        let mut buffer = vec![0.0; room * 3];
+        let mut output = Vec::new();
+        let mut consumed = 0;
+        let mut receive = |soundtouch: &mut SoundTouch, scratch: &mut Scratch, input: &[f32]| {
+            let mut result = soundtouch.process_block(input, &mut buffer, scratch);
+            let consumed = result.consumed_frames;
+            loop {
+                assert!(result.produced_frames <= room);
+                output.extend_from_slice(&buffer[..result.produced_frames * 3]);
+                if result.pending != Pending::MoreAvailable {
+                    break consumed;
+                }
+                result = soundtouch.process_block(&[], &mut buffer, scratch);
+            }
+        };
+        for chunk in input.chunks(block) {
+            consumed += receive(&mut soundtouch, &mut scratch, chunk);
+        }
+        assert_eq!(consumed, 20_000);
+        assert_eq!(scratch.pending_samples(), 0);
+        soundtouch.flush();
+        receive(&mut soundtouch, &mut scratch, &[]);
+        assert!(
+            output == expected,
+            "blocks of {block} samples, room for {room} frames"
+        );
+    }
+}
+
+#[test]
+fn partial_frames_are_held_back() {
+    let config = config();
+    let mut soundtouch = SoundTouch::with_config(&config);
+    let mut scratch = Scratch::for_config(&config);
+    let result = soundtouch.process_block(&[0.1, 0.2], &mut [], &mut scratch);
+    assert_eq!(result.consumed_frames, 0);
+    assert_eq!(scratch.pending_samples(), 2);
+    assert_eq!(soundtouch.num_unprocessed_samples(), 0);
+
+    let result = soundtouch.process_block(&[0.3, 0.4, 0.5, 0.6, 0.7], &mut [], &mut scratch);
+    assert_eq!(result.consumed_frames, 2);
+    assert_eq!(scratch.pending_samples(), 1);
+    scratch.clear();
+    assert_eq!(scratch.pending_samples(), 0);
+}
+
+#[test]
+fn reports_pending_output() {
+    let config = config();
+    let mut soundtouch = SoundTouch::with_config(&config);
+    let mut scratch = Scratch::for_config(&config);
+    let input = signal::sine(220.0, 44100, 20_000).channels(3).into_vec();
+    let mut output = [0.0; 30];
+    let result = soundtouch.process_block(&input, &mut output, &mut scratch);
+    assert_eq!(result.consumed_frames, 20_000);
+    assert_eq!(result.produced_frames, 10);
+    assert_eq!(result.pending, Pending::MoreAvailable);
+}
+
+#[test]
+#[should_panic(expected = "expected 3 channels, but got 2")]
+fn mismatched_scratch_panics() {
+    let mut soundtouch = SoundTouch::with_config(&config());
+    soundtouch.process_block(&[], &mut [], &mut Scratch::for_channels(2));
+}
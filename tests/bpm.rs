@@ -0,0 +1,473 @@
+mod common;
+
+use std::time::Duration;
+
+use soundtouch::bpm::BeatGrid;
+use soundtouch::{signal, BPMDetect, Beat, BpmResult, Downmix, SoundTouch, SoundTouchError};
+
+#[test]
+fn builder_defaults() {
+    let bpm = BPMDetect::builder().build().unwrap();
+    assert_eq!(bpm.decimation_factor(), 44);
+    assert!((bpm.effective_analysis_rate() - 44100.0 / 44.0).abs() < 1e-9);
+}
+
+#[test]
+fn builder_formats() {
+    for (channels, sample_rate, factor) in [(1, 8000, 8), (2, 48000, 48), (16, 192000, 192)] {
+        let bpm = BPMDetect::builder()
+            .channels(channels)
+            .sample_rate(sample_rate)
+            .build()
+            .unwrap();
+        assert_eq!(bpm.decimation_factor(), factor);
+        assert_eq!(bpm.effective_analysis_rate(), 1000.0);
+    }
+}
+
+#[test]
+fn builder_rejects_invalid_channels() {
+    for channels in [0, SoundTouch::MAX_CHANNELS + 1, u32::MAX] {
+        assert_eq!(
+            BPMDetect::builder().channels(channels).build().err(),
+            Some(SoundTouchError::InvalidChannels(channels))
+        );
+    }
+}
+
+#[test]
+fn builder_rejects_invalid_sample_rates() {
+    for sample_rate in [
+        0,
+        BPMDetect::MIN_UPSAMPLED_SAMPLE_RATE - 1,
+        SoundTouch::MAX_SAMPLE_RATE + 1,
+        u32::MAX,
+    ] {
+        assert_eq!(
+            BPMDetect::builder().sample_rate(sample_rate).build().err(),
+            Some(SoundTouchError::InvalidSampleRate(sample_rate))
+        );
+    }
+}
+
+#[test]
+fn chunked_input_matches() {
+    let input = common::click_track(2, 44100, 120.0, 20.0, 0.8);
+    let mut whole = BPMDetect::new(2, 44100);
+    whole.input_samples(&input);
+    let expected = whole.get_bpm();
+    assert!((expected - 120.0).abs() < 1.0, "{expected}");
+
+    for chunk_frames in [1, 1000, BPMDetect::RECOMMENDED_CHUNK_FRAMES, 1 << 20] {
+        let mut bpm = BPMDetect::new(2, 44100);
+        bpm.input_all(&input, chunk_frames);
+        assert_eq!(bpm.get_bpm(), expected, "chunks of {chunk_frames} frames");
+    }
+}
+
+#[test]
+fn input_progress() {
+    // The incomplete trailing frame isn't counted.
+    let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    let mut bpm = BPMDetect::new(2, 44100);
+    let mut calls = Vec::new();
+    bpm.input_all_with_progress(&input[..input.len() - 1], 4096, |done, total| {
+        calls.push((done, total))
+    });
+    assert_eq!(calls, [(4096, 9999), (8192, 9999), (9999, 9999)]);
+}
+
+#[test]
+#[should_panic(expected = "chunk_frames must be greater than 0")]
+fn zero_chunk_frames_panics() {
+    BPMDetect::new(2, 44100).input_all(&[0.0; 4], 0);
+}
+
+fn beats(amplitude: f32) -> (Vec<Beat>, Vec<Beat>, f32) {
+    let mut bpm = BPMDetect::new(2, 44100);
+    bpm.input_all(
+        &common::click_track(2, 44100, 120.0, 20.0, amplitude),
+        BPMDetect::RECOMMENDED_CHUNK_FRAMES,
+    );
+    (bpm.beats(), bpm.beats_raw(), bpm.suggested_threshold())
+}
+
+#[test]
+fn normalized_beats_do_not_depend_on_level() {
+    let (loud, loud_raw, loud_threshold) = beats(0.8);
+    let (quiet, quiet_raw, quiet_threshold) = beats(0.1);
+    assert!(loud.len() > 20);
+    assert_eq!(loud.len(), quiet.len());
+
+    let max = loud.iter().map(|beat| beat.strength).fold(0.0, f32::max);
+    assert_eq!(max, 1.0);
+    assert!(loud.iter().all(|beat| (0.0..=1.0).contains(&beat.strength)));
+    // The raw strengths differ by orders of magnitude.
+    assert!(loud_raw[0].strength > 10.0 * quiet_raw[0].strength);
+
+    for (loud, quiet) in loud.iter().zip(&quiet) {
+        assert_eq!(loud.position, quiet.position);
+        assert!((loud.strength - quiet.strength).abs() < 1e-3);
+    }
+    assert_eq!(loud_threshold, quiet_threshold);
+
+    let selected = |beats: &[Beat], threshold: f32| -> Vec<f32> {
+        beats
+            .iter()
+            .filter(|beat| beat.strength >= threshold)
+            .map(|beat| beat.position)
+            .collect()
+    };
+    let positions = selected(&loud, loud_threshold);
+    assert_eq!(positions, selected(&quiet, quiet_threshold));
+    // Only the clicks, half a second apart, are above the threshold.
+    assert!(positions.len() > 30);
+    assert!(positions
+        .windows(2)
+        .all(|pair| (pair[1] - pair[0] - 0.5).abs() < 0.05));
+}
+
+#[test]
+fn threshold_without_beats() {
+    let mut bpm = BPMDetect::new(1, 44100);
+    assert!(bpm.beats().is_empty());
+    assert_eq!(bpm.suggested_threshold(), 0.0);
+}
+
+#[test]
+fn midi_clock() {
+    let grid = BeatGrid::new(100.0, 0.1);
+    let ticks: Vec<u64> = grid.to_midi_clock(44100).take(24 * 100 + 1).collect();
+    assert_eq!(ticks[0], 4410);
+    // 24 ticks per beat, without accumulating rounding errors.
+    for beat in 0..=100 {
+        let expected = (grid.beat_position(beat) * 44100.0).round() as u64;
+        assert_eq!(ticks[beat as usize * 24], expected);
+    }
+    assert!(ticks
+        .windows(2)
+        .all(|pair| pair[1] - pair[0] == 1102 || pair[1] - pair[0] == 1103));
+}
+
+#[test]
+fn grid_offset_is_wrapped() {
+    assert_eq!(BeatGrid::new(120.0, 1.25).offset, 0.25);
+    assert_eq!(BeatGrid::new(120.0, -0.125).offset, 0.375);
+}
+
+#[test]
+fn beats_between() {
+    let grid = BeatGrid::new(100.0, 0.1);
+    let beats: Vec<Duration> = grid
+        .beats_between(Duration::from_millis(100), Duration::from_millis(1900))
+        .collect();
+    // The start is included, the end isn't.
+    assert_eq!(beats, [100, 700, 1300].map(Duration::from_millis).to_vec());
+    let beats: Vec<Duration> = grid
+        .beats_between(Duration::from_millis(101), Duration::from_millis(1901))
+        .collect();
+    assert_eq!(beats, [700, 1300, 1900].map(Duration::from_millis).to_vec());
+    let late = grid.beats_between(Duration::from_secs(3600), Duration::from_secs(3601));
+    assert_eq!(
+        late.collect::<Vec<_>>(),
+        [
+            Duration::from_millis(3_600_100),
+            Duration::from_millis(3_600_700)
+        ]
+    );
+    assert_eq!(
+        grid.beats_between(Duration::from_secs(2), Duration::from_secs(1))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn detected_grid_round_trip() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    // Clicks at 0.5 s intervals, delayed by 0.1 s.
+    let mut input = vec![0.0; 4410 * 2];
+    input.extend(common::click_track(2, 44100, 120.0, 20.0, 0.8));
+    bpm.input_all(&input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    let grid = bpm.beat_grid().unwrap();
+    assert!((grid.bpm - 120.0).abs() < 1.0, "{grid:?}");
+    assert!((grid.offset - 0.1).abs() < 0.03, "{grid:?}");
+
+    let clicks = grid.to_click_track(48000, 48000 * 20);
+    assert_eq!(clicks.len(), 48000 * 20);
+    let first = clicks.iter().position(|&sample| sample != 0.0).unwrap();
+    assert_eq!(first, (grid.offset * 48000.0).round() as usize);
+
+    let mut detect = BPMDetect::new(1, 48000);
+    detect.input_all(&clicks, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    assert!((detect.get_bpm() as f64 - grid.bpm).abs() < 1.0);
+}
+
+#[test]
+fn no_grid_without_beats() {
+    let mut bpm = BPMDetect::new(1, 44100);
+    bpm.input_samples(&[0.0; 44100]);
+    assert_eq!(bpm.beat_grid(), None);
+}
+
+#[test]
+fn envelope() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    assert!(bpm.take_envelope().is_empty());
+    bpm.set_collect_envelope(true);
+    assert_eq!(bpm.envelope_rate(), bpm.effective_analysis_rate());
+
+    // Quiet noise with a loud burst from 3.0 to 3.1 seconds.
+    let mut input: Vec<f32> = signal::noise(1, 44100, 44100 * 10)
+        .stereo()
+        .map(|sample| 0.01 * sample)
+        .collect();
+    input[3 * 44100 * 2..31 * 4410 * 2].fill(0.9);
+    let mut envelope = Vec::new();
+    for chunk in input.chunks(1998) {
+        bpm.input_samples(chunk);
+        envelope.extend(bpm.take_envelope());
+    }
+    assert_eq!(
+        envelope.len(),
+        44100 * 10 / bpm.decimation_factor() as usize
+    );
+
+    let rate = bpm.envelope_rate();
+    let loud: Vec<usize> = (0..envelope.len()).filter(|&i| envelope[i] > 0.5).collect();
+    let start = (3.0 * rate) as usize;
+    let end = (3.1 * rate) as usize;
+    assert!(loud.first().unwrap().abs_diff(start) <= 1);
+    assert!(loud.last().unwrap().abs_diff(end) <= 1);
+    assert_eq!(loud.len(), loud.last().unwrap() - loud.first().unwrap() + 1);
+
+    bpm.set_collect_envelope(false);
+    bpm.input_samples(&input);
+    assert!(bpm.take_envelope().is_empty());
+}
+
+fn detect_with(downmix: Downmix, input: &[f32]) -> f32 {
+    let mut bpm = BPMDetect::builder().downmix(downmix).build().unwrap();
+    bpm.input_all(input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    bpm.get_bpm()
+}
+
+#[test]
+fn downmix() {
+    // Both channels are identical.
+    let input = common::click_track(2, 44100, 120.0, 20.0, 0.8);
+    for downmix in [
+        Downmix::Average,
+        Downmix::LeftOnly,
+        Downmix::RightOnly,
+        Downmix::Mid,
+    ] {
+        let bpm = detect_with(downmix, &input);
+        assert!((bpm - 120.0).abs() < 1.0, "{downmix:?}: {bpm}");
+    }
+    assert_eq!(detect_with(Downmix::Side, &input), 0.0);
+
+    // Clicks only on the right channel.
+    let right: Vec<f32> = input.chunks(2).flat_map(|frame| [0.0, frame[1]]).collect();
+    assert_eq!(detect_with(Downmix::LeftOnly, &right), 0.0);
+    assert!((detect_with(Downmix::RightOnly, &right) - 120.0).abs() < 1.0);
+    assert!((detect_with(Downmix::Side, &right) - 120.0).abs() < 1.0);
+}
+
+#[test]
+fn downmix_keeps_input_format() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    assert_eq!(bpm.downmix(), Downmix::Average);
+    bpm.set_downmix(Downmix::Mid).set_collect_envelope(true);
+    assert_eq!(bpm.downmix(), Downmix::Mid);
+    assert_eq!(bpm.decimation_factor(), 44);
+    bpm.input_all(&vec![0.5; 44 * 2 * 10], 7);
+    assert_eq!(bpm.take_envelope(), vec![0.5; 10]);
+}
+
+#[test]
+fn decimate_checks_lengths() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    let src = vec![0.25; 100 * 2];
+    let mut dest = [0.0; 2];
+
+    assert_eq!(
+        bpm.try_decimate(&mut dest, &src[..99 * 2], 100),
+        Err(SoundTouchError::InputTooShort {
+            frames: 100,
+            channels: 2,
+            len: 99 * 2
+        })
+    );
+    assert_eq!(
+        bpm.try_decimate(&mut dest[..1], &src, 100),
+        Err(SoundTouchError::OutputTooSmall {
+            frames: 2,
+            channels: 1,
+            len: 1
+        })
+    );
+    assert_eq!(bpm.try_decimate(&mut dest, &src, 100), Ok(2));
+    // The 12 frames left over count towards the next output.
+    assert_eq!(
+        bpm.try_decimate(&mut [], &src, 32),
+        Err(SoundTouchError::OutputTooSmall {
+            frames: 1,
+            channels: 1,
+            len: 0
+        })
+    );
+}
+
+#[test]
+fn update_steps_check_the_buffer() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    assert_eq!(
+        bpm.try_update_x_corr(1),
+        Err(SoundTouchError::TooManySamples {
+            requested: 1,
+            max: 0
+        })
+    );
+    assert_eq!(
+        bpm.try_update_beat_pos(BPMDetect::MAX_BEAT_POS_SAMPLES as i32),
+        Err(SoundTouchError::TooManySamples {
+            requested: BPMDetect::MAX_BEAT_POS_SAMPLES,
+            max: 0
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "at most 0 can be processed")]
+fn update_x_corr_panics_on_an_empty_buffer() {
+    BPMDetect::new(1, 44100).update_x_corr(BPMDetect::MAX_XCORR_SAMPLES as i32);
+}
+
+#[test]
+fn low_sample_rates_detect_the_tempo() {
+    for sample_rate in [4000, 8000, 11025, 16000] {
+        let input = common::click_track(1, sample_rate as usize, 120.0, 20.0, 0.8);
+        let mut bpm = BPMDetect::new(1, sample_rate);
+        assert_eq!(
+            bpm.is_upsampling(),
+            sample_rate < BPMDetect::MIN_SAMPLE_RATE
+        );
+        bpm.input_all(&input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+        let detected = bpm.get_bpm();
+        assert!(
+            (detected - 120.0).abs() < 1.0,
+            "{sample_rate} Hz: {detected}"
+        );
+    }
+}
+
+#[test]
+fn decimation_stays_near_the_target_rate() {
+    assert_eq!(BPMDetect::decimation_factor_for(8000), 8);
+    assert_eq!(BPMDetect::decimation_factor_for(15999), 15);
+    for sample_rate in [2000, 4000, 7999, 8000, 11025, 15999, 22050, 192000] {
+        let bpm = BPMDetect::new(1, sample_rate);
+        assert!(bpm.decimation_factor() >= BPMDetect::MIN_DECIMATION_FACTOR);
+        let drift = bpm.effective_analysis_rate() / 1000.0 - 1.0;
+        assert!(
+            drift.abs() <= BPMDetect::MAX_ANALYSIS_RATE_DRIFT,
+            "{sample_rate} Hz: {drift}"
+        );
+    }
+    // Upsampled input is analyzed at the lowest native rate.
+    let bpm = BPMDetect::new(2, 6000);
+    assert_eq!(bpm.decimation_factor(), 8);
+    assert_eq!(bpm.effective_analysis_rate(), 1000.0);
+}
+
+fn result_of(input: &[f32]) -> BpmResult {
+    let mut bpm = BPMDetect::new(1, 44100);
+    bpm.input_all(input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    bpm.result()
+}
+
+#[test]
+fn click_track_is_confident() {
+    let result = result_of(&common::click_track(1, 44100, 120.0, 20.0, 0.8));
+    let detected = result.bpm.expect("a tempo");
+    assert!((detected - 120.0).abs() < 1.0, "{detected}");
+    assert!(result.confidence > 0.8, "{}", result.confidence);
+    assert!(result.competing.len() <= BpmResult::MAX_COMPETING);
+    for &(tempo, strength) in &result.competing {
+        assert!((tempo - 120.0).abs() > 1.0, "{tempo}");
+        assert!((0.0..=1.0).contains(&strength), "{strength}");
+    }
+    assert!(result
+        .competing
+        .windows(2)
+        .all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn white_noise_has_no_tempo() {
+    let mut seed: u32 = 0x9e37_79b9;
+    let noise: Vec<f32> = (0..44100 * 20)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32 - 0.5
+        })
+        .collect();
+    let result = result_of(&noise);
+    assert_eq!(result.bpm, None);
+    assert!(
+        result.confidence < BpmResult::MIN_CONFIDENCE,
+        "{}",
+        result.confidence
+    );
+}
+
+#[test]
+fn no_result_without_input() {
+    assert_eq!(BPMDetect::new(1, 44100).result(), BpmResult::default());
+}
+
+#[test]
+fn xcorr_covers_the_detected_lags() {
+    let mut bpm = BPMDetect::new(1, 44100);
+    bpm.input_all(
+        &common::click_track(1, 44100, 120.0, 20.0, 0.8),
+        BPMDetect::RECOMMENDED_CHUNK_FRAMES,
+    );
+    let tempo = bpm.get_bpm() as f64;
+    let lags = bpm.xcorr_lags();
+    assert_eq!(lags.end, bpm.xcorr().len());
+    let lag = (60.0 * bpm.effective_analysis_rate() / tempo).round() as usize;
+    assert!(lags.contains(&lag));
+    let xcorr = bpm.xcorr();
+    assert!(xcorr[..lags.start].iter().all(|&value| value == 0.0));
+    let local_peak = xcorr[lag - 20..=lag + 20]
+        .iter()
+        .copied()
+        .fold(0.0, f32::max);
+    assert!(local_peak > 0.0);
+    assert!(xcorr[lag - 2..=lag + 2].contains(&local_peak));
+}
+
+#[test]
+fn beat_positions_stop_growing_past_the_cap() {
+    let input = common::click_track(1, 44100, 120.0, 60.0, 0.8);
+    let mut bpm = BPMDetect::new(1, 44100);
+    bpm.set_max_analysis_duration(Some(Duration::from_secs(20)));
+    assert_eq!(bpm.max_analysis_duration(), Some(Duration::from_secs(20)));
+
+    let (capped, rest) = input.split_at(44100 * 20);
+    bpm.input_all(capped, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    let beats_at_cap = bpm.query_size(0);
+    assert!(beats_at_cap > 0);
+    bpm.input_all(rest, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    assert_eq!(bpm.query_size(0), beats_at_cap);
+    assert_eq!(bpm.analysis_duration(), Duration::from_secs(60));
+    let detected = bpm.get_bpm();
+    assert!((detected - 120.0).abs() < 1.0, "{detected}");
+
+    let mut uncapped = BPMDetect::new(1, 44100);
+    uncapped.input_all(&input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    assert!(uncapped.query_size(0) > 2 * beats_at_cap);
+    assert!((uncapped.get_bpm() - detected).abs() < 0.5);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use soundtouch::testing::Rng;
+use soundtouch::{BPMDetect, Beat, Downmix, PrefilterConfig};
+
+/// Splits `samples` into chunks of random lengths, many of them not whole frames.
+fn random_chunks<'a>(rng: &mut Rng, samples: &'a [f32]) -> Vec<&'a [f32]> {
+    let mut chunks = Vec::new();
+    let mut rest = samples;
+    while !rest.is_empty() {
+        let len = if rng.chance(0.25) {
+            rng.range(1, 8)
+        } else {
+            rng.range(1, 70_000)
+        };
+        let (chunk, tail) = rest.split_at(len.min(rest.len()));
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+fn detect<'a>(
+    channels: u32,
+    setup: fn(&mut BPMDetect),
+    chunks: impl IntoIterator<Item = &'a [f32]>,
+) -> (f32, Vec<Beat>) {
+    let mut bpm = BPMDetect::new(channels, 44100);
+    setup(&mut bpm);
+    for chunk in chunks {
+        bpm.input_samples(chunk);
+    }
+    (bpm.get_bpm(), bpm.beats_raw())
+}
+
+#[test]
+fn random_chunkings_match() {
+    let setups: [fn(&mut BPMDetect); 3] = [
+        |_| {},
+        |bpm| {
+            bpm.set_downmix(Downmix::Mid);
+        },
+        |bpm| {
+            bpm.set_prefilter(Some(PrefilterConfig::default()));
+        },
+    ];
+    let mut rng = Rng::new(0xb9b_c4a2);
+    for channels in [1, 2, 3] {
+        let input = common::click_track(channels as usize, 44100, 120.0, 15.0, 0.8);
+        for (index, &setup) in setups.iter().enumerate() {
+            let (expected, beats) = detect(channels, setup, [&input[..]]);
+            assert!((expected - 120.0).abs() < 1.0, "{expected}");
+            for case in 0..4 {
+                let chunks = random_chunks(&mut rng, &input);
+                let (bpm, chunked_beats) = detect(channels, setup, chunks);
+                assert_eq!(
+                    bpm, expected,
+                    "{channels} channels, setup {index}, case {case}"
+                );
+                assert!(
+                    chunked_beats == beats,
+                    "{channels} channels, setup {index}, case {case}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn partial_frames_are_completed() {
+    let input = common::click_track(3, 44100, 120.0, 5.0, 0.8);
+    let mut whole = BPMDetect::new(3, 44100);
+    whole.input_samples(&input);
+
+    // One sample at a time at the start, then the rest split off by a single sample.
+    let mut split = BPMDetect::new(3, 44100);
+    for sample in &input[..10] {
+        split.input_samples(std::slice::from_ref(sample));
+    }
+    split.input_samples(&input[10..input.len() - 1]);
+    assert!(split.analysis_duration() < whole.analysis_duration());
+    split.input_samples(&input[input.len() - 1..]);
+    assert_eq!(split.analysis_duration(), whole.analysis_duration());
+    assert_eq!(split.get_bpm(), whole.get_bpm());
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouch};
+
+const BLOCK: usize = 512;
+
+fn stereo(mono: &[f32]) -> Vec<f32> {
+    mono.iter().flat_map(|&s| [s, s]).collect()
+}
+
+/// The largest step between consecutive samples of the left channel.
+fn max_step(samples: &[f32]) -> f32 {
+    samples
+        .iter()
+        .step_by(2)
+        .zip(samples.iter().step_by(2).skip(1))
+        .map(|(a, b)| (b - a).abs())
+        .fold(0.0, f32::max)
+}
+
+/// Puts `input` block by block, calling `before_block` with the block index first, and
+/// receives the output after every block.
+fn stream(
+    soundtouch: &mut SoundTouch,
+    input: &[f32],
+    mut before_block: impl FnMut(&mut SoundTouch, usize),
+) -> Vec<f32> {
+    let mut output = Vec::new();
+    for (index, block) in input.chunks(BLOCK * 2).enumerate() {
+        before_block(soundtouch, index);
+        soundtouch.put_samples(block, block.len() / 2);
+        output.extend(common::drain(soundtouch));
+    }
+    output
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut st = stretcher(2, 44100, 1.0);
+    assert!(!st.bypass_when_neutral());
+    st.put_samples(
+        &signal::sine(220.0, 44100, BLOCK).stereo().into_vec(),
+        BLOCK,
+    );
+    assert!(!st.is_bypassed());
+    assert!(st.buffer_requirements().initial_latency_frames > 0);
+}
+
+#[test]
+fn neutral_input_passes_through_bit_exact() {
+    let input = signal::sine(220.0, 44100, 20 * BLOCK).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_bypass_when_neutral(true);
+    let mut output = Vec::new();
+    for block in input.chunks(BLOCK * 2) {
+        st.put_samples(block, BLOCK);
+        assert!(st.is_bypassed());
+        assert_eq!(st.available_frames(), BLOCK);
+        output.extend(common::drain(&mut st));
+    }
+    assert_eq!(output, input);
+    st.flush();
+    assert!(common::drain(&mut st).is_empty());
+}
+
+#[test]
+fn latency_is_reported_as_zero_while_bypassed() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_bypass_when_neutral(true);
+    assert_eq!(st.buffer_requirements().initial_latency_frames, 0);
+    st.set_tempo(1.2);
+    assert!(st.buffer_requirements().initial_latency_frames > 0);
+    st.set_tempo(1.0).set_pitch(1.5);
+    assert!(st.buffer_requirements().initial_latency_frames > 0);
+    st.set_pitch(1.0);
+    assert_eq!(st.buffer_requirements().initial_latency_frames, 0);
+    st.set_bypass_when_neutral(false);
+    assert!(st.buffer_requirements().initial_latency_frames > 0);
+}
+
+#[test]
+fn tempo_changes_are_click_free() {
+    let tone = stereo(&common::tone(220.0, 44100, 200 * BLOCK));
+    let baseline = {
+        let mut st = stretcher(2, 44100, 1.0);
+        st.set_bypass_when_neutral(true);
+        st.set_tempo(1.2);
+        max_step(&stream(&mut st, &tone, |_, _| {}))
+    };
+    assert!(baseline > 0.0);
+
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_bypass_when_neutral(true);
+    let output = stream(&mut st, &tone, |st, index| match index {
+        50 => {
+            st.set_tempo(1.2);
+        }
+        120 => {
+            st.set_tempo(1.0);
+        }
+        _ => {}
+    });
+    assert!(st.is_bypassed());
+    assert_eq!(output[..100 * BLOCK], tone[..100 * BLOCK]);
+    let step = max_step(&output);
+    assert!(
+        step <= 2.0 * baseline,
+        "step of {step} around the changes, at most {baseline} at a steady tempo"
+    );
+}
+
+#[test]
+fn lengths_stay_consistent_across_transitions() {
+    let input = signal::sine(220.0, 44100, 200 * BLOCK).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_bypass_when_neutral(true);
+    let mut output = stream(&mut st, &input, |st, index| {
+        st.set_tempo(if (40..100).contains(&index) {
+            1.25
+        } else {
+            1.0
+        });
+    });
+    st.flush();
+    output.extend(common::drain(&mut st));
+
+    let expected = 140 * BLOCK + 60 * BLOCK * 4 / 5;
+    let frames = output.len() / 2;
+    assert!(
+        frames.abs_diff(expected) < 500,
+        "{frames} frames, expected about {expected}"
+    );
+}
+
+#[test]
+fn reset_disables_the_bypass() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_bypass_when_neutral(true);
+    st.reset();
+    assert!(!st.bypass_when_neutral());
+}
@@ -0,0 +1,112 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use soundtouch::{Config, Preset, Setting, SoundTouchError, TuningParams};
+
+fn hash(config: &Config) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn setter_order_doesnt_matter() {
+    let a = Config::new()
+        .set_channels(1)
+        .set_tempo(1.25)
+        .set_pitch(0.8)
+        .set_setting(Setting::UseQuickseek, 1)
+        .clone();
+    let b = Config::new()
+        .set_setting(Setting::UseQuickseek, 1)
+        .set_pitch(0.5)
+        .set_pitch(0.8)
+        .set_tempo(1.25)
+        .set_channels(1)
+        .clone();
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+    assert_eq!(a.cache_key(), b.cache_key());
+
+    let keys: HashSet<u64> = [
+        Config::new(),
+        a.clone(),
+        a.clone().set_tempo(1.2500000000000002).clone(),
+        a.clone().set_channels(2).clone(),
+        a.clone().set_setting(Setting::OverlapMs, 9).clone(),
+    ]
+    .iter()
+    .map(Config::cache_key)
+    .collect();
+    assert_eq!(keys.len(), 5);
+}
+
+#[test]
+fn zeros_and_subnormals_are_normalized() {
+    let zero = Config::new().set_rate(0.0).clone();
+    for value in [-0.0, f64::MIN_POSITIVE / 2.0, -f64::MIN_POSITIVE / 4.0] {
+        let config = Config::new().set_rate(value).clone();
+        assert_eq!(config.rate().to_bits(), 0.0f64.to_bits(), "{value:e}");
+        assert_eq!(config, zero);
+        assert_eq!(hash(&config), hash(&zero));
+        assert_eq!(config.cache_key(), zero.cache_key());
+    }
+    // The smallest normal value is kept.
+    let normal = Config::new().set_rate(f64::MIN_POSITIVE).clone();
+    assert_eq!(normal.rate(), f64::MIN_POSITIVE);
+    assert_ne!(normal.cache_key(), zero.cache_key());
+}
+
+#[test]
+fn non_finite_factors_are_rejected() {
+    let mut config = Config::new();
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert!(matches!(
+            config.try_set_tempo(value),
+            Err(SoundTouchError::ParametersOutOfRange { .. })
+        ));
+        assert!(config.try_set_pitch(value).is_err());
+        assert!(config.try_set_rate(value).is_err());
+    }
+    assert_eq!(config, Config::new());
+    config.try_set_tempo(1.5).unwrap();
+    assert_eq!(config.tempo(), 1.5);
+
+    assert!(Config::from_kv_pairs(&[("tempo", "NaN")]).is_err());
+    assert!(Config::from_kv_pairs(&[("rate", "inf")]).is_err());
+}
+
+#[test]
+fn cache_key_is_stable() {
+    // Recorded from this version; a change means cached audio is keyed anew.
+    assert_eq!(Config::new().cache_key(), 0x6400_e58c_ed87_f6ef);
+    assert_eq!(TuningParams::default().cache_key(), 0x1e03_0da1_100b_684a);
+    assert_eq!(Preset::Speech.cache_key(), 0xfbe9_42d7_978b_869b);
+    let automatic = TuningParams::default();
+    let fixed = TuningParams {
+        sequence_ms: Some(0),
+        ..automatic
+    };
+    assert_ne!(automatic.cache_key(), fixed.cache_key());
+    assert_ne!(
+        automatic.cache_key(),
+        TuningParams {
+            quickseek: !automatic.quickseek,
+            ..automatic
+        }
+        .cache_key()
+    );
+}
+
+#[test]
+fn preset_keys_are_those_of_their_settings() {
+    for preset in Preset::ALL {
+        assert_eq!(preset.cache_key(), preset.tuning().cache_key(), "{preset}");
+    }
+    assert_eq!(
+        Preset::Default.cache_key(),
+        TuningParams::default().cache_key()
+    );
+    assert_ne!(Preset::Speech.cache_key(), Preset::Music.cache_key());
+}
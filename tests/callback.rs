@@ -0,0 +1,49 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use common::stretcher;
+use soundtouch::signal;
+
+#[test]
+fn callback_output_matches_generate_audio() {
+    for channels in [1, 2, 6] {
+        let input = signal::sine(220.0, 44100, 40_000)
+            .channels(channels as u32)
+            .into_vec();
+        let mut reference = stretcher(channels as u32, 44100, 0.8);
+        reference.set_pitch_semitones(2);
+        let expected = reference.generate_audio(&input);
+
+        let mut soundtouch = stretcher(channels as u32, 44100, 0.8);
+        soundtouch.set_pitch_semitones(2);
+        let mut output = Vec::new();
+        for chunk in input.chunks(1000 * channels) {
+            soundtouch.process_with_callback(chunk, |batch| {
+                assert_eq!(batch.len() % channels, 0);
+                assert!(batch.len() <= 4096);
+                output.extend_from_slice(batch);
+            });
+        }
+        soundtouch.flush_with_callback(|batch| output.extend_from_slice(batch));
+        assert_eq!(output, expected, "{channels} channels");
+    }
+}
+
+#[test]
+fn usable_after_callback_panic() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut soundtouch = stretcher(2, 44100, 0.8);
+    soundtouch.set_pitch_semitones(2);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        soundtouch.process_with_callback(&input, |_| panic!("callback failed"));
+    }));
+    assert!(result.is_err());
+
+    soundtouch.clear();
+    let mut output = Vec::new();
+    soundtouch.process_with_callback(&input, |batch| output.extend_from_slice(batch));
+    soundtouch.flush_with_callback(|batch| output.extend_from_slice(batch));
+    assert!(!output.is_empty());
+    assert_eq!(soundtouch.num_samples(), 0);
+}
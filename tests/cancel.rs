@@ -0,0 +1,123 @@
+mod common;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::stretcher;
+use soundtouch::{
+    process_streaming, process_streaming_cancellable, signal, BPMDetect, CancellationToken,
+    SoundTouchError,
+};
+
+#[test]
+fn generate_audio_returns_promptly() {
+    // Three minutes, far more than is processed before the token is cancelled.
+    let input = vec![0.1; 3 * 60 * 44100 * 2];
+    let token = CancellationToken::new();
+    let mut soundtouch = stretcher(2, 44100, 1.3);
+
+    let (result, returned, cancelled) = thread::scope(|scope| {
+        let canceller = scope.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            token.clone().cancel();
+            Instant::now()
+        });
+        let result = soundtouch.generate_audio_cancellable(&input, &token);
+        (result, Instant::now(), canceller.join().unwrap())
+    });
+    assert_eq!(result, Err(SoundTouchError::Cancelled));
+    assert!(
+        returned.saturating_duration_since(cancelled) < Duration::from_millis(500),
+        "{:?}",
+        returned - cancelled
+    );
+
+    // The instance is cleared and works like a new one.
+    assert_eq!(soundtouch.available_frames(), 0);
+    assert_eq!(soundtouch.pending_input_frames(), 0);
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    assert_eq!(
+        soundtouch.generate_audio(&input),
+        self::soundtouch().generate_audio(&input)
+    );
+}
+
+#[test]
+fn uncancelled_token_changes_nothing() {
+    let input = signal::sine(220.0, 44100, 50_000).stereo().into_vec();
+    let token = CancellationToken::new();
+    assert_eq!(
+        stretcher(2, 44100, 1.3).generate_audio_cancellable(&input, &token),
+        Ok(stretcher(2, 44100, 1.3).generate_audio(&input))
+    );
+    assert!(!token.is_cancelled());
+}
+
+#[test]
+fn streaming_stops_at_the_next_chunk() {
+    let input = signal::sine(220.0, 44100, 100_000).stereo().into_vec();
+    let token = CancellationToken::new();
+    let mut soundtouch = stretcher(2, 44100, 1.3);
+    let mut reads = 0;
+    let result = process_streaming_cancellable(
+        &mut soundtouch,
+        |buffer| {
+            reads += 1;
+            if reads == 3 {
+                thread::scope(|scope| scope.spawn(|| token.cancel()).join().unwrap());
+            }
+            let frames = buffer.len() / 2;
+            buffer.copy_from_slice(&input[..frames * 2]);
+            frames
+        },
+        |_| {},
+        1024,
+        &token,
+    );
+    assert_eq!(result, Err(SoundTouchError::Cancelled));
+    assert_eq!(reads, 3);
+    assert_eq!(soundtouch.available_frames(), 0);
+    assert_eq!(soundtouch.pending_input_frames(), 0);
+
+    let mut remaining = &input[..];
+    let mut output = Vec::new();
+    process_streaming(
+        &mut soundtouch,
+        |buffer| {
+            let len = remaining.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&remaining[..len]);
+            remaining = &remaining[len..];
+            len / 2
+        },
+        |samples| output.extend_from_slice(samples),
+        1024,
+    );
+    assert_eq!(output, self::soundtouch().generate_audio(&input));
+}
+
+#[test]
+fn bpm_detection_restarts() {
+    let clicks = common::click_track(2, 44100, 120.0, 20.0, 0.8);
+    let mut bpm = BPMDetect::new(2, 44100);
+    // Noise that would skew the result if it weren't forgotten.
+    bpm.input_all(
+        &signal::sine(220.0, 44100, 5 * 44100).stereo().into_vec(),
+        4096,
+    );
+
+    let token = CancellationToken::new();
+    thread::scope(|scope| scope.spawn(|| token.cancel()).join().unwrap());
+    assert_eq!(
+        bpm.input_all_cancellable(&clicks, 4096, &token),
+        Err(SoundTouchError::Cancelled)
+    );
+    assert!(bpm.beats().is_empty());
+    assert_eq!(bpm.analysis_duration(), Duration::ZERO);
+
+    bpm.input_all_cancellable(&clicks, 4096, &CancellationToken::new())
+        .unwrap();
+    let mut fresh = BPMDetect::new(2, 44100);
+    fresh.input_all(&clicks, 4096);
+    assert_eq!(bpm.get_bpm(), fresh.get_bpm());
+    assert!((bpm.get_bpm() - 120.0).abs() < 1.0, "{}", bpm.get_bpm());
+}
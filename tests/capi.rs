@@ -0,0 +1,131 @@
+//! Calls the C interface the way a foreign caller would, through raw pointers only.
+
+use std::ffi::CStr;
+use std::ptr;
+
+use soundtouch::capi::{
+    st_flush, st_free, st_last_error_message, st_new, st_put_samples, st_receive_samples,
+    st_set_tempo, StStatus,
+};
+use soundtouch::signal;
+
+fn last_error() -> String {
+    // SAFETY: The message stays valid until the next failing call on this thread.
+    unsafe { CStr::from_ptr(st_last_error_message()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn round_trip() {
+    let handle = st_new(2, 44100);
+    assert!(!handle.is_null());
+    let input = signal::sine(220.0, 44100, 44100).stereo().into_vec();
+    let mut output = vec![0.0; 2 * 1024];
+    let mut frames_out = 0;
+    // SAFETY: The handle is live, the buffers have the lengths passed, and it is freed
+    // last.
+    unsafe {
+        assert_eq!(st_set_tempo(handle, 2.0), StStatus::Ok);
+        assert_eq!(
+            st_put_samples(handle, input.as_ptr(), input.len() / 2),
+            StStatus::Ok
+        );
+        assert_eq!(st_flush(handle), StStatus::Ok);
+        loop {
+            let mut received = 0;
+            let status = st_receive_samples(handle, output.as_mut_ptr(), 1024, &mut received);
+            assert_eq!(status, StStatus::Ok);
+            if received == 0 {
+                break;
+            }
+            frames_out += received;
+        }
+        st_free(handle);
+    }
+    assert!(
+        (frames_out as i64 - 22050).abs() < 2048,
+        "{frames_out} frames"
+    );
+}
+
+#[test]
+fn null_pointers_are_reported() {
+    let mut received = 0;
+    let mut output = [0.0; 2];
+    // SAFETY: Null handles are allowed, and so is freeing one.
+    unsafe {
+        assert_eq!(st_set_tempo(ptr::null_mut(), 1.5), StStatus::NullPointer);
+        assert!(!last_error().is_empty());
+        assert_eq!(
+            st_receive_samples(ptr::null_mut(), output.as_mut_ptr(), 1, &mut received),
+            StStatus::NullPointer
+        );
+        st_free(ptr::null_mut());
+    }
+
+    let handle = st_new(2, 44100);
+    // SAFETY: The handle is live and freed last, and null buffers are allowed.
+    unsafe {
+        assert_eq!(st_put_samples(handle, ptr::null(), 0), StStatus::Ok);
+        assert_eq!(
+            st_put_samples(handle, ptr::null(), 16),
+            StStatus::NullPointer
+        );
+        assert_eq!(
+            st_receive_samples(handle, output.as_mut_ptr(), 1, ptr::null_mut()),
+            StStatus::NullPointer
+        );
+        received = 1;
+        assert_eq!(
+            st_receive_samples(handle, ptr::null_mut(), 0, &mut received),
+            StStatus::Ok
+        );
+        assert_eq!(received, 0);
+        assert_eq!(
+            st_receive_samples(handle, ptr::null_mut(), 1, &mut received),
+            StStatus::NullPointer
+        );
+        st_free(handle);
+    }
+}
+
+#[test]
+fn invalid_configuration_returns_null() {
+    assert!(st_new(0, 44100).is_null());
+    assert!(last_error().contains('0'), "{}", last_error());
+    assert!(st_new(2, 0).is_null());
+    assert!(!last_error().is_empty());
+}
+
+#[test]
+fn errors_map_to_their_status() {
+    let handle = st_new(1, 44100);
+    let input = signal::sine(220.0, 44100, 44100).into_vec();
+    let mut output = [0.0; 1];
+    let mut received = 0;
+    // SAFETY: The handle is live, the buffers have the lengths passed, and it is freed
+    // last.
+    unsafe {
+        assert_eq!(st_set_tempo(handle, 1000.0), StStatus::ParametersOutOfRange);
+        assert!(last_error().contains("1000"), "{}", last_error());
+        assert_eq!(st_set_tempo(handle, 1.0), StStatus::Ok);
+
+        assert_eq!(
+            st_put_samples(handle, input.as_ptr(), input.len()),
+            StStatus::Ok
+        );
+        assert_eq!(st_flush(handle), StStatus::Ok);
+        assert_eq!(
+            st_receive_samples(handle, output.as_mut_ptr(), 1, &mut received),
+            StStatus::Ok
+        );
+        assert_eq!(received, 1);
+        assert_eq!(
+            st_put_samples(handle, input.as_ptr(), input.len()),
+            StStatus::AlreadyFlushed
+        );
+        assert!(!last_error().is_empty());
+        st_free(handle);
+    }
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{drain, process, stretcher};
+use soundtouch::{signal, ChannelChangePolicy, SoundTouch, SoundTouchError};
+
+#[test]
+fn flush_then_change_stereo_to_mono() {
+    let old = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let new = signal::sine(220.0, 44100, 15_000).into_vec();
+    let expected_old = process(&mut stretcher(2, 44100, 1.25), &old);
+    let expected_new = process(&mut stretcher(1, 44100, 1.25), &new);
+
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(&old, 20_000);
+    st.change_channels(1, ChannelChangePolicy::FlushThenChange)
+        .unwrap();
+    // The flushed output is still received as stereo.
+    assert_eq!(st.num_channels(), 2);
+    assert_eq!(drain(&mut st), expected_old);
+
+    st.put_samples(&new, 15_000);
+    assert_eq!(st.num_channels(), 1);
+    st.flush();
+    assert_eq!(drain(&mut st), expected_new);
+}
+
+#[test]
+fn flush_then_change_requires_receiving_old_audio() {
+    let mut st = stretcher(1, 44100, 1.25);
+    st.put_samples(&signal::sine(220.0, 44100, 20_000).into_vec(), 20_000);
+    st.change_channels(2, ChannelChangePolicy::FlushThenChange)
+        .unwrap();
+    assert!(matches!(
+        st.try_put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100),
+        Err(SoundTouchError::AlreadyFlushed { .. })
+    ));
+
+    // Clearing drops the old audio and switches right away.
+    st.clear();
+    assert_eq!(st.num_channels(), 2);
+    assert_eq!(st.available_frames(), 0);
+}
+
+#[test]
+fn drop_pending_mono_to_stereo() {
+    let new = signal::sine(220.0, 44100, 15_000).stereo().into_vec();
+    let expected_new = process(&mut stretcher(2, 44100, 1.25), &new);
+
+    let mut st = stretcher(1, 44100, 1.25);
+    st.put_samples(&signal::sine(220.0, 44100, 20_000).into_vec(), 20_000);
+    st.change_channels(2, ChannelChangePolicy::DropPending)
+        .unwrap();
+    assert_eq!(st.num_channels(), 2);
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.pending_input_frames(), 0);
+    assert_eq!(process(&mut st, &new), expected_new);
+}
+
+#[test]
+fn downmix_pending_stereo_to_mono() {
+    let old = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let new = signal::sine(220.0, 44100, 15_000).into_vec();
+    let mut expected: Vec<f32> = process(&mut stretcher(2, 44100, 1.25), &old)
+        .chunks(2)
+        .map(|frame| (frame[0] + frame[1]) / 2.0)
+        .collect();
+    let converted_frames = expected.len();
+    expected.extend(process(&mut stretcher(1, 44100, 1.25), &new));
+
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(&old, 20_000);
+    st.change_channels(1, ChannelChangePolicy::UpmixDownmixPending)
+        .unwrap();
+    assert_eq!(st.num_channels(), 1);
+    assert_eq!(st.available_frames(), converted_frames);
+    assert_eq!(process(&mut st, &new), expected);
+}
+
+#[test]
+fn upmix_pending_mono_to_stereo() {
+    let old = signal::sine(220.0, 44100, 20_000).into_vec();
+    let new = signal::sine(220.0, 44100, 15_000).stereo().into_vec();
+    let mut expected: Vec<f32> = process(&mut stretcher(1, 44100, 1.25), &old)
+        .iter()
+        .flat_map(|&sample| [sample, sample])
+        .collect();
+    expected.extend(process(&mut stretcher(2, 44100, 1.25), &new));
+
+    let mut st = stretcher(1, 44100, 1.25);
+    st.put_samples(&old, 20_000);
+    st.change_channels(2, ChannelChangePolicy::UpmixDownmixPending)
+        .unwrap();
+    assert_eq!(st.num_channels(), 2);
+
+    // The converted output can be received in pieces, skipped and mixed with new input.
+    let mut output = vec![0.0; 2 * 1000];
+    assert_eq!(st.receive_samples(&mut output, 1000), 1000);
+    assert_eq!(st.skip_samples(500), 500);
+    output.extend(process(&mut st, &new));
+    expected.drain(2000..3000);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn invalid_channels_change_nothing() {
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    let pending = st.pending_input_frames();
+    assert_eq!(
+        st.change_channels(0, ChannelChangePolicy::DropPending)
+            .err(),
+        Some(SoundTouchError::InvalidChannels(0))
+    );
+    let too_many = SoundTouch::MAX_CHANNELS + 1;
+    assert_eq!(
+        st.change_channels(too_many, ChannelChangePolicy::FlushThenChange)
+            .err(),
+        Some(SoundTouchError::InvalidChannels(too_many))
+    );
+    assert_eq!(st.num_channels(), 2);
+    assert_eq!(st.pending_input_frames(), pending);
+}
+
+#[test]
+fn unconfigured() {
+    let mut st = SoundTouch::new();
+    st.change_channels(2, ChannelChangePolicy::UpmixDownmixPending)
+        .unwrap();
+    assert_eq!(st.num_channels(), 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "change_channels")]
+fn set_channels_with_pending_input() {
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(&signal::sine(220.0, 44100, 1000).stereo().into_vec(), 1000);
+    st.set_channels(1);
+}
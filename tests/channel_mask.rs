@@ -0,0 +1,103 @@
+mod common;
+
+use std::f32::consts::TAU;
+
+use soundtouch::{signal, ChannelMask, SoundTouch};
+
+const SAMPLE_RATE: usize = 44100;
+const LFE: usize = 3;
+
+/// Five seconds of 5.1: the test signal in every channel but the LFE, which gets `lfe`.
+fn surround(lfe: impl Fn(usize, f32) -> f32) -> Vec<f32> {
+    signal::sine(220.0, SAMPLE_RATE as u32, 5 * SAMPLE_RATE)
+        .enumerate()
+        .flat_map(|(i, sample)| {
+            let mut frame = [sample; 6];
+            frame[LFE] = lfe(i, sample);
+            frame
+        })
+        .collect()
+}
+
+fn stretch(input: &[f32], mask: u32) -> Vec<f32> {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(6)
+        .set_sample_rate(SAMPLE_RATE as u32)
+        .set_tempo(1.2)
+        .set_correlation_channel_mask(mask);
+    soundtouch.generate_audio(input)
+}
+
+#[test]
+fn speakers_map_to_channels() {
+    let side = ChannelMask::FRONT_LEFT
+        | ChannelMask::FRONT_RIGHT
+        | ChannelMask::FRONT_CENTER
+        | ChannelMask::LOW_FREQUENCY
+        | ChannelMask::SIDE_LEFT
+        | ChannelMask::SIDE_RIGHT;
+    assert_eq!(side.channels(), 6);
+    assert_eq!(side.index_of(ChannelMask::SIDE_LEFT), Some(4));
+    assert_eq!(side.index_of(ChannelMask::BACK_LEFT), None);
+    assert_eq!(side.index_of(ChannelMask::STEREO), None);
+    assert_eq!(
+        side.channel_bits(ChannelMask::LOW_FREQUENCY | ChannelMask::SIDE_RIGHT),
+        0b10_1000
+    );
+    assert_eq!(side.channel_bits(ChannelMask::BACK_CENTER), 0);
+    assert_eq!(ChannelMask::default_for(6), ChannelMask::SURROUND_5_1);
+    assert!(ChannelMask::SURROUND_7_1.contains(ChannelMask::SURROUND_5_1));
+}
+
+#[test]
+fn masked_channel_is_processed_like_the_others() {
+    // The LFE carries the same signal as the other channels, so with the same splices
+    // it comes out the same, at the same level and in the same frames.
+    let input = surround(|_, sample| sample);
+    let output = stretch(&input, 1 << LFE);
+    assert_eq!(output.len(), stretch(&input, 0).len());
+    for frame in output.chunks_exact(6) {
+        assert!((frame[LFE] - frame[0]).abs() <= 1e-6, "{frame:?}");
+    }
+}
+
+#[test]
+fn mask_changes_the_splices() {
+    // A loud 40 Hz tone that dominates the correlation unless it is masked.
+    let input = surround(|i, _| 0.9 * (TAU * 40.0 * i as f32 / SAMPLE_RATE as f32).sin());
+    let plain = stretch(&input, 0);
+    let masked = stretch(&input, 1 << LFE);
+    assert_ne!(plain, masked);
+    let expected = plain.len() as f64;
+    assert!(
+        (masked.len() as f64 / expected - 1.0).abs() < 0.01,
+        "{} vs {expected}",
+        masked.len()
+    );
+}
+
+#[test]
+fn new_mask_waits_for_the_next_stream() {
+    let input = surround(|_, sample| 0.5 * sample);
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(6)
+        .set_sample_rate(SAMPLE_RATE as u32)
+        .set_tempo(1.2);
+    let half = input.len() / 12 * 6;
+    soundtouch.put_samples(&input[..half], half / 6);
+    soundtouch.set_correlation_channel_mask(1 << LFE);
+    assert_eq!(soundtouch.correlation_channel_mask(), 1 << LFE);
+    soundtouch.put_samples(&input[half..], (input.len() - half) / 6);
+    soundtouch.flush();
+    assert_eq!(common::drain(&mut soundtouch), stretch(&input, 0));
+
+    soundtouch.clear();
+    assert_eq!(
+        common::process(&mut soundtouch, &input),
+        stretch(&input, 1 << LFE)
+    );
+    soundtouch.reset();
+    assert_eq!(soundtouch.correlation_channel_mask(), 0);
+}
@@ -0,0 +1,76 @@
+use soundtouch::{ffi_chunks, signal, BPMDetect, SoundTouch};
+
+const MAX_SAMPLES_PER_CALL: usize = 1 << 20;
+
+#[test]
+fn counts_beyond_u32_are_split() {
+    for channels in [1, 2, 6, 16] {
+        let max = MAX_SAMPLES_PER_CALL / channels as usize;
+        for frames in [
+            0,
+            1,
+            max - 1,
+            max,
+            max + 1,
+            u32::MAX as usize / channels as usize + 1,
+            u32::MAX as usize + 1,
+            usize::MAX,
+        ] {
+            let mut total = 0usize;
+            let mut calls = 0usize;
+            for chunk in ffi_chunks(frames, channels).take(3) {
+                assert!(chunk > 0 && chunk <= max);
+                assert!(chunk * channels as usize <= u32::MAX as usize);
+                total += chunk;
+                calls += 1;
+            }
+            if frames <= 3 * max {
+                assert_eq!(total, frames, "{frames} frames of {channels} channels");
+            } else {
+                assert_eq!(calls, 3);
+            }
+        }
+    }
+}
+
+#[test]
+fn chunked_processing_matches() {
+    let input = signal::sine(220.0, 44100, MAX_SAMPLES_PER_CALL / 2 + 10_000)
+        .stereo()
+        .into_vec();
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.25);
+    let output = soundtouch.generate_audio(&input);
+    let expected = (input.len() as f64 / 1.25).round() as usize;
+    assert!(output.len().abs_diff(expected) <= 2);
+
+    // Putting all of it at once gives the same output as putting it in small blocks.
+    let mut reference = SoundTouch::new();
+    reference
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.25);
+    let mut blocks = Vec::new();
+    let mut buffer = vec![0.0; 8192];
+    for chunk in input.chunks(8192) {
+        reference.put_samples(chunk, chunk.len() / 2);
+        while let n @ 1.. = reference.receive_samples(&mut buffer, 4096) {
+            blocks.extend_from_slice(&buffer[..n * 2]);
+        }
+    }
+    reference.flush();
+    while let n @ 1.. = reference.receive_samples(&mut buffer, 4096) {
+        blocks.extend_from_slice(&buffer[..n * 2]);
+    }
+    assert!(output == blocks);
+}
+
+#[test]
+fn chunked_bpm_input() {
+    let mut bpm = BPMDetect::new(1, 8000);
+    bpm.input_samples(&signal::sine(220.0, 44100, MAX_SAMPLES_PER_CALL + 1000).into_vec());
+    assert!(bpm.get_bpm() >= 0.0);
+}
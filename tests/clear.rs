@@ -0,0 +1,76 @@
+mod common;
+
+use common::{drain, process, stretcher};
+use soundtouch::{signal, ChannelChangePolicy};
+
+#[test]
+fn clear_output_only_keeps_queued_input() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let reference = process(&mut stretcher(2, 44100, 1.3), &input);
+
+    let mut st = stretcher(2, 44100, 1.3);
+    st.put_samples(&input, 30_000);
+    let stale = st.available_frames();
+    let pending = st.pending_input_frames();
+    assert!(stale > 0 && pending > 0);
+
+    st.clear_output_only();
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.pending_input_frames(), pending);
+
+    // The queued input still comes out, exactly as without the clear.
+    st.flush();
+    let output = drain(&mut st);
+    assert!(!output.is_empty());
+    assert_eq!(output, reference[2 * stale..]);
+}
+
+#[test]
+fn clear_drops_queued_input() {
+    let mut st = stretcher(2, 44100, 1.3);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 30_000).stereo().into_vec(),
+        30_000,
+    );
+    st.clear();
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.pending_input_frames(), 0);
+    st.flush();
+    assert!(drain(&mut st).is_empty());
+}
+
+#[test]
+fn clear_output_only_after_flush() {
+    let mut st = stretcher(2, 44100, 1.3);
+    st.set_output_block_frames(Some(1000));
+    st.put_samples(
+        &signal::sine(220.0, 44100, 30_001).stereo().into_vec(),
+        30_001,
+    );
+    st.flush();
+    assert!(st.output_block_padding() > 0);
+
+    st.clear_output_only();
+    assert_eq!(st.available_frames(), 0);
+    assert!(st.is_flushed());
+    // Nothing is left to receive, so new input starts a new stream.
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut fresh = stretcher(2, 44100, 1.3);
+    fresh.set_output_block_frames(Some(1000));
+    assert_eq!(process(&mut st, &input), process(&mut fresh, &input));
+}
+
+#[test]
+fn clear_output_only_drops_converted_output() {
+    let mut st = stretcher(2, 44100, 1.3);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 30_000).stereo().into_vec(),
+        30_000,
+    );
+    st.change_channels(1, ChannelChangePolicy::UpmixDownmixPending)
+        .unwrap();
+    assert!(st.available_frames() > 0);
+    st.clear_output_only();
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.num_channels(), 1);
+}
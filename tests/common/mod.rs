@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+use soundtouch::{signal, SoundTouch};
+
+/// A `SoundTouch` with `channels` channels at `sample_rate` Hz and the given tempo.
+pub fn stretcher(channels: u32, sample_rate: u32, tempo: f64) -> SoundTouch {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(channels)
+        .set_sample_rate(sample_rate)
+        .set_tempo(tempo);
+    soundtouch
+}
+
+/// Puts all of `samples`, flushes and receives everything that comes out.
+pub fn process(soundtouch: &mut SoundTouch, samples: &[f32]) -> Vec<f32> {
+    let channels = soundtouch.num_channels() as usize;
+    soundtouch.put_samples(samples, samples.len() / channels);
+    soundtouch.flush();
+    drain(soundtouch)
+}
+
+/// Receives all ready samples.
+pub fn drain(soundtouch: &mut SoundTouch) -> Vec<f32> {
+    let channels = soundtouch.num_channels() as usize;
+    let mut output = Vec::new();
+    let mut buffer = [0.0; 4096];
+    let buffer = &mut buffer[..4096 / channels * channels];
+    loop {
+        let n = soundtouch.receive_samples(buffer, buffer.len() / channels);
+        if n == 0 {
+            break output;
+        }
+        output.extend_from_slice(&buffer[..n * channels]);
+    }
+}
+
+/// An interleaved click track: decaying 100 Hz bursts of `amplitude` at `bpm` beats
+/// per minute over quiet noise.
+pub fn click_track(
+    channels: usize,
+    sample_rate: usize,
+    bpm: f64,
+    seconds: f64,
+    amplitude: f32,
+) -> Vec<f32> {
+    let frames = (seconds * sample_rate as f64) as usize;
+    let clicks = signal::click_track(bpm, sample_rate as u32, frames);
+    let noise = signal::noise(1, sample_rate as u32, frames);
+    clicks
+        .zip(noise)
+        // The clicks peak at 0.5.
+        .map(|(click, noise)| amplitude * (2.0 * click + 0.02 * noise))
+        .flat_map(|sample| std::iter::repeat_n(sample, channels))
+        .collect()
+}
+
+/// A mono sine tone of `frequency` Hz.
+pub fn tone(frequency: f64, sample_rate: usize, frames: usize) -> Vec<f32> {
+    signal::sine(frequency, sample_rate as u32, frames).into_vec()
+}
+
+/// Returns the frequency between `low` and `high` Hz, in steps of `step` Hz, with the
+/// most energy in the mono `samples`, using the Goertzel algorithm.
+pub fn peak_frequency(samples: &[f32], sample_rate: usize, low: f64, high: f64, step: f64) -> f64 {
+    let power = |frequency: f64| {
+        let coefficient = 2.0 * (std::f64::consts::TAU * frequency / sample_rate as f64).cos();
+        let (mut s1, mut s2) = (0.0, 0.0);
+        for &sample in samples {
+            let s0 = sample as f64 + coefficient * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coefficient * s1 * s2
+    };
+    let steps = ((high - low) / step) as usize;
+    (0..=steps)
+        .map(|i| low + i as f64 * step)
+        .max_by(|&a, &b| power(a).total_cmp(&power(b)))
+        .unwrap()
+}
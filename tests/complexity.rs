@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use soundtouch::{bench, ComplexityClass, Config, Setting};
+
+#[test]
+fn documented_classes() {
+    assert_eq!(Config::new().complexity_hint(), ComplexityClass::Medium);
+    assert_eq!(
+        Config::new().set_channels(1).complexity_hint(),
+        ComplexityClass::Low
+    );
+    assert_eq!(
+        Config::new()
+            .set_setting(Setting::UseQuickseek, 1)
+            .complexity_hint(),
+        ComplexityClass::Low
+    );
+    assert_eq!(
+        Config::new().set_sample_rate(96000).complexity_hint(),
+        ComplexityClass::High
+    );
+    assert_eq!(
+        bench::representative_config().complexity_hint(),
+        ComplexityClass::Medium
+    );
+}
+
+#[test]
+fn estimate_follows_the_parameters() {
+    let ops = |config: &mut Config| config.estimated_ops_per_second();
+    let base = ops(&mut Config::new());
+    assert!(base > 0.0);
+
+    // Linear in the number of channels, quadratic in the sample rate.
+    let six = ops(Config::new().set_channels(6));
+    assert!((six / base - 3.0).abs() < 1e-9);
+    let double_rate = ops(Config::new().set_sample_rate(88200));
+    assert!((double_rate / base - 4.0).abs() < 0.01);
+
+    assert!(ops(Config::new().set_setting(Setting::UseQuickseek, 1)) < base);
+    assert!(ops(Config::new().set_setting(Setting::SeekwindowMs, 30)) > base);
+    assert!(ops(Config::new().set_rate(0.8)) > base);
+    assert!(
+        ops(Config::new()
+            .set_rate(0.8)
+            .set_setting(Setting::UseAaFilter, 0))
+            < ops(Config::new().set_rate(0.8))
+    );
+    assert!(
+        ops(Config::new()
+            .set_rate(0.8)
+            .set_setting(Setting::AaFilterLength, 128))
+            > ops(Config::new().set_rate(0.8))
+    );
+}
+
+#[test]
+fn selftest() {
+    let report = bench::quick_selftest(Duration::ZERO);
+    assert_eq!(report.config, bench::representative_config());
+    assert!(report.frames >= 4096);
+    assert!(report.frames_per_second > 0.0);
+    assert!(report.realtime_factor() > 0.0);
+    let capacity = report.ops_per_second_capacity();
+    assert!(
+        (capacity / report.config.estimated_ops_per_second() - report.realtime_factor()).abs()
+            < 1e-9
+    );
+}
@@ -0,0 +1,157 @@
+//! The concatenated output must not depend on the sizes of the receive calls.
+
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouch};
+
+/// A small xorshift generator, so that failures are reproducible from the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+fn configurations() -> Vec<(&'static str, SoundTouch)> {
+    let base = |tempo, pitch| {
+        let mut soundtouch = SoundTouch::new();
+        soundtouch
+            .set_channels(2)
+            .set_sample_rate(44100)
+            .set_tempo(tempo)
+            .set_pitch(pitch);
+        soundtouch
+    };
+    let mut formant = base(0.9, 1.0);
+    formant.set_pitch_with_formant_correction(3.0);
+    let mut blocks = base(1.3, 1.0);
+    blocks.set_output_block_frames(Some(256));
+    let mut metered = base(1.1, 0.95);
+    metered.set_metering(true);
+    vec![
+        ("plain", base(1.25, 1.0)),
+        ("pitch", base(1.0, 1.2)),
+        ("formant", formant),
+        ("blocks", blocks),
+        ("metered", metered),
+    ]
+}
+
+/// Puts `input` in chunks of `put_frames`, pulling with `pull` after every put and
+/// until the end after the flush.
+fn run(
+    soundtouch: &mut SoundTouch,
+    input: &[f32],
+    put_frames: usize,
+    mut pull: impl FnMut(&mut SoundTouch, &mut Vec<f32>),
+) -> Vec<f32> {
+    let mut output = Vec::new();
+    for chunk in input.chunks(put_frames * 2) {
+        soundtouch.put_samples(chunk, chunk.len() / 2);
+        pull(soundtouch, &mut output);
+    }
+    soundtouch.flush();
+    loop {
+        let before = output.len();
+        pull(soundtouch, &mut output);
+        if output.len() == before && soundtouch.available_frames() == 0 {
+            return output;
+        }
+    }
+}
+
+/// Receives up to `max_frames` frames, or one output block if that is longer, as
+/// shorter receives get nothing with a block size.
+fn pull_frames(soundtouch: &mut SoundTouch, output: &mut Vec<f32>, max_frames: usize) {
+    let max_frames = max_frames.max(soundtouch.output_block_frames().unwrap_or(0));
+    let mut buffer = vec![0.0; max_frames * 2];
+    let frames = soundtouch.receive_samples(&mut buffer, max_frames);
+    output.extend_from_slice(&buffer[..frames * 2]);
+}
+
+fn bits(samples: &[f32]) -> Vec<u32> {
+    samples.iter().map(|sample| sample.to_bits()).collect()
+}
+
+#[test]
+fn output_is_independent_of_the_pull_size() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    for index in 0..configurations().len() {
+        // Configurations are rebuilt per run, as `SoundTouch` isn't `Clone`.
+        let fresh = || configurations().swap_remove(index);
+        let (name, mut reference_st) = fresh();
+        let reference = run(&mut reference_st, &input, 1024, |st, output| {
+            output.extend(st.receive_all())
+        });
+        assert!(!reference.is_empty(), "{name}");
+
+        for pull_size in [1, 7, 256, 4096] {
+            let output = run(&mut fresh().1, &input, 1024, |st, output| loop {
+                let before = output.len();
+                pull_frames(st, output, pull_size);
+                if output.len() == before {
+                    break;
+                }
+            });
+            assert!(
+                bits(&output) == bits(&reference),
+                "{name}: pulling {pull_size} frames gave {} samples instead of {}",
+                output.len(),
+                reference.len()
+            );
+        }
+    }
+}
+
+#[test]
+fn randomized_pulls_match_a_reference_drain() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    for seed in 1..=8u64 {
+        for index in 0..configurations().len() {
+            let fresh = || configurations().swap_remove(index);
+            let (name, mut reference_st) = fresh();
+            let reference = run(&mut reference_st, &input, 700, |st, output| {
+                output.extend(st.receive_all())
+            });
+
+            let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let output = run(&mut fresh().1, &input, 700, |st, output| {
+                // Sometimes leaves output for later, sometimes drains in odd sizes.
+                for _ in 0..rng.below(6) {
+                    let size = match rng.below(4) {
+                        0 => 1,
+                        1 => 1 + rng.below(32),
+                        2 => 1 + rng.below(1000),
+                        _ => 4096,
+                    };
+                    pull_frames(st, output, size);
+                }
+            });
+            assert!(
+                bits(&output) == bits(&reference),
+                "{name}, seed {seed}: {} samples instead of {}",
+                output.len(),
+                reference.len()
+            );
+        }
+    }
+}
+
+#[test]
+fn receive_all_takes_everything_available() {
+    let mut st = stretcher(2, 44100, 1.5);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    let available = st.available_frames();
+    assert!(available > 0);
+    assert_eq!(st.receive_all().len(), available * 2);
+    assert_eq!(st.available_frames(), 0);
+    assert!(st.receive_all().is_empty());
+}
@@ -0,0 +1,85 @@
+use soundtouch::{Setting, SoundTouch};
+
+/// Deterministic stereo input: a triangle wave plus LCG noise, avoiding `libm`.
+fn input() -> Vec<f32> {
+    let mut seed: u32 = 0x1234_5678;
+    (0..44100 * 2)
+        .map(|i| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (seed >> 9) as f32 / (1 << 23) as f32 - 0.5;
+            let phase = (i / 2 % 200) as f32 / 200.0;
+            let triangle = 4.0 * (phase - 0.5).abs() - 1.0;
+            0.5 * triangle + 0.1 * noise
+        })
+        .collect()
+}
+
+fn fnv1a(samples: &[f32]) -> u64 {
+    samples.iter().fold(0xcbf2_9ce4_8422_2325, |hash, sample| {
+        sample
+            .to_bits()
+            .to_le_bytes()
+            .iter()
+            .fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+    })
+}
+
+fn process() -> Vec<f32> {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.25)
+        .set_pitch_semitones(2)
+        .set_deterministic(true)
+        .set_setting(Setting::UseQuickseek, 1);
+    assert!(soundtouch.is_deterministic());
+    assert_eq!(soundtouch.get_setting(Setting::UseQuickseek), 0);
+
+    let input = input();
+    let mut output = Vec::new();
+    let mut buffer = [0.0; 4096];
+    soundtouch.put_samples(&input, input.len() / 2);
+    soundtouch.flush();
+    loop {
+        let n = soundtouch.receive_samples(&mut buffer, 2048);
+        if n == 0 {
+            break output;
+        }
+        output.extend_from_slice(&buffer[..n * 2]);
+    }
+}
+
+#[test]
+fn deterministic_output_is_reproducible() {
+    assert_eq!(fnv1a(&process()), fnv1a(&process()));
+}
+
+/// Output is only bit-exact per target architecture (see `SoundTouch::set_deterministic`),
+/// so each architecture CI runs on needs its own stored hash.
+const STORED_HASHES: &[(&str, u64)] = &[("x86_64", 0xa6dd_5e98_c6c5_5866)];
+
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn deterministic_output_matches_stored_hash() {
+    let arch = std::env::consts::ARCH;
+    let hash = fnv1a(&process());
+    match STORED_HASHES.iter().find(|(a, _)| *a == arch) {
+        Some((_, stored)) => assert_eq!(hash, *stored),
+        None => panic!("no stored hash for {arch}; add (\"{arch}\", {hash:#018x})"),
+    }
+}
+
+#[test]
+fn set_deterministic_preserves_parameters() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(2)
+        .set_sample_rate(48000)
+        .set_tempo(0.8)
+        .set_setting(Setting::OverlapMs, 12)
+        .set_deterministic(true);
+    assert_eq!(soundtouch.num_channels(), 2);
+    assert_eq!(soundtouch.get_setting(Setting::OverlapMs), 12);
+    assert!((soundtouch.get_input_output_sample_ratio() - 1.25).abs() < 1e-9);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, Sanitize, SoundTouch, SoundTouchError};
+
+/// A signal with more precision than `f32` holds.
+fn signal_f64(frames: usize) -> Vec<f64> {
+    signal::sine(220.0, 44100, frames)
+        .stereo()
+        .enumerate()
+        .map(|(i, s)| f64::from(s) + 1e-10 * (i % 7) as f64)
+        .collect()
+}
+
+fn rounded(samples: &[f64]) -> Vec<f32> {
+    samples.iter().map(|&s| s as f32).collect()
+}
+
+fn widened(samples: &[f32]) -> Vec<f64> {
+    samples.iter().map(|&s| f64::from(s)).collect()
+}
+
+#[test]
+fn generate_matches_f32() {
+    let input = signal_f64(30_000);
+    let mut reference = stretcher(2, 44100, 1.0);
+    reference.set_pitch_semitones(-2.0);
+    let expected = widened(&reference.generate_audio(&rounded(&input)));
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_pitch_semitones(-2.0);
+    let output = st.generate_audio_f64(&input);
+    assert!(!output.is_empty());
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn put_and_receive_match_f32() {
+    let input = signal_f64(30_000);
+    let mut reference = stretcher(2, 44100, 1.0);
+    reference.set_pitch_semitones(-2.0);
+    let expected = widened(&reference.generate_audio(&rounded(&input)));
+
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_pitch_semitones(-2.0);
+    let mut output = Vec::new();
+    let mut buffer = vec![0.0; 2 * 700];
+    for chunk in input.chunks(2 * 3001) {
+        st.put_samples_f64(chunk, chunk.len() / 2);
+        loop {
+            let frames = st.receive_samples_f64(&mut buffer, 700);
+            if frames == 0 {
+                break;
+            }
+            output.extend_from_slice(&buffer[..2 * frames]);
+        }
+    }
+    st.flush();
+    loop {
+        let frames = st.receive_samples_f64(&mut buffer, 700);
+        if frames == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..2 * frames]);
+    }
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn blocks_longer_than_the_conversion_buffer() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_pitch_semitones(-2.0);
+    st.set_output_block_frames(Some(3000));
+    st.put_samples_f64(&signal_f64(30_000), 30_000);
+    let mut buffer = vec![0.0; 2 * 5000];
+    assert_eq!(st.receive_samples_f64(&mut buffer, 5000), 3000);
+}
+
+#[test]
+fn errors() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_pitch_semitones(-2.0);
+    assert_eq!(
+        st.try_put_samples_f64(&[0.0; 10], 6),
+        Err(SoundTouchError::InputTooShort {
+            frames: 6,
+            channels: 2,
+            len: 10
+        })
+    );
+    assert_eq!(
+        st.try_receive_samples_f64(&mut [0.0; 10], 6),
+        Err(SoundTouchError::OutputTooSmall {
+            frames: 6,
+            channels: 2,
+            len: 10
+        })
+    );
+
+    // Out of the range of `f32`.
+    let mut input = signal_f64(10_000);
+    input[15_000] = 1e300;
+    st.set_input_sanitization(Sanitize::Error);
+    assert_eq!(
+        st.try_put_samples_f64(&input, 10_000),
+        Err(SoundTouchError::NonFiniteInput { index: 15_000 })
+    );
+    assert_eq!(st.pending_input_frames(), 0);
+
+    assert_eq!(
+        SoundTouch::new().try_generate_audio_f64(&input),
+        Err(SoundTouchError::NotConfigured)
+    );
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, DriftReport};
+
+const SAMPLE_RATE: usize = 44100;
+
+#[test]
+fn pending_output_explains_the_lag() {
+    let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 2.0);
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 10 * SAMPLE_RATE).into_vec(),
+        10 * SAMPLE_RATE,
+    );
+    let report = soundtouch.drift();
+    assert_eq!(report.expected_output_frames, 5 * SAMPLE_RATE as u64);
+    assert_eq!(report.actual_output_frames, 0);
+    assert_eq!(report.drift_frames, -5 * SAMPLE_RATE as i64);
+    // Only the part of a sequence the pipeline is working on is unaccounted for.
+    assert!(
+        report.residual_frames().unsigned_abs() < SAMPLE_RATE as u64 / 5,
+        "{report:?}"
+    );
+
+    let output = common::drain(&mut soundtouch);
+    let report = soundtouch.drift();
+    assert_eq!(report.actual_output_frames, output.len() as u64);
+    assert!(
+        report.residual_frames().unsigned_abs() < SAMPLE_RATE as u64 / 5,
+        "{report:?}"
+    );
+}
+
+#[test]
+fn tempo_changes_start_segments() {
+    let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 1.0);
+    let input = signal::sine(220.0, 44100, SAMPLE_RATE).into_vec();
+    soundtouch.put_samples(&input, SAMPLE_RATE);
+    soundtouch.set_tempo(0.5);
+    soundtouch.put_samples(&input, SAMPLE_RATE);
+    // The input not processed before the change is expected at the new ratio too.
+    let expected = soundtouch.drift().expected_output_frames;
+    let nominal = 3 * SAMPLE_RATE as u64;
+    assert!(
+        (nominal..nominal + SAMPLE_RATE as u64 / 5).contains(&expected),
+        "{expected}"
+    );
+
+    // A raw flush pads the stream to the ratio at the end, which the flush is blamed
+    // for once the stream ends.
+    soundtouch.flush();
+    common::drain(&mut soundtouch);
+    let drift = soundtouch.drift().drift_frames;
+    assert!(drift > 0, "{drift}");
+    soundtouch.put_samples(&input, SAMPLE_RATE);
+    let report = soundtouch.drift();
+    assert!((report.flush_drift_frames - drift).abs() <= 1, "{report:?}");
+    assert_eq!(report.clear_drift_frames, 0);
+}
+
+#[test]
+fn clear_is_blamed_for_the_discarded_output() {
+    let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 1.25);
+    let input = signal::sine(220.0, 44100, 2 * SAMPLE_RATE).into_vec();
+    soundtouch.put_samples(&input, 2 * SAMPLE_RATE);
+    common::drain(&mut soundtouch);
+    soundtouch.clear();
+    let report = soundtouch.drift();
+    assert_eq!(report.pending_output_frames, 0);
+    assert_eq!(report.flush_drift_frames, 0);
+    assert!(report.clear_drift_frames < 0, "{report:?}");
+    assert!(report.residual_frames().abs() <= 1, "{report:?}");
+
+    // So is the flushed output that wasn't received.
+    soundtouch.put_samples(&input, 2 * SAMPLE_RATE);
+    soundtouch.flush();
+    soundtouch.clear();
+    let cleared = soundtouch.drift();
+    assert_eq!(cleared.flush_drift_frames, 0);
+    assert!(
+        cleared.clear_drift_frames < report.clear_drift_frames - SAMPLE_RATE as i64,
+        "{cleared:?}"
+    );
+
+    soundtouch.reset();
+    assert_eq!(soundtouch.drift(), DriftReport::default());
+}
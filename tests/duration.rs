@@ -0,0 +1,41 @@
+use soundtouch::SoundTouch;
+
+#[test]
+fn duration_helpers_need_sample_rate() {
+    let mut soundtouch = SoundTouch::new();
+    assert_eq!(soundtouch.sample_rate(), None);
+    assert_eq!(soundtouch.frames_for_duration(1.0), None);
+    assert_eq!(soundtouch.duration_for_frames(1000), None);
+    assert_eq!(soundtouch.output_duration_for_input(1.0), None);
+}
+
+#[test]
+fn duration_helpers_at_common_sample_rates() {
+    for sample_rate in [8000, 22050, 44100, 48000, 96000] {
+        let mut soundtouch = SoundTouch::new();
+        soundtouch.set_sample_rate(sample_rate).set_tempo(2.0);
+        let rate = sample_rate as f64;
+
+        assert_eq!(soundtouch.sample_rate(), Some(sample_rate));
+        assert_eq!(soundtouch.frames_for_duration(1.0), Some(sample_rate as usize));
+        assert_eq!(soundtouch.frames_for_duration(0.25), Some((rate / 4.0).round() as usize));
+        assert_eq!(soundtouch.frames_for_duration(-1.0), Some(0));
+        assert_eq!(soundtouch.duration_for_frames(sample_rate as usize / 2), Some(0.5));
+
+        let frames = soundtouch.frames_for_duration(0.123).unwrap();
+        let secs = soundtouch.duration_for_frames(frames).unwrap();
+        assert!((secs - 0.123).abs() <= 0.5 / rate);
+
+        assert_eq!(soundtouch.output_duration_for_input(3.0), Some(1.5));
+    }
+}
+
+#[test]
+fn frames_for_duration_rounds_to_nearest() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_sample_rate(44100);
+    // 10 ms at 44.1 kHz is exactly 441 frames, 1 ms is 44.1 frames.
+    assert_eq!(soundtouch.frames_for_duration(0.01), Some(441));
+    assert_eq!(soundtouch.frames_for_duration(0.001), Some(44));
+    assert_eq!(soundtouch.frames_for_duration(0.0015), Some(66));
+}
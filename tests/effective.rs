@@ -0,0 +1,83 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{EffectiveParams, SoundTouch, SoundTouchError};
+
+const MIN: f64 = SoundTouch::MIN_EFFECTIVE_FACTOR;
+const MAX: f64 = SoundTouch::MAX_EFFECTIVE_FACTOR;
+
+#[test]
+fn matches_input_output_ratio() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    for (tempo, rate, pitch) in [
+        (1.0, 1.0, 1.0),
+        (1.5, 1.0, 0.8),
+        (0.7, 1.3, 1.25),
+        (2.0, 0.5, 3.0),
+    ] {
+        soundtouch.set_tempo(tempo).set_rate(rate).set_pitch(pitch);
+        let params = soundtouch.effective_parameters();
+        assert_eq!(params, EffectiveParams::new(tempo, rate, pitch));
+        assert_eq!(params.tempo, tempo / pitch);
+        assert_eq!(params.rate, pitch * rate);
+        assert_eq!(
+            params.input_output_ratio(),
+            soundtouch.get_input_output_sample_ratio()
+        );
+    }
+}
+
+#[test]
+fn semitones_are_reflected() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_pitch_semitones(12);
+    let params = soundtouch.effective_parameters();
+    assert!((params.virtual_pitch - 2.0).abs() < 1e-9);
+    assert!((params.rate - 2.0).abs() < 1e-9);
+    assert!((params.tempo - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn boundaries() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.try_set_tempo(MIN).unwrap();
+    soundtouch.try_set_tempo(MAX).unwrap();
+    soundtouch.try_set_tempo(1.0).unwrap();
+    soundtouch.try_set_rate(MIN).unwrap();
+    soundtouch.try_set_rate(MAX).unwrap();
+    soundtouch.try_set_rate(1.0).unwrap();
+
+    assert!(soundtouch.try_set_tempo(MIN * 0.99).is_err());
+    assert!(soundtouch.try_set_tempo(MAX * 1.01).is_err());
+    assert!(soundtouch.try_set_rate(MIN * 0.99).is_err());
+    assert!(soundtouch.try_set_rate(MAX * 1.01).is_err());
+    assert!(soundtouch.try_set_tempo(f64::NAN).is_err());
+    assert!(soundtouch.try_set_pitch(0.0).is_err());
+}
+
+#[test]
+fn combinations_are_checked() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .try_set_tempo(0.5)
+        .unwrap()
+        .try_set_rate(4.0)
+        .unwrap();
+
+    // Each value is in range on its own, but the pitch moves both effective values.
+    assert_eq!(
+        soundtouch.try_set_pitch(4.0).unwrap_err(),
+        SoundTouchError::ParametersOutOfRange {
+            tempo: 0.125,
+            rate: 16.0
+        }
+    );
+    soundtouch.try_set_pitch(2.0).unwrap();
+    assert_eq!(soundtouch.effective_parameters().rate, 8.0);
+    assert!(soundtouch.try_set_rate(4.1).is_err());
+    assert!(soundtouch.try_set_tempo(0.2).is_err());
+
+    // Rejected values leave the parameters untouched.
+    let params = soundtouch.effective_parameters();
+    assert_eq!(params, EffectiveParams::new(0.5, 4.0, 2.0));
+}
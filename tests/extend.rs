@@ -0,0 +1,103 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use common::{drain, process, stretcher};
+use soundtouch::{signal, Sanitize, SoundTouch, SoundTouchError};
+
+#[test]
+fn extend_matches_put_samples() {
+    // Longer than the stack buffer and not a multiple of it.
+    let input = signal::sine(220.0, 44100, 10_001).stereo().into_vec();
+    let expected = process(&mut stretcher(2, 44100, 1.2), &input);
+
+    let mut st = stretcher(2, 44100, 1.2);
+    st.extend(input.iter().copied());
+    st.flush();
+    assert_eq!(drain(&mut st), expected);
+}
+
+#[test]
+fn extend_with_frames() {
+    let input = signal::sine(220.0, 44100, 7_000).stereo().into_vec();
+    let expected = process(&mut stretcher(2, 44100, 1.2), &input);
+
+    let mut st = stretcher(2, 44100, 1.2);
+    st.extend(input.chunks(2).map(|frame| [frame[0], frame[1]]));
+    st.flush();
+    assert_eq!(drain(&mut st), expected);
+
+    let mut st = stretcher(2, 44100, 1.2);
+    assert_eq!(
+        st.try_put_frames_iter([[0.0; 3]; 10]),
+        Err(SoundTouchError::ChannelMismatch {
+            expected: 2,
+            actual: 3
+        })
+    );
+    assert_eq!(st.pending_input_frames(), 0);
+}
+
+#[test]
+fn odd_counts() {
+    // 4097 samples: a whole stack buffer plus one, ending in the middle of a frame.
+    let input = signal::sine(220.0, 44100, 2049).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 1.2);
+    assert_eq!(
+        st.try_put_iter(input[..4097].iter().copied()),
+        Err(SoundTouchError::PartialFrame {
+            trailing_samples: 1,
+            channels: 2
+        })
+    );
+    // The whole frames are put.
+    let mut expected = stretcher(2, 44100, 1.2);
+    expected.put_samples(&input, 2048);
+    assert_eq!(st.pending_input_frames(), expected.pending_input_frames());
+
+    let mut st = stretcher(2, 44100, 1.2);
+    let result = catch_unwind(AssertUnwindSafe(|| st.extend([0.0f32; 7])));
+    assert!(result.is_err());
+
+    let mut st = stretcher(3, 44100, 1.2);
+    assert_eq!(
+        st.try_put_iter(signal::sine(220.0, 44100, 2000).channels(3)),
+        Ok(2000)
+    );
+    assert_eq!(st.try_put_iter(std::iter::empty()), Ok(0));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        SoundTouch::new().try_put_iter([0.0; 4]),
+        Err(SoundTouchError::NotConfigured)
+    );
+
+    let mut input = signal::sine(220.0, 44100, 9_000).into_vec();
+    input[8_500] = f32::NAN;
+    let mut st = stretcher(1, 44100, 1.2);
+    st.set_input_sanitization(Sanitize::Error);
+    assert_eq!(
+        st.try_put_iter(input),
+        Err(SoundTouchError::NonFiniteInput { index: 8_500 })
+    );
+}
+
+#[test]
+fn chains_instances() {
+    let pitched = || {
+        let mut soundtouch = stretcher(2, 44100, 1.2);
+        soundtouch.set_tempo(1.0).set_pitch_semitones(3);
+        soundtouch
+    };
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let intermediate = process(&mut stretcher(2, 44100, 1.2), &input);
+    let expected = process(&mut pitched(), &intermediate);
+    assert!(!expected.is_empty());
+
+    let mut second = pitched();
+    stretcher(2, 44100, 1.2).generate_audio_extend(&input, &mut second);
+    second.flush();
+    assert_eq!(drain(&mut second), expected);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::units::Frames;
+use soundtouch::{signal, Pending, SoundTouch};
+
+const SAMPLE_RATE: usize = 44100;
+const SECONDS: usize = 20;
+const PACKET_FRAMES: usize = 1024;
+
+/// Speech-like mono noise: bursts of a few syllables per second with short pauses.
+fn speech(frames: usize) -> Vec<f32> {
+    signal::noise(1, SAMPLE_RATE as u32, frames)
+        .enumerate()
+        .map(|(i, sample)| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let syllable = (t * 4.0 * std::f32::consts::PI).sin().max(0.0);
+            let word = if (t * 0.7).fract() < 0.8 { 1.0 } else { 0.0 };
+            sample * syllable * word
+        })
+        .collect()
+}
+
+/// Puts `input` in packets, receiving after each until the status says to stop, and
+/// returns the output and its length after each packet. Fails on a receive that gets
+/// nothing while reporting more.
+fn stretch(
+    soundtouch: &mut SoundTouch,
+    input: &[f32],
+    buffer_frames: usize,
+) -> (Vec<f32>, Vec<usize>) {
+    let mut buffer = vec![0.0; buffer_frames];
+    let mut output = Vec::new();
+    let mut lengths = Vec::new();
+    let mut receive = |soundtouch: &mut SoundTouch, output: &mut Vec<f32>| loop {
+        let status = soundtouch.receive_frames(&mut buffer, Frames(buffer_frames));
+        assert!(
+            status.frames.0 > 0 || status.pending != Pending::MoreAvailable,
+            "empty batch reported as more available"
+        );
+        output.extend_from_slice(&buffer[..status.frames.0]);
+        if status.pending != Pending::MoreAvailable {
+            break;
+        }
+    };
+    for packet in input.chunks(PACKET_FRAMES) {
+        soundtouch.put_samples(packet, packet.len());
+        receive(soundtouch, &mut output);
+        lengths.push(output.len());
+    }
+    soundtouch.flush();
+    receive(soundtouch, &mut output);
+    (output, lengths)
+}
+
+#[test]
+fn high_tempos_make_progress() {
+    let input = speech(SECONDS * SAMPLE_RATE);
+    for tempo in [4.0, 8.0] {
+        let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 1.0);
+        soundtouch.try_set_tempo(tempo).unwrap();
+        let (output, lengths) = stretch(&mut soundtouch, &input, 256);
+        // The output keeps up with the input, less the latency of the pipeline.
+        for (packet, &length) in lengths.iter().enumerate() {
+            let put = (packet + 1) * PACKET_FRAMES;
+            assert!(
+                length as f64 * tempo + SAMPLE_RATE as f64 >= put as f64,
+                "tempo {tempo}: {length} frames out after {put} in"
+            );
+        }
+        let expected = (input.len() as f64 / tempo) as usize;
+        assert!(
+            output.len().abs_diff(expected) < SAMPLE_RATE / 10,
+            "tempo {tempo}: {} frames instead of {expected}",
+            output.len()
+        );
+        assert!(output.iter().any(|&sample| sample != 0.0));
+        assert_eq!(soundtouch.pending_output(), Pending::None);
+    }
+}
+
+#[test]
+fn blocks_longer_than_the_buffer_do_not_spin() {
+    let input = speech(SECONDS * SAMPLE_RATE);
+    let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 1.0);
+    soundtouch.try_set_tempo(8.0).unwrap();
+    soundtouch.set_output_block_frames(Some(1024));
+    // The buffer can't hold a block, so nothing is received, but the loop ends.
+    let (output, _) = stretch(&mut soundtouch, &input, 256);
+    assert!(output.is_empty());
+    assert_eq!(soundtouch.pending_output(), Pending::MoreAvailable);
+
+    let mut unblocked = stretcher(1, SAMPLE_RATE as u32, 1.0);
+    unblocked.try_set_tempo(8.0).unwrap();
+    let (output, _) = stretch(&mut unblocked, &input, 1024);
+    let mut blocked = stretcher(1, SAMPLE_RATE as u32, 1.0);
+    blocked.try_set_tempo(8.0).unwrap();
+    blocked.set_output_block_frames(Some(1024));
+    let (blocks, _) = stretch(&mut blocked, &input, 1024);
+    assert_eq!(blocks[..output.len()], output[..]);
+}
+
+#[test]
+fn range_ends_at_the_maximum() {
+    let mut soundtouch = stretcher(1, SAMPLE_RATE as u32, 1.0);
+    soundtouch
+        .try_set_tempo(SoundTouch::MAX_EFFECTIVE_FACTOR)
+        .unwrap();
+    assert!(soundtouch
+        .try_set_tempo(SoundTouch::MAX_EFFECTIVE_FACTOR * 1.01)
+        .is_err());
+    assert_eq!(
+        soundtouch.effective_parameters().tempo,
+        SoundTouch::MAX_EFFECTIVE_FACTOR
+    );
+}
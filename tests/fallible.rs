@@ -0,0 +1,180 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, BPMDetect, Config, Scratch, Setting, SoundTouch, SoundTouchError};
+
+#[test]
+fn unconfigured() {
+    let mut soundtouch = SoundTouch::new();
+    assert_eq!(
+        soundtouch.try_put_samples(&[0.0; 4], 2),
+        Err(SoundTouchError::NotConfigured)
+    );
+    assert_eq!(
+        soundtouch.try_generate_audio(&[0.0; 4]),
+        Err(SoundTouchError::NotConfigured)
+    );
+    let mut scratch = Scratch::for_channels(2);
+    assert_eq!(
+        soundtouch.try_process_block(&[0.0; 4], &mut [0.0; 4], &mut scratch),
+        Err(SoundTouchError::NotConfigured)
+    );
+
+    soundtouch.set_channels(2);
+    assert_eq!(
+        soundtouch.try_put_samples(&[0.0; 4], 2),
+        Err(SoundTouchError::NotConfigured)
+    );
+}
+
+#[test]
+fn short_buffers() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    assert_eq!(
+        soundtouch.try_put_samples(&[0.0; 5], 3),
+        Err(SoundTouchError::InputTooShort {
+            frames: 3,
+            channels: 2,
+            len: 5
+        })
+    );
+    assert_eq!(
+        soundtouch.try_put_samples(&[0.0; 4], usize::MAX),
+        Err(SoundTouchError::InputTooShort {
+            frames: usize::MAX,
+            channels: 2,
+            len: 4
+        })
+    );
+    assert_eq!(
+        soundtouch.try_receive_samples(&mut [0.0; 5], 3),
+        Err(SoundTouchError::OutputTooSmall {
+            frames: 3,
+            channels: 2,
+            len: 5
+        })
+    );
+    assert_eq!(soundtouch.num_unprocessed_samples(), 0);
+
+    soundtouch.try_put_samples(&[0.0; 6], 3).unwrap();
+    assert_eq!(soundtouch.try_receive_samples(&mut [0.0; 6], 3), Ok(0));
+}
+
+#[test]
+fn generate_audio_matches() {
+    let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 1.3).generate_audio(&input);
+    let output = stretcher(2, 44100, 1.3).try_generate_audio(&input).unwrap();
+    assert!(output == expected);
+}
+
+#[test]
+fn scratch_channels() {
+    assert_eq!(
+        Scratch::try_for_channels(0).unwrap_err(),
+        SoundTouchError::InvalidChannels(0)
+    );
+    assert_eq!(
+        Scratch::try_for_channels(SoundTouch::MAX_CHANNELS + 1).unwrap_err(),
+        SoundTouchError::InvalidChannels(SoundTouch::MAX_CHANNELS + 1)
+    );
+    let mut scratch = Scratch::try_for_channels(1).unwrap();
+    assert_eq!(
+        stretcher(2, 44100, 1.0).try_process_block(&[], &mut [], &mut scratch),
+        Err(SoundTouchError::ChannelMismatch {
+            expected: 2,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn aa_filter_length() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    for value in [0, -8, 7, 60] {
+        assert_eq!(
+            soundtouch
+                .try_set_setting(Setting::AaFilterLength, value)
+                .unwrap_err(),
+            SoundTouchError::InvalidSetting {
+                setting: Setting::AaFilterLength,
+                value
+            }
+        );
+    }
+    assert_eq!(soundtouch.get_setting(Setting::AaFilterLength), 64);
+    soundtouch
+        .try_set_setting(Setting::AaFilterLength, 32)
+        .unwrap();
+    assert_eq!(soundtouch.get_setting(Setting::AaFilterLength), 32);
+    // Only the filter length has restrictions.
+    soundtouch.try_set_setting(Setting::SequenceMs, -1).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "invalid value 7 for setting AaFilterLength")]
+#[cfg(debug_assertions)]
+fn invalid_setting_debug_asserts() {
+    stretcher(2, 44100, 1.0).set_setting(Setting::AaFilterLength, 7);
+}
+
+#[test]
+fn bpm_detect() {
+    assert_eq!(
+        BPMDetect::try_new(2, 4000).err(),
+        Some(SoundTouchError::InvalidSampleRate(4000))
+    );
+    assert_eq!(
+        BPMDetect::try_new(0, 44100).err(),
+        Some(SoundTouchError::InvalidChannels(0))
+    );
+    assert_eq!(
+        BPMDetect::try_new(2, SoundTouch::MAX_SAMPLE_RATE + 1).err(),
+        Some(SoundTouchError::InvalidSampleRate(
+            SoundTouch::MAX_SAMPLE_RATE + 1
+        ))
+    );
+    assert!(BPMDetect::try_new(1, BPMDetect::MIN_SAMPLE_RATE).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "invalid sample rate 4000")]
+fn bpm_detect_panics() {
+    BPMDetect::new(2, 4000);
+}
+
+#[test]
+fn get_beats_is_bounded_by_slices() {
+    let mut bpm = BPMDetect::new(2, 44100);
+    bpm.input_samples(&signal::sine(220.0, 44100, 44100 * 5).stereo().into_vec());
+    bpm.get_bpm();
+    let beats = bpm.query_size(0);
+    assert!(beats > 2);
+    // Returns the total number of beats but only writes as many as fit.
+    let (mut pos, mut values) = ([-1.0; 4], [-1.0; 2]);
+    assert_eq!(bpm.get_beats(&mut pos, &mut values, 1000), beats);
+    assert_eq!(pos[2..], [-1.0; 2]);
+}
+
+#[test]
+#[should_panic(expected = "invalid number of channels 0")]
+fn concatenator_validates_config() {
+    soundtouch::segments::Concatenator::new(16).push(&[0.0; 4], Config::new().set_channels(0));
+}
+
+#[test]
+fn version_string() {
+    let version = SoundTouch::get_version_string();
+    assert!(!version.is_empty());
+    assert_eq!(
+        version,
+        SoundTouch::version_string_from_id(SoundTouch::get_version_id())
+    );
+}
+
+#[test]
+fn version_string_from_id() {
+    assert_eq!(SoundTouch::version_string_from_id(20302), "2.3.2");
+    assert_eq!(SoundTouch::version_string_from_id(10917), "1.9.17");
+    assert_eq!(SoundTouch::version_string_from_id(0), "0.0.0");
+}
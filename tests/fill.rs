@@ -0,0 +1,106 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouchError};
+
+const CHANNELS: usize = 2;
+
+/// Returns a `pull_input` handing out `input` `chunk_frames` frames at a time.
+fn source(input: &[f32], chunk_frames: usize) -> impl FnMut(&mut Vec<f32>) -> bool + '_ {
+    let mut chunks = input.chunks(chunk_frames * CHANNELS);
+    move |buffer| match chunks.next() {
+        Some(chunk) => {
+            buffer.extend_from_slice(chunk);
+            true
+        }
+        None => false,
+    }
+}
+
+#[test]
+fn exact_fills() {
+    let input = signal::sine(220.0, 44100, 60_000)
+        .channels(CHANNELS as u32)
+        .into_vec();
+    let expected = common::process(&mut stretcher(CHANNELS as u32, 44100, 1.5), &input);
+
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.5);
+    let mut pull = source(&input, 1000);
+    let mut output = Vec::new();
+    let mut block = [0.0; 4096 * CHANNELS];
+    for _ in 0..8 {
+        soundtouch.fill_exact(&mut block, &mut pull).unwrap();
+        output.extend_from_slice(&block);
+    }
+    assert_eq!(output, expected[..output.len()]);
+}
+
+#[test]
+fn end_of_stream_mid_fill() {
+    let input = signal::sine(220.0, 44100, 10_000)
+        .channels(CHANNELS as u32)
+        .into_vec();
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.5);
+    soundtouch.set_tempo(1.0);
+    let mut pull = source(&input, 4096);
+    let mut block = [9.0; 4096 * CHANNELS];
+    soundtouch.fill_exact(&mut block, &mut pull).unwrap();
+    soundtouch.fill_exact(&mut block, &mut pull).unwrap();
+
+    block.fill(9.0);
+    let written_frames = 10_000 - 2 * 4096;
+    assert_eq!(
+        soundtouch.fill_exact(&mut block, &mut pull),
+        Err(SoundTouchError::StreamEnded { written_frames })
+    );
+    assert!(block[written_frames * CHANNELS..]
+        .iter()
+        .all(|&sample| sample == 9.0));
+    assert_eq!(soundtouch.available_frames(), 0);
+}
+
+#[test]
+fn one_frame_at_a_time() {
+    let input = signal::sine(220.0, 44100, 20_000)
+        .channels(CHANNELS as u32)
+        .into_vec();
+    let expected = common::process(&mut stretcher(CHANNELS as u32, 44100, 1.5), &input);
+    let frames = expected.len() / CHANNELS;
+
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.5);
+    let mut calls = 0;
+    let mut pull = source(&input, 1);
+    let mut output = vec![0.0; expected.len()];
+    soundtouch
+        .fill_exact(&mut output, |buffer| {
+            calls += 1;
+            pull(buffer)
+        })
+        .unwrap();
+    assert_eq!(output, expected);
+    assert_eq!(calls, 20_001);
+
+    // The output of a flushed stream is exhausted, and the next input starts a new one.
+    let mut pull = source(&input, 1);
+    let mut output = vec![0.0; frames * CHANNELS];
+    soundtouch.fill_exact(&mut output, &mut pull).unwrap();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn partial_frame_at_the_end() {
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.5);
+    let mut calls = 0;
+    let result = soundtouch.fill_exact(&mut [0.0; 64], |buffer| {
+        calls += 1;
+        buffer.extend_from_slice(&[0.25; 3]);
+        calls < 3
+    });
+    assert_eq!(
+        result,
+        Err(SoundTouchError::PartialFrame {
+            trailing_samples: 1,
+            channels: CHANNELS
+        })
+    );
+}
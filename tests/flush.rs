@@ -0,0 +1,143 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, FlushMode};
+
+#[test]
+fn flush_length_matches_ratio() {
+    let frames = 30_000;
+    let input = signal::pink_noise(7, 44100, frames).stereo().into_vec();
+    for tempo in [0.5, 0.8, 1.0, 1.3, 2.0] {
+        let mut st = stretcher(2, 44100, tempo);
+        st.put_samples(&input, frames);
+        let before = common::drain(&mut st).len() / 2;
+        let padding = st.expected_flush_padding();
+        st.flush();
+        let flushed = common::drain(&mut st).len() / 2;
+
+        let expected = (frames as f64 / tempo).round() as usize;
+        assert_eq!(flushed, padding, "tempo {tempo}");
+        assert!(
+            (before + flushed).abs_diff(expected) <= 1,
+            "tempo {tempo}: got {} frames, expected {expected}",
+            before + flushed
+        );
+    }
+}
+
+#[test]
+fn trailing_silence_is_trimmed() {
+    let frames = 30_000;
+    let input = signal::pink_noise(7, 44100, frames).stereo().into_vec();
+    for tempo in [0.5, 0.8, 1.0, 1.3, 2.0] {
+        let mut st = stretcher(2, 44100, tempo);
+        st.put_samples(&input, frames);
+        let mut output = common::drain(&mut st);
+        let before = output.len() / 2;
+        let padding = st.expected_flush_padding();
+        st.flush_trimmed(&mut output);
+
+        assert_eq!(output.len() % 2, 0);
+        let trimmed = output.len() / 2;
+        assert!(
+            trimmed >= before && trimmed <= before + padding,
+            "tempo {tempo}"
+        );
+        assert!(
+            output[output.len() - 2..].iter().any(|s| s.abs() >= 1.0e-6),
+            "tempo {tempo}: output ends in silence"
+        );
+    }
+}
+
+#[test]
+fn earlier_quiet_audio_is_kept() {
+    let frames = 40_000;
+    let input = vec![0.0; frames * 2];
+    let mut st = stretcher(2, 44100, 1.0);
+    st.put_samples(&input, frames);
+    let mut output = common::drain(&mut st);
+    let received = output.len();
+    assert!(received > 0);
+    st.flush_trimmed(&mut output);
+    assert_eq!(output.len(), received);
+}
+
+#[test]
+fn padding_matches_flushed_frames() {
+    let frames = 12_345;
+    let input = signal::sine(220.0, 44100, frames).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 0.9);
+    st.put_samples(&input, frames);
+    let padding = st.expected_flush_padding();
+    let ready = st.num_samples() as usize;
+    st.flush();
+    assert_eq!(st.num_samples() as usize, ready + padding);
+}
+
+#[test]
+fn raw_is_the_default_flush_mode() {
+    let mut st = stretcher(2, 44100, 1.0);
+    assert_eq!(st.flush_mode(), FlushMode::Raw);
+    st.set_flush_mode(FlushMode::DrainOnly);
+    assert_eq!(st.flush_mode(), FlushMode::DrainOnly);
+    st.reset();
+    assert_eq!(st.flush_mode(), FlushMode::Raw);
+}
+
+#[test]
+fn pad_to_ratio_hits_the_exact_length() {
+    for frames in [1, 500, 30_000] {
+        let input = signal::sine(220.0, 44100, frames).stereo().into_vec();
+        for tempo in [0.5, 0.8, 1.0, 1.3, 2.0] {
+            let mut st = stretcher(2, 44100, tempo);
+            st.set_flush_mode(FlushMode::PadToRatio);
+            let output = st.generate_audio(&input);
+            let expected = (frames as f64 / tempo).round() as usize;
+            assert_eq!(
+                output.len() / 2,
+                expected,
+                "{frames} frames at tempo {tempo}"
+            );
+        }
+    }
+}
+
+#[test]
+fn pad_to_ratio_uses_the_final_ratio() {
+    let frames = 20_000;
+    let input = signal::sine(220.0, 44100, 2 * frames).stereo().into_vec();
+    for tempo in [0.7, 1.5] {
+        let mut st = stretcher(2, 44100, 1.0);
+        st.set_flush_mode(FlushMode::PadToRatio);
+        st.put_samples(&input[..2 * frames], frames);
+        let mut output = common::drain(&mut st);
+        st.set_tempo(tempo);
+        output.extend(common::process(&mut st, &input[2 * frames..]));
+
+        let expected = (2.0 * frames as f64 / tempo).round() as usize;
+        assert_eq!(output.len() / 2, expected, "tempo {tempo}");
+    }
+}
+
+#[test]
+fn drain_only_drops_the_silent_tail() {
+    let frames = 20_000;
+    let mut input = signal::sine(220.0, 44100, frames).stereo().into_vec();
+    input.resize(2 * (frames + 10_000), 0.0);
+    for tempo in [0.5, 1.0, 1.3, 2.0] {
+        let raw = common::process(&mut stretcher(2, 44100, tempo), &input);
+        let mut st = stretcher(2, 44100, tempo);
+        st.set_flush_mode(FlushMode::DrainOnly);
+        let drained = common::process(&mut st, &input);
+
+        assert!(drained.len() < raw.len(), "tempo {tempo}");
+        assert_eq!(drained[..], raw[..drained.len()], "tempo {tempo}");
+        assert!(
+            drained[drained.len() - 2..]
+                .iter()
+                .any(|s| s.abs() >= 1.0e-6),
+            "tempo {tempo}: output ends in silence"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use std::f64::consts::TAU;
+
+use soundtouch::SoundTouch;
+
+const SAMPLE_RATE: usize = 44100;
+
+/// A sung "ah": harmonics of 150 Hz shaped by formants at 700, 1200 and 2600 Hz.
+fn vowel(frames: usize) -> Vec<f32> {
+    let formant = |frequency: f64, centre: f64, width: f64| {
+        1.0 / (1.0 + ((frequency - centre) / width).powi(2))
+    };
+    (0..frames)
+        .map(|i| {
+            let t = i as f64 / SAMPLE_RATE as f64;
+            let sample: f64 = (1..=30)
+                .map(|k| {
+                    let frequency = 150.0 * k as f64;
+                    let gain = formant(frequency, 700.0, 100.0)
+                        + 0.6 * formant(frequency, 1200.0, 120.0)
+                        + 0.3 * formant(frequency, 2600.0, 200.0);
+                    gain * (TAU * frequency * t).sin()
+                })
+                .sum();
+            (0.2 * sample) as f32
+        })
+        .collect()
+}
+
+/// Returns the power-weighted mean frequency of the mono `samples` between 50 Hz and
+/// 6 kHz, using the Goertzel algorithm.
+fn spectral_centroid(samples: &[f32]) -> f64 {
+    let power = |frequency: f64| {
+        let coefficient = 2.0 * (TAU * frequency / SAMPLE_RATE as f64).cos();
+        let (mut s1, mut s2) = (0.0, 0.0);
+        for &sample in samples {
+            let s0 = sample as f64 + coefficient * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coefficient * s1 * s2
+    };
+    let (weighted, total) =
+        (1..=240)
+            .map(|i| i as f64 * 25.0)
+            .fold((0.0, 0.0), |(weighted, total), frequency| {
+                let power = power(frequency);
+                (weighted + frequency * power, total + power)
+            });
+    weighted / total
+}
+
+fn shifted(semitones: f64, corrected: bool) -> Vec<f32> {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(1)
+        .set_sample_rate(SAMPLE_RATE as u32);
+    if corrected {
+        soundtouch.set_pitch_with_formant_correction(semitones);
+    } else {
+        soundtouch.set_pitch(2f64.powf(semitones / 12.0));
+    }
+    soundtouch.generate_audio(&vowel(SAMPLE_RATE))
+}
+
+#[test]
+fn correction_reduces_centroid_drift() {
+    let original = spectral_centroid(&vowel(SAMPLE_RATE)[8192..36_000]);
+    for semitones in [-7.0, -4.0, 5.0, 9.0] {
+        let plain = spectral_centroid(&shifted(semitones, false)[8192..36_000]);
+        let corrected = spectral_centroid(&shifted(semitones, true)[8192..36_000]);
+        assert!(
+            (corrected - original).abs() < (plain - original).abs(),
+            "{semitones} semitones: original {original} Hz, plain {plain} Hz, corrected {corrected} Hz"
+        );
+    }
+}
+
+#[test]
+fn correction_keeps_length_and_pitch() {
+    let input = common::tone(440.0, SAMPLE_RATE, SAMPLE_RATE);
+    let mut plain = SoundTouch::new();
+    plain
+        .set_channels(1)
+        .set_sample_rate(44100)
+        .set_pitch_semitones(3);
+    let mut corrected = SoundTouch::new();
+    corrected
+        .set_channels(1)
+        .set_sample_rate(44100)
+        .set_pitch_with_formant_correction(3.0);
+    let plain = plain.generate_audio(&input);
+    let corrected = corrected.generate_audio(&input);
+    assert_eq!(corrected.len(), plain.len());
+    let peak = common::peak_frequency(&corrected[4096..40_000], SAMPLE_RATE, 500.0, 550.0, 1.0);
+    assert!((peak - 523.25).abs() <= 2.0, "{peak} Hz");
+}
+
+#[test]
+fn correction_setting() {
+    let mut soundtouch = SoundTouch::new();
+    assert_eq!(soundtouch.formant_correction(), None);
+    soundtouch.set_pitch_with_formant_correction(-2.5);
+    assert_eq!(soundtouch.formant_correction(), Some(-2.5));
+    assert!(
+        (soundtouch.effective_parameters().virtual_pitch - 2f64.powf(-2.5 / 12.0)).abs() < 1e-9
+    );
+
+    // Other pitch changes keep the correction.
+    soundtouch.set_pitch(1.5);
+    assert_eq!(soundtouch.formant_correction(), Some(-2.5));
+    soundtouch.clear_formant_correction();
+    assert_eq!(soundtouch.formant_correction(), None);
+    assert_eq!(soundtouch.effective_parameters().virtual_pitch, 1.5);
+
+    soundtouch.set_pitch_with_formant_correction(4.0);
+    soundtouch.set_pitch_with_formant_correction(0.0);
+    assert_eq!(soundtouch.formant_correction(), None);
+    soundtouch.set_pitch_with_formant_correction(4.0).reset();
+    assert_eq!(soundtouch.formant_correction(), None);
+}
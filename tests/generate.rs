@@ -0,0 +1,166 @@
+mod common;
+
+use common::{process, stretcher};
+use soundtouch::{signal, SoundTouch, SoundTouchError};
+
+/// Small xorshift generator so the property tests are reproducible without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, low: usize, high: usize) -> usize {
+        low + self.next() as usize % (high - low)
+    }
+
+    fn float(&mut self, low: f64, high: f64) -> f64 {
+        low + (self.next() >> 11) as f64 / (1u64 << 53) as f64 * (high - low)
+    }
+}
+
+#[test]
+fn generate_audio_length_follows_ratio() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..60 {
+        let channels = [1, 2, 6][rng.range(0, 3)];
+        let frames = rng.range(0, 30_000);
+        let tempo = rng.float(0.5, 2.0);
+
+        let mut soundtouch = SoundTouch::new();
+        soundtouch
+            .set_channels(channels as u32)
+            .set_sample_rate(44100)
+            .set_tempo(tempo);
+        let output = soundtouch.generate_audio(
+            &signal::sine(220.0, 44100, frames)
+                .channels(channels as u32)
+                .into_vec(),
+        );
+
+        assert_eq!(output.len() % channels, 0);
+        let expected = frames as f64 / tempo;
+        let actual = (output.len() / channels) as f64;
+        assert!(
+            (actual - expected).abs() <= 1.0,
+            "{channels} channels, {frames} frames, tempo {tempo}: {actual} frames out, expected {expected}"
+        );
+        assert_eq!(soundtouch.num_unprocessed_samples(), 0);
+        assert_eq!(soundtouch.num_samples(), 0);
+    }
+}
+
+#[test]
+fn generate_audio_matches_manual_processing() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut expected = stretcher(2, 44100, 1.4);
+    let mut soundtouch = stretcher(2, 44100, 1.4);
+    assert_eq!(
+        soundtouch.generate_audio(&input),
+        process(&mut expected, &input)
+    );
+}
+
+#[test]
+fn generate_audio_ignores_partial_frame() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    let mut input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    input.push(0.5);
+    assert_eq!(soundtouch.generate_audio(&input).len(), 2 * 10_000);
+}
+
+#[test]
+fn generate_audio_rejects_a_primed_pipeline() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut soundtouch = stretcher(2, 44100, 1.4);
+    // This used to be mixed into the output of `generate_audio`.
+    soundtouch.put_samples(&input[..20_000], 10_000);
+    assert!(matches!(
+        soundtouch.try_generate_audio(&input),
+        Err(SoundTouchError::PipelineNotEmpty { pending_frames, .. }) if pending_frames > 0
+    ));
+    assert!(matches!(
+        soundtouch.try_generate_audio_f64(&[0.0; 100]),
+        Err(SoundTouchError::PipelineNotEmpty { .. })
+    ));
+
+    // Nothing was put, and clearing makes it usable.
+    soundtouch.clear();
+    assert_eq!(
+        soundtouch.generate_audio(&input),
+        stretcher(2, 44100, 1.4).generate_audio(&input)
+    );
+}
+
+#[test]
+fn generate_audio_rejects_unreceived_output() {
+    let mut soundtouch = stretcher(2, 44100, 1.4);
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    let mut buffer = vec![0.0; 2 * 20_000];
+    while soundtouch.receive_samples(&mut buffer, 20_000) > 0 {}
+    soundtouch.put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100);
+    soundtouch.flush();
+    assert!(matches!(
+        soundtouch.try_generate_audio(&[0.0; 100]),
+        Err(SoundTouchError::PipelineNotEmpty { ready_frames, .. }) if ready_frames > 0
+    ));
+
+    // Once the flushed output is received, a new stream starts.
+    while soundtouch.receive_samples(&mut buffer, 20_000) > 0 {}
+    let input = signal::sine(220.0, 44100, 5_000).stereo().into_vec();
+    assert_eq!(
+        soundtouch.generate_audio(&input),
+        stretcher(2, 44100, 1.4).generate_audio(&input)
+    );
+}
+
+#[test]
+#[should_panic(expected = "unprocessed")]
+fn generate_audio_with_pending_input_panics() {
+    let mut soundtouch = stretcher(2, 44100, 1.4);
+    soundtouch.put_samples(&signal::sine(220.0, 44100, 1000).stereo().into_vec(), 1000);
+    soundtouch.generate_audio(&signal::sine(220.0, 44100, 1000).stereo().into_vec());
+}
+
+#[test]
+fn generate_audio_appending_continues_the_stream() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 1.4).generate_audio(&input);
+
+    let mut soundtouch = stretcher(2, 44100, 1.4);
+    soundtouch.put_samples(&input, 8_000);
+    let mut output = vec![0.0; 2 * 1000];
+    let received = soundtouch.receive_samples(&mut output, 1000);
+    output.truncate(2 * received);
+    output.extend(soundtouch.generate_audio_appending(&input[2 * 8_000..]));
+    assert_eq!(output, expected);
+}
+
+#[test]
+#[should_panic(expected = "must be set before processing")]
+fn generate_audio_without_channels_panics() {
+    SoundTouch::new()
+        .set_sample_rate(44100)
+        .generate_audio(&[0.0; 100]);
+}
+
+#[test]
+#[should_panic(expected = "only 100 samples given")]
+fn put_samples_is_bounds_checked() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch.put_samples(&[0.0; 100], 51);
+}
+
+#[test]
+#[should_panic(expected = "only room for 100 samples")]
+fn receive_samples_is_bounds_checked() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch.receive_samples(&mut [0.0; 100], 51);
+}
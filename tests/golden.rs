@@ -0,0 +1,147 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use common::process;
+use soundtouch::{fingerprint, quantize, signal, SoundTouch};
+
+/// The configurations of the corpus: name, channels, sample rate and a setup function.
+const CASES: &[(&str, u32, u32, fn(&mut SoundTouch))] = &[
+    ("mono-unity", 1, 44100, |_| {}),
+    ("mono-tempo-1.25", 1, 44100, |st| {
+        st.set_tempo(1.25);
+    }),
+    ("stereo-tempo-0.8", 2, 44100, |st| {
+        st.set_tempo(0.8);
+    }),
+    ("stereo-rate-1.5", 2, 48000, |st| {
+        st.set_rate(1.5);
+    }),
+    ("stereo-pitch-0.75", 2, 48000, |st| {
+        st.set_pitch(0.75);
+    }),
+    ("stereo-tempo-1.1-rate-0.9", 2, 22050, |st| {
+        st.set_tempo(1.1).set_rate(0.9);
+    }),
+    ("quad-tempo-1.5", 4, 44100, |st| {
+        st.set_tempo(1.5);
+    }),
+];
+
+/// Corpus fingerprints are per architecture, as deterministic output isn't identical
+/// across them.
+fn corpus_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("fingerprints-{}.txt", std::env::consts::ARCH))
+}
+
+fn fingerprints() -> BTreeMap<String, u64> {
+    CASES
+        .iter()
+        .map(|&(name, channels, sample_rate, setup)| {
+            let mut soundtouch = SoundTouch::new();
+            soundtouch
+                .set_channels(channels)
+                .set_sample_rate(sample_rate)
+                .set_deterministic(true);
+            setup(&mut soundtouch);
+            let input = signal::sine(220.0, 44100, 20_000)
+                .channels(channels)
+                .into_vec();
+            let output = process(&mut soundtouch, &input);
+            (name.to_owned(), fingerprint(&output))
+        })
+        .collect()
+}
+
+fn parse(corpus: &str) -> BTreeMap<String, u64> {
+    corpus
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hash) = line.split_once(' ').expect("`name hash` lines");
+            let hash = u64::from_str_radix(hash, 16).expect("hexadecimal fingerprint");
+            (name.to_owned(), hash)
+        })
+        .collect()
+}
+
+/// Compares the output of the corpus configurations with the recorded fingerprints. See
+/// tests/golden/README.md for when they may change and how to record them.
+#[test]
+#[ignore = "no fingerprints are recorded yet; see tests/golden/README.md"]
+fn corpus_fingerprints() {
+    let path = corpus_path();
+    let actual = fingerprints();
+    if std::env::var_os("SOUNDTOUCH_BLESS").is_some() {
+        let mut corpus =
+            String::from("# Recorded with SOUNDTOUCH_BLESS=1 cargo test --test golden\n");
+        for (name, hash) in &actual {
+            writeln!(corpus, "{name} {hash:016x}").unwrap();
+        }
+        std::fs::write(&path, corpus).unwrap();
+        eprintln!("recorded {}", path.display());
+        return;
+    }
+
+    let corpus = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no fingerprints recorded at {}; see tests/golden/README.md",
+            path.display()
+        )
+    });
+    let expected = parse(&corpus);
+    let changed: Vec<_> = actual
+        .iter()
+        .filter(|(name, hash)| expected.get(*name) != Some(hash))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert!(
+        changed.is_empty(),
+        "output changed for {changed:?}; see tests/golden/README.md before re-recording"
+    );
+    assert_eq!(
+        expected.keys().collect::<Vec<_>>(),
+        actual.keys().collect::<Vec<_>>(),
+        "the corpus lists other configurations"
+    );
+}
+
+#[test]
+fn quantize_rounds_and_clamps() {
+    assert_eq!(quantize(0.0), 0);
+    assert_eq!(quantize(0.5), 16384);
+    assert_eq!(quantize(-0.5), -16384);
+    assert_eq!(quantize(1.5 / 32768.0), 2);
+    assert_eq!(quantize(-1.5 / 32768.0), -2);
+    assert_eq!(quantize(1.0), i16::MAX);
+    assert_eq!(quantize(-1.0), i16::MIN);
+    assert_eq!(quantize(f32::INFINITY), i16::MAX);
+    assert_eq!(quantize(f32::NEG_INFINITY), i16::MIN);
+    assert_eq!(quantize(f32::NAN), 0);
+}
+
+#[test]
+fn fingerprint_ignores_noise_below_the_step() {
+    let samples = signal::sine(220.0, 44100, 1000).stereo().into_vec();
+    let lsb = 1.0 / 32768.0;
+    // Snap to the middle of the four-LSB steps, so ±1 LSB stays within them.
+    let centred: Vec<f32> = samples
+        .iter()
+        .map(|&s| ((quantize(s) >> 2) * 4 + 2) as f32 * lsb)
+        .collect();
+    let nudged: Vec<f32> = centred
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| if i % 2 == 0 { s + lsb } else { s - lsb })
+        .collect();
+    assert_eq!(fingerprint(&centred), fingerprint(&nudged));
+
+    let mut changed = centred.clone();
+    changed[500] += 8.0 * lsb;
+    assert_ne!(fingerprint(&centred), fingerprint(&changed));
+    assert_ne!(fingerprint(&centred), fingerprint(&centred[1..]));
+}
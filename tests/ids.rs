@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::thread;
+
+use soundtouch::{BPMDetect, SoundTouch};
+
+#[test]
+fn ids_are_unique_and_increasing() {
+    let a = SoundTouch::new();
+    let b = BPMDetect::new(2, 44100);
+    let c = SoundTouch::new();
+    assert!(a.id() < b.id());
+    assert!(b.id() < c.id());
+}
+
+#[test]
+fn ids_are_unique_across_threads() {
+    let handles: Vec<_> = (0..8)
+        .map(|_| thread::spawn(|| (0..50).map(|_| SoundTouch::new().id()).collect::<Vec<_>>()))
+        .collect();
+    let mut ids = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(ids.insert(id), "duplicate id {id}");
+        }
+    }
+    assert_eq!(ids.len(), 400);
+}
+
+#[test]
+fn debug_shows_id() {
+    let soundtouch = SoundTouch::new();
+    assert!(format!("{soundtouch:?}").contains(&format!("id: {}", soundtouch.id())));
+    let bpm = BPMDetect::new(1, 8000);
+    assert!(format!("{bpm:?}").contains(&format!("id: {}", bpm.id())));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouch};
+
+const CHANNELS: usize = 2;
+
+const TEMPOS: [f64; 7] = [0.5, 0.75, 0.9, 1.0, 1.25, 1.5, 2.0];
+
+/// Puts as many frames as needed for `desired` output frames and checks they are
+/// available.
+fn check(soundtouch: &mut SoundTouch, desired: usize, what: &str) {
+    let needed = soundtouch.input_needed_for_output(desired);
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, needed)
+            .channels(CHANNELS as u32)
+            .into_vec(),
+        needed,
+    );
+    assert!(
+        soundtouch.available_frames() >= desired,
+        "{what}: {needed} frames gave {} of {desired}",
+        soundtouch.available_frames()
+    );
+}
+
+#[test]
+fn fresh_pipelines() {
+    for tempo in TEMPOS {
+        for desired in [1, 500, 4096, 44100] {
+            check(
+                &mut stretcher(CHANNELS as u32, 44100, tempo),
+                desired,
+                &format!("tempo {tempo}"),
+            );
+        }
+    }
+}
+
+#[test]
+fn primed_pipelines() {
+    for tempo in TEMPOS {
+        for primed in [100, 3000, 20_000] {
+            let mut soundtouch = stretcher(CHANNELS as u32, 44100, tempo);
+            soundtouch.put_samples(
+                &signal::sine(220.0, 44100, primed)
+                    .channels(CHANNELS as u32)
+                    .into_vec(),
+                primed,
+            );
+            common::drain(&mut soundtouch);
+            for desired in [1, 4096, 10_000] {
+                check(
+                    &mut soundtouch,
+                    desired,
+                    &format!("tempo {tempo}, primed with {primed}"),
+                );
+                common::drain(&mut soundtouch);
+            }
+        }
+    }
+}
+
+#[test]
+fn with_rate_and_pitch() {
+    for tempo in TEMPOS {
+        let mut soundtouch = stretcher(CHANNELS as u32, 44100, tempo);
+        soundtouch.set_rate(0.8).set_pitch_semitones(3);
+        check(&mut soundtouch, 4096, &format!("tempo {tempo}"));
+        common::drain(&mut soundtouch);
+        check(&mut soundtouch, 4096, &format!("tempo {tempo}, primed"));
+    }
+}
+
+#[test]
+fn nothing_needed_when_available() {
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.5);
+    assert_eq!(soundtouch.input_needed_for_output(0), 0);
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 44100)
+            .channels(CHANNELS as u32)
+            .into_vec(),
+        44100,
+    );
+    let available = soundtouch.available_frames();
+    assert!(available > 0);
+    assert_eq!(soundtouch.input_needed_for_output(available), 0);
+    assert!(soundtouch.input_needed_for_output(available + 1) > 0);
+}
+
+#[test]
+fn output_blocks() {
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 0.8);
+    soundtouch.set_output_block_frames(Some(1000));
+    check(&mut soundtouch, 2500, "blocks");
+    assert!(soundtouch.available_frames() >= 3000);
+}
+
+#[test]
+fn bypass() {
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.0);
+    soundtouch.set_bypass_when_neutral(true);
+    assert_eq!(soundtouch.input_needed_for_output(1000), 1000);
+    check(&mut soundtouch, 1000, "bypass");
+}
+
+#[test]
+fn staging() {
+    let mut soundtouch = stretcher(CHANNELS as u32, 44100, 1.25);
+    soundtouch.set_input_staging_frames(Some(3000));
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 1000)
+            .channels(CHANNELS as u32)
+            .into_vec(),
+        1000,
+    );
+    check(&mut soundtouch, 5000, "staging");
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use soundtouch::testing::{
+    arbitrary_config, arbitrary_samples, check_long_run_drift, check_roundtrip_invariants,
+    drift_bound, InvariantViolation, Rng,
+};
+use soundtouch::Config;
+
+#[test]
+fn arbitrary_configs_keep_invariants() {
+    let mut rng = Rng::new(0x5eed_1234);
+    for case in 0..150 {
+        let config = arbitrary_config(&mut rng);
+        assert_eq!(config.validate(), Ok(()));
+        let samples = arbitrary_samples(&mut rng, config.channels(), 30_000);
+        if let Err(violation) = check_roundtrip_invariants(&config, &samples) {
+            panic!(
+                "case {case}: {violation}\n{config:?}, {} samples",
+                samples.len()
+            );
+        }
+    }
+}
+
+#[test]
+fn edge_case_lengths() {
+    for channels in [1, 2, 6] {
+        let mut config = Config::new();
+        config.set_channels(channels).set_tempo(1.7).set_pitch(0.8);
+        let channels = channels as usize;
+        for frames in [0, 1, 2, 100, 1000] {
+            let mut rng = Rng::new(frames as u64 + 1);
+            let mut samples: Vec<f32> = (0..frames * channels)
+                .map(|_| rng.float(-1.0, 1.0) as f32)
+                .collect();
+            check_roundtrip_invariants(&config, &samples).unwrap();
+            // A trailing partial frame is ignored.
+            samples.push(0.5);
+            check_roundtrip_invariants(&config, &samples).unwrap();
+        }
+    }
+}
+
+#[test]
+fn generators_are_deterministic() {
+    let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+    assert_eq!(arbitrary_config(&mut a), arbitrary_config(&mut b));
+    assert_eq!(
+        arbitrary_samples(&mut a, 2, 5000),
+        arbitrary_samples(&mut b, 2, 5000)
+    );
+}
+
+#[test]
+fn drift_stays_bounded_over_hours() {
+    // Two simulated hours at a low rate, through a flush or clear and a dozen tempo
+    // changes; a day only takes longer.
+    let mut config = Config::new();
+    config.set_channels(1).set_sample_rate(8000).set_tempo(1.1);
+    let report = check_long_run_drift(&config, Duration::from_secs(2 * 3600), 3)
+        .unwrap_or_else(|violation| panic!("{violation}"));
+    assert!(report.expected_output_frames > 8000 * 3600, "{report:?}");
+    assert_eq!(report.pending_output_frames, 0);
+    assert!(drift_bound(&config) < 8000 / 2);
+}
+
+#[test]
+fn violation_messages() {
+    let violation = InvariantViolation::Length {
+        expected: 100.0,
+        actual: 90,
+    };
+    assert_eq!(violation.to_string(), "90 frames out, expected 100");
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use common::process;
+use soundtouch::io::{pipe, pipe_with_bpm_analysis, BpmAnalysis, PipeStats, SampleFormat};
+use soundtouch::{signal, Config, PipeError, SoundTouch, SoundTouchError};
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.25);
+    config
+}
+
+fn to_bytes(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect()
+}
+
+fn from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Hands out its bytes a few at a time, splitting samples and frames.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len()).min(7);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+/// Accepts `capacity` bytes, then fails like a pipe whose reader has exited.
+struct ClosingPipe {
+    capacity: usize,
+}
+
+impl Write for ClosingPipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.capacity == 0 {
+            return Err(ErrorKind::BrokenPipe.into());
+        }
+        let n = buf.len().min(self.capacity);
+        self.capacity -= n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn output_matches_generate_audio() {
+    let input = signal::sine(220.0, 44100, 44100).stereo().into_vec();
+    let mut output = Vec::new();
+    let stats = pipe(
+        &to_bytes(&input)[..],
+        &mut output,
+        &config(),
+        SampleFormat::F32Le,
+    )
+    .unwrap();
+
+    let expected = process(&mut SoundTouch::with_config(&config()), &input);
+    assert_eq!(from_bytes(&output), expected);
+    assert_eq!(
+        stats,
+        PipeStats {
+            frames_in: 44100,
+            frames_out: (expected.len() / 2) as u64,
+            trailing_bytes: 0,
+            output_closed: false,
+        }
+    );
+    assert!((stats.frames_out as f64 - 44100.0 / 1.25).abs() <= 2.0);
+}
+
+#[test]
+fn reads_split_anywhere() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut bytes = to_bytes(&input);
+    let mut whole = Vec::new();
+    pipe(&bytes[..], &mut whole, &config(), SampleFormat::F32Le).unwrap();
+
+    // A truncated last frame is dropped.
+    bytes.extend_from_slice(&[0; 5]);
+    let mut split = Vec::new();
+    let stats = pipe(Trickle(&bytes), &mut split, &config(), SampleFormat::F32Le).unwrap();
+    assert_eq!(split, whole);
+    assert_eq!(stats.frames_in, 20_000);
+    assert_eq!(stats.trailing_bytes, 5);
+}
+
+#[test]
+fn s16_output_length() {
+    let input: Vec<u8> = signal::sine(220.0, 44100, 8000)
+        .flat_map(|sample| ((sample * 32767.0) as i16).to_le_bytes())
+        .collect();
+    let mut config = config();
+    config.set_channels(1).set_tempo(0.5);
+    let mut output = Vec::new();
+    let stats = pipe(&input[..], &mut output, &config, SampleFormat::S16Le).unwrap();
+    assert_eq!(stats.frames_in, 8000);
+    assert_eq!(output.len() as u64, stats.frames_out * 2);
+    assert!((stats.frames_out as f64 - 16000.0).abs() <= 2.0);
+}
+
+#[test]
+fn closed_output_ends_cleanly() {
+    let input = to_bytes(&signal::sine(220.0, 44100, 44100).stereo().into_vec());
+    let writer = ClosingPipe { capacity: 10_000 };
+    let stats = pipe(&input[..], writer, &config(), SampleFormat::F32Le).unwrap();
+    assert!(stats.output_closed);
+    assert!(stats.frames_out * 8 <= 10_000);
+}
+
+#[test]
+fn invalid_config_is_an_error() {
+    let mut config = config();
+    config.set_channels(0);
+    let result = pipe(&[][..], io::sink(), &config, SampleFormat::F32Le);
+    assert!(matches!(
+        result,
+        Err(PipeError::Config(SoundTouchError::InvalidChannels(0)))
+    ));
+}
+
+#[test]
+fn read_errors_are_returned() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(ErrorKind::PermissionDenied.into())
+        }
+    }
+    let result = pipe(Failing, io::sink(), &config(), SampleFormat::F32Le);
+    assert!(matches!(result, Err(PipeError::Io(err)) if err.kind() == ErrorKind::PermissionDenied));
+}
+
+#[test]
+fn bpm_analysis_of_the_input() {
+    let input = common::click_track(2, 44100, 120.0, 20.0, 0.8);
+    let bytes = to_bytes(&input);
+    let mut analysis = BpmAnalysis::new();
+    assert_eq!(analysis.current_bpm_estimate(), None);
+
+    let mut output = Vec::new();
+    let stats = pipe_with_bpm_analysis(
+        Trickle(&bytes),
+        &mut output,
+        &config(),
+        SampleFormat::F32Le,
+        &mut analysis,
+    )
+    .unwrap();
+    // The tap doesn't change the output.
+    let mut plain = Vec::new();
+    assert_eq!(
+        pipe(&bytes[..], &mut plain, &config(), SampleFormat::F32Le).unwrap(),
+        stats
+    );
+    assert_eq!(output, plain);
+
+    // The input is analyzed before it is stretched.
+    let estimate = analysis.current_bpm_estimate().unwrap();
+    assert!((estimate - 120.0).abs() < 1.0, "{estimate}");
+    let result = analysis.take_analysis().unwrap();
+    assert_eq!(result.bpm, Some(estimate));
+    assert_eq!(analysis.take_analysis(), None);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, BufferLevels, SoundTouch};
+
+#[test]
+fn levels_follow_put_and_receive() {
+    let mut st = stretcher(2, 44100, 1.1);
+    assert_eq!(st.buffer_levels(), BufferLevels::default());
+
+    st.put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100);
+    let levels = st.buffer_levels();
+    assert_eq!(levels.unprocessed_frames, st.num_unprocessed_samples());
+    assert!(levels.unprocessed_frames > 0 && levels.unprocessed_frames <= 100);
+    assert_eq!(levels.ready_frames, 0);
+
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    let levels = st.buffer_levels();
+    assert_eq!(levels.unprocessed_frames, st.num_unprocessed_samples());
+    assert_eq!(levels.ready_frames, st.num_samples() as usize);
+    assert!(levels.ready_frames > 0);
+    assert!(levels.unprocessed_frames < 20_100);
+
+    let mut buffer = [0.0; 200];
+    assert_eq!(st.receive_samples(&mut buffer, 100), 100);
+    let after = st.buffer_levels();
+    assert_eq!(after.ready_frames, levels.ready_frames - 100);
+    assert_eq!(after.unprocessed_frames, levels.unprocessed_frames);
+
+    st.flush();
+    assert_eq!(st.buffer_levels().unprocessed_frames, 0);
+    st.clear();
+    assert_eq!(st.buffer_levels(), BufferLevels::default());
+}
+
+#[test]
+fn debug_shows_levels() {
+    let mut st = stretcher(2, 44100, 1.1);
+    st.put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100);
+    let debug = format!("{st:?}");
+    assert!(debug.contains("channels: 2"), "{debug}");
+    assert!(debug.contains("tempo: 1.1"), "{debug}");
+    let unprocessed = st.num_unprocessed_samples();
+    assert!(
+        debug.contains(&format!("unprocessed_frames: {unprocessed}")),
+        "{debug}"
+    );
+    assert!(debug.contains("ready_frames: 0"), "{debug}");
+}
+
+#[test]
+fn frame_queries() {
+    for channels in [1, 2, 5] {
+        let mut st = SoundTouch::new();
+        st.set_channels(channels)
+            .set_sample_rate(44100)
+            .set_rate(1.2);
+        let input = signal::sine(220.0, 44100, 30_000)
+            .channels(channels)
+            .into_vec();
+        st.put_samples(&input, 30_000);
+        assert_eq!(st.pending_input_frames(), st.num_unprocessed_samples());
+        assert_eq!(st.available_frames(), st.num_samples() as usize);
+
+        let available = st.available_frames();
+        assert!(available > 0);
+        assert_eq!(available * channels as usize, common::drain(&mut st).len());
+        assert_eq!(st.available_frames(), 0);
+
+        st.flush();
+        let available = st.available_frames();
+        assert_eq!(available * channels as usize, common::drain(&mut st).len());
+        assert_eq!(st.pending_input_frames(), 0);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{ConfigLint, Setting, SoundTouch};
+
+fn names(soundtouch: &SoundTouch) -> Vec<&'static str> {
+    soundtouch.lint().iter().map(ConfigLint::name).collect()
+}
+
+#[test]
+fn defaults_are_clean() {
+    assert!(stretcher(2, 44100, 1.0).lint().is_empty());
+    assert!(SoundTouch::new().lint().is_empty());
+}
+
+#[test]
+fn aliasing_rate() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch
+        .set_setting(Setting::UseAaFilter, 0)
+        .set_rate(2.5);
+    assert_eq!(soundtouch.lint(), [ConfigLint::AliasingRate { rate: 2.5 }]);
+
+    // The pitch contributes to the effective rate.
+    soundtouch.set_rate(1.5).set_pitch(0.3);
+    assert_eq!(names(&soundtouch), ["aliasing-rate"]);
+    soundtouch.set_pitch(1.0);
+    assert!(soundtouch.lint().is_empty());
+    soundtouch.set_rate_change(-60.0);
+    assert_eq!(names(&soundtouch), ["aliasing-rate"]);
+
+    // Within range, or with the filter on, it doesn't alias.
+    soundtouch.set_rate(2.0);
+    assert!(soundtouch.lint().is_empty());
+    soundtouch.set_rate(0.5);
+    assert!(soundtouch.lint().is_empty());
+    soundtouch
+        .set_rate(3.0)
+        .set_setting(Setting::UseAaFilter, 1);
+    assert!(soundtouch.lint().is_empty());
+}
+
+#[test]
+fn small_quickseek_window() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch.set_setting(Setting::SeekwindowMs, 5);
+    assert!(soundtouch.lint().is_empty());
+    soundtouch.set_setting(Setting::UseQuickseek, 1);
+    assert_eq!(
+        soundtouch.lint(),
+        [ConfigLint::SmallQuickseekWindow { seek_window_ms: 5 }]
+    );
+    soundtouch.set_setting(Setting::SeekwindowMs, ConfigLint::MIN_QUICKSEEK_WINDOW_MS);
+    assert!(soundtouch.lint().is_empty());
+    // The automatic window isn't reported.
+    soundtouch.set_setting(Setting::SeekwindowMs, 0);
+    assert!(soundtouch.lint().is_empty());
+}
+
+#[test]
+fn overlap_not_shorter_than_sequence() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch
+        .set_setting(Setting::SequenceMs, 20)
+        .set_setting(Setting::OverlapMs, 20);
+    assert_eq!(
+        soundtouch.lint(),
+        [ConfigLint::OverlapNotShorterThanSequence {
+            overlap_ms: 20,
+            sequence_ms: 20
+        }]
+    );
+    soundtouch.set_setting(Setting::OverlapMs, 19);
+    assert!(soundtouch.lint().is_empty());
+    // The automatic sequence length isn't reported.
+    soundtouch
+        .set_setting(Setting::OverlapMs, 30)
+        .set_setting(Setting::SequenceMs, 0);
+    assert!(soundtouch.lint().is_empty());
+}
+
+#[test]
+fn low_sample_rate() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch.set_sample_rate(4000);
+    assert_eq!(
+        soundtouch.lint(),
+        [ConfigLint::LowSampleRate { sample_rate: 4000 }]
+    );
+    soundtouch.set_sample_rate(ConfigLint::MIN_SAMPLE_RATE);
+    assert!(soundtouch.lint().is_empty());
+}
+
+#[test]
+fn lints_combine() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    soundtouch
+        .set_sample_rate(6000)
+        .set_rate(0.25)
+        .set_setting(Setting::UseAaFilter, 0);
+    assert_eq!(names(&soundtouch), ["aliasing-rate", "low-sample-rate"]);
+    for lint in soundtouch.lint() {
+        assert!(!lint.to_string().is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use soundtouch::loops::{loop_frames, stretch_loop};
+
+/// The largest step between consecutive frames of `samples`, over all channels.
+fn max_step(samples: &[f32], channels: usize) -> f32 {
+    samples
+        .windows(2 * channels)
+        .step_by(channels)
+        .flat_map(|w| (0..channels).map(move |c| (w[channels + c] - w[c]).abs()))
+        .fold(0.0, f32::max)
+}
+
+fn stereo(mono: &[f32]) -> Vec<f32> {
+    mono.iter().flat_map(|&s| [s, s]).collect()
+}
+
+#[test]
+fn loop_frames_rounds_to_the_nearest_frame() {
+    assert_eq!(loop_frames(4, 120.0, 44100), 88200);
+    assert_eq!(loop_frames(3, 70.0, 44100), 113400);
+    assert_eq!(loop_frames(1, 128.0, 48000), 22500);
+    assert_eq!(loop_frames(1, 97.0, 44100), 27278);
+}
+
+#[test]
+fn repeated_loops_keep_time_without_clicks() {
+    // Four beats at 120 BPM of a tone that loops seamlessly: 440 whole cycles.
+    let source = stereo(&common::tone(220.0, 44100, 88200));
+    for target_bpm in [90.0, 100.0, 128.0, 150.0] {
+        let stretched = stretch_loop(&source, 2, 44100, Some(120.0), target_bpm, 4);
+        let frames = loop_frames(4, target_bpm, 44100);
+        assert_eq!(stretched.len(), frames * 2, "{target_bpm} BPM");
+
+        let looped = stretched.repeat(3);
+        assert_eq!(looped.len(), 3 * frames * 2);
+        let body = max_step(&stretched, 2);
+        for seam in [frames, 2 * frames] {
+            let step = max_step(&looped[(seam - 1) * 2..(seam + 1) * 2], 2);
+            assert!(
+                step <= 2.0 * body,
+                "{target_bpm} BPM: step of {step} at the seam, at most {body} elsewhere"
+            );
+        }
+    }
+}
+
+#[test]
+fn only_the_loop_is_stretched() {
+    // Two beats at 120 BPM followed by a loud tail that isn't part of the loop.
+    let mut source = stereo(&common::tone(220.0, 44100, 44100));
+    source.extend(std::iter::repeat_n(0.9, 2 * 20_000));
+    let stretched = stretch_loop(&source, 2, 44100, Some(120.0), 120.0, 2);
+    assert_eq!(stretched.len(), 44100 * 2);
+    assert!(stretched.iter().all(|s| s.abs() < 0.6));
+}
+
+#[test]
+fn source_tempo_is_detected() {
+    let source = common::click_track(2, 44100, 120.0, 8.0, 0.8);
+    let stretched = stretch_loop(&source, 2, 44100, None, 100.0, 16);
+    assert_eq!(stretched.len(), loop_frames(16, 100.0, 44100) * 2);
+    assert!(stretched.iter().any(|s| s.abs() > 0.1));
+}
+
+#[test]
+#[should_panic(expected = "at least one beat")]
+fn zero_beats_panic() {
+    stretch_loop(&[0.0; 200], 2, 44100, Some(120.0), 100.0, 0);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{process_streaming, signal, LoudnessReport};
+
+fn loudness_of(channels: u32, sample_rate: u32, samples: &[f32]) -> LoudnessReport {
+    let mut soundtouch = stretcher(channels, sample_rate, 1.25);
+    soundtouch.set_analyze_loudness(true);
+    soundtouch.generate_audio(samples);
+    soundtouch.loudness().unwrap()
+}
+
+fn scaled(samples: &[f32], gain: f32) -> Vec<f32> {
+    samples.iter().map(|sample| gain * sample).collect()
+}
+
+#[test]
+fn off_by_default() {
+    let mut soundtouch = stretcher(1, 48000, 1.0);
+    soundtouch.generate_audio(&signal::sine(997.0, 48000, 48000).into_vec());
+    assert_eq!(soundtouch.loudness(), None);
+}
+
+#[test]
+fn sine_levels() {
+    // A 1 kHz sine on one channel reads 3.01 dB below its peak level in dBFS.
+    let sine = signal::sine(997.0, 48000, 10 * 48000).into_vec();
+    for gain in [1.0, 0.5, 0.1] {
+        let level = 20.0 * (0.5 * gain).log10();
+        let report = loudness_of(1, 48000, &scaled(&sine, gain));
+        // The samples miss the crests by up to 0.2%.
+        assert!(
+            (report.peak / (0.5 * gain) - 1.0).abs() < 0.01,
+            "{report:?}"
+        );
+        assert!((report.rms_db - (level - 3.01)).abs() < 0.01, "{report:?}");
+        assert!(
+            (report.approx_lufs - (level - 3.01)).abs() < 0.1,
+            "{report:?}"
+        );
+        assert_eq!(report.frames_measured, 10 * 48000);
+
+        // The channels add up.
+        let stereo: Vec<f32> = scaled(&sine, gain).iter().flat_map(|&s| [s, s]).collect();
+        let report = loudness_of(2, 48000, &stereo);
+        assert!((report.rms_db - (level - 3.01)).abs() < 0.01, "{report:?}");
+        assert!((report.approx_lufs - level).abs() < 0.1, "{report:?}");
+    }
+}
+
+#[test]
+fn filter_is_designed_for_the_sample_rate() {
+    let at = |sample_rate| {
+        let sine = signal::sine(997.0, sample_rate, 10 * sample_rate as usize).into_vec();
+        loudness_of(1, sample_rate, &sine).approx_lufs
+    };
+    let reference = at(48000);
+    for sample_rate in [22050, 44100, 96000] {
+        assert!(
+            (at(sample_rate) - reference).abs() < 0.1,
+            "{sample_rate} Hz"
+        );
+    }
+}
+
+#[test]
+fn pink_noise_levels() {
+    // Within 3 dB of the level, as pink noise has as much energy in the octaves the
+    // K-weighting raises as in those it lowers.
+    let noise = signal::pink_noise(3, 44100, 20 * 44100).into_vec();
+    let report = loudness_of(1, 44100, &noise);
+    assert!(
+        (report.approx_lufs - report.rms_db).abs() < 3.0,
+        "{report:?}"
+    );
+
+    let quieter = loudness_of(1, 44100, &scaled(&noise, 0.5));
+    let step = 20.0 * 0.5f32.log10();
+    assert!((quieter.rms_db - report.rms_db - step).abs() < 0.01);
+    assert!((quieter.approx_lufs - report.approx_lufs - step).abs() < 0.02);
+}
+
+#[test]
+fn silence_is_gated_out() {
+    let sine = signal::sine(997.0, 48000, 10 * 48000).into_vec();
+    let mut with_silence = sine.clone();
+    with_silence.resize(2 * sine.len(), 0.0);
+    let (loud, gated) = (
+        loudness_of(1, 48000, &sine),
+        loudness_of(1, 48000, &with_silence),
+    );
+    assert!((gated.rms_db - loud.rms_db + 3.01).abs() < 0.01);
+    assert!((gated.approx_lufs - loud.approx_lufs).abs() < 0.2);
+
+    let silent = loudness_of(1, 48000, &vec![0.0; 48000]);
+    assert_eq!(silent.peak, 0.0);
+    assert_eq!(silent.rms_db, f32::NEG_INFINITY);
+    assert_eq!(silent.approx_lufs, f32::NEG_INFINITY);
+}
+
+#[test]
+fn short_input_has_no_gated_loudness() {
+    let sine = signal::sine(997.0, 48000, 48000 * 39 / 100).into_vec();
+    let report = loudness_of(1, 48000, &sine);
+    assert_eq!(report.approx_lufs, f32::NEG_INFINITY);
+    assert!(report.rms_db.is_finite());
+    assert_eq!(report.frames_measured, sine.len() as u64);
+}
+
+#[test]
+fn output_is_unchanged() {
+    let input = signal::pink_noise(5, 44100, 5 * 44100).stereo().into_vec();
+    let mut plain = stretcher(2, 44100, 1.25);
+    let mut analyzed = stretcher(2, 44100, 1.25);
+    analyzed.set_analyze_loudness(true);
+    assert_eq!(
+        plain.generate_audio(&input),
+        analyzed.generate_audio(&input)
+    );
+}
+
+#[test]
+fn streaming_matches_generate_audio() {
+    let input = signal::pink_noise(5, 44100, 5 * 44100).stereo().into_vec();
+    let expected = loudness_of(2, 44100, &input);
+
+    let mut soundtouch = stretcher(2, 44100, 1.25);
+    soundtouch.set_analyze_loudness(true);
+    let mut remaining = &input[..];
+    process_streaming(
+        &mut soundtouch,
+        |buffer| {
+            let len = buffer.len().min(remaining.len());
+            buffer[..len].copy_from_slice(&remaining[..len]);
+            remaining = &remaining[len..];
+            len / 2
+        },
+        |_| {},
+        1000,
+    );
+    assert_eq!(soundtouch.loudness(), Some(expected));
+}
+
+#[test]
+fn disabling_discards_the_measurement() {
+    let mut soundtouch = stretcher(1, 48000, 1.25);
+    soundtouch.set_analyze_loudness(true);
+    soundtouch.generate_audio(&signal::sine(997.0, 48000, 48000).into_vec());
+    soundtouch.set_analyze_loudness(true);
+    assert_eq!(soundtouch.loudness().unwrap().frames_measured, 48000);
+    soundtouch
+        .set_analyze_loudness(false)
+        .set_analyze_loudness(true);
+    assert_eq!(soundtouch.loudness().unwrap().frames_measured, 0);
+}
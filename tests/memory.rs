@@ -0,0 +1,106 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouch, SoundTouchError};
+
+const CHUNK: usize = 1024;
+
+#[test]
+fn memory_usage_grows_with_a_stalled_consumer() {
+    let mut soundtouch = stretcher(2, 44100, 0.5);
+    let idle = soundtouch.approx_memory_usage();
+    assert!(idle >= std::mem::size_of::<SoundTouch>());
+
+    let input = signal::sine(220.0, 44100, CHUNK).stereo().into_vec();
+    for _ in 0..50 {
+        soundtouch.put_samples(&input, CHUNK);
+    }
+    let ready = soundtouch.available_frames();
+    assert!(ready > 50 * CHUNK);
+    let stalled = soundtouch.approx_memory_usage();
+    assert!(stalled >= idle + ready * 2 * 4, "{idle} -> {stalled} bytes");
+
+    common::drain(&mut soundtouch);
+    assert!(soundtouch.approx_memory_usage() < stalled);
+}
+
+#[test]
+fn cap_holds_with_a_stalled_consumer() {
+    for max_frames in [0, 5000, 40_000] {
+        let mut soundtouch = stretcher(2, 44100, 0.5);
+        soundtouch.set_max_pending_output_frames(Some(max_frames));
+        assert_eq!(soundtouch.max_pending_output_frames(), Some(max_frames));
+        let requirements = soundtouch.buffer_requirements();
+        let ratio = soundtouch.get_input_output_sample_ratio();
+        let bound = max_frames
+            + ((CHUNK + requirements.initial_latency_frames) as f64 * ratio) as usize
+            + requirements.nominal_output_frames;
+
+        let input = signal::sine(220.0, 44100, CHUNK).stereo().into_vec();
+        let mut puts = 0;
+        let err = loop {
+            match soundtouch.try_put_samples(&input, CHUNK) {
+                Ok(()) => puts += 1,
+                Err(err) => break err,
+            }
+            let ready = soundtouch.available_frames();
+            assert!(ready <= bound, "{ready} frames ready, cap {max_frames}");
+            assert!(puts < 1000, "never pushed back");
+        };
+        let ready = soundtouch.available_frames();
+        assert_eq!(
+            err,
+            SoundTouchError::Backpressure {
+                ready_frames: ready,
+                max_frames
+            }
+        );
+        assert!(ready > max_frames);
+
+        // Receiving lets input in again, and flushing isn't capped.
+        common::drain(&mut soundtouch);
+        soundtouch.put_samples(&input, CHUNK);
+        soundtouch.flush();
+        assert!(!common::drain(&mut soundtouch).is_empty());
+    }
+}
+
+#[test]
+#[should_panic(expected = "more than the cap of 10")]
+fn put_samples_panics_over_the_cap() {
+    let mut soundtouch = stretcher(2, 44100, 0.5);
+    soundtouch.set_max_pending_output_frames(Some(10));
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    soundtouch.put_samples(&input, 20_000);
+    soundtouch.put_samples(&input, 20_000);
+}
+
+#[test]
+fn other_inputs_are_capped() {
+    let mut soundtouch = stretcher(2, 44100, 0.5);
+    soundtouch.set_max_pending_output_frames(Some(10));
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    soundtouch.put_samples(&input, 20_000);
+    assert!(matches!(
+        soundtouch.try_put_iter(input.iter().copied()),
+        Err(SoundTouchError::Backpressure { .. })
+    ));
+    // Empty input is always fine.
+    soundtouch.put_samples(&[], 0);
+}
+
+#[test]
+fn receiving_adapters_work_under_the_cap() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 0.5).generate_audio(&input);
+    let mut capped = stretcher(2, 44100, 0.5);
+    capped.set_max_pending_output_frames(Some(0));
+    assert!(capped.generate_audio(&input) == expected);
+}
+
+#[test]
+fn reset_removes_the_cap() {
+    let mut soundtouch = stretcher(2, 44100, 0.5);
+    soundtouch.set_max_pending_output_frames(Some(100)).reset();
+    assert_eq!(soundtouch.max_pending_output_frames(), None);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{drain, stretcher, tone};
+use soundtouch::{signal, Meter};
+
+#[test]
+fn off_by_default() {
+    let mut st = stretcher(2, 44100, 1.1);
+    assert!(!st.is_metering());
+    st.generate_audio(&signal::sine(220.0, 44100, 10_000).stereo().into_vec());
+    assert_eq!(st.meter(), Meter::default());
+}
+
+#[test]
+fn matches_the_received_output() {
+    let mut st = stretcher(2, 44100, 1.1);
+    st.set_metering(true);
+    let output = st.generate_audio(&signal::sine(220.0, 44100, 30_000).stereo().into_vec());
+
+    let meter = st.meter();
+    let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let sum_of_squares: f64 = output.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+    let rms = (sum_of_squares / output.len() as f64).sqrt() as f32;
+    assert_eq!(meter.frames_measured, output.len() as u64 / 2);
+    assert_eq!(meter.peak, peak);
+    assert!((meter.rms - rms).abs() < 1e-6, "{} vs {rms}", meter.rms);
+}
+
+#[test]
+fn sine_amplitude() {
+    let mut st = stretcher(1, 44100, 1.1);
+    st.put_samples(&tone(441.0, 44100, 5 * 44100), 5 * 44100);
+    // Leave out the start of the stream.
+    st.skip_samples(4410);
+    st.set_metering(true);
+    drain(&mut st);
+
+    let meter = st.meter();
+    assert!(meter.frames_measured > 3 * 44100);
+    assert!((meter.peak - 0.5).abs() < 0.01, "peak {}", meter.peak);
+    let rms = 0.5 / 2f32.sqrt();
+    assert!((meter.rms - rms).abs() < 0.01 * rms, "rms {}", meter.rms);
+}
+
+#[test]
+fn reset() {
+    let mut st = stretcher(2, 44100, 1.1);
+    st.set_metering(true);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    drain(&mut st);
+    assert!(st.meter().frames_measured > 0);
+
+    st.reset_meter();
+    assert_eq!(st.meter(), Meter::default());
+
+    // Disabling keeps what was measured.
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    drain(&mut st);
+    let meter = st.meter();
+    st.set_metering(false);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    drain(&mut st);
+    assert_eq!(st.meter(), meter);
+
+    st.set_metering(true);
+    st.reset();
+    assert!(!st.is_metering());
+    assert_eq!(st.meter(), Meter::default());
+}
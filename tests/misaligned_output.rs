@@ -0,0 +1,98 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::units::Frames;
+use soundtouch::{signal, SoundTouch, SoundTouchError};
+
+/// Returns an instance of `channels` channels with output ready.
+fn primed(channels: u32) -> SoundTouch {
+    let mut soundtouch = stretcher(channels, 44100, 1.2);
+    let input = signal::sine(220.0, 44100, 20_000)
+        .channels(channels)
+        .into_vec();
+    soundtouch.put_samples(&input, 20_000);
+    assert!(soundtouch.available_frames() > 1000);
+    soundtouch
+}
+
+#[test]
+fn odd_stereo_buffer_is_rejected() {
+    let mut soundtouch = primed(2);
+    let available = soundtouch.available_frames();
+    let mut buffer = vec![0.0; 4095];
+    assert_eq!(
+        soundtouch.try_receive_samples(&mut buffer, buffer.len() / 2),
+        Err(SoundTouchError::MisalignedOutput {
+            len: 4095,
+            channels: 2
+        })
+    );
+    assert_eq!(soundtouch.available_frames(), available);
+    assert!(buffer.iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn six_channel_buffer_is_rejected() {
+    let mut soundtouch = primed(6);
+    for len in [4096, 6 * 100 + 1, 6 * 100 + 5] {
+        assert_eq!(
+            soundtouch.try_receive_samples(&mut vec![0.0; len], len / 6),
+            Err(SoundTouchError::MisalignedOutput { len, channels: 6 })
+        );
+    }
+    assert_eq!(
+        soundtouch.try_receive_samples(&mut [0.0; 600], 100),
+        Ok(100)
+    );
+}
+
+#[test]
+#[should_panic(expected = "output of 4095 samples isn't a whole number of frames of 2 channels")]
+fn receive_samples_panics() {
+    primed(2).receive_samples(&mut [0.0; 4095], 1000);
+}
+
+#[test]
+fn nothing_requested() {
+    let mut soundtouch = primed(2);
+    assert_eq!(soundtouch.try_receive_samples(&mut [0.0; 5], 0), Ok(0));
+    assert_eq!(soundtouch.try_receive_samples_f64(&mut [0.0; 5], 0), Ok(0));
+}
+
+#[test]
+fn f64_buffer_is_rejected() {
+    let mut soundtouch = primed(2);
+    assert_eq!(
+        soundtouch.try_receive_samples_f64(&mut [0.0; 4095], 2047),
+        Err(SoundTouchError::MisalignedOutput {
+            len: 4095,
+            channels: 2
+        })
+    );
+}
+
+#[test]
+fn receive_frames_uses_the_whole_frames() {
+    for (channels, len) in [(2, 4095), (6, 4096)] {
+        let input = signal::sine(220.0, 44100, 30_000)
+            .channels(channels as u32)
+            .into_vec();
+        let expected = common::process(&mut stretcher(channels as u32, 44100, 1.2), &input);
+
+        let mut soundtouch = stretcher(channels as u32, 44100, 1.2);
+        soundtouch.put_samples(&input, 30_000);
+        soundtouch.flush();
+        let whole = len / channels * channels;
+        let mut buffer = vec![9.0; len];
+        let mut output = Vec::new();
+        loop {
+            let status = soundtouch.receive_frames(&mut buffer, Frames(len / channels));
+            if status.frames.0 == 0 {
+                break;
+            }
+            output.extend_from_slice(&buffer[..status.frames.0 * channels]);
+        }
+        assert!(buffer[whole..].iter().all(|&sample| sample == 9.0));
+        assert_eq!(output, expected, "{channels} channels");
+    }
+}
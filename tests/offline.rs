@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use soundtouch::{signal, Config, OfflineStretcher, SoundTouch};
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.set_tempo(1.25).set_pitch(0.9);
+    config
+}
+
+fn input() -> Arc<[f32]> {
+    signal::sine(220.0, 44100, 60_000).stereo().collect()
+}
+
+fn full_run(input: &[f32]) -> Vec<f32> {
+    SoundTouch::with_config(&config()).generate_audio(input)
+}
+
+fn rms(samples: &[f32]) -> f64 {
+    let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+    (sum / samples.len() as f64).sqrt()
+}
+
+#[test]
+fn contiguous_ranges_match_a_full_run() {
+    let input = input();
+    let full = full_run(&input);
+    let frames = full.len() / 2;
+
+    let mut stretcher = OfflineStretcher::new(input, &config());
+    assert!(stretcher.expected_output_frames().abs_diff(48_000) <= 1);
+    let mut stitched = Vec::new();
+    for range in [0..1000, 1000..1001, 1001..17_000, 17_000..frames + 500] {
+        stitched.extend(stretcher.render_range(range));
+    }
+    assert_eq!(stitched, full);
+}
+
+#[test]
+fn overlapping_ranges_reuse_the_output() {
+    let input = input();
+    let full = full_run(&input);
+
+    let mut stretcher = OfflineStretcher::new(input, &config());
+    assert_eq!(stretcher.render_range(0..5000), full[..10_000]);
+    assert_eq!(stretcher.render_range(4000..9000), full[8000..18_000]);
+    assert_eq!(stretcher.render_range(4500..4600), full[9000..9200]);
+    // A little past the end of the previous range, which skips ahead.
+    assert_eq!(stretcher.render_range(9100..12_000), full[18_200..24_000]);
+}
+
+#[test]
+fn jumps_render_the_requested_length() {
+    let input = input();
+    let full = full_run(&input);
+
+    let mut stretcher = OfflineStretcher::new(input, &config());
+    let region = stretcher.render_range(30_000..34_000);
+    assert_eq!(region.len(), 8000);
+    // The splice points differ from the full run's, but the level is the same.
+    let expected = rms(&full[60_000..68_000]);
+    assert!((rms(&region) - expected).abs() < 0.1 * expected);
+
+    // Backwards, and then continuing from there matches the previous jump.
+    let earlier = stretcher.render_range(10_000..12_000);
+    assert_eq!(earlier.len(), 4000);
+    let continued = stretcher.render_range(12_000..13_000);
+    assert_eq!(continued.len(), 2000);
+}
+
+#[test]
+fn past_the_end() {
+    let mut stretcher = OfflineStretcher::new(input(), &config());
+    assert!(stretcher.render_range(100_000..101_000).is_empty());
+    assert!(stretcher.render_range(5..5).is_empty());
+    assert_eq!(stretcher.render_range(0..10).len(), 20);
+}
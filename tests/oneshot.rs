@@ -0,0 +1,159 @@
+mod common;
+
+use std::time::Duration;
+
+use soundtouch::{
+    pitch_shift, signal, time_stretch, time_stretch_to_duration, try_time_stretch_to_duration,
+    SoundTouchError,
+};
+
+#[test]
+fn pitch_shift_keeps_the_length() {
+    for channels in [1, 2, 6] {
+        for frames in [0, 1, 999, 44_100] {
+            for semitones in [-7.0, -0.5, 0.0, 3.0, 12.0] {
+                let input = signal::sine(220.0, 44100, frames)
+                    .channels(channels as u32)
+                    .into_vec();
+                let output = pitch_shift(&input, channels as u32, 44100, semitones);
+                assert_eq!(
+                    output.len(),
+                    input.len(),
+                    "{channels} channels, {frames} frames, {semitones} semitones"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn pitch_shift_changes_the_frequency() {
+    let input = common::tone(440.0, 44100, 44100);
+    for semitones in [-5.0, 7.0, 12.0] {
+        let output = pitch_shift(&input, 1, 44100, semitones);
+        let expected = 440.0 * 2f64.powf(semitones / 12.0);
+        let peak = common::peak_frequency(
+            &output[4096..40_000],
+            44100,
+            expected - 50.0,
+            expected + 50.0,
+            1.0,
+        );
+        assert!(
+            (peak - expected).abs() <= 3.0,
+            "{semitones} semitones: {peak} Hz, expected {expected}"
+        );
+    }
+}
+
+/// Returns the first frame of the mono `samples` louder than `0.1`.
+fn onset(samples: &[f32]) -> usize {
+    samples.iter().position(|s| s.abs() > 0.1).unwrap()
+}
+
+#[test]
+fn pitch_shift_is_aligned() {
+    let mut input = vec![0.0; 10_000];
+    input.extend(common::tone(300.0, 44100, 10_000));
+    for semitones in [-12.0, -3.0, 4.0, 12.0] {
+        let output = pitch_shift(&input, 1, 44100, semitones);
+        let onset = onset(&output);
+        assert!(
+            onset.abs_diff(10_000) <= 441,
+            "{semitones} semitones: onset at {onset}"
+        );
+    }
+}
+
+#[test]
+fn time_stretch_scales_the_length() {
+    for channels in [1, 2] {
+        for frames in [0, 1, 999, 44_100] {
+            for factor in [0.5, 0.9, 1.0, 1.37, 2.0] {
+                let input = signal::sine(220.0, 44100, frames)
+                    .channels(channels as u32)
+                    .into_vec();
+                let output = time_stretch(&input, channels as u32, 44100, factor);
+                let expected = (frames as f64 * factor).round() as usize;
+                assert_eq!(
+                    output.len(),
+                    expected * channels,
+                    "{channels} channels, {frames} frames, factor {factor}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn time_stretch_keeps_the_frequency_and_alignment() {
+    let mut input = vec![0.0; 10_000];
+    input.extend(common::tone(440.0, 44100, 44100));
+    for factor in [0.75, 1.5] {
+        let output = time_stretch(&input, 1, 44100, factor);
+        let onset = onset(&output);
+        let expected = 10_000.0 * factor;
+        assert!(
+            (onset as f64 - expected).abs() <= 441.0,
+            "factor {factor}: onset at {onset}, expected {expected}"
+        );
+        let peak = common::peak_frequency(
+            &output[onset + 4096..onset + 30_000],
+            44100,
+            400.0,
+            480.0,
+            1.0,
+        );
+        assert!((peak - 440.0).abs() <= 3.0, "factor {factor}: {peak} Hz");
+    }
+}
+
+#[test]
+fn time_stretch_to_duration_is_exact() {
+    let input = signal::sine(220.0, 44100, 44_100).stereo().into_vec();
+    for (millis, frames) in [(500, 22_050), (1005, 44_321), (2300, 101_430)] {
+        let output = time_stretch_to_duration(&input, 2, 44100, Duration::from_millis(millis));
+        assert_eq!(output.len(), frames * 2, "{millis} ms");
+    }
+    let output = time_stretch_to_duration(&input, 2, 44100, Duration::from_micros(1_499_995));
+    assert_eq!(output.len(), 66_150 * 2);
+    assert_eq!(
+        time_stretch_to_duration(&input, 2, 44100, Duration::from_millis(1500)),
+        time_stretch(&input, 2, 44100, 1.5)
+    );
+}
+
+#[test]
+fn try_time_stretch_to_duration_reports_errors() {
+    let second = Duration::from_secs(1);
+    assert_eq!(
+        try_time_stretch_to_duration(&[0.0; 100], 0, 44100, second),
+        Err(SoundTouchError::InvalidChannels(0))
+    );
+    assert_eq!(
+        try_time_stretch_to_duration(&[0.0; 1], 2, 44100, second),
+        Err(SoundTouchError::InputTooShort {
+            frames: 1,
+            channels: 2,
+            len: 1
+        })
+    );
+    for target in [Duration::ZERO, Duration::MAX] {
+        assert!(matches!(
+            try_time_stretch_to_duration(&[0.0; 100], 1, 44100, target),
+            Err(SoundTouchError::ParametersOutOfRange { .. })
+        ));
+    }
+}
+
+#[test]
+#[should_panic]
+fn overflowing_duration_panics() {
+    time_stretch_to_duration(&[0.0; 100], 1, 44100, Duration::MAX);
+}
+
+#[test]
+#[should_panic]
+fn zero_factor_panics() {
+    time_stretch(&[0.0; 100], 1, 44100, 0.0);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use soundtouch::bpm::BeatGrid;
+use soundtouch::onsets::{detect_onsets, Onset, OnsetConfig};
+use soundtouch::{signal, Downmix};
+
+/// Renders decaying 2 kHz hits at `times` seconds over quiet noise.
+fn hits(channels: usize, sample_rate: usize, seconds: f64, times: &[(f64, f32)]) -> Vec<f32> {
+    let frames = (seconds * sample_rate as f64) as usize;
+    let mut mono: Vec<f32> = signal::noise(1, sample_rate as u32, frames)
+        .map(|noise| 0.005 * noise)
+        .collect();
+    for &(time, amplitude) in times {
+        let start = (time * sample_rate as f64) as usize;
+        for (i, sample) in mono[start..].iter_mut().take(sample_rate / 10).enumerate() {
+            let t = i as f32 / sample_rate as f32;
+            *sample += amplitude * (-t * 60.0).exp() * (t * 2000.0 * std::f32::consts::TAU).sin();
+        }
+    }
+    mono.iter()
+        .flat_map(|&sample| std::iter::repeat_n(sample, channels))
+        .collect()
+}
+
+#[test]
+fn finds_impulses_within_a_few_milliseconds() {
+    let times = [0.1, 0.37, 0.5, 1.013, 1.25, 1.9];
+    for (channels, sample_rate) in [(1, 44100), (2, 48000), (6, 22050)] {
+        let input = hits(channels, sample_rate, 2.2, &times.map(|time| (time, 0.8)));
+        let onsets = detect_onsets(
+            &input,
+            channels as u32,
+            sample_rate as u32,
+            OnsetConfig::default(),
+        );
+        let found: Vec<f64> = onsets.iter().map(|onset| onset.time_secs).collect();
+        assert_eq!(found.len(), times.len(), "{found:?}");
+        for (found, expected) in found.iter().zip(times) {
+            assert!(
+                (found - expected).abs() < 0.002,
+                "{channels} channels at {sample_rate} Hz: {found} instead of {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn strengths_are_relative_to_the_strongest() {
+    let input = hits(1, 44100, 1.0, &[(0.2, 0.2), (0.6, 0.8)]);
+    let onsets = detect_onsets(&input, 1, 44100, OnsetConfig::default());
+    assert_eq!(onsets.len(), 2);
+    assert_eq!(onsets[1].strength, 1.0);
+    assert!(
+        (onsets[0].strength - 0.25).abs() < 0.05,
+        "{}",
+        onsets[0].strength
+    );
+
+    let config = OnsetConfig {
+        min_strength: 0.5,
+        ..OnsetConfig::default()
+    };
+    let onsets = detect_onsets(&input, 1, 44100, config);
+    assert_eq!(onsets.len(), 1);
+}
+
+#[test]
+fn close_onsets_keep_the_strongest() {
+    let input = hits(1, 44100, 1.0, &[(0.3, 0.4), (0.32, 0.8)]);
+    let onsets = detect_onsets(&input, 1, 44100, OnsetConfig::default());
+    assert_eq!(onsets.len(), 1);
+    assert!((onsets[0].time_secs - 0.32).abs() < 0.002, "{onsets:?}");
+
+    let config = OnsetConfig {
+        min_interval_ms: 10.0,
+        ..OnsetConfig::default()
+    };
+    assert_eq!(detect_onsets(&input, 1, 44100, config).len(), 2);
+}
+
+#[test]
+fn silence_has_none_and_a_steady_tone_one() {
+    let config = OnsetConfig::default();
+    assert!(detect_onsets(&[0.0; 44100], 1, 44100, config).is_empty());
+    assert!(detect_onsets(&[], 2, 44100, config).is_empty());
+
+    // A steady tone only starts once.
+    let tone = common::tone(440.0, 44100, 44100);
+    let onsets = detect_onsets(&tone, 1, 44100, config);
+    assert_eq!(onsets.len(), 1, "{onsets:?}");
+    assert!(onsets[0].time_secs < 0.001);
+}
+
+#[test]
+fn downmix_selects_the_channel() {
+    let left = hits(1, 44100, 1.0, &[(0.25, 0.8)]);
+    let right = hits(1, 44100, 1.0, &[(0.75, 0.8)]);
+    let stereo: Vec<f32> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(&left, &right)| [left, right])
+        .collect();
+    for (downmix, expected) in [(Downmix::LeftOnly, 0.25), (Downmix::RightOnly, 0.75)] {
+        let config = OnsetConfig {
+            downmix,
+            ..OnsetConfig::default()
+        };
+        let onsets = detect_onsets(&stereo, 2, 44100, config);
+        assert_eq!(onsets.len(), 1, "{downmix:?}");
+        assert!((onsets[0].time_secs - expected).abs() < 0.002);
+    }
+}
+
+#[test]
+fn snapping_to_the_grid() {
+    let grid = BeatGrid::new(120.0, 0.1);
+    assert_eq!(grid.nearest_beat(0.0), (0, 0.1));
+    assert_eq!(grid.nearest_beat(0.58), (1, 0.6));
+    assert_eq!(grid.nearest_beat(0.86), (2, 1.1));
+
+    let onset = |time_secs, strength| Onset {
+        time_secs,
+        strength,
+    };
+    let snapped = grid.snap_onsets(&[
+        onset(0.09, 0.5),
+        onset(0.62, 0.3),
+        onset(0.64, 0.9),
+        onset(1.55, 1.0),
+    ]);
+    assert_eq!(snapped, [onset(0.1, 0.5), onset(0.6, 0.9), onset(1.6, 1.0)]);
+
+    let input = hits(1, 44100, 2.0, &[(0.105, 0.8), (0.595, 0.8), (1.09, 0.8)]);
+    let onsets = detect_onsets(&input, 1, 44100, OnsetConfig::default());
+    let snapped = grid.snap_onsets(&onsets);
+    let times: Vec<f64> = snapped.iter().map(|onset| onset.time_secs).collect();
+    assert_eq!(times, [0.1, 0.6, 1.1]);
+}
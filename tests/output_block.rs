@@ -0,0 +1,106 @@
+mod common;
+
+use common::{process, stretcher};
+use soundtouch::{signal, SoundTouch};
+
+const BLOCK: usize = 960;
+
+/// Checks that `blocked` is `unblocked` followed by `padding` silent frames.
+fn assert_padded(blocked: &[f32], unblocked: &[f32], padding: usize) {
+    assert_eq!(blocked.len(), unblocked.len() + 2 * padding);
+    assert_eq!(&blocked[..unblocked.len()], unblocked);
+    assert!(blocked[unblocked.len()..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn every_batch_is_whole_blocks() {
+    let input = signal::sine(220.0, 44100, 50_000).stereo().into_vec();
+    let unblocked = process(&mut stretcher(2, 48000, 1.1), &input);
+
+    let mut st = stretcher(2, 48000, 1.1);
+    st.set_output_block_frames(Some(BLOCK));
+    let mut output = Vec::new();
+    let mut buffer = vec![0.0; 2 * 5000];
+    let mut receive = |st: &mut SoundTouch, output: &mut Vec<f32>| loop {
+        assert_eq!(st.available_frames() % BLOCK, 0);
+        let frames = st.receive_samples(&mut buffer, 5000);
+        assert_eq!(frames % BLOCK, 0, "batch of {frames} frames");
+        if frames == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..2 * frames]);
+    };
+    for chunk in input.chunks(2 * 777) {
+        st.put_samples(chunk, chunk.len() / 2);
+        receive(&mut st, &mut output);
+    }
+    st.flush();
+    receive(&mut st, &mut output);
+
+    assert_eq!(output.len() % (2 * BLOCK), 0);
+    let padding = st.output_block_padding();
+    assert!(padding < BLOCK);
+    assert_padded(&output, &unblocked, padding);
+}
+
+#[test]
+fn small_requests_get_nothing() {
+    let mut st = stretcher(2, 48000, 1.1);
+    st.set_output_block_frames(Some(BLOCK));
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    assert!(st.available_frames() >= BLOCK);
+    let mut buffer = vec![0.0; 2 * 1500];
+    assert_eq!(st.receive_samples(&mut buffer, BLOCK - 1), 0);
+    assert_eq!(st.receive_samples(&mut buffer, 1500), BLOCK);
+    assert_eq!(st.skip_samples(1500), BLOCK);
+}
+
+#[test]
+fn blocks_longer_than_the_stack_buffer() {
+    let input = signal::sine(220.0, 44100, 50_000).stereo().into_vec();
+    let unblocked = process(&mut stretcher(2, 48000, 1.1), &input);
+
+    let mut st = stretcher(2, 48000, 1.1);
+    st.set_output_block_frames(Some(3000));
+    let mut output = Vec::new();
+    st.process_chunk(&input, &mut output);
+    assert_eq!(output.len() % 6000, 0);
+    st.finish(&mut output);
+    assert_eq!(output.len() % 6000, 0);
+    assert_padded(&output, &unblocked, st.output_block_padding());
+}
+
+#[test]
+fn clear_and_reset() {
+    let mut st = stretcher(2, 48000, 1.1);
+    st.set_output_block_frames(Some(BLOCK));
+    st.put_samples(
+        &signal::sine(220.0, 44100, 10_001).stereo().into_vec(),
+        10_001,
+    );
+    st.flush();
+    assert_eq!(st.available_frames() % BLOCK, 0);
+    st.clear();
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.output_block_padding(), 0);
+    assert_eq!(st.output_block_frames(), Some(BLOCK));
+
+    st.reset();
+    assert_eq!(st.output_block_frames(), None);
+}
+
+#[test]
+fn zero_is_unaligned() {
+    let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    let mut st = stretcher(2, 48000, 1.1);
+    st.set_output_block_frames(Some(0));
+    assert_eq!(st.output_block_frames(), None);
+    assert_eq!(
+        process(&mut st, &input),
+        process(&mut stretcher(2, 48000, 1.1), &input)
+    );
+    assert_eq!(st.output_block_padding(), 0);
+}
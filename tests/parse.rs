@@ -0,0 +1,144 @@
+use soundtouch::{Config, ConfigParseError, ParsePresetError, ParseSettingError, Preset, Setting};
+
+#[test]
+fn setting_names_round_trip() {
+    for setting in Setting::ALL {
+        let name = setting.to_string();
+        assert_eq!(name, setting.name());
+        assert_eq!(name.parse(), Ok(setting));
+        assert_eq!(name.to_uppercase().parse(), Ok(setting));
+        assert_eq!(Setting::from_name(&name), Some(setting));
+    }
+}
+
+#[test]
+fn setting_names_are_kebab_case() {
+    for setting in Setting::ALL {
+        let name = setting.name();
+        assert!(
+            name.chars().all(|c| c.is_ascii_lowercase() || c == '-'),
+            "{name}"
+        );
+    }
+    assert_eq!("sequence-ms".parse(), Ok(Setting::SequenceMs));
+    assert_eq!("Use-AA-Filter".parse(), Ok(Setting::UseAaFilter));
+}
+
+#[test]
+fn unknown_setting() {
+    assert_eq!("sequence_ms".parse::<Setting>(), Err(ParseSettingError));
+    assert_eq!(Setting::from_name(""), None);
+    assert!(ParseSettingError.to_string().contains("overlap-ms"));
+}
+
+#[test]
+fn preset_names_round_trip() {
+    for preset in Preset::ALL {
+        let name = preset.to_string();
+        assert_eq!(name, preset.name());
+        assert_eq!(name.parse(), Ok(preset));
+        assert_eq!(name.to_uppercase().parse(), Ok(preset));
+        assert_eq!(Preset::from_name(&name), Some(preset));
+        assert!(
+            name.chars().all(|c| c.is_ascii_lowercase() || c == '-'),
+            "{name}"
+        );
+    }
+    assert_eq!("Speech".parse(), Ok(Preset::Speech));
+    assert_eq!(Preset::default(), Preset::Default);
+}
+
+#[test]
+fn unknown_preset() {
+    assert_eq!("podcast".parse::<Preset>(), Err(ParsePresetError));
+    assert_eq!(Preset::from_name(""), None);
+    assert!(ParsePresetError.to_string().contains("speech"));
+}
+
+#[test]
+fn presets_are_valid() {
+    for preset in Preset::ALL {
+        assert_eq!(preset.tuning().validate(), Ok(()), "{preset}");
+    }
+    assert_eq!(Preset::Default.tuning(), Default::default());
+    assert_eq!(Preset::Speech.tuning().sequence_ms, Some(40));
+}
+
+#[test]
+fn read_only() {
+    for setting in Setting::ALL {
+        assert_eq!(
+            setting.is_read_only(),
+            matches!(
+                setting,
+                Setting::NominalInputSequence
+                    | Setting::NominalOutputSequence
+                    | Setting::InitialLatency
+            ),
+            "{setting}"
+        );
+    }
+}
+
+#[test]
+fn config_from_pairs() {
+    let config = Config::from_kv_pairs(&[
+        ("channels", "1"),
+        ("Sample-Rate", "48000"),
+        ("tempo", "1.4"),
+        ("pitch", " 0.9 "),
+        ("rate", "1.1"),
+        ("aa-filter-length", "32"),
+        ("use-quickseek", "1"),
+        ("tempo", "0.8"),
+    ])
+    .unwrap();
+    let mut expected = Config::new();
+    expected
+        .set_channels(1)
+        .set_sample_rate(48000)
+        .set_tempo(0.8)
+        .set_pitch(0.9)
+        .set_rate(1.1)
+        .set_setting(Setting::AaFilterLength, 32)
+        .set_setting(Setting::UseQuickseek, 1);
+    assert_eq!(config, expected);
+    assert_eq!(Config::from_kv_pairs(&[]), Ok(Config::new()));
+}
+
+#[test]
+fn config_from_pairs_writable_settings() {
+    for setting in Setting::ALL.into_iter().filter(|s| !s.is_read_only()) {
+        let config = Config::from_kv_pairs(&[(setting.name(), "7")]).unwrap();
+        assert_eq!(config.setting(setting), 7, "{setting}");
+    }
+}
+
+#[test]
+fn config_parse_errors() {
+    assert_eq!(
+        Config::from_kv_pairs(&[("speed", "2")]),
+        Err(ConfigParseError::UnknownKey("speed".into()))
+    );
+    let err = Config::from_kv_pairs(&[("tempo", "fast")]).unwrap_err();
+    assert_eq!(
+        err,
+        ConfigParseError::InvalidValue {
+            key: "tempo".into(),
+            value: "fast".into()
+        }
+    );
+    assert_eq!(err.to_string(), "invalid value `fast` for `tempo`");
+    assert!(matches!(
+        Config::from_kv_pairs(&[("channels", "-1")]),
+        Err(ConfigParseError::InvalidValue { .. })
+    ));
+    assert_eq!(
+        Config::from_kv_pairs(&[("initial-latency", "100")]),
+        Err(ConfigParseError::ReadOnlySetting(Setting::InitialLatency))
+    );
+    assert!(Config::from_kv_pairs(&[("initial-latency", "100")])
+        .unwrap_err()
+        .to_string()
+        .contains("read-only"));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, Sanitize, SoundTouch, SoundTouchError};
+
+/// Splits interleaved `samples` into one plane per channel.
+fn planes(samples: &[f32], channels: usize) -> Vec<Vec<f32>> {
+    (0..channels)
+        .map(|channel| {
+            samples[channel..]
+                .iter()
+                .step_by(channels)
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn matches_interleaved_input() {
+    for channels in [1, 2, 3, 6] {
+        let input = signal::sine(220.0, 44100, 30_000)
+            .channels(channels as u32)
+            .into_vec();
+        let mut interleaved = stretcher(channels as u32, 48000, 1.25);
+        interleaved.set_pitch(0.9);
+        let expected = common::process(&mut interleaved, &input);
+
+        let planes = planes(&input, channels);
+        let mut soundtouch = stretcher(channels as u32, 48000, 1.25);
+        soundtouch.set_pitch(0.9);
+        // Packets of varying length, some longer than the interleaving chunks.
+        let mut offset = 0;
+        for packet in [1, 1152, 4096, 9000].iter().cycle() {
+            let frames = (*packet).min(30_000 - offset);
+            if frames == 0 {
+                break;
+            }
+            let packet: Vec<&[f32]> = planes.iter().map(|plane| &plane[offset..]).collect();
+            soundtouch.put_audio_buffer(&packet, frames).unwrap();
+            offset += frames;
+        }
+        soundtouch.flush();
+        assert_eq!(
+            common::drain(&mut soundtouch),
+            expected,
+            "{channels} channels"
+        );
+    }
+}
+
+#[test]
+fn errors() {
+    let plane = [0.5; 64];
+    assert_eq!(
+        SoundTouch::new().put_audio_buffer(&[&plane], 64),
+        Err(SoundTouchError::NotConfigured)
+    );
+    // An empty put does nothing, even without a configuration.
+    assert_eq!(SoundTouch::new().put_audio_buffer(&[], 0), Ok(()));
+
+    let mut soundtouch = stretcher(2, 48000, 1.25);
+    soundtouch.set_pitch(0.9);
+    assert_eq!(
+        soundtouch.put_audio_buffer(&[&plane], 64),
+        Err(SoundTouchError::ChannelMismatch {
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        soundtouch.put_audio_buffer(&[&plane, &plane[..10]], 64),
+        Err(SoundTouchError::InputTooShort {
+            frames: 64,
+            channels: 1,
+            len: 10
+        })
+    );
+
+    let mut right = plane;
+    right[3] = f32::NAN;
+    let mut left = plane;
+    left[7] = f32::INFINITY;
+    soundtouch.set_input_sanitization(Sanitize::Error);
+    assert_eq!(
+        soundtouch.put_audio_buffer(&[&left, &right], 64),
+        Err(SoundTouchError::NonFiniteInput { index: 7 })
+    );
+    // Frames beyond `frames` aren't looked at.
+    assert_eq!(soundtouch.put_audio_buffer(&[&left, &right], 3), Ok(()));
+    assert_eq!(soundtouch.num_unprocessed_samples(), 3);
+}
@@ -0,0 +1,55 @@
+use std::thread;
+use std::time::Duration;
+
+use soundtouch::{Config, Setting, SoundTouchPool};
+
+#[test]
+fn reuses_reconfigured_instances() {
+    let pool = SoundTouchPool::new(2);
+    let mut config = Config::new();
+    config.set_tempo(2.0).set_setting(Setting::OverlapMs, 16);
+    {
+        let mut soundtouch = pool.get(&config);
+        soundtouch.set_setting(Setting::UseQuickseek, 1);
+        soundtouch.put_samples(&[0.5; 2048], 1024);
+    }
+
+    config.set_tempo(0.5);
+    let mut soundtouch = pool.get(&config);
+    assert_eq!(soundtouch.get_input_output_sample_ratio(), 2.0);
+    assert_eq!(soundtouch.get_setting(Setting::OverlapMs), 16);
+    assert_eq!(soundtouch.get_setting(Setting::UseQuickseek), 0);
+    assert_eq!(soundtouch.num_unprocessed_samples(), 0);
+    assert_eq!(pool.metrics().hits, 1);
+    assert_eq!(pool.metrics().misses, 1);
+}
+
+#[test]
+fn concurrent_checkouts_are_exclusive() {
+    const THREADS: usize = 8;
+    const ROUNDS: usize = 20;
+    let pool = SoundTouchPool::new(4);
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let pool = &pool;
+            scope.spawn(move || {
+                for round in 0..ROUNDS {
+                    let tempo = 1.0 + (t * ROUNDS + round) as f64 / 100.0;
+                    let mut config = Config::new();
+                    config.set_tempo(tempo);
+                    let mut soundtouch = pool.get(&config);
+                    soundtouch.generate_audio(&[0.1; 4096]);
+                    thread::sleep(Duration::from_micros(200));
+                    // Nobody else may have reconfigured the instance in the meantime.
+                    assert_eq!(soundtouch.get_input_output_sample_ratio(), 1.0 / tempo);
+                }
+            });
+        }
+    });
+
+    let metrics = pool.metrics();
+    assert_eq!(metrics.hits + metrics.misses, (THREADS * ROUNDS) as u64);
+    assert!(metrics.misses <= THREADS as u64);
+    assert!(metrics.idle <= pool.max_size());
+}
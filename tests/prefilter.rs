@@ -0,0 +1,103 @@
+use soundtouch::{signal, BPMDetect, BpmResult, Downmix, PrefilterConfig};
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// A 120 BPM kick in white noise of the same RMS level, i.e. at 0 dB SNR.
+fn buried_kick(seconds: usize) -> Vec<f32> {
+    let frames = seconds * SAMPLE_RATE as usize;
+    let kick = signal::click_track(120.0, SAMPLE_RATE, frames).into_vec();
+    let noise = signal::noise(7, SAMPLE_RATE, frames).into_vec();
+    let rms = |samples: &[f32]| {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    };
+    let gain = rms(&kick) / rms(&noise);
+    kick.iter().zip(&noise).map(|(k, n)| k + gain * n).collect()
+}
+
+fn result_with(prefilter: Option<PrefilterConfig>, channels: u32, input: &[f32]) -> BpmResult {
+    let mut bpm = BPMDetect::new(channels, SAMPLE_RATE);
+    bpm.set_prefilter(prefilter);
+    bpm.input_all(input, BPMDetect::RECOMMENDED_CHUNK_FRAMES);
+    bpm.result()
+}
+
+#[test]
+fn prefilter_finds_a_kick_in_noise() {
+    let input = buried_kick(30);
+    let filtered = result_with(Some(PrefilterConfig::new(200.0, 4)), 1, &input);
+    let detected = filtered.bpm.expect("a tempo");
+    assert!((detected - 120.0).abs() < 1.0, "{detected}");
+
+    let unfiltered = result_with(None, 1, &input);
+    let missed = unfiltered.bpm.is_none_or(|bpm| (bpm - 120.0).abs() >= 1.0);
+    assert!(
+        missed || unfiltered.confidence < filtered.confidence,
+        "{unfiltered:?} {filtered:?}"
+    );
+}
+
+#[test]
+fn prefilter_runs_on_the_downmix() {
+    let mono = buried_kick(10);
+    let stereo: Vec<f32> = mono.iter().flat_map(|&s| [s, s]).collect();
+    for order in 1..=PrefilterConfig::MAX_ORDER {
+        let prefilter = Some(PrefilterConfig::new(250.0, order));
+        assert_eq!(
+            result_with(prefilter, 1, &mono),
+            result_with(prefilter, 2, &stereo),
+            "order {order}"
+        );
+    }
+}
+
+#[test]
+fn filter_state_carries_over_chunks() {
+    let input = buried_kick(10);
+    let detect = |chunk_frames| {
+        let mut bpm = BPMDetect::new(1, SAMPLE_RATE);
+        bpm.set_prefilter(Some(PrefilterConfig::default()));
+        bpm.input_all(&input, chunk_frames);
+        bpm.get_bpm()
+    };
+    assert_eq!(detect(input.len()), detect(1000));
+}
+
+#[test]
+fn changing_the_prefilter_restarts_the_analysis() {
+    let input = buried_kick(10);
+    let mut bpm = BPMDetect::new(1, SAMPLE_RATE);
+    assert_eq!(bpm.prefilter(), None);
+    bpm.set_prefilter(Some(PrefilterConfig::default()));
+    assert_eq!(bpm.prefilter(), Some(PrefilterConfig::new(250.0, 2)));
+    bpm.input_samples(&input);
+    assert!(bpm.analysis_duration().as_secs_f64() > 9.9);
+
+    // Setting the same filter again keeps the analysis.
+    bpm.set_prefilter(Some(PrefilterConfig::default()));
+    assert!(bpm.analysis_duration().as_secs_f64() > 9.9);
+
+    // The filter starts from silence again, so the result matches a fresh instance.
+    bpm.set_downmix(Downmix::Mid).set_downmix(Downmix::Average);
+    assert!(bpm.analysis_duration().is_zero());
+    bpm.input_samples(&input);
+    let mut fresh = BPMDetect::new(1, SAMPLE_RATE);
+    fresh.set_prefilter(Some(PrefilterConfig::default()));
+    fresh.input_samples(&input);
+    assert_eq!(bpm.get_bpm(), fresh.get_bpm());
+
+    bpm.set_prefilter(None);
+    assert_eq!(bpm.prefilter(), None);
+    assert!(bpm.analysis_duration().is_zero());
+}
+
+#[test]
+#[should_panic(expected = "invalid prefilter")]
+fn cutoff_above_nyquist_panics() {
+    BPMDetect::new(1, 8000).set_prefilter(Some(PrefilterConfig::new(4000.0, 2)));
+}
+
+#[test]
+#[should_panic(expected = "invalid prefilter")]
+fn order_zero_panics() {
+    BPMDetect::new(1, SAMPLE_RATE).set_prefilter(Some(PrefilterConfig::new(250.0, 0)));
+}
@@ -0,0 +1,31 @@
+use soundtouch::{compare_quickseek, signal, Config};
+
+#[test]
+fn report_is_sane() {
+    let input = signal::sine(220.0, 44100, 44_100).stereo().into_vec();
+    let report = compare_quickseek(&input, Config::new().set_tempo(1.2));
+    assert!(report.lag.unsigned_abs() <= 64);
+    assert!(report.frames > (44_100.0 / 1.2) as usize - 200);
+    assert!(report.reference_rms > 0.1);
+    assert!(report.rms_difference.is_finite());
+    assert!(report.relative_rms_difference() < 1.0);
+    assert!(report.peak_difference >= 0.0);
+    assert!(report.cpu_time_ratio.unwrap() > 0.0);
+}
+
+#[test]
+fn identical_runs_align_at_zero() {
+    // Both searches settle on the same splice points here.
+    let input = signal::sine(220.0, 44100, 20_000).into_vec();
+    let report = compare_quickseek(&input, Config::new().set_channels(1));
+    assert_eq!(report.lag, 0);
+    assert!(report.relative_rms_difference() < 1e-3, "{report:?}");
+}
+
+#[test]
+fn silence() {
+    let report = compare_quickseek(&[0.0; 8000], &Config::new());
+    assert_eq!(report.lag, 0);
+    assert_eq!(report.rms_difference, 0.0);
+    assert_eq!(report.relative_rms_difference(), 0.0);
+}
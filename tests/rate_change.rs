@@ -0,0 +1,90 @@
+mod common;
+
+use common::{drain, process, stretcher};
+use soundtouch::{signal, RateChangePolicy, SoundTouch, SoundTouchError};
+
+#[test]
+fn flush_then_change_keeps_old_audio_separate() {
+    let old = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let new = signal::sine(220.0, 44100, 15_000).stereo().into_vec();
+    let expected_old = process(&mut stretcher(2, 44100, 1.25), &old);
+    let expected_new = process(&mut stretcher(2, 48000, 1.25), &new);
+
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(&old, 20_000);
+    let mut output = drain(&mut st);
+    st.change_sample_rate(48000, RateChangePolicy::FlushThenChange)
+        .unwrap();
+    assert_eq!(st.sample_rate(), Some(48000));
+    output.extend(drain(&mut st));
+    assert_eq!(output, expected_old);
+
+    // Nothing of the old stream comes out with the new one.
+    assert_eq!(process(&mut st, &new), expected_new);
+}
+
+#[test]
+fn flush_then_change_requires_receiving_old_audio() {
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    st.change_sample_rate(22050, RateChangePolicy::FlushThenChange)
+        .unwrap();
+    assert!(matches!(
+        st.try_put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100),
+        Err(SoundTouchError::AlreadyFlushed { .. })
+    ));
+}
+
+#[test]
+fn drop_pending() {
+    let new = signal::sine(220.0, 44100, 15_000).stereo().into_vec();
+    let expected_new = process(&mut stretcher(2, 22050, 1.25), &new);
+
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    st.change_sample_rate(22050, RateChangePolicy::DropPending)
+        .unwrap();
+    assert_eq!(st.available_frames(), 0);
+    assert_eq!(st.pending_input_frames(), 0);
+    assert_eq!(process(&mut st, &new), expected_new);
+}
+
+#[test]
+fn invalid_rate_changes_nothing() {
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    let pending = st.pending_input_frames();
+    assert_eq!(
+        st.change_sample_rate(0, RateChangePolicy::DropPending)
+            .err(),
+        Some(SoundTouchError::InvalidSampleRate(0))
+    );
+    assert_eq!(st.sample_rate(), Some(44100));
+    assert_eq!(st.pending_input_frames(), pending);
+}
+
+#[test]
+fn unconfigured() {
+    let mut st = SoundTouch::new();
+    st.change_sample_rate(8000, RateChangePolicy::FlushThenChange)
+        .unwrap();
+    assert_eq!(st.sample_rate(), Some(8000));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "change_sample_rate")]
+fn set_sample_rate_with_pending_input() {
+    let mut st = stretcher(2, 44100, 1.25);
+    st.put_samples(&signal::sine(220.0, 44100, 1000).stereo().into_vec(), 1000);
+    st.set_sample_rate(48000);
+}
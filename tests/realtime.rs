@@ -0,0 +1,146 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use common::stretcher;
+use soundtouch::realtime::{ParamMailbox, Params};
+use soundtouch::{signal, Scratch, SoundTouchError};
+
+#[test]
+fn setters_keep_the_other_parameters() {
+    let mailbox = ParamMailbox::default();
+    mailbox.set_tempo(1.5);
+    mailbox.set_pitch(0.8);
+    mailbox.set_rate(1.1);
+
+    assert_eq!(
+        mailbox.read(),
+        Some((
+            3,
+            Params {
+                tempo: 1.5,
+                pitch: 0.8,
+                rate: 1.1
+            }
+        ))
+    );
+}
+
+#[test]
+fn read_new_only_returns_changes() {
+    let mailbox = ParamMailbox::default();
+    let mut version = mailbox.version();
+    assert_eq!(mailbox.read_new(&mut version), None);
+
+    mailbox.set_tempo(2.0);
+    mailbox.set_tempo(1.25);
+    let params = mailbox.read_new(&mut version);
+    assert_eq!(params.map(|params| params.tempo), Some(1.25));
+    assert_eq!(version, 2);
+    assert_eq!(mailbox.read_new(&mut version), None);
+}
+
+#[test]
+fn concurrent_reads_are_never_torn() {
+    let mailbox = Arc::new(ParamMailbox::default());
+    let done = Arc::new(AtomicBool::new(false));
+    let writes = if cfg!(miri) { 200 } else { 20_000 };
+
+    // Every write stores the same value in all three fields, so a read mixing two writes
+    // shows up as unequal fields.
+    let writers: Vec<_> = (0..3)
+        .map(|writer| {
+            let mailbox = Arc::clone(&mailbox);
+            thread::spawn(move || {
+                for i in 0..writes {
+                    let value = 1.0 + (i * 3 + writer) as f64 / 100_000.0;
+                    mailbox.set(Params {
+                        tempo: value,
+                        pitch: value,
+                        rate: value,
+                    });
+                }
+            })
+        })
+        .collect();
+
+    let reader = {
+        let (mailbox, done) = (Arc::clone(&mailbox), Arc::clone(&done));
+        thread::spawn(move || {
+            let mut last_version = 0;
+            let mut reads = 0;
+            while !done.load(Ordering::Acquire) {
+                if let Some((version, params)) = mailbox.read() {
+                    assert_eq!(params.tempo, params.pitch);
+                    assert_eq!(params.tempo, params.rate);
+                    assert!(version >= last_version);
+                    last_version = version;
+                    reads += 1;
+                }
+            }
+            reads
+        })
+    };
+
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Release);
+    assert!(reader.join().unwrap() > 0);
+    assert_eq!(mailbox.version(), 3 * writes as u64);
+    assert!(mailbox.read().is_some());
+}
+
+#[test]
+fn changes_are_picked_up_at_the_next_block() {
+    let mailbox = Arc::new(ParamMailbox::default());
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    let mut scratch = Scratch::for_channels(2);
+    let input = signal::sine(220.0, 44100, 512).stereo().into_vec();
+    let mut output = vec![0.0; input.len()];
+    let mut version = mailbox.version();
+
+    assert!(!mailbox.apply_to(&mut soundtouch, &mut version).unwrap());
+    soundtouch.process_block(&input, &mut output, &mut scratch);
+
+    let (sent, received) = mpsc::channel();
+    let ui = {
+        let mailbox = Arc::clone(&mailbox);
+        thread::spawn(move || {
+            mailbox.set_tempo(1.4);
+            mailbox.set_pitch(0.9);
+            sent.send(()).unwrap();
+        })
+    };
+    received.recv().unwrap();
+    ui.join().unwrap();
+
+    assert!(mailbox.apply_to(&mut soundtouch, &mut version).unwrap());
+    soundtouch.process_block(&input, &mut output, &mut scratch);
+    let params = soundtouch.effective_parameters();
+    assert_eq!(params.virtual_tempo, 1.4);
+    assert_eq!(params.virtual_pitch, 0.9);
+    assert!(!mailbox.apply_to(&mut soundtouch, &mut version).unwrap());
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let mailbox = ParamMailbox::default();
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    let mut version = mailbox.version();
+    mailbox.set_tempo(1.25);
+    assert!(mailbox.apply_to(&mut soundtouch, &mut version).unwrap());
+
+    mailbox.set_pitch(f64::NAN);
+    assert!(matches!(
+        mailbox.apply_to(&mut soundtouch, &mut version),
+        Err(SoundTouchError::ParametersOutOfRange { .. })
+    ));
+    let params = soundtouch.effective_parameters();
+    assert_eq!(params.virtual_tempo, 1.25);
+    assert_eq!(params.virtual_pitch, 1.0);
+    // The rejected parameters aren't applied again.
+    assert!(!mailbox.apply_to(&mut soundtouch, &mut version).unwrap());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::units::Frames;
+use soundtouch::{signal, Pending, ReceiveStatus, SoundTouch, TimeStretcher};
+
+#[test]
+fn reports_each_pending_state() {
+    let mut soundtouch = stretcher(1, 44100, 1.25);
+    soundtouch.set_output_block_frames(Some(256));
+    let mut buffer = [0.0; 256];
+    assert_eq!(soundtouch.pending_output(), Pending::None);
+
+    soundtouch.put_samples(&signal::sine(220.0, 44100, 20_000).into_vec(), 20_000);
+    let status = soundtouch.receive_frames(&mut buffer, Frames(256));
+    assert_eq!(status.frames, Frames(256));
+    assert_eq!(status.pending, Pending::MoreAvailable);
+
+    // A buffer shorter than a block receives nothing while whole blocks are ready.
+    let status = soundtouch.receive_frames(&mut buffer, Frames(100));
+    assert_eq!(status.frames, Frames(0));
+    assert_eq!(
+        status.pending,
+        Pending::BufferTooSmall { block_frames: 256 }
+    );
+    assert_eq!(soundtouch.pending_output(), Pending::MoreAvailable);
+
+    let mut last = soundtouch.receive_frames(&mut buffer, Frames(256));
+    assert_eq!(last.frames, Frames(256));
+    while last.pending == Pending::MoreAvailable {
+        last = soundtouch.receive_frames(&mut buffer, Frames(256));
+        assert_eq!(last.frames, Frames(256));
+    }
+    // Receiving nothing doesn't mean drained: the frames of the incomplete block remain.
+    assert_eq!(last.pending, Pending::PartialBlockHeld);
+    let held = soundtouch.receive_frames(&mut buffer, Frames(256));
+    assert_eq!(
+        held,
+        ReceiveStatus {
+            frames: Frames(0),
+            pending: Pending::PartialBlockHeld
+        }
+    );
+
+    soundtouch.flush();
+    assert_eq!(soundtouch.pending_output(), Pending::MoreAvailable);
+    common::drain(&mut soundtouch);
+    assert_eq!(soundtouch.pending_output(), Pending::None);
+}
+
+#[test]
+fn loop_on_status_matches_receive_all() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let mut expected = stretcher(1, 44100, 1.25);
+    expected.set_channels(2);
+    let expected = common::process(&mut expected, &input);
+
+    let mut soundtouch = stretcher(1, 44100, 1.25);
+    soundtouch.set_channels(2);
+    let mut buffer = [0.0; 2 * 1000];
+    let mut output = Vec::new();
+    let mut receive = |soundtouch: &mut SoundTouch| loop {
+        let status = soundtouch.receive_with_status(&mut buffer);
+        output.extend_from_slice(&buffer[..status.frames.0 * 2]);
+        if status.pending != Pending::MoreAvailable {
+            break;
+        }
+    };
+    for chunk in input.chunks(2 * 4096) {
+        soundtouch.put_samples(chunk, chunk.len() / 2);
+        receive(&mut soundtouch);
+    }
+    soundtouch.flush();
+    receive(&mut soundtouch);
+    assert_eq!(output, expected);
+    assert_eq!(soundtouch.pending_output(), Pending::None);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::registry::{snapshot, InstanceInfo};
+use soundtouch::{signal, BPMDetect, BufferLevels, SoundTouch};
+
+fn find(id: u64) -> Option<InstanceInfo> {
+    snapshot().into_iter().find(|info| info.id == id)
+}
+
+#[test]
+fn construction_and_drop() {
+    let soundtouch = SoundTouch::new();
+    let bpm = BPMDetect::new(2, 44100);
+    let (st_id, bpm_id) = (soundtouch.id(), bpm.id());
+
+    let info = find(st_id).unwrap();
+    assert_eq!(info.kind, "SoundTouch");
+    assert_eq!((info.channels, info.sample_rate), (0, 0));
+    let info = find(bpm_id).unwrap();
+    assert_eq!(info.kind, "BPMDetect");
+    assert_eq!((info.channels, info.sample_rate), (2, 44100));
+
+    drop(soundtouch);
+    assert_eq!(find(st_id), None);
+    assert!(find(bpm_id).is_some());
+    drop(bpm);
+    assert_eq!(find(bpm_id), None);
+}
+
+#[test]
+fn snapshot_is_ordered() {
+    let instances: Vec<_> = (0..5).map(|_| SoundTouch::new()).collect();
+    let ids: Vec<_> = instances.iter().map(SoundTouch::id).collect();
+    let listed: Vec<_> = snapshot()
+        .into_iter()
+        .map(|info| info.id)
+        .filter(|id| ids.contains(id))
+        .collect();
+    assert_eq!(listed, ids);
+}
+
+#[test]
+fn stats_follow_processing() {
+    let mut soundtouch = stretcher(2, 48000, 1.0);
+    let output = soundtouch.generate_audio(&signal::sine(220.0, 44100, 10_000).stereo().into_vec());
+
+    let info = find(soundtouch.id()).unwrap();
+    assert_eq!((info.channels, info.sample_rate), (2, 48000));
+    assert_eq!(info.frames_put, 10_000);
+    assert_eq!(info.frames_received, (output.len() / 2) as u64);
+
+    let mut bpm = BPMDetect::new(2, 44100);
+    bpm.input_samples(&signal::sine(220.0, 44100, 4096).stereo().into_vec());
+    assert_eq!(find(bpm.id()).unwrap().frames_put, 4096);
+}
+
+#[test]
+fn buffer_levels_follow_processing() {
+    let mut soundtouch = stretcher(2, 44100, 1.25);
+    let id = soundtouch.id();
+    let listed = || find(id).unwrap().buffer_levels;
+    assert_eq!(listed(), BufferLevels::default());
+
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+        10_000,
+    );
+    assert!(soundtouch.buffer_levels().ready_frames > 0);
+    assert_eq!(listed(), soundtouch.buffer_levels());
+
+    let mut buffer = vec![0.0; 2 * 100];
+    soundtouch.receive_samples(&mut buffer, 100);
+    assert_eq!(listed(), soundtouch.buffer_levels());
+
+    soundtouch.clear();
+    assert_eq!(listed(), BufferLevels::default());
+}
@@ -0,0 +1,52 @@
+use soundtouch::{Setting, SoundTouch, SoundTouchError};
+
+#[test]
+fn parameters_return_the_previous_value() {
+    let mut soundtouch = SoundTouch::new();
+    assert_eq!(soundtouch.replace_tempo(1.5), 1.0);
+    assert_eq!(soundtouch.replace_tempo(0.75), 1.5);
+    assert_eq!(soundtouch.replace_pitch(1.25), 1.0);
+    assert_eq!(soundtouch.replace_rate(0.9), 1.0);
+    assert_eq!(soundtouch.replace_pitch(0.8), 1.25);
+    assert_eq!(soundtouch.replace_rate(1.1), 0.9);
+
+    // Undoing in reverse order restores the defaults.
+    assert_eq!(soundtouch.replace_rate(0.9), 1.1);
+    assert_eq!(soundtouch.replace_pitch(1.25), 0.8);
+    assert_eq!(soundtouch.replace_rate(1.0), 0.9);
+    assert_eq!(soundtouch.replace_pitch(1.0), 1.25);
+    assert_eq!(soundtouch.replace_tempo(1.5), 0.75);
+    assert_eq!(soundtouch.replace_tempo(1.0), 1.5);
+    let params = soundtouch.effective_parameters();
+    assert_eq!((params.tempo, params.rate), (1.0, 1.0));
+}
+
+#[test]
+fn settings_return_the_previous_value() {
+    let mut soundtouch = SoundTouch::new();
+    assert_eq!(
+        soundtouch.replace_setting(Setting::SequenceMs, 40),
+        Ok(Setting::SequenceMs.default_value())
+    );
+    assert_eq!(soundtouch.replace_setting(Setting::SequenceMs, 60), Ok(40));
+    assert_eq!(
+        soundtouch.replace_setting(Setting::AaFilterLength, 32),
+        Ok(64)
+    );
+    assert_eq!(
+        soundtouch.replace_setting(Setting::AaFilterLength, 30),
+        Err(SoundTouchError::InvalidSetting {
+            setting: Setting::AaFilterLength,
+            value: 30
+        })
+    );
+    assert_eq!(soundtouch.get_setting(Setting::AaFilterLength), 32);
+    assert_eq!(soundtouch.replace_setting(Setting::SequenceMs, 0), Ok(60));
+}
+
+#[test]
+#[should_panic(expected = "effective tempo")]
+fn out_of_range_panics() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.replace_tempo(100.0);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use soundtouch::{resample, signal};
+
+#[test]
+fn output_length() {
+    for (from, to) in [
+        (48000, 44100),
+        (44100, 48000),
+        (44100, 22050),
+        (22050, 44100),
+        (8000, 44100),
+        (44100, 44100),
+    ] {
+        for frames in [0, 1, 999, 44_100] {
+            let input = signal::sine(220.0, 44100, frames).stereo().into_vec();
+            let output = resample(&input, 2, from, to);
+            let expected = (frames as f64 * to as f64 / from as f64).round() as usize;
+            assert_eq!(
+                output.len(),
+                expected * 2,
+                "{frames} frames, {from} -> {to}"
+            );
+        }
+    }
+}
+
+#[test]
+fn tone_keeps_frequency() {
+    for (from, to) in [(48000, 44100), (44100, 48000), (22050, 44100)] {
+        let input = common::tone(1000.0, from, from);
+        let output = resample(&input, 1, from as u32, to as u32);
+        let peak = common::peak_frequency(&output[4096..], to, 900.0, 1100.0, 1.0);
+        assert!((peak - 1000.0).abs() <= 1.0, "{from} -> {to}: {peak} Hz");
+    }
+}
+
+#[test]
+fn latency_is_trimmed() {
+    for (from, to) in [(48000, 44100), (44100, 48000), (22050, 44100)] {
+        let mut input = vec![0.0; 20_000];
+        input[10_000] = 1.0;
+        let output = resample(&input, 1, from, to);
+        let peak = output
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .unwrap()
+            .0;
+        let expected = 10_000.0 * to as f64 / from as f64;
+        assert!(
+            (peak as f64 - expected).abs() <= 1.5,
+            "{from} -> {to}: {peak}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "must both be within")]
+fn ratio_out_of_range() {
+    resample(&[0.0; 100], 1, 8000, 96000);
+}
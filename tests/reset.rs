@@ -0,0 +1,29 @@
+mod common;
+
+use common::{process, stretcher};
+use soundtouch::{signal, Setting, SoundTouch};
+
+#[test]
+fn reset_behaves_like_new_instance() {
+    let input = signal::sine(220.0, 44100, 44100).stereo().into_vec();
+
+    let mut fresh = stretcher(2, 44100, 1.0);
+    let expected = process(&mut fresh, &input);
+
+    let mut reused = SoundTouch::new();
+    reused
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.7)
+        .set_pitch_semitones(-3)
+        .set_rate(0.9)
+        .set_setting(Setting::UseQuickseek, 1)
+        .set_setting(Setting::OverlapMs, 20);
+    process(&mut reused, &input);
+    reused.put_samples(&input[..2048], 1024);
+    reused.reset();
+
+    assert_eq!(reused.num_unprocessed_samples(), 0);
+    assert_eq!(reused.get_input_output_sample_ratio(), 1.0);
+    assert_eq!(process(&mut reused, &input), expected);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::{drain, stretcher};
+use soundtouch::{signal, Sanitize, SoundTouch, SoundTouchError};
+
+fn poisoned() -> Vec<f32> {
+    let mut input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    input[5_001] = f32::NAN;
+    input[12_000] = f32::INFINITY;
+    input[30_000] = f32::NEG_INFINITY;
+    input
+}
+
+#[test]
+fn default_is_off() {
+    assert_eq!(SoundTouch::new().input_sanitization(), Sanitize::Off);
+}
+
+#[test]
+fn zero_non_finite_gives_finite_output() {
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_input_sanitization(Sanitize::ZeroNonFinite);
+    let output = st.generate_audio(&poisoned());
+    assert!(!output.is_empty());
+    assert!(output.iter().all(|s| s.is_finite()));
+
+    let mut zeroed = poisoned();
+    for sample in &mut zeroed {
+        if !sample.is_finite() {
+            *sample = 0.0;
+        }
+    }
+    assert_eq!(output, stretcher(2, 44100, 1.2).generate_audio(&zeroed));
+}
+
+#[test]
+fn error_reports_first_index() {
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_input_sanitization(Sanitize::Error);
+    assert_eq!(
+        st.try_put_samples(&poisoned(), 20_000),
+        Err(SoundTouchError::NonFiniteInput { index: 5_001 })
+    );
+    // Nothing was put.
+    assert_eq!(st.pending_input_frames(), 0);
+
+    // Frames beyond `num_samples` aren't checked.
+    assert_eq!(st.try_put_samples(&poisoned(), 2_500), Ok(()));
+
+    let mut input = signal::sine(220.0, 44100, 1000).stereo().into_vec();
+    input[1999] = f32::NAN;
+    assert_eq!(
+        st.try_put_samples(&input, 1000),
+        Err(SoundTouchError::NonFiniteInput { index: 1999 })
+    );
+    assert!(drain(&mut st).iter().all(|s| s.is_finite()));
+}
+
+#[test]
+fn error_index_across_chunks() {
+    let mut input = signal::sine(220.0, 44100, 40_000).stereo().into_vec();
+    input[70_001] = f32::NAN;
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_input_sanitization(Sanitize::Error);
+    let mut output = Vec::new();
+    for chunk in input.chunks(20_000) {
+        if let Err(err) = st.try_process_chunk(chunk, &mut output) {
+            assert_eq!(err, SoundTouchError::NonFiniteInput { index: 10_001 });
+            return;
+        }
+    }
+    panic!("NaN not reported");
+}
+
+#[test]
+#[should_panic(expected = "input sample 5001 is NaN or infinite")]
+fn error_panics_in_generate_audio() {
+    let mut st = stretcher(2, 44100, 1.2);
+    st.set_input_sanitization(Sanitize::Error);
+    st.generate_audio(&poisoned());
+}
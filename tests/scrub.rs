@@ -0,0 +1,73 @@
+mod common;
+
+use soundtouch::{Config, Scrubber};
+
+/// Largest difference between consecutive samples of the same channel.
+fn max_step(samples: &[f32], channels: usize) -> f32 {
+    samples
+        .windows(channels + 1)
+        .map(|pair| (pair[channels] - pair[0]).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn consecutive_windows_are_continuous() {
+    let config = Config::new();
+    let source: Vec<f32> = common::tone(440.0, 44100, 44100 * 10)
+        .iter()
+        .flat_map(|&sample| [sample, -sample])
+        .collect();
+    // Consecutive samples of the tone differ by at most this much.
+    let smooth = max_step(&source, 2);
+
+    let mut scrubber = Scrubber::new(&config);
+    let mut output = Vec::new();
+    let mut cursor = 44100;
+    // Drag back and forth at varying speeds, jumping far in between.
+    for (i, speed) in [1.0, 0.5, 2.0, -1.0, 0.25, 3.0, -0.5, 1.5, 0.0, 1.0]
+        .into_iter()
+        .enumerate()
+    {
+        cursor = (cursor + 37_000 * (i + 1)) % (44100 * 9);
+        let window = &source[cursor * 2..(cursor + 4410) * 2];
+        let scrubbed = scrubber.scrub_to(window, speed);
+        assert_eq!(scrubbed.len() % 2, 0);
+        if speed != 0.0 {
+            let expected = (4410.0 / speed.abs()) as usize;
+            let frames = scrubbed.len() / 2;
+            assert!(frames + 200 >= expected, "speed {speed}: {frames} frames");
+            assert!(frames <= expected + 4410, "speed {speed}: {frames} frames");
+        }
+        output.extend_from_slice(scrubbed);
+    }
+    // Fades and pitch-preserving stretching don't make the signal much steeper; a
+    // jump between windows would.
+    assert!(
+        max_step(&output, 2) < 2.0 * smooth,
+        "{} > {smooth}",
+        max_step(&output, 2)
+    );
+}
+
+#[test]
+fn output_contains_window() {
+    let mut scrubber = Scrubber::new(&Config::new());
+    let window = vec![0.5; 4410 * 2];
+    let energy = |samples: &[f32]| samples.iter().map(|s| s * s).sum::<f32>();
+    let first = energy(scrubber.scrub_to(&window, 1.0));
+    let rest = energy(scrubber.scrub_to(&[], 1.0));
+    // Nearly all of the window comes out in the same call.
+    assert!(rest < 0.02 * first, "{rest} vs {first}");
+    assert!(scrubber.scrub_to(&[], 0.0).is_empty());
+}
+
+#[test]
+fn reverse() {
+    // A ramp played backwards falls.
+    let ramp: Vec<f32> = (0..8820).map(|i| i as f32 / 8820.0).collect();
+    let mut scrubber = Scrubber::new(Config::new().set_channels(1));
+    scrubber.set_fade_frames(0);
+    let output = scrubber.scrub_to(&ramp, -1.0).to_vec();
+    let quarter = output.len() / 4;
+    assert!(output[quarter] > output[3 * quarter]);
+}
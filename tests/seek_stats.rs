@@ -0,0 +1,79 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, ConfigLint, SeekStats, Setting, SoundTouch};
+
+const SAMPLE_RATE: u32 = 44100;
+
+fn sine(frequency: f64, seconds: usize) -> Vec<f32> {
+    signal::sine(frequency, SAMPLE_RATE, seconds * SAMPLE_RATE as usize).into_vec()
+}
+
+/// Bursts of noise decaying within a few milliseconds, every 21 ms.
+fn transient_train(seconds: usize) -> Vec<f32> {
+    let period = (0.021 * SAMPLE_RATE as f32) as usize;
+    signal::noise(1, SAMPLE_RATE, seconds * SAMPLE_RATE as usize)
+        .enumerate()
+        .map(|(i, noise)| {
+            let t = (i % period) as f32 / SAMPLE_RATE as f32;
+            (-t * 1000.0 / 3.0).exp() * noise * 2.0
+        })
+        .collect()
+}
+
+fn stretched(input: &[f32], seek_window_ms: i32) -> SoundTouch {
+    let mut soundtouch = stretcher(1, SAMPLE_RATE, 1.25);
+    soundtouch
+        .set_setting(Setting::SeekwindowMs, seek_window_ms)
+        .set_collect_seek_stats(true);
+    soundtouch.generate_audio(input);
+    soundtouch
+}
+
+#[test]
+fn transients_hit_the_edge_more_than_a_sine() {
+    let sine = stretched(&sine(440.0, 10), 0).seek_stats();
+    let transients = stretched(&transient_train(10), 0).seek_stats();
+    assert!(sine.blocks > 100, "{sine:?}");
+    assert_eq!(transients.blocks, sine.blocks);
+
+    assert!(sine.edge_hit_ratio() < 0.02, "{sine:?}");
+    assert!(sine.avg_correlation > 0.99, "{sine:?}");
+    assert!(transients.edge_hit_ratio() > 0.08, "{transients:?}");
+    assert!(transients.avg_correlation < 0.6, "{transients:?}");
+}
+
+#[test]
+fn small_seek_window_is_linted() {
+    // A 3 ms window can't hold a period of 110 Hz, so the best match is always cut off.
+    let input = sine(110.0, 5);
+    let small = stretched(&input, 3);
+    assert!(
+        small.seek_stats().edge_hit_ratio() > 0.9,
+        "{:?}",
+        small.seek_stats()
+    );
+    let lint = small
+        .lint()
+        .into_iter()
+        .find(|lint| lint.name() == "small-seek-window");
+    assert!(
+        matches!(lint, Some(ConfigLint::SmallSeekWindow { edge_hit_ratio }) if edge_hit_ratio > 0.9),
+        "{lint:?}"
+    );
+
+    let automatic = stretched(&input, 0);
+    assert_eq!(automatic.seek_stats().edge_hits, 0);
+    assert!(automatic.lint().is_empty());
+}
+
+#[test]
+fn disabling_discards_the_stats() {
+    let mut soundtouch = stretched(&sine(440.0, 1), 0);
+    assert!(soundtouch.seek_stats().blocks > 0);
+    soundtouch.set_collect_seek_stats(false);
+    assert_eq!(soundtouch.seek_stats(), SeekStats::default());
+    soundtouch.generate_audio(&sine(440.0, 1));
+    assert_eq!(soundtouch.seek_stats(), SeekStats::default());
+    assert_eq!(SeekStats::default().edge_hit_ratio(), 0.0);
+}
@@ -0,0 +1,62 @@
+use soundtouch::segments::Concatenator;
+use soundtouch::{signal, Config};
+
+#[test]
+fn length_matches_expected() {
+    let tempos = [0.8, 1.25, 1.0];
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let mut concatenator = Concatenator::new(256);
+    for tempo in tempos {
+        concatenator.push(&input, Config::new().set_tempo(tempo));
+    }
+    let concatenated = concatenator.finish();
+
+    let expected: f64 = tempos.iter().map(|tempo| 20_000.0 / tempo).sum();
+    let frames = concatenated.samples.len() / 2;
+    assert_eq!(concatenated.samples.len() % 2, 0);
+    assert!(
+        (frames as f64 - expected).abs() <= 1.0,
+        "got {frames} frames, expected {expected}"
+    );
+    assert!((concatenated.expected_frames - expected).abs() < 1e-6);
+
+    assert_eq!(concatenated.joints.len(), 2);
+    let mut boundary = 0.0;
+    for (joint, tempo) in concatenated.joints.iter().zip(tempos) {
+        boundary += 20_000.0 / tempo;
+        assert!((joint.expected - boundary).abs() < 1e-6);
+        assert!((joint.position as f64 - boundary).abs() <= 0.5);
+        assert_eq!(joint.crossfade, 256);
+    }
+}
+
+#[test]
+fn joints_have_no_gaps() {
+    let input = signal::sine(220.0, 44100, 20_000).into_vec();
+    let mut concatenator = Concatenator::new(128);
+    concatenator
+        .push(&input, Config::new().set_channels(1).set_tempo(0.5))
+        .push(&input, Config::new().set_channels(1).set_tempo(0.7));
+    let concatenated = concatenator.finish();
+
+    let joint = concatenated.joints[0].position;
+    let around = &concatenated.samples[joint - 2000..joint + 2000];
+    let longest_silence = around
+        .split(|s| s.abs() >= 1e-3)
+        .map(<[f32]>::len)
+        .max()
+        .unwrap();
+    assert!(
+        longest_silence < 8,
+        "{longest_silence} silent frames at the joint"
+    );
+}
+
+#[test]
+#[should_panic(expected = "same number of channels")]
+fn mismatched_channels_panic() {
+    let mut concatenator = Concatenator::new(64);
+    concatenator
+        .push(&[0.0; 200], &Config::new())
+        .push(&[0.0; 100], Config::new().set_channels(1));
+}
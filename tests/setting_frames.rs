@@ -0,0 +1,82 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{Setting, SoundTouch, SoundTouchError};
+
+#[test]
+fn round_trip_at_44100() {
+    let mut st = stretcher(2, 44100, 1.0);
+
+    st.set_sequence_frames(3528).unwrap();
+    assert_eq!(st.get_setting(Setting::SequenceMs), 80);
+    assert_eq!(st.sequence_frames(), Some(3528));
+
+    // 15.01 ms rounds to 15 ms, which truncates to 661 frames.
+    st.set_seek_window_frames(662).unwrap();
+    assert_eq!(st.get_setting(Setting::SeekwindowMs), 15);
+    assert_eq!(st.seek_window_frames(), Some(661));
+
+    // 7.6 ms rounds to 8 ms, 352.8 frames, which SoundTouch makes 352.
+    st.set_overlap_frames(335).unwrap();
+    assert_eq!(st.get_setting(Setting::OverlapMs), 8);
+    assert_eq!(st.overlap_frames(), Some(352));
+}
+
+#[test]
+fn round_trip_at_96000() {
+    let mut st = stretcher(2, 96000, 1.0);
+    for ms in 1..=200 {
+        let frames = ms * 96;
+        st.set_sequence_frames(frames + 40).unwrap();
+        assert_eq!(st.get_setting(Setting::SequenceMs), ms as i32);
+        let overlap = st.overlap_frames().unwrap();
+        assert_eq!(st.sequence_frames(), Some(frames.max(2 * overlap)));
+
+        st.set_seek_window_frames(frames - 40).unwrap();
+        assert_eq!(st.seek_window_frames(), Some(frames));
+    }
+
+    st.set_overlap_frames(770).unwrap();
+    assert_eq!(st.overlap_frames(), Some(768));
+    // Shorter overlaps are lengthened to 16 frames.
+    st.set_overlap_frames(0).unwrap();
+    assert_eq!(st.overlap_frames(), Some(16));
+}
+
+#[test]
+fn error_within_half_a_millisecond() {
+    for sample_rate in [44100, 96000] {
+        let mut st = stretcher(2, sample_rate, 1.0);
+        let half_ms = sample_rate as f64 / 2000.0;
+        for frames in (2000..20_000).step_by(37) {
+            st.set_sequence_frames(frames).unwrap();
+            let actual = st.sequence_frames().unwrap();
+            assert!(
+                (actual as f64 - frames as f64).abs() <= half_ms + 1.0,
+                "{sample_rate} Hz: {frames} -> {actual}"
+            );
+        }
+    }
+}
+
+#[test]
+fn zero_is_automatic() {
+    let mut st = stretcher(2, 44100, 1.0);
+    assert_eq!(st.sequence_frames(), None);
+    assert_eq!(st.seek_window_frames(), None);
+    st.set_sequence_frames(4000).unwrap();
+    st.set_sequence_frames(0).unwrap();
+    assert_eq!(st.get_setting(Setting::SequenceMs), 0);
+    assert_eq!(st.sequence_frames(), None);
+}
+
+#[test]
+fn sample_rate_required() {
+    let mut st = SoundTouch::new();
+    assert_eq!(
+        st.set_overlap_frames(300).err(),
+        Some(SoundTouchError::NotConfigured)
+    );
+    assert_eq!(st.overlap_frames(), None);
+    assert_eq!(st.sequence_frames(), None);
+}
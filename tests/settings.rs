@@ -0,0 +1,73 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{Setting, SoundTouch};
+
+const WRITABLE: [Setting; 6] = [
+    Setting::UseAaFilter,
+    Setting::AaFilterLength,
+    Setting::UseQuickseek,
+    Setting::SequenceMs,
+    Setting::SeekwindowMs,
+    Setting::OverlapMs,
+];
+
+#[test]
+fn default_values_match_new_instance() {
+    let soundtouch = SoundTouch::new();
+    for setting in WRITABLE {
+        assert_eq!(soundtouch.get_setting(setting), setting.default_value(), "{setting:?}");
+    }
+}
+
+#[test]
+fn reset_settings_restores_defaults_and_keeps_tempo() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(2)
+        .set_sample_rate(44100)
+        .set_tempo(1.5)
+        .set_setting(Setting::UseAaFilter, 0)
+        .set_setting(Setting::AaFilterLength, 16)
+        .set_setting(Setting::UseQuickseek, 1)
+        .set_setting(Setting::SequenceMs, 50)
+        .set_setting(Setting::SeekwindowMs, 20)
+        .set_setting(Setting::OverlapMs, 12);
+    soundtouch.reset_settings();
+
+    for setting in WRITABLE {
+        assert_eq!(soundtouch.get_setting(setting), setting.default_value(), "{setting:?}");
+    }
+    assert_eq!(soundtouch.num_channels(), 2);
+    assert!((soundtouch.get_input_output_sample_ratio() - 1.0 / 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn valid_ranges_match_the_library() {
+    for setting in Setting::ALL {
+        let Some(range) = setting.valid_range() else {
+            assert!(setting.is_read_only(), "{setting:?}");
+            continue;
+        };
+        assert!(!setting.is_read_only(), "{setting:?}");
+        assert!(range.contains(&setting.default_value()), "{setting:?}");
+        for value in [*range.start(), *range.end()] {
+            let mut soundtouch = stretcher(2, 44100, 1.0);
+            // The overlap has to stay shorter than the sequence.
+            if setting == Setting::OverlapMs {
+                soundtouch.set_setting(Setting::SequenceMs, *range.end() + 1);
+            }
+            soundtouch.try_set_setting(setting, value).unwrap();
+            assert_eq!(soundtouch.get_setting(setting), value, "{setting:?}");
+        }
+    }
+}
+
+#[test]
+fn descriptions() {
+    for setting in Setting::ALL {
+        let description = setting.description();
+        assert!(!description.is_empty(), "{setting:?}");
+        assert!(!description.ends_with('.'), "{setting:?}");
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{process, stretcher};
+use soundtouch::{signal, BufferRequirements, Setting, SoundTouch};
+
+/// Sets a callback on `soundtouch` recording every call.
+fn record(soundtouch: &mut SoundTouch) -> Arc<Mutex<Vec<BufferRequirements>>> {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&calls);
+    soundtouch.on_settings_changed(move |requirements| {
+        recorded.lock().unwrap().push(requirements);
+    });
+    calls
+}
+
+#[test]
+fn fires_with_the_new_requirements() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let before = st.buffer_requirements();
+    let calls = record(&mut st);
+
+    st.set_tempo(2.0);
+    let after = st.buffer_requirements();
+    assert_ne!(after, before);
+    assert_eq!(*calls.lock().unwrap(), [after]);
+    assert_eq!(after.channels, 2);
+    assert_eq!(
+        after.nominal_output_frames,
+        st.get_setting(Setting::NominalOutputSequence) as usize
+    );
+    assert_eq!(
+        after.initial_latency_frames,
+        st.get_setting(Setting::InitialLatency) as usize
+    );
+    assert_eq!(
+        after.recommended_buffer_len(),
+        2 * after.nominal_output_frames
+    );
+}
+
+#[test]
+fn only_fires_on_changes() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_tempo(1.5);
+    let calls = record(&mut st);
+
+    st.set_tempo(1.5)
+        .set_setting(Setting::UseQuickseek, 1)
+        .set_deterministic(true);
+    assert!(calls.lock().unwrap().is_empty());
+
+    st.set_channels(1);
+    assert_eq!(*calls.lock().unwrap(), [st.buffer_requirements()]);
+}
+
+#[test]
+fn processing_is_unchanged() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let mut plain = stretcher(2, 44100, 1.0);
+    plain.set_tempo(0.7);
+    let expected = process(&mut plain, &input);
+
+    let mut st = stretcher(2, 44100, 1.0);
+    let calls = record(&mut st);
+    st.set_tempo(0.7);
+    assert_eq!(process(&mut st, &input), expected);
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn cleared_and_reset() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let calls = record(&mut st);
+    st.clear_on_settings_changed().set_tempo(2.0);
+    assert!(calls.lock().unwrap().is_empty());
+
+    let calls = record(&mut st);
+    st.reset();
+    st.set_tempo(0.5);
+    assert!(calls.lock().unwrap().is_empty());
+}
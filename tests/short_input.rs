@@ -0,0 +1,70 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, Setting};
+
+fn assert_length(channels: u32, tempo: f64, frames: usize) {
+    let mut st = stretcher(channels, 44100, tempo);
+    let output = st.generate_audio(
+        &signal::sine(220.0, 44100, frames)
+            .channels(channels)
+            .into_vec(),
+    );
+    assert_eq!(output.len() % channels as usize, 0);
+    let expected = (frames as f64 / tempo).round() as usize;
+    let actual = output.len() / channels as usize;
+    assert!(
+        actual.abs_diff(expected) <= 1,
+        "{channels} channels at tempo {tempo}: {actual} frames from {frames}, expected {expected}"
+    );
+    assert!(output.iter().all(|s| s.is_finite()));
+}
+
+#[test]
+fn single_frame() {
+    for channels in [1, 2] {
+        for tempo in [0.5, 1.1, 2.0] {
+            assert_length(channels, tempo, 1);
+        }
+    }
+}
+
+#[test]
+fn sixty_four_frames() {
+    for channels in [1, 2] {
+        for tempo in [0.5, 1.1, 2.0] {
+            assert_length(channels, tempo, 64);
+        }
+    }
+}
+
+#[test]
+fn half_the_initial_latency() {
+    for channels in [1, 2] {
+        for tempo in [0.5, 1.1, 2.0] {
+            let latency = stretcher(channels, 44100, tempo).get_setting(Setting::InitialLatency);
+            assert!(latency > 0);
+            assert_length(channels, tempo, latency as usize / 2);
+        }
+    }
+}
+
+#[test]
+fn sound_effect_keeps_some_of_its_content() {
+    // 100 ms at tempo 1.1, as in a game playing an effect slightly faster.
+    let input = signal::sine(220.0, 44100, 4410).stereo().into_vec();
+    let output = stretcher(2, 44100, 1.1).generate_audio(&input);
+    assert_eq!(output.len() / 2, 4009);
+    assert!(output.iter().any(|s| s.abs() > 0.1));
+}
+
+#[test]
+fn flushed_length_is_exact_at_high_sample_rates() {
+    // The initial latency at 192 kHz and half tempo is far longer than at 44.1 kHz.
+    let mut st = stretcher(1, 192_000, 0.5);
+    st.put_samples(&signal::sine(220.0, 44100, 100).into_vec(), 100);
+    let padding = st.expected_flush_padding();
+    st.flush();
+    assert_eq!(padding, 200);
+    assert_eq!(st.available_frames(), 200);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use soundtouch::signal::{self, Signal};
+use soundtouch::BPMDetect;
+
+const SAMPLE_RATE: u32 = 44100;
+
+#[test]
+fn sine_matches_std() {
+    let sine: Vec<f32> = signal::sine(440.0, SAMPLE_RATE, 100_000).collect();
+    for (i, &sample) in sine.iter().enumerate() {
+        let expected = 0.5 * (std::f64::consts::TAU * 440.0 * i as f64 / 44100.0).sin();
+        assert!((sample as f64 - expected).abs() < 1e-6, "frame {i}");
+    }
+}
+
+#[test]
+fn sweep_ends_at_its_frequencies() {
+    let sweep = signal::sweep(200.0, 3200.0, SAMPLE_RATE, 4 * 44100).into_vec();
+    let peak = |samples: &[f32]| common::peak_frequency(samples, 44100, 100.0, 4000.0, 10.0);
+    let start = peak(&sweep[..4410]);
+    let end = peak(&sweep[sweep.len() - 4410..]);
+    // Four octaves in four seconds: a tenth of a second covers about 7%.
+    assert!((190.0..=230.0).contains(&start), "{start}");
+    assert!((2900.0..=3210.0).contains(&end), "{end}");
+    // Each second is an octave.
+    let second = peak(&sweep[44100..44100 + 441]);
+    assert!((350.0..=450.0).contains(&second), "{second}");
+}
+
+#[test]
+fn click_track_is_detected() {
+    let clicks = signal::click_track(128.0, SAMPLE_RATE, 20 * 44100);
+    let mut bpm = BPMDetect::new(2, SAMPLE_RATE);
+    bpm.input_samples(&clicks.stereo().into_vec());
+    let detected = bpm.get_bpm();
+    assert!((detected - 128.0).abs() < 1.0, "{detected}");
+}
+
+#[test]
+fn noise_is_deterministic() {
+    let a: Vec<f32> = signal::noise(3, SAMPLE_RATE, 10_000).collect();
+    assert_eq!(a, signal::noise(3, 48000, 10_000).into_vec());
+    assert_ne!(a, signal::noise(4, SAMPLE_RATE, 10_000).into_vec());
+    assert!(a.iter().all(|sample| sample.abs() <= 0.5));
+    let mean = a.iter().sum::<f32>() / a.len() as f32;
+    assert!(mean.abs() < 0.01, "{mean}");
+
+    let pink = signal::pink_noise(3, SAMPLE_RATE, 100_000).into_vec();
+    assert!(pink.iter().all(|sample| sample.abs() < 1.0));
+    // Pink noise has more energy in the low octaves than white noise.
+    let low = common::peak_frequency(&pink, 44100, 20.0, 20_000.0, 2000.0);
+    assert!(low < 2000.0, "{low}");
+}
+
+#[test]
+fn channels_repeat_samples() {
+    let mono = signal::sine(100.0, SAMPLE_RATE, 1000).into_vec();
+    let stereo = signal::sine(100.0, SAMPLE_RATE, 1000).stereo();
+    assert_eq!(stereo.len(), 2000);
+    assert_eq!(stereo.num_channels(), 2);
+    let stereo = stereo.into_vec();
+    for (frame, &sample) in stereo.chunks_exact(2).zip(&mono) {
+        assert_eq!(frame, [sample, sample]);
+    }
+
+    let mut six: Signal = signal::click_track(90.0, SAMPLE_RATE, 3).channels(6);
+    assert_eq!(six.len(), 18);
+    six.next();
+    assert_eq!(six.len(), 17);
+    assert_eq!(six.count(), 17);
+    assert_eq!(signal::noise(1, SAMPLE_RATE, 0).stereo().next(), None);
+}
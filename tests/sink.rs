@@ -0,0 +1,58 @@
+mod common;
+
+use std::collections::VecDeque;
+
+use common::{drain, stretcher};
+use soundtouch::{signal, SliceSink};
+
+#[test]
+fn vec_matches_generate_audio() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 1.3).generate_audio(&input);
+
+    let mut output = vec![1.0, 2.0];
+    stretcher(2, 44100, 1.3).generate_audio_extend(&input, &mut output);
+    assert_eq!(output[..2], [1.0, 2.0]);
+    assert_eq!(output[2..], expected[..]);
+}
+
+#[test]
+fn vec_deque() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 1.3).generate_audio(&input);
+
+    let mut output = VecDeque::new();
+    stretcher(2, 44100, 1.3).generate_audio_extend(&input, &mut output);
+    assert!(output.iter().eq(expected.iter()));
+}
+
+#[test]
+fn slice_keeps_the_rest_in_the_pipeline() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 1.3).generate_audio(&input);
+
+    let mut soundtouch = stretcher(2, 44100, 1.3);
+    // Not a whole number of frames, so the last sample stays untouched.
+    let mut buffer = vec![f32::NAN; 10_001];
+    let mut sink = SliceSink::new(&mut buffer);
+    soundtouch.generate_audio_extend(&input, &mut sink);
+    assert_eq!(sink.written().len(), 10_000);
+    assert!(!sink.is_full());
+    assert_eq!(buffer[..10_000], expected[..10_000]);
+    assert!(buffer[10_000].is_nan());
+
+    let rest = drain(&mut soundtouch);
+    assert_eq!(rest[..], expected[10_000..]);
+}
+
+#[test]
+fn slice_larger_than_output() {
+    let input = signal::sine(220.0, 44100, 5000).into_vec();
+    let mono = || stretcher(1, 44100, 1.0);
+    let expected = mono().generate_audio(&input);
+
+    let mut buffer = vec![0.0; expected.len() + 100];
+    let mut sink = SliceSink::from(&mut buffer[..]);
+    mono().generate_audio_extend(&input, &mut sink);
+    assert_eq!(sink.written(), &expected[..]);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{drain, stretcher};
+use soundtouch::SoundTouch;
+
+fn ramp(channels: usize, frames: usize) -> Vec<f32> {
+    (0..frames * channels)
+        .map(|i| (i / channels) as f32 / frames as f32)
+        .collect()
+}
+
+fn primed(input: &[f32]) -> SoundTouch {
+    let mut soundtouch = stretcher(2, 44100, 1.2);
+    soundtouch.put_samples(input, input.len() / 2);
+    soundtouch
+}
+
+#[test]
+fn skip_samples_continues_at_right_position() {
+    let input = ramp(2, 44100);
+    let expected = drain(&mut primed(&input));
+
+    let mut soundtouch = primed(&input);
+    let ready = soundtouch.num_samples() as usize;
+    assert!(ready > 0);
+    let skipped = soundtouch.skip_samples(ready / 2);
+    assert_eq!(skipped, ready / 2);
+
+    let output = drain(&mut soundtouch);
+    assert_eq!(output, expected[skipped * 2..]);
+    // The ramp is increasing, so the first frame after the skip comes later in the input.
+    assert!(output[0] > expected[0]);
+}
+
+#[test]
+fn skip_samples_is_bounded_by_ready_frames() {
+    let mut soundtouch = primed(&ramp(2, 44100));
+    let ready = soundtouch.num_samples() as usize;
+    assert_eq!(soundtouch.skip_samples(ready + 1000), ready);
+    assert_eq!(soundtouch.skip_samples(10), 0);
+}
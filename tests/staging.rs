@@ -0,0 +1,78 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, SoundTouch};
+
+/// Puts `input` in packets of the sizes of `packets`, in turn, receiving the output
+/// after every packet, then flushes.
+fn in_packets(soundtouch: &mut SoundTouch, input: &[f32], packets: &[usize]) -> Vec<f32> {
+    let mut output = Vec::new();
+    let mut offset = 0;
+    for &frames in packets.iter().cycle() {
+        let frames = frames.min(input.len() / 2 - offset);
+        if frames == 0 {
+            break;
+        }
+        soundtouch.put_samples(&input[offset * 2..], frames);
+        offset += frames;
+        output.extend(common::drain(soundtouch));
+    }
+    soundtouch.flush();
+    output.extend(common::drain(soundtouch));
+    output
+}
+
+#[test]
+fn matches_unstaged_processing() {
+    let input = signal::sine(220.0, 44100, 100_000).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 1.3);
+    st.set_pitch(0.9);
+    let unstaged = in_packets(&mut st, &input, &[128]);
+    for packets in [&[128][..], &[1152, 960, 7, 4096, 1]] {
+        let mut st = stretcher(2, 44100, 1.3);
+        st.set_pitch(0.9).set_input_staging_frames(Some(2048));
+        let staged = in_packets(&mut st, &input, packets);
+        assert_eq!(staged, unstaged, "{packets:?}");
+    }
+}
+
+#[test]
+fn staged_frames_count_as_unprocessed() {
+    let input = signal::sine(220.0, 44100, 3000).stereo().into_vec();
+    let mut soundtouch = stretcher(2, 44100, 1.3);
+    soundtouch
+        .set_pitch(0.9)
+        .set_input_staging_frames(Some(2048));
+    soundtouch.put_samples(&input, 1000);
+    assert_eq!(soundtouch.num_unprocessed_samples(), 1000);
+    assert_eq!(soundtouch.available_frames(), 0);
+
+    // Completing the batch puts it; the remaining 952 frames are staged.
+    soundtouch.put_samples(&input[2000..], 2000);
+    let unprocessed = soundtouch.num_unprocessed_samples();
+    assert!(unprocessed >= 952, "{unprocessed}");
+    assert_eq!(unprocessed, soundtouch.pending_input_frames());
+
+    soundtouch.clear();
+    assert_eq!(soundtouch.num_unprocessed_samples(), 0);
+}
+
+#[test]
+fn changing_the_batch_size_puts_the_staged_frames() {
+    let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    let mut staged = stretcher(2, 44100, 1.3);
+    staged.set_pitch(0.9).set_input_staging_frames(Some(4096));
+    staged.put_samples(&input, 10_000);
+    let mut unstaged = stretcher(2, 44100, 1.3);
+    unstaged.set_pitch(0.9).set_input_staging_frames(None);
+    unstaged.put_samples(&input, 10_000);
+    assert_eq!(staged.input_staging_frames(), Some(4096));
+
+    staged.set_input_staging_frames(None);
+    assert_eq!(staged.input_staging_frames(), None);
+    assert_eq!(
+        staged.num_unprocessed_samples(),
+        unstaged.num_unprocessed_samples()
+    );
+    assert_eq!(common::drain(&mut staged), common::drain(&mut unstaged));
+}
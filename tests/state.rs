@@ -0,0 +1,145 @@
+mod common;
+
+use common::{drain, process, stretcher};
+use soundtouch::{signal, SoundTouch, SoundTouchError, StreamState};
+
+/// A flushed instance whose output has been received.
+fn flushed() -> SoundTouch {
+    let mut st = stretcher(2, 44100, 0.9);
+    process(
+        &mut st,
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+    );
+    st
+}
+
+#[test]
+fn new_is_streaming() {
+    let st = stretcher(2, 44100, 0.9);
+    assert_eq!(st.state(), StreamState::Streaming);
+    assert!(!st.is_flushed());
+}
+
+#[test]
+fn flush_and_second_flush() {
+    let mut st = stretcher(2, 44100, 0.9);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+        10_000,
+    );
+    assert_eq!(st.state(), StreamState::Streaming);
+    st.flush();
+    assert!(st.is_flushed());
+
+    let ready = st.available_frames();
+    let padding = st.expected_flush_padding();
+    st.flush();
+    assert!(st.is_flushed());
+    assert_eq!(st.available_frames(), ready);
+    assert_eq!(st.expected_flush_padding(), padding);
+}
+
+#[test]
+fn put_with_pending_flushed_output() {
+    let mut st = stretcher(2, 44100, 0.9);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+        10_000,
+    );
+    st.flush();
+    let ready = st.available_frames();
+    assert_eq!(
+        st.try_put_samples(&signal::sine(220.0, 44100, 100).stereo().into_vec(), 100),
+        Err(SoundTouchError::AlreadyFlushed {
+            ready_frames: ready
+        })
+    );
+    assert!(st.is_flushed());
+    assert_eq!(st.available_frames(), ready);
+}
+
+#[test]
+fn put_samples_discards_pending_flushed_output() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let expected = process(&mut stretcher(2, 44100, 0.9), &input);
+
+    let mut st = stretcher(2, 44100, 0.9);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+        10_000,
+    );
+    st.flush();
+    let mut buffer = [0.0; 200];
+    assert_eq!(st.receive_samples(&mut buffer, 100), 100);
+    assert!(st.available_frames() > 0);
+
+    st.put_samples(&input, 20_000);
+    assert_eq!(st.state(), StreamState::Streaming);
+    st.flush();
+    assert_eq!(drain(&mut st), expected);
+}
+
+#[test]
+fn put_after_received_flush_starts_new_stream() {
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let expected = process(&mut stretcher(2, 44100, 0.9), &input);
+
+    let mut st = flushed();
+    st.put_samples(&input, 20_000);
+    assert_eq!(st.state(), StreamState::Streaming);
+    st.flush();
+    assert_eq!(drain(&mut st), expected);
+}
+
+#[test]
+fn empty_put_keeps_flushed() {
+    let mut st = flushed();
+    st.put_samples(&[], 0);
+    assert!(st.is_flushed());
+}
+
+#[test]
+fn clear_reset_and_deterministic_restart() {
+    let mut st = flushed();
+    st.clear();
+    assert_eq!(st.state(), StreamState::Streaming);
+
+    let mut st = flushed();
+    st.reset();
+    assert_eq!(st.state(), StreamState::Streaming);
+
+    let mut st = flushed();
+    st.set_deterministic(true);
+    assert_eq!(st.state(), StreamState::Streaming);
+}
+
+#[test]
+fn process_chunk_and_finish() {
+    let input = signal::sine(220.0, 44100, 30_000).stereo().into_vec();
+    let expected = stretcher(2, 44100, 0.9).generate_audio(&input);
+
+    let mut st = stretcher(2, 44100, 0.9);
+    let mut output = Vec::new();
+    for chunk in input.chunks(4000) {
+        st.process_chunk(chunk, &mut output);
+        assert!(!st.is_flushed());
+    }
+    st.finish(&mut output);
+    assert!(st.is_flushed());
+    st.finish(&mut output);
+    assert_eq!(output, expected);
+
+    // The next chunk starts a new stream.
+    output.clear();
+    st.process_chunk(&input, &mut output);
+    st.finish(&mut output);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn generate_audio_twice() {
+    let input = signal::sine(220.0, 44100, 15_000).stereo().into_vec();
+    let mut st = stretcher(2, 44100, 0.9);
+    let first = st.generate_audio(&input);
+    assert_eq!(st.generate_audio(&input), first);
+}
@@ -0,0 +1,105 @@
+use soundtouch::{Config, SoundTouchError, StereoMode, StereoStretcher, TimeStretcher};
+
+const SAMPLE_RATE: usize = 44100;
+
+/// A wide stereo signal: a tone in the mid channel and a different one, with decaying
+/// bursts, in the side channel.
+fn wide_signal(seconds: usize) -> Vec<f32> {
+    (0..seconds * SAMPLE_RATE)
+        .flat_map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let mid = 0.4 * (std::f32::consts::TAU * 220.0 * t).sin();
+            let burst = (-(t % 0.25) * 20.0).exp();
+            let side = 0.3 * burst * (std::f32::consts::TAU * 587.0 * t).sin();
+            [mid + side, mid - side]
+        })
+        .collect()
+}
+
+/// Returns the RMS of the side channel of interleaved stereo `samples`.
+fn side_rms(samples: &[f32]) -> f64 {
+    let energy: f64 = samples
+        .chunks_exact(2)
+        .map(|frame| {
+            let side = (frame[0] - frame[1]) as f64 / 2.0;
+            side * side
+        })
+        .sum();
+    (energy / (samples.len() / 2) as f64).sqrt()
+}
+
+fn stretch(mode: StereoMode, input: &[f32]) -> (Vec<f32>, StereoStretcher) {
+    let config = Config::new().set_tempo(1.3).clone();
+    let mut stretcher = StereoStretcher::new(&config, mode).unwrap();
+    let mut output = Vec::new();
+    let mut buffer = [0.0; 2 * 1000];
+    for chunk in input.chunks(2 * 3000) {
+        stretcher.put(chunk);
+        loop {
+            let frames = stretcher.receive(&mut buffer);
+            if frames == 0 {
+                break;
+            }
+            output.extend_from_slice(&buffer[..2 * frames]);
+        }
+    }
+    stretcher.flush();
+    loop {
+        let frames = stretcher.receive(&mut buffer);
+        if frames == 0 {
+            break (output, stretcher);
+        }
+        output.extend_from_slice(&buffer[..2 * frames]);
+    }
+}
+
+#[test]
+fn mid_side_preserves_width() {
+    let input = wide_signal(10);
+    let (plain, _) = stretch(StereoMode::LeftRight, &input);
+    let (mid_side, stretcher) = stretch(StereoMode::MidSide, &input);
+    assert_eq!(stretcher.misaligned_blocks(), 0);
+    assert_eq!(stretcher.padded_frames(), 0);
+
+    let expected = side_rms(&input);
+    let plain_error = (side_rms(&plain) / expected - 1.0).abs();
+    let mid_side_error = (side_rms(&mid_side) / expected - 1.0).abs();
+    assert!(
+        mid_side_error <= plain_error + 0.01,
+        "mid/side {mid_side_error}, left/right {plain_error}"
+    );
+    assert!(mid_side_error < 0.15, "{mid_side_error}");
+}
+
+#[test]
+fn mid_side_keeps_the_length() {
+    let input = wide_signal(5);
+    let (plain, _) = stretch(StereoMode::LeftRight, &input);
+    let (mid_side, _) = stretch(StereoMode::MidSide, &input);
+    let expected = input.len() as f64 / 2.0 / 1.3;
+    for output in [&plain, &mid_side] {
+        let frames = output.len() as f64 / 2.0;
+        assert!(
+            (frames / expected - 1.0).abs() < 0.02,
+            "{frames} vs {expected}"
+        );
+    }
+}
+
+#[test]
+fn requires_stereo() {
+    let config = Config::new().set_channels(1).clone();
+    assert_eq!(
+        StereoStretcher::new(&config, StereoMode::MidSide).unwrap_err(),
+        SoundTouchError::ChannelMismatch {
+            expected: 2,
+            actual: 1
+        }
+    );
+    let mut stretcher = StereoStretcher::new(&Config::new(), StereoMode::MidSide).unwrap();
+    assert_eq!(stretcher.channels(), 2);
+    assert!(matches!(
+        stretcher.try_put(&[0.0; 3]),
+        Err(SoundTouchError::PartialFrame { .. })
+    ));
+}
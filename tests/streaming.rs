@@ -0,0 +1,69 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{process_streaming, signal, SoundTouch};
+
+/// Streams `input` through `soundtouch`, handing out at most `max_read` frames per read.
+fn stream(
+    soundtouch: &mut SoundTouch,
+    input: &[f32],
+    chunk_frames: usize,
+    max_read: usize,
+) -> Vec<f32> {
+    let channels = soundtouch.num_channels() as usize;
+    let mut remaining = input;
+    let mut output = Vec::new();
+    process_streaming(
+        soundtouch,
+        |buffer| {
+            let frames = (remaining.len() / channels)
+                .min(buffer.len() / channels)
+                .min(max_read);
+            let (chunk, rest) = remaining.split_at(frames * channels);
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            remaining = rest;
+            frames
+        },
+        |samples| {
+            assert!(samples.len() <= chunk_frames * channels);
+            output.extend_from_slice(samples);
+        },
+        chunk_frames,
+    );
+    output
+}
+
+#[test]
+fn matches_generate_audio() {
+    for channels in [1, 2, 6] {
+        let input = signal::sine(220.0, 44100, 25_000)
+            .channels(channels)
+            .into_vec();
+        let mut soundtouch = stretcher(channels, 44100, 1.3);
+        soundtouch.set_pitch_semitones(2);
+        let expected = soundtouch.generate_audio(&input);
+        for (chunk_frames, max_read) in [(64, 64), (1000, 333), (4096, 4096), (100_000, 100_000)] {
+            let mut soundtouch = stretcher(channels, 44100, 1.3);
+            soundtouch.set_pitch_semitones(2);
+            let output = stream(&mut soundtouch, &input, chunk_frames, max_read);
+            assert!(
+                output == expected,
+                "{channels} channels, chunks of {chunk_frames}"
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_input() {
+    let mut soundtouch = stretcher(2, 44100, 0.7);
+    soundtouch.set_pitch_semitones(2);
+    let output = stream(&mut soundtouch, &[], 512, 512);
+    assert!(output.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "chunk_frames")]
+fn zero_chunk_panics() {
+    process_streaming(&mut stretcher(2, 44100, 1.0), |_| 0, |_| {}, 0);
+}
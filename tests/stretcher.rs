@@ -0,0 +1,122 @@
+use soundtouch::testing::PassthroughStretcher;
+use soundtouch::{process_streaming, signal, Config, SoundTouch, TimeStretcher};
+
+fn config(channels: u32, tempo: f64, rate: f64) -> Config {
+    let mut config = Config::new();
+    config
+        .set_channels(channels)
+        .set_sample_rate(44100)
+        .set_tempo(tempo)
+        .set_rate(rate);
+    config
+}
+
+/// Streams `input` through `stretcher` in reads of `chunk_frames` frames.
+fn stream<S: TimeStretcher + ?Sized>(
+    stretcher: &mut S,
+    input: &[f32],
+    chunk_frames: usize,
+) -> Vec<f32> {
+    let channels = stretcher.channels() as usize;
+    let mut remaining = input;
+    let mut output = Vec::new();
+    process_streaming(
+        stretcher,
+        |buffer| {
+            let frames = (remaining.len() / channels).min(buffer.len() / channels);
+            let (chunk, rest) = remaining.split_at(frames * channels);
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            remaining = rest;
+            frames
+        },
+        |samples| output.extend_from_slice(samples),
+        chunk_frames,
+    );
+    output
+}
+
+fn configured<S: TimeStretcher>(mut stretcher: S, config: &Config) -> S {
+    stretcher.configure(config);
+    stretcher
+}
+
+#[test]
+fn soundtouch_streams_like_generate_audio() {
+    let config = config(2, 1.25, 0.9);
+    let input = signal::sine(220.0, 44100, 20_000).stereo().into_vec();
+    let expected = SoundTouch::with_config(&config).generate_audio(&input);
+    for chunk_frames in [64, 1000, 50_000] {
+        let mut soundtouch = configured(SoundTouch::new(), &config);
+        assert!(stream(&mut soundtouch, &input, chunk_frames) == expected);
+    }
+}
+
+#[test]
+fn passthrough_scales_the_length() {
+    for (tempo, rate) in [(1.0, 1.0), (0.5, 1.0), (1.3, 1.0), (1.1, 0.7)] {
+        let config = config(2, tempo, rate);
+        let mut passthrough = configured(PassthroughStretcher::new(), &config);
+        let ratio = passthrough.io_ratio();
+        assert!((ratio - 1.0 / (tempo * rate)).abs() < 1e-12);
+
+        let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+        let output = stream(&mut passthrough, &input, 333);
+        assert_eq!(output.len() / 2, (10_000.0 * ratio).ceil() as usize);
+        for (k, frame) in output.chunks(2).enumerate() {
+            let source = ((k as f64 / ratio) as usize).min(9_999);
+            assert_eq!(frame, &input[source * 2..source * 2 + 2], "frame {k}");
+        }
+    }
+}
+
+#[test]
+fn passthrough_output_doesnt_depend_on_chunks() {
+    let config = config(3, 0.8, 1.0);
+    let input = signal::sine(220.0, 44100, 5_000).channels(3).into_vec();
+    let expected = stream(
+        &mut configured(PassthroughStretcher::new(), &config),
+        &input,
+        5_000,
+    );
+    for chunk_frames in [1, 7, 64, 1000] {
+        let mut passthrough = configured(PassthroughStretcher::new(), &config);
+        assert_eq!(stream(&mut passthrough, &input, chunk_frames), expected);
+    }
+}
+
+#[test]
+fn passthrough_flush_starts_a_new_stream() {
+    let mut passthrough = configured(PassthroughStretcher::new(), &config(1, 0.5, 1.0));
+    let mut out = [0.0; 16];
+    passthrough.put(&[1.0, 2.0, 3.0]);
+    passthrough.flush();
+    assert_eq!(passthrough.receive(&mut out), 6);
+    passthrough.put(&[4.0]);
+    assert_eq!(passthrough.receive(&mut out), 2);
+    assert_eq!(out[..2], [4.0, 4.0]);
+    assert_eq!(passthrough.receive(&mut out), 0);
+}
+
+#[test]
+fn adapters_take_trait_objects() {
+    let config = config(2, 1.5, 1.0);
+    let input = signal::sine(220.0, 44100, 8_000).stereo().into_vec();
+    let mut stretchers: Vec<Box<dyn TimeStretcher>> = vec![
+        Box::new(configured(SoundTouch::new(), &config)),
+        Box::new(configured(PassthroughStretcher::new(), &config)),
+    ];
+    for stretcher in &mut stretchers {
+        let expected = input.len() as f64 / 2.0 * stretcher.io_ratio();
+        let frames = stream(stretcher.as_mut(), &input, 512).len() / 2;
+        assert!(
+            (frames as f64 - expected).abs() <= 1.0,
+            "{frames} frames, expected {expected}"
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "must be set")]
+fn unconfigured_passthrough_panics() {
+    stream(&mut PassthroughStretcher::new(), &[], 64);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::thread;
+
+use common::{drain, stretcher};
+use soundtouch::{signal, BPMDetect};
+
+#[test]
+fn soundtouch_moved_between_threads_mid_stream() {
+    let mut first = signal::sine(220.0, 44100, 44100).stereo().into_vec();
+    let second = first.split_off(first.len() / 2);
+
+    let mut reference = stretcher(2, 44100, 1.3);
+    reference.put_samples(&first, first.len() / 2);
+    let mut expected = drain(&mut reference);
+    reference.put_samples(&second, second.len() / 2);
+    reference.flush();
+    expected.extend(drain(&mut reference));
+
+    let mut soundtouch = stretcher(2, 44100, 1.3);
+    let (mut soundtouch, mut output) = thread::spawn(move || {
+        soundtouch.put_samples(&first, first.len() / 2);
+        let output = drain(&mut soundtouch);
+        (soundtouch, output)
+    })
+    .join()
+    .unwrap();
+    let output = thread::spawn(move || {
+        soundtouch.put_samples(&second, second.len() / 2);
+        soundtouch.flush();
+        output.extend(drain(&mut soundtouch));
+        output
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn bpm_detect_moved_between_threads_mid_stream() {
+    let mut first = signal::sine(220.0, 44100, 44100 * 4).stereo().into_vec();
+    let second = first.split_off(first.len() / 2);
+
+    let mut reference = BPMDetect::new(2, 44100);
+    reference.input_samples(&first);
+    reference.input_samples(&second);
+
+    let mut detector = BPMDetect::new(2, 44100);
+    detector.input_samples(&first);
+    let mut detector = thread::spawn(move || {
+        detector.input_samples(&second);
+        detector
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(detector.get_bpm(), reference.get_bpm());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{signal, BlockKind, SoundTouch, TimingPercentiles};
+
+#[test]
+fn disabled_by_default() {
+    let mut st = stretcher(2, 44100, 1.0);
+    assert!(!st.is_block_timing());
+    st.put_samples(&signal::sine(220.0, 44100, 1024).stereo().into_vec(), 1024);
+    assert_eq!(st.recent_timings().count(), 0);
+    assert_eq!(st.timing_percentiles(), None);
+}
+
+#[test]
+fn puts_and_receives_are_recorded() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_block_timing(true);
+    st.put_samples(
+        &signal::sine(220.0, 44100, 10_000).stereo().into_vec(),
+        10_000,
+    );
+    let mut buffer = [0.0; 2 * 512];
+    let received = st.receive_samples(&mut buffer, 512);
+
+    let timings: Vec<_> = st.recent_timings().collect();
+    assert_eq!(timings.len(), 2);
+    assert_eq!(
+        (timings[0].kind, timings[0].frames),
+        (BlockKind::Put, 10_000)
+    );
+    assert_eq!(
+        (timings[1].kind, timings[1].frames),
+        (BlockKind::Receive, received)
+    );
+}
+
+#[test]
+fn failed_calls_are_not_recorded() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_block_timing(true);
+    assert!(st.try_put_samples(&[0.0; 10], 100).is_err());
+    assert!(st.try_receive_samples(&mut [0.0; 10], 100).is_err());
+    assert_eq!(st.recent_timings().count(), 0);
+}
+
+#[test]
+fn ring_keeps_the_latest_timings_in_order() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_block_timing(true);
+    let input = signal::sine(220.0, 44100, 300).stereo().into_vec();
+    for frames in 1..=300 {
+        st.put_samples(&input, frames);
+    }
+    let frames: Vec<_> = st.recent_timings().map(|timing| timing.frames).collect();
+    let expected: Vec<_> = (300 - SoundTouch::BLOCK_TIMING_CAPACITY + 1..=300).collect();
+    assert_eq!(frames, expected);
+
+    let percentiles = st.timing_percentiles().unwrap();
+    assert_eq!(percentiles.count, SoundTouch::BLOCK_TIMING_CAPACITY);
+    assert!(percentiles.p50 <= percentiles.p90);
+    assert!(percentiles.p90 <= percentiles.p99);
+    assert!(percentiles.p99 <= percentiles.max);
+}
+
+#[test]
+fn enabling_again_keeps_and_disabling_drops_the_timings() {
+    let mut st = stretcher(2, 44100, 1.0);
+    st.set_block_timing(true);
+    st.put_samples(&signal::sine(220.0, 44100, 64).stereo().into_vec(), 64);
+    st.set_block_timing(true);
+    assert_eq!(st.recent_timings().count(), 1);
+    st.set_block_timing(false);
+    assert_eq!(st.recent_timings().count(), 0);
+    st.set_block_timing(true);
+    assert_eq!(st.recent_timings().count(), 0);
+    st.reset();
+    assert!(!st.is_block_timing());
+}
+
+#[test]
+fn percentiles_use_the_nearest_rank() {
+    // 1 to 100, shuffled.
+    let mut micros: Vec<u64> = (0..100).map(|i| (i * 37 % 100) + 1).collect();
+    assert_eq!(
+        TimingPercentiles::from_micros(&mut micros),
+        Some(TimingPercentiles {
+            count: 100,
+            p50: 50,
+            p90: 90,
+            p99: 99,
+            max: 100,
+        })
+    );
+
+    let percentiles = TimingPercentiles::from_micros(&mut [5, 1, 3]).unwrap();
+    assert_eq!(
+        (percentiles.p50, percentiles.p90, percentiles.p99),
+        (3, 5, 5)
+    );
+    let percentiles = TimingPercentiles::from_micros(&mut [7]).unwrap();
+    assert_eq!((percentiles.p50, percentiles.max), (7, 7));
+    assert_eq!(TimingPercentiles::from_micros(&mut []), None);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::stretcher;
+use soundtouch::{Setting, SoundTouchError, TuningParams};
+
+#[test]
+fn default_matches_a_new_instance() {
+    assert_eq!(stretcher(2, 44100, 1.0).tuning(), TuningParams::default());
+    assert_eq!(TuningParams::default().validate(), Ok(()));
+}
+
+#[test]
+fn round_trips() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let tuning = TuningParams {
+        sequence_ms: Some(60),
+        seek_window_ms: Some(18),
+        overlap_ms: 12,
+        quickseek: true,
+        aa_filter: false,
+        aa_filter_length: 32,
+    };
+    st.set_tuning(&tuning).unwrap();
+    assert_eq!(st.tuning(), tuning);
+    assert_eq!(st.get_setting(Setting::SequenceMs), 60);
+    assert_eq!(st.get_setting(Setting::OverlapMs), 12);
+
+    st.set_tuning(&TuningParams::default()).unwrap();
+    assert_eq!(st.tuning(), TuningParams::default());
+}
+
+#[test]
+fn order_handles_overlap_beyond_the_old_sequence() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let short = TuningParams {
+        sequence_ms: Some(20),
+        overlap_ms: 5,
+        ..TuningParams::default()
+    };
+    st.set_tuning(&short).unwrap();
+    let long = TuningParams {
+        sequence_ms: Some(100),
+        overlap_ms: 30,
+        ..TuningParams::default()
+    };
+    st.set_tuning(&long).unwrap();
+    assert_eq!(st.tuning(), long);
+    st.set_tuning(&short).unwrap();
+    assert_eq!(st.tuning(), short);
+}
+
+#[test]
+fn overlap_must_be_shorter_than_the_sequence() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let tuning = TuningParams {
+        sequence_ms: Some(30),
+        overlap_ms: 30,
+        ..TuningParams::default()
+    };
+    assert_eq!(
+        st.set_tuning(&tuning).err(),
+        Some(SoundTouchError::OverlapTooLong {
+            overlap_ms: 30,
+            sequence_ms: Some(30)
+        })
+    );
+
+    let automatic = TuningParams {
+        overlap_ms: TuningParams::MIN_AUTO_SEQUENCE_MS,
+        ..TuningParams::default()
+    };
+    assert_eq!(
+        automatic.validate(),
+        Err(SoundTouchError::OverlapTooLong {
+            overlap_ms: TuningParams::MIN_AUTO_SEQUENCE_MS,
+            sequence_ms: None
+        })
+    );
+    assert_eq!(st.tuning(), TuningParams::default());
+}
+
+#[test]
+fn out_of_range_values_are_rejected_without_changes() {
+    let mut st = stretcher(2, 44100, 1.0);
+    let cases = [
+        (
+            TuningParams {
+                sequence_ms: Some(0),
+                ..TuningParams::default()
+            },
+            Setting::SequenceMs,
+            0,
+        ),
+        (
+            TuningParams {
+                seek_window_ms: Some(TuningParams::MAX_MS + 1),
+                ..TuningParams::default()
+            },
+            Setting::SeekwindowMs,
+            1001,
+        ),
+        (
+            TuningParams {
+                aa_filter_length: 12,
+                quickseek: true,
+                ..TuningParams::default()
+            },
+            Setting::AaFilterLength,
+            12,
+        ),
+        (
+            TuningParams {
+                aa_filter_length: 136,
+                ..TuningParams::default()
+            },
+            Setting::AaFilterLength,
+            136,
+        ),
+    ];
+    for (tuning, setting, value) in cases {
+        assert_eq!(
+            st.set_tuning(&tuning).err(),
+            Some(SoundTouchError::InvalidSetting { setting, value })
+        );
+    }
+    assert_eq!(st.tuning(), TuningParams::default());
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::time::Duration;
+
+use common::stretcher;
+use soundtouch::signal;
+use soundtouch::units::{Frames, Samples};
+
+#[test]
+fn conversions() {
+    assert_eq!(Frames(3360).samples(2), Samples(6720));
+    assert_eq!(Samples(6720).frames(2), Frames(3360));
+    assert_eq!(Samples(6721).frames(2), Frames(3360));
+    assert_eq!(Samples(6721).remainder(2), 1);
+    assert_eq!(Samples(10).frames(0), Frames(0));
+    assert_eq!(Samples(10).remainder(0), 0);
+    assert_eq!(Frames(usize::MAX).samples(2), Samples(usize::MAX));
+    assert_eq!(Samples::len_of(&[0.0; 12]).frames(6), Frames(2));
+    assert_eq!(Frames(3) + Frames(4) - Frames(2), Frames(5));
+    assert_eq!(usize::from(Samples(7)), 7);
+}
+
+#[test]
+fn duration_conversions() {
+    // 220.5 frames round up, 44.1 down.
+    assert_eq!(
+        Frames::from_duration(Duration::from_millis(5), 44100),
+        Some(Frames(221))
+    );
+    assert_eq!(
+        Frames::from_duration(Duration::from_millis(1), 44100),
+        Some(Frames(44))
+    );
+    assert_eq!(
+        Frames::from_duration(Duration::from_secs(3), 44100),
+        Some(Frames(132_300))
+    );
+    assert_eq!(
+        Frames::from_duration(Duration::ZERO, 44100),
+        Some(Frames(0))
+    );
+    assert_eq!(Frames::from_duration(Duration::from_secs(1), 0), None);
+    assert_eq!(Frames::from_duration(Duration::MAX, 44100), None);
+
+    assert_eq!(Frames(44100).to_duration(44100), Duration::from_secs(1));
+    // 22.6757... µs.
+    assert_eq!(Frames(1).to_duration(44100), Duration::from_nanos(22_676));
+    assert_eq!(
+        Frames(221).to_duration(44100),
+        Duration::from_nanos(5_011_338)
+    );
+    for frames in [0, 1, 220, 221, 44_099, 1 << 40] {
+        let duration = Frames(frames).to_duration(44100);
+        assert_eq!(Frames::from_duration(duration, 44100), Some(Frames(frames)));
+    }
+}
+
+#[test]
+fn frame_methods_match_raw_methods() {
+    let input = signal::sine(220.0, 44100, 10_000).stereo().into_vec();
+    let mut raw = stretcher(2, 44100, 0.8);
+    let mut typed = stretcher(2, 44100, 0.8);
+
+    raw.put_samples(&input, input.len() / 2);
+    typed.put_frames(&input, Samples::len_of(&input).frames(2));
+    assert_eq!(raw.skip_samples(100), 100);
+    assert_eq!(typed.skip_frames(Frames(100)), Frames(100));
+
+    let mut a = [0.0; 2048];
+    let mut b = [0.0; 2048];
+    let received = raw.receive_samples(&mut a, 1024);
+    assert_eq!(
+        typed.receive_frames(&mut b, Frames(1024)).frames,
+        Frames(received)
+    );
+    assert_eq!(a, b);
+}
+
+/// Passing the slice length as the frame count of a stereo buffer asks for twice the
+/// available frames, which is caught instead of reading past the end.
+#[test]
+#[should_panic(expected = "frames of 2 channels requested")]
+fn sample_count_as_frames_panics() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    let input = vec![0.0; 6720];
+    soundtouch.put_frames(&input, Frames(input.len()));
+}
+
+/// The same mix-up when receiving would write past the end of the buffer.
+#[test]
+#[should_panic(expected = "but only room for 6720 samples")]
+fn receive_sample_count_as_frames_panics() {
+    let mut soundtouch = stretcher(2, 44100, 1.0);
+    let mut output = vec![0.0; 6720];
+    let max = Frames(output.len());
+    soundtouch.receive_frames(&mut output, max);
+}
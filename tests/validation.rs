@@ -0,0 +1,45 @@
+use soundtouch::{SoundTouch, SoundTouchError};
+
+#[test]
+fn try_set_channels() {
+    let mut soundtouch = SoundTouch::new();
+    for channels in [1, 2, 6, SoundTouch::MAX_CHANNELS] {
+        soundtouch.try_set_channels(channels).unwrap();
+        assert_eq!(soundtouch.num_channels(), channels);
+    }
+    for channels in [0, SoundTouch::MAX_CHANNELS + 1, 65] {
+        assert_eq!(
+            soundtouch.try_set_channels(channels).unwrap_err(),
+            SoundTouchError::InvalidChannels(channels)
+        );
+        assert_eq!(soundtouch.num_channels(), SoundTouch::MAX_CHANNELS);
+    }
+}
+
+#[test]
+fn try_set_sample_rate() {
+    let mut soundtouch = SoundTouch::new();
+    for sample_rate in [1, 8000, 44100, SoundTouch::MAX_SAMPLE_RATE] {
+        assert!(soundtouch.try_set_sample_rate(sample_rate).is_ok());
+    }
+    for sample_rate in [0, SoundTouch::MAX_SAMPLE_RATE + 1] {
+        assert_eq!(
+            soundtouch.try_set_sample_rate(sample_rate).unwrap_err(),
+            SoundTouchError::InvalidSampleRate(sample_rate)
+        );
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid number of channels")]
+fn set_channels_zero_asserts() {
+    SoundTouch::new().set_channels(0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid sample rate")]
+fn set_sample_rate_zero_asserts() {
+    SoundTouch::new().set_sample_rate(0);
+}
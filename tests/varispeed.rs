@@ -0,0 +1,66 @@
+mod common;
+
+use soundtouch::{SoundTouch, Varispeed};
+
+fn process(soundtouch: &mut SoundTouch) -> Vec<f32> {
+    soundtouch.set_channels(1).set_sample_rate(44100);
+    soundtouch.generate_audio(&common::tone(1000.0, 44100, 44100 * 2))
+}
+
+#[test]
+fn turntable() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_varispeed(8.0, false);
+    let varispeed = soundtouch.varispeed();
+    assert!((varispeed.percent - 8.0).abs() < 1e-9);
+    assert!(!varispeed.key_lock);
+    let params = soundtouch.effective_parameters();
+    assert_eq!(params.tempo, 1.0);
+    assert!((params.rate - 1.08).abs() < 1e-9);
+
+    let output = process(&mut soundtouch);
+    let expected = (44100.0 * 2.0 / 1.08) as usize;
+    assert!(output.len().abs_diff(expected) <= 1);
+    let peak = common::peak_frequency(&output[4096..], 44100, 1000.0, 1150.0, 1.0);
+    assert!((peak - 1080.0).abs() <= 2.0, "{peak} Hz");
+}
+
+#[test]
+fn key_lock() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_varispeed(-6.0, true);
+    let varispeed = soundtouch.varispeed();
+    assert!((varispeed.percent + 6.0).abs() < 1e-9);
+    assert!(varispeed.key_lock);
+
+    let output = process(&mut soundtouch);
+    let expected = (44100.0 * 2.0 / 0.94) as usize;
+    assert!(output.len().abs_diff(expected) <= 1);
+    let peak = common::peak_frequency(&output[4096..], 44100, 900.0, 1100.0, 1.0);
+    assert!((peak - 1000.0).abs() <= 2.0, "{peak} Hz");
+}
+
+#[test]
+fn switching_modes() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_pitch_semitones(2);
+    soundtouch
+        .set_varispeed(8.0, true)
+        .set_varispeed(4.0, false);
+    let params = soundtouch.effective_parameters();
+    assert_eq!(params.virtual_tempo, 1.0);
+    assert!((params.virtual_rate - 1.04).abs() < 1e-9);
+    // The pitch setting isn't touched.
+    assert!((params.virtual_pitch - 2f64.powf(2.0 / 12.0)).abs() < 1e-9);
+
+    soundtouch.reset();
+    assert_eq!(soundtouch.varispeed(), Varispeed::default());
+}
+
+#[test]
+fn rate_change_sets_rate() {
+    let mut soundtouch = SoundTouch::new();
+    soundtouch.set_rate_change(25.0);
+    let params = soundtouch.effective_parameters();
+    assert_eq!((params.virtual_tempo, params.virtual_rate), (1.0, 1.25));
+}
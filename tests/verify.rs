@@ -0,0 +1,52 @@
+mod common;
+
+use soundtouch::verify::stretch_and_check_bpm;
+
+#[test]
+fn click_track() {
+    let input = common::click_track(2, 44100, 100.0, 30.0, 0.8);
+    for tempo in [0.8, 0.9, 1.1, 1.25, 1.5] {
+        let check = stretch_and_check_bpm(&input, 2, 44100, tempo);
+        assert!((check.input_bpm - 100.0).abs() < 1.0, "{check:?}");
+        assert_eq!(check.expected_output_bpm, check.input_bpm * tempo);
+        assert!(check.relative_error < 0.01, "tempo {tempo}: {check:?}");
+    }
+}
+
+#[test]
+fn beer() {
+    // beer.wav is a short clip without a steady beat of its own, so loop it over a
+    // click track.
+    let mut reader = hound::WavReader::open("tests/beer.wav").unwrap();
+    assert_eq!(reader.spec().channels, 2);
+    let beer: Vec<f32> = reader
+        .samples::<i16>()
+        .map(|sample| sample.unwrap() as f32 / 32768.0)
+        .collect();
+    let clicks = common::click_track(2, reader.spec().sample_rate as usize, 100.0, 20.0, 0.5);
+    let input: Vec<f32> = beer
+        .iter()
+        .cycle()
+        .zip(&clicks)
+        .map(|(beer, click)| beer + click)
+        .collect();
+
+    for tempo in [0.8, 1.1, 1.25] {
+        let check = stretch_and_check_bpm(&input, 2, reader.spec().sample_rate, tempo);
+        assert!((check.input_bpm - 100.0).abs() < 1.0, "{check:?}");
+        assert!(check.relative_error < 0.01, "tempo {tempo}: {check:?}");
+    }
+}
+
+#[test]
+fn silence() {
+    let check = stretch_and_check_bpm(&[0.0; 44100 * 2], 2, 44100, 1.5);
+    assert_eq!(check.input_bpm, 0.0);
+    assert_eq!(check.relative_error, f64::INFINITY);
+}
+
+#[test]
+#[should_panic(expected = "effective tempo 100 and rate 1 must both be within")]
+fn invalid_tempo() {
+    stretch_and_check_bpm(&[0.0; 44100 * 2], 2, 44100, 100.0);
+}
@@ -0,0 +1,291 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+use soundtouch::wav::{
+    process_file_streaming, process_file_streaming_cancellable, process_file_streaming_excluding,
+    process_file_streaming_with_loudness, process_file_streaming_with_progress, read_channel_mask,
+    write_channel_mask,
+};
+use soundtouch::{quantize, signal, CancellationToken, ChannelMask, Config, SoundTouch, WavError};
+
+const SAMPLE_RATE: u32 = 44100;
+const CHUNK_FRAMES: usize = 4096;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("soundtouch-wav-{}-{name}.wav", std::process::id()))
+}
+
+fn spec(sample_format: hound::SampleFormat, bits_per_sample: u16) -> hound::WavSpec {
+    hound::WavSpec {
+        channels: 2,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample,
+        sample_format,
+    }
+}
+
+/// Writes 30 seconds of the test signal as a float WAV file and returns the samples.
+fn write_float_input(path: &Path) -> Vec<f32> {
+    let samples = signal::sine(220.0, SAMPLE_RATE, 30 * SAMPLE_RATE as usize)
+        .stereo()
+        .into_vec();
+    let mut writer = hound::WavWriter::create(path, spec(hound::SampleFormat::Float, 32)).unwrap();
+    for &sample in &samples {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+    samples
+}
+
+fn config() -> Config {
+    Config::new().set_tempo(1.25).clone()
+}
+
+fn in_memory(samples: &[f32]) -> Vec<f32> {
+    let mut config = config();
+    config.set_channels(2).set_sample_rate(SAMPLE_RATE);
+    SoundTouch::with_config(&config).generate_audio(samples)
+}
+
+#[test]
+fn matches_the_in_memory_path() {
+    let (input, output) = (temp_path("float-in"), temp_path("float-out"));
+    let samples = write_float_input(&input);
+
+    let stats = process_file_streaming(&input, &output, &config(), CHUNK_FRAMES).unwrap();
+    let mut reader = hound::WavReader::open(&output).unwrap();
+    assert_eq!(reader.spec(), spec(hound::SampleFormat::Float, 32));
+    let written: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+
+    assert_eq!(stats.frames_in, 30 * SAMPLE_RATE as u64);
+    assert_eq!(stats.frames_out, written.len() as u64 / 2);
+    assert!(!stats.cancelled);
+    assert_eq!(written, in_memory(&samples));
+    // The pipeline holds a chunk and the time-stretcher's window, not the file.
+    assert!(
+        stats.peak_pipeline_frames < SAMPLE_RATE as usize,
+        "{}",
+        stats.peak_pipeline_frames
+    );
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn loudness_of_the_input() {
+    let (input, output) = (temp_path("loudness-in"), temp_path("loudness-out"));
+    let samples = write_float_input(&input);
+
+    let (stats, loudness) =
+        process_file_streaming_with_loudness(&input, &output, &config(), CHUNK_FRAMES).unwrap();
+    assert_eq!(
+        stats,
+        process_file_streaming(&input, &output, &config(), CHUNK_FRAMES).unwrap()
+    );
+    let mut config = config();
+    config.set_channels(2).set_sample_rate(SAMPLE_RATE);
+    let mut soundtouch = SoundTouch::with_config(&config);
+    soundtouch
+        .set_analyze_loudness(true)
+        .generate_audio(&samples);
+    assert_eq!(Some(loudness), soundtouch.loudness());
+    assert_eq!(loudness.frames_measured, stats.frames_in);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn integer_samples_are_quantized() {
+    let (input, output) = (temp_path("int-in"), temp_path("int-out"));
+    let samples: Vec<f32> = signal::sine(220.0, SAMPLE_RATE, 5 * SAMPLE_RATE as usize)
+        .stereo()
+        .map(|sample| quantize(sample) as f32 / 32768.0)
+        .collect();
+    let mut writer = hound::WavWriter::create(&input, spec(hound::SampleFormat::Int, 16)).unwrap();
+    for &sample in &samples {
+        writer.write_sample(quantize(sample)).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    process_file_streaming(&input, &output, &config(), CHUNK_FRAMES).unwrap();
+    let written: Vec<i16> = hound::WavReader::open(&output)
+        .unwrap()
+        .samples::<i16>()
+        .map(Result::unwrap)
+        .collect();
+    let expected: Vec<i16> = in_memory(&samples).into_iter().map(quantize).collect();
+    assert_eq!(written, expected);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn cancelling_leaves_a_valid_file() {
+    let (input, output) = (temp_path("cancel-in"), temp_path("cancel-out"));
+    let samples = write_float_input(&input);
+
+    let mut calls = 0;
+    let stats = process_file_streaming_with_progress(
+        &input,
+        &output,
+        &config(),
+        CHUNK_FRAMES,
+        |done, total| {
+            calls += 1;
+            assert_eq!(done, calls * CHUNK_FRAMES as u64);
+            assert_eq!(total, 30 * SAMPLE_RATE as u64);
+            if calls == 50 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        },
+    )
+    .unwrap();
+    assert!(stats.cancelled);
+    assert_eq!(stats.frames_in, 50 * CHUNK_FRAMES as u64);
+
+    let mut reader = hound::WavReader::open(&output).unwrap();
+    assert_eq!(reader.duration() as u64, stats.frames_out);
+    let written: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+    assert!(!written.is_empty());
+    assert_eq!(written[..], in_memory(&samples)[..written.len()]);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn cancelled_token_stops_processing() {
+    let (input, output) = (temp_path("token-in"), temp_path("token-out"));
+    write_float_input(&input);
+
+    let token = CancellationToken::new();
+    std::thread::scope(|scope| scope.spawn(|| token.cancel()).join().unwrap());
+    let result =
+        process_file_streaming_cancellable(&input, &output, &config(), CHUNK_FRAMES, &token);
+    assert!(matches!(result, Err(WavError::Cancelled)), "{result:?}");
+    // Only the first chunk was processed, and the output is still readable.
+    let reader = hound::WavReader::open(&output).unwrap();
+    assert!((reader.duration() as usize) < CHUNK_FRAMES);
+
+    let stats = process_file_streaming_cancellable(
+        &input,
+        &output,
+        &config(),
+        CHUNK_FRAMES,
+        &CancellationToken::new(),
+    )
+    .unwrap();
+    assert_eq!(stats.frames_in, 30 * SAMPLE_RATE as u64);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+/// 5.1 with the surround speakers at the sides.
+const SIDE_5_1: ChannelMask = ChannelMask(0x60F);
+
+/// Writes 10 seconds of 5.1 as a float WAV file with the `SIDE_5_1` mask: the test signal
+/// in all channels but the LFE, which gets a loud 40 Hz tone.
+fn write_surround_input(path: &Path) {
+    let spec = hound::WavSpec {
+        channels: 6,
+        ..spec(hound::SampleFormat::Float, 32)
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let tone = signal::sine(220.0, SAMPLE_RATE, 10 * SAMPLE_RATE as usize);
+    for (i, sample) in tone.enumerate() {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let lfe = 0.9 * (std::f32::consts::TAU * 40.0 * t).sin();
+        for channel in 0..6 {
+            writer
+                .write_sample(if channel == 3 { lfe } else { sample })
+                .unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+    write_channel_mask(path, SIDE_5_1).unwrap();
+}
+
+fn read_float(path: &Path) -> Vec<f32> {
+    let mut reader = hound::WavReader::open(path).unwrap();
+    reader.samples::<f32>().map(Result::unwrap).collect()
+}
+
+fn hash(samples: &[f32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for sample in samples {
+        sample.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[test]
+fn channel_mask_round_trips() {
+    let (input, output) = (temp_path("mask-in"), temp_path("mask-out"));
+    write_surround_input(&input);
+    assert_eq!(read_channel_mask(&input).unwrap(), Some(SIDE_5_1));
+
+    process_file_streaming(&input, &output, &config(), CHUNK_FRAMES).unwrap();
+    assert_eq!(read_channel_mask(&output).unwrap(), Some(SIDE_5_1));
+    assert_eq!(hound::WavReader::open(&output).unwrap().spec().channels, 6);
+
+    // Plain headers have no mask and can't be given one.
+    write_float_input(&input);
+    assert_eq!(
+        read_channel_mask(&input).unwrap(),
+        Some(ChannelMask::STEREO)
+    );
+    let plain = hound::WavWriter::create(&input, spec(hound::SampleFormat::Int, 16)).unwrap();
+    plain.finalize().unwrap();
+    assert_eq!(read_channel_mask(&input).unwrap(), None);
+    assert!(matches!(
+        write_channel_mask(&input, ChannelMask::STEREO),
+        Err(WavError::Wav(hound::Error::FormatError(_)))
+    ));
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn excluding_the_lfe_changes_the_splices() {
+    let input = temp_path("lfe-in");
+    let (plain, excluded) = (temp_path("lfe-plain"), temp_path("lfe-excluded"));
+    write_surround_input(&input);
+
+    process_file_streaming(&input, &plain, &config(), CHUNK_FRAMES).unwrap();
+    let stats = process_file_streaming_excluding(
+        &input,
+        &excluded,
+        &config(),
+        CHUNK_FRAMES,
+        ChannelMask::LOW_FREQUENCY,
+    )
+    .unwrap();
+    assert_eq!(read_channel_mask(&excluded).unwrap(), Some(SIDE_5_1));
+    let samples = read_float(&excluded);
+    assert_ne!(hash(&read_float(&plain)), hash(&samples));
+    assert_eq!(stats.frames_out, samples.len() as u64 / 6);
+
+    // The channels still share their splices, and the LFE keeps its level.
+    let mut lfe_energy = 0.0;
+    for frame in samples.chunks_exact(6) {
+        assert!(frame[..3].iter().chain(&frame[4..]).all(|&s| s == frame[0]));
+        lfe_energy += (frame[3] as f64).powi(2);
+    }
+    let lfe_rms = (lfe_energy / stats.frames_out as f64).sqrt();
+    assert!(
+        (lfe_rms / (0.9 / 2f64.sqrt()) - 1.0).abs() < 0.15,
+        "{lfe_rms}"
+    );
+
+    for path in [input, plain, excluded] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
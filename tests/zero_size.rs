@@ -0,0 +1,89 @@
+mod common;
+
+use std::time::Duration;
+
+use common::stretcher;
+use soundtouch::units::Frames;
+use soundtouch::{signal, BPMDetect, DriftReport, Pending, SoundTouch};
+
+/// Returns a stretcher with output ready to receive.
+fn with_output() -> SoundTouch {
+    let mut soundtouch = stretcher(2, 44100, 1.25);
+    soundtouch.put_samples(
+        &signal::sine(220.0, 44100, 20_000).stereo().into_vec(),
+        20_000,
+    );
+    assert!(soundtouch.available_frames() > 0);
+    soundtouch
+}
+
+#[test]
+fn empty_puts_are_no_ops() {
+    // Even without a configuration to check against.
+    let mut unconfigured = SoundTouch::new();
+    unconfigured.put_samples(&[], 0);
+    assert_eq!(unconfigured.try_put_samples(&[0.5; 4], 0), Ok(()));
+    unconfigured.put_frames(&[], Frames(0));
+
+    let mut soundtouch = with_output();
+    let unprocessed = soundtouch.num_unprocessed_samples();
+    let available = soundtouch.available_frames();
+    let drift = soundtouch.drift();
+    soundtouch.put_samples(&[], 0);
+    soundtouch.put_samples(&[0.5; 8], 0);
+    soundtouch.put_frames(&[], Frames(0));
+    assert_eq!(soundtouch.num_unprocessed_samples(), unprocessed);
+    assert_eq!(soundtouch.available_frames(), available);
+    assert_eq!(soundtouch.drift(), drift);
+}
+
+#[test]
+fn empty_receives_are_no_ops() {
+    let mut unconfigured = SoundTouch::new();
+    assert_eq!(unconfigured.receive_samples(&mut [], 0), 0);
+    assert_eq!(unconfigured.try_receive_samples(&mut [0.0; 4], 0), Ok(0));
+    assert_eq!(unconfigured.drift(), DriftReport::default());
+
+    let mut soundtouch = with_output();
+    let available = soundtouch.available_frames();
+    let mut buffer = [9.0; 8];
+    assert_eq!(soundtouch.receive_samples(&mut [], 0), 0);
+    assert_eq!(soundtouch.receive_samples(&mut buffer, 0), 0);
+    assert_eq!(buffer, [9.0; 8]);
+    let status = soundtouch.receive_frames(&mut [], Frames(0));
+    assert_eq!(status.frames, Frames(0));
+    assert_eq!(status.pending, Pending::MoreAvailable);
+    assert_eq!(soundtouch.available_frames(), available);
+
+    // The output continues where it would have without the empty calls.
+    let mut expected = with_output();
+    assert_eq!(common::drain(&mut soundtouch), common::drain(&mut expected));
+}
+
+#[test]
+fn empty_skips_are_no_ops() {
+    assert_eq!(SoundTouch::new().skip_samples(0), 0);
+
+    let mut soundtouch = with_output();
+    let available = soundtouch.available_frames();
+    assert_eq!(soundtouch.skip_samples(0), 0);
+    assert_eq!(soundtouch.skip_frames(Frames(0)), Frames(0));
+    assert_eq!(soundtouch.available_frames(), available);
+}
+
+#[test]
+fn empty_bpm_input_is_a_no_op() {
+    let input = common::click_track(2, 44100, 120.0, 10.0, 0.8);
+    let mut expected = BPMDetect::new(2, 44100);
+    expected.input_samples(&input);
+
+    let mut bpm = BPMDetect::new(2, 44100);
+    bpm.input_samples(&[]);
+    assert_eq!(bpm.analysis_duration(), Duration::ZERO);
+    for chunk in input.chunks(10_000) {
+        bpm.input_samples(chunk);
+        bpm.input_samples(&[]);
+    }
+    assert_eq!(bpm.analysis_duration(), expected.analysis_duration());
+    assert_eq!(bpm.get_bpm(), expected.get_bpm());
+}